@@ -0,0 +1,34 @@
+// anonymous_typedef_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o anonymous_typedef_test.elf anonymous_typedef_test.c
+//
+// The structs, unions and enums in this file have no tag name of their own; they are only named by a typedef.
+
+#include <stdint.h>
+
+typedef struct {
+    uint16_t speed;
+    uint8_t gear;
+} Anon_Struct;
+
+typedef union {
+    uint32_t word;
+    uint8_t bytes[4];
+} Anon_Union;
+
+typedef enum {
+    MODE_OFF,
+    MODE_ON,
+} Anon_Enum;
+
+typedef struct {
+    Anon_Struct inner[2];
+    Anon_Union value;
+    Anon_Enum mode;
+} Anon_Outer;
+
+Anon_Struct Anon_Struct_Var;
+Anon_Outer Anon_Outer_Var;
+
+int main() {
+    return Anon_Struct_Var.gear + Anon_Outer_Var.mode;
+}
@@ -0,0 +1,17 @@
+#include <stdint.h>
+
+typedef struct {
+    uint16_t unsigned_1: 1;
+    uint16_t unsigned_3: 3;
+    int16_t signed_4: 4;
+    int16_t signed_1: 1;
+    uint32_t unsigned_12: 12;
+    int32_t signed_12: 12;
+    uint8_t plain_value;
+} Bitfield_Struct;
+
+Bitfield_Struct Bitfield_Values;
+
+int main() {
+    return Bitfield_Values.plain_value;
+}
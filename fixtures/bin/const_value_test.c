@@ -0,0 +1,18 @@
+// const_value_test.elf is built from this file:
+// gcc -gdwarf-5 -O2 -o const_value_test.elf const_value_test.c
+
+#include <stdint.h>
+
+// these calibration defaults are optimized away, and only their values remain in the debug info
+static const int16_t Default_Offset = -300;
+static const uint32_t Default_Timeout = 5000;
+static const float Default_Gain = 1.5f;
+static const double Default_Scale = 0.25;
+
+// this variable has storage, because it is not const
+volatile int32_t Output_Value;
+
+int main() {
+    Output_Value = (int32_t)(Default_Offset * Default_Gain * Default_Scale) + (int32_t)Default_Timeout;
+    return 0;
+}
@@ -0,0 +1,35 @@
+/* Source for fixtures/bin/dwarf5_test.elf
+   When gcc generates DWARF 5, attributes that have the same value in all DIEs sharing an
+   abbreviation are encoded with DW_FORM_implicit_const. The bitfields below all have a size of
+   one bit, so that DW_AT_bit_size is encoded this way. */
+#include <stdint.h>
+
+struct Flags {
+    uint8_t flag_a : 1;
+    uint8_t flag_b : 1;
+    uint8_t flag_c : 1;
+    uint8_t flag_d : 1;
+    uint8_t flag_e : 1;
+    uint8_t flag_f : 1;
+};
+
+struct StatusWord {
+    uint32_t ready : 1;
+    uint32_t error : 1;
+    uint32_t active : 1;
+    uint32_t done : 1;
+};
+
+struct Composite {
+    struct Flags flags;
+    struct StatusWord status;
+    uint32_t counter;
+};
+
+struct Flags Flags_Var;
+struct StatusWord Status_Var;
+struct Composite Composite_Var;
+
+int main(void) {
+    return Flags_Var.flag_a + Status_Var.ready + Composite_Var.counter;
+}
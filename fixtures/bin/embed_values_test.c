@@ -0,0 +1,12 @@
+#include <stdint.h>
+
+volatile uint16_t Calib_Scalar = 1234;
+volatile int8_t Calib_Negative = -5;
+volatile float Calib_Float = 2.5f;
+volatile uint8_t Calib_Array[4] = {1, 2, 3, 4};
+volatile uint32_t Calib_Large_Array[100] = {1};
+volatile uint32_t Calib_Uninitialized;
+
+int main() {
+    return Calib_Scalar + Calib_Negative + (int)Calib_Float + Calib_Array[0] + Calib_Large_Array[0] + Calib_Uninitialized;
+}
@@ -0,0 +1,35 @@
+// embedded_axis_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o embedded_axis_test.elf embedded_axis_test.c
+//
+// The curves and the map contain their axes in the same struct as the values.
+// The members of MisorderedCurve are not in the order of the matching record layout.
+
+#include <stdint.h>
+
+typedef struct {
+    uint8_t no_axis_pts_x;
+    uint16_t axis_pts_x[8];
+    int16_t values[8];
+} EmbeddedCurve;
+
+typedef struct {
+    uint8_t no_axis_pts_x;
+    uint8_t no_axis_pts_y;
+    uint16_t axis_pts_x[6];
+    uint16_t axis_pts_y[4];
+    int16_t values[4][6];
+} EmbeddedMap;
+
+typedef struct {
+    uint16_t axis_pts_x[8];
+    uint8_t no_axis_pts_x;
+    int16_t values[8];
+} MisorderedCurve;
+
+EmbeddedCurve Embedded_Curve;
+EmbeddedMap Embedded_Map;
+MisorderedCurve Misordered_Curve;
+
+int main() {
+    return Embedded_Curve.no_axis_pts_x + Embedded_Map.no_axis_pts_x + Misordered_Curve.no_axis_pts_x;
+}
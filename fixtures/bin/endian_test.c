@@ -0,0 +1,26 @@
+// endian_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o endian_test.elf endian_test.c
+
+#include <stdint.h>
+
+// gcc emits DW_AT_endianity for the base types of the members of this struct
+struct __attribute__((scalar_storage_order("big-endian"))) BigEndianRegs {
+    uint32_t control;
+    uint16_t status;
+    int16_t offset;
+    uint16_t table[4];
+};
+
+struct BigEndianRegs big_regs;
+
+// the members of this struct use the default byte order of the target
+struct NativeRegs {
+    uint32_t control;
+    uint16_t status;
+};
+
+struct NativeRegs native_regs;
+
+int main() {
+    return big_regs.status + big_regs.table[0] + native_regs.status;
+}
@@ -0,0 +1,17 @@
+// epk_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o epk_test.elf epk_test.c
+
+#include <stdint.h>
+
+// the version string that is also written into the EPK of the a2l file
+const char app_version[32] = "ECU_APP V1.2.3 2024-05-01";
+
+// initialized data that is not a string
+const uint8_t calibration_id[4] = {0x01, 0x02, 0xFF, 0x00};
+
+// not initialized, so there is no content in the elf file
+char runtime_version[16];
+
+int main() {
+    return app_version[0] + calibration_id[0] + runtime_version[0];
+}
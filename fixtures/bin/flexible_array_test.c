@@ -0,0 +1,19 @@
+// flexible_array_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o flexible_array_test.elf flexible_array_test.c
+//
+// The last member of Flexible_Array_Struct is a flexible array member, so its size is not known from the debug info.
+// gcc allows the static initialization of the flexible array as an extension.
+
+#include <stdint.h>
+
+typedef struct {
+    uint32_t count;
+    uint16_t status;
+    uint16_t data[];
+} Flexible_Array_Struct;
+
+Flexible_Array_Struct Flexible_Struct = {4, 0, {1, 2, 3, 4}};
+
+int main() {
+    return (int)Flexible_Struct.data[0];
+}
@@ -0,0 +1,189 @@
+# lower_bound_test.elf is built from this file:
+# gcc -nostdlib -static -o lower_bound_test.elf lower_bound_test.s
+#
+# The debug info is written by hand and describes the following Fortran code:
+#
+#   type fortran_data
+#     integer(kind=4) :: values(4)
+#     integer(kind=2) :: offsets(-2:2)
+#   end type
+#   type(fortran_data) :: Fortran_Data
+#   integer(kind=4) :: Fortran_Array(3)
+#
+# The subranges of values and Fortran_Array have no DW_AT_lower_bound, so the default lower bound
+# of Fortran (1) applies. The subrange of offsets has an explicit negative lower bound.
+
+    .text
+    .globl _start
+_start:
+    ret
+
+    .data
+    .balign 4
+    .globl Fortran_Data
+    .type Fortran_Data, @object
+    .size Fortran_Data, 28
+Fortran_Data:
+    .long 1, 2, 3, 4
+    .short -2, -1, 0, 1, 2
+    .short 0
+    .globl Fortran_Array
+    .type Fortran_Array, @object
+    .size Fortran_Array, 12
+Fortran_Array:
+    .long 10, 20, 30
+
+    .section .debug_abbrev,"",@progbits
+.Labbrev:
+    # 1: DW_TAG_compile_unit, has children
+    .uleb128 1
+    .uleb128 0x11
+    .byte 1
+    .uleb128 0x03   # DW_AT_name
+    .uleb128 0x08   # DW_FORM_string
+    .uleb128 0x13   # DW_AT_language
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0
+    .uleb128 0
+    # 2: DW_TAG_base_type
+    .uleb128 2
+    .uleb128 0x24
+    .byte 0
+    .uleb128 0x03   # DW_AT_name
+    .uleb128 0x08   # DW_FORM_string
+    .uleb128 0x0b   # DW_AT_byte_size
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0x3e   # DW_AT_encoding
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0
+    .uleb128 0
+    # 3: DW_TAG_array_type, has children
+    .uleb128 3
+    .uleb128 0x01
+    .byte 1
+    .uleb128 0x49   # DW_AT_type
+    .uleb128 0x13   # DW_FORM_ref4
+    .uleb128 0
+    .uleb128 0
+    # 4: DW_TAG_subrange_type with only an upper bound
+    .uleb128 4
+    .uleb128 0x21
+    .byte 0
+    .uleb128 0x2f   # DW_AT_upper_bound
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0
+    .uleb128 0
+    # 5: DW_TAG_subrange_type with lower and upper bound
+    .uleb128 5
+    .uleb128 0x21
+    .byte 0
+    .uleb128 0x22   # DW_AT_lower_bound
+    .uleb128 0x0d   # DW_FORM_sdata
+    .uleb128 0x2f   # DW_AT_upper_bound
+    .uleb128 0x0d   # DW_FORM_sdata
+    .uleb128 0
+    .uleb128 0
+    # 6: DW_TAG_structure_type, has children
+    .uleb128 6
+    .uleb128 0x13
+    .byte 1
+    .uleb128 0x03   # DW_AT_name
+    .uleb128 0x08   # DW_FORM_string
+    .uleb128 0x0b   # DW_AT_byte_size
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0
+    .uleb128 0
+    # 7: DW_TAG_member
+    .uleb128 7
+    .uleb128 0x0d
+    .byte 0
+    .uleb128 0x03   # DW_AT_name
+    .uleb128 0x08   # DW_FORM_string
+    .uleb128 0x49   # DW_AT_type
+    .uleb128 0x13   # DW_FORM_ref4
+    .uleb128 0x38   # DW_AT_data_member_location
+    .uleb128 0x0b   # DW_FORM_data1
+    .uleb128 0
+    .uleb128 0
+    # 8: DW_TAG_variable
+    .uleb128 8
+    .uleb128 0x34
+    .byte 0
+    .uleb128 0x03   # DW_AT_name
+    .uleb128 0x08   # DW_FORM_string
+    .uleb128 0x49   # DW_AT_type
+    .uleb128 0x13   # DW_FORM_ref4
+    .uleb128 0x3f   # DW_AT_external
+    .uleb128 0x19   # DW_FORM_flag_present
+    .uleb128 0x02   # DW_AT_location
+    .uleb128 0x18   # DW_FORM_exprloc
+    .uleb128 0
+    .uleb128 0
+    .uleb128 0
+
+    .section .debug_info,"",@progbits
+.Lcu_start:
+    .long .Lcu_end - .Lcu_version
+.Lcu_version:
+    .short 4
+    .long .Labbrev
+    .byte 8
+    .uleb128 1
+    .string "lower_bound_test.f90"
+    .byte 0x08      # DW_LANG_Fortran90
+.Lint4:
+    .uleb128 2
+    .string "integer(kind=4)"
+    .byte 4
+    .byte 0x05      # DW_ATE_signed
+.Lint2:
+    .uleb128 2
+    .string "integer(kind=2)"
+    .byte 2
+    .byte 0x05      # DW_ATE_signed
+.Lvalues_type:
+    .uleb128 3
+    .long .Lint4 - .Lcu_start
+    .uleb128 4
+    .byte 4
+    .uleb128 0
+.Loffsets_type:
+    .uleb128 3
+    .long .Lint2 - .Lcu_start
+    .uleb128 5
+    .sleb128 -2
+    .sleb128 2
+    .uleb128 0
+.Larray_type:
+    .uleb128 3
+    .long .Lint4 - .Lcu_start
+    .uleb128 4
+    .byte 3
+    .uleb128 0
+.Lstruct_type:
+    .uleb128 6
+    .string "fortran_data"
+    .byte 28
+    .uleb128 7
+    .string "values"
+    .long .Lvalues_type - .Lcu_start
+    .byte 0
+    .uleb128 7
+    .string "offsets"
+    .long .Loffsets_type - .Lcu_start
+    .byte 16
+    .uleb128 0
+    .uleb128 8
+    .string "Fortran_Data"
+    .long .Lstruct_type - .Lcu_start
+    .uleb128 9
+    .byte 0x03      # DW_OP_addr
+    .quad Fortran_Data
+    .uleb128 8
+    .string "Fortran_Array"
+    .long .Larray_type - .Lcu_start
+    .uleb128 9
+    .byte 0x03      # DW_OP_addr
+    .quad Fortran_Array
+    .uleb128 0
+.Lcu_end:
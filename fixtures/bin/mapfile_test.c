@@ -0,0 +1,21 @@
+// mapfile_test.c: test program for the linker map file fallback
+// The main file is compiled with debug info, the library file is compiled without it.
+#include <stdint.h>
+
+#ifdef MAPFILE_LIB
+// library without debug info: these variables can only be found in the linker map file
+uint32_t lib_counter;
+uint16_t lib_table[8] = {1, 2, 3, 4, 5, 6, 7, 8};
+#else
+extern uint32_t lib_counter;
+extern uint16_t lib_table[8];
+
+// variable with debug info
+uint32_t dbg_value = 42;
+
+int main(void)
+{
+    lib_counter += dbg_value + lib_table[0];
+    return 0;
+}
+#endif
@@ -0,0 +1,200 @@
+#!/usr/bin/env python3
+"""Build a synthetic PDB test fixture.
+
+usage: pdb_fixture.py <name> [<variant>]
+
+The type records and module symbols are described in <name>.yaml, which is converted by
+llvm-pdbutil yaml2pdb. The YAML format can't describe section headers, OMAP tables or global
+symbols, so these are read from <variant>.json and added to the generated file.
+The result is written to <variant>.pdb. If no variant is given, it is the same as the name.
+"""
+
+import json
+import os
+import struct
+import subprocess
+import sys
+import tempfile
+
+MSF_MAGIC = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\x00\x00\x00"
+BLOCK_SIZE = 4096
+NIL_STREAM = 0xFFFF
+NIL_STREAM_SIZE = 0xFFFFFFFF
+
+DBI_HEADER_SIZE = 64
+
+# indices in the optional debug header of the DBI stream
+DBG_OMAP_TO_SRC = 3
+DBG_OMAP_FROM_SRC = 4
+DBG_SECTION_HDR = 5
+DBG_SECTION_HDR_ORIG = 10
+DBG_HEADER_COUNT = 11
+
+S_GDATA32 = 0x110D
+IMAGE_SCN_CNT_INITIALIZED_DATA = 0x00000040
+IMAGE_SCN_MEM_READ = 0x40000000
+IMAGE_SCN_MEM_WRITE = 0x80000000
+
+
+def read_msf(data):
+    assert data[: len(MSF_MAGIC)] == MSF_MAGIC
+    block_size, _, _, dir_bytes, _, block_map_addr = struct.unpack_from("<6I", data, 32)
+
+    def blocks_of(size):
+        return (size + block_size - 1) // block_size
+
+    dir_block_count = blocks_of(dir_bytes)
+    dir_blocks = struct.unpack_from(f"<{dir_block_count}I", data, block_map_addr * block_size)
+    directory = b"".join(data[b * block_size : (b + 1) * block_size] for b in dir_blocks)
+
+    stream_count = struct.unpack_from("<I", directory, 0)[0]
+    sizes = struct.unpack_from(f"<{stream_count}I", directory, 4)
+    pos = 4 + 4 * stream_count
+    streams = []
+    for size in sizes:
+        if size == NIL_STREAM_SIZE:
+            streams.append(None)
+            continue
+        count = blocks_of(size)
+        blocks = struct.unpack_from(f"<{count}I", directory, pos)
+        pos += 4 * count
+        content = b"".join(data[b * block_size : (b + 1) * block_size] for b in blocks)
+        streams.append(content[:size])
+    return streams
+
+
+def write_msf(streams):
+    def blocks_of(size):
+        return (size + BLOCK_SIZE - 1) // BLOCK_SIZE
+
+    # block 0 is the super block, blocks 1 and 2 are the free page maps
+    next_block = 3
+    stream_blocks = []
+    for stream in streams:
+        count = blocks_of(len(stream)) if stream is not None else 0
+        stream_blocks.append(list(range(next_block, next_block + count)))
+        next_block += count
+
+    directory = struct.pack("<I", len(streams))
+    for stream in streams:
+        directory += struct.pack("<I", len(stream) if stream is not None else NIL_STREAM_SIZE)
+    for blocks in stream_blocks:
+        directory += struct.pack(f"<{len(blocks)}I", *blocks)
+    dir_blocks = list(range(next_block, next_block + blocks_of(len(directory))))
+    next_block += len(dir_blocks)
+    block_map_addr = next_block
+    num_blocks = next_block + 1
+
+    out = bytearray(num_blocks * BLOCK_SIZE)
+    struct.pack_into("<32s6I", out, 0, MSF_MAGIC, BLOCK_SIZE, 1, num_blocks, len(directory), 0, block_map_addr)
+    # all blocks after the end of the file are free
+    fpm = bytearray(b"\xff" * BLOCK_SIZE)
+    for block in range(num_blocks):
+        fpm[block // 8] &= ~(1 << (block % 8)) & 0xFF
+    out[BLOCK_SIZE : 2 * BLOCK_SIZE] = fpm
+    out[2 * BLOCK_SIZE : 3 * BLOCK_SIZE] = b"\xff" * BLOCK_SIZE
+    for stream, blocks in zip(streams, stream_blocks):
+        for idx, block in enumerate(blocks):
+            chunk = stream[idx * BLOCK_SIZE : (idx + 1) * BLOCK_SIZE]
+            out[block * BLOCK_SIZE : block * BLOCK_SIZE + len(chunk)] = chunk
+    for idx, block in enumerate(dir_blocks):
+        chunk = directory[idx * BLOCK_SIZE : (idx + 1) * BLOCK_SIZE]
+        out[block * BLOCK_SIZE : block * BLOCK_SIZE + len(chunk)] = chunk
+    struct.pack_into(f"<{len(dir_blocks)}I", out, block_map_addr * BLOCK_SIZE, *dir_blocks)
+    return bytes(out)
+
+
+def section_headers(sections):
+    data = b""
+    for section in sections:
+        characteristics = IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ
+        if section.get("writable", False):
+            characteristics |= IMAGE_SCN_MEM_WRITE
+        data += struct.pack(
+            "<8s6I2HI",
+            section["name"].encode(),
+            section["size"],
+            section["address"],
+            section["size"],
+            0,
+            0,
+            0,
+            0,
+            0,
+            characteristics,
+        )
+    return data
+
+
+def omap(records):
+    return b"".join(struct.pack("<2I", source, target) for source, target in records)
+
+
+def global_symbols(symbols):
+    data = b""
+    for symbol in symbols:
+        record = struct.pack("<HIIH", S_GDATA32, symbol["type"], symbol["offset"], symbol["segment"])
+        record += symbol["name"].encode() + b"\x00"
+        record += b"\x00" * (-(len(record) + 2) % 4)
+        data += struct.pack("<H", len(record)) + record
+    return data
+
+
+def add_stream(streams, content):
+    streams.append(content)
+    return len(streams) - 1
+
+
+def build(name, variant):
+    base_dir = os.path.dirname(os.path.abspath(__file__))
+    with open(os.path.join(base_dir, f"{variant}.json")) as json_file:
+        extra = json.load(json_file)
+
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        tmp_pdb = os.path.join(tmp_dir, f"{name}.pdb")
+        subprocess.run(
+            ["llvm-pdbutil", "yaml2pdb", f"--pdb={tmp_pdb}", os.path.join(base_dir, f"{name}.yaml")],
+            check=True,
+        )
+        with open(tmp_pdb, "rb") as pdb_file:
+            streams = read_msf(pdb_file.read())
+
+    dbi = streams[3]
+    header = bytearray(dbi[:DBI_HEADER_SIZE])
+    substream_sizes = list(struct.unpack_from("<5i", header, 24))
+    dbg_size, ec_size = struct.unpack_from("<2i", header, 48)
+    pos = DBI_HEADER_SIZE
+    substreams = []
+    for size in substream_sizes:
+        substreams.append(dbi[pos : pos + size])
+        pos += size
+    ec_substream = dbi[pos : pos + ec_size]
+    pos += ec_size
+    dbg_streams = list(struct.unpack_from(f"<{dbg_size // 2}H", dbi, pos))
+    dbg_streams += [NIL_STREAM] * (DBG_HEADER_COUNT - len(dbg_streams))
+
+    dbg_streams[DBG_SECTION_HDR] = add_stream(streams, section_headers(extra["sections"]))
+    if "original_sections" in extra:
+        dbg_streams[DBG_SECTION_HDR_ORIG] = add_stream(streams, section_headers(extra["original_sections"]))
+    if "omap_from_src" in extra:
+        dbg_streams[DBG_OMAP_FROM_SRC] = add_stream(streams, omap(extra["omap_from_src"]))
+        dbg_streams[DBG_OMAP_TO_SRC] = add_stream(streams, omap(extra["omap_to_src"]))
+    sym_record_stream = add_stream(streams, global_symbols(extra.get("globals", [])))
+    struct.pack_into("<H", header, 20, sym_record_stream)
+    struct.pack_into("<i", header, 48, 2 * DBG_HEADER_COUNT)
+
+    streams[3] = (
+        bytes(header)
+        + b"".join(substreams)
+        + ec_substream
+        + struct.pack(f"<{DBG_HEADER_COUNT}H", *dbg_streams)
+    )
+
+    with open(os.path.join(base_dir, f"{variant}.pdb"), "wb") as pdb_file:
+        pdb_file.write(write_msf(streams))
+
+
+if __name__ == "__main__":
+    if len(sys.argv) not in (2, 3):
+        sys.exit(__doc__)
+    build(sys.argv[1], sys.argv[-1])
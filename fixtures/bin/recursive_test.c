@@ -0,0 +1,46 @@
+// recursive_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o recursive_test.elf recursive_test.c
+
+#include <stdint.h>
+
+// a doubly linked list node refers to its own type
+struct ListNode {
+    struct ListNode *next;
+    struct ListNode *prev;
+    uint32_t value;
+};
+
+struct ListNode list_head;
+
+// a tree node refers to its own type through an array of pointers
+typedef struct TreeNode {
+    struct TreeNode *children[2];
+    const struct TreeNode *parent;
+    uint16_t data;
+} TreeNode_t;
+
+TreeNode_t tree_root;
+
+// two structs that refer to each other
+struct MutualB;
+
+struct MutualA {
+    struct MutualB *other;
+    int32_t a_value;
+};
+
+struct MutualB {
+    struct MutualA *other;
+    int32_t b_value;
+};
+
+struct MutualA mutual_a;
+struct MutualB mutual_b;
+
+int main() {
+    list_head.next = &list_head;
+    tree_root.parent = &tree_root;
+    mutual_a.other = &mutual_b;
+    mutual_b.other = &mutual_a;
+    return list_head.value + tree_root.data + mutual_a.a_value + mutual_b.b_value;
+}
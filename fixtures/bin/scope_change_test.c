@@ -0,0 +1,24 @@
+// scope_change_test_v1.elf and scope_change_test_v2.elf are built from this file:
+// gcc -gdwarf-5 -O0 -o scope_change_test_v1.elf scope_change_test.c
+// gcc -gdwarf-5 -O0 -DSCOPE_V2 -o scope_change_test_v2.elf scope_change_test.c
+//
+// In v2, the global variable Scope_Value has become a static variable inside the function get_scope_value.
+
+#include <stdint.h>
+
+uint32_t Global_Value = 1;
+
+#ifndef SCOPE_V2
+uint32_t Scope_Value = 2;
+#endif
+
+uint32_t get_scope_value() {
+#ifdef SCOPE_V2
+    static uint32_t Scope_Value = 2;
+#endif
+    return Scope_Value;
+}
+
+int main() {
+    return (int)(Global_Value + get_scope_value());
+}
@@ -0,0 +1,32 @@
+// shared_axes_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o shared_axes_test.elf shared_axes_test.c
+
+#include <stdint.h>
+
+// axis points, which are shared by several maps and curves
+uint16_t Speed_Axis[6] = {500, 1000, 2000, 3000, 4000, 6000};
+int16_t Load_Axis[4] = {0, 25, 50, 100};
+
+// the maps are declared as [y][x]: the x axis (Speed_Axis) varies fastest
+int16_t Ignition_Map[4][6];
+int16_t Fuel_Map[4][6];
+
+// a curve that uses the same x axis as the maps
+uint8_t Idle_Curve[6];
+
+// a curve whose size does not match the axis
+uint8_t Short_Curve[5];
+
+// calibration data in a struct: the maps use the axes that are stored in the same struct
+struct engine_calibration {
+    uint16_t speed_axis[6];
+    int16_t load_axis[4];
+    int16_t ignition_map[4][6];
+    int16_t fuel_map[4][6];
+    uint8_t enable;
+};
+struct engine_calibration Engine_Calibration;
+
+int main() {
+    return Speed_Axis[0] + Load_Axis[0] + Ignition_Map[0][0] + Fuel_Map[0][0] + Idle_Curve[0] + Short_Curve[0] + Engine_Calibration.enable;
+}
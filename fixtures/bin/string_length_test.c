@@ -0,0 +1,21 @@
+// string_length_test_v1.elf and string_length_test_v2.elf are built from this file:
+// gcc -gdwarf-5 -O0 -o string_length_test_v1.elf string_length_test.c
+// gcc -gdwarf-5 -O0 -DSTRING_V2 -o string_length_test_v2.elf string_length_test.c
+//
+// In v2, the string buffers and the calibration block are larger.
+
+#include <stdint.h>
+
+#ifndef STRING_V2
+char Vin_String[17];
+char Version_String[16];
+uint8_t Calibration_Block[8];
+#else
+char Vin_String[18];
+char Version_String[32];
+uint8_t Calibration_Block[12];
+#endif
+
+int main() {
+    return Vin_String[0] + Version_String[0] + Calibration_Block[0];
+}
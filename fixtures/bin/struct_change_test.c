@@ -0,0 +1,30 @@
+// struct_change_test_v1.elf and struct_change_test_v2.elf are built from this file:
+// gcc -gdwarf-5 -O0 -o struct_change_test_v1.elf struct_change_test.c
+// gcc -gdwarf-5 -O0 -DSTRUCT_V2 -o struct_change_test_v2.elf struct_change_test.c
+//
+// In v2, the struct ChangingStruct has gained the member "added" and lost the member "removed".
+
+#include <stdint.h>
+
+typedef struct {
+    uint16_t inner_a;
+    uint16_t inner_b;
+} InnerStruct;
+
+typedef struct {
+    uint32_t first;
+#ifndef STRUCT_V2
+    uint8_t removed;
+#endif
+    InnerStruct inner;
+#ifdef STRUCT_V2
+    uint64_t added;
+#endif
+    uint32_t last;
+} ChangingStruct;
+
+ChangingStruct changing_struct;
+
+int main() {
+    return (int)changing_struct.first;
+}
@@ -0,0 +1,28 @@
+// typedef_only_test.elf is built from this file and typedef_only_test_b.c:
+// gcc -gdwarf-5 -O0 -fno-eliminate-unused-debug-types -o typedef_only_test.elf typedef_only_test.c typedef_only_test_b.c
+//
+// No variable uses the types Component_Params, Component_State and Ambiguous_Type.
+// Ambiguous_Type has a different definition in typedef_only_test_b.c.
+
+#include <stdint.h>
+
+typedef struct {
+    uint16_t gain;
+    float offset;
+    int8_t table[4];
+} Component_Params;
+
+struct Component_State {
+    uint32_t counter;
+    uint8_t active;
+};
+
+typedef struct {
+    uint32_t value;
+} Ambiguous_Type;
+
+uint32_t Some_Value = 1;
+
+int main(void) {
+    return (int)Some_Value;
+}
@@ -0,0 +1,10 @@
+// see typedef_only_test.c
+
+#include <stdint.h>
+
+typedef struct {
+    float value;
+    float other_value;
+} Ambiguous_Type;
+
+uint32_t Other_Value = 2;
@@ -0,0 +1,31 @@
+// union_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o union_test.elf union_test.c
+
+#include <stdint.h>
+
+typedef union {
+    uint8_t as_u8;
+    uint16_t as_u16;
+    uint32_t as_u32;
+} ValueUnion;
+
+ValueUnion union_value;
+
+// an array of structs that contain nested unions
+typedef struct {
+    uint8_t kind;
+    union {
+        uint16_t word;
+        uint8_t bytes[2];
+        struct {
+            uint8_t lo;
+            uint8_t hi;
+        } parts;
+    } data;
+} TaggedValue;
+
+TaggedValue tagged_array[3];
+
+int main() {
+    return (int)union_value.as_u8 + tagged_array[0].data.parts.hi;
+}
@@ -0,0 +1,45 @@
+// untyped_symbol_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o untyped_symbol_test.elf untyped_symbol_test.c
+//
+// The variables Asm_Byte, Asm_Word, Asm_Long and Asm_Buffer are defined in assembly code, so the
+// debug info does not describe them. They are only present in the ELF symbol table.
+// Asm_Local is also defined in assembly code, but it is a local symbol.
+
+#include <stdint.h>
+
+__asm__(
+    "    .data\n"
+    "    .globl Asm_Byte\n"
+    "    .type Asm_Byte, @object\n"
+    "    .size Asm_Byte, 1\n"
+    "Asm_Byte:\n"
+    "    .byte 0x12\n"
+    "    .balign 2\n"
+    "    .globl Asm_Word\n"
+    "    .type Asm_Word, @object\n"
+    "    .size Asm_Word, 2\n"
+    "Asm_Word:\n"
+    "    .short 0x1234\n"
+    "    .balign 4\n"
+    "    .globl Asm_Long\n"
+    "    .type Asm_Long, @object\n"
+    "    .size Asm_Long, 4\n"
+    "Asm_Long:\n"
+    "    .long 0x12345678\n"
+    "    .globl Asm_Buffer\n"
+    "    .type Asm_Buffer, @object\n"
+    "    .size Asm_Buffer, 24\n"
+    "Asm_Buffer:\n"
+    "    .zero 24\n"
+    "    .local Asm_Local\n"
+    "    .type Asm_Local, @object\n"
+    "    .size Asm_Local, 6\n"
+    "Asm_Local:\n"
+    "    .byte 1, 2, 3, 4, 5, 6\n"
+    "    .text\n");
+
+uint32_t C_Value = 7;
+
+int main(void) {
+    return (int)C_Value;
+}
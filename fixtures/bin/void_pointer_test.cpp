@@ -0,0 +1,24 @@
+// void_pointer_test.elf is built from this file:
+// g++ -gdwarf-5 -O0 -o void_pointer_test.elf void_pointer_test.cpp
+
+#include <cstdint>
+#include <cstddef>
+
+uint32_t Target_Value = 42;
+
+extern "C" {
+// pointers to void have no type reference in the debug info
+void *Void_Pointer = &Target_Value;
+const void *Const_Void_Pointer = &Target_Value;
+
+// std::nullptr_t is described by DW_TAG_unspecified_type
+std::nullptr_t *Nullptr_Pointer = nullptr;
+
+struct Opaque_Struct;
+// the target of this pointer is only declared
+Opaque_Struct *Opaque_Pointer = nullptr;
+}
+
+int main() {
+    return Target_Value + (Void_Pointer != nullptr) + (Const_Void_Pointer != nullptr) + (Nullptr_Pointer != nullptr) + (Opaque_Pointer != nullptr);
+}
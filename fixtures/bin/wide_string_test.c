@@ -0,0 +1,16 @@
+// wide_string_test.elf is built from this file:
+// gcc -gdwarf-5 -O0 -o wide_string_test.elf wide_string_test.c
+//
+// wchar_t is a 32-bit type with gcc on linux, while char16_t is always a 16-bit type.
+
+#include <stdint.h>
+#include <uchar.h>
+#include <wchar.h>
+
+wchar_t Wide_String[16] = L"wide string";
+char16_t Utf16_String[12] = u"utf16";
+uint16_t Word_Array[12] = {1, 2, 3};
+
+int main() {
+    return (int)Wide_String[0] + (int)Utf16_String[0] + (int)Word_Array[0];
+}
@@ -0,0 +1,271 @@
+use crate::ifdata;
+use a2lfile::{A2lFile, A2lObject, IfData};
+use std::ffi::OsStr;
+
+// Load an A2ML specification from a file, which is used instead of the built-in specification to parse IF_DATA blocks.
+// The file may either contain only the A2ML text, or the complete /begin A2ML ... /end A2ML block.
+pub(crate) fn load_a2ml_file(filename: &OsStr) -> Result<String, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Error: could not read the A2ML file \"{}\": {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    let trimmed = text.trim();
+    let spec = trimmed
+        .strip_prefix("/begin A2ML")
+        .and_then(|inner| inner.strip_suffix("/end A2ML"))
+        .unwrap_or(trimmed);
+    Ok(spec.to_string())
+}
+
+// Report all IF_DATA blocks that could not be parsed using the A2ML specification.
+// These blocks would be removed by --ifdata-cleanup.
+// Returns the number of invalid IF_DATA blocks.
+pub(crate) fn check_ifdata(a2l_file: &A2lFile, log_msgs: &mut Vec<String>) -> usize {
+    let mut invalid_count = 0;
+
+    for module in &a2l_file.project.module {
+        let mut check = |blocktype: &str, name: &str, if_data: &[IfData]| {
+            for ifdata in if_data.iter().filter(|ifdata| !ifdata.ifdata_valid) {
+                log_msgs.push(format!(
+                    "The IF_DATA block on line {} in {blocktype} {name} does not match the A2ML specification",
+                    ifdata.get_line()
+                ));
+                invalid_count += 1;
+            }
+        };
+
+        check("MODULE", &module.name, &module.if_data);
+        if let Some(mod_par) = &module.mod_par {
+            for memory_layout in &mod_par.memory_layout {
+                check(
+                    "MEMORY_LAYOUT",
+                    &format!("0x{:X}", memory_layout.address),
+                    &memory_layout.if_data,
+                );
+            }
+            for memory_segment in &mod_par.memory_segment {
+                check(
+                    "MEMORY_SEGMENT",
+                    &memory_segment.name,
+                    &memory_segment.if_data,
+                );
+            }
+        }
+        for axis_pts in &module.axis_pts {
+            check("AXIS_PTS", &axis_pts.name, &axis_pts.if_data);
+        }
+        for blob in &module.blob {
+            check("BLOB", &blob.name, &blob.if_data);
+        }
+        for characteristic in &module.characteristic {
+            check(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                &characteristic.if_data,
+            );
+        }
+        for frame in &module.frame {
+            check("FRAME", &frame.name, &frame.if_data);
+        }
+        for function in &module.function {
+            check("FUNCTION", &function.name, &function.if_data);
+        }
+        for group in &module.group {
+            check("GROUP", &group.name, &group.if_data);
+        }
+        for instance in &module.instance {
+            check("INSTANCE", &instance.name, &instance.if_data);
+        }
+        for measurement in &module.measurement {
+            check("MEASUREMENT", &measurement.name, &measurement.if_data);
+        }
+    }
+
+    invalid_count
+}
+
+// Parse the IF_DATA blocks that don't match the A2ML specification of the file again, using the built-in specification.
+// Files from newer tools may contain IF_DATA blocks like XCPplus that are not part of an older A2ML block in the file,
+// so these blocks could neither be displayed nor updated, and --ifdata-cleanup would delete them.
+// Returns the number of IF_DATA blocks that are now valid.
+pub(crate) fn revalidate_ifdata(a2l_file: &mut A2lFile) -> usize {
+    let mut valid_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let mut invalid: Vec<&mut IfData> = module.if_data.iter_mut().collect();
+        if let Some(mod_par) = &mut module.mod_par {
+            for memory_layout in &mut mod_par.memory_layout {
+                invalid.extend(memory_layout.if_data.iter_mut());
+            }
+            for memory_segment in &mut mod_par.memory_segment {
+                invalid.extend(memory_segment.if_data.iter_mut());
+            }
+        }
+        for axis_pts in &mut module.axis_pts {
+            invalid.extend(axis_pts.if_data.iter_mut());
+        }
+        for blob in &mut module.blob {
+            invalid.extend(blob.if_data.iter_mut());
+        }
+        for characteristic in &mut module.characteristic {
+            invalid.extend(characteristic.if_data.iter_mut());
+        }
+        for frame in &mut module.frame {
+            invalid.extend(frame.if_data.iter_mut());
+        }
+        for function in &mut module.function {
+            invalid.extend(function.if_data.iter_mut());
+        }
+        for group in &mut module.group {
+            invalid.extend(group.if_data.iter_mut());
+        }
+        for instance in &mut module.instance {
+            invalid.extend(instance.if_data.iter_mut());
+        }
+        for measurement in &mut module.measurement {
+            invalid.extend(measurement.if_data.iter_mut());
+        }
+        invalid.retain(|ifdata| !ifdata.ifdata_valid);
+        if invalid.is_empty() {
+            continue;
+        }
+
+        // all invalid blocks of the module are parsed together as module-level IF_DATA in a temporary file
+        let mut tmp_file = a2lfile::new();
+        tmp_file.project.module[0].if_data = invalid
+            .iter()
+            .map(|ifdata| {
+                let mut ifdata_copy = (**ifdata).clone();
+                // without the layout info the blocks are written in the order of the list
+                ifdata_copy.get_layout_mut().uid = 0;
+                ifdata_copy.get_layout_mut().incfile = None;
+                ifdata_copy
+            })
+            .collect();
+        let text = tmp_file.write_to_string();
+        let Ok(reparsed_file) = a2lfile::load_from_string(
+            &text,
+            Some(ifdata::A2MLVECTOR_TEXT.to_string()),
+            &mut Vec::new(),
+            false,
+        ) else {
+            continue;
+        };
+        let Some(reparsed_module) = reparsed_file.project.module.into_iter().next() else {
+            continue;
+        };
+        if reparsed_module.if_data.len() != invalid.len() {
+            continue;
+        }
+
+        for (ifdata, mut reparsed_ifdata) in invalid.into_iter().zip(reparsed_module.if_data) {
+            if reparsed_ifdata.ifdata_valid {
+                // keep the position of the block in the original file
+                *reparsed_ifdata.get_layout_mut() = ifdata.get_layout().clone();
+                *ifdata = reparsed_ifdata;
+                valid_count += 1;
+            }
+        }
+    }
+
+    valid_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2ML_TEXT: &str = r#"block "IF_DATA" taggedunion if_data {
+  "CUSTOM" struct {
+    uint;
+    char[32];
+  };
+};"#;
+
+    #[test]
+    fn test_check_ifdata() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin IF_DATA CUSTOM 1 "module"
+    /end IF_DATA
+    /begin MEASUREMENT Valid "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      /begin IF_DATA CUSTOM 2 "valid"
+      /end IF_DATA
+    /end MEASUREMENT
+    /begin MEASUREMENT Invalid "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      /begin IF_DATA CUSTOM "missing number"
+      /end IF_DATA
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Unknown "" VALUE 0x1000 RL 0 NO_COMPU_METHOD 0 255
+      /begin IF_DATA OTHER 3
+      /end IF_DATA
+    /end CHARACTERISTIC
+  /end MODULE
+/end PROJECT"#;
+        let a2l_file = a2lfile::load_from_string(
+            A2L_TEXT,
+            Some(A2ML_TEXT.to_string()),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let mut log_msgs = Vec::new();
+        let invalid_count = check_ifdata(&a2l_file, &mut log_msgs);
+        assert_eq!(invalid_count, 2);
+        assert!(log_msgs[0].contains("line 15 in CHARACTERISTIC Unknown"));
+        assert!(log_msgs[1].contains("line 11 in MEASUREMENT Invalid"));
+    }
+
+    #[test]
+    fn test_load_a2ml_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let plain_file = tempdir.path().join("plain.a2ml");
+        std::fs::write(&plain_file, A2ML_TEXT).unwrap();
+        let block_file = tempdir.path().join("block.a2ml");
+        std::fs::write(
+            &block_file,
+            format!("/begin A2ML\n{A2ML_TEXT}\n/end A2ML\n"),
+        )
+        .unwrap();
+
+        let plain = load_a2ml_file(plain_file.as_os_str()).unwrap();
+        let block = load_a2ml_file(block_file.as_os_str()).unwrap();
+        assert_eq!(plain, A2ML_TEXT);
+        assert_eq!(block.trim(), A2ML_TEXT);
+        assert!(load_a2ml_file(tempdir.path().join("missing.a2ml").as_os_str()).is_err());
+    }
+
+    #[test]
+    fn test_revalidate_ifdata() {
+        // the A2ML block in the file only knows XCP, but not XCPplus
+        let mut a2l_file = a2lfile::load(
+            "fixtures/a2l/xcpplus_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        assert!(!a2l_file.project.module[0].if_data[0].ifdata_valid);
+        let mut log_msgs = Vec::new();
+        assert_eq!(check_ifdata(&a2l_file, &mut log_msgs), 1);
+
+        let valid_count = revalidate_ifdata(&mut a2l_file);
+        assert_eq!(valid_count, 1);
+        let ifdata = &a2l_file.project.module[0].if_data[0];
+        assert!(ifdata.ifdata_valid);
+        assert_eq!(ifdata.get_line(), 17);
+        let decoded = ifdata::A2mlVector::load_from_ifdata(ifdata).unwrap();
+        let xcpplus = decoded.xcpplus.unwrap();
+        assert_eq!(xcpplus.xcp_on_can.len(), 1);
+        assert_eq!(xcpplus.xcp_on_udp_ip.len(), 1);
+        assert_eq!(xcpplus.daq.unwrap().event.len(), 2);
+
+        // blocks that are valid now are kept by the ifdata cleanup
+        a2l_file.ifdata_cleanup();
+        assert_eq!(a2l_file.project.module[0].if_data.len(), 1);
+        assert_eq!(revalidate_ifdata(&mut a2l_file), 0);
+    }
+}
@@ -0,0 +1,480 @@
+use a2lfile::{
+    A2lFile, A2lObject, BitMask, CompuMethod, CompuTabRef, CompuVtab, ConversionType, EcuAddress,
+    Measurement, Module, SymbolLink, ValuePairsStruct,
+};
+use std::ffi::OsStr;
+
+use crate::datatype::get_a2l_datatype;
+use crate::debuginfo::{DbgDataType, DebugData};
+use crate::symbol::find_symbol;
+use crate::update::make_symbol_link_string;
+use crate::A2lVersion;
+
+// A named field inside of an integer variable, as described by one line of the definition file
+#[derive(Debug, PartialEq)]
+pub(crate) struct BitfieldDef {
+    pub(crate) symbol: String,
+    pub(crate) field: String,
+    pub(crate) bit_offset: u32,
+    pub(crate) bit_width: u32,
+    pub(crate) values: Vec<(String, i64)>,
+}
+
+// Load the definitions of bit fields from a file.
+//
+// Each non-empty line that does not start with '#' defines one field of a variable:
+//   <symbol>; <field name>; <bit>[-<last bit>] [; <value table>]
+// The value table is optional. It contains comma separated pairs of value and name, e.g.
+//   StatusWord; Ready; 0
+//   StatusWord; Mode; 1-2; 0=Off, 1=Standby, 2=Run
+pub(crate) fn load_definitions(filename: &OsStr) -> Result<Vec<BitfieldDef>, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the bit field definitions from {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    parse_definitions(&text).map_err(|err| format!("{}: {err}", filename.to_string_lossy()))
+}
+
+fn parse_definitions(text: &str) -> Result<Vec<BitfieldDef>, String> {
+    let mut definitions: Vec<BitfieldDef> = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        if fields.len() != 3 && fields.len() != 4 {
+            return Err(format!(
+                "line {line_num}: expected 3 or 4 fields separated by ';', found {}",
+                fields.len()
+            ));
+        }
+
+        let symbol = fields[0];
+        if symbol.is_empty() {
+            return Err(format!("line {line_num}: the symbol name is empty"));
+        }
+        let field = fields[1];
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!(
+                "line {line_num}: \"{field}\" is not a valid field name"
+            ));
+        }
+        if definitions
+            .iter()
+            .any(|def| def.symbol == symbol && def.field == field)
+        {
+            return Err(format!(
+                "line {line_num}: the field {field} of {symbol} is defined twice"
+            ));
+        }
+        let Some((bit_offset, bit_width)) = parse_bit_range(fields[2]) else {
+            return Err(format!(
+                "line {line_num}: \"{}\" is not a valid bit position or range",
+                fields[2]
+            ));
+        };
+        let values = match fields.get(3) {
+            Some(value_text) if !value_text.is_empty() => parse_value_table(value_text, bit_width)
+                .map_err(|err| format!("line {line_num}: {err}"))?,
+            _ => Vec::new(),
+        };
+
+        definitions.push(BitfieldDef {
+            symbol: symbol.to_string(),
+            field: field.to_string(),
+            bit_offset,
+            bit_width,
+            values,
+        });
+    }
+
+    Ok(definitions)
+}
+
+// parse a single bit "5" or an inclusive range of bits "4-7"
+// returns the offset of the lowest bit and the number of bits
+fn parse_bit_range(text: &str) -> Option<(u32, u32)> {
+    let (first, last) = match text.split_once('-') {
+        Some((first, last)) => (
+            first.trim().parse::<u32>().ok()?,
+            last.trim().parse::<u32>().ok()?,
+        ),
+        None => {
+            let bit = text.parse::<u32>().ok()?;
+            (bit, bit)
+        }
+    };
+    if first > last || last >= 64 {
+        return None;
+    }
+    Some((first, last - first + 1))
+}
+
+// parse a value table "0=Off, 1=On"; all values must fit into the field
+fn parse_value_table(text: &str, bit_width: u32) -> Result<Vec<(String, i64)>, String> {
+    let max_value = field_max_value(bit_width);
+    let mut values: Vec<(String, i64)> = Vec::new();
+    for entry in text.split(',') {
+        let Some((value_text, name)) = entry.split_once('=') else {
+            return Err(format!(
+                "\"{}\" is not a valid value table entry",
+                entry.trim()
+            ));
+        };
+        let name = name.trim();
+        let value = value_text
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|value| *value >= 0 && *value as u64 <= max_value)
+            .ok_or_else(|| {
+                format!(
+                    "the value \"{}\" of {name} does not fit into a field of {bit_width} bits",
+                    value_text.trim()
+                )
+            })?;
+        if name.is_empty() {
+            return Err(format!("the value {value} has no name"));
+        }
+        if values.iter().any(|(_, existing)| *existing == value) {
+            return Err(format!("the value {value} is defined twice"));
+        }
+        values.push((name.to_string(), value));
+    }
+    values.sort_by_key(|(_, value)| *value);
+
+    Ok(values)
+}
+
+// the largest value that can be stored in a field
+fn field_max_value(bit_width: u32) -> u64 {
+    if bit_width >= 64 {
+        u64::MAX
+    } else {
+        (1 << bit_width) - 1
+    }
+}
+
+// the BIT_MASK which selects the field in the containing variable
+fn field_bit_mask(bit_offset: u32, bit_width: u32) -> u64 {
+    field_max_value(bit_width) << bit_offset
+}
+
+// the name of the MEASUREMENT for a field, e.g. "StatusWord.Ready"
+fn field_measurement_name(symbol: &str, field: &str) -> String {
+    format!("{symbol}.{field}")
+}
+
+// Create a MEASUREMENT with a BIT_MASK for each field in the definitions.
+// The MEASUREMENTs of all fields of a variable share its address and SYMBOL_LINK.
+// MEASUREMENTs that already exist are updated instead, so that the definitions can be applied repeatedly.
+// Returns the number of MEASUREMENTs that were created or updated.
+pub(crate) fn create_bitfield_measurements(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    definitions: &[BitfieldDef],
+    log_msgs: &mut Vec<String>,
+) -> Result<usize, String> {
+    let version = A2lVersion::from(&*a2l_file);
+    let module = &mut a2l_file.project.module[0];
+
+    // check all definitions before modifying anything
+    let mut errors = Vec::new();
+    let mut symbols = Vec::new();
+    for def in definitions {
+        let sym_info = match find_symbol(&def.symbol, debug_data) {
+            Ok(sym_info) => sym_info,
+            Err(errmsg) => {
+                errors.push(format!(
+                    "the symbol {} could not be found: {errmsg}",
+                    def.symbol
+                ));
+                continue;
+            }
+        };
+        let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+        if !matches!(
+            typeinfo.datatype,
+            DbgDataType::Uint8
+                | DbgDataType::Uint16
+                | DbgDataType::Uint32
+                | DbgDataType::Uint64
+                | DbgDataType::Sint8
+                | DbgDataType::Sint16
+                | DbgDataType::Sint32
+                | DbgDataType::Sint64
+        ) {
+            errors.push(format!(
+                "the symbol {} is not an integer variable",
+                def.symbol
+            ));
+            continue;
+        }
+        // BIT_MASK is a 32 bit value, so fields in the upper half of a 64 bit variable can not be described
+        let word_bits = (typeinfo.get_size() * 8).min(32) as u32;
+        if def.bit_offset + def.bit_width > word_bits {
+            errors.push(format!(
+                "the field {} does not fit into the {word_bits} bits of {}",
+                def.field, def.symbol
+            ));
+            continue;
+        }
+        let symbol_link = make_symbol_link_string(&sym_info, debug_data);
+        symbols.push((
+            def,
+            sym_info.address,
+            get_a2l_datatype(typeinfo),
+            symbol_link,
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "Could not create the bit field measurements:\n  {}",
+            errors.join("\n  ")
+        ));
+    }
+
+    for (def, address, datatype, symbol_link) in symbols {
+        let name = field_measurement_name(&def.symbol, &def.field);
+        let conversion = if def.values.is_empty() {
+            "NO_COMPU_METHOD".to_string()
+        } else {
+            create_value_table_conversion(module, &name, &def.values)
+        };
+        let upper_limit = field_max_value(def.bit_width) as f64;
+        // the bit offset is at most 31, so the mask always fits
+        let mask = field_bit_mask(def.bit_offset, def.bit_width) as u32;
+
+        let measurement = if let Some(measurement) =
+            module.measurement.iter_mut().find(|meas| meas.name == name)
+        {
+            log_msgs.push(format!(
+                "Updated bit field MEASUREMENT {name} on line {}",
+                measurement.get_line()
+            ));
+            measurement.datatype = datatype;
+            measurement.conversion = conversion;
+            measurement.lower_limit = 0.0;
+            measurement.upper_limit = upper_limit;
+            measurement
+        } else {
+            let measurement = Measurement::new(
+                name.clone(),
+                format!(
+                    "bits {}-{} of {}",
+                    def.bit_offset,
+                    def.bit_offset + def.bit_width - 1,
+                    def.symbol
+                ),
+                datatype,
+                conversion,
+                0,
+                0f64,
+                0.0,
+                upper_limit,
+            );
+            module.measurement.push(measurement);
+            log_msgs.push(format!("Created bit field MEASUREMENT {name}"));
+            module.measurement.last_mut().unwrap()
+        };
+
+        // create an ECU_ADDRESS attribute, and set it to hex display mode
+        let ecu_address = measurement
+            .ecu_address
+            .get_or_insert_with(|| EcuAddress::new(0));
+        ecu_address.get_layout_mut().item_location.0 .1 = true;
+        ecu_address.address = address as u32;
+        if version >= A2lVersion::V1_6_0 {
+            measurement.symbol_link = Some(SymbolLink::new(symbol_link, 0));
+        }
+        let bit_mask = measurement.bit_mask.get_or_insert_with(|| BitMask::new(0));
+        bit_mask.get_layout_mut().item_location.0 = (0, true);
+        bit_mask.mask = mask;
+    }
+
+    Ok(definitions.len())
+}
+
+// create or update the COMPU_METHOD and COMPU_VTAB which name the values of a field
+fn create_value_table_conversion(
+    module: &mut Module,
+    name: &str,
+    values: &[(String, i64)],
+) -> String {
+    let compu_method_name = format!("{name}_compu_method");
+    let value_pairs: Vec<ValuePairsStruct> = values
+        .iter()
+        .map(|(value_name, value)| ValuePairsStruct::new(*value as f64, value_name.clone()))
+        .collect();
+
+    if !module
+        .compu_method
+        .iter()
+        .any(|cm| cm.name == compu_method_name)
+    {
+        let mut compu_method = CompuMethod::new(
+            compu_method_name.clone(),
+            format!("Value table for {name}"),
+            ConversionType::TabVerb,
+            "%.4".to_string(),
+            String::new(),
+        );
+        compu_method.compu_tab_ref = Some(CompuTabRef::new(compu_method_name.clone()));
+        module.compu_method.push(compu_method);
+    }
+
+    if let Some(compu_vtab) = module
+        .compu_vtab
+        .iter_mut()
+        .find(|vtab| vtab.name == compu_method_name)
+    {
+        compu_vtab.number_value_pairs = value_pairs.len() as u16;
+        compu_vtab.value_pairs = value_pairs;
+    } else {
+        let mut compu_vtab = CompuVtab::new(
+            compu_method_name.clone(),
+            format!("Value table for {name}"),
+            ConversionType::TabVerb,
+            value_pairs.len() as u16,
+        );
+        compu_vtab.value_pairs = value_pairs;
+        module.compu_vtab.push(compu_vtab);
+    }
+
+    compu_method_name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    static DEFINITIONS: &str = r#"
+# symbol; field name; bits; value table
+Measurement_Value; Ready; 0
+Measurement_Value; Mode; 1-2; 2=Run, 0=Off, 1=Standby
+"#;
+
+    #[test]
+    fn test_bit_math() {
+        assert_eq!(parse_bit_range("0"), Some((0, 1)));
+        assert_eq!(parse_bit_range("4-7"), Some((4, 4)));
+        assert_eq!(parse_bit_range("3-3"), Some((3, 1)));
+        assert_eq!(parse_bit_range("7-4"), None);
+        assert_eq!(parse_bit_range("64"), None);
+        assert_eq!(parse_bit_range("x"), None);
+
+        assert_eq!(field_bit_mask(0, 1), 0x1);
+        assert_eq!(field_bit_mask(4, 4), 0xF0);
+        assert_eq!(field_bit_mask(0, 32), 0xFFFF_FFFF);
+        assert_eq!(field_max_value(3), 7);
+        assert_eq!(field_max_value(64), u64::MAX);
+        assert_eq!(field_measurement_name("Status", "Ready"), "Status.Ready");
+    }
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions[0].symbol, "Measurement_Value");
+        assert_eq!(definitions[0].field, "Ready");
+        assert_eq!(definitions[0].bit_offset, 0);
+        assert_eq!(definitions[0].bit_width, 1);
+        assert!(definitions[0].values.is_empty());
+        // the value table is sorted
+        assert_eq!(
+            definitions[1].values,
+            vec![
+                ("Off".to_string(), 0),
+                ("Standby".to_string(), 1),
+                ("Run".to_string(), 2)
+            ]
+        );
+
+        assert!(parse_definitions("Status; Ready").is_err());
+        assert!(parse_definitions("Status; Ready; 9-8").is_err());
+        assert!(parse_definitions("Status; Bad Name; 0").is_err());
+        assert!(parse_definitions("Status; Ready; 0\nStatus; Ready; 1").is_err());
+        // the value 4 does not fit into 2 bits
+        assert!(parse_definitions("Status; Mode; 1-2; 4=Error").is_err());
+        assert!(parse_definitions("Status; Mode; 1-2; 0=Off, 0=On").is_err());
+    }
+
+    #[test]
+    fn test_create_bitfield_measurements() {
+        let mut a2l_file = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let address = debug_data.variables["Measurement_Value"][0].address as u32;
+        let measurement_count = a2l_file.project.module[0].measurement.len();
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+
+        let mut log_msgs = Vec::new();
+        let count =
+            create_bitfield_measurements(&mut a2l_file, &debug_data, &definitions, &mut log_msgs)
+                .unwrap();
+        assert_eq!(count, 2);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement.len(), measurement_count + 2);
+        let mode = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Measurement_Value.Mode")
+            .unwrap();
+        assert_eq!(mode.ecu_address.as_ref().unwrap().address, address);
+        assert_eq!(mode.bit_mask.as_ref().unwrap().mask, 0x6);
+        assert_eq!(mode.upper_limit, 3.0);
+        assert_eq!(mode.conversion, "Measurement_Value.Mode_compu_method");
+        let compu_vtab = module
+            .compu_vtab
+            .iter()
+            .find(|vtab| vtab.name == mode.conversion)
+            .unwrap();
+        assert_eq!(compu_vtab.value_pairs.len(), 3);
+
+        // running the definitions again updates the existing measurements
+        let definitions = parse_definitions(
+            "Measurement_Value; Ready; 0\nMeasurement_Value; Mode; 1-3; 0=Off, 4=Error",
+        )
+        .unwrap();
+        let compu_method_count = module.compu_method.len();
+        create_bitfield_measurements(&mut a2l_file, &debug_data, &definitions, &mut log_msgs)
+            .unwrap();
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement.len(), measurement_count + 2);
+        assert_eq!(module.compu_method.len(), compu_method_count);
+        let mode = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Measurement_Value.Mode")
+            .unwrap();
+        assert_eq!(mode.bit_mask.as_ref().unwrap().mask, 0xE);
+        assert_eq!(mode.upper_limit, 7.0);
+        let compu_vtab = module
+            .compu_vtab
+            .iter()
+            .find(|vtab| vtab.name == mode.conversion)
+            .unwrap();
+        assert_eq!(compu_vtab.value_pairs.len(), 2);
+        assert_eq!(compu_vtab.number_value_pairs, 2);
+
+        // unknown symbols and fields that exceed the variable are rejected
+        let definitions =
+            parse_definitions("Does_Not_Exist; Ready; 0\nMeasurement_Value; High; 40-41").unwrap();
+        let errmsg =
+            create_bitfield_measurements(&mut a2l_file, &debug_data, &definitions, &mut log_msgs)
+                .unwrap_err();
+        assert!(errmsg.contains("Does_Not_Exist"));
+        assert!(errmsg.contains("does not fit"));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::datatype::get_datatype_size;
+use a2lfile::{A2lFile, A2lObject, DataType};
+
+// Check that the BIT_MASK of each CHARACTERISTIC fits into the data type of its RECORD_LAYOUT.
+// A BIT_MASK like 0xFF00 on a UBYTE value is impossible, and different tools interpret it differently.
+// Returns the number of CHARACTERISTICs with an invalid BIT_MASK.
+pub(crate) fn check_bitmasks(a2l_file: &A2lFile, log_msgs: &mut Vec<String>) -> usize {
+    let mut problem_count = 0;
+
+    for module in &a2l_file.project.module {
+        for characteristic in &module.characteristic {
+            let Some(bit_mask) = &characteristic.bit_mask else {
+                continue;
+            };
+            let Some(datatype) = module
+                .record_layout
+                .iter()
+                .find(|rl| rl.name == characteristic.deposit)
+                .and_then(|rl| rl.fnc_values.as_ref())
+                .map(|fnc_values| &fnc_values.datatype)
+            else {
+                continue;
+            };
+            if !bitmask_fits(bit_mask.mask, datatype) {
+                log_msgs.push(format!(
+                    "CHARACTERISTIC {} on line {}: the BIT_MASK 0x{:X} does not fit into the data type {datatype} of the RECORD_LAYOUT {}",
+                    characteristic.name,
+                    characteristic.get_line(),
+                    bit_mask.mask,
+                    characteristic.deposit
+                ));
+                problem_count += 1;
+            }
+        }
+    }
+
+    problem_count
+}
+
+// a BIT_MASK must select at least one bit, and all selected bits must be inside the data type
+pub(crate) fn bitmask_fits(mask: u32, datatype: &DataType) -> bool {
+    let bit_count = get_datatype_size(datatype) * 8;
+    mask != 0 && (bit_count >= u32::BITS || mask >> bit_count == 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_bitmasks() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Valid "" VALUE 0x1000 RL_UBYTE 0 NO_COMPU_METHOD 0 255
+      BIT_MASK 0xF0
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC TooWide "" VALUE 0x1001 RL_UBYTE 0 NO_COMPU_METHOD 0 255
+      BIT_MASK 0xFF00
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Word "" VALUE 0x1002 RL_UWORD 0 NO_COMPU_METHOD 0 255
+      BIT_MASK 0xFF00
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT RL_UBYTE
+      FNC_VALUES 1 UBYTE COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT RL_UWORD
+      FNC_VALUES 1 UWORD COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+        let a2l_file = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let problem_count = check_bitmasks(&a2l_file, &mut log_msgs);
+        assert_eq!(problem_count, 1);
+        assert!(log_msgs[0].starts_with("CHARACTERISTIC TooWide on line 7"));
+
+        assert!(bitmask_fits(0x80, &DataType::Sbyte));
+        assert!(!bitmask_fits(0x100, &DataType::Sbyte));
+        assert!(!bitmask_fits(0, &DataType::Ulong));
+        assert!(bitmask_fits(0xFFFF_FFFF, &DataType::Ulong));
+        assert!(bitmask_fits(0xFFFF_FFFF, &DataType::AUint64));
+    }
+}
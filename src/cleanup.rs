@@ -0,0 +1,170 @@
+use a2lfile::{A2lFile, Module};
+use regex::Regex;
+use std::collections::HashMap;
+
+// an item that was removed by the cleanup, or that would have been removed if it wasn't protected by --cleanup-keep
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CleanupItem {
+    pub(crate) blocktype: &'static str,
+    pub(crate) name: String,
+    pub(crate) kept: bool,
+}
+
+// compare the items of the listed block types before and after the cleanup.
+// Items that are still present keep the state after the cleanup, e.g. GROUPs whose references were cleaned up.
+// Removed items whose name matches one of the keep regexes are restored at their original position.
+macro_rules! restore_removed_items {
+    ($module:expr, $orig_module:expr, $keep:expr, $removed:expr, $($field:ident: $blocktype:literal),+ $(,)?) => {
+        $(
+            let mut remaining: HashMap<String, _> = std::mem::take(&mut $module.$field)
+                .into_iter()
+                .map(|item| (item.name.clone(), item))
+                .collect();
+            for item in &$orig_module.$field {
+                if let Some(remaining_item) = remaining.remove(&item.name) {
+                    $module.$field.push(remaining_item);
+                } else {
+                    let kept = $keep.iter().any(|re| re.is_match(&item.name));
+                    if kept {
+                        $module.$field.push(item.clone());
+                    }
+                    $removed.push(CleanupItem {
+                        blocktype: $blocktype,
+                        name: item.name.clone(),
+                        kept,
+                    });
+                }
+            }
+            // the cleanup does not create new items, but nothing should be lost if it ever does
+            $module.$field.extend(remaining.into_values());
+        )+
+    };
+}
+
+// Remove unreferenced items and empty groups using the cleanup of a2lfile, and report what was removed.
+// The cleanup of a2lfile does not tell which items it removed, so the names of the items in each module
+// are compared before and after the cleanup.
+// Items whose names match one of the keep regexes are never removed.
+// If dry_run is set, the file is not modified and the returned list shows what would be removed.
+pub(crate) fn cleanup_with_report(
+    a2l_file: &mut A2lFile,
+    keep: &[Regex],
+    dry_run: bool,
+) -> Vec<CleanupItem> {
+    let original = a2l_file.clone();
+    a2l_file.cleanup();
+
+    let mut removed = Vec::new();
+    for (module, orig_module) in a2l_file
+        .project
+        .module
+        .iter_mut()
+        .zip(&original.project.module)
+    {
+        compare_module(module, orig_module, keep, &mut removed);
+    }
+
+    if dry_run {
+        *a2l_file = original;
+    }
+    removed
+}
+
+fn compare_module(
+    module: &mut Module,
+    orig_module: &Module,
+    keep: &[Regex],
+    removed: &mut Vec<CleanupItem>,
+) {
+    restore_removed_items!(
+        module,
+        orig_module,
+        keep,
+        removed,
+        compu_method: "COMPU_METHOD",
+        compu_tab: "COMPU_TAB",
+        compu_vtab: "COMPU_VTAB",
+        compu_vtab_range: "COMPU_VTAB_RANGE",
+        function: "FUNCTION",
+        group: "GROUP",
+        record_layout: "RECORD_LAYOUT",
+        typedef_axis: "TYPEDEF_AXIS",
+        typedef_blob: "TYPEDEF_BLOB",
+        typedef_characteristic: "TYPEDEF_CHARACTERISTIC",
+        typedef_measurement: "TYPEDEF_MEASUREMENT",
+        typedef_structure: "TYPEDEF_STRUCTURE",
+        unit: "UNIT",
+    );
+}
+
+// count the removed items of each block type, in the order in which the block types first appear
+pub(crate) fn count_by_blocktype(items: &[CleanupItem]) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for item in items.iter().filter(|item| !item.kept) {
+        if let Some((_, count)) = counts.iter_mut().find(|(bt, _)| *bt == item.blocktype) {
+            *count += 1;
+        } else {
+            counts.push((item.blocktype, 1));
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin COMPU_METHOD Used_Compu "" IDENTICAL "%4.2" ""
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Unused_Compu "" IDENTICAL "%4.2" ""
+    /end COMPU_METHOD
+    /begin COMPU_VTAB Vendor_Table "" TAB_VERB 1
+      0 "zero"
+    /end COMPU_VTAB
+    /begin MEASUREMENT Value "" UBYTE Used_Compu 0 0 0 255
+    /end MEASUREMENT
+    /begin RECORD_LAYOUT Unused_Layout
+      FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_cleanup_with_report() {
+        // dry run: the file is not modified
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let original = a2l_file.clone();
+        let removed = cleanup_with_report(&mut a2l_file, &[], true);
+        assert_eq!(a2l_file, original);
+        assert!(removed.iter().any(|item| item.blocktype == "COMPU_METHOD"
+            && item.name == "Unused_Compu"
+            && !item.kept));
+        assert!(removed
+            .iter()
+            .any(|item| item.blocktype == "RECORD_LAYOUT" && item.name == "Unused_Layout"));
+        assert!(!removed.iter().any(|item| item.name == "Used_Compu"));
+
+        // items matching a keep regex are not removed
+        let keep = vec![Regex::new("^Vendor_").unwrap()];
+        let removed = cleanup_with_report(&mut a2l_file, &keep, false);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.compu_method.len(), 1);
+        assert_eq!(module.compu_method[0].name, "Used_Compu");
+        assert!(module.record_layout.is_empty());
+        assert_eq!(module.compu_vtab.len(), 1);
+        assert!(removed
+            .iter()
+            .any(|item| item.name == "Vendor_Table" && item.kept));
+
+        let counts = count_by_blocktype(&removed);
+        assert!(counts.contains(&("COMPU_METHOD", 1)));
+        assert!(counts.contains(&("RECORD_LAYOUT", 1)));
+        assert!(!counts
+            .iter()
+            .any(|(blocktype, _)| *blocktype == "COMPU_VTAB"));
+    }
+}
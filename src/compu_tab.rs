@@ -0,0 +1,261 @@
+use crate::debuginfo::{DbgDataType, DebugData};
+use a2lfile::{
+    A2lFile, A2lObject, CompuVtab, CompuVtabRange, ConversionType, ValuePairsStruct,
+    ValueTriplesStruct,
+};
+use std::collections::HashSet;
+
+// Convert each COMPU_VTAB_RANGE where every range consists of a single value into an equivalent COMPU_VTAB.
+// Such tables are often created by merging, or by tools that always generate range tables.
+// COMPU_VTAB and COMPU_VTAB_RANGE share a namespace, so the new COMPU_VTAB keeps the name of the
+// COMPU_VTAB_RANGE and all references to it remain valid.
+// Returns the number of converted tables.
+pub(crate) fn normalize_compu_tabs(a2l_file: &mut A2lFile, log_msgs: &mut Vec<String>) -> usize {
+    let mut converted_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let (degenerate, ranges): (Vec<CompuVtabRange>, Vec<CompuVtabRange>) =
+            std::mem::take(&mut module.compu_vtab_range)
+                .into_iter()
+                .partition(is_single_value_table);
+        module.compu_vtab_range = ranges;
+
+        let mut converted_names = HashSet::new();
+        for compu_vtab_range in degenerate {
+            log_msgs.push(format!(
+                "Converted COMPU_VTAB_RANGE {} on line {} to a COMPU_VTAB",
+                compu_vtab_range.name,
+                compu_vtab_range.get_line()
+            ));
+            converted_names.insert(compu_vtab_range.name.clone());
+            module.compu_vtab.push(make_compu_vtab(compu_vtab_range));
+            converted_count += 1;
+        }
+
+        // the COMPU_METHODs that use the new COMPU_VTABs as their conversion table must have the type TAB_VERB
+        for compu_method in &mut module.compu_method {
+            let converted = compu_method
+                .compu_tab_ref
+                .as_ref()
+                .is_some_and(|tab_ref| converted_names.contains(&tab_ref.conversion_table));
+            if converted && compu_method.conversion_type != ConversionType::TabVerb {
+                log_msgs.push(format!(
+                    "Changed the conversion type of COMPU_METHOD {} on line {} to TAB_VERB",
+                    compu_method.name,
+                    compu_method.get_line()
+                ));
+                compu_method.conversion_type = ConversionType::TabVerb;
+            }
+        }
+    }
+
+    converted_count
+}
+
+// Convert each COMPU_VTAB that was created for an enum into a COMPU_VTAB_RANGE in which every value is a single-point range.
+// Enum-derived tables are named after the enum type, so only COMPU_VTABs whose name is the name of an enum
+// in the debug info are converted. Some tools prefer range tables, especially if the enum values have gaps.
+// The update keeps COMPU_VTAB_RANGEs of enums up to date, so the conversion only needs to be done once.
+// Returns the number of converted tables.
+pub(crate) fn enum_tabs_to_ranges(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut converted_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let (enum_tabs, other_tabs): (Vec<CompuVtab>, Vec<CompuVtab>) =
+            std::mem::take(&mut module.compu_vtab)
+                .into_iter()
+                .partition(|compu_vtab| is_enum_name(&compu_vtab.name, debug_data));
+        module.compu_vtab = other_tabs;
+
+        for compu_vtab in enum_tabs {
+            log_msgs.push(format!(
+                "Converted COMPU_VTAB {} on line {} to a COMPU_VTAB_RANGE",
+                compu_vtab.name,
+                compu_vtab.get_line()
+            ));
+            module
+                .compu_vtab_range
+                .push(make_compu_vtab_range(compu_vtab));
+            converted_count += 1;
+        }
+    }
+
+    converted_count
+}
+
+fn is_enum_name(name: &str, debug_data: &DebugData) -> bool {
+    debug_data.typenames().get(name).is_some_and(|typerefs| {
+        typerefs.iter().any(|typeref| {
+            debug_data
+                .types
+                .get(typeref)
+                .is_some_and(|typeinfo| matches!(typeinfo.datatype, DbgDataType::Enum { .. }))
+        })
+    })
+}
+
+fn make_compu_vtab_range(compu_vtab: CompuVtab) -> CompuVtabRange {
+    let mut compu_vtab_range = CompuVtabRange::new(
+        compu_vtab.name,
+        compu_vtab.long_identifier,
+        compu_vtab.value_pairs.len() as u16,
+    );
+    compu_vtab_range.value_triples = compu_vtab
+        .value_pairs
+        .into_iter()
+        .map(|pair| ValueTriplesStruct::new(pair.in_val, pair.in_val, pair.out_val))
+        .collect();
+    compu_vtab_range.default_value = compu_vtab.default_value;
+    compu_vtab_range
+}
+
+// A COMPU_VTAB_RANGE can be represented as a COMPU_VTAB if the lower and upper limits of all ranges are equal.
+// This is the opposite of the decision to create a range table for values that can't be listed individually.
+fn is_single_value_table(compu_vtab_range: &CompuVtabRange) -> bool {
+    !compu_vtab_range.value_triples.is_empty()
+        && compu_vtab_range
+            .value_triples
+            .iter()
+            .all(|triple| triple.in_val_min == triple.in_val_max)
+}
+
+fn make_compu_vtab(compu_vtab_range: CompuVtabRange) -> CompuVtab {
+    let mut compu_vtab = CompuVtab::new(
+        compu_vtab_range.name,
+        compu_vtab_range.long_identifier,
+        ConversionType::TabVerb,
+        compu_vtab_range.value_triples.len() as u16,
+    );
+    compu_vtab.value_pairs = compu_vtab_range
+        .value_triples
+        .into_iter()
+        .map(|triple| ValuePairsStruct::new(triple.in_val_min, triple.out_val))
+        .collect();
+    compu_vtab.default_value = compu_vtab_range.default_value;
+    compu_vtab
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_compu_tabs() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD cm_degenerate "" TAB_VERB "%6.3" "" COMPU_TAB_REF degenerate
+    /end COMPU_METHOD
+    /begin COMPU_METHOD cm_identical "" IDENTICAL "%6.3" "" COMPU_TAB_REF degenerate
+    /end COMPU_METHOD
+    /begin COMPU_METHOD cm_range "" TAB_VERB "%6.3" "" COMPU_TAB_REF range
+    /end COMPU_METHOD
+    /begin COMPU_VTAB_RANGE degenerate "single values" 3
+      0 0 "zero"
+      1 1 "one"
+      5 5 "five"
+      DEFAULT_VALUE "invalid"
+    /end COMPU_VTAB_RANGE
+    /begin COMPU_VTAB_RANGE range "" 2
+      0 0 "zero"
+      1 10 "some"
+    /end COMPU_VTAB_RANGE
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let count = normalize_compu_tabs(&mut a2l_file, &mut log_msgs);
+        assert_eq!(count, 1);
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.compu_vtab_range.len(), 1);
+        assert_eq!(module.compu_vtab_range[0].name, "range");
+        assert_eq!(module.compu_vtab.len(), 1);
+        let compu_vtab = &module.compu_vtab[0];
+        assert_eq!(compu_vtab.name, "degenerate");
+        assert_eq!(compu_vtab.long_identifier, "single values");
+        assert_eq!(compu_vtab.number_value_pairs, 3);
+        assert_eq!(compu_vtab.value_pairs[2].in_val, 5.0);
+        assert_eq!(compu_vtab.value_pairs[2].out_val, "five");
+        assert_eq!(
+            compu_vtab.default_value.as_ref().unwrap().display_string,
+            "invalid"
+        );
+        // the COMPU_METHOD that uses the new COMPU_VTAB was corrected
+        assert_eq!(
+            module.compu_method[1].conversion_type,
+            ConversionType::TabVerb
+        );
+        assert_eq!(log_msgs.len(), 2);
+    }
+
+    #[test]
+    fn test_enum_tabs_to_ranges() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD gapped_enum "" TAB_VERB "%.4" "" COMPU_TAB_REF gapped_enum
+    /end COMPU_METHOD
+    /begin COMPU_METHOD other "" TAB_VERB "%.4" "" COMPU_TAB_REF other
+    /end COMPU_METHOD
+    /begin COMPU_VTAB gapped_enum "Conversion table for enum gapped_enum" TAB_VERB 3
+      1 "ONE"
+      2 "TWO"
+      10 "TEN"
+    /end COMPU_VTAB
+    /begin COMPU_VTAB other "" TAB_VERB 1
+      0 "zero"
+    /end COMPU_VTAB
+  /end MODULE
+/end PROJECT"#;
+        let mut debug_data = DebugData::default();
+        debug_data.types.insert(
+            1,
+            crate::debuginfo::TypeInfo {
+                datatype: DbgDataType::Enum {
+                    size: 4,
+                    signed: false,
+                    enumerators: vec![
+                        ("ONE".to_string(), 1),
+                        ("TWO".to_string(), 2),
+                        ("TEN".to_string(), 10),
+                    ],
+                },
+                name: Some("gapped_enum".to_string()),
+                unit_idx: 0,
+                dbginfo_offset: 1,
+                big_endian: None,
+            },
+        );
+        debug_data
+            .types
+            .typenames_mut()
+            .insert("gapped_enum".to_string(), vec![1]);
+
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let count = enum_tabs_to_ranges(&mut a2l_file, &debug_data, &mut log_msgs);
+        assert_eq!(count, 1);
+
+        let module = &a2l_file.project.module[0];
+        // the table that is not based on an enum is not changed
+        assert_eq!(module.compu_vtab.len(), 1);
+        assert_eq!(module.compu_vtab[0].name, "other");
+        assert_eq!(module.compu_vtab_range.len(), 1);
+        let compu_vtab_range = &module.compu_vtab_range[0];
+        assert_eq!(compu_vtab_range.name, "gapped_enum");
+        assert_eq!(compu_vtab_range.number_value_triples, 3);
+        assert_eq!(compu_vtab_range.value_triples[2].in_val_min, 10.0);
+        assert_eq!(compu_vtab_range.value_triples[2].in_val_max, 10.0);
+        assert_eq!(compu_vtab_range.value_triples[2].out_val, "TEN");
+        // converting back gives the original COMPU_VTAB
+        assert_eq!(normalize_compu_tabs(&mut a2l_file, &mut log_msgs), 1);
+        assert_eq!(a2l_file.project.module[0].compu_vtab.len(), 2);
+    }
+}
@@ -0,0 +1,139 @@
+use a2lfile::{A2lFile, Module};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt::Write;
+
+// Display each COMPU_METHOD with its type, its unit and the number of objects that reference it.
+// The most used COMPU_METHODs are shown first.
+pub(crate) fn list_conversions(a2l_file: &A2lFile, filename: &OsStr) {
+    log_println!("COMPU_METHODs in {}:", filename.to_string_lossy());
+    log_println!("{}", format_conversions(a2l_file));
+}
+
+fn format_conversions(a2l_file: &A2lFile) -> String {
+    let multi_module = a2l_file.project.module.len() > 1;
+    let mut out = String::new();
+
+    for module in &a2l_file.project.module {
+        if multi_module {
+            let _ = writeln!(out, "Module {}:", module.name);
+        }
+        if module.compu_method.is_empty() {
+            let _ = writeln!(out, "No COMPU_METHODs found in module {}", module.name);
+            continue;
+        }
+
+        let usage = count_conversion_usage(module);
+        let mut compu_methods: Vec<_> = module
+            .compu_method
+            .iter()
+            .map(|compu_method| {
+                let count = usage.get(&*compu_method.name).copied().unwrap_or(0);
+                (compu_method, count)
+            })
+            .collect();
+        // stable sort: COMPU_METHODs with the same usage count stay in file order
+        compu_methods.sort_by(|(_, count_a), (_, count_b)| count_b.cmp(count_a));
+
+        for (compu_method, count) in compu_methods {
+            let unit = if compu_method.unit.is_empty() {
+                compu_method
+                    .ref_unit
+                    .as_ref()
+                    .map_or("", |ref_unit| ref_unit.unit.as_str())
+            } else {
+                compu_method.unit.as_str()
+            };
+            let _ = writeln!(
+                out,
+                "  {}: {}, unit \"{unit}\", used {count} times",
+                compu_method.name, compu_method.conversion_type
+            );
+        }
+    }
+
+    out
+}
+
+// count the references to each conversion from objects, typedefs and AXIS_DESCRs
+fn count_conversion_usage<'a>(module: &'a Module) -> HashMap<&'a str, usize> {
+    let mut usage = HashMap::<&str, usize>::new();
+    let mut add = |conversion: &'a str| *usage.entry(conversion).or_default() += 1;
+
+    for measurement in &module.measurement {
+        add(&measurement.conversion);
+    }
+    for characteristic in &module.characteristic {
+        add(&characteristic.conversion);
+        for axis_descr in &characteristic.axis_descr {
+            add(&axis_descr.conversion);
+        }
+    }
+    for axis_pts in &module.axis_pts {
+        add(&axis_pts.conversion);
+    }
+    for typedef_measurement in &module.typedef_measurement {
+        add(&typedef_measurement.conversion);
+    }
+    for typedef_characteristic in &module.typedef_characteristic {
+        add(&typedef_characteristic.conversion);
+        for axis_descr in &typedef_characteristic.axis_descr {
+            add(&axis_descr.conversion);
+        }
+    }
+    for typedef_axis in &module.typedef_axis {
+        add(&typedef_axis.conversion);
+    }
+
+    usage
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin COMPU_METHOD Unused "" IDENTICAL "%4.2" ""
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Speed "" LINEAR "%4.2" "km/h"
+      COEFFS_LINEAR 2 0
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Temperature "" RAT_FUNC "%4.2" ""
+      COEFFS 0 1 0 0 0 1
+      REF_UNIT degC
+    /end COMPU_METHOD
+    /begin MEASUREMENT Speed_1 "" UBYTE Speed 0 0 0 255
+    /end MEASUREMENT
+    /begin MEASUREMENT Speed_2 "" UBYTE Speed 0 0 0 255
+    /end MEASUREMENT
+    /begin MEASUREMENT Temperature_1 "" UBYTE Temperature 0 0 0 255
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Curve "" CURVE 0x1000 RL 0 NO_COMPU_METHOD 0 255
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY Speed 8 0 255
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_format_conversions() {
+        let a2l_file = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let text = format_conversions(&a2l_file);
+
+        assert!(text.contains("  Speed: LINEAR, unit \"km/h\", used 3 times"));
+        // the unit is taken from the REF_UNIT if there is no unit string
+        assert!(text.contains("  Temperature: RAT_FUNC, unit \"degC\", used 1 times"));
+        assert!(text.contains("  Unused: IDENTICAL, unit \"\", used 0 times"));
+
+        // the output is sorted by usage
+        let speed_pos = text.find("Speed:").unwrap();
+        let temperature_pos = text.find("Temperature:").unwrap();
+        let unused_pos = text.find("Unused:").unwrap();
+        assert!(speed_pos < temperature_pos);
+        assert!(temperature_pos < unused_pos);
+        // NO_COMPU_METHOD is not a COMPU_METHOD and is not listed
+        assert!(!text.contains("NO_COMPU_METHOD"));
+    }
+}
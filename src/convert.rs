@@ -0,0 +1,621 @@
+use crate::insert::create_record_layout;
+use a2lfile::{
+    A2lFile, A2lObject, Characteristic, CharacteristicType, DefCharacteristic, EcuAddress,
+    InMeasurement, LocMeasurement, MatrixDim, Measurement, Module, ReadOnly, ReadWrite,
+    RefCharacteristic, RefMeasurement,
+};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+// move all identifiers in names from the identifier_list of $src to the identifier_list of $dest
+// $src is set to None if it becomes empty, and $dest is created if it does not exist yet
+macro_rules! move_references {
+    ($src:expr, $dest:expr, $desttype:ident, $names:expr) => {
+        if let Some(src_list) = &mut $src {
+            let (moved, kept): (Vec<String>, Vec<String>) =
+                std::mem::take(&mut src_list.identifier_list)
+                    .into_iter()
+                    .partition(|ident| $names.contains(ident));
+            src_list.identifier_list = kept;
+            if src_list.identifier_list.is_empty() {
+                $src = None;
+            }
+            if !moved.is_empty() {
+                let dest_list = $dest.get_or_insert_with($desttype::new);
+                for ident in moved {
+                    if !dest_list.identifier_list.contains(&ident) {
+                        dest_list.identifier_list.push(ident);
+                    }
+                }
+            }
+        }
+    };
+}
+
+// compile the regexes given on the command line
+// like in --remove, each regex must match the whole name, not just a substring
+pub(crate) fn compile_regexes(regex_strings: &[&str]) -> Result<Vec<Regex>, String> {
+    regex_strings
+        .iter()
+        .map(|re| {
+            let extended_regex = if !re.starts_with('^') && !re.ends_with('$') {
+                format!("^{re}$")
+            } else {
+                re.to_string()
+            };
+            Regex::new(&extended_regex).map_err(|err| format!("Invalid regex \"{re}\": {err}"))
+        })
+        .collect()
+}
+
+// convert MEASUREMENTs whose names match to_characteristic into CHARACTERISTICs, and
+// CHARACTERISTICs whose names match to_measurement into MEASUREMENTs.
+// References to the converted objects in GROUPs and FUNCTIONs are moved to the lists of the new kind.
+// Objects that cannot be converted, e.g. because an object of the target kind with the same name
+// already exists, remain unchanged and an error message is added to error_msgs.
+pub(crate) fn convert_items(
+    a2l_file: &mut A2lFile,
+    to_characteristic: &[Regex],
+    to_measurement: &[Regex],
+    log_msgs: &mut Vec<String>,
+    error_msgs: &mut Vec<String>,
+) -> usize {
+    let mut converted_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let (meas_convert, mut meas_keep): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut module.measurement)
+                .into_iter()
+                .partition(|meas| to_characteristic.iter().any(|re| re.is_match(&meas.name)));
+        let (chr_convert, mut chr_keep): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut module.characteristic)
+                .into_iter()
+                .partition(|chr| to_measurement.iter().any(|re| re.is_match(&chr.name)));
+
+        // MEASUREMENTs, CHARACTERISTICs, AXIS_PTS, BLOBs and INSTANCEs share one namespace.
+        // A conversion is not possible if an object of the target kind or of one of the other
+        // kinds with the same name remains in the file
+        let other_names: HashMap<String, &str> = module
+            .axis_pts
+            .iter()
+            .map(|axis_pts| (axis_pts.name.clone(), "an AXIS_PTS"))
+            .chain(module.blob.iter().map(|blob| (blob.name.clone(), "a BLOB")))
+            .chain(
+                module
+                    .instance
+                    .iter()
+                    .map(|instance| (instance.name.clone(), "an INSTANCE")),
+            )
+            .collect();
+        let mut chr_namespace = other_names.clone();
+        chr_namespace.extend(
+            chr_keep
+                .iter()
+                .map(|chr| (chr.name.clone(), "a CHARACTERISTIC")),
+        );
+        let mut meas_namespace = other_names;
+        meas_namespace.extend(
+            meas_keep
+                .iter()
+                .map(|meas| (meas.name.clone(), "a MEASUREMENT")),
+        );
+
+        let mut new_characteristics = Vec::new();
+        let mut converted_to_characteristic = HashSet::new();
+        for measurement in meas_convert {
+            let line = measurement.get_layout().line;
+            let result = if let Some(kind) = chr_namespace.get(&measurement.name) {
+                Err(format!("{kind} with the same name already exists"))
+            } else {
+                measurement_to_characteristic(module, &measurement)
+            };
+            match result {
+                Ok(characteristic) => {
+                    log_msgs.push(format!(
+                        "Converted MEASUREMENT {} on line {line} to CHARACTERISTIC",
+                        measurement.name
+                    ));
+                    converted_to_characteristic.insert(measurement.name);
+                    new_characteristics.push(characteristic);
+                }
+                Err(errmsg) => {
+                    error_msgs.push(format!(
+                        "MEASUREMENT {} on line {line} could not be converted: {errmsg}",
+                        measurement.name
+                    ));
+                    meas_keep.push(measurement);
+                }
+            }
+        }
+
+        let mut new_measurements = Vec::new();
+        let mut converted_to_measurement = HashSet::new();
+        for characteristic in chr_convert {
+            let line = characteristic.get_layout().line;
+            let result = if let Some(kind) = meas_namespace.get(&characteristic.name) {
+                Err(format!("{kind} with the same name already exists"))
+            } else {
+                characteristic_to_measurement(module, &characteristic)
+            };
+            match result {
+                Ok(measurement) => {
+                    log_msgs.push(format!(
+                        "Converted CHARACTERISTIC {} on line {line} to MEASUREMENT",
+                        characteristic.name
+                    ));
+                    converted_to_measurement.insert(characteristic.name);
+                    new_measurements.push(measurement);
+                }
+                Err(errmsg) => {
+                    error_msgs.push(format!(
+                        "CHARACTERISTIC {} on line {line} could not be converted: {errmsg}",
+                        characteristic.name
+                    ));
+                    chr_keep.push(characteristic);
+                }
+            }
+        }
+
+        meas_keep.extend(new_measurements);
+        chr_keep.extend(new_characteristics);
+        module.measurement = meas_keep;
+        module.characteristic = chr_keep;
+
+        move_group_references(
+            module,
+            &converted_to_characteristic,
+            &converted_to_measurement,
+        );
+        move_function_references(
+            module,
+            &converted_to_characteristic,
+            &converted_to_measurement,
+        );
+
+        converted_count += converted_to_characteristic.len() + converted_to_measurement.len();
+    }
+
+    converted_count
+}
+
+fn measurement_to_characteristic(
+    module: &mut Module,
+    measurement: &Measurement,
+) -> Result<Characteristic, String> {
+    let Some(ecu_address) = &measurement.ecu_address else {
+        return Err("it has no ECU_ADDRESS".to_string());
+    };
+    if measurement.var_virtual.is_some() {
+        return Err("it is a VIRTUAL MEASUREMENT".to_string());
+    }
+    if measurement.address_type.is_some() {
+        return Err("a CHARACTERISTIC cannot have an ADDRESS_TYPE".to_string());
+    }
+
+    // the deprecated ARRAY_SIZE is replaced by MATRIX_DIM
+    let matrix_dim = measurement.matrix_dim.clone().or_else(|| {
+        measurement.array_size.as_ref().map(|array_size| {
+            let mut matrix_dim = MatrixDim::new();
+            matrix_dim.dim_list = vec![array_size.number];
+            matrix_dim
+        })
+    });
+    let ctype = if matrix_dim.is_some() {
+        CharacteristicType::ValBlk
+    } else {
+        CharacteristicType::Value
+    };
+    let recordlayout_name = create_record_layout(module, measurement.datatype);
+
+    let mut characteristic = Characteristic::new(
+        measurement.name.clone(),
+        measurement.long_identifier.clone(),
+        ctype,
+        ecu_address.address,
+        recordlayout_name,
+        0f64,
+        measurement.conversion.clone(),
+        measurement.lower_limit,
+        measurement.upper_limit,
+    );
+    // enable hex mode for the address (item 3 in the CHARACTERISTIC)
+    characteristic.get_layout_mut().item_location.3 .1 = true;
+    characteristic.matrix_dim = matrix_dim;
+    characteristic
+        .annotation
+        .clone_from(&measurement.annotation);
+    characteristic.bit_mask.clone_from(&measurement.bit_mask);
+    characteristic
+        .byte_order
+        .clone_from(&measurement.byte_order);
+    characteristic.discrete.clone_from(&measurement.discrete);
+    characteristic
+        .display_identifier
+        .clone_from(&measurement.display_identifier);
+    characteristic
+        .ecu_address_extension
+        .clone_from(&measurement.ecu_address_extension);
+    characteristic.format.clone_from(&measurement.format);
+    characteristic
+        .function_list
+        .clone_from(&measurement.function_list);
+    characteristic.if_data.clone_from(&measurement.if_data);
+    characteristic
+        .max_refresh
+        .clone_from(&measurement.max_refresh);
+    characteristic
+        .model_link
+        .clone_from(&measurement.model_link);
+    characteristic.phys_unit.clone_from(&measurement.phys_unit);
+    characteristic
+        .ref_memory_segment
+        .clone_from(&measurement.ref_memory_segment);
+    characteristic
+        .symbol_link
+        .clone_from(&measurement.symbol_link);
+    // MEASUREMENTs are read-only unless READ_WRITE is set, CHARACTERISTICs are writable unless READ_ONLY is set
+    if measurement.read_write.is_none() {
+        characteristic.read_only = Some(ReadOnly::new());
+    }
+
+    Ok(characteristic)
+}
+
+fn characteristic_to_measurement(
+    module: &Module,
+    characteristic: &Characteristic,
+) -> Result<Measurement, String> {
+    if characteristic.characteristic_type != CharacteristicType::Value
+        && characteristic.characteristic_type != CharacteristicType::ValBlk
+    {
+        return Err(format!(
+            "only VALUE and VAL_BLK can be converted, but it is of type {}",
+            characteristic.characteristic_type
+        ));
+    }
+    if characteristic.virtual_characteristic.is_some() {
+        return Err("it is a VIRTUAL_CHARACTERISTIC".to_string());
+    }
+    let datatype = module
+        .record_layout
+        .iter()
+        .find(|rl| rl.name == characteristic.deposit)
+        .and_then(|rl| rl.fnc_values.as_ref())
+        .map(|fnc_values| fnc_values.datatype)
+        .ok_or_else(|| {
+            format!(
+                "the data type could not be determined from RECORD_LAYOUT {}",
+                characteristic.deposit
+            )
+        })?;
+
+    let mut measurement = Measurement::new(
+        characteristic.name.clone(),
+        characteristic.long_identifier.clone(),
+        datatype,
+        characteristic.conversion.clone(),
+        0,
+        0f64,
+        characteristic.lower_limit,
+        characteristic.upper_limit,
+    );
+    // create an ECU_ADDRESS attribute, and set it to hex display mode
+    let mut ecu_address = EcuAddress::new(characteristic.address);
+    ecu_address.get_layout_mut().item_location.0 .1 = true;
+    measurement.ecu_address = Some(ecu_address);
+    // a VAL_BLK can use NUMBER instead of MATRIX_DIM to set its size
+    measurement.matrix_dim = characteristic.matrix_dim.clone().or_else(|| {
+        if characteristic.characteristic_type == CharacteristicType::ValBlk {
+            characteristic.number.as_ref().map(|number| {
+                let mut matrix_dim = MatrixDim::new();
+                matrix_dim.dim_list = vec![number.number];
+                matrix_dim
+            })
+        } else {
+            None
+        }
+    });
+    measurement
+        .annotation
+        .clone_from(&characteristic.annotation);
+    measurement.bit_mask.clone_from(&characteristic.bit_mask);
+    measurement
+        .byte_order
+        .clone_from(&characteristic.byte_order);
+    measurement.discrete.clone_from(&characteristic.discrete);
+    measurement
+        .display_identifier
+        .clone_from(&characteristic.display_identifier);
+    measurement
+        .ecu_address_extension
+        .clone_from(&characteristic.ecu_address_extension);
+    measurement.format.clone_from(&characteristic.format);
+    measurement
+        .function_list
+        .clone_from(&characteristic.function_list);
+    measurement.if_data.clone_from(&characteristic.if_data);
+    measurement
+        .max_refresh
+        .clone_from(&characteristic.max_refresh);
+    measurement
+        .model_link
+        .clone_from(&characteristic.model_link);
+    measurement.phys_unit.clone_from(&characteristic.phys_unit);
+    measurement
+        .ref_memory_segment
+        .clone_from(&characteristic.ref_memory_segment);
+    measurement
+        .symbol_link
+        .clone_from(&characteristic.symbol_link);
+    if characteristic.read_only.is_none() {
+        measurement.read_write = Some(ReadWrite::new());
+    }
+
+    Ok(measurement)
+}
+
+// move the references to converted objects between REF_MEASUREMENT and REF_CHARACTERISTIC
+fn move_group_references(
+    module: &mut Module,
+    converted_to_characteristic: &HashSet<String>,
+    converted_to_measurement: &HashSet<String>,
+) {
+    for group in &mut module.group {
+        move_references!(
+            group.ref_measurement,
+            group.ref_characteristic,
+            RefCharacteristic,
+            converted_to_characteristic
+        );
+        move_references!(
+            group.ref_characteristic,
+            group.ref_measurement,
+            RefMeasurement,
+            converted_to_measurement
+        );
+    }
+}
+
+// MEASUREMENTs that are written by a FUNCTION (OUT_MEASUREMENT, LOC_MEASUREMENT) become
+// CHARACTERISTICs that are defined by it (DEF_CHARACTERISTIC), while its inputs (IN_MEASUREMENT)
+// become referenced CHARACTERISTICs (REF_CHARACTERISTIC). The reverse conversion works analogously.
+fn move_function_references(
+    module: &mut Module,
+    converted_to_characteristic: &HashSet<String>,
+    converted_to_measurement: &HashSet<String>,
+) {
+    for function in &mut module.function {
+        move_references!(
+            function.in_measurement,
+            function.ref_characteristic,
+            RefCharacteristic,
+            converted_to_characteristic
+        );
+        move_references!(
+            function.loc_measurement,
+            function.def_characteristic,
+            DefCharacteristic,
+            converted_to_characteristic
+        );
+        move_references!(
+            function.out_measurement,
+            function.def_characteristic,
+            DefCharacteristic,
+            converted_to_characteristic
+        );
+        move_references!(
+            function.ref_characteristic,
+            function.in_measurement,
+            InMeasurement,
+            converted_to_measurement
+        );
+        move_references!(
+            function.def_characteristic,
+            function.loc_measurement,
+            LocMeasurement,
+            converted_to_measurement
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin MEASUREMENT Writeable "" UWORD CompuMethod 0 0 0 1000
+      ECU_ADDRESS 0x1000
+      ECU_ADDRESS_EXTENSION 2
+      READ_WRITE
+      SYMBOL_LINK "Writeable" 0
+    /end MEASUREMENT
+    /begin MEASUREMENT Array "" FLOAT32_IEEE NO_COMPU_METHOD 0 0 -10 10
+      ECU_ADDRESS 0x2000
+      MATRIX_DIM 4
+    /end MEASUREMENT
+    /begin MEASUREMENT Collision "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      ECU_ADDRESS 0x3000
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Collision "" VALUE 0x3000 __UBYTE_Z 0 NO_COMPU_METHOD 0 255
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Param "" VALUE 0x4000 __SLONG_Z 0 NO_COMPU_METHOD -100 100
+      BIT_MASK 0xFF
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Curve "" CURVE 0x5000 __SLONG_Z 0 NO_COMPU_METHOD -100 100
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 4 0 100
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin FUNCTION Func ""
+      /begin DEF_CHARACTERISTIC Param
+      /end DEF_CHARACTERISTIC
+      /begin OUT_MEASUREMENT Writeable
+      /end OUT_MEASUREMENT
+      /begin IN_MEASUREMENT Array Collision
+      /end IN_MEASUREMENT
+    /end FUNCTION
+    /begin GROUP Grp ""
+      /begin REF_CHARACTERISTIC Collision Param Curve
+      /end REF_CHARACTERISTIC
+      /begin REF_MEASUREMENT Writeable Array Collision
+      /end REF_MEASUREMENT
+    /end GROUP
+    /begin RECORD_LAYOUT __UBYTE_Z FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT __SLONG_Z FNC_VALUES 1 SLONG ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT
+"#;
+
+    static COLLISION_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin MEASUREMENT Shared_Name "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      ECU_ADDRESS 0x1000
+    /end MEASUREMENT
+    /begin BLOB Shared_Name "" 0x2000 16
+    /end BLOB
+    /begin CHARACTERISTIC Instance_Name "" VALUE 0x3000 __UBYTE_Z 0 NO_COMPU_METHOD 0 255
+    /end CHARACTERISTIC
+    /begin INSTANCE Instance_Name "" Struct_Type 0x4000
+    /end INSTANCE
+    /begin RECORD_LAYOUT __UBYTE_Z FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT
+"#;
+
+    #[test]
+    fn test_convert_items() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let to_characteristic = compile_regexes(&["Writeable", "Array", "Collision"]).unwrap();
+        let to_measurement = compile_regexes(&["Param", "Curve"]).unwrap();
+        let mut log_msgs = Vec::new();
+        let mut error_msgs = Vec::new();
+        let count = convert_items(
+            &mut a2l_file,
+            &to_characteristic,
+            &to_measurement,
+            &mut log_msgs,
+            &mut error_msgs,
+        );
+        // Writeable, Array -> CHARACTERISTIC; Param -> MEASUREMENT
+        // Collision and Curve cannot be converted
+        assert_eq!(count, 3);
+        assert_eq!(log_msgs.len(), 3);
+        assert_eq!(error_msgs.len(), 2);
+        assert!(error_msgs.iter().any(|msg| msg.contains("Collision")));
+        assert!(error_msgs.iter().any(|msg| msg.contains("Curve")));
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        assert_eq!(module.characteristic.len(), 4);
+
+        let writeable = module
+            .characteristic
+            .iter()
+            .find(|chr| chr.name == "Writeable")
+            .unwrap();
+        assert_eq!(writeable.characteristic_type, CharacteristicType::Value);
+        assert_eq!(writeable.address, 0x1000);
+        assert_eq!(writeable.deposit, "__UWORD_Z");
+        assert_eq!(writeable.conversion, "CompuMethod");
+        assert_eq!(writeable.upper_limit, 1000.0);
+        assert_eq!(
+            writeable.ecu_address_extension.as_ref().unwrap().extension,
+            2
+        );
+        assert!(writeable.symbol_link.is_some());
+        assert!(writeable.read_only.is_none());
+        // the new RECORD_LAYOUTs __UWORD_Z and __FLOAT32_IEEE_Z were created
+        assert_eq!(module.record_layout.len(), 4);
+
+        let array = module
+            .characteristic
+            .iter()
+            .find(|chr| chr.name == "Array")
+            .unwrap();
+        assert_eq!(array.characteristic_type, CharacteristicType::ValBlk);
+        assert_eq!(array.matrix_dim.as_ref().unwrap().dim_list, vec![4]);
+        assert!(array.read_only.is_some());
+
+        let param = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Param")
+            .unwrap();
+        assert_eq!(param.datatype, a2lfile::DataType::Slong);
+        assert_eq!(param.ecu_address.as_ref().unwrap().address, 0x4000);
+        assert_eq!(param.bit_mask.as_ref().unwrap().mask, 0xFF);
+        assert!(param.read_write.is_some());
+
+        // the references in the GROUP and the FUNCTION were moved
+        let group = &module.group[0];
+        assert_eq!(
+            group.ref_characteristic.as_ref().unwrap().identifier_list,
+            vec!["Collision", "Curve", "Writeable", "Array"]
+        );
+        assert_eq!(
+            group.ref_measurement.as_ref().unwrap().identifier_list,
+            vec!["Collision", "Param"]
+        );
+        let function = &module.function[0];
+        assert!(function.out_measurement.is_none());
+        assert_eq!(
+            function
+                .def_characteristic
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec!["Writeable"]
+        );
+        assert_eq!(
+            function.loc_measurement.as_ref().unwrap().identifier_list,
+            vec!["Param"]
+        );
+        assert_eq!(
+            function.in_measurement.as_ref().unwrap().identifier_list,
+            vec!["Collision"]
+        );
+        assert_eq!(
+            function
+                .ref_characteristic
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec!["Array"]
+        );
+    }
+
+    #[test]
+    fn test_compile_regexes() {
+        assert!(compile_regexes(&["abc.*", "^x"]).is_ok());
+        assert!(compile_regexes(&["(abc"]).is_err());
+    }
+
+    #[test]
+    fn test_convert_name_collision() {
+        let mut a2l_file =
+            a2lfile::load_from_string(COLLISION_TEXT, None, &mut Vec::new(), false).unwrap();
+        let to_characteristic = compile_regexes(&["Shared_Name"]).unwrap();
+        let to_measurement = compile_regexes(&["Instance_Name"]).unwrap();
+        let mut error_msgs = Vec::new();
+        let count = convert_items(
+            &mut a2l_file,
+            &to_characteristic,
+            &to_measurement,
+            &mut Vec::new(),
+            &mut error_msgs,
+        );
+        // objects of other kinds also block the conversion
+        assert_eq!(count, 0);
+        assert_eq!(error_msgs.len(), 2);
+        assert!(error_msgs[0].contains("a BLOB with the same name"));
+        assert!(error_msgs[1].contains("an INSTANCE with the same name"));
+        assert_eq!(a2l_file.project.module[0].measurement.len(), 1);
+        assert_eq!(a2l_file.project.module[0].characteristic.len(), 1);
+    }
+}
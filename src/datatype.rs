@@ -1,5 +1,5 @@
 use crate::debuginfo::{DbgDataType, TypeInfo};
-use a2lfile::DataType;
+use a2lfile::{CharacterEncoding, DataType};
 
 // map the datatypes from the elf_info to a2l datatypes
 // the only really relevant cases are for the integer, floating point and enum types
@@ -52,6 +52,73 @@ pub(crate) fn get_a2l_datatype(typeinfo: &TypeInfo) -> DataType {
     }
 }
 
+// Wide characters are stored as 16 or 32 bit integers, so a string of wide characters can only be
+// recognized by the name of the element type, e.g. wchar_t[N].
+// Returns the encoding of the string if the typeinfo is a one-dimensional array of wide characters.
+pub(crate) fn get_wide_string_encoding(typeinfo: &TypeInfo) -> Option<CharacterEncoding> {
+    let DbgDataType::Array { dim, arraytype, .. } = &typeinfo.datatype else {
+        return None;
+    };
+    let is_char_type = matches!(
+        arraytype.name.as_deref(),
+        Some("wchar_t" | "char16_t" | "char32_t" | "WCHAR")
+    );
+    if dim.len() != 1 || !is_char_type {
+        return None;
+    }
+    match &arraytype.datatype {
+        DbgDataType::Uint16 | DbgDataType::Sint16 => Some(CharacterEncoding::Utf16),
+        DbgDataType::Uint32 | DbgDataType::Sint32 => Some(CharacterEncoding::Utf32),
+        _ => None,
+    }
+}
+
+// map an a2l datatype to the corresponding basic type of the debug info
+// This is used when the type of a symbol is not known from the debug info, but is given by the user.
+pub(crate) fn get_dbg_datatype(datatype: &DataType) -> DbgDataType {
+    match datatype {
+        DataType::Ubyte => DbgDataType::Uint8,
+        DataType::Uword => DbgDataType::Uint16,
+        DataType::Ulong => DbgDataType::Uint32,
+        DataType::AUint64 => DbgDataType::Uint64,
+        DataType::Sbyte => DbgDataType::Sint8,
+        DataType::Sword => DbgDataType::Sint16,
+        DataType::Slong => DbgDataType::Sint32,
+        DataType::AInt64 => DbgDataType::Sint64,
+        DataType::Float16Ieee => DbgDataType::Other(2),
+        DataType::Float32Ieee => DbgDataType::Float,
+        DataType::Float64Ieee => DbgDataType::Double,
+    }
+}
+
+// get the a2l datatype from its name, e.g. "FLOAT64_IEEE"
+pub(crate) fn parse_a2l_datatype(name: &str) -> Option<DataType> {
+    match name {
+        "UBYTE" => Some(DataType::Ubyte),
+        "SBYTE" => Some(DataType::Sbyte),
+        "UWORD" => Some(DataType::Uword),
+        "SWORD" => Some(DataType::Sword),
+        "ULONG" => Some(DataType::Ulong),
+        "SLONG" => Some(DataType::Slong),
+        "A_UINT64" => Some(DataType::AUint64),
+        "A_INT64" => Some(DataType::AInt64),
+        "FLOAT16_IEEE" => Some(DataType::Float16Ieee),
+        "FLOAT32_IEEE" => Some(DataType::Float32Ieee),
+        "FLOAT64_IEEE" => Some(DataType::Float64Ieee),
+        _ => None,
+    }
+}
+
+// the size in bytes of a value of an a2l datatype
+pub(crate) fn get_datatype_size(datatype: &DataType) -> u32 {
+    match datatype {
+        DataType::Ubyte | DataType::Sbyte => 1,
+        DataType::Uword | DataType::Sword | DataType::Float16Ieee => 2,
+        DataType::Ulong | DataType::Slong | DataType::Float32Ieee => 4,
+        DataType::AUint64 | DataType::AInt64 | DataType::Float64Ieee => 8,
+    }
+}
+
 pub(crate) fn get_type_limits(
     typeinfo: &TypeInfo,
     default_lower: f64,
@@ -64,17 +131,15 @@ pub(crate) fn get_type_limits(
         DbgDataType::Bitfield {
             bit_size, basetype, ..
         } => {
-            let raw_range: u64 = 1 << bit_size;
+            // the limits depend on the width of the bitfield, not on the size of the storage type
+            // a signed field of n bits holds -2^(n-1) ..= 2^(n-1) - 1, an unsigned one 0 ..= 2^n - 1
+            let raw_range = 2f64.powi(i32::from(*bit_size));
             match &basetype.datatype {
                 DbgDataType::Sint8
                 | DbgDataType::Sint16
                 | DbgDataType::Sint32
-                | DbgDataType::Sint64 => {
-                    let lower = -((raw_range / 2) as f64);
-                    let upper = (raw_range / 2) as f64;
-                    (lower, upper)
-                }
-                _ => (0f64, raw_range as f64),
+                | DbgDataType::Sint64 => (-(raw_range / 2.0), raw_range / 2.0 - 1.0),
+                _ => (0f64, raw_range - 1.0),
             }
         }
         DbgDataType::Double => (f64::MIN, f64::MAX),
@@ -96,3 +161,54 @@ pub(crate) fn get_type_limits(
     };
     (new_lower_limit, new_upper_limit)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_datatype_size() {
+        assert_eq!(get_datatype_size(&DataType::Ubyte), 1);
+        assert_eq!(get_datatype_size(&DataType::Sbyte), 1);
+        assert_eq!(get_datatype_size(&DataType::Uword), 2);
+        assert_eq!(get_datatype_size(&DataType::Sword), 2);
+        assert_eq!(get_datatype_size(&DataType::Float16Ieee), 2);
+        assert_eq!(get_datatype_size(&DataType::Ulong), 4);
+        assert_eq!(get_datatype_size(&DataType::Slong), 4);
+        assert_eq!(get_datatype_size(&DataType::Float32Ieee), 4);
+        assert_eq!(get_datatype_size(&DataType::AUint64), 8);
+        assert_eq!(get_datatype_size(&DataType::AInt64), 8);
+        assert_eq!(get_datatype_size(&DataType::Float64Ieee), 8);
+    }
+
+    #[test]
+    fn test_get_type_limits_bitfield() {
+        let make_typeinfo = |datatype| TypeInfo {
+            name: None,
+            unit_idx: 0,
+            dbginfo_offset: 0,
+            big_endian: None,
+            datatype,
+        };
+        let bitfield = |basetype, bit_size| {
+            make_typeinfo(DbgDataType::Bitfield {
+                basetype: Box::new(make_typeinfo(basetype)),
+                bit_offset: 0,
+                bit_size,
+            })
+        };
+        let limits = |typeinfo| get_type_limits(&typeinfo, 0.0, 0.0);
+
+        assert_eq!(limits(bitfield(DbgDataType::Uint16, 1)), (0.0, 1.0));
+        assert_eq!(limits(bitfield(DbgDataType::Uint16, 3)), (0.0, 7.0));
+        assert_eq!(limits(bitfield(DbgDataType::Uint32, 12)), (0.0, 4095.0));
+        assert_eq!(limits(bitfield(DbgDataType::Sint16, 1)), (-1.0, 0.0));
+        assert_eq!(limits(bitfield(DbgDataType::Sint16, 4)), (-8.0, 7.0));
+        assert_eq!(limits(bitfield(DbgDataType::Sint32, 12)), (-2048.0, 2047.0));
+        // a bitfield that fills the whole storage type
+        assert_eq!(
+            limits(bitfield(DbgDataType::Uint64, 64)),
+            (0.0, u64::MAX as f64)
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use crate::datatype::{get_a2l_datatype, get_datatype_size, get_dbg_datatype, parse_a2l_datatype};
+use crate::debuginfo::{DbgDataType, DebugData, TypeInfo};
+use crate::update::get_symbol_info;
+use a2lfile::{A2lFile, A2lObject, CompuMethod, ConversionType, DataType, IfData, SymbolLink};
+use std::collections::{HashMap, HashSet};
+
+// the COMPU_METHOD that is created by --datatype-map-create-cm for objects without a conversion
+const PASSTHROUGH_COMPU_METHOD: &str = "DatatypeMap_Passthrough";
+
+// Parse a data type mapping like "FLOAT64_IEEE=A_UINT64,FLOAT32_IEEE=ULONG".
+// The mapped data type must have the same size as the original data type, since only the
+// interpretation of the value changes and not the amount of memory that is read.
+pub(crate) fn parse_datatype_map(text: &str) -> Result<Vec<(DataType, DataType)>, String> {
+    let mut map: Vec<(DataType, DataType)> = Vec::new();
+    for entry in text
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let Some((from_text, to_text)) = entry.split_once('=') else {
+            return Err(format!(
+                "Error: invalid data type mapping \"{entry}\"; expected TYPE=TYPE, e.g. FLOAT64_IEEE=A_UINT64"
+            ));
+        };
+        let from = parse_a2l_datatype(from_text.trim())
+            .ok_or_else(|| format!("Error: \"{}\" is not a valid data type", from_text.trim()))?;
+        let to = parse_a2l_datatype(to_text.trim())
+            .ok_or_else(|| format!("Error: \"{}\" is not a valid data type", to_text.trim()))?;
+        // the debug info has no 16 bit floating point type, so it can neither be found nor created
+        if from == DataType::Float16Ieee || to == DataType::Float16Ieee {
+            return Err(format!(
+                "Error: invalid data type mapping \"{entry}\"; FLOAT16_IEEE can't be mapped"
+            ));
+        }
+        if get_datatype_size(&from) != get_datatype_size(&to) {
+            return Err(format!(
+                "Error: the data types in \"{entry}\" have different sizes, so {from} cannot be replaced by {to}"
+            ));
+        }
+        if map.iter().any(|(existing, _)| *existing == from) {
+            return Err(format!(
+                "Error: the data type {from} is mapped more than once"
+            ));
+        }
+        if from != to {
+            map.push((from, to));
+        }
+    }
+    if map.is_empty() {
+        return Err("Error: the data type mapping is empty".to_string());
+    }
+    Ok(map)
+}
+
+// Replace the basic types in the debug info according to the mapping, before anything is inserted or updated.
+// This way get_a2l_datatype returns the mapped data type everywhere, so inserted and updated objects
+// get the mapped data type, limits that match it, and RECORD_LAYOUTs that are named after it.
+// Returns the debug info offsets of all replaced types, which are needed by check_mapped_objects.
+pub(crate) fn map_debug_types(
+    debug_data: &mut DebugData,
+    map: &[(DataType, DataType)],
+) -> HashSet<usize> {
+    let mut mapped_types = HashSet::new();
+    for typeinfo in debug_data.types.values_mut() {
+        map_typeinfo(typeinfo, map, &mut mapped_types);
+    }
+    mapped_types
+}
+
+fn map_typeinfo(
+    typeinfo: &mut TypeInfo,
+    map: &[(DataType, DataType)],
+    mapped_types: &mut HashSet<usize>,
+) {
+    match &mut typeinfo.datatype {
+        DbgDataType::Uint8
+        | DbgDataType::Uint16
+        | DbgDataType::Uint32
+        | DbgDataType::Uint64
+        | DbgDataType::Sint8
+        | DbgDataType::Sint16
+        | DbgDataType::Sint32
+        | DbgDataType::Sint64
+        | DbgDataType::Float
+        | DbgDataType::Double => {
+            let datatype = get_a2l_datatype(typeinfo);
+            if let Some((_, to)) = map.iter().find(|(from, _)| *from == datatype) {
+                typeinfo.datatype = get_dbg_datatype(to);
+                mapped_types.insert(typeinfo.dbginfo_offset);
+            }
+        }
+        DbgDataType::Bitfield { basetype, .. } => map_typeinfo(basetype, map, mapped_types),
+        DbgDataType::Array { arraytype, .. } => map_typeinfo(arraytype, map, mapped_types),
+        DbgDataType::Struct { members, .. } | DbgDataType::Union { members, .. } => {
+            for (member, _) in members.values_mut() {
+                map_typeinfo(member, map, mapped_types);
+            }
+        }
+        DbgDataType::Class {
+            inheritance,
+            members,
+            ..
+        } => {
+            for (member, _) in inheritance.values_mut().chain(members.values_mut()) {
+                map_typeinfo(member, map, mapped_types);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Find the MEASUREMENTs, CHARACTERISTICs and AXIS_PTS that received a mapped data type during the insertion or the update.
+// The stored value keeps its meaning, so the conversion must be maintained manually. If create_cm is set,
+// objects without a conversion get an IDENTICAL COMPU_METHOD instead, which marks them as converted.
+// Returns the number of objects that have a mapped data type.
+pub(crate) fn check_mapped_objects(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    map: &[(DataType, DataType)],
+    mapped_types: &HashSet<usize>,
+    create_cm: bool,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut mapped_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let mut checker = MappedObjectChecker {
+            debug_data,
+            map,
+            mapped_types,
+            create_cm,
+            passthrough_needed: false,
+            log_msgs: &mut *log_msgs,
+        };
+
+        // the data types of CHARACTERISTICs and AXIS_PTS are stored in their RECORD_LAYOUTs
+        let record_layouts: HashMap<&str, (Option<DataType>, Option<DataType>)> = module
+            .record_layout
+            .iter()
+            .map(|rl| {
+                (
+                    rl.name.as_str(),
+                    (
+                        rl.fnc_values.as_ref().map(|fnc_values| fnc_values.datatype),
+                        rl.axis_pts_x.as_ref().map(|axis_pts_x| axis_pts_x.datatype),
+                    ),
+                )
+            })
+            .collect();
+
+        for measurement in &mut module.measurement {
+            if checker.is_mapped(
+                &measurement.name,
+                &measurement.symbol_link,
+                &measurement.if_data,
+                measurement.datatype,
+            ) {
+                let line = measurement.get_line();
+                checker.mark_object(
+                    ("MEASUREMENT", &measurement.name, line),
+                    measurement.datatype,
+                    &mut measurement.conversion,
+                );
+                mapped_count += 1;
+            }
+        }
+        for characteristic in &mut module.characteristic {
+            let Some((Some(datatype), _)) = record_layouts.get(characteristic.deposit.as_str())
+            else {
+                continue;
+            };
+            if checker.is_mapped(
+                &characteristic.name,
+                &characteristic.symbol_link,
+                &characteristic.if_data,
+                *datatype,
+            ) {
+                let line = characteristic.get_line();
+                checker.mark_object(
+                    ("CHARACTERISTIC", &characteristic.name, line),
+                    *datatype,
+                    &mut characteristic.conversion,
+                );
+                mapped_count += 1;
+            }
+        }
+        for axis_pts in &mut module.axis_pts {
+            let Some((_, Some(datatype))) = record_layouts.get(axis_pts.deposit_record.as_str())
+            else {
+                continue;
+            };
+            if checker.is_mapped(
+                &axis_pts.name,
+                &axis_pts.symbol_link,
+                &axis_pts.if_data,
+                *datatype,
+            ) {
+                let line = axis_pts.get_line();
+                checker.mark_object(
+                    ("AXIS_PTS", &axis_pts.name, line),
+                    *datatype,
+                    &mut axis_pts.conversion,
+                );
+                mapped_count += 1;
+            }
+        }
+
+        if checker.passthrough_needed
+            && !module
+                .compu_method
+                .iter()
+                .any(|cm| cm.name == PASSTHROUGH_COMPU_METHOD)
+        {
+            module.compu_method.push(CompuMethod::new(
+                PASSTHROUGH_COMPU_METHOD.to_string(),
+                "raw value with a replaced data type, created by --datatype-map".to_string(),
+                ConversionType::Identical,
+                "%.0".to_string(),
+                String::new(),
+            ));
+        }
+    }
+
+    mapped_count
+}
+
+struct MappedObjectChecker<'a> {
+    debug_data: &'a DebugData,
+    map: &'a [(DataType, DataType)],
+    mapped_types: &'a HashSet<usize>,
+    create_cm: bool,
+    passthrough_needed: bool,
+    log_msgs: &'a mut Vec<String>,
+}
+
+impl MappedObjectChecker<'_> {
+    // an object has a mapped data type if its data type is the target of a mapping and its symbol uses a replaced type
+    // Objects whose variable originally had the target data type are not affected.
+    fn is_mapped(
+        &self,
+        name: &str,
+        symbol_link: &Option<SymbolLink>,
+        if_data: &[IfData],
+        datatype: DataType,
+    ) -> bool {
+        if !self.map.iter().any(|(_, to)| *to == datatype) {
+            return false;
+        }
+        get_symbol_info(name, symbol_link, if_data, self.debug_data)
+            .is_ok_and(|sym_info| self.contains_mapped_type(sym_info.typeinfo))
+    }
+
+    fn contains_mapped_type(&self, typeinfo: &TypeInfo) -> bool {
+        match &typeinfo.datatype {
+            DbgDataType::Bitfield { basetype, .. } => self.contains_mapped_type(basetype),
+            DbgDataType::Array { arraytype, .. } => self.contains_mapped_type(arraytype),
+            DbgDataType::Struct { members, .. } | DbgDataType::Union { members, .. } => members
+                .values()
+                .any(|(member, _)| self.contains_mapped_type(member)),
+            DbgDataType::Class {
+                inheritance,
+                members,
+                ..
+            } => inheritance
+                .values()
+                .chain(members.values())
+                .any(|(member, _)| self.contains_mapped_type(member)),
+            _ => self.mapped_types.contains(&typeinfo.dbginfo_offset),
+        }
+    }
+
+    fn mark_object(
+        &mut self,
+        (blocktype, name, line): (&str, &str, u32),
+        datatype: DataType,
+        conversion: &mut String,
+    ) {
+        if self.create_cm && conversion == "NO_COMPU_METHOD" {
+            *conversion = PASSTHROUGH_COMPU_METHOD.to_string();
+            self.passthrough_needed = true;
+        } else {
+            self.log_msgs.push(format!(
+                "Warning: {blocktype} {name} on line {line}: the data type {datatype} was set by the data type mapping; the conversion must be maintained manually"
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    // Characteristic_ValBlk is a float array and Characteristic_Value is an uint32_t in update_test.elf
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Characteristic_ValBlk "" VAL_BLK 0x0 __ULONG_Z 0 NO_COMPU_METHOD 0 4294967295
+      MATRIX_DIM 5
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Characteristic_Value "" VALUE 0x0 __ULONG_Z 0 NO_COMPU_METHOD 0 4294967295
+    /end CHARACTERISTIC
+    /begin MEASUREMENT ValBlk_Copy "" ULONG CM_Linear 0 0 0 100
+      MATRIX_DIM 5
+      SYMBOL_LINK "Characteristic_ValBlk" 0
+    /end MEASUREMENT
+    /begin COMPU_METHOD CM_Linear "" LINEAR "%6.3" ""
+      COEFFS_LINEAR 2 0
+    /end COMPU_METHOD
+    /begin RECORD_LAYOUT __ULONG_Z
+      FNC_VALUES 1 ULONG ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_parse_datatype_map() {
+        let map = parse_datatype_map("FLOAT64_IEEE=A_UINT64, FLOAT32_IEEE = ULONG").unwrap();
+        assert_eq!(
+            map,
+            vec![
+                (DataType::Float64Ieee, DataType::AUint64),
+                (DataType::Float32Ieee, DataType::Ulong)
+            ]
+        );
+        // unknown data types
+        assert!(parse_datatype_map("DOUBLE=A_UINT64").is_err());
+        assert!(parse_datatype_map("FLOAT64_IEEE=U64").is_err());
+        // missing '='
+        assert!(parse_datatype_map("FLOAT64_IEEE").is_err());
+        // different sizes
+        assert!(parse_datatype_map("FLOAT64_IEEE=ULONG").is_err());
+        // duplicate mapping
+        assert!(parse_datatype_map("FLOAT32_IEEE=ULONG,FLOAT32_IEEE=SLONG").is_err());
+        // there is no 16 bit floating point type in the debug info
+        assert!(parse_datatype_map("UWORD=FLOAT16_IEEE").is_err());
+        assert!(parse_datatype_map("").is_err());
+    }
+
+    #[test]
+    fn test_map_debug_types() {
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let map = parse_datatype_map("FLOAT32_IEEE=ULONG").unwrap();
+        let mapped_types = map_debug_types(&mut debug_data, &map);
+        assert!(!mapped_types.is_empty());
+
+        // the element type of the float array is replaced, including inside of structs
+        let sym_info = crate::symbol::find_symbol("Characteristic_ValBlk", &debug_data).unwrap();
+        let DbgDataType::Array { arraytype, .. } = &sym_info.typeinfo.datatype else {
+            panic!("Characteristic_ValBlk is not an array");
+        };
+        assert!(matches!(arraytype.datatype, DbgDataType::Uint32));
+        assert!(mapped_types.contains(&arraytype.dbginfo_offset));
+        let sym_info = crate::symbol::find_symbol("Curve_ExternalAxis.value", &debug_data).unwrap();
+        assert_eq!(get_a2l_datatype(sym_info.typeinfo), DataType::Ulong);
+
+        // variables of other types are unchanged
+        let sym_info = crate::symbol::find_symbol("Measurement_Value", &debug_data).unwrap();
+        assert!(matches!(sym_info.typeinfo.datatype, DbgDataType::Uint16));
+        let sym_info = crate::symbol::find_symbol("Characteristic_Value", &debug_data).unwrap();
+        assert!(!mapped_types.contains(&sym_info.typeinfo.dbginfo_offset));
+    }
+
+    #[test]
+    fn test_check_mapped_objects() {
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let map = parse_datatype_map("FLOAT32_IEEE=ULONG").unwrap();
+        let mapped_types = map_debug_types(&mut debug_data, &map);
+
+        // without a passthrough COMPU_METHOD every object with a mapped data type gets a warning
+        // Characteristic_Value was an uint32_t all along, so it is not affected
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let count = check_mapped_objects(
+            &mut a2l_file,
+            &debug_data,
+            &map,
+            &mapped_types,
+            false,
+            &mut log_msgs,
+        );
+        assert_eq!(count, 2);
+        assert_eq!(log_msgs.len(), 2);
+        assert_eq!(a2l_file.project.module[0].compu_method.len(), 1);
+
+        // with --datatype-map-create-cm, only the MEASUREMENT with a LINEAR conversion still gets a warning
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let count = check_mapped_objects(
+            &mut a2l_file,
+            &debug_data,
+            &map,
+            &mapped_types,
+            true,
+            &mut log_msgs,
+        );
+        assert_eq!(count, 2);
+        assert_eq!(log_msgs.len(), 1);
+        assert!(log_msgs[0].contains("MEASUREMENT ValBlk_Copy"));
+        let module = &a2l_file.project.module[0];
+        assert_eq!(
+            module.characteristic[0].conversion,
+            PASSTHROUGH_COMPU_METHOD
+        );
+        assert_eq!(module.characteristic[1].conversion, "NO_COMPU_METHOD");
+        assert_eq!(module.compu_method.len(), 2);
+        assert_eq!(module.compu_method[1].name, PASSTHROUGH_COMPU_METHOD);
+    }
+}
@@ -1,4 +1,5 @@
 use super::{DebugDataReader, UnitList};
+use crate::debuginfo::ConstValue;
 use gimli::{DebugAddrBase, DebuggingInformationEntry, EndianSlice, RunTimeEndian, UnitHeader};
 
 type SliceType<'a> = EndianSlice<'a, RunTimeEndian>;
@@ -12,14 +13,45 @@ pub(crate) fn get_attr_value<'unit>(
     entry.attr_value(attrtype).unwrap_or(None)
 }
 
+// get the value of an attribute that contains an unsigned constant
+// The value can be encoded using any of the data forms. DW_FORM_implicit_const (Dwarf 5) is
+// always returned as Sdata by gimli, even if the value is unsigned. Negative values are invalid.
+fn get_unsigned_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+    attrtype: gimli::DwAt,
+) -> Option<u64> {
+    match get_attr_value(entry, attrtype)? {
+        gimli::AttributeValue::Sdata(value) => u64::try_from(value).ok(),
+        other => other.udata_value(),
+    }
+}
+
 // get a name as a String from a DW_AT_name attribute
 pub(crate) fn get_name_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
     dwarf: &gimli::Dwarf<EndianSlice<RunTimeEndian>>,
     unit_header: &gimli::UnitHeader<EndianSlice<RunTimeEndian>>,
 ) -> Result<String, String> {
-    let name_attr = get_attr_value(entry, gimli::constants::DW_AT_name)
-        .ok_or_else(|| "failed to get name attribute".to_string())?;
+    get_string_attribute(entry, gimli::constants::DW_AT_name, dwarf, unit_header)
+}
+
+// get the DW_AT_producer attribute of a compile unit, which names the compiler that created it
+pub(crate) fn get_producer_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+    dwarf: &gimli::Dwarf<EndianSlice<RunTimeEndian>>,
+    unit_header: &gimli::UnitHeader<EndianSlice<RunTimeEndian>>,
+) -> Result<String, String> {
+    get_string_attribute(entry, gimli::constants::DW_AT_producer, dwarf, unit_header)
+}
+
+fn get_string_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+    attr: gimli::DwAt,
+    dwarf: &gimli::Dwarf<EndianSlice<RunTimeEndian>>,
+    unit_header: &gimli::UnitHeader<EndianSlice<RunTimeEndian>>,
+) -> Result<String, String> {
+    let name_attr =
+        get_attr_value(entry, attr).ok_or_else(|| format!("failed to get {attr} attribute"))?;
     match name_attr {
         gimli::AttributeValue::String(slice) => {
             if let Ok(utf8string) = slice.to_string() {
@@ -41,11 +73,12 @@ pub(crate) fn get_name_attribute(
             }
         }
         gimli::AttributeValue::DebugStrOffsetsIndex(index) => {
-            let unit = dwarf.unit(*unit_header).unwrap();
+            // Dwarf 5: DW_FORM_strx - the string offset is stored in a table in .debug_str_offsets
+            let unit = dwarf.unit(*unit_header).map_err(|err| err.to_string())?;
             let offset = dwarf
                 .debug_str_offsets
                 .get_str_offset(unit.encoding().format, unit.str_offsets_base, index)
-                .unwrap();
+                .map_err(|err| err.to_string())?;
             match dwarf.debug_str.get_str(offset) {
                 Ok(slice) => {
                     if let Ok(utf8string) = slice.to_string() {
@@ -57,7 +90,19 @@ pub(crate) fn get_name_attribute(
                 Err(err) => Err(err.to_string()),
             }
         }
-        _ => Err(format!("invalid name attribute type {name_attr:#?}")),
+        gimli::AttributeValue::DebugLineStrRef(line_str_offset) => {
+            // Dwarf 5: DW_FORM_line_strp - the string is stored in .debug_line_str
+            match dwarf.debug_line_str.get_str(line_str_offset) {
+                Ok(slice) => {
+                    if let Ok(utf8string) = slice.to_string() {
+                        return Ok(utf8string.to_owned());
+                    }
+                    Err(format!("could not decode {slice:#?} as a utf-8 string"))
+                }
+                Err(err) => Err(err.to_string()),
+            }
+        }
+        _ => Err(format!("invalid {attr} attribute type {name_attr:#?}")),
     }
 }
 
@@ -114,15 +159,11 @@ pub(crate) fn get_data_member_location_attribute(
         gimli::AttributeValue::Exprloc(expression) => {
             evaluate_exprloc(debug_data_reader, expression, encoding, current_unit)
         }
-        gimli::AttributeValue::Udata(val) => Some(val),
-        gimli::AttributeValue::Data1(val) => Some(u64::from(val)),
-        gimli::AttributeValue::Data2(val) => Some(u64::from(val)),
-        gimli::AttributeValue::Data4(val) => Some(u64::from(val)),
-        gimli::AttributeValue::Data8(val) => Some(val),
-        other => {
-            println!("unexpected data_member_location attribute: {other:?}");
+        gimli::AttributeValue::Sdata(val) => Some(val as u64),
+        other => other.udata_value().or_else(|| {
+            log_println!("unexpected data_member_location attribute: {other:?}");
             None
-        }
+        }),
     }
 }
 
@@ -130,16 +171,7 @@ pub(crate) fn get_data_member_location_attribute(
 pub(crate) fn get_byte_size_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let byte_size_attr = get_attr_value(entry, gimli::constants::DW_AT_byte_size)?;
-    match byte_size_attr {
-        gimli::AttributeValue::Sdata(byte_size) => Some(byte_size as u64),
-        gimli::AttributeValue::Udata(byte_size) => Some(byte_size),
-        gimli::AttributeValue::Data1(byte_size) => Some(u64::from(byte_size)),
-        gimli::AttributeValue::Data2(byte_size) => Some(u64::from(byte_size)),
-        gimli::AttributeValue::Data4(byte_size) => Some(u64::from(byte_size)),
-        gimli::AttributeValue::Data8(byte_size) => Some(byte_size),
-        _ => None,
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_byte_size)
 }
 
 // get the encoding of a variable from the DW_AT_encoding attribute
@@ -154,69 +186,73 @@ pub(crate) fn get_encoding_attribute(
     }
 }
 
-// get the upper bound of an array from the DW_AT_upper_bound attribute
-pub(crate) fn get_lower_bound_attribute(
+// get the byte order of a type from the DW_AT_endianity attribute
+// Returns Some(true) for big endian, Some(false) for little endian and None if the default applies
+pub(crate) fn get_endianity_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
-) -> Option<u64> {
-    let lbound_attr = get_attr_value(entry, gimli::constants::DW_AT_lower_bound)?;
-    match lbound_attr {
-        gimli::AttributeValue::Sdata(lbound) => Some(lbound as u64),
-        gimli::AttributeValue::Udata(lbound) => Some(lbound),
-        gimli::AttributeValue::Data1(lbound) => Some(u64::from(lbound)),
-        gimli::AttributeValue::Data2(lbound) => Some(u64::from(lbound)),
-        gimli::AttributeValue::Data4(lbound) => Some(u64::from(lbound)),
-        gimli::AttributeValue::Data8(lbound) => Some(lbound),
+) -> Option<bool> {
+    let endianity_attr = get_attr_value(entry, gimli::constants::DW_AT_endianity)?;
+    match endianity_attr {
+        gimli::AttributeValue::Endianity(gimli::constants::DW_END_big) => Some(true),
+        gimli::AttributeValue::Endianity(gimli::constants::DW_END_little) => Some(false),
         _ => None,
     }
 }
 
+// get the lower bound of an array from the DW_AT_lower_bound attribute
+// Unlike the other bounds, the lower bound is frequently negative in languages like Ada
+pub(crate) fn get_lower_bound_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+) -> Option<i64> {
+    match get_attr_value(entry, gimli::constants::DW_AT_lower_bound)? {
+        gimli::AttributeValue::Sdata(value) => Some(value),
+        other => other.udata_value().map(|value| value as i64),
+    }
+}
+
 // get the upper bound of an array from the DW_AT_upper_bound attribute
+// The upper bound can be negative if the lower bound is negative, so Sdata values are read as signed.
 pub(crate) fn get_upper_bound_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
-) -> Option<u64> {
-    let ubound_attr = get_attr_value(entry, gimli::constants::DW_AT_upper_bound)?;
-    match ubound_attr {
-        gimli::AttributeValue::Sdata(ubound) => Some(ubound as u64),
-        gimli::AttributeValue::Udata(ubound) => Some(ubound),
-        gimli::AttributeValue::Data1(ubound) => Some(u64::from(ubound)),
-        gimli::AttributeValue::Data2(ubound) => Some(u64::from(ubound)),
-        gimli::AttributeValue::Data4(ubound) => Some(u64::from(ubound)),
-        gimli::AttributeValue::Data8(ubound) => Some(ubound),
+) -> Option<i64> {
+    match get_attr_value(entry, gimli::constants::DW_AT_upper_bound)? {
+        gimli::AttributeValue::Sdata(value) => Some(value),
+        other => {
+            let value = other.udata_value()?;
+            // compilers may use the bit pattern FFF.. to mean that the array size is unknown
+            // this can happen when a pointer to an array is declared
+            if value == u64::from(u32::MAX) || value == u64::MAX {
+                None
+            } else {
+                i64::try_from(value).ok()
+            }
+        }
+    }
+}
+
+// get the source language of a compile unit from the DW_AT_language attribute
+pub(crate) fn get_language_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+) -> Option<gimli::DwLang> {
+    match get_attr_value(entry, gimli::constants::DW_AT_language)? {
+        gimli::AttributeValue::Language(lang) => Some(lang),
         _ => None,
     }
 }
 
-// get the upper bound of an array from the DW_AT_upper_bound attribute
+// get the number of elements of an array from the DW_AT_count attribute
 pub(crate) fn get_count_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let count_attr = get_attr_value(entry, gimli::constants::DW_AT_count)?;
-    match count_attr {
-        gimli::AttributeValue::Sdata(count) => Some(count as u64),
-        gimli::AttributeValue::Udata(count) => Some(count),
-        gimli::AttributeValue::Data1(count) => Some(u64::from(count)),
-        gimli::AttributeValue::Data2(count) => Some(u64::from(count)),
-        gimli::AttributeValue::Data4(count) => Some(u64::from(count)),
-        gimli::AttributeValue::Data8(count) => Some(count),
-        _ => None,
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_count)
 }
 
-// get the byte stride of an array from the DW_AT_upper_bound attribute
+// get the byte stride of an array from the DW_AT_byte_stride attribute
 // this attribute is only present if the stride is different from the element size
 pub(crate) fn get_byte_stride_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let stride_attr = get_attr_value(entry, gimli::constants::DW_AT_byte_stride)?;
-    match stride_attr {
-        gimli::AttributeValue::Sdata(stride) => Some(stride as u64),
-        gimli::AttributeValue::Udata(stride) => Some(stride),
-        gimli::AttributeValue::Data1(stride) => Some(u64::from(stride)),
-        gimli::AttributeValue::Data2(stride) => Some(u64::from(stride)),
-        gimli::AttributeValue::Data4(stride) => Some(u64::from(stride)),
-        gimli::AttributeValue::Data8(stride) => Some(stride),
-        _ => None,
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_byte_stride)
 }
 
 // get the const value of an enumerator from the DW_AT_const_value attribute
@@ -235,17 +271,25 @@ pub(crate) fn get_const_value_attribute(
     }
 }
 
+// get the value of a variable that was optimized away from the DW_AT_const_value attribute
+// Floating point values and other values that do not fit into a data form are stored in a block
+pub(crate) fn get_variable_const_value_attribute(
+    entry: &DebuggingInformationEntry<SliceType, usize>,
+) -> Option<ConstValue> {
+    let constval_attr = get_attr_value(entry, gimli::constants::DW_AT_const_value)?;
+    if let gimli::AttributeValue::Block(data) = constval_attr {
+        Some(ConstValue::Data(data.to_vec()))
+    } else {
+        get_const_value_attribute(entry).map(ConstValue::Integer)
+    }
+}
+
 // get the bit size of a variable from the DW_AT_bit_size attribute
 // this attribute is only present if the variable is in a bitfield
 pub(crate) fn get_bit_size_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let bit_size_attr = get_attr_value(entry, gimli::constants::DW_AT_bit_size)?;
-    if let gimli::AttributeValue::Udata(bit_size) = bit_size_attr {
-        Some(bit_size)
-    } else {
-        None
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_bit_size)
 }
 
 // get the bit offset of a variable from the DW_AT_bit_offset attribute
@@ -253,18 +297,9 @@ pub(crate) fn get_bit_size_attribute(
 pub(crate) fn get_bit_offset_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let data_bit_offset_attr = get_attr_value(entry, gimli::constants::DW_AT_bit_offset)?;
     // DW_AT_bit_offset: up to Dwarf 3
     // DW_AT_data_bit_offset: Dwarf 4 and following
-    match data_bit_offset_attr {
-        gimli::AttributeValue::Sdata(bit_offset) => Some(bit_offset as u64),
-        gimli::AttributeValue::Udata(bit_offset) => Some(bit_offset),
-        gimli::AttributeValue::Data1(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data2(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data4(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data8(bit_offset) => Some(bit_offset),
-        _ => None,
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_bit_offset)
 }
 
 // get the bit offset of a variable from the DW_AT_data_bit_offset attribute
@@ -272,18 +307,9 @@ pub(crate) fn get_bit_offset_attribute(
 pub(crate) fn get_data_bit_offset_attribute(
     entry: &DebuggingInformationEntry<SliceType, usize>,
 ) -> Option<u64> {
-    let data_bit_offset_attr = get_attr_value(entry, gimli::constants::DW_AT_data_bit_offset)?;
     // DW_AT_bit_offset: up to Dwarf 3
     // DW_AT_data_bit_offset: Dwarf 4 and following
-    match data_bit_offset_attr {
-        gimli::AttributeValue::Sdata(bit_offset) => Some(bit_offset as u64),
-        gimli::AttributeValue::Udata(bit_offset) => Some(bit_offset),
-        gimli::AttributeValue::Data1(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data2(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data4(bit_offset) => Some(u64::from(bit_offset)),
-        gimli::AttributeValue::Data8(bit_offset) => Some(bit_offset),
-        _ => None,
-    }
+    get_unsigned_attribute(entry, gimli::constants::DW_AT_data_bit_offset)
 }
 
 pub(crate) fn get_specification_attribute<'data, 'abbrev, 'unit>(
@@ -1,19 +1,29 @@
-use crate::debuginfo::{DbgDataType, DebugData, TypeInfo, VarInfo};
+use crate::debuginfo::{
+    find_section, BinaryInfo, ConstInfo, ConstValue, DbgDataType, DebugData, MemoryImage, TypeInfo,
+    TypeMap, VarInfo, VarSource,
+};
 use gimli::{Abbreviations, DebuggingInformationEntry, Dwarf, UnitHeader};
 use gimli::{EndianSlice, RunTimeEndian};
 use indexmap::IndexMap;
 use object::read::ObjectSection;
-use object::{Endianness, Object};
+use object::{
+    Endianness, Object, ObjectSymbol, SectionFlags, SectionIndex, SectionKind, SymbolKind,
+};
 use std::ffi::OsStr;
 use std::ops::Index;
-use std::{collections::HashMap, fs::File};
+use std::time::Instant;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+};
 
 type SliceType<'a> = EndianSlice<'a, RunTimeEndian>;
 
 mod attributes;
 use attributes::{
     get_abstract_origin_attribute, get_location_attribute, get_name_attribute,
-    get_specification_attribute, get_typeref_attribute,
+    get_producer_attribute, get_specification_attribute, get_typeref_attribute,
+    get_variable_const_value_attribute,
 };
 mod typereader;
 
@@ -27,14 +37,242 @@ struct DebugDataReader<'elffile> {
     units: UnitList<'elffile>,
     unit_names: Vec<Option<String>>,
     endian: Endianness,
-    sections: HashMap<String, (u64, u64)>,
+    sections: HashMap<String, Vec<(u64, u64)>>,
+    readonly_sections: HashSet<String>,
+    memory: MemoryImage,
+    binary: BinaryInfo,
+    // loading is cancelled once this point in time is reached
+    deadline: Option<Instant>,
+    // names of types that should be loaded even if no variable uses them
+    named_types: HashSet<String>,
+    // the struct, union, class and typedef entries whose names are in named_types
+    named_typerefs: Vec<(String, usize)>,
+    // descriptions of the compile units that were skipped because of errors in the debug info
+    skipped_units: Vec<String>,
+    // the offsets of all type entries; each of them gets a slot in the TypeMap
+    type_offsets: Vec<usize>,
+}
+
+// Reads the types of an elf file when they are used for the first time.
+// The resolver owns the mapped file, because the DWARF data that the reader uses is borrowed from it.
+pub(crate) struct TypeResolver {
+    // the reader must be dropped before the file data that it borrows
+    reader: DebugDataReader<'static>,
+    _filedata: memmap2::Mmap,
 }
 
 // load the debug info from an elf file
-pub(crate) fn load_dwarf(filename: &OsStr, verbose: bool) -> Result<DebugData, String> {
+// Only the variables are read here. Their types are read by the TypeResolver when they are used.
+pub(crate) fn load_dwarf(
+    filename: &OsStr,
+    verbose: bool,
+    deadline: Option<Instant>,
+    named_types: &HashSet<String>,
+) -> Result<DebugData, String> {
     let filedata = load_filedata(filename)?;
-    let elffile = load_elf_file(&filename.to_string_lossy(), &filedata)?;
+    // SAFETY: the mapped memory stays at the same address until the Mmap is dropped, even if the Mmap
+    // itself is moved. The Mmap is moved into the TypeResolver together with the reader that borrows
+    // from it, and the field order of the TypeResolver ensures that the reader is dropped first.
+    // The elffile is only used inside of this function, while the Mmap is still alive.
+    let filedata_static: &'static [u8] =
+        unsafe { std::slice::from_raw_parts(filedata.as_ptr(), filedata.len()) };
+    let elffile = load_elf_file(&filename.to_string_lossy(), filedata_static)?;
+
+    let mut dbg_reader = create_reader(filename, &elffile, verbose, deadline, named_types)?;
+
+    let mut debug_data = dbg_reader
+        .read_debug_info_entries()
+        .map_err(|errmsg| format!("Error: {}: {errmsg}", filename.to_string_lossy()))?;
+
+    // the types of all variables and constants, and the requested named types
+    let roots: Vec<usize> = debug_data
+        .variables
+        .values()
+        .flatten()
+        .map(|varinfo| varinfo.typeref)
+        .chain(
+            debug_data
+                .constants
+                .values()
+                .map(|constinfo| constinfo.typeref),
+        )
+        .chain(
+            dbg_reader
+                .named_typerefs
+                .iter()
+                .map(|(_, typeref)| *typeref),
+        )
+        .collect();
+    let type_offsets = std::mem::take(&mut dbg_reader.type_offsets);
+    // the time limit only applies to loading the file, not to reading the types later on
+    dbg_reader.deadline = None;
+    let resolver = TypeResolver {
+        reader: dbg_reader,
+        _filedata: filedata,
+    };
+    debug_data.types = TypeMap::new_lazy(type_offsets, roots, resolver);
+    add_untyped_symbols(&mut debug_data, &elffile);
+
+    Ok(debug_data)
+}
+
+impl TypeResolver {
+    // read one type, together with all the types that it references, and store them in the TypeMap
+    pub(crate) fn read_type(&self, typeref: usize, types: &TypeMap) {
+        let (new_types, typenames) = self.reader.load_type(typeref, types);
+        types.store(new_types, typenames);
+    }
+}
+
+// Variables that are defined in assembly code, or that are created by the linker, are not described
+// by the debug info. They can still be found in the symbol table, which provides an address and a size.
+// Both global and local symbols are used, as long as they are located in a data section.
+// The data type of these symbols is inferred from the size: an unsigned integer for sizes of 1, 2, 4
+// or 8 bytes, and an array of bytes for all other sizes.
+// The symbols are added to the variables with the source VarSource::SymbolTable.
+fn add_untyped_symbols(debug_data: &mut DebugData, elffile: &object::read::File) {
+    let mut next_typeref = debug_data.types.keys().max().map_or(0, |max| max + 1);
+    // all symbols of the same size share one type
+    let mut size_types = HashMap::<u64, usize>::new();
+    // several local symbols can have the same name, so the symbols are only added at the end
+    let mut symtab_variables = IndexMap::<String, Vec<VarInfo>>::new();
+    // C++ variables appear in the symbol table with their mangled names, e.g. "_ZL6class1" for "class1"
+    let known_addresses: HashSet<u64> = debug_data
+        .variables
+        .values()
+        .flatten()
+        .map(|varinfo| varinfo.address)
+        .collect();
+
+    for symbol in elffile.symbols() {
+        if symbol.kind() != SymbolKind::Data
+            || !symbol.is_definition()
+            || symbol.size() == 0
+            || symbol.address() == 0
+            || !is_data_section(elffile, symbol.section_index())
+        {
+            continue;
+        }
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        // a '.' in the name would be interpreted as a struct member, e.g. in "counter.1"
+        if name.is_empty()
+            || name.contains('.')
+            || debug_data.variables.contains_key(name)
+            || debug_data.constants.contains_key(name)
+            || known_addresses.contains(&symbol.address())
+        {
+            continue;
+        }
+
+        let size = symbol.size();
+        let typeref = *size_types.entry(size).or_insert_with(|| {
+            let typeref = next_typeref;
+            next_typeref += 1;
+            debug_data.types.insert(typeref, make_inferred_type(size));
+            typeref
+        });
+        let section = find_section(&debug_data.sections, symbol.address());
+        symtab_variables
+            .entry(name.to_string())
+            .or_default()
+            .push(VarInfo {
+                address: symbol.address(),
+                typeref,
+                unit_idx: usize::MAX,
+                source: VarSource::SymbolTable,
+                function: None,
+                namespaces: vec![],
+                section,
+            });
+    }
+    debug_data.variables.extend(symtab_variables);
+}
+
+// symbols in other sections, e.g. the ".note.ABI-tag" section, are not variables
+fn is_data_section(elffile: &object::read::File, section_index: Option<SectionIndex>) -> bool {
+    section_index
+        .and_then(|index| elffile.section_by_index(index).ok())
+        .is_some_and(|section| {
+            matches!(
+                section.kind(),
+                SectionKind::Data
+                    | SectionKind::UninitializedData
+                    | SectionKind::ReadOnlyData
+                    | SectionKind::ReadOnlyDataWithRel
+            )
+        })
+}
+
+// all entries that describe a type, which can be referenced by variables or by other types
+fn is_type_tag(tag: gimli::DwTag) -> bool {
+    matches!(
+        tag,
+        gimli::constants::DW_TAG_base_type
+            | gimli::constants::DW_TAG_pointer_type
+            | gimli::constants::DW_TAG_reference_type
+            | gimli::constants::DW_TAG_rvalue_reference_type
+            | gimli::constants::DW_TAG_ptr_to_member_type
+            | gimli::constants::DW_TAG_array_type
+            | gimli::constants::DW_TAG_enumeration_type
+            | gimli::constants::DW_TAG_structure_type
+            | gimli::constants::DW_TAG_class_type
+            | gimli::constants::DW_TAG_union_type
+            | gimli::constants::DW_TAG_interface_type
+            | gimli::constants::DW_TAG_typedef
+            | gimli::constants::DW_TAG_const_type
+            | gimli::constants::DW_TAG_volatile_type
+            | gimli::constants::DW_TAG_packed_type
+            | gimli::constants::DW_TAG_restrict_type
+            | gimli::constants::DW_TAG_immutable_type
+            | gimli::constants::DW_TAG_atomic_type
+            | gimli::constants::DW_TAG_subroutine_type
+            | gimli::constants::DW_TAG_unspecified_type
+            | gimli::constants::DW_TAG_string_type
+            | gimli::constants::DW_TAG_subrange_type
+    )
+}
+
+// types that can be requested by name, in order to create a TYPEDEF_STRUCTURE for them
+fn is_named_type_tag(tag: gimli::DwTag) -> bool {
+    tag == gimli::constants::DW_TAG_structure_type
+        || tag == gimli::constants::DW_TAG_union_type
+        || tag == gimli::constants::DW_TAG_class_type
+        || tag == gimli::constants::DW_TAG_typedef
+}
+
+fn make_inferred_type(size: u64) -> TypeInfo {
+    let make_typeinfo = |datatype| TypeInfo {
+        name: None,
+        unit_idx: usize::MAX,
+        datatype,
+        dbginfo_offset: 0,
+        big_endian: None,
+    };
+    match size {
+        1 => make_typeinfo(DbgDataType::Uint8),
+        2 => make_typeinfo(DbgDataType::Uint16),
+        4 => make_typeinfo(DbgDataType::Uint32),
+        8 => make_typeinfo(DbgDataType::Uint64),
+        _ => make_typeinfo(DbgDataType::Array {
+            size,
+            dim: vec![size],
+            lbound: vec![0],
+            stride: 1,
+            arraytype: Box::new(make_typeinfo(DbgDataType::Uint8)),
+        }),
+    }
+}
 
+// check that the elf file contains DWARF debug info, and set up the reader for it
+fn create_reader<'data>(
+    filename: &OsStr,
+    elffile: &object::read::File<'data>,
+    verbose: bool,
+    deadline: Option<Instant>,
+    named_types: &HashSet<String>,
+) -> Result<DebugDataReader<'data>, String> {
     if !elffile
         .sections()
         .any(|section| section.name() == Ok(".debug_info"))
@@ -45,28 +283,37 @@ pub(crate) fn load_dwarf(filename: &OsStr, verbose: bool) -> Result<DebugData, S
         ));
     }
 
-    let dwarf = load_dwarf_sections(&elffile)?;
+    let dwarf = load_dwarf_sections(elffile)?;
 
     if !verify_dwarf_compile_units(&dwarf) {
         return Err(format!("Error: {} does not contain DWARF2+ debug info - zero compile units contain debug info.", filename.to_string_lossy()));
     }
 
-    let sections = get_elf_sections(&elffile);
+    let sections = get_elf_sections(elffile);
+    let readonly_sections = get_readonly_sections(elffile);
+    let memory = get_memory_image(elffile);
+    let binary = get_binary_info(filename, elffile);
 
-    let dbg_reader = DebugDataReader {
+    Ok(DebugDataReader {
         dwarf,
         verbose,
         units: UnitList::new(),
         unit_names: Vec::new(),
         endian: elffile.endianness(),
         sections,
-    };
-
-    Ok(dbg_reader.read_debug_info_entries())
+        readonly_sections,
+        memory,
+        binary,
+        deadline,
+        named_types: named_types.clone(),
+        named_typerefs: Vec::new(),
+        skipped_units: Vec::new(),
+        type_offsets: Vec::new(),
+    })
 }
 
 // open a file and mmap its content
-fn load_filedata(filename: &OsStr) -> Result<memmap2::Mmap, String> {
+pub(super) fn load_filedata(filename: &OsStr) -> Result<memmap2::Mmap, String> {
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(error) => {
@@ -87,7 +334,7 @@ fn load_filedata(filename: &OsStr) -> Result<memmap2::Mmap, String> {
 }
 
 // read the headers and sections of an elf/object file
-fn load_elf_file<'data>(
+pub(super) fn load_elf_file<'data>(
     filename: &str,
     filedata: &'data [u8],
 ) -> Result<object::read::File<'data>, String> {
@@ -97,15 +344,18 @@ fn load_elf_file<'data>(
     }
 }
 
-fn get_elf_sections(elffile: &object::read::File) -> HashMap<String, (u64, u64)> {
-    let mut map = HashMap::new();
+pub(super) fn get_elf_sections(elffile: &object::read::File) -> HashMap<String, Vec<(u64, u64)>> {
+    let mut map: HashMap<String, Vec<(u64, u64)>> = HashMap::new();
 
+    // sections of size zero are kept, so that they can be reported when they are used for inserting
     for section in elffile.sections() {
         let addr = section.address();
         let size = section.size();
-        if addr != 0 && size != 0 {
+        if addr != 0 {
             if let Ok(name) = section.name() {
-                map.insert(name.to_string(), (addr, addr + size));
+                map.entry(name.to_string())
+                    .or_default()
+                    .push((addr, addr + size));
             }
         }
     }
@@ -113,6 +363,89 @@ fn get_elf_sections(elffile: &object::read::File) -> HashMap<String, (u64, u64)>
     map
 }
 
+// get the names of all sections that are loaded into memory, but are not writable
+// if several sections have the same name, the name is only included if none of them is writable
+fn get_readonly_sections(elffile: &object::read::File) -> HashSet<String> {
+    let mut readonly = HashSet::new();
+    let mut writable = HashSet::new();
+
+    for section in elffile.sections() {
+        if section.address() == 0 {
+            continue;
+        }
+        let Ok(name) = section.name() else {
+            continue;
+        };
+        let is_writable = match section.flags() {
+            SectionFlags::Elf { sh_flags } => {
+                if sh_flags & u64::from(object::elf::SHF_ALLOC) == 0 {
+                    continue;
+                }
+                sh_flags & u64::from(object::elf::SHF_WRITE) != 0
+            }
+            SectionFlags::Coff { characteristics } => {
+                characteristics & object::pe::IMAGE_SCN_MEM_WRITE != 0
+            }
+            _ => !matches!(
+                section.kind(),
+                SectionKind::Text
+                    | SectionKind::ReadOnlyData
+                    | SectionKind::ReadOnlyDataWithRel
+                    | SectionKind::ReadOnlyString
+            ),
+        };
+        if is_writable {
+            writable.insert(name.to_string());
+        } else {
+            readonly.insert(name.to_string());
+        }
+    }
+
+    readonly.retain(|name| !writable.contains(name));
+    readonly
+}
+
+// copy the content of all sections that contain initialized data
+fn get_memory_image(elffile: &object::read::File) -> MemoryImage {
+    let mut segments = Vec::new();
+
+    for section in elffile.sections() {
+        if matches!(
+            section.kind(),
+            SectionKind::Data
+                | SectionKind::ReadOnlyData
+                | SectionKind::ReadOnlyDataWithRel
+                | SectionKind::ReadOnlyString
+        ) {
+            if let Ok(data) = section.data() {
+                if section.address() != 0 && !data.is_empty() {
+                    segments.push((section.address(), data.to_vec()));
+                }
+            }
+        }
+    }
+
+    MemoryImage {
+        big_endian: !elffile.is_little_endian(),
+        segments,
+    }
+}
+
+// get the build-id and the target architecture from the headers of the elf file
+fn get_binary_info(filename: &OsStr, elffile: &object::read::File) -> BinaryInfo {
+    let build_id = elffile
+        .build_id()
+        .ok()
+        .flatten()
+        .map(|id| id.iter().map(|byte| format!("{byte:02x}")).collect());
+    BinaryInfo {
+        build_id,
+        architecture: Some(format!("{:?}", elffile.architecture())),
+        is_64bit: Some(elffile.is_64()),
+        ..BinaryInfo::new(filename)
+    }
+}
+
 // load the DWARF debug info from the .debug_<xyz> sections
 fn load_dwarf_sections<'data>(
     elffile: &object::read::File<'data>,
@@ -159,33 +492,87 @@ fn get_endian(elffile: &object::read::File) -> RunTimeEndian {
 }
 
 impl DebugDataReader<'_> {
-    // read the debug information entries in the DWAF data to get all the global variables and their types
-    fn read_debug_info_entries(mut self) -> DebugData {
-        let variables = self.load_variables();
-        let (types, typenames) = self.load_types(&variables);
+    // read the debug information entries in the DWAF data to get all the global variables
+    // The reader keeps everything that is needed to read the types later on.
+    fn read_debug_info_entries(&mut self) -> Result<DebugData, String> {
+        let (variables, constants) = self.load_variables()?;
         let varname_list: Vec<&String> = variables.keys().collect();
         let demangled_names = demangle_cpp_varnames(&varname_list);
 
-        let mut unit_names = Vec::new();
-        std::mem::swap(&mut unit_names, &mut self.unit_names);
-
-        DebugData {
+        Ok(DebugData {
             variables,
-            types,
-            typenames,
             demangled_names,
-            unit_names,
-            sections: self.sections,
+            unit_names: std::mem::take(&mut self.unit_names),
+            sections: std::mem::take(&mut self.sections),
+            readonly_sections: std::mem::take(&mut self.readonly_sections),
+            memory: std::mem::take(&mut self.memory),
+            binary: std::mem::take(&mut self.binary),
+            constants,
+            skipped_units: std::mem::take(&mut self.skipped_units),
+            ..Default::default()
+        })
+    }
+
+    // return an error if loading the debug info has taken too long
+    fn check_deadline(&self) -> Result<(), String> {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Err(
+                "loading the debug info was aborted, because the time limit was reached"
+                    .to_string(),
+            )
+        } else {
+            Ok(())
         }
     }
 
     // load all global variables from the dwarf data
-    fn load_variables(&mut self) -> IndexMap<String, Vec<VarInfo>> {
+    // Global variables that were optimized to a constant value are returned separately, since they have no address
+    #[allow(clippy::type_complexity)]
+    fn load_variables(
+        &mut self,
+    ) -> Result<(IndexMap<String, Vec<VarInfo>>, HashMap<String, ConstInfo>), String> {
         let mut variables = IndexMap::<String, Vec<VarInfo>>::new();
+        let mut constants = HashMap::<String, ConstInfo>::new();
 
         let mut iter = self.dwarf.debug_info.units();
-        while let Ok(Some(unit)) = iter.next() {
-            let abbreviations = unit.abbreviations(&self.dwarf.debug_abbrev).unwrap();
+        let mut unit_offset = 0;
+        let mut units_read = 0;
+        loop {
+            // A compile unit that is damaged, e.g. in the object file of a binary blob, is skipped.
+            // Only the broken unit is lost, instead of all of the debug info.
+            let unit = match iter.next() {
+                Ok(Some(unit)) => unit,
+                Ok(None) => break,
+                Err(err) => {
+                    // without a valid header the length of the unit is unknown, so the following units can't be found
+                    self.skipped_units.push(format_skipped_unit(
+                        unit_offset,
+                        None,
+                        &format!("invalid unit header: {err}; all following units were skipped"),
+                    ));
+                    break;
+                }
+            };
+            unit_offset = unit
+                .offset()
+                .as_debug_info_offset()
+                .map_or(unit_offset, |offset| offset.0);
+            let current_unit_offset = unit_offset;
+            unit_offset += unit.length_including_self();
+            let abbreviations = match unit.abbreviations(&self.dwarf.debug_abbrev) {
+                Ok(abbreviations) => abbreviations,
+                Err(err) => {
+                    self.skipped_units.push(format_skipped_unit(
+                        current_unit_offset,
+                        None,
+                        &format!("invalid abbreviations: {err}"),
+                    ));
+                    continue;
+                }
+            };
             self.units.add(unit, abbreviations);
             let unit_idx = self.units.list.len() - 1;
             let (unit, abbreviations) = &self.units[unit_idx];
@@ -195,18 +582,46 @@ impl DebugDataReader<'_> {
             // in functions are declared inside of DW_TAG_subprogram[/DW_TAG_lexical_block]*.
             // We can easily find all of them by using depth-first traversal of the tree
             let mut entries_cursor = unit.entries(abbreviations);
-            if let Ok(Some((_, entry))) = entries_cursor.next_dfs() {
-                if entry.tag() == gimli::constants::DW_TAG_compile_unit
-                    || entry.tag() == gimli::constants::DW_TAG_partial_unit
-                {
-                    self.unit_names
-                        .push(get_name_attribute(entry, &self.dwarf, unit).ok());
+            let mut unit_name = None;
+            let mut producer = None;
+            let mut unit_error = None;
+            match entries_cursor.next_dfs() {
+                Ok(Some((_, entry))) => {
+                    if entry.tag() == gimli::constants::DW_TAG_compile_unit
+                        || entry.tag() == gimli::constants::DW_TAG_partial_unit
+                    {
+                        unit_name = get_name_attribute(entry, &self.dwarf, unit).ok();
+                        producer = get_producer_attribute(entry, &self.dwarf, unit).ok();
+                    }
                 }
+                Ok(None) => {}
+                Err(err) => unit_error = Some(err),
             }
+            // the index of each name must match the index of the unit
+            self.unit_names.push(unit_name);
+
+            // the content of the unit is only used if the whole unit can be read
+            let mut unit_variables = Vec::new();
+            let mut unit_constants = Vec::new();
+            let mut unit_named_typerefs = Vec::new();
 
             let mut depth = 0;
+            let mut entry_count = 0u32;
             let mut context: Vec<(gimli::DwTag, Option<String>)> = Vec::new();
-            while let Ok(Some((depth_delta, entry))) = entries_cursor.next_dfs() {
+            while unit_error.is_none() {
+                let (depth_delta, entry) = match entries_cursor.next_dfs() {
+                    Ok(Some(item)) => item,
+                    Ok(None) => break,
+                    Err(err) => {
+                        unit_error = Some(err);
+                        break;
+                    }
+                };
+                // checking the time for every entry would be needlessly slow
+                if entry_count.is_multiple_of(1024) {
+                    self.check_deadline()?;
+                }
+                entry_count = entry_count.wrapping_add(1);
                 depth += depth_delta;
                 debug_assert!(depth >= 1);
                 context.truncate((depth - 1) as usize);
@@ -223,20 +638,48 @@ impl DebugDataReader<'_> {
                 }
                 debug_assert_eq!(depth as usize, context.len());
 
+                if is_type_tag(tag) {
+                    if let Some(offset) = entry.offset().to_debug_info_offset(unit) {
+                        self.type_offsets.push(offset.0);
+                    }
+                }
+                if !self.named_types.is_empty() && is_named_type_tag(tag) {
+                    if let Some(named_typeref) = self.get_named_type(entry, unit) {
+                        unit_named_typerefs.push(named_typeref);
+                    }
+                }
+
                 if entry.tag() == gimli::constants::DW_TAG_variable {
                     match self.get_global_variable(entry, unit, abbreviations) {
                         Ok(Some((name, typeref, address))) => {
                             let (function, namespaces) = get_varinfo_from_context(&context);
-                            variables.entry(name).or_default().push(VarInfo {
-                                address,
-                                typeref,
-                                unit_idx,
-                                function,
-                                namespaces,
-                            });
+                            let section = find_section(&self.sections, address);
+                            unit_variables.push((
+                                name,
+                                VarInfo {
+                                    address,
+                                    typeref,
+                                    unit_idx,
+                                    source: VarSource::DebugInfo,
+                                    function,
+                                    namespaces,
+                                    section,
+                                },
+                            ));
                         }
                         Ok(None) => {
-                            // unremarkable, the variable is not a global variable
+                            // unremarkable, the variable is not a global variable.
+                            // However, a global variable could also have been optimized to a constant value
+                            let in_function = context
+                                .iter()
+                                .any(|(tag, _)| *tag == gimli::constants::DW_TAG_subprogram);
+                            if !in_function {
+                                if let Some((name, typeref, value)) =
+                                    self.get_const_variable(entry, unit, abbreviations)
+                                {
+                                    unit_constants.push((name, ConstInfo { value, typeref }));
+                                }
+                            }
                         }
                         Err(errmsg) => {
                             if self.verbose {
@@ -245,15 +688,61 @@ impl DebugDataReader<'_> {
                                     .to_debug_info_offset(unit)
                                     .unwrap_or(gimli::DebugInfoOffset(0))
                                     .0;
-                                println!("Error loading variable @{offset:x}: {errmsg}");
+                                log_println!("Error loading variable @{offset:x}: {errmsg}");
                             }
                         }
                     }
                 }
             }
+
+            if let Some(err) = unit_error {
+                self.skipped_units.push(format_skipped_unit(
+                    current_unit_offset,
+                    producer.as_deref(),
+                    &err.to_string(),
+                ));
+                continue;
+            }
+            for (name, varinfo) in unit_variables {
+                variables.entry(name).or_default().push(varinfo);
+            }
+            constants.extend(unit_constants);
+            self.named_typerefs.extend(unit_named_typerefs);
+            units_read += 1;
         }
 
-        variables
+        if units_read == 0 && !self.skipped_units.is_empty() {
+            return Err(format!(
+                "none of the compile units could be read:\n{}",
+                self.skipped_units.join("\n")
+            ));
+        }
+
+        // a variable that has storage in some compile unit is not a constant
+        constants.retain(|name, _| !variables.contains_key(name));
+
+        Ok((variables, constants))
+    }
+
+    // get the name and offset of a type entry if its name is one of the requested type names
+    // Declarations are skipped, since they don't contain any information about the members of the type.
+    fn get_named_type(
+        &self,
+        entry: &DebuggingInformationEntry<SliceType, usize>,
+        unit: &UnitHeader<SliceType>,
+    ) -> Option<(String, usize)> {
+        if let Ok(Some(gimli::AttributeValue::Flag(true))) =
+            entry.attr_value(gimli::constants::DW_AT_declaration)
+        {
+            return None;
+        }
+        let name = get_name_attribute(entry, &self.dwarf, unit).ok()?;
+        if self.named_types.contains(&name) {
+            let offset = entry.offset().to_debug_info_offset(unit)?;
+            Some((name, offset.0))
+        } else {
+            None
+        }
     }
 
     // an entry of the type DW_TAG_variable only describes a global variable if there is a name, a type and an address
@@ -300,6 +789,29 @@ impl DebugDataReader<'_> {
             }
         }
     }
+
+    // a global variable that was optimized away has a DW_AT_const_value instead of a DW_AT_location
+    fn get_const_variable(
+        &self,
+        entry: &DebuggingInformationEntry<SliceType, usize>,
+        unit: &UnitHeader<SliceType>,
+        abbrev: &gimli::Abbreviations,
+    ) -> Option<(String, usize, ConstValue)> {
+        let value = get_variable_const_value_attribute(entry)?;
+        let name_entry = get_specification_attribute(entry, unit, abbrev);
+        let name_entry = name_entry.as_ref().unwrap_or(entry);
+        let name = get_name_attribute(name_entry, &self.dwarf, unit).ok()?;
+        let typeref = get_typeref_attribute(name_entry, unit).ok()?;
+        Some((name, typeref, value))
+    }
+}
+
+fn format_skipped_unit(offset: usize, producer: Option<&str>, errmsg: &str) -> String {
+    if let Some(producer) = producer {
+        format!("compile unit at offset 0x{offset:x} (producer: {producer}) was skipped: {errmsg}")
+    } else {
+        format!("compile unit at offset 0x{offset:x} was skipped: {errmsg}")
+    }
 }
 
 fn get_varinfo_from_context(
@@ -635,6 +1147,393 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_load_dwarf5_forms() {
+        // dwarf5_test.elf was compiled with gcc using -gdwarf-5. The bit sizes of all bitfields
+        // are encoded using DW_FORM_implicit_const
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/dwarf5_test.elf"), true).unwrap();
+
+        let varinfo = &debugdata.variables["Status_Var"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert_eq!(members.len(), 4);
+        let (done_type, done_offset) = &members["done"];
+        assert_eq!(*done_offset, 0);
+        let DbgDataType::Bitfield {
+            bit_size,
+            bit_offset,
+            ..
+        } = &done_type.datatype
+        else {
+            panic!("Expected bitfield type, got {:?}", done_type.datatype);
+        };
+        assert_eq!(*bit_size, 1);
+        assert_eq!(*bit_offset, 3);
+
+        let varinfo = &debugdata.variables["Flags_Var"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let (flag_type, _) = &typeinfo.get_members().unwrap()["flag_f"];
+        assert!(matches!(
+            flag_type.datatype,
+            DbgDataType::Bitfield {
+                bit_size: 1,
+                bit_offset: 5,
+                ..
+            }
+        ));
+
+        let varinfo = &debugdata.variables["Composite_Var"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert_eq!(members["status"].1, 4);
+        assert_eq!(members["counter"].1, 8);
+    }
+
+    #[test]
+    fn test_load_endianity() {
+        // the members of big_regs have base types with DW_AT_endianity = DW_END_big
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/endian_test.elf"), true).unwrap();
+        assert!(!debugdata.memory.big_endian);
+
+        let varinfo = &debugdata.variables["big_regs"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert_eq!(members["control"].0.big_endian, Some(true));
+        assert_eq!(members["offset"].0.big_endian, Some(true));
+        let table_type = members["table"].0.get_arraytype().unwrap();
+        assert_eq!(table_type.big_endian, Some(true));
+
+        // native_regs does not have any explicit byte order
+        let varinfo = &debugdata.variables["native_regs"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert_eq!(members["control"].0.big_endian, None);
+        assert_eq!(members["status"].0.big_endian, None);
+    }
+
+    #[test]
+    fn test_load_recursive_types() {
+        // loading self-referential and mutually recursive structs must terminate
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/recursive_test.elf"), true).unwrap();
+
+        // the pointers back to the struct that is being decoded are opaque pointers to the struct
+        let varinfo = &debugdata.variables["list_head"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert!(matches!(
+            members["next"].0.datatype,
+            DbgDataType::Pointer(8, dest) if dest == typeinfo.dbginfo_offset
+        ));
+        assert!(matches!(
+            members["prev"].0.datatype,
+            DbgDataType::Pointer(8, dest) if dest == typeinfo.dbginfo_offset
+        ));
+
+        let varinfo = &debugdata.variables["tree_root"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        let child_type = members["children"].0.get_arraytype().unwrap();
+        assert!(matches!(child_type.datatype, DbgDataType::Pointer(8, _)));
+        assert!(matches!(
+            members["parent"].0.datatype,
+            DbgDataType::Pointer(8, _)
+        ));
+
+        let varinfo = &debugdata.variables["mutual_a"][0];
+        let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+        let members = typeinfo.get_members().unwrap();
+        assert!(matches!(
+            members["other"].0.datatype,
+            DbgDataType::Pointer(8, _)
+        ));
+
+        // iterating over all variables does not follow the pointers
+        let names: Vec<String> = crate::debuginfo::iter::VariablesIterator::new(&debugdata, false)
+            .map(|sym_info| sym_info.name)
+            .collect();
+        assert!(names.contains(&"list_head.next".to_string()));
+        assert!(!names.iter().any(|name| name.starts_with("list_head.next.")));
+    }
+
+    #[test]
+    fn test_load_void_pointers() {
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/void_pointer_test.elf"), true).unwrap();
+        for name in [
+            "Void_Pointer",
+            "Const_Void_Pointer",
+            "Nullptr_Pointer",
+            "Opaque_Pointer",
+        ] {
+            let varinfo = &debugdata.variables[name][0];
+            let typeinfo = debugdata.types[&varinfo.typeref].get_reference(&debugdata.types);
+            assert!(matches!(typeinfo.datatype, DbgDataType::Pointer(8, _)));
+            assert!(typeinfo.is_opaque_pointer(&debugdata.types), "{name}");
+            // the pointer itself is used instead of the unknown target
+            let target = typeinfo.get_pointer_target(&debugdata.types);
+            assert!(std::ptr::eq(target, typeinfo));
+        }
+
+        // a pointer to an integer is not opaque
+        let varinfo = &debugdata.variables["Target_Value"][0];
+        let typeinfo = &debugdata.types[&varinfo.typeref];
+        assert!(!typeinfo.is_opaque_pointer(&debugdata.types));
+    }
+
+    #[test]
+    fn test_load_corrupt_units() {
+        // the file contains one valid compile unit and three corrupt ones
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/corrupt_unit_test.elf"), true).unwrap();
+        assert!(debugdata.variables.contains_key("app_version"));
+        assert!(debugdata.variables.contains_key("calibration_id"));
+        assert!(debugdata.variables.contains_key("runtime_version"));
+        assert_eq!(debugdata.skipped_units.len(), 3);
+        assert!(debugdata.skipped_units[0].contains("offset 0x0 "));
+        assert!(debugdata.skipped_units[1].contains("offset 0xe "));
+        assert!(debugdata.skipped_units[2].contains("offset 0x145 "));
+        // the names of the units remain aligned with the units that were read
+        let unit_idx = debugdata.variables["app_version"][0].unit_idx;
+        assert_eq!(debugdata.unit_names.len(), 2);
+        assert!(debugdata.unit_names[unit_idx].is_some());
+
+        // valid debug info has no skipped units
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/epk_test.elf"), true).unwrap();
+        assert!(debugdata.skipped_units.is_empty());
+    }
+
+    #[test]
+    fn test_load_anonymous_typedefs() {
+        // "typedef struct {...} Name;" - the anonymous struct, union or enum is named after the typedef
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/anonymous_typedef_test.elf"), true)
+                .unwrap();
+        let aggregate_types: Vec<&TypeInfo> = debugdata
+            .types
+            .iter()
+            .map(|(_, typeinfo)| typeinfo)
+            .filter(|typeinfo| {
+                matches!(
+                    typeinfo.datatype,
+                    DbgDataType::Struct { .. }
+                        | DbgDataType::Union { .. }
+                        | DbgDataType::Enum { .. }
+                )
+            })
+            .collect();
+        assert!(!aggregate_types.is_empty());
+        assert!(aggregate_types
+            .iter()
+            .all(|typeinfo| typeinfo.name.is_some()));
+        // both the typedef and the anonymous type can be found by name
+        for name in ["Anon_Struct", "Anon_Union", "Anon_Enum", "Anon_Outer"] {
+            assert_eq!(debugdata.typenames()[name].len(), 2);
+        }
+
+        let varinfo = &debugdata.variables["Anon_Outer_Var"][0];
+        let members = debugdata.types[&varinfo.typeref].get_members().unwrap();
+        let inner_type = members["inner"].0.get_arraytype().unwrap();
+        assert_eq!(inner_type.name.as_deref(), Some("Anon_Struct"));
+    }
+
+    #[test]
+    fn test_load_const_values() {
+        // the static const variables are optimized away and only have a DW_AT_const_value
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/const_value_test.elf"), true).unwrap();
+        assert!(debugdata.variables.contains_key("Output_Value"));
+        assert!(!debugdata.constants.contains_key("Output_Value"));
+        assert!(!debugdata.variables.contains_key("Default_Offset"));
+        assert_eq!(
+            debugdata.get_const_value_text("Default_Offset").as_deref(),
+            Some("-300")
+        );
+        assert_eq!(
+            debugdata.get_const_value_text("Default_Timeout").as_deref(),
+            Some("5000")
+        );
+        assert_eq!(
+            debugdata.get_const_value_text("Default_Gain").as_deref(),
+            Some("1.5")
+        );
+        assert_eq!(
+            debugdata.get_const_value_text("Default_Scale").as_deref(),
+            Some("0.25")
+        );
+
+        // looking up a constant reports that it has no storage
+        let errmsg = crate::symbol::find_symbol("Default_Offset", &debugdata)
+            .err()
+            .unwrap();
+        assert_eq!(
+            errmsg,
+            "symbol Default_Offset has no storage; it was optimized to a constant value -300"
+        );
+    }
+
+    #[test]
+    fn test_load_untyped_symbols() {
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/untyped_symbol_test.elf"), false)
+                .unwrap();
+        assert_eq!(
+            debugdata.variables["C_Value"][0].source,
+            VarSource::DebugInfo
+        );
+        for (name, size) in [
+            ("Asm_Byte", 1),
+            ("Asm_Word", 2),
+            ("Asm_Long", 4),
+            ("Asm_Buffer", 24),
+            ("Asm_Local", 6),
+        ] {
+            let varinfo = &debugdata.variables[name][0];
+            assert_eq!(varinfo.source, VarSource::SymbolTable);
+            assert_eq!(debugdata.types[&varinfo.typeref].get_size(), size);
+        }
+        let buffer_type = &debugdata.types[&debugdata.variables["Asm_Buffer"][0].typeref];
+        assert!(matches!(
+            &buffer_type.datatype,
+            DbgDataType::Array { size: 24, arraytype, .. }
+                if matches!(arraytype.datatype, DbgDataType::Uint8)
+        ));
+        // local symbols are used too, but not the ones outside of the data sections
+        assert_eq!(debugdata.variables["Asm_Local"][0].address, 0x4030);
+        assert!(!debugdata.variables.contains_key("__abi_tag"));
+        let symbol = crate::symbol::find_symbol("Asm_Local", &debugdata).unwrap();
+        assert_eq!(symbol.address, 0x4030);
+        assert!(symbol.is_type_inferred());
+
+        // Asm_Long contains the value 0x12345678
+        let address = debugdata.variables["Asm_Long"][0].address;
+        assert_eq!(
+            debugdata.memory.read_integer(address, 4, false),
+            Some(0x12345678)
+        );
+    }
+
+    #[test]
+    fn test_load_array_lower_bound() {
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/lower_bound_test.elf"), false).unwrap();
+
+        // Fortran arrays have the lower bound 1 if the debug info doesn't specify it
+        let symbol = crate::symbol::find_symbol("Fortran_Array", &debugdata).unwrap();
+        let DbgDataType::Array { dim, lbound, .. } = &symbol.typeinfo.datatype else {
+            panic!("Expected array type, got {:?}", symbol.typeinfo.datatype);
+        };
+        assert_eq!(dim, &vec![3]);
+        assert_eq!(lbound, &vec![1]);
+        assert!(crate::symbol::find_symbol("Fortran_Array._3_", &debugdata).is_err());
+
+        let symbol = crate::symbol::find_symbol("Fortran_Data.offsets", &debugdata).unwrap();
+        let DbgDataType::Array { dim, lbound, .. } = &symbol.typeinfo.datatype else {
+            panic!("Expected array type, got {:?}", symbol.typeinfo.datatype);
+        };
+        assert_eq!(dim, &vec![5]);
+        assert_eq!(lbound, &vec![-2]);
+
+        // the indices are zero-based, regardless of the declared lower bound
+        let base_address = debugdata.variables["Fortran_Data"][0].address;
+        let symbol = crate::symbol::find_symbol("Fortran_Data.values._1_", &debugdata).unwrap();
+        assert_eq!(symbol.address, base_address + 4);
+        assert_eq!(
+            debugdata.memory.read_integer(symbol.address, 4, false),
+            Some(2)
+        );
+        let symbol = crate::symbol::find_symbol("Fortran_Data.offsets._4_", &debugdata).unwrap();
+        assert_eq!(symbol.address, base_address + 16 + 8);
+        assert_eq!(
+            debugdata.memory.read_integer(symbol.address, 2, false),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_load_types_on_demand() {
+        let filename = OsStr::new("fixtures/bin/debugdata_gcc.elf");
+        let debugdata = DebugData::load_dwarf(filename, false).unwrap();
+        // only the types of the symbol table entries exist before any type is used
+        let initial_count = debugdata.types.len();
+        let sym_info = crate::symbol::find_symbol("staticvar", &debugdata).unwrap();
+        let partial_count = debugdata.types.len();
+        assert!(partial_count > initial_count);
+
+        // the type is the same as the one that is read when all types are loaded
+        let debugdata_full = DebugData::load_dwarf(filename, false).unwrap();
+        debugdata_full.types.load_all();
+        assert!(partial_count < debugdata_full.types.len());
+        let sym_info_full = crate::symbol::find_symbol("staticvar", &debugdata_full).unwrap();
+        assert_eq!(sym_info.address, sym_info_full.address);
+        assert!(sym_info
+            .typeinfo
+            .compare(sym_info_full.typeinfo, &debugdata_full.types));
+        assert_eq!(debugdata.typenames(), debugdata_full.typenames());
+    }
+
+    #[test]
+    fn test_variable_sections() {
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/update_test.elf"), true).unwrap();
+        let char_var = &debugdata.variables["Characteristic_Value"][0];
+        assert_eq!(char_var.section.as_deref(), Some(".data"));
+        let meas_var = &debugdata.variables["Measurement_Value"][0];
+        assert_eq!(meas_var.section.as_deref(), Some(".bss"));
+    }
+
+    #[test]
+    fn test_memory_image() {
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/update_test.elf"), false).unwrap();
+        // uint32_t Characteristic_Value = 3; is located in .data
+        let char_var = &debugdata.variables["Characteristic_Value"][0];
+        let value = debugdata.memory.read_integer(char_var.address, 4, false);
+        assert_eq!(value, Some(3));
+        // Measurement_Value is located in .bss, which has no content in the elf file
+        let meas_var = &debugdata.variables["Measurement_Value"][0];
+        assert!(debugdata.memory.read(meas_var.address, 2).is_none());
+    }
+
+    #[test]
+    fn test_load_deadline() {
+        // The deadline is set just before the DIE walk starts, and the time limit is raised until
+        // the walk is aborted after some of the units were read. This proves that the walk can be
+        // cancelled part way through, and not only before it has started.
+        let filename = OsStr::new("fixtures/bin/debugdata_gcc.exe");
+        let filedata = load_filedata(filename).unwrap();
+        let elffile = load_elf_file(&filename.to_string_lossy(), &filedata).unwrap();
+        let named_types = HashSet::new();
+
+        let mut reader = create_reader(filename, &elffile, false, None, &named_types).unwrap();
+        let start = Instant::now();
+        reader.load_variables().unwrap();
+        let full_duration = start.elapsed();
+        let unit_count = reader.units.list.len();
+        assert!(unit_count > 1);
+
+        let mut aborted_mid_walk = false;
+        // the walk might be slower than the first time, so the time limit can exceed full_duration
+        for step in 1..256 {
+            let mut reader = create_reader(filename, &elffile, false, None, &named_types).unwrap();
+            reader.deadline = Some(Instant::now() + full_duration * step / 64);
+            let Err(errmsg) = reader.load_variables() else {
+                // the deadline was reached after the whole walk was done
+                break;
+            };
+            assert!(errmsg.contains("time limit"));
+            // the unit that was being read when the deadline was reached is also in the list
+            if reader.units.list.len() > 1 && reader.units.list.len() < unit_count {
+                aborted_mid_walk = true;
+                break;
+            }
+        }
+        assert!(aborted_mid_walk);
+    }
+
     #[test]
     fn test_load_mingw_exe() {
         // The file fixtures/bin/update_test.c was compiled with mingw64 gcc
@@ -1,5 +1,5 @@
 use super::{attributes::*, DebugDataReader};
-use super::{DbgDataType, TypeInfo, VarInfo};
+use super::{DbgDataType, TypeInfo, TypeMap};
 use gimli::{DebugInfoOffset, DwTag, EndianSlice, EntriesTreeNode, RunTimeEndian, UnitOffset};
 use indexmap::IndexMap;
 use object::Endianness;
@@ -12,41 +12,35 @@ struct WipItemInfo {
     tag: DwTag,
 }
 
-struct TypeReaderData {
+struct TypeReaderData<'a> {
     types: HashMap<usize, TypeInfo>,
     typenames: HashMap<String, Vec<usize>>,
     wip_items: Vec<WipItemInfo>,
+    // the types that were read previously, which don't need to be read again
+    cache: &'a TypeMap,
 }
 
 impl DebugDataReader<'_> {
-    // load all the types referenced by variables in given HashMap
-    pub(crate) fn load_types(
-        &mut self,
-        variables: &IndexMap<String, Vec<VarInfo>>,
+    // load one type, and all the types that it references unless they are already in the cache
+    // The newly loaded types and their names are returned, so that they can be added to the cache.
+    pub(crate) fn load_type(
+        &self,
+        typeref: usize,
+        cache: &TypeMap,
     ) -> (HashMap<usize, TypeInfo>, HashMap<String, Vec<usize>>) {
         let mut typereader_data = TypeReaderData {
             types: HashMap::<usize, TypeInfo>::new(),
             typenames: HashMap::<String, Vec<usize>>::new(),
             wip_items: Vec::new(),
+            cache,
         };
-        // for each variable
-        for (name, var_list) in variables {
-            for VarInfo { typeref, .. } in var_list {
-                // check if the type was already loaded
-                if !typereader_data.types.contains_key(typeref) {
-                    if let Some(unit_idx) = self.units.get_unit(*typeref) {
-                        // create an entries_tree iterator that makes it possible to read the DIEs of this type
-                        let dbginfo_offset = gimli::DebugInfoOffset(*typeref);
-
-                        // load one type and add it to the collection (always succeeds for correctly structured DWARF debug info)
-                        let result = self.get_type(unit_idx, dbginfo_offset, &mut typereader_data);
-                        if let Err(errmsg) = result {
-                            if self.verbose {
-                                println!("Error loading type info for variable {name}: {errmsg}");
-                            }
-                        }
-                        typereader_data.wip_items.clear();
-                    }
+        if let Some(unit_idx) = self.units.get_unit(typeref) {
+            // load the type and add it to the collection (always succeeds for correctly structured DWARF debug info)
+            let dbginfo_offset = gimli::DebugInfoOffset(typeref);
+            let result = self.get_type(unit_idx, dbginfo_offset, &mut typereader_data);
+            if let Err(errmsg) = result {
+                if self.verbose {
+                    log_println!("Error loading type info @0x{typeref:X}: {errmsg}");
                 }
             }
         }
@@ -65,13 +59,20 @@ impl DebugDataReader<'_> {
             Ok(typeinfo) => Ok(typeinfo),
             Err(errmsg) => {
                 // try to print a readable error message
-                println!("Failed to read type: {errmsg}");
+                log_println!("Failed to read type: {errmsg}");
                 for (idx, wip) in typereader_data.wip_items.iter().enumerate() {
-                    print!("  {:indent$}{}", "", wip.tag, indent = idx * 2);
-                    if let Some(name) = &wip.name {
-                        print!(" {name}");
-                    }
-                    println!(" @0x{:X}", wip.offset);
+                    let name = wip
+                        .name
+                        .as_ref()
+                        .map(|name| format!(" {name}"))
+                        .unwrap_or_default();
+                    log_println!(
+                        "  {:indent$}{}{name} @0x{:X}",
+                        "",
+                        wip.tag,
+                        wip.offset,
+                        indent = idx * 2
+                    );
                 }
 
                 // create a dummy typeinfo using DwarfDataType::Other, rather than propagate the error
@@ -86,6 +87,7 @@ impl DebugDataReader<'_> {
                         .and_then(|wip| wip.name.clone()),
                     unit_idx: current_unit,
                     dbginfo_offset: dbginfo_offset.0,
+                    big_endian: None,
                 };
                 typereader_data
                     .types
@@ -105,6 +107,9 @@ impl DebugDataReader<'_> {
         if let Some(t) = typereader_data.types.get(&dbginfo_offset.0) {
             return Ok(t.clone());
         }
+        if let Some(t) = typereader_data.cache.get_loaded(dbginfo_offset.0) {
+            return Ok(t.clone());
+        }
 
         let (unit, abbrev) = &self.units[current_unit];
         let offset = dbginfo_offset.to_unit_offset(unit).unwrap();
@@ -113,7 +118,10 @@ impl DebugDataReader<'_> {
             .map_err(|err| err.to_string())?;
         let entries_tree_node = entries_tree.root().map_err(|err| err.to_string())?;
         let entry = entries_tree_node.entry();
-        let typename = get_name_attribute(entry, &self.dwarf, unit).ok();
+        // some compilers emit an empty name for anonymous types instead of omitting the name
+        let typename = get_name_attribute(entry, &self.dwarf, unit)
+            .ok()
+            .filter(|name| !name.is_empty());
         let is_declaration = get_declaration_attribute(entry).unwrap_or(false);
 
         if is_declaration {
@@ -125,6 +133,7 @@ impl DebugDataReader<'_> {
                 name: typename,
                 unit_idx: current_unit,
                 dbginfo_offset: dbginfo_offset.0,
+                big_endian: None,
             });
         }
 
@@ -135,9 +144,12 @@ impl DebugDataReader<'_> {
             entry.tag(),
         ));
 
+        // GCC emits DW_AT_endianity for base types declared with scalar_storage_order
+        let mut big_endian = None;
         let (datatype, inner_name) = match entry.tag() {
             gimli::constants::DW_TAG_base_type => {
                 let (datatype, name) = get_base_type(entry, &self.units[current_unit].0);
+                big_endian = get_endianity_attribute(entry);
                 (datatype, Some(name))
             }
             gimli::constants::DW_TAG_pointer_type => {
@@ -218,6 +230,10 @@ impl DebugDataReader<'_> {
                 let (new_cur_unit, dbginfo_offset) =
                     get_type_attribute(entry, &self.units, current_unit)?;
                 let reftype = self.get_type(new_cur_unit, dbginfo_offset, typereader_data)?;
+                big_endian = reftype.big_endian;
+                if let Some(name) = &typename {
+                    typereader_data.name_anonymous_type(&reftype, name);
+                }
                 (reftype.datatype, None)
             }
             gimli::constants::DW_TAG_const_type
@@ -232,6 +248,7 @@ impl DebugDataReader<'_> {
                     get_type_attribute(entry, &self.units, current_unit)
                 {
                     let typeinfo = self.get_type(new_cur_unit, dbginfo_offset, typereader_data)?;
+                    big_endian = typeinfo.big_endian;
                     (typeinfo.datatype, typeinfo.name)
                 } else {
                     // const void* / volatile void* / packed void*???
@@ -249,7 +266,8 @@ impl DebugDataReader<'_> {
                 )
             }
             gimli::constants::DW_TAG_unspecified_type => {
-                // ?
+                // e.g. decltype(nullptr) in C++; some compilers also describe void this way
+                // Pointers to this type are opaque pointers, since the data they point to is unknown
                 (
                     DbgDataType::Other(get_byte_size_attribute(entry).unwrap_or(0)),
                     None,
@@ -269,6 +287,7 @@ impl DebugDataReader<'_> {
             name: display_name,
             unit_idx: current_unit,
             dbginfo_offset: dbginfo_offset.0,
+            big_endian,
         };
 
         if let Some(name) = typename {
@@ -320,24 +339,23 @@ impl DebugDataReader<'_> {
 
         // get the array dimensions
         let mut dim = Vec::<u64>::new();
+        let mut lbound = Vec::<i64>::new();
         let mut iter = entries_tree_node.children();
         while let Ok(Some(child_node)) = iter.next() {
             let child_entry = child_node.entry();
             if child_entry.tag() == gimli::constants::DW_TAG_subrange_type {
+                // the lower bound is only given if it differs from the default lower bound of the language
+                let lower = get_lower_bound_attribute(child_entry)
+                    .unwrap_or_else(|| self.get_default_lower_bound(current_unit));
                 let count = if let Some(ubound) = get_upper_bound_attribute(child_entry) {
-                    let lbound = get_lower_bound_attribute(child_entry).unwrap_or(0);
-                    // compilers may use the bit pattern FFF.. to mean that the array size is unknown
-                    // this can happen when a pointer to an array is declared
-                    if ubound != u64::from(u32::MAX) && ubound != u64::MAX {
-                        ubound - lbound + 1
-                    } else {
-                        0
-                    }
+                    // the upper bound can be negative if the lower bound is negative, e.g. in Ada
+                    ubound.saturating_sub(lower).saturating_add(1).max(0) as u64
                 } else {
                     // clang generates DW_AT_count instead of DW_AT_ubound
                     get_count_attribute(child_entry).unwrap_or_default()
                 };
                 dim.push(count);
+                lbound.push(lower);
             } else if child_entry.tag() == gimli::constants::DW_TAG_enumeration_type {
                 // the DWARF spec allows an array dimension to be given using an enumeration type
                 // presumably this could be created by languages other than C / C++
@@ -349,9 +367,19 @@ impl DebugDataReader<'_> {
                     }
                 }
                 dim.push(enum_count);
+                lbound.push(0);
             }
         }
 
+        // Elements are always addressed with zero-based indices, e.g. the first element of
+        // an array declared as (1:10) in Fortran is array._0_
+        if self.verbose && lbound.iter().any(|lower| *lower != 0) {
+            log_println!(
+                "Array type @0x{:X} has the lower bounds {lbound:?}; its elements are accessed with zero-based indices",
+                offset.0
+            );
+        }
+
         // try to fix the dimension of the array, if the DW_TAG_subrange_type didn't contain enough info
         if dim.len() == 1 && dim[0] == 0 && stride != 0 {
             if let Some(count) = maybe_size.map(|s: u64| s / stride) {
@@ -362,6 +390,7 @@ impl DebugDataReader<'_> {
         Ok((
             DbgDataType::Array {
                 dim,
+                lbound,
                 arraytype: Box::new(arraytype),
                 size,
                 stride,
@@ -370,6 +399,20 @@ impl DebugDataReader<'_> {
         ))
     }
 
+    // The DWARF spec defines the lower bound that applies when a subrange has no DW_AT_lower_bound.
+    // It is 0 for the C family of languages and 1 for Fortran, Ada, Pascal, etc.
+    fn get_default_lower_bound(&self, current_unit: usize) -> i64 {
+        let (unit, abbrev) = &self.units[current_unit];
+        let mut entries_cursor = unit.entries(abbrev);
+        let language = match entries_cursor.next_dfs() {
+            Ok(Some((_, entry))) => get_language_attribute(entry),
+            _ => None,
+        };
+        language
+            .and_then(|lang| lang.default_lower_bound())
+            .unwrap_or(0) as i64
+    }
+
     fn get_enumeration_type(
         &self,
         current_unit: usize,
@@ -520,6 +563,7 @@ impl DebugDataReader<'_> {
                                 name: membertype.name.clone(),
                                 unit_idx: membertype.unit_idx,
                                 dbginfo_offset,
+                                big_endian: membertype.big_endian,
                                 datatype: DbgDataType::Bitfield {
                                     basetype: Box::new(membertype),
                                     bit_size: bit_size as u16,
@@ -550,6 +594,7 @@ impl DebugDataReader<'_> {
                                 name: membertype.name.clone(),
                                 unit_idx: membertype.unit_idx,
                                 dbginfo_offset,
+                                big_endian: membertype.big_endian,
                                 datatype: DbgDataType::Bitfield {
                                     basetype: Box::new(membertype),
                                     bit_size: bit_size as u16,
@@ -674,9 +719,11 @@ fn get_base_type(
             8 => (DbgDataType::Sint64, "sint64".to_string()),
             _ => (DbgDataType::Other(byte_size), "double".to_string()),
         },
+        // DW_ATE_UTF is used for the C++ character types char8_t, char16_t and char32_t
         gimli::constants::DW_ATE_boolean
         | gimli::constants::DW_ATE_unsigned
-        | gimli::constants::DW_ATE_unsigned_char => match byte_size {
+        | gimli::constants::DW_ATE_unsigned_char
+        | gimli::constants::DW_ATE_UTF => match byte_size {
             1 => (DbgDataType::Uint8, "uint8".to_string()),
             2 => (DbgDataType::Uint16, "uint16".to_string()),
             4 => (DbgDataType::Uint32, "uint32".to_string()),
@@ -693,7 +740,7 @@ impl WipItemInfo {
     }
 }
 
-impl TypeReaderData {
+impl TypeReaderData<'_> {
     // get_pointer_name() is a solution for a really ugly edge case:
     // Data structures can reference themselves using pointers.
     // Since types are normally read recursively, this would case would result in an infinite loop.
@@ -718,4 +765,25 @@ impl TypeReaderData {
         }
         None
     }
+
+    // C code often declares structs as "typedef struct {...} Foo;", where the struct itself has no name.
+    // The anonymous struct, union or enum is named after the typedef, so that it can be referred to by name
+    // elsewhere, e.g. as the name of the TYPEDEF_STRUCTURE that is created for it.
+    fn name_anonymous_type(&mut self, reftype: &TypeInfo, name: &str) {
+        if !matches!(
+            reftype.datatype,
+            DbgDataType::Struct { .. } | DbgDataType::Union { .. } | DbgDataType::Enum { .. }
+        ) {
+            return;
+        }
+        if let Some(anon_type) = self.types.get_mut(&reftype.dbginfo_offset) {
+            if anon_type.name.is_none() {
+                anon_type.name = Some(name.to_string());
+                self.typenames
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(reftype.dbginfo_offset);
+            }
+        }
+    }
 }
@@ -1,10 +1,9 @@
-use crate::debuginfo::{DbgDataType, DebugData, TypeInfo, VarInfo};
+use crate::debuginfo::{DbgDataType, DebugData, TypeInfo, TypeMap, VarInfo};
 use crate::symbol::SymbolInfo;
-use std::collections::HashMap;
 use std::fmt::Write;
 
 pub(crate) struct TypeInfoIter<'dbg> {
-    types: &'dbg HashMap<usize, TypeInfo>,
+    types: &'dbg TypeMap,
     type_stack: Vec<&'dbg TypeInfo>,
     position_stack: Vec<usize>,
     offset_stack: Vec<u64>,
@@ -19,6 +18,10 @@ pub(crate) struct VariablesIterator<'dbg> {
     position: usize,
     type_iter: Option<TypeInfoIter<'dbg>>,
     use_new_arrays: bool,
+    // the peripherals of an SVD file, which are returned after the variables of the debug info
+    svd_data: Option<&'dbg DebugData>,
+    // while the peripherals are returned, this is the debug info whose variables hide peripherals with the same name
+    shadowing: Option<&'dbg DebugData>,
 }
 
 impl<'dbg> Iterator for TypeInfoIter<'dbg> {
@@ -36,7 +39,7 @@ impl<'dbg> Iterator for TypeInfoIter<'dbg> {
 
 impl<'dbg> TypeInfoIter<'dbg> {
     pub(crate) fn new(
-        types: &'dbg HashMap<usize, TypeInfo>,
+        types: &'dbg TypeMap,
         typeinfo: &'dbg TypeInfo,
         use_new_arrays: bool,
     ) -> Self {
@@ -81,6 +84,7 @@ impl<'dbg> TypeInfoIter<'dbg> {
                 dim,
                 stride,
                 arraytype,
+                ..
             } => {
                 let total_elemcount = size / stride;
                 let depth = self.type_stack.len() - 1;
@@ -152,7 +156,7 @@ impl<'dbg> Iterator for VariablesIterator<'dbg> {
         if let Some((varname, list)) = self.current_var {
             if self.position < list.len() {
                 let varinfo = &list[self.position];
-                let is_unique = list.len() == 1;
+                let is_unique = list.len() == 1 || self.debugdata.is_unique_variable(list);
 
                 if self.type_iter.is_none() {
                     // newly set current_var, should be returned before using type_iter to return its sub-elements
@@ -171,8 +175,10 @@ impl<'dbg> Iterator for VariablesIterator<'dbg> {
                         address: varinfo.address,
                         typeinfo,
                         unit_idx: varinfo.unit_idx,
+                        source: varinfo.source,
                         function_name: &varinfo.function,
                         namespaces: &varinfo.namespaces,
+                        section: &varinfo.section,
                         is_unique,
                     })
                 } else if let Some((var_component_name, typeinfo, offset)) =
@@ -183,23 +189,39 @@ impl<'dbg> Iterator for VariablesIterator<'dbg> {
                         address: varinfo.address + offset,
                         typeinfo,
                         unit_idx: varinfo.unit_idx,
+                        source: varinfo.source,
                         function_name: &varinfo.function,
                         namespaces: &varinfo.namespaces,
+                        section: &varinfo.section,
                         is_unique,
                     })
                 } else {
                     // reached the end of this type_iter, try to advance to the next position within the list
-                    self.position += 1;
+                    // if all entries in the list refer to the same object, then it is only returned once
+                    if is_unique {
+                        self.position = list.len();
+                    } else {
+                        self.position += 1;
+                    }
                     self.type_iter = None;
                     self.next()
                 }
             } else {
                 // reached the end of this var list, try to advance var_iter to get a new current_var
-                self.current_var = self.var_iter.next();
+                self.current_var = self.next_var();
                 self.position = 0;
                 self.type_iter = None;
                 self.next()
             }
+        } else if let Some(svd_data) = self.svd_data.take() {
+            // all variables of the debug info have been returned, continue with the peripherals of the SVD file
+            self.shadowing = Some(self.debugdata);
+            self.debugdata = svd_data;
+            self.var_iter = svd_data.variables.iter();
+            self.current_var = self.next_var();
+            self.position = 0;
+            self.type_iter = None;
+            self.next()
         } else {
             // current_var is None -> reached the end
             None
@@ -213,6 +235,7 @@ impl<'dbg> VariablesIterator<'dbg> {
         unit_idx: usize::MAX,
         datatype: DbgDataType::Sint16,
         dbginfo_offset: 0,
+        big_endian: None,
     };
 
     pub(crate) fn new(debugdata: &'dbg DebugData, use_new_arrays: bool) -> Self {
@@ -226,9 +249,18 @@ impl<'dbg> VariablesIterator<'dbg> {
             position: 0,
             type_iter: None,
             use_new_arrays,
+            svd_data: debugdata.svd.as_ref().map(|svd| &svd.peripherals),
+            shadowing: None,
         }
     }
 
+    fn next_var(&mut self) -> Option<(&'dbg String, &'dbg Vec<VarInfo>)> {
+        let shadowing = self.shadowing;
+        self.var_iter.by_ref().find(|(varname, _)| {
+            !shadowing.is_some_and(|debugdata| debugdata.variables.contains_key(*varname))
+        })
+    }
+
     pub(crate) fn next_sibling(&mut self) -> Option<SymbolInfo<'dbg>> {
         if let Some(type_iter) = &mut self.type_iter {
             type_iter.up();
@@ -243,6 +275,7 @@ impl<'dbg> VariablesIterator<'dbg> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::debuginfo::VarSource;
     use indexmap::IndexMap;
 
     const DEFAULT_TYPEINFO: TypeInfo = TypeInfo {
@@ -250,6 +283,7 @@ mod test {
         unit_idx: usize::MAX,
         datatype: DbgDataType::Sint16,
         dbginfo_offset: 0,
+        big_endian: None,
     };
 
     #[test]
@@ -260,13 +294,13 @@ mod test {
             datatype: DbgDataType::Sint16,
             ..DEFAULT_TYPEINFO.clone()
         };
-        let types = HashMap::new();
+        let types = TypeMap::default();
         let mut iter = TypeInfoIter::new(&types, &typeinfo, false);
         let result = iter.next();
         assert!(result.is_none());
 
         // a struct iterates over all of its members
-        let mut types = HashMap::new();
+        let mut types = TypeMap::default();
         let t_uint64 = TypeInfo {
             datatype: DbgDataType::Uint64,
             ..DEFAULT_TYPEINFO.clone()
@@ -332,8 +366,10 @@ mod test {
                 address: 1,
                 typeref: 0,
                 unit_idx: 0,
+                source: VarSource::DebugInfo,
                 function: None,
                 namespaces: vec![],
+                section: None,
             }],
         );
         variables.insert(
@@ -342,8 +378,10 @@ mod test {
                 address: 2,
                 typeref: 0,
                 unit_idx: 0,
+                source: VarSource::DebugInfo,
                 function: None,
                 namespaces: vec![],
+                section: None,
             }],
         );
         variables.insert(
@@ -353,15 +391,19 @@ mod test {
                     address: 3,
                     typeref: 1,
                     unit_idx: 0,
+                    source: VarSource::DebugInfo,
                     function: None,
                     namespaces: vec![],
+                    section: None,
                 },
                 VarInfo {
                     address: 33,
                     typeref: 1,
                     unit_idx: 1,
+                    source: VarSource::DebugInfo,
                     function: None,
                     namespaces: vec![],
+                    section: None,
                 },
             ],
         );
@@ -371,12 +413,14 @@ mod test {
                 address: 4,
                 typeref: 404, // some number with no correspondence in the types hash map
                 unit_idx: 0,
+                source: VarSource::DebugInfo,
                 function: None,
                 namespaces: vec![],
+                section: None,
             }],
         );
 
-        let mut types = HashMap::<usize, TypeInfo>::new();
+        let mut types = TypeMap::default();
         let t_uint8 = TypeInfo {
             datatype: DbgDataType::Uint8,
             ..DEFAULT_TYPEINFO.clone()
@@ -392,14 +436,11 @@ mod test {
             ..DEFAULT_TYPEINFO.clone()
         };
         types.insert(1, structtype);
-        let demangled_names = HashMap::new();
         let debugdata = DebugData {
             variables,
             types,
-            typenames: HashMap::new(),
-            demangled_names,
             unit_names: vec![Some("file_a.c".to_string()), Some("file_b.c".to_string())],
-            sections: HashMap::new(),
+            ..Default::default()
         };
 
         // test iter.next_sibling()
@@ -418,4 +459,45 @@ mod test {
         }
         assert_eq!(count, 5);
     }
+
+    #[test]
+    fn test_variables_iter_svd() {
+        let make_varinfo = |address| {
+            vec![VarInfo {
+                address,
+                typeref: 0,
+                unit_idx: usize::MAX,
+                source: VarSource::DebugInfo,
+                function: None,
+                namespaces: vec![],
+                section: None,
+            }]
+        };
+        let mut peripherals = DebugData::default();
+        peripherals.types.insert(0, DEFAULT_TYPEINFO.clone());
+        peripherals
+            .variables
+            .insert("UART0".to_string(), make_varinfo(0x4000));
+        peripherals
+            .variables
+            .insert("GPIO".to_string(), make_varinfo(0x5000));
+        let mut debugdata = DebugData::default();
+        debugdata.types.insert(0, DEFAULT_TYPEINFO.clone());
+        debugdata
+            .variables
+            .insert("GPIO".to_string(), make_varinfo(0x2000));
+        debugdata.svd = Some(Box::new(crate::debuginfo::SvdData {
+            filename: "test.svd".to_string(),
+            peripherals,
+        }));
+
+        // the peripherals follow the variables, and GPIO of the debug info hides the peripheral GPIO
+        let symbols: Vec<(String, u64)> = VariablesIterator::new(&debugdata, false)
+            .map(|sym_info| (sym_info.name, sym_info.address))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![("GPIO".to_string(), 0x2000), ("UART0".to_string(), 0x4000)]
+        );
+    }
 }
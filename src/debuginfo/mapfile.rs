@@ -0,0 +1,333 @@
+use super::{DbgDataType, DebugData, MapFileData, TypeInfo};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct MapSymbol {
+    pub(crate) address: u64,
+    pub(crate) size: u64,
+}
+
+/// Load the data symbols from a linker map file as a separate source of symbols.
+/// The map file symbols are only used if a name is not found in the debug info.
+/// Returns the number of symbols that were loaded.
+pub(crate) fn load_mapfile(debug_data: &mut DebugData, filename: &OsStr) -> Result<usize, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the map file {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    let symbols = parse_mapfile(&text).map_err(|err| {
+        format!(
+            "Failed to load the map file {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+
+    let count = symbols.len();
+    debug_data.mapfile = Some(Box::new(MapFileData::new(
+        filename.to_string_lossy().to_string(),
+        symbols,
+    )));
+    Ok(count)
+}
+
+fn parse_mapfile(text: &str) -> Result<HashMap<String, MapSymbol>, String> {
+    if text.contains("Image Symbol Table") {
+        Ok(parse_armlink_map(text))
+    } else if text.contains("Linker script and memory map") {
+        Ok(parse_gnu_map(text))
+    } else {
+        Err(
+            "unknown format; only map files created by GNU ld and armlink are supported"
+                .to_string(),
+        )
+    }
+}
+
+// Map files created by GNU ld list the symbols below their input sections:
+//  .bss.lib_counter
+//                 0x0000000000004034        0x4 lib.o
+//                 0x0000000000004034                lib_counter
+// The size of a symbol is not given. It is calculated from the address of the next symbol
+// in the same input section, or from the end of the input section.
+fn parse_gnu_map(text: &str) -> HashMap<String, MapSymbol> {
+    let mut symbols = SymbolCollector::default();
+    // the name of an input section is printed on a line of its own if it is too long
+    let mut pending_section_name: Option<&str> = None;
+    // name, start and end address of the current input section
+    let mut current_section: Option<(&str, u64, u64)> = None;
+    let mut section_symbols: Vec<(&str, u64)> = Vec::new();
+
+    let memory_map = text
+        .split_once("Linker script and memory map")
+        .map_or("", |(_, memory_map)| memory_map);
+    for line in memory_map.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+
+        let section_info = if !line.starts_with(' ') {
+            // output section: the symbols of the previous input section are complete
+            Some(None)
+        } else if !line.starts_with("  ") {
+            // input section, e.g. " .bss  0x4030  0x8 file.o", or only " .bss.lib_counter"
+            if tokens.len() == 1 {
+                pending_section_name = Some(tokens[0]);
+                Some(None)
+            } else {
+                pending_section_name = None;
+                parse_hex_pair(&tokens[1..]).map(|range| Some((tokens[0], range)))
+            }
+        } else if let Some(name) = pending_section_name.take() {
+            // continuation line of an input section with a long name
+            parse_hex_pair(&tokens).map(|range| Some((name, range)))
+        } else {
+            None
+        };
+
+        if let Some(section_info) = section_info {
+            if let Some((_, _, end)) = current_section {
+                symbols.add_section_symbols(&mut section_symbols, end);
+            }
+            current_section = section_info.map(|(name, (start, size))| (name, start, start + size));
+        } else if let Some((section_name, start, end)) = current_section {
+            // symbol line, e.g. "    0x4034    lib_counter"; assignments like ". = ALIGN(8)" are ignored
+            if tokens.len() == 2 && !section_name.starts_with(".text") {
+                if let Some(address) = parse_hex(tokens[0]) {
+                    if (start..end).contains(&address) && is_symbol_name(tokens[1]) {
+                        section_symbols.push((tokens[1], address));
+                    }
+                }
+            }
+        }
+    }
+    if let Some((_, _, end)) = current_section {
+        symbols.add_section_symbols(&mut section_symbols, end);
+    }
+
+    symbols.finish()
+}
+
+// Map files created by armlink contain an image symbol table:
+//     Symbol Name          Value     Ov Type        Size  Object(Section)
+//     lib_counter          0x20000014   Data           4  lib.o(.bss.lib_counter)
+// Only the symbols of type "Data" with a non-zero size are used.
+fn parse_armlink_map(text: &str) -> HashMap<String, MapSymbol> {
+    let mut symbols = SymbolCollector::default();
+
+    let symbol_table = text
+        .split_once("Image Symbol Table")
+        .map_or("", |(_, symbol_table)| symbol_table);
+    // the symbol table ends at the next separator line
+    let symbol_table = symbol_table
+        .split_once("=====")
+        .map_or(symbol_table, |(symbol_table, _)| symbol_table);
+    for line in symbol_table.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        // the symbol name is followed by its address; the name itself never starts with "0x"
+        let Some(addr_pos) = tokens.iter().skip(1).position(|t| t.starts_with("0x")) else {
+            continue;
+        };
+        let addr_pos = addr_pos + 1;
+        let Some(address) = parse_hex(tokens[addr_pos]) else {
+            continue;
+        };
+        // the overlay column "Ov" is usually empty
+        let Some(type_pos) = tokens[addr_pos..].iter().position(|t| *t == "Data") else {
+            continue;
+        };
+        let type_pos = addr_pos + type_pos;
+        if type_pos > addr_pos + 2 {
+            continue;
+        }
+        let Some(size) = tokens.get(type_pos + 1).and_then(|t| t.parse::<u64>().ok()) else {
+            continue;
+        };
+        let name = tokens[..addr_pos].join(" ");
+        if size > 0 && is_symbol_name(&name) {
+            symbols.add(&name, MapSymbol { address, size });
+        }
+    }
+
+    symbols.finish()
+}
+
+// collects the symbols of a map file
+// Names that occur several times with different addresses (e.g. static variables in different files)
+// are ambiguous, and are dropped.
+#[derive(Default)]
+struct SymbolCollector {
+    symbols: HashMap<String, Option<MapSymbol>>,
+}
+
+impl SymbolCollector {
+    fn add(&mut self, name: &str, symbol: MapSymbol) {
+        self.symbols
+            .entry(name.to_string())
+            .and_modify(|entry| {
+                if entry.is_some_and(|existing| existing.address != symbol.address) {
+                    *entry = None;
+                }
+            })
+            .or_insert(Some(symbol));
+    }
+
+    // add all symbols of an input section; each symbol extends to the next symbol or to the end of the section
+    fn add_section_symbols(&mut self, section_symbols: &mut Vec<(&str, u64)>, section_end: u64) {
+        section_symbols.sort_by_key(|(_, address)| *address);
+        for (idx, (name, address)) in section_symbols.iter().enumerate() {
+            let next_address = section_symbols[idx + 1..]
+                .iter()
+                .map(|(_, next_address)| *next_address)
+                .find(|next_address| next_address > address)
+                .unwrap_or(section_end);
+            let size = next_address - address;
+            if size > 0 {
+                self.add(
+                    name,
+                    MapSymbol {
+                        address: *address,
+                        size,
+                    },
+                );
+            }
+        }
+        section_symbols.clear();
+    }
+
+    fn finish(self) -> HashMap<String, MapSymbol> {
+        self.symbols
+            .into_iter()
+            .filter_map(|(name, symbol)| symbol.map(|symbol| (name, symbol)))
+            .collect()
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.strip_prefix("0x")?, 16).ok()
+}
+
+// parse the address and size of an input section
+fn parse_hex_pair(tokens: &[&str]) -> Option<(u64, u64)> {
+    let address = parse_hex(tokens.first()?)?;
+    let size = parse_hex(tokens.get(1)?)?;
+    Some((address, size))
+}
+
+fn is_symbol_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.')
+}
+
+impl MapFileData {
+    fn new(filename: String, symbols: HashMap<String, MapSymbol>) -> Self {
+        // the data type of a map file symbol is unknown; all symbols of the same size share one placeholder type
+        let mut size_types = HashMap::new();
+        for symbol in symbols.values() {
+            size_types.entry(symbol.size).or_insert_with(|| TypeInfo {
+                name: None,
+                unit_idx: usize::MAX,
+                datatype: DbgDataType::Other(symbol.size),
+                dbginfo_offset: 0,
+                big_endian: None,
+            });
+        }
+        Self {
+            filename,
+            symbols,
+            size_types,
+        }
+    }
+
+    // get the address and the placeholder type of a map file symbol
+    pub(crate) fn get_symbol(&self, name: &str) -> Option<(u64, &TypeInfo)> {
+        let symbol = self.symbols.get(name)?;
+        Some((symbol.address, &self.size_types[&symbol.size]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_parse_gnu_map() {
+        let text = std::fs::read_to_string("fixtures/bin/mapfile_test_gnu.map").unwrap();
+        let symbols = parse_mapfile(&text).unwrap();
+        assert_eq!(
+            symbols["lib_counter"],
+            MapSymbol {
+                address: 0x4034,
+                size: 4
+            }
+        );
+        assert_eq!(
+            symbols["lib_table"],
+            MapSymbol {
+                address: 0x4020,
+                size: 16
+            }
+        );
+        assert_eq!(symbols["dbg_value"].address, 0x4010);
+        // functions and linker-defined symbols like _edata are not included
+        assert!(!symbols.contains_key("main"));
+        assert!(!symbols.contains_key("_edata"));
+    }
+
+    #[test]
+    fn test_parse_armlink_map() {
+        let text = std::fs::read_to_string("fixtures/bin/mapfile_test_armlink.map").unwrap();
+        let symbols = parse_mapfile(&text).unwrap();
+        assert_eq!(symbols.len(), 4);
+        assert_eq!(
+            symbols["lib_counter"],
+            MapSymbol {
+                address: 0x2000_0014,
+                size: 4
+            }
+        );
+        assert_eq!(
+            symbols["lib_table"],
+            MapSymbol {
+                address: 0x2000_0004,
+                size: 16
+            }
+        );
+        assert_eq!(symbols["lib_state"].size, 2);
+        assert!(!symbols.contains_key("main"));
+        assert!(!symbols.contains_key("__initial_sp"));
+
+        assert!(parse_mapfile("not a map file").is_err());
+    }
+
+    #[test]
+    fn test_load_mapfile() {
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/mapfile_test.elf"), false).unwrap();
+        let variable_count = debug_data.variables.len();
+        let type_count = debug_data.types.len();
+
+        let count = load_mapfile(
+            &mut debug_data,
+            &OsString::from("fixtures/bin/mapfile_test_armlink.map"),
+        )
+        .unwrap();
+        assert_eq!(count, 4);
+        // the map file symbols are kept apart from the variables and types of the debug info
+        assert_eq!(debug_data.variables.len(), variable_count);
+        assert_eq!(debug_data.types.len(), type_count);
+        let mapfile = debug_data.mapfile.as_ref().unwrap();
+        let (address, typeinfo) = mapfile.get_symbol("lib_state").unwrap();
+        assert_eq!(address, 0x2000_0018);
+        assert!(matches!(typeinfo.datatype, DbgDataType::Other(2)));
+        assert!(mapfile.get_symbol("main").is_none());
+    }
+}
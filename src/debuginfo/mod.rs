@@ -1,19 +1,41 @@
 use indexmap::IndexMap;
-use std::collections::HashMap;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt::Display;
+use std::ops::Index;
+use std::time::Instant;
 
 mod dwarf;
 pub(crate) mod iter;
+mod mapfile;
 mod pdb;
+mod svd;
+mod verify;
+
+// where the information about a variable comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VarSource {
+    // a compile unit of the debug info
+    DebugInfo,
+    // a linker map file, which only provides the address and the size
+    MapFile,
+    // the ELF symbol table; the data type is inferred from the size of the symbol
+    SymbolTable,
+    // a peripheral of an SVD file
+    Svd,
+}
 
 #[derive(Debug)]
 pub(crate) struct VarInfo {
     pub(crate) address: u64,
     pub(crate) typeref: usize,
+    // usize::MAX if the variable does not belong to a compile unit
     pub(crate) unit_idx: usize,
+    pub(crate) source: VarSource,
     pub(crate) function: Option<String>,
     pub(crate) namespaces: Vec<String>,
+    pub(crate) section: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +44,8 @@ pub(crate) struct TypeInfo {
     pub(crate) unit_idx: usize,
     pub(crate) datatype: DbgDataType,
     pub(crate) dbginfo_offset: usize,
+    // explicit byte order from DW_AT_endianity; None if the default byte order of the file applies
+    pub(crate) big_endian: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +87,8 @@ pub(crate) enum DbgDataType {
     Array {
         size: u64,
         dim: Vec<u64>,
+        // the declared lower bound of each dimension; element indices are always zero-based
+        lbound: Vec<i64>,
         stride: u64,
         arraytype: Box<TypeInfo>,
     },
@@ -71,29 +97,499 @@ pub(crate) enum DbgDataType {
     Other(u64),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub(crate) struct DebugData {
     pub(crate) variables: IndexMap<String, Vec<VarInfo>>,
-    pub(crate) types: HashMap<usize, TypeInfo>,
-    pub(crate) typenames: HashMap<String, Vec<usize>>,
+    pub(crate) types: TypeMap,
     pub(crate) demangled_names: HashMap<String, String>,
     pub(crate) unit_names: Vec<Option<String>>,
-    pub(crate) sections: HashMap<String, (u64, u64)>,
+    // multiple sections can have the same name, e.g. one .bss section for each memory bank
+    pub(crate) sections: HashMap<String, Vec<(u64, u64)>>,
+    // names of the sections that are not writable, e.g. flash memory
+    pub(crate) readonly_sections: HashSet<String>,
+    pub(crate) memory: MemoryImage,
+    pub(crate) binary: BinaryInfo,
+    // global variables without storage, which the compiler replaced by a constant value
+    pub(crate) constants: HashMap<String, ConstInfo>,
+    // the peripherals of an SVD file, which are only searched if a name is not found in the debug info
+    pub(crate) svd: Option<Box<SvdData>>,
+    // the symbols of a linker map file, which are only used if a name can't be found anywhere else
+    pub(crate) mapfile: Option<Box<MapFileData>>,
+    // compile units that could not be read because their debug info is corrupt
+    pub(crate) skipped_units: Vec<String>,
+}
+
+/// The data types of the debug info, indexed by their offset in the debug info.
+/// The types of an elf file are only read when they are used for the first time. Reading all of them
+/// up front needs a lot of memory for large files, while an a2l file usually only uses a few of them.
+/// Types that are loaded from a PDB or SVD file are inserted directly.
+#[derive(Default)]
+pub(crate) struct TypeMap {
+    // a slot is filled once its type has been read
+    slots: HashMap<usize, OnceCell<Box<TypeInfo>>>,
+    // the types of all variables and constants; reading them gives the same result as loading everything up front
+    roots: Vec<usize>,
+    resolver: Option<dwarf::TypeResolver>,
+    // types that could not be read are not retried
+    attempted: RefCell<HashSet<usize>>,
+    // the names of the types that were read so far
+    partial_typenames: RefCell<HashMap<String, Vec<usize>>>,
+    typenames: OnceCell<HashMap<String, Vec<usize>>>,
+}
+
+/// The peripherals of a CMSIS-SVD file. They are kept apart from the variables of the debug info,
+/// so that loading an SVD file does not change how the symbols of the elf or pdb file are resolved.
+#[derive(Debug)]
+pub(crate) struct SvdData {
+    pub(crate) filename: String,
+    pub(crate) peripherals: DebugData,
+}
+
+/// The data symbols of a linker map file. Only their address and size are known, so they are
+/// not mixed with the variables of the debug info.
+#[derive(Debug)]
+pub(crate) struct MapFileData {
+    pub(crate) filename: String,
+    pub(crate) symbols: HashMap<String, mapfile::MapSymbol>,
+    size_types: HashMap<u64, TypeInfo>,
+}
+
+/// A global variable that was optimized away. The debug info contains its value in a
+/// DW_AT_const_value attribute instead of an address.
+#[derive(Debug, Clone)]
+pub(crate) struct ConstInfo {
+    pub(crate) value: ConstValue,
+    pub(crate) typeref: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ConstValue {
+    Integer(i64),
+    // values that do not fit into an integer form, e.g. floating point values, are stored as bytes
+    Data(Vec<u8>),
+}
+
+/// The initialized data of the program, taken from the data sections of the elf file.
+/// PDB files do not contain any data, so the image is always empty in this case.
+#[derive(Default)]
+pub(crate) struct MemoryImage {
+    pub(crate) big_endian: bool,
+    pub(crate) segments: Vec<(u64, Vec<u8>)>,
+}
+
+/// Identifies the binary file that the debug info was loaded from.
+/// This makes it possible to tell whether an a2l file was updated from the expected build.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct BinaryInfo {
+    pub(crate) path: String,
+    // content of the .note.gnu.build-id section as a hex string; not all linkers create it
+    pub(crate) build_id: Option<String>,
+    // modification time of the file, in seconds since the unix epoch
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) architecture: Option<String>,
+    pub(crate) is_64bit: Option<bool>,
+}
+
+impl BinaryInfo {
+    pub(crate) fn new(filename: &OsStr) -> Self {
+        let timestamp = std::fs::metadata(filename)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs());
+        Self {
+            path: filename.to_string_lossy().to_string(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl Display for BinaryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(build_id) = &self.build_id {
+            write!(f, " build-id {build_id}")?;
+        }
+        if let Some(timestamp) = self.timestamp {
+            write!(f, " timestamp {timestamp}")?;
+        }
+        Ok(())
+    }
 }
 
 impl DebugData {
     // load the debug info from an elf file
+    #[cfg(test)]
     pub(crate) fn load_dwarf(filename: &OsStr, verbose: bool) -> Result<Self, String> {
-        dwarf::load_dwarf(filename, verbose)
+        dwarf::load_dwarf(filename, verbose, None, &HashSet::new())
+    }
+
+    // load the debug info from an elf file, but give up if it is not complete by the deadline
+    // The types in named_types are available by name even if there is no variable of that type.
+    pub(crate) fn load_dwarf_with_options(
+        filename: &OsStr,
+        verbose: bool,
+        deadline: Option<Instant>,
+        named_types: &HashSet<String>,
+    ) -> Result<Self, String> {
+        dwarf::load_dwarf(filename, verbose, deadline, named_types)
     }
 
     pub(crate) fn load_pdb(filename: &OsStr, verbose: bool) -> Result<Self, String> {
         pdb::load_pdb(filename, verbose)
     }
 
+    // load the data symbols from a linker map file as a fallback for names that are missing in the debug info
+    pub(crate) fn load_mapfile(&mut self, filename: &OsStr) -> Result<usize, String> {
+        mapfile::load_mapfile(self, filename)
+    }
+
+    // load the peripheral registers from a CMSIS-SVD file as a separate source of symbols
+    pub(crate) fn load_svd(&mut self, filename: &OsStr) -> Result<usize, String> {
+        svd::load_svd(self, filename)
+    }
+
+    // compare the addresses of the variables with the symbols of the binary file
+    pub(crate) fn verify_addresses(
+        &self,
+        filename: &OsStr,
+        log_msgs: &mut Vec<String>,
+    ) -> Result<usize, String> {
+        verify::verify_addresses(self, filename, log_msgs)
+    }
+
+    // get the offsets of all types with a given name
+    pub(crate) fn typenames(&self) -> &HashMap<String, Vec<usize>> {
+        self.types.typenames()
+    }
+
     pub(crate) fn iter(&self, use_new_arrays: bool) -> iter::VariablesIterator {
         iter::VariablesIterator::new(self, use_new_arrays)
     }
+
+    // get the value of a variable that was optimized to a constant, formatted according to its type
+    pub(crate) fn get_const_value_text(&self, name: &str) -> Option<String> {
+        let const_info = self.constants.get(name)?;
+        let datatype = self
+            .types
+            .get(&const_info.typeref)
+            .map(|typeinfo| &typeinfo.get_reference(&self.types).datatype);
+        let big_endian = self.memory.big_endian;
+        let text = match (&const_info.value, datatype) {
+            (ConstValue::Integer(value), Some(DbgDataType::Float)) => {
+                f32::from_bits(*value as u32).to_string()
+            }
+            (ConstValue::Integer(value), Some(DbgDataType::Double)) => {
+                f64::from_bits(*value as u64).to_string()
+            }
+            (ConstValue::Integer(value), Some(DbgDataType::Uint64)) => (*value as u64).to_string(),
+            (ConstValue::Integer(value), _) => value.to_string(),
+            (ConstValue::Data(data), Some(DbgDataType::Float)) if data.len() == 4 => {
+                let bytes: [u8; 4] = data.as_slice().try_into().ok()?;
+                if big_endian {
+                    f32::from_be_bytes(bytes).to_string()
+                } else {
+                    f32::from_le_bytes(bytes).to_string()
+                }
+            }
+            (ConstValue::Data(data), Some(DbgDataType::Double)) if data.len() == 8 => {
+                let bytes: [u8; 8] = data.as_slice().try_into().ok()?;
+                if big_endian {
+                    f64::from_be_bytes(bytes).to_string()
+                } else {
+                    f64::from_le_bytes(bytes).to_string()
+                }
+            }
+            (ConstValue::Data(data), _) => {
+                let hex: String = data.iter().map(|byte| format!("{byte:02X}")).collect();
+                format!("0x{hex} (raw bytes)")
+            }
+        };
+        Some(text)
+    }
+
+    /// check if all the entries for a variable name refer to the same object.
+    /// The same global variable can be present in many compile units, e.g. if it is
+    /// defined as an inline variable in a header. These entries all have the same address
+    /// and structurally identical types, so no discriminator is needed to tell them apart.
+    /// Entries with the same address and the same size are also treated as one object, even if
+    /// the compile units describe its type differently.
+    pub(crate) fn is_unique_variable(&self, varinfo_list: &[VarInfo]) -> bool {
+        let Some((first, others)) = varinfo_list.split_first() else {
+            return false;
+        };
+        let first_type = self.types.get(&first.typeref);
+        others.iter().all(|varinfo| {
+            varinfo.address == first.address
+                && (varinfo.typeref == first.typeref
+                    || match (first_type, self.types.get(&varinfo.typeref)) {
+                        (Some(type_1), Some(type_2)) => {
+                            type_1.compare(type_2, &self.types)
+                                || type_1.get_size() == type_2.get_size()
+                        }
+                        _ => false,
+                    })
+        })
+    }
+}
+
+impl TypeMap {
+    // create a map of types that are read by the resolver when they are used
+    // The offsets of all type entries must be known, since no slots can be added on demand.
+    fn new_lazy(offsets: Vec<usize>, roots: Vec<usize>, resolver: dwarf::TypeResolver) -> Self {
+        Self {
+            slots: offsets
+                .iter()
+                .chain(&roots)
+                .map(|offset| (*offset, OnceCell::new()))
+                .collect(),
+            roots,
+            resolver: Some(resolver),
+            ..Default::default()
+        }
+    }
+
+    // get a type, and read it from the debug info if this has not happened yet
+    pub(crate) fn get(&self, typeref: &usize) -> Option<&TypeInfo> {
+        let slot = self.slots.get(typeref)?;
+        if slot.get().is_none() && self.attempted.borrow_mut().insert(*typeref) {
+            if let Some(resolver) = &self.resolver {
+                resolver.read_type(*typeref, self);
+            }
+        }
+        slot.get().map(|typeinfo| &**typeinfo)
+    }
+
+    // get a type only if it has already been read
+    fn get_loaded(&self, typeref: usize) -> Option<&TypeInfo> {
+        self.slots.get(&typeref)?.get().map(|typeinfo| &**typeinfo)
+    }
+
+    // store the types that the resolver has read
+    fn store(&self, types: HashMap<usize, TypeInfo>, typenames: HashMap<String, Vec<usize>>) {
+        for (offset, typeinfo) in types {
+            if let Some(slot) = self.slots.get(&offset) {
+                // the resolver never reads a type twice, so the slot is always empty
+                let _ = slot.set(Box::new(typeinfo));
+            }
+        }
+        let mut partial_typenames = self.partial_typenames.borrow_mut();
+        for (name, offsets) in typenames {
+            partial_typenames.entry(name).or_default().extend(offsets);
+        }
+    }
+
+    // read the types of all variables, which is needed by everything that looks at all types at once
+    pub(crate) fn load_all(&self) {
+        for root in &self.roots {
+            self.get(root);
+        }
+    }
+
+    // the type names can only be complete once all types are read
+    pub(crate) fn typenames(&self) -> &HashMap<String, Vec<usize>> {
+        self.typenames.get_or_init(|| {
+            self.load_all();
+            // the order in which the types were read depends on how they were used, but the result must not
+            let mut typenames = self.partial_typenames.take();
+            for offsets in typenames.values_mut() {
+                offsets.sort_unstable();
+            }
+            typenames
+        })
+    }
+
+    pub(crate) fn typenames_mut(&mut self) -> &mut HashMap<String, Vec<usize>> {
+        self.typenames();
+        self.typenames.get_mut().unwrap()
+    }
+
+    pub(crate) fn contains_key(&self, typeref: &usize) -> bool {
+        self.slots.contains_key(typeref)
+    }
+
+    pub(crate) fn insert(&mut self, typeref: usize, typeinfo: TypeInfo) {
+        self.slots
+            .insert(typeref, OnceCell::from(Box::new(typeinfo)));
+    }
+
+    // the offsets of all types, including the ones that have not been read yet
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &usize> {
+        self.slots.keys()
+    }
+
+    // the number of types that have been read
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.slots
+            .values()
+            .filter(|slot| slot.get().is_some())
+            .count()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&usize, &TypeInfo)> {
+        self.load_all();
+        self.slots
+            .iter()
+            .filter_map(|(offset, slot)| slot.get().map(|typeinfo| (offset, &**typeinfo)))
+    }
+
+    pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut TypeInfo> {
+        self.load_all();
+        self.slots
+            .values_mut()
+            .filter_map(|slot| slot.get_mut().map(|typeinfo| &mut **typeinfo))
+    }
+}
+
+impl Index<&usize> for TypeMap {
+    type Output = TypeInfo;
+
+    fn index(&self, typeref: &usize) -> &TypeInfo {
+        self.get(typeref).expect("type not found")
+    }
+}
+
+// printing the types reads all of them
+impl std::fmt::Debug for TypeMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypeMap")
+            .field("types", &self.iter().collect::<HashMap<_, _>>())
+            .field("typenames", self.typenames())
+            .finish()
+    }
+}
+
+// only the address and length of each segment are shown; the data would be far too long
+impl std::fmt::Debug for MemoryImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let segments: Vec<(u64, usize)> = self
+            .segments
+            .iter()
+            .map(|(address, data)| (*address, data.len()))
+            .collect();
+        f.debug_struct("MemoryImage")
+            .field("big_endian", &self.big_endian)
+            .field("segments", &segments)
+            .finish()
+    }
+}
+
+impl MemoryImage {
+    // get the bytes at the given address, if the whole range is inside of one segment
+    pub(crate) fn read(&self, address: u64, size: u64) -> Option<&[u8]> {
+        self.segments.iter().find_map(|(start, data)| {
+            let offset = address.checked_sub(*start)?;
+            let end = offset.checked_add(size)?;
+            if end <= data.len() as u64 {
+                Some(&data[offset as usize..end as usize])
+            } else {
+                None
+            }
+        })
+    }
+
+    // read a NUL-terminated string from a char array with the given size
+    // The string ends at the first NUL byte, or at the end of the array if it contains no NUL byte.
+    pub(crate) fn read_c_string(&self, address: u64, size: u64) -> Option<&[u8]> {
+        let bytes = self.read(address, size)?;
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Some(&bytes[..len])
+    }
+
+    // read an integer with a size of up to 8 bytes. Signed values are sign-extended.
+    // The result is an i128, which can hold both i64 and u64 values.
+    pub(crate) fn read_integer(&self, address: u64, size: u64, signed: bool) -> Option<i128> {
+        if size == 0 || size > 8 {
+            return None;
+        }
+        let bytes = self.read(address, size)?;
+        let mut buffer = [0u8; 8];
+        let value = if self.big_endian {
+            buffer[8 - bytes.len()..].copy_from_slice(bytes);
+            u64::from_be_bytes(buffer)
+        } else {
+            buffer[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(buffer)
+        };
+        if signed {
+            let shift = 64 - 8 * size;
+            Some(i128::from(((value << shift) as i64) >> shift))
+        } else {
+            Some(i128::from(value))
+        }
+    }
+}
+
+/// find the name of the section that contains the given address
+/// Sections may overlap; in this case the smallest section containing the address is used, and
+/// sections of equal size are ordered by name so that the result is always the same.
+/// Sections of size zero never contain any address.
+pub(crate) fn find_section(
+    sections: &HashMap<String, Vec<(u64, u64)>>,
+    address: u64,
+) -> Option<String> {
+    sections
+        .iter()
+        .flat_map(|(name, ranges)| ranges.iter().map(move |range| (name, range)))
+        .filter(|(_, (start, end))| *start <= address && address < *end)
+        .min_by(|(name_a, (start_a, end_a)), (name_b, (start_b, end_b))| {
+            (end_a - start_a)
+                .cmp(&(end_b - start_b))
+                .then_with(|| name_a.cmp(name_b))
+        })
+        .map(|(name, _)| name.clone())
+}
+
+/// get the address ranges of all sections whose name matches the pattern.
+/// The pattern may contain the wildcards '*' (any sequence of characters) and '?' (any single character).
+/// The result is sorted by name and address.
+pub(crate) fn find_section_ranges<'a>(
+    sections: &'a HashMap<String, Vec<(u64, u64)>>,
+    pattern: &str,
+) -> Vec<(&'a str, u64, u64)> {
+    let mut result: Vec<(&str, u64, u64)> = sections
+        .iter()
+        .filter(|(name, _)| wildcard_match(pattern, name))
+        .flat_map(|(name, ranges)| {
+            ranges
+                .iter()
+                .map(move |(start, end)| (name.as_str(), *start, *end))
+        })
+        .collect();
+    result.sort_unstable();
+    result
+}
+
+// match a text against a pattern containing the wildcards '*' and '?'
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let mut pat_pos = 0;
+    let mut text_pos = 0;
+    // position of the last '*' in the pattern, and the text position where it was matched
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while text_pos < text.len() {
+        if pat_pos < pattern.len()
+            && (pattern[pat_pos] == '?' || pattern[pat_pos] == text[text_pos])
+        {
+            pat_pos += 1;
+            text_pos += 1;
+        } else if pat_pos < pattern.len() && pattern[pat_pos] == '*' {
+            backtrack = Some((pat_pos, text_pos));
+            pat_pos += 1;
+        } else if let Some((star_pos, star_text_pos)) = backtrack {
+            // let the last '*' consume one more character
+            backtrack = Some((star_pos, star_text_pos + 1));
+            pat_pos = star_pos + 1;
+            text_pos = star_text_pos + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[pat_pos..].iter().all(|c| *c == '*')
 }
 
 /// convert a full unit name, which might include a path, into a simple unit name
@@ -172,10 +668,7 @@ impl TypeInfo {
         }
     }
 
-    pub(crate) fn get_pointer<'a>(
-        &self,
-        types: &'a HashMap<usize, TypeInfo>,
-    ) -> Option<(u64, &'a TypeInfo)> {
+    pub(crate) fn get_pointer<'a>(&self, types: &'a TypeMap) -> Option<(u64, &'a TypeInfo)> {
         if let DbgDataType::Pointer(pt_size, pt_ref) = &self.datatype {
             let typeinfo = types.get(pt_ref)?;
             Some((*pt_size, typeinfo))
@@ -184,6 +677,27 @@ impl TypeInfo {
         }
     }
 
+    // get the target of a pointer, unless it is an opaque pointer
+    // MEASUREMENTs of opaque pointers use the pointer itself as the data type, since the target is unknown.
+    pub(crate) fn get_pointer_target<'a>(&'a self, types: &'a TypeMap) -> &'a TypeInfo {
+        if self.is_opaque_pointer(types) {
+            self
+        } else {
+            self.get_pointer(types).map_or(self, |(_, t)| t)
+        }
+    }
+
+    // true if the type is a pointer whose target is unknown: void*, const void*, a pointer to
+    // DW_TAG_unspecified_type, or a pointer to a struct that is only declared
+    pub(crate) fn is_opaque_pointer(&self, types: &TypeMap) -> bool {
+        match &self.datatype {
+            DbgDataType::Pointer(_, pt_ref) => types.get(pt_ref).is_none_or(|pt_type| {
+                matches!(pt_type.get_reference(types).datatype, DbgDataType::Other(_))
+            }),
+            _ => false,
+        }
+    }
+
     pub(crate) fn get_arraytype(&self) -> Option<&TypeInfo> {
         if let DbgDataType::Array { arraytype, .. } = &self.datatype {
             Some(arraytype)
@@ -192,7 +706,7 @@ impl TypeInfo {
         }
     }
 
-    pub(crate) fn get_reference<'a>(&'a self, types: &'a HashMap<usize, TypeInfo>) -> &'a Self {
+    pub(crate) fn get_reference<'a>(&'a self, types: &'a TypeMap) -> &'a Self {
         if let DbgDataType::TypeRef(dbginfo_offset, _) = &self.datatype {
             types.get(dbginfo_offset).unwrap_or(self)
         } else {
@@ -201,16 +715,11 @@ impl TypeInfo {
     }
 
     // not using PartialEq, because not all fields are considered for this comparison
-    pub(crate) fn compare(&self, other: &TypeInfo, types: &HashMap<usize, TypeInfo>) -> bool {
+    pub(crate) fn compare(&self, other: &TypeInfo, types: &TypeMap) -> bool {
         self.compare_internal(other, types, 0)
     }
 
-    fn compare_internal(
-        &self,
-        other: &TypeInfo,
-        types: &HashMap<usize, TypeInfo>,
-        depth: usize,
-    ) -> bool {
+    fn compare_internal(&self, other: &TypeInfo, types: &TypeMap, depth: usize) -> bool {
         let type_1 = self.get_reference(types);
         let type_2 = other.get_reference(types);
 
@@ -243,18 +752,21 @@ impl TypeInfo {
                         DbgDataType::Array {
                             size,
                             dim,
+                            lbound,
                             stride,
                             arraytype,
                         },
                         DbgDataType::Array {
                             size: size2,
                             dim: dim2,
+                            lbound: lbound2,
                             stride: stride2,
                             arraytype: arraytype2,
                         },
                     ) => {
                         size == size2
                             && dim == dim2
+                            && lbound == lbound2
                             && stride == stride2
                             && arraytype.compare_internal(arraytype2, types, depth + 1)
                     }
@@ -339,7 +851,7 @@ impl TypeInfo {
     fn compare_members(
         members1: &IndexMap<String, (TypeInfo, u64)>,
         members2: &IndexMap<String, (TypeInfo, u64)>,
-        types: &HashMap<usize, TypeInfo>,
+        types: &TypeMap,
         depth: usize,
     ) -> bool {
         if members1.len() != members2.len() {
@@ -425,4 +937,122 @@ impl Display for TypeInfo {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    fn make_varinfo(address: u64, typeref: usize, unit_idx: usize) -> VarInfo {
+        VarInfo {
+            address,
+            typeref,
+            unit_idx,
+            source: VarSource::DebugInfo,
+            function: None,
+            namespaces: vec![],
+            section: None,
+        }
+    }
+
+    #[test]
+    fn test_is_unique_variable() {
+        let mut debug_data = DebugData::default();
+        // two structurally identical types from different compile units, and one different type
+        for (dbginfo_offset, unit_idx, datatype) in [
+            (10, 0, DbgDataType::Uint32),
+            (20, 1, DbgDataType::Uint32),
+            (30, 1, DbgDataType::Uint16),
+            (40, 1, DbgDataType::Float),
+        ] {
+            debug_data.types.insert(
+                dbginfo_offset,
+                TypeInfo {
+                    name: None,
+                    unit_idx,
+                    datatype,
+                    dbginfo_offset,
+                    big_endian: None,
+                },
+            );
+        }
+
+        assert!(debug_data.is_unique_variable(&[make_varinfo(0x100, 10, 0)]));
+        // same address, identical type in different compile units
+        assert!(debug_data
+            .is_unique_variable(&[make_varinfo(0x100, 10, 0), make_varinfo(0x100, 20, 1)]));
+        // different addresses
+        assert!(!debug_data
+            .is_unique_variable(&[make_varinfo(0x100, 10, 0), make_varinfo(0x200, 20, 1)]));
+        // same address, but different types
+        assert!(!debug_data
+            .is_unique_variable(&[make_varinfo(0x100, 10, 0), make_varinfo(0x100, 30, 1)]));
+        // same address and same size, but the types are described differently
+        assert!(debug_data
+            .is_unique_variable(&[make_varinfo(0x100, 10, 0), make_varinfo(0x100, 40, 1)]));
+    }
+
+    #[test]
+    fn test_memory_image_read_integer() {
+        let mut memory = MemoryImage {
+            big_endian: false,
+            segments: vec![(0x100, vec![0xfe, 0xff, 0x34, 0x12])],
+        };
+        assert_eq!(memory.read_integer(0x100, 2, false), Some(0xfffe));
+        assert_eq!(memory.read_integer(0x100, 2, true), Some(-2));
+        assert_eq!(memory.read_integer(0x102, 2, false), Some(0x1234));
+        // the range must be completely inside of the segment
+        assert_eq!(memory.read_integer(0x102, 4, false), None);
+        assert_eq!(memory.read_integer(0xfe, 4, false), None);
+
+        memory.big_endian = true;
+        assert_eq!(memory.read_integer(0x102, 2, false), Some(0x3412));
+        assert_eq!(memory.read_integer(0x100, 4, false), Some(0xfeff3412));
+    }
+
+    #[test]
+    fn test_find_section() {
+        let mut sections = HashMap::new();
+        sections.insert(".data".to_string(), vec![(0x1000, 0x2000)]);
+        sections.insert(".sdata".to_string(), vec![(0x1800, 0x1900)]);
+        sections.insert(".sdata2".to_string(), vec![(0x1800, 0x1900)]);
+        sections.insert(".empty".to_string(), vec![(0x1000, 0x1000)]);
+        sections.insert(".bss".to_string(), vec![(0x3000, 0x3000), (0x4000, 0x4100)]);
+
+        assert_eq!(find_section(&sections, 0x1000), Some(".data".to_string()));
+        assert_eq!(find_section(&sections, 0x1fff), Some(".data".to_string()));
+        // overlapping sections: the smaller one wins, equal sizes are sorted by name
+        assert_eq!(find_section(&sections, 0x1880), Some(".sdata".to_string()));
+        // the end address is not part of the section
+        assert_eq!(find_section(&sections, 0x2000), None);
+        assert_eq!(find_section(&sections, 0x800), None);
+        // the second of two sections with the same name
+        assert_eq!(find_section(&sections, 0x4080), Some(".bss".to_string()));
+    }
+
+    #[test]
+    fn test_find_section_ranges() {
+        let mut sections = HashMap::new();
+        sections.insert(".data".to_string(), vec![(0x1000, 0x2000)]);
+        sections.insert(".bss".to_string(), vec![(0x5000, 0x5000), (0x4000, 0x4100)]);
+        sections.insert(".bss_bank1".to_string(), vec![(0x6000, 0x6200)]);
+
+        // all ranges of sections with the same name are returned
+        assert_eq!(
+            find_section_ranges(&sections, ".bss"),
+            vec![(".bss", 0x4000, 0x4100), (".bss", 0x5000, 0x5000)]
+        );
+        assert_eq!(
+            find_section_ranges(&sections, ".bss*"),
+            vec![
+                (".bss", 0x4000, 0x4100),
+                (".bss", 0x5000, 0x5000),
+                (".bss_bank1", 0x6000, 0x6200)
+            ]
+        );
+        assert_eq!(find_section_ranges(&sections, ".bss_bank?").len(), 1);
+        assert!(find_section_ranges(&sections, ".rodata").is_empty());
+
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "aXXbYbc"));
+        assert!(!wildcard_match("a*b", "aXXbc"));
+        assert!(!wildcard_match(".bss", ".bss_bank1"));
+    }
+}
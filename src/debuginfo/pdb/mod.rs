@@ -1,10 +1,17 @@
-use crate::debuginfo::{DbgDataType, DebugData, VarInfo};
+use crate::debuginfo::{
+    find_section, BinaryInfo, DbgDataType, DebugData, MemoryImage, VarInfo, VarSource,
+};
 use indexmap::IndexMap;
 use pdb2::{AddressMap, FallibleIterator, RawString, SymbolData, PDB};
-use std::{collections::HashMap, ffi::OsStr, fs::File, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs::File,
+    vec,
+};
 use typereader::TypeReaderData;
 
-use super::TypeInfo;
+use super::TypeMap;
 
 mod typereader;
 
@@ -43,53 +50,92 @@ pub(crate) fn load_pdb(filename: &OsStr, _verbose: bool) -> Result<DebugData, St
         }
     };
 
-    read_pdb(pdb).map_err(|pdberr| format!("PDB error: {pdberr:?}"))
+    let mut debug_data = read_pdb(pdb).map_err(|pdberr| match pdberr {
+        pdb2::Error::AddressMapNotFound => format!(
+            "PDB file {} describes a binary that was rearranged after linking, but it does not contain the OMAP data that is required to translate the addresses",
+            filename.to_string_lossy()
+        ),
+        _ => format!("PDB error: {pdberr:?}"),
+    })?;
+    debug_data.binary = BinaryInfo::new(filename);
+    Ok(debug_data)
 }
 
 fn read_pdb(mut pdb: PDB<'_, File>) -> Result<DebugData, pdb2::Error> {
-    let address_map = pdb.address_map().unwrap();
-    let global_variables = read_global_variables(&mut pdb, &address_map)?;
+    // The AddressMap translates the section-relative offsets of the symbols to RVAs. If the binary
+    // was rearranged after linking, the translation uses the OMAP tables.
+    let address_map = pdb.address_map()?;
+    let (sections, readonly_sections) = read_sections(&mut pdb)?;
+    let global_variables = read_global_variables(&mut pdb, &address_map, &sections)?;
     let ModuleVars {
         static_variables,
         unit_list,
-    } = read_static_variables(&mut pdb, &address_map)?;
+    } = read_static_variables(&mut pdb, &address_map, &sections)?;
     let mut variables = global_variables
         .into_iter()
         .chain(static_variables)
         .collect();
 
     let TypeReaderData {
-        types, typenames, ..
+        mut types,
+        typenames,
+        ..
     } = typereader::read_all_types(&mut pdb, &variables)?;
+    *types.typenames_mut() = typenames;
 
     filter_extern_variables(&mut variables, &types);
 
     // names in PDB debug info are not mangled, so we don't need to demangle them
     let demangled_names = HashMap::new();
 
-    let mut sections = HashMap::new();
-    if let Some(sections_list) = pdb.sections()? {
-        for section in sections_list {
-            let name = section.name().to_string();
-            let virt_addr = section.virtual_address as u64;
-            let length = section.virtual_size as u64;
-            sections.insert(name, (virt_addr, virt_addr + length));
-        }
-    }
-
     Ok(DebugData {
         variables,
         types,
-        typenames,
         demangled_names,
         unit_names: unit_list,
         sections,
+        readonly_sections,
+        memory: MemoryImage::default(),
+        binary: BinaryInfo::default(),
+        constants: HashMap::new(),
+        svd: None,
+        mapfile: None,
+        skipped_units: Vec::new(),
     })
 }
 
+// get the address ranges of all sections, and the names of the sections that are not writable
+#[allow(clippy::type_complexity)]
+fn read_sections(
+    pdb: &mut PDB<'_, File>,
+) -> Result<(HashMap<String, Vec<(u64, u64)>>, HashSet<String>), pdb2::Error> {
+    let mut sections: HashMap<String, Vec<(u64, u64)>> = HashMap::new();
+    let mut readonly_sections = HashSet::new();
+    let mut writable_sections = HashSet::new();
+    if let Some(sections_list) = pdb.sections()? {
+        for section in sections_list {
+            let name = section.name().to_string();
+            let virt_addr = section.virtual_address as u64;
+            let length = section.virtual_size as u64;
+            if section.characteristics.write() {
+                writable_sections.insert(name.clone());
+            } else {
+                readonly_sections.insert(name.clone());
+            }
+            sections
+                .entry(name)
+                .or_default()
+                .push((virt_addr, virt_addr + length));
+        }
+    }
+    readonly_sections.retain(|name| !writable_sections.contains(name));
+    Ok((sections, readonly_sections))
+}
+
 fn read_global_variables(
     pdb: &mut PDB<'_, File>,
     address_map: &AddressMap<'_>,
+    sections: &HashMap<String, Vec<(u64, u64)>>,
 ) -> Result<IndexMap<String, Vec<VarInfo>>, pdb2::Error> {
     let mut global_variables: IndexMap<String, Vec<VarInfo>> = IndexMap::new();
 
@@ -103,17 +149,22 @@ fn read_global_variables(
                 .map(|s| s.to_string())
                 .collect::<Vec<_>>();
             let symbol_name = ns_components.pop();
-            let virt_addr = data_symbol.offset.to_rva(address_map);
+            let virt_addr = data_symbol
+                .offset
+                .to_rva(address_map)
+                .map(|rva| u64::from(rva.0));
             if let (Some(symbol_name), Some(virt_addr)) = (symbol_name, virt_addr) {
                 global_variables
                     .entry(symbol_name)
                     .or_default()
                     .push(VarInfo {
-                        address: virt_addr.0 as u64,
+                        address: virt_addr,
                         typeref: data_symbol.type_index.0 as usize,
                         unit_idx: 0,
+                        source: VarSource::DebugInfo,
                         function: None,
                         namespaces: ns_components,
+                        section: find_section(sections, virt_addr),
                     });
             }
         }
@@ -125,6 +176,7 @@ fn read_global_variables(
 fn read_static_variables(
     pdb: &mut PDB<'_, File>,
     address_map: &AddressMap<'_>,
+    sections: &HashMap<String, Vec<(u64, u64)>>,
 ) -> Result<ModuleVars, pdb2::Error> {
     let mut modvars = ModuleVars {
         static_variables: IndexMap::new(),
@@ -175,18 +227,23 @@ fn read_static_variables(
                         .map(|name| name.to_string().into());
                     let sym_name: String = data_symbol.name.to_string().into();
 
-                    let virt_addr = data_symbol.offset.to_rva(address_map);
+                    let virt_addr = data_symbol
+                        .offset
+                        .to_rva(address_map)
+                        .map(|rva| u64::from(rva.0));
                     if let Some(virt_addr) = virt_addr {
                         modvars
                             .static_variables
                             .entry(sym_name)
                             .or_default()
                             .push(VarInfo {
-                                address: virt_addr.0 as u64,
+                                address: virt_addr,
                                 typeref: data_symbol.type_index.0 as usize,
                                 unit_idx: modvars.unit_list.len() - 1,
+                                source: VarSource::DebugInfo,
                                 function: function_name,
                                 namespaces: vec![],
+                                section: find_section(sections, virt_addr),
                             });
                     }
                 }
@@ -198,10 +255,7 @@ fn read_static_variables(
 }
 
 // extern declarations cause duplicated variables to be created in the variables list
-fn filter_extern_variables(
-    variables: &mut IndexMap<String, Vec<VarInfo>>,
-    types: &HashMap<usize, TypeInfo>,
-) {
+fn filter_extern_variables(variables: &mut IndexMap<String, Vec<VarInfo>>, types: &TypeMap) {
     for varinfo in variables.values_mut() {
         if varinfo.len() > 1 {
             // retain all elements which are not TypeRefs
@@ -220,6 +274,7 @@ fn filter_extern_variables(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::debuginfo::TypeInfo;
 
     static PDB_FILE_NAMES: [&str; 2] = [
         "fixtures/bin/debugdata_cl.pdb",
@@ -250,6 +305,7 @@ mod test {
             }
 
             let varinfo = debugdata.variables.get("class1").unwrap();
+            assert!(varinfo[0].section.is_some());
             let typeinfo = debugdata.types.get(&varinfo[0].typeref).unwrap();
             assert!(matches!(
                 typeinfo,
@@ -467,4 +523,87 @@ mod test {
             assert!(matches!(arraytype.datatype, DbgDataType::Float));
         }
     }
+
+    #[test]
+    fn test_load_qualified_types() {
+        // qualifier_test.pdb was generated from qualifier_test.yaml, see fixtures/bin/README.md
+        let debugdata =
+            DebugData::load_pdb(OsStr::new("fixtures/bin/qualifier_test.pdb"), false).unwrap();
+        let get_type = |name: &str| {
+            let varinfo = debugdata.variables.get(name).unwrap();
+            assert_eq!(varinfo.len(), 1);
+            debugdata.types.get(&varinfo[0].typeref).unwrap()
+        };
+
+        // volatile unsigned int
+        let varinfo = &debugdata.variables.get("volatile_counter").unwrap()[0];
+        assert_eq!(varinfo.address, 0x2000);
+        assert_eq!(varinfo.section.as_deref(), Some(".data"));
+        assert!(matches!(
+            get_type("volatile_counter").datatype,
+            DbgDataType::Uint32
+        ));
+
+        // const volatile applied to a forward reference of a struct
+        let typeinfo = get_type("const_point");
+        let DbgDataType::Struct { size, members } = &typeinfo.datatype else {
+            panic!("Expected struct type, got {:?}", typeinfo.datatype);
+        };
+        assert_eq!(*size, 8);
+        assert!(members.contains_key("x"));
+        assert!(members.contains_key("y"));
+
+        // __unaligned double
+        assert!(matches!(
+            get_type("unaligned_value").datatype,
+            DbgDataType::Double
+        ));
+
+        // std::atomic<int> is represented by the wrapped int, both as a variable and as a struct member
+        let typeinfo = get_type("atomic_counter");
+        assert!(matches!(typeinfo.datatype, DbgDataType::Sint32));
+        assert_eq!(typeinfo.name.as_deref(), Some("std::atomic<int>"));
+        let typeinfo = get_type("shared_state");
+        let DbgDataType::Struct { members, .. } = &typeinfo.datatype else {
+            panic!("Expected struct type, got {:?}", typeinfo.datatype);
+        };
+        assert!(matches!(
+            members.get("counter"),
+            Some((
+                TypeInfo {
+                    datatype: DbgDataType::Sint32,
+                    ..
+                },
+                0
+            ))
+        ));
+
+        // static variables are read from the module symbols
+        let varinfo = &debugdata.variables.get("static_point").unwrap()[0];
+        assert_eq!(varinfo.address, 0x2040);
+        assert_eq!(varinfo.unit_idx, 1);
+    }
+
+    #[test]
+    fn test_load_omap() {
+        // omap_test.pdb describes a binary whose .data section was moved from 0x2000 to 0x3000 after
+        // linking. The OMAP tables also insert 0x80 bytes of padding at 0x2100, and remove the data at 0x2200
+        let debugdata =
+            DebugData::load_pdb(OsStr::new("fixtures/bin/omap_test.pdb"), false).unwrap();
+        let get_address = |name: &str| debugdata.variables.get(name).map(|vars| vars[0].address);
+        assert_eq!(get_address("first_var"), Some(0x3010));
+        assert_eq!(get_address("moved_var"), Some(0x31a0));
+        assert_eq!(get_address("static_var"), Some(0x31c0));
+        assert_eq!(get_address("removed_var"), None);
+        assert_eq!(
+            debugdata.variables.get("moved_var").unwrap()[0]
+                .section
+                .as_deref(),
+            Some(".data")
+        );
+
+        // without the OMAP tables, the addresses can't be translated
+        let result = DebugData::load_pdb(OsStr::new("fixtures/bin/omap_missing_test.pdb"), false);
+        assert!(result.unwrap_err().contains("OMAP"));
+    }
 }
@@ -1,7 +1,10 @@
-use crate::debuginfo::{DbgDataType, TypeInfo, VarInfo};
+use crate::debuginfo::{DbgDataType, TypeInfo, TypeMap, VarInfo};
 use indexmap::IndexMap;
 use pdb2::{FallibleIterator, ItemIter, TypeData, TypeIndex, PDB};
-use std::{collections::HashMap, fs::File};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs::File,
+};
 
 mod builtin_types;
 
@@ -12,7 +15,7 @@ struct WipItemInfo {
 }
 
 pub(crate) struct TypeReaderData {
-    pub(crate) types: HashMap<usize, TypeInfo>,
+    pub(crate) types: TypeMap,
     pub(crate) typenames: HashMap<String, Vec<usize>>,
     wip_items: Vec<WipItemInfo>,
 }
@@ -116,7 +119,7 @@ pub(crate) fn read_all_types(
     variables: &IndexMap<String, Vec<VarInfo>>,
 ) -> Result<TypeReaderData, pdb2::Error> {
     let mut typereader_data = TypeReaderData {
-        types: HashMap::<usize, TypeInfo>::new(),
+        types: TypeMap::default(),
         typenames: HashMap::<String, Vec<usize>>::new(),
         wip_items: Vec::new(),
     };
@@ -125,24 +128,35 @@ pub(crate) fn read_all_types(
     let mut type_iter = type_information.iter();
     let pdb_data = PdbData::new(&mut type_iter)?;
 
+    // types that could not be read are only attempted once; the error is kept so that it can be
+    // reported for each affected variable
+    let mut failed_types = HashMap::<usize, String>::new();
+
     // read all of the types that are used by the variables and convert them into TypeInfo objects
     for (varname, vars) in variables {
+        // several variables with the same name often share the same type, but they should only be reported once
+        let mut reported = false;
         for var in vars {
             let type_index = var.typeref;
             if typereader_data.types.contains_key(&type_index) {
                 continue;
             }
 
-            match read_type(type_index as u32, &mut typereader_data, &pdb_data) {
-                Ok(_) => {}
-                Err(err) => {
-                    println!(
-                        "for variable {varname}: Error reading type 0x{:X}: {:?}",
-                        type_index, err
+            if let Entry::Vacant(entry) = failed_types.entry(type_index) {
+                if let Err(err) = read_type(type_index as u32, &mut typereader_data, &pdb_data) {
+                    entry.insert(err);
+                }
+                typereader_data.wip_items.clear();
+            }
+
+            if let Some(err) = failed_types.get(&type_index) {
+                if !reported {
+                    log_println!(
+                        "for variable {varname}: Error reading type 0x{type_index:X}: {err}"
                     );
+                    reported = true;
                 }
             }
-            typereader_data.wip_items.clear();
         }
     }
 
@@ -188,6 +202,7 @@ fn read_type(
             name: display_name,
             unit_idx: 0, // in the PDB, all types are global
             dbginfo_offset: type_index as usize,
+            big_endian: None,
         };
 
         typereader_data.types.insert(type_index as usize, typeinfo);
@@ -253,7 +268,7 @@ fn read_type_from_typedata(
         TypeData::Bitfield(bitfield_type) => {
             read_bitfield(bitfield_type, typereader_data, pdb_data)?
         }
-        TypeData::MemberFunction(_) | TypeData::Procedure(_) => {
+        TypeData::MemberFunction(_) | TypeData::Procedure(_) | TypeData::MethodList(_) => {
             // probably not relevant for a2l
             return Ok(None);
         }
@@ -272,7 +287,6 @@ fn read_type_from_typedata(
             // e.g. a field list is nested inside a class type, and an enumerate is nested inside an enumeration type
             unreachable!("Type {type_data:?} should not be encountered here");
         }
-        TypeData::MethodList(_) => todo!(),
         _ => {
             return Err(format!("Could not read unknown type: {type_data:?}"));
         }
@@ -285,7 +299,7 @@ fn read_primitive_type(primitive_type: &pdb2::PrimitiveType) -> (DbgDataType, Op
         pdb2::PrimitiveKind::NoType => (DbgDataType::Uint8, "notype"), // representing this as uint8 allows variables of this type to be inserted in the a2l
         pdb2::PrimitiveKind::Void => (DbgDataType::Other(0), "void"),
         pdb2::PrimitiveKind::Char => (DbgDataType::Sint8, "char"),
-        pdb2::PrimitiveKind::UChar => (DbgDataType::Sint8, "uchar"),
+        pdb2::PrimitiveKind::UChar => (DbgDataType::Uint8, "uchar"),
         pdb2::PrimitiveKind::RChar => (DbgDataType::Uint8, "rchar"),
         pdb2::PrimitiveKind::WChar => (DbgDataType::Uint16, "wchar"),
         pdb2::PrimitiveKind::RChar16 => (DbgDataType::Uint16, "rchar16"),
@@ -418,9 +432,53 @@ fn read_class(
         }
     };
 
+    // CodeView has no modifier for _Atomic. The MSVC standard library implements std::atomic<T> as a
+    // class that wraps a single value of type T in several layers of internal structs.
+    // A variable of this type is represented by the wrapped value, so that it can be used like a plain T.
+    if class_type.name.to_string().starts_with("std::atomic<") {
+        if let Some(value_type) = get_atomic_value_type(&datatype, size, &typereader_data.types) {
+            return Ok((value_type.datatype, value_type.name));
+        }
+    }
+
     Ok((datatype, None))
 }
 
+// get the type of the value inside of a std::atomic<T>.
+// The struct must contain only this value, either directly or inside of further nested structs.
+fn get_atomic_value_type(datatype: &DbgDataType, size: u64, types: &TypeMap) -> Option<TypeInfo> {
+    let (DbgDataType::Struct { members, .. } | DbgDataType::Class { members, .. }) = datatype
+    else {
+        return None;
+    };
+    if members.len() != 1 {
+        return None;
+    }
+    let (member_type, offset) = &members[0];
+    let member_type = member_type.get_reference(types);
+    if *offset != 0 || member_type.get_size() != size {
+        return None;
+    }
+    match &member_type.datatype {
+        DbgDataType::Struct { .. } | DbgDataType::Class { .. } => {
+            get_atomic_value_type(&member_type.datatype, size, types)
+        }
+        DbgDataType::Uint8
+        | DbgDataType::Uint16
+        | DbgDataType::Uint32
+        | DbgDataType::Uint64
+        | DbgDataType::Sint8
+        | DbgDataType::Sint16
+        | DbgDataType::Sint32
+        | DbgDataType::Sint64
+        | DbgDataType::Float
+        | DbgDataType::Double
+        | DbgDataType::Enum { .. }
+        | DbgDataType::Pointer(..) => Some(member_type.clone()),
+        _ => None,
+    }
+}
+
 fn read_union(
     union_type: &pdb2::UnionType<'_>,
     typereader_data: &mut TypeReaderData,
@@ -486,6 +544,7 @@ fn read_fields(
                         name,
                         unit_idx: 0,
                         dbginfo_offset: 0,
+                        big_endian: None,
                     }
                 } else {
                     // use simple types directly
@@ -645,14 +704,19 @@ fn read_modifier(
     typereader_data: &mut TypeReaderData,
     pdb_data: &PdbData<'_>,
 ) -> Result<(DbgDataType, Option<String>), String> {
+    // const / volatile / unaligned modifiers don't change the layout of the underlying type
     let type_index = modifier_type.underlying_type.0;
     read_type(type_index, typereader_data, pdb_data)?;
     let underlying_type = typereader_data
         .types
-        .get(&(modifier_type.underlying_type.0 as usize))
+        .get(&(type_index as usize))
         .ok_or_else(|| {
             format!("Modifier underlying type 0x{type_index:X} was not loaded correctly")
         })?;
+    // A modified struct / class is often a modifier applied to a forward reference.
+    // Copying the TypeRef would make the variable look like an extern declaration, which is then
+    // removed by filter_extern_variables(), so the referenced type is used instead.
+    let underlying_type = underlying_type.get_reference(&typereader_data.types);
 
     Ok((
         underlying_type.datatype.clone(),
@@ -697,6 +761,7 @@ fn read_array(
 
     let datatype = DbgDataType::Array {
         size,
+        lbound: vec![0; array_dim.len()],
         dim: array_dim,
         stride,
         arraytype: Box::new(element_type.clone()),
@@ -103,6 +103,8 @@ const BUILTIN_TYPE_UINT128: u32 = 0x0079;
 const BUILTIN_TYPE_CHAR16: u32 = 0x007a;
 /// 32 bit unicode character
 const BUILTIN_TYPE_CHAR32: u32 = 0x007b;
+/// 8 bit utf-8 character (char8_t)
+const BUILTIN_TYPE_CHAR8: u32 = 0x007c;
 
 // pointer types - the x:y notation indicates the use of segmented addressing (DOS)
 /// not a pointer
@@ -119,6 +121,8 @@ const BUILTIN_PTR_32: u32 = 0x04;
 const BUILTIN_PTR_32FAR: u32 = 0x05;
 /// 64 bit pointer
 const BUILTIN_PTR_64: u32 = 0x06;
+/// 128 bit pointer - unsupported
+const BUILTIN_PTR_128: u32 = 0x07;
 
 const BUILTIN_LIMIT: u32 = 0x1000;
 
@@ -156,7 +160,9 @@ pub(crate) fn read_builtin_type(
         BUILTIN_TYPE_LONG | BUILTIN_TYPE_INT32 => DbgDataType::Sint32,
         BUILTIN_TYPE_QUAD | BUILTIN_TYPE_INT64 => DbgDataType::Sint64,
 
-        BUILTIN_TYPE_UCHAR | BUILTIN_TYPE_UINT8 | BUILTIN_TYPE_BOOL08 => DbgDataType::Uint8,
+        BUILTIN_TYPE_UCHAR | BUILTIN_TYPE_UINT8 | BUILTIN_TYPE_BOOL08 | BUILTIN_TYPE_CHAR8 => {
+            DbgDataType::Uint8
+        }
 
         BUILTIN_TYPE_USHORT | BUILTIN_TYPE_WCHAR | BUILTIN_TYPE_UINT16 | BUILTIN_TYPE_BOOL16
         | BUILTIN_TYPE_CHAR16 => DbgDataType::Uint16,
@@ -203,10 +209,11 @@ pub(crate) fn read_builtin_type(
             DbgDataType::Other(8)
         }
         BUILTIN_TYPE_BIT => {
-            todo!("not sure how to handle bit type - width?");
+            // a single bit can't be addressed directly, so it is treated like the smallest addressable unit
+            DbgDataType::Uint8
         }
         _ => {
-            return Err(format!("Unknown built-in type: {subtype}"));
+            return Err(format!("Unknown built-in type: 0x{subtype:02X}"));
         }
     };
     typereader_data.types.insert(
@@ -216,6 +223,7 @@ pub(crate) fn read_builtin_type(
             name: None,
             unit_idx: 0,
             dbginfo_offset: 0,
+            big_endian: None,
         },
     );
 
@@ -227,6 +235,7 @@ pub(crate) fn read_builtin_type(
         BUILTIN_PTR_32 => Some(DbgDataType::Pointer(4, subtype as usize)),
         BUILTIN_PTR_32FAR => return Err("32-bit far pointers are not supported".to_string()),
         BUILTIN_PTR_64 => Some(DbgDataType::Pointer(8, subtype as usize)),
+        BUILTIN_PTR_128 => return Err("128-bit pointers are not supported".to_string()),
         _ => {
             return Err(format!(
                 "Unknown built-in pointer type: {pointer_type} for type {type_index:04x}"
@@ -241,9 +250,53 @@ pub(crate) fn read_builtin_type(
                 name: None,
                 unit_idx: 0,
                 dbginfo_offset: 0,
+                big_endian: None,
             },
         );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::debuginfo::TypeMap;
+    use std::collections::HashMap;
+
+    fn new_typereader_data() -> TypeReaderData {
+        TypeReaderData {
+            types: TypeMap::default(),
+            typenames: HashMap::new(),
+            wip_items: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_read_builtin_type() {
+        let mut typereader_data = new_typereader_data();
+
+        // uint32 (T_UINT4)
+        read_builtin_type(0x0075, &mut typereader_data).unwrap();
+        let typeinfo = typereader_data.types.get(&0x75).unwrap();
+        assert!(matches!(typeinfo.datatype, DbgDataType::Uint32));
+
+        // 64 bit pointer to uint32 (T_64PUINT4); the pointed-to type is also created
+        read_builtin_type(0x0675, &mut typereader_data).unwrap();
+        let typeinfo = typereader_data.types.get(&0x675).unwrap();
+        assert!(matches!(typeinfo.datatype, DbgDataType::Pointer(8, 0x75)));
+
+        // char8_t and the bit type are represented as uint8
+        read_builtin_type(BUILTIN_TYPE_CHAR8, &mut typereader_data).unwrap();
+        let typeinfo = typereader_data.types.get(&0x7c).unwrap();
+        assert_eq!(typeinfo.get_size(), 1);
+        read_builtin_type(BUILTIN_TYPE_BIT, &mut typereader_data).unwrap();
+        let typeinfo = typereader_data.types.get(&0x60).unwrap();
+        assert_eq!(typeinfo.get_size(), 1);
+
+        // unknown types and pointer modes return an error instead of panicking
+        assert!(read_builtin_type(0x00ff, &mut typereader_data).is_err());
+        assert!(read_builtin_type(0x0775, &mut typereader_data).is_err());
+        assert!(!typereader_data.types.contains_key(&0x775));
+    }
+}
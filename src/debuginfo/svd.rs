@@ -0,0 +1,784 @@
+use super::{DbgDataType, DebugData, SvdData, TypeInfo, VarInfo, VarSource};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+
+type Members = IndexMap<String, (TypeInfo, u64)>;
+
+/// Load the peripherals of a CMSIS-SVD file as a separate source of symbols beside the debug data.
+/// Each peripheral becomes a variable at its base address. Its type is a struct containing the registers,
+/// and the fields of a register are bitfields inside of a struct for the register. This way the registers
+/// can be addressed like variables, e.g. "UART0.STATUS.RXNE".
+/// The peripherals are only searched if a name is not found in the debug info, so variables of the
+/// debug info take precedence over peripherals with the same name.
+/// Returns the number of peripherals.
+pub(crate) fn load_svd(debug_data: &mut DebugData, filename: &OsStr) -> Result<usize, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the SVD file {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    let device = parse_xml(&text).map_err(|err| {
+        format!(
+            "Failed to load the SVD file {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    if device.name != "device" {
+        return Err(format!(
+            "Failed to load the SVD file {}: the root element is <{}> instead of <device>",
+            filename.to_string_lossy(),
+            device.name
+        ));
+    }
+
+    let peripherals = load_peripherals(&device).map_err(|err| {
+        format!(
+            "Error in the SVD file {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    // without an elf file the byte order of the registers is given by the cpu description
+    if debug_data.binary.path.is_empty() {
+        debug_data.memory.big_endian =
+            device.child("cpu").and_then(|cpu| cpu.child_text("endian")) == Some("big");
+    }
+    let count = peripherals.variables.len();
+    debug_data.svd = Some(Box::new(SvdData {
+        filename: filename.to_string_lossy().to_string(),
+        peripherals,
+    }));
+    Ok(count)
+}
+
+fn load_peripherals(device: &XmlElement) -> Result<DebugData, String> {
+    let mut svd_data = DebugData::default();
+    let mut next_typeref = 0;
+    let default_size = device
+        .child_text("size")
+        .map(parse_svd_number)
+        .transpose()?
+        .unwrap_or(32);
+    let peripherals: Vec<&XmlElement> = device
+        .child("peripherals")
+        .map(|peripherals| peripherals.children("peripheral").collect())
+        .unwrap_or_default();
+    let by_name: HashMap<&str, &XmlElement> = peripherals
+        .iter()
+        .filter_map(|peripheral| Some((peripheral.child_text("name")?, *peripheral)))
+        .collect();
+    // derived peripherals share the type of the peripheral they are derived from
+    let mut peripheral_types = HashMap::<&str, usize>::new();
+
+    for peripheral in &peripherals {
+        let name = peripheral
+            .child_text("name")
+            .ok_or_else(|| format!("a peripheral on line {} has no name", peripheral.line))?;
+        let base_address = peripheral
+            .child_text("baseAddress")
+            .map(parse_svd_number)
+            .transpose()?
+            .ok_or_else(|| format!("the peripheral {name} has no baseAddress"))?;
+        let derived_from = peripheral
+            .attribute("derivedFrom")
+            .and_then(|base_name| Some((base_name, *by_name.get(base_name)?)));
+        // a derived peripheral takes the registers of its base, unless it defines its own registers
+        let type_source = match derived_from {
+            Some((base_name, base)) if peripheral.child("registers").is_none() => {
+                Some((base_name, base))
+            }
+            _ => None,
+        };
+
+        let typeref = if let Some(typeref) =
+            type_source.and_then(|(base_name, _)| peripheral_types.get(base_name).copied())
+        {
+            typeref
+        } else {
+            let (type_name, definition) = type_source.unwrap_or((name, *peripheral));
+            let default_size = definition
+                .child_text("size")
+                .map(parse_svd_number)
+                .transpose()?
+                .unwrap_or(default_size);
+            let (size, members) = match definition.child("registers") {
+                Some(registers) => build_members(registers, type_name, default_size)?,
+                None => (0, IndexMap::new()),
+            };
+            let typeref = next_typeref;
+            next_typeref += 1;
+            let struct_name = definition
+                .child_text("headerStructName")
+                .unwrap_or(type_name)
+                .to_string();
+            svd_data.types.insert(
+                typeref,
+                TypeInfo {
+                    name: Some(struct_name.clone()),
+                    unit_idx: usize::MAX,
+                    datatype: DbgDataType::Struct { size, members },
+                    dbginfo_offset: typeref,
+                    big_endian: None,
+                },
+            );
+            svd_data
+                .types
+                .typenames_mut()
+                .entry(struct_name)
+                .or_default()
+                .push(typeref);
+            peripheral_types.insert(type_name, typeref);
+            typeref
+        };
+        peripheral_types.entry(name).or_insert(typeref);
+
+        svd_data.variables.insert(
+            name.to_string(),
+            vec![VarInfo {
+                address: base_address,
+                typeref,
+                unit_idx: usize::MAX,
+                source: VarSource::Svd,
+                function: None,
+                namespaces: vec![],
+                section: None,
+            }],
+        );
+    }
+
+    Ok(svd_data)
+}
+
+// build the members of a peripheral or cluster from its registers and clusters
+// Returns the total size and the members with their offsets.
+fn build_members(
+    container: &XmlElement,
+    prefix: &str,
+    default_size: u64,
+) -> Result<(u64, Members), String> {
+    let mut members = IndexMap::new();
+    let mut total_size = 0;
+
+    for element in &container.children {
+        let name = match element.name.as_str() {
+            "register" | "cluster" => element.child_text("name").ok_or_else(|| {
+                format!("a {} on line {} has no name", element.name, element.line)
+            })?,
+            _ => continue,
+        };
+        let offset = element
+            .child_text("addressOffset")
+            .map(parse_svd_number)
+            .transpose()?
+            .unwrap_or(0);
+        let plain_name = name.replace("[%s]", "").replace("%s", "");
+        let member_prefix = format!("{prefix}_{plain_name}");
+        let typeinfo = if element.name == "register" {
+            let size = element
+                .child_text("size")
+                .map(parse_svd_number)
+                .transpose()?
+                .unwrap_or(default_size);
+            build_register_type(element, &member_prefix, size)?
+        } else {
+            let size = element
+                .child_text("size")
+                .map(parse_svd_number)
+                .transpose()?
+                .unwrap_or(default_size);
+            let (cluster_size, cluster_members) = build_members(element, &member_prefix, size)?;
+            make_type(
+                DbgDataType::Struct {
+                    size: cluster_size,
+                    members: cluster_members,
+                },
+                None,
+            )
+        };
+
+        for (member_name, member_type, member_offset) in
+            expand_dim(element, name, offset, typeinfo)?
+        {
+            total_size = total_size.max(member_offset + member_type.get_size());
+            members.insert(member_name, (member_type, member_offset));
+        }
+    }
+
+    Ok((total_size, members))
+}
+
+// A register without fields is a plain integer of the register size.
+// If the register has fields, then it is a struct of bitfields, which all have the offset 0.
+fn build_register_type(register: &XmlElement, prefix: &str, size: u64) -> Result<TypeInfo, String> {
+    let basetype = match size {
+        8 => DbgDataType::Uint8,
+        16 => DbgDataType::Uint16,
+        32 => DbgDataType::Uint32,
+        64 => DbgDataType::Uint64,
+        _ => {
+            return Err(format!(
+                "the register on line {} has an unsupported size of {size} bits",
+                register.line
+            ))
+        }
+    };
+    let Some(fields) = register.child("fields") else {
+        return Ok(make_type(basetype, None));
+    };
+
+    let mut members = IndexMap::new();
+    for field in fields.children("field") {
+        let name = field
+            .child_text("name")
+            .ok_or_else(|| format!("a field on line {} has no name", field.line))?;
+        let (bit_offset, bit_size) = get_field_bits(field)?;
+        if bit_offset + bit_size > size {
+            return Err(format!(
+                "the field {name} on line {} does not fit into the register",
+                field.line
+            ));
+        }
+        let enumerators = get_enumerated_values(field)?;
+        let field_basetype = if enumerators.is_empty() {
+            make_type(basetype.clone(), None)
+        } else {
+            make_type(
+                DbgDataType::Enum {
+                    size: size / 8,
+                    signed: false,
+                    enumerators,
+                },
+                Some(format!("{prefix}_{name}")),
+            )
+        };
+        members.insert(
+            name.to_string(),
+            (
+                make_type(
+                    DbgDataType::Bitfield {
+                        basetype: Box::new(field_basetype),
+                        bit_offset: bit_offset as u16,
+                        bit_size: bit_size as u16,
+                    },
+                    None,
+                ),
+                0,
+            ),
+        );
+    }
+
+    Ok(make_type(
+        DbgDataType::Struct {
+            size: size / 8,
+            members,
+        },
+        None,
+    ))
+}
+
+fn make_type(datatype: DbgDataType, name: Option<String>) -> TypeInfo {
+    TypeInfo {
+        name,
+        unit_idx: usize::MAX,
+        datatype,
+        dbginfo_offset: 0,
+        big_endian: None,
+    }
+}
+
+// the position of a field can be given as bitOffset + bitWidth, as lsb + msb, or as a bitRange "[msb:lsb]"
+fn get_field_bits(field: &XmlElement) -> Result<(u64, u64), String> {
+    let number = |name: &str| field.child_text(name).map(parse_svd_number).transpose();
+    if let Some(bit_offset) = number("bitOffset")? {
+        let bit_width = number("bitWidth")?.unwrap_or(1);
+        Ok((bit_offset, bit_width))
+    } else if let (Some(lsb), Some(msb)) = (number("lsb")?, number("msb")?) {
+        Ok((lsb, msb.saturating_sub(lsb) + 1))
+    } else if let Some(bit_range) = field.child_text("bitRange") {
+        let (msb, lsb) = bit_range
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split_once(':')
+            .ok_or_else(|| format!("invalid bitRange \"{bit_range}\" on line {}", field.line))?;
+        let msb = parse_svd_number(msb)?;
+        let lsb = parse_svd_number(lsb)?;
+        Ok((lsb, msb.saturating_sub(lsb) + 1))
+    } else {
+        Err(format!(
+            "the position of the field on line {} is not specified",
+            field.line
+        ))
+    }
+}
+
+// get the enumerated values of a field. Values with don't-care bits like "#1x" and default entries are skipped.
+fn get_enumerated_values(field: &XmlElement) -> Result<Vec<(String, i64)>, String> {
+    let mut enumerators = Vec::new();
+    // if there are separate values for reading and writing, then the values for reading are used
+    let enumerated_values = field
+        .children("enumeratedValues")
+        .find(|values| values.child_text("usage") != Some("write"));
+    if let Some(enumerated_values) = enumerated_values {
+        for value in enumerated_values.children("enumeratedValue") {
+            let (Some(name), Some(value_text)) =
+                (value.child_text("name"), value.child_text("value"))
+            else {
+                continue;
+            };
+            if let Ok(number) = parse_svd_number(value_text) {
+                enumerators.push((name.to_string(), number as i64));
+            }
+        }
+    }
+    Ok(enumerators)
+}
+
+// Expand a register or cluster with a dim element.
+// "NAME[%s]" becomes an array, while "NAME%s" is repeated with each index from dimIndex, or from 0 to dim-1.
+fn expand_dim(
+    element: &XmlElement,
+    name: &str,
+    offset: u64,
+    typeinfo: TypeInfo,
+) -> Result<Vec<(String, TypeInfo, u64)>, String> {
+    let Some(dim) = element
+        .child_text("dim")
+        .map(parse_svd_number)
+        .transpose()?
+    else {
+        return Ok(vec![(name.to_string(), typeinfo, offset)]);
+    };
+    let increment = element
+        .child_text("dimIncrement")
+        .map(parse_svd_number)
+        .transpose()?
+        .unwrap_or_else(|| typeinfo.get_size());
+
+    if let Some(array_name) = name.strip_suffix("[%s]") {
+        let array_type = make_type(
+            DbgDataType::Array {
+                size: dim * increment,
+                dim: vec![dim],
+                lbound: vec![0],
+                stride: increment,
+                arraytype: Box::new(typeinfo),
+            },
+            None,
+        );
+        Ok(vec![(array_name.to_string(), array_type, offset)])
+    } else if name.contains("%s") {
+        let indices = match element.child_text("dimIndex") {
+            Some(dim_index) => parse_dim_index(dim_index)?,
+            None => (0..dim).map(|idx| idx.to_string()).collect(),
+        };
+        Ok(indices
+            .iter()
+            .enumerate()
+            .map(|(pos, index)| {
+                (
+                    name.replace("%s", index),
+                    typeinfo.clone(),
+                    offset + pos as u64 * increment,
+                )
+            })
+            .collect())
+    } else {
+        Err(format!(
+            "the name {name} on line {} has a dim, but does not contain %s",
+            element.line
+        ))
+    }
+}
+
+// dimIndex is either a list "A,B,C" or a numeric range "0-3"
+fn parse_dim_index(dim_index: &str) -> Result<Vec<String>, String> {
+    if let Some((start, end)) = dim_index.split_once('-') {
+        if let (Ok(start), Ok(end)) = (start.trim().parse::<u64>(), end.trim().parse::<u64>()) {
+            return Ok((start..=end).map(|idx| idx.to_string()).collect());
+        }
+    }
+    let indices: Vec<String> = dim_index
+        .split(',')
+        .map(|index| index.trim().to_string())
+        .collect();
+    if indices.iter().any(String::is_empty) {
+        Err(format!("invalid dimIndex \"{dim_index}\""))
+    } else {
+        Ok(indices)
+    }
+}
+
+// numbers in SVD files can be decimal, hex with the prefix 0x, or binary with the prefix # or 0b
+fn parse_svd_number(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let result = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(bin) = text
+        .strip_prefix('#')
+        .or_else(|| text.strip_prefix("0b"))
+        .or_else(|| text.strip_prefix("0B"))
+    {
+        u64::from_str_radix(bin, 2)
+    } else {
+        text.parse::<u64>()
+    };
+    result.map_err(|_| format!("\"{text}\" is not a valid number"))
+}
+
+// A minimal XML element tree, which is sufficient for SVD files.
+// Namespaces, DTDs and processing instructions are not interpreted.
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+    line: usize,
+}
+
+impl XmlElement {
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.trim())
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attr_name, _)| attr_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// Counts the lines of the text up to a position. The positions must not decrease,
+// so that each part of the text is only scanned once.
+struct LineCounter<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl LineCounter<'_> {
+    fn line_at(&mut self, pos: usize) -> usize {
+        self.line += self.text[self.pos..pos].matches('\n').count();
+        self.pos = pos;
+        self.line
+    }
+}
+
+fn parse_xml(text: &str) -> Result<XmlElement, String> {
+    let mut lines = LineCounter {
+        text,
+        pos: 0,
+        line: 1,
+    };
+    // the bottom of the stack collects the top-level element
+    let mut stack = vec![XmlElement::default()];
+    let mut pos = 0;
+
+    while let Some(offset) = text[pos..].find('<') {
+        let content = &text[pos..pos + offset];
+        if let Some(current) = stack.last_mut() {
+            current.text.push_str(&decode_entities(content));
+        }
+        pos += offset;
+        let rest = &text[pos..];
+
+        let start = pos;
+        let skip_to = |end_marker: &str| {
+            rest.find(end_marker)
+                .map(|end| start + end + end_marker.len())
+        };
+        let unterminated_markup = |lines: &mut LineCounter| {
+            format!("unterminated markup on line {}", lines.line_at(start))
+        };
+        if rest.starts_with("<?") {
+            pos = skip_to("?>").ok_or_else(|| unterminated_markup(&mut lines))?;
+        } else if rest.starts_with("<!--") {
+            pos = skip_to("-->").ok_or_else(|| unterminated_markup(&mut lines))?;
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata
+                .find("]]>")
+                .ok_or_else(|| format!("unterminated CDATA on line {}", lines.line_at(pos)))?;
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&cdata[..end]);
+            }
+            pos += "<![CDATA[".len() + end + "]]>".len();
+        } else if rest.starts_with("<!") {
+            pos = skip_to(">").ok_or_else(|| unterminated_markup(&mut lines))?;
+        } else if let Some(end_tag) = rest.strip_prefix("</") {
+            let end = end_tag
+                .find('>')
+                .ok_or_else(|| format!("unterminated end tag on line {}", lines.line_at(pos)))?;
+            let name = end_tag[..end].trim();
+            if stack.len() < 2 || stack.last().is_some_and(|element| element.name != name) {
+                return Err(format!(
+                    "unexpected end tag </{name}> on line {}",
+                    lines.line_at(pos)
+                ));
+            }
+            let element = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(element);
+            pos += 2 + end + 1;
+        } else {
+            let tag_len = find_tag_end(rest)
+                .ok_or_else(|| format!("unterminated tag on line {}", lines.line_at(pos)))?;
+            let tag = &rest[1..tag_len - 1];
+            let (tag, self_closing) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let line = lines.line_at(pos);
+            let mut element =
+                parse_tag(tag).ok_or_else(|| format!("invalid tag on line {line}"))?;
+            element.line = line;
+            if self_closing {
+                stack.last_mut().unwrap().children.push(element);
+            } else {
+                stack.push(element);
+            }
+            pos += tag_len;
+        }
+    }
+
+    if stack.len() > 1 {
+        let element = stack.last().unwrap();
+        return Err(format!(
+            "the element <{}> on line {} is not closed",
+            element.name, element.line
+        ));
+    }
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| "the file does not contain any XML elements".to_string())
+}
+
+// find the length of a tag including the closing '>'; a '>' inside of an attribute value does not end the tag
+fn find_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+// parse the name and the attributes of a start tag, e.g. peripheral derivedFrom="UART0"
+fn parse_tag(tag: &str) -> Option<XmlElement> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let (attr_name, value_part) = rest.split_once('=')?;
+        let value_part = value_part.trim_start();
+        let quote = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')?;
+        let value_end = value_part[1..].find(quote)? + 1;
+        attributes.push((
+            attr_name.trim().to_string(),
+            decode_entities(&value_part[1..value_end]),
+        ));
+        rest = value_part[value_end + 1..].trim_start();
+    }
+    Some(XmlElement {
+        name: name.to_string(),
+        attributes,
+        ..Default::default()
+    })
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semicolon) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semicolon];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        if let Some(c) = decoded {
+            result.push(c);
+            rest = &rest[semicolon + 1..];
+        } else {
+            result.push('&');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_xml() {
+        let text = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- comment with <tags> -->
+<device schemaVersion="1.3">
+  <name>Test &amp; Device</name>
+  <empty attr='a &lt; b'/>
+  <description><![CDATA[some <text>]]></description>
+</device>"#;
+        let device = parse_xml(text).unwrap();
+        assert_eq!(device.name, "device");
+        assert_eq!(device.attribute("schemaVersion"), Some("1.3"));
+        assert_eq!(device.child_text("name"), Some("Test & Device"));
+        let empty = device.child("empty").unwrap();
+        assert_eq!(empty.attribute("attr"), Some("a < b"));
+        assert_eq!(empty.line, 5);
+        assert_eq!(device.child_text("description"), Some("some <text>"));
+
+        assert!(parse_xml("<device><name>x</device>").is_err());
+        assert!(parse_xml("<device>").is_err());
+        assert!(parse_xml("no xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_svd_number() {
+        assert_eq!(parse_svd_number("42"), Ok(42));
+        assert_eq!(parse_svd_number(" 0x4000C000 "), Ok(0x4000_C000));
+        assert_eq!(parse_svd_number("0XFF"), Ok(255));
+        assert_eq!(parse_svd_number("#101"), Ok(5));
+        assert_eq!(parse_svd_number("0b11"), Ok(3));
+        assert!(parse_svd_number("#1x").is_err());
+        assert!(parse_svd_number("").is_err());
+        assert_eq!(parse_dim_index("0-2").unwrap(), vec!["0", "1", "2"]);
+        assert_eq!(parse_dim_index("A,B").unwrap(), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_load_svd() {
+        let mut debug_data = DebugData::default();
+        let count = load_svd(&mut debug_data, OsStr::new("fixtures/svd/svd_test.svd")).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(
+            debug_data.svd.as_ref().unwrap().filename,
+            "fixtures/svd/svd_test.svd"
+        );
+        // the peripherals are a separate source, they are not added to the variables of the debug info
+        assert!(debug_data.variables.is_empty());
+
+        // a field of a register is a bitfield
+        let sym_info = crate::symbol::find_symbol("UART0.STATUS.RXNE", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x4000_C004);
+        let DbgDataType::Bitfield {
+            basetype,
+            bit_offset,
+            bit_size,
+        } = &sym_info.typeinfo.datatype
+        else {
+            panic!("RXNE should be a bitfield");
+        };
+        assert_eq!((*bit_offset, *bit_size), (5, 1));
+        assert!(matches!(basetype.datatype, DbgDataType::Uint32));
+
+        // a field with enumerated values has an enum type
+        let sym_info = crate::symbol::find_symbol("UART0.CTRL.MODE", &debug_data).unwrap();
+        let DbgDataType::Bitfield {
+            basetype,
+            bit_offset,
+            bit_size,
+        } = &sym_info.typeinfo.datatype
+        else {
+            panic!("MODE should be a bitfield");
+        };
+        assert_eq!((*bit_offset, *bit_size), (4, 2));
+        let DbgDataType::Enum { enumerators, .. } = &basetype.datatype else {
+            panic!("MODE should have an enum type");
+        };
+        assert_eq!(
+            enumerators,
+            &vec![
+                ("Normal".to_string(), 0),
+                ("Loopback".to_string(), 1),
+                ("IrDA".to_string(), 2)
+            ]
+        );
+
+        // a register without fields has the data type of the register size
+        let sym_info = crate::symbol::find_symbol("UART0.DATA", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x4000_C008);
+        assert!(matches!(sym_info.typeinfo.datatype, DbgDataType::Uint8));
+
+        // the derived peripheral has the registers of its base at a different address
+        let sym_info = crate::symbol::find_symbol("UART1.STATUS.TXE", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x4000_D004);
+
+        // register arrays
+        let sym_info = crate::symbol::find_symbol("GPIO.PIN[3]", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x5000_000C);
+        let sym_info = crate::symbol::find_symbol("GPIO.CFGB", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x5000_0024);
+
+        // unknown names report that the SVD file was searched
+        let errmsg = crate::symbol::find_symbol("UART2", &debug_data)
+            .err()
+            .unwrap();
+        assert!(errmsg.contains("SVD file fixtures/svd/svd_test.svd"));
+        let errmsg = crate::symbol::find_symbol("UART0.STATUS.PARITY", &debug_data)
+            .err()
+            .unwrap();
+        assert!(errmsg.contains("PARITY"));
+
+        // a variable of the debug info takes precedence over a peripheral with the same name
+        debug_data.types.insert(
+            0,
+            make_type(DbgDataType::Uint32, Some("uint32_t".to_string())),
+        );
+        debug_data.variables.insert(
+            "UART1".to_string(),
+            vec![VarInfo {
+                address: 0x2000_0000,
+                typeref: 0,
+                unit_idx: 0,
+                source: VarSource::DebugInfo,
+                function: None,
+                namespaces: vec![],
+                section: None,
+            }],
+        );
+        let sym_info = crate::symbol::find_symbol("UART1", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x2000_0000);
+        let sym_info = crate::symbol::find_symbol("UART0", &debug_data).unwrap();
+        assert_eq!(sym_info.address, 0x4000_C000);
+    }
+}
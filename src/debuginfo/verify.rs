@@ -0,0 +1,242 @@
+use super::dwarf::{get_elf_sections, load_elf_file, load_filedata};
+use super::DebugData;
+use object::{Object, ObjectSymbol, SymbolKind};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+
+// the maximum number of variables whose addresses are compared with the symbol table
+const SAMPLE_SIZE: usize = 1000;
+// the maximum number of individual address mismatches that are reported
+const MAX_REPORTED: usize = 20;
+
+/// Compare the addresses in the debug info with the export table and symbol table of the binary file.
+/// Returns the number of variables whose address differs.
+/// If the section layout of the binary does not match the debug info, then the binary does not belong
+/// to the debug info, or it was modified after it was linked. This is an error.
+pub(crate) fn verify_addresses(
+    debug_data: &DebugData,
+    filename: &OsStr,
+    log_msgs: &mut Vec<String>,
+) -> Result<usize, String> {
+    let filedata = load_filedata(filename)?;
+    let binfile = load_elf_file(&filename.to_string_lossy(), &filedata)?;
+
+    // PDB files contain RVAs, which are relative to the image base of the PE file
+    let bin_sections = get_elf_sections(&binfile);
+    let base = find_address_base(debug_data, &bin_sections, binfile.relative_address_base())
+        .map_err(|mismatches| {
+            // only PDB files can describe a binary that was rearranged after linking
+            let from_pdb = Path::new(&debug_data.binary.path)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pdb"));
+            let reason = if from_pdb {
+                "The file was modified after linking, and the PDB file does not contain OMAP data to translate the addresses."
+            } else {
+                "The file was not built together with the debug info, or it was modified after linking."
+            };
+            format!(
+                "The section layout of {} does not match the debug info:\n  {}\n{reason}",
+                filename.to_string_lossy(),
+                mismatches.join("\n  ")
+            )
+        })?;
+
+    // collect the addresses of all named data symbols and exports of the binary
+    // names that occur several times with different addresses (e.g. static variables) are set to None
+    let mut bin_symbols = HashMap::<String, Option<u64>>::new();
+    let mut add_symbol = |name: String, address: u64| {
+        bin_symbols
+            .entry(name)
+            .and_modify(|entry| {
+                if *entry != Some(address) {
+                    *entry = None;
+                }
+            })
+            .or_insert(Some(address));
+    };
+    for symbol in binfile.symbols() {
+        if matches!(symbol.kind(), SymbolKind::Data | SymbolKind::Unknown) && symbol.is_definition()
+        {
+            if let Ok(name) = symbol.name() {
+                add_symbol(name.to_string(), symbol.address());
+            }
+        }
+    }
+    if let Ok(exports) = binfile.exports() {
+        for export in exports {
+            add_symbol(
+                String::from_utf8_lossy(export.name()).to_string(),
+                export.address(),
+            );
+        }
+    }
+    if bin_symbols.is_empty() {
+        log_msgs.push(format!(
+            "The addresses could not be verified: {} contains neither exports nor a symbol table",
+            filename.to_string_lossy()
+        ));
+        return Ok(0);
+    }
+
+    let mut checked_count = 0;
+    let mut mismatches = Vec::new();
+    for (name, varinfo_list) in &debug_data.variables {
+        // variables with several definitions, e.g. statics in different files, can't be matched by name
+        if varinfo_list.len() != 1 {
+            continue;
+        }
+        // 32-bit x86 symbols carry a leading underscore
+        let Some(Some(bin_address)) = bin_symbols
+            .get(name)
+            .or_else(|| bin_symbols.get(&format!("_{name}")))
+        else {
+            continue;
+        };
+        let address = varinfo_list[0].address.wrapping_add(base);
+        if address != *bin_address {
+            mismatches.push((name, address, *bin_address));
+        }
+        checked_count += 1;
+        if checked_count >= SAMPLE_SIZE {
+            break;
+        }
+    }
+
+    for (name, address, bin_address) in mismatches.iter().take(MAX_REPORTED) {
+        log_msgs.push(format!(
+            "Variable {name}: address 0x{address:X} in the debug info, 0x{bin_address:X} in {}",
+            filename.to_string_lossy()
+        ));
+    }
+    if mismatches.len() > MAX_REPORTED {
+        log_msgs.push(format!(
+            "... and {} more mismatched addresses",
+            mismatches.len() - MAX_REPORTED
+        ));
+    }
+
+    // warn if most of the mismatched addresses are off by the same amount
+    let mut offset_counts = HashMap::<i64, usize>::new();
+    for (_, address, bin_address) in &mismatches {
+        *offset_counts
+            .entry(bin_address.wrapping_sub(*address) as i64)
+            .or_default() += 1;
+    }
+    if let Some((offset, count)) = offset_counts.iter().max_by_key(|(_, count)| **count) {
+        if *count >= 2 && *count * 2 >= mismatches.len() {
+            let sign = if *offset < 0 { "-" } else { "+" };
+            log_msgs.push(format!(
+                "Warning: {count} addresses in the debug info differ from {} by a systematic offset of {sign}0x{:X}",
+                filename.to_string_lossy(),
+                offset.unsigned_abs()
+            ));
+        }
+    }
+
+    log_msgs.push(format!(
+        "Compared the addresses of {checked_count} variables with the symbols of {}: {} mismatches",
+        filename.to_string_lossy(),
+        mismatches.len()
+    ));
+
+    Ok(mismatches.len())
+}
+
+// Determine the value that must be added to the addresses in the debug info to get the addresses in
+// the binary. The debug info either uses the same addresses as the binary, or addresses relative to
+// the image base. If neither choice makes all sections match, the list of mismatched sections is returned.
+fn find_address_base(
+    debug_data: &DebugData,
+    bin_sections: &HashMap<String, Vec<(u64, u64)>>,
+    image_base: u64,
+) -> Result<u64, Vec<String>> {
+    // (number of matched sections, mismatch descriptions) of the best candidate
+    let mut best: Option<(usize, Vec<String>)> = None;
+    for base in [0, image_base] {
+        let mut mismatches = Vec::new();
+        let mut matched = 0;
+        for (name, ranges) in &debug_data.sections {
+            let Some(bin_ranges) = bin_sections.get(name) else {
+                continue;
+            };
+            let Some((start, _)) = ranges.first() else {
+                continue;
+            };
+            let Some((bin_start, _)) = bin_ranges.first() else {
+                continue;
+            };
+            if start.wrapping_add(base) == *bin_start {
+                matched += 1;
+            } else {
+                mismatches.push(format!(
+                    "section {name} is located at 0x{:X} in the debug info, but at 0x{bin_start:X} in the file",
+                    start.wrapping_add(base)
+                ));
+            }
+        }
+        if mismatches.is_empty() {
+            // the debug info may not have any section information, then there is nothing to check
+            return Ok(base);
+        }
+        if best
+            .as_ref()
+            .is_none_or(|(best_matched, _)| matched > *best_matched)
+        {
+            mismatches.sort();
+            best = Some((matched, mismatches));
+        }
+    }
+    Err(best.map(|(_, mismatches)| mismatches).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify_addresses() {
+        // the addresses in the debug info match the symbol table of the elf file
+        let filename = OsStr::new("fixtures/bin/update_test.elf");
+        let mut debug_data = DebugData::load_dwarf(filename, false).unwrap();
+        let mut log_msgs = Vec::new();
+        let mismatches = verify_addresses(&debug_data, filename, &mut log_msgs).unwrap();
+        assert_eq!(mismatches, 0);
+        assert_eq!(log_msgs.len(), 1);
+
+        // the same is true for the exe file built with mingw, except for the two placeholder variables
+        // __RUNTIME_PSEUDO_RELOC_LIST__ and __RUNTIME_PSEUDO_RELOC_LIST_END__ of the startup code,
+        // whose symbols are replaced by the linker
+        let filename_exe = OsStr::new("fixtures/bin/update_test.exe");
+        let debug_data_exe = DebugData::load_dwarf(filename_exe, false).unwrap();
+        let mut log_msgs = Vec::new();
+        let mismatches = verify_addresses(&debug_data_exe, filename_exe, &mut log_msgs).unwrap();
+        assert_eq!(mismatches, 2);
+        assert!(log_msgs[0].starts_with("Variable __RUNTIME_PSEUDO_RELOC_LIST"));
+
+        // shift all variables: a systematic offset is reported
+        for varinfo_list in debug_data.variables.values_mut() {
+            for varinfo in varinfo_list {
+                varinfo.address += 0x10;
+            }
+        }
+        let mut log_msgs = Vec::new();
+        let mismatches = verify_addresses(&debug_data, filename, &mut log_msgs).unwrap();
+        assert!(mismatches > 2);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("systematic offset of -0x10")));
+
+        // shift the sections: the layout no longer matches the file
+        for ranges in debug_data.sections.values_mut() {
+            for (start, end) in ranges {
+                *start += 0x1000;
+                *end += 0x1000;
+            }
+        }
+        let mut log_msgs = Vec::new();
+        let result = verify_addresses(&debug_data, filename, &mut log_msgs);
+        // OMAP data only exists in PDB files, so it is not mentioned for an elf file
+        assert!(!result.unwrap_err().contains("OMAP"));
+    }
+}
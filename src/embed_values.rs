@@ -0,0 +1,221 @@
+use crate::datatype::get_datatype_size;
+use crate::debuginfo::MemoryImage;
+use a2lfile::{
+    A2lFile, Annotation, AnnotationLabel, AnnotationOrigin, AnnotationText, Characteristic,
+    CharacteristicType, DataType,
+};
+use std::collections::HashMap;
+
+// the current value of a CHARACTERISTIC is stored in an ANNOTATION using this label
+const VALUE_LABEL: &str = "a2ltool-value";
+
+// larger arrays are not embedded, because the ANNOTATION would be much larger than the CHARACTERISTIC itself
+const MAX_ELEMENTS: usize = 64;
+
+// Read the current values of all VALUE and VAL_BLK CHARACTERISTICs from the initialized data of the elf file,
+// and store them in an ANNOTATION of each CHARACTERISTIC. The values are raw values, i.e. before the
+// COMPU_METHOD is applied, and the elements of a VAL_BLK are separated by spaces.
+// An existing a2ltool-value ANNOTATION is replaced. Returns the number of CHARACTERISTICs whose value was embedded.
+pub(crate) fn embed_values(
+    a2l_file: &mut A2lFile,
+    memory: &MemoryImage,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut count = 0;
+    for module in &mut a2l_file.project.module {
+        // the data type of the values is set in the FNC_VALUES of the RECORD_LAYOUT
+        let value_datatypes: HashMap<&str, DataType> = module
+            .record_layout
+            .iter()
+            .filter_map(|rl| Some((rl.name.as_str(), rl.fnc_values.as_ref()?.datatype)))
+            .collect();
+
+        for characteristic in &mut module.characteristic {
+            let Some(element_count) = get_element_count(characteristic) else {
+                continue;
+            };
+            let Some(datatype) = value_datatypes.get(characteristic.deposit.as_str()) else {
+                log_msgs.push(format!(
+                    "CHARACTERISTIC {}: the value was not embedded, because the RECORD_LAYOUT {} has no FNC_VALUES",
+                    characteristic.name, characteristic.deposit
+                ));
+                continue;
+            };
+            if element_count > MAX_ELEMENTS {
+                log_msgs.push(format!(
+                    "CHARACTERISTIC {}: the value was not embedded, because it has {element_count} elements (at most {MAX_ELEMENTS} are supported)",
+                    characteristic.name
+                ));
+                continue;
+            }
+
+            let mask = characteristic
+                .bit_mask
+                .as_ref()
+                .map(|bm| u64::from(bm.mask))
+                .filter(|mask| *mask != 0);
+            let values: Option<Vec<String>> = (0..element_count)
+                .map(|idx| {
+                    let address = u64::from(characteristic.address)
+                        + idx as u64 * u64::from(get_datatype_size(datatype));
+                    read_value(memory, address, datatype, mask)
+                })
+                .collect();
+            if let Some(values) = values {
+                remove_value_annotation(&mut characteristic.annotation);
+                characteristic
+                    .annotation
+                    .push(make_value_annotation(values.join(" ")));
+                count += 1;
+            } else {
+                log_msgs.push(format!(
+                    "CHARACTERISTIC {}: the value at address 0x{:X} is not part of the initialized data",
+                    characteristic.name, characteristic.address
+                ));
+            }
+        }
+    }
+
+    count
+}
+
+// the number of elements of a scalar VALUE or a VAL_BLK; other types of CHARACTERISTIC are not supported
+fn get_element_count(characteristic: &Characteristic) -> Option<usize> {
+    match characteristic.characteristic_type {
+        CharacteristicType::Value => Some(1),
+        CharacteristicType::ValBlk => {
+            if let Some(matrix_dim) = &characteristic.matrix_dim {
+                Some(
+                    matrix_dim
+                        .dim_list
+                        .iter()
+                        .map(|dim| *dim as usize)
+                        .product(),
+                )
+            } else {
+                // NUMBER was used instead of MATRIX_DIM before a2l version 1.7.0
+                characteristic
+                    .number
+                    .as_ref()
+                    .map(|number| number.number as usize)
+            }
+        }
+        _ => None,
+    }
+}
+
+// read a single value from the memory image and format it as a string
+fn read_value(
+    memory: &MemoryImage,
+    address: u64,
+    datatype: &DataType,
+    mask: Option<u64>,
+) -> Option<String> {
+    let size = u64::from(get_datatype_size(datatype));
+    match datatype {
+        DataType::Float32Ieee => {
+            let bytes: [u8; 4] = memory.read(address, size)?.try_into().ok()?;
+            let value = if memory.big_endian {
+                f32::from_be_bytes(bytes)
+            } else {
+                f32::from_le_bytes(bytes)
+            };
+            Some(value.to_string())
+        }
+        DataType::Float64Ieee => {
+            let bytes: [u8; 8] = memory.read(address, size)?.try_into().ok()?;
+            let value = if memory.big_endian {
+                f64::from_be_bytes(bytes)
+            } else {
+                f64::from_le_bytes(bytes)
+            };
+            Some(value.to_string())
+        }
+        // there is no f16 type in stable rust
+        DataType::Float16Ieee => None,
+        DataType::Sbyte | DataType::Sword | DataType::Slong | DataType::AInt64 => {
+            if let Some(mask) = mask {
+                // a masked value is shifted down, and the sign bit is the highest bit of the mask
+                let raw = memory.read_integer(address, size, false)? as u64;
+                let shift = mask.trailing_zeros();
+                let width = 64 - mask.leading_zeros() - shift;
+                let field = (raw & mask) >> shift;
+                let value = ((field << (64 - width)) as i64) >> (64 - width);
+                Some(value.to_string())
+            } else {
+                Some(memory.read_integer(address, size, true)?.to_string())
+            }
+        }
+        _ => {
+            let raw = memory.read_integer(address, size, false)? as u64;
+            if let Some(mask) = mask {
+                Some(((raw & mask) >> mask.trailing_zeros()).to_string())
+            } else {
+                Some(raw.to_string())
+            }
+        }
+    }
+}
+
+fn remove_value_annotation(annotations: &mut Vec<Annotation>) {
+    annotations.retain(|annotation| {
+        annotation
+            .annotation_label
+            .as_ref()
+            .is_none_or(|label| label.label != VALUE_LABEL)
+    });
+}
+
+fn make_value_annotation(value_text: String) -> Annotation {
+    let mut annotation = Annotation::new();
+    annotation.annotation_label = Some(AnnotationLabel::new(VALUE_LABEL.to_string()));
+    annotation.annotation_origin = Some(AnnotationOrigin::new("a2ltool".to_string()));
+    let mut annotation_text = AnnotationText::new();
+    annotation_text.annotation_text_list.push(value_text);
+    annotation.annotation_text = Some(annotation_text);
+    annotation
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_value() {
+        let memory = MemoryImage {
+            big_endian: false,
+            segments: vec![(
+                0x100,
+                vec![0xfe, 0xff, 0x34, 0x12, 0x00, 0x00, 0x20, 0x40, 0xa5, 0x00],
+            )],
+        };
+        assert_eq!(
+            read_value(&memory, 0x100, &DataType::Uword, None).as_deref(),
+            Some("65534")
+        );
+        assert_eq!(
+            read_value(&memory, 0x100, &DataType::Sword, None).as_deref(),
+            Some("-2")
+        );
+        assert_eq!(
+            read_value(&memory, 0x102, &DataType::Uword, None).as_deref(),
+            Some("4660")
+        );
+        assert_eq!(
+            read_value(&memory, 0x104, &DataType::Float32Ieee, None).as_deref(),
+            Some("2.5")
+        );
+        // bits 4-7 of 0xa5 contain 0xa, which is -6 as a signed 4-bit value
+        assert_eq!(
+            read_value(&memory, 0x108, &DataType::Ubyte, Some(0xf0)).as_deref(),
+            Some("10")
+        );
+        assert_eq!(
+            read_value(&memory, 0x108, &DataType::Sbyte, Some(0xf0)).as_deref(),
+            Some("-6")
+        );
+        // the value must be entirely inside of the initialized data
+        assert_eq!(read_value(&memory, 0x109, &DataType::Uword, None), None);
+        assert_eq!(read_value(&memory, 0x200, &DataType::Ubyte, None), None);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::formats::FormatSpec;
+use a2lfile::{A2lFile, Format};
+use regex::Regex;
+use std::ffi::OsStr;
+
+pub(crate) struct FormatMapEntry {
+    regex_text: String,
+    regex: Regex,
+    format: String,
+}
+
+// Load a format map file.
+// Each line contains a regex for object names and a FORMAT string, separated by ';', e.g. "^Speed_.*; %8.3"
+// Empty lines and lines starting with '#' are ignored.
+pub(crate) fn load_format_map(
+    filename: &OsStr,
+    log_msgs: &mut Vec<String>,
+) -> Result<Vec<FormatMapEntry>, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the format map from {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    parse_format_map(&text, log_msgs)
+        .map_err(|err| format!("{}: {err}", filename.to_string_lossy()))
+}
+
+fn parse_format_map(text: &str, log_msgs: &mut Vec<String>) -> Result<Vec<FormatMapEntry>, String> {
+    let mut entries = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((regex_text, format)) = line.rsplit_once(';') else {
+            return Err(format!(
+                "line {line_num}: expected a regex and a format string separated by ';'"
+            ));
+        };
+        let regex_text = regex_text.trim();
+        let format = format.trim().trim_matches('"');
+
+        // like in --remove, the regex must match the whole name, not just a substring
+        let extended_regex = if !regex_text.starts_with('^') && !regex_text.ends_with('$') {
+            format!("^{regex_text}$")
+        } else {
+            regex_text.to_string()
+        };
+        let regex = Regex::new(&extended_regex)
+            .map_err(|err| format!("line {line_num}: invalid regex \"{regex_text}\": {err}"))?;
+
+        if !is_valid_format(format) {
+            log_msgs.push(format!(
+                "Warning: line {line_num}: \"{format}\" is not a valid format string (expected %Length.Layout, e.g. \"%8.3\"); the line is ignored"
+            ));
+            continue;
+        }
+
+        entries.push(FormatMapEntry {
+            regex_text: regex_text.to_string(),
+            regex,
+            format: format.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+// a FORMAT string has the form %Length.Layout; either the length or the layout may be omitted
+fn is_valid_format(format: &str) -> bool {
+    FormatSpec::parse(format).is_some()
+}
+
+// set the FORMAT of all MEASUREMENTs, CHARACTERISTICs and AXIS_PTS whose names match an entry of the format map
+// If several entries match the same object, the last one is used.
+// Returns the number of objects whose FORMAT was set.
+pub(crate) fn apply_format_map(
+    a2l_file: &mut A2lFile,
+    entries: &[FormatMapEntry],
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut match_counts = vec![0; entries.len()];
+    let mut changed_count = 0;
+
+    let mut set_format = |name: &str, opt_format: &mut Option<Format>| {
+        let mut new_format = None;
+        for (idx, entry) in entries.iter().enumerate() {
+            if entry.regex.is_match(name) {
+                match_counts[idx] += 1;
+                new_format = Some(&entry.format);
+            }
+        }
+        if let Some(new_format) = new_format {
+            if let Some(format) = opt_format {
+                format.format_string.clone_from(new_format);
+            } else {
+                *opt_format = Some(Format::new(new_format.clone()));
+            }
+            changed_count += 1;
+        }
+    };
+
+    for module in &mut a2l_file.project.module {
+        for measurement in &mut module.measurement {
+            set_format(&measurement.name, &mut measurement.format);
+        }
+        for characteristic in &mut module.characteristic {
+            set_format(&characteristic.name, &mut characteristic.format);
+        }
+        for axis_pts in &mut module.axis_pts {
+            set_format(&axis_pts.name, &mut axis_pts.format);
+        }
+    }
+
+    for (entry, count) in entries.iter().zip(match_counts) {
+        if count == 0 {
+            log_msgs.push(format!(
+                "Warning: the format map regex \"{}\" did not match any object",
+                entry.regex_text
+            ));
+        }
+    }
+
+    changed_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Speed_Front "" FLOAT32_IEEE NO_COMPU_METHOD 0 0 0 100
+      FORMAT "%8.2"
+    /end MEASUREMENT
+    /begin MEASUREMENT Speed_Rear "" FLOAT32_IEEE NO_COMPU_METHOD 0 0 0 100
+    /end MEASUREMENT
+    /begin MEASUREMENT Temperature "" FLOAT32_IEEE NO_COMPU_METHOD 0 0 0 100
+      FORMAT "%8.2"
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Speed_Limit "" VALUE 0x1000 RL 0 NO_COMPU_METHOD 0 100
+    /end CHARACTERISTIC
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_is_valid_format() {
+        assert!(is_valid_format("%.6"));
+        assert!(is_valid_format("%8.3"));
+        assert!(is_valid_format("%10.0"));
+        assert!(!is_valid_format("8.3"));
+        assert!(!is_valid_format("%8."));
+        assert!(!is_valid_format("%"));
+        assert!(!is_valid_format("%8.3f"));
+    }
+
+    #[test]
+    fn test_apply_format_map() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let text =
+            "# precision for speeds\nSpeed_.*; \"%.6\"\nTemperature; %8.x\nPressure_.*; %4.1\n";
+        let entries = parse_format_map(text, &mut log_msgs).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(log_msgs.len(), 1);
+        assert!(log_msgs[0].contains("line 3"));
+
+        let count = apply_format_map(&mut a2l_file, &entries, &mut log_msgs);
+        assert_eq!(count, 3);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(
+            module.measurement[0].format.as_ref().unwrap().format_string,
+            "%.6"
+        );
+        assert_eq!(
+            module.measurement[1].format.as_ref().unwrap().format_string,
+            "%.6"
+        );
+        assert_eq!(
+            module.measurement[2].format.as_ref().unwrap().format_string,
+            "%8.2"
+        );
+        assert_eq!(
+            module.characteristic[0]
+                .format
+                .as_ref()
+                .unwrap()
+                .format_string,
+            "%.6"
+        );
+        assert_eq!(log_msgs.len(), 2);
+        assert!(log_msgs[1].contains("Pressure_.*"));
+
+        // an invalid regex is an error
+        assert!(parse_format_map("Speed_(; %.6", &mut Vec::new()).is_err());
+    }
+}
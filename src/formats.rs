@@ -0,0 +1,335 @@
+use a2lfile::{A2lFile, A2lObject};
+use std::fmt::Display;
+
+// The parsed form of a FORMAT string %Length.Layout; either the length or the layout may be omitted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct FormatSpec {
+    pub(crate) length: Option<u32>,
+    pub(crate) layout: Option<u32>,
+}
+
+impl FormatSpec {
+    // parse a FORMAT string that is in the canonical form required by the specification, e.g. "%8.3" or "%.6"
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let spec = text.strip_prefix('%')?;
+        let parse_number = |text: &str| {
+            if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+                text.parse::<u32>().ok()
+            } else {
+                None
+            }
+        };
+        match spec.split_once('.') {
+            Some(("", layout)) => Some(Self {
+                length: None,
+                layout: Some(parse_number(layout)?),
+            }),
+            Some((length, layout)) => Some(Self {
+                length: Some(parse_number(length)?),
+                layout: Some(parse_number(layout)?),
+            }),
+            None => Some(Self {
+                length: Some(parse_number(spec)?),
+                layout: None,
+            }),
+        }
+    }
+
+    // parse a FORMAT string that deviates from the canonical form in a way that can be corrected:
+    // surrounding whitespace, a missing '%', printf flags like "%-8.3", and printf conversions like "%8.3f" or "%5d"
+    pub(crate) fn parse_lenient(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix('%').unwrap_or(text);
+        let text = text.trim_start_matches(['-', '+', ' ', '#']);
+
+        // the conversion character and an optional length modifier, e.g. "lf"
+        let (text, conversion) = match text.char_indices().last() {
+            Some((pos, c)) if "fFeEgGdiu".contains(c) => {
+                (text[..pos].trim_end_matches(['l', 'L', 'h']), Some(c))
+            }
+            _ => (text, None),
+        };
+
+        if text.is_empty() {
+            // printf uses 6 fractional digits by default, integer conversions have none
+            return match conversion {
+                Some('d' | 'i' | 'u') => Some(Self {
+                    length: None,
+                    layout: Some(0),
+                }),
+                Some(_) => Some(Self {
+                    length: None,
+                    layout: Some(6),
+                }),
+                None => None,
+            };
+        }
+
+        let spec = Self::parse(&format!("%{text}"))?;
+        if matches!(conversion, Some('d' | 'i' | 'u')) && spec.layout.is_none() {
+            // "%5d" displays integers without fractional digits
+            Some(Self {
+                layout: Some(0),
+                ..spec
+            })
+        } else {
+            Some(spec)
+        }
+    }
+}
+
+impl Display for FormatSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%")?;
+        if let Some(length) = self.length {
+            write!(f, "{length}")?;
+        }
+        if let Some(layout) = self.layout {
+            write!(f, ".{layout}")?;
+        }
+        Ok(())
+    }
+}
+
+// Check the FORMAT strings of all COMPU_METHODs, MEASUREMENTs, CHARACTERISTICs, AXIS_PTS, AXIS_DESCRs and TYPEDEFs.
+// Format strings that are not in the form %Length.Layout are reported. If fix is set, recoverable deviations
+// like "%8.3f" or "8.3" are rewritten into the canonical form; all other invalid format strings are only reported.
+// Returns the number of invalid format strings.
+pub(crate) fn check_formats(
+    a2l_file: &mut A2lFile,
+    fix: bool,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut problem_count = 0;
+
+    let mut check = |blocktype: &str, name: &str, line: u32, format: &mut String| {
+        if FormatSpec::parse(format).is_some() {
+            return;
+        }
+        problem_count += 1;
+        match FormatSpec::parse_lenient(format) {
+            Some(spec) if fix => {
+                log_msgs.push(format!(
+                    "{blocktype} {name} on line {line}: the FORMAT \"{format}\" was changed to \"{spec}\""
+                ));
+                *format = spec.to_string();
+            }
+            Some(spec) => log_msgs.push(format!(
+                "{blocktype} {name} on line {line}: the FORMAT \"{format}\" is not valid, it should be \"{spec}\""
+            )),
+            None => log_msgs.push(format!(
+                "{blocktype} {name} on line {line}: the FORMAT \"{format}\" is not valid and can not be corrected"
+            )),
+        }
+    };
+
+    for module in &mut a2l_file.project.module {
+        for compu_method in &mut module.compu_method {
+            let line = compu_method.get_line();
+            check(
+                "COMPU_METHOD",
+                &compu_method.name,
+                line,
+                &mut compu_method.format,
+            );
+        }
+        for measurement in &mut module.measurement {
+            let line = measurement.get_line();
+            if let Some(format) = &mut measurement.format {
+                check(
+                    "MEASUREMENT",
+                    &measurement.name,
+                    line,
+                    &mut format.format_string,
+                );
+            }
+        }
+        for characteristic in &mut module.characteristic {
+            let line = characteristic.get_line();
+            if let Some(format) = &mut characteristic.format {
+                check(
+                    "CHARACTERISTIC",
+                    &characteristic.name,
+                    line,
+                    &mut format.format_string,
+                );
+            }
+            for axis_descr in &mut characteristic.axis_descr {
+                let line = axis_descr.get_line();
+                if let Some(format) = &mut axis_descr.format {
+                    check(
+                        "AXIS_DESCR of CHARACTERISTIC",
+                        &characteristic.name,
+                        line,
+                        &mut format.format_string,
+                    );
+                }
+            }
+        }
+        for axis_pts in &mut module.axis_pts {
+            let line = axis_pts.get_line();
+            if let Some(format) = &mut axis_pts.format {
+                check("AXIS_PTS", &axis_pts.name, line, &mut format.format_string);
+            }
+        }
+        for typedef_axis in &mut module.typedef_axis {
+            let line = typedef_axis.get_line();
+            if let Some(format) = &mut typedef_axis.format {
+                check(
+                    "TYPEDEF_AXIS",
+                    &typedef_axis.name,
+                    line,
+                    &mut format.format_string,
+                );
+            }
+        }
+        for typedef_characteristic in &mut module.typedef_characteristic {
+            let line = typedef_characteristic.get_line();
+            if let Some(format) = &mut typedef_characteristic.format {
+                check(
+                    "TYPEDEF_CHARACTERISTIC",
+                    &typedef_characteristic.name,
+                    line,
+                    &mut format.format_string,
+                );
+            }
+            for axis_descr in &mut typedef_characteristic.axis_descr {
+                let line = axis_descr.get_line();
+                if let Some(format) = &mut axis_descr.format {
+                    check(
+                        "AXIS_DESCR of TYPEDEF_CHARACTERISTIC",
+                        &typedef_characteristic.name,
+                        line,
+                        &mut format.format_string,
+                    );
+                }
+            }
+        }
+        for typedef_measurement in &mut module.typedef_measurement {
+            let line = typedef_measurement.get_line();
+            if let Some(format) = &mut typedef_measurement.format {
+                check(
+                    "TYPEDEF_MEASUREMENT",
+                    &typedef_measurement.name,
+                    line,
+                    &mut format.format_string,
+                );
+            }
+        }
+    }
+
+    problem_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        let spec = |length, layout| Some(FormatSpec { length, layout });
+        assert_eq!(FormatSpec::parse("%8.3"), spec(Some(8), Some(3)));
+        assert_eq!(FormatSpec::parse("%.6"), spec(None, Some(6)));
+        assert_eq!(FormatSpec::parse("%10.0"), spec(Some(10), Some(0)));
+        assert_eq!(FormatSpec::parse("%5"), spec(Some(5), None));
+        assert_eq!(FormatSpec::parse("8.3"), None);
+        assert_eq!(FormatSpec::parse("%8."), None);
+        assert_eq!(FormatSpec::parse("%"), None);
+        assert_eq!(FormatSpec::parse("%."), None);
+        assert_eq!(FormatSpec::parse("%8.3f"), None);
+        assert_eq!(FormatSpec::parse("% 8.3"), None);
+        assert_eq!(FormatSpec::parse("%-8.3"), None);
+        assert_eq!(FormatSpec::parse(""), None);
+    }
+
+    #[test]
+    fn test_parse_format_lenient() {
+        let normalize = |text: &str| FormatSpec::parse_lenient(text).map(|spec| spec.to_string());
+        // canonical format strings are not changed
+        assert_eq!(normalize("%8.3").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%.6").as_deref(), Some("%.6"));
+        assert_eq!(normalize("%5").as_deref(), Some("%5"));
+        // recoverable deviations
+        assert_eq!(normalize("%8.3f").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%8.3lf").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%8.3e").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("8.3").as_deref(), Some("%8.3"));
+        assert_eq!(normalize(" %8.3 ").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%-8.3").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%+6.2f").as_deref(), Some("%6.2"));
+        assert_eq!(normalize("%08.3").as_deref(), Some("%8.3"));
+        assert_eq!(normalize("%5d").as_deref(), Some("%5.0"));
+        assert_eq!(normalize("%d").as_deref(), Some("%.0"));
+        assert_eq!(normalize("%f").as_deref(), Some("%.6"));
+        // unrecoverable
+        assert_eq!(normalize(""), None);
+        assert_eq!(normalize("%"), None);
+        assert_eq!(normalize("%s"), None);
+        assert_eq!(normalize("%8.3x"), None);
+        assert_eq!(normalize("abc"), None);
+        assert_eq!(normalize("%8.x"), None);
+        assert_eq!(normalize("%8,3"), None);
+    }
+
+    #[test]
+    fn test_check_formats() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD Valid "" IDENTICAL "%8.3" ""
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Printf "" IDENTICAL "%8.3f" ""
+    /end COMPU_METHOD
+    /begin MEASUREMENT No_Percent "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      FORMAT "6.2"
+    /end MEASUREMENT
+    /begin MEASUREMENT Broken "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      FORMAT "%s"
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Curve "" CURVE 0x1000 RL 0 NO_COMPU_METHOD 0 255
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 8 0 255
+        FORMAT "%5d"
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin TYPEDEF_MEASUREMENT Typedef_Meas "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      FORMAT "%-4.1"
+    /end TYPEDEF_MEASUREMENT
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let original = a2l_file.clone();
+
+        // without fix, the problems are only reported
+        let mut log_msgs = Vec::new();
+        let count = check_formats(&mut a2l_file, false, &mut log_msgs);
+        assert_eq!(count, 5);
+        assert_eq!(a2l_file, original);
+        assert!(log_msgs[0].contains("COMPU_METHOD Printf on line 6"));
+        assert!(log_msgs[0].contains("it should be \"%8.3\""));
+
+        let mut log_msgs = Vec::new();
+        let count = check_formats(&mut a2l_file, true, &mut log_msgs);
+        assert_eq!(count, 5);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.compu_method[0].format, "%8.3");
+        assert_eq!(module.compu_method[1].format, "%8.3");
+        let format_string =
+            |format: &Option<a2lfile::Format>| format.as_ref().unwrap().format_string.clone();
+        assert_eq!(format_string(&module.measurement[0].format), "%6.2");
+        // the unrecoverable format string is not changed
+        assert_eq!(format_string(&module.measurement[1].format), "%s");
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("MEASUREMENT Broken") && msg.contains("can not be corrected")));
+        assert_eq!(
+            format_string(&module.characteristic[0].axis_descr[0].format),
+            "%5.0"
+        );
+        assert_eq!(format_string(&module.typedef_measurement[0].format), "%4.1");
+
+        // after the fix only the unrecoverable format string remains
+        let mut log_msgs = Vec::new();
+        assert_eq!(check_formats(&mut a2l_file, true, &mut log_msgs), 1);
+    }
+}
@@ -0,0 +1,627 @@
+use a2lfile::{A2lFile, Module};
+use std::collections::{HashMap, HashSet};
+
+// Collect the names of all objects that are referenced by the given GROUPs and FUNCTIONs.
+// Sub-groups and sub-functions are followed, as well as the FUNCTION_LIST of each GROUP. The AXIS_PTS
+// referenced by the AXIS_DESCRs of the selected CHARACTERISTICs are also included.
+// Each group and function name must exist in at least one MODULE.
+pub(crate) fn collect_group_members(
+    a2l_file: &A2lFile,
+    group_names: &[&str],
+    function_names: &[&str],
+) -> Result<HashSet<String>, String> {
+    let mut members = HashSet::new();
+    let mut found_groups = HashSet::new();
+    let mut found_functions = HashSet::new();
+
+    for module in &a2l_file.project.module {
+        let mut collector = GroupCollector::new(module);
+        for group_name in group_names {
+            if collector.groups.contains_key(group_name) {
+                collector.add_group(group_name);
+                found_groups.insert(*group_name);
+            }
+        }
+        for function_name in function_names {
+            if collector.functions.contains_key(function_name) {
+                collector.add_function(function_name);
+                found_functions.insert(*function_name);
+            }
+        }
+        collector.add_axis_pts_refs();
+        members.extend(collector.members);
+    }
+
+    let mut errors = Vec::new();
+    for group_name in group_names {
+        if !found_groups.contains(group_name) {
+            errors.push(format!("there is no GROUP {group_name}"));
+        }
+    }
+    for function_name in function_names {
+        if !found_functions.contains(function_name) {
+            errors.push(format!("there is no FUNCTION {function_name}"));
+        }
+    }
+    if errors.is_empty() {
+        Ok(members)
+    } else {
+        Err(errors.join("\n  "))
+    }
+}
+
+// Reduce the file to the given GROUPs and everything they depend on: the GROUPs themselves with
+// their sub-groups and FUNCTIONs, the objects referenced by them (see collect_group_members), the
+// MEASUREMENTs used as INPUT_QUANTITY or COMPARISON_QUANTITY by these objects, the TYPEDEFs of the
+// selected INSTANCEs, and the RECORD_LAYOUTs and conversions that are still referenced.
+// MODULEs that contain none of the GROUPs are removed entirely.
+// Returns the number of removed objects.
+pub(crate) fn extract_groups(
+    a2l_file: &mut A2lFile,
+    group_names: &[&str],
+    log_msgs: &mut Vec<String>,
+) -> Result<usize, String> {
+    // fail before anything is modified if any of the groups doesn't exist
+    collect_group_members(a2l_file, group_names, &[])?;
+
+    let mut removed_count = 0;
+    a2l_file.project.module.retain(|module| {
+        let keep = module
+            .group
+            .iter()
+            .any(|group| group_names.contains(&group.name.as_str()));
+        if !keep {
+            log_msgs.push(format!("Removed MODULE {}", module.name));
+        }
+        keep
+    });
+
+    for module in &mut a2l_file.project.module {
+        let mut collector = GroupCollector::new(module);
+        for group_name in group_names {
+            collector.add_group(group_name);
+        }
+        collector.add_axis_pts_refs();
+        collector.add_quantity_refs();
+        let kept_groups: HashSet<String> = collector
+            .visited_groups
+            .iter()
+            .map(|idx| module.group[*idx].name.clone())
+            .collect();
+        let kept_functions: HashSet<String> = collector
+            .visited_functions
+            .iter()
+            .map(|idx| module.function[*idx].name.clone())
+            .collect();
+        let members = collector.members;
+
+        let mut count = retain_named(
+            &mut module.characteristic,
+            |item| item.name.as_str(),
+            &members,
+        );
+        count += retain_named(&mut module.measurement, |item| item.name.as_str(), &members);
+        count += retain_named(&mut module.axis_pts, |item| item.name.as_str(), &members);
+        count += retain_named(&mut module.blob, |item| item.name.as_str(), &members);
+        count += retain_named(&mut module.instance, |item| item.name.as_str(), &members);
+        count += retain_named(&mut module.group, |item| item.name.as_str(), &kept_groups);
+        count += retain_named(
+            &mut module.function,
+            |item| item.name.as_str(),
+            &kept_functions,
+        );
+
+        let used_typedefs = get_used_typedefs(module);
+        count += retain_named(
+            &mut module.typedef_axis,
+            |item| item.name.as_str(),
+            &used_typedefs,
+        );
+        count += retain_named(
+            &mut module.typedef_blob,
+            |item| item.name.as_str(),
+            &used_typedefs,
+        );
+        count += retain_named(
+            &mut module.typedef_characteristic,
+            |item| item.name.as_str(),
+            &used_typedefs,
+        );
+        count += retain_named(
+            &mut module.typedef_measurement,
+            |item| item.name.as_str(),
+            &used_typedefs,
+        );
+        count += retain_named(
+            &mut module.typedef_structure,
+            |item| item.name.as_str(),
+            &used_typedefs,
+        );
+
+        let used_record_layouts: HashSet<String> = module
+            .characteristic
+            .iter()
+            .map(|characteristic| characteristic.deposit.clone())
+            .chain(
+                module
+                    .axis_pts
+                    .iter()
+                    .map(|axis_pts| axis_pts.deposit_record.clone()),
+            )
+            .chain(
+                module
+                    .typedef_characteristic
+                    .iter()
+                    .map(|td_char| td_char.record_layout.clone()),
+            )
+            .chain(
+                module
+                    .typedef_axis
+                    .iter()
+                    .map(|td_axis| td_axis.record_layout.clone()),
+            )
+            .collect();
+        count += retain_named(
+            &mut module.record_layout,
+            |item| item.name.as_str(),
+            &used_record_layouts,
+        );
+
+        log_msgs.push(format!(
+            "Removed {count} objects from MODULE {} that are not needed by the extracted groups",
+            module.name
+        ));
+        removed_count += count;
+    }
+
+    // the conversions are only referenced by the objects, so they can be cleaned up last
+    removed_count += crate::remove::remove_unused_conversions(a2l_file, log_msgs);
+
+    Ok(removed_count)
+}
+
+// keep only the items whose name is in the set, and return the number of removed items
+fn retain_named<T>(
+    items: &mut Vec<T>,
+    get_name: impl Fn(&T) -> &str,
+    keep: &HashSet<String>,
+) -> usize {
+    let len = items.len();
+    items.retain(|item| keep.contains(get_name(item)));
+    len - items.len()
+}
+
+// the TYPEDEFs that are used by the INSTANCEs, directly or as a component of a TYPEDEF_STRUCTURE
+fn get_used_typedefs(module: &Module) -> HashSet<String> {
+    let mut used_typedefs = HashSet::new();
+    let mut pending: Vec<&str> = module
+        .instance
+        .iter()
+        .map(|instance| instance.type_ref.as_str())
+        .collect();
+    while let Some(typedef_name) = pending.pop() {
+        if !used_typedefs.insert(typedef_name.to_string()) {
+            continue;
+        }
+        if let Some(typedef_structure) = module
+            .typedef_structure
+            .iter()
+            .find(|td_struct| td_struct.name == typedef_name)
+        {
+            pending.extend(
+                typedef_structure
+                    .structure_component
+                    .iter()
+                    .map(|component| component.component_type.as_str()),
+            );
+        }
+    }
+    used_typedefs
+}
+
+// Find cycles in the SUB_GROUP references of the GROUPs in each module.
+// A GROUP that contains itself directly or indirectly causes endless loops in tools that walk the group hierarchy.
+// Each cycle is reported once, as the path of group names that leads back to its start.
+// Returns the number of cycles.
+pub(crate) fn check_circular_groups(a2l_file: &A2lFile, log_msgs: &mut Vec<String>) -> usize {
+    let mut cycle_count = 0;
+
+    for module in &a2l_file.project.module {
+        let groups: HashMap<&str, usize> = module
+            .group
+            .iter()
+            .enumerate()
+            .map(|(idx, group)| (group.name.as_str(), idx))
+            .collect();
+        let mut finished = vec![false; module.group.len()];
+
+        for start_idx in 0..module.group.len() {
+            if finished[start_idx] {
+                continue;
+            }
+            // depth-first traversal: each entry of the path holds a group and the position of the next sub-group to visit
+            let mut path: Vec<(usize, usize)> = vec![(start_idx, 0)];
+            while let Some((idx, pos)) = path.last_mut() {
+                let group = &module.group[*idx];
+                let sub_groups = group
+                    .sub_group
+                    .as_ref()
+                    .map_or(&[][..], |sub_group| &sub_group.identifier_list[..]);
+                let Some(sub_group_name) = sub_groups.get(*pos) else {
+                    finished[*idx] = true;
+                    path.pop();
+                    continue;
+                };
+                *pos += 1;
+                let Some(&sub_idx) = groups.get(sub_group_name.as_str()) else {
+                    // missing groups are reported by the consistency check
+                    continue;
+                };
+                if let Some(cycle_start) =
+                    path.iter().position(|(path_idx, _)| *path_idx == sub_idx)
+                {
+                    let cycle: Vec<&str> = path[cycle_start..]
+                        .iter()
+                        .map(|(path_idx, _)| module.group[*path_idx].name.as_str())
+                        .chain(std::iter::once(sub_group_name.as_str()))
+                        .collect();
+                    log_msgs.push(format!(
+                        "Circular SUB_GROUP reference in module {}: {}",
+                        module.name,
+                        cycle.join(" -> ")
+                    ));
+                    cycle_count += 1;
+                } else if !finished[sub_idx] {
+                    path.push((sub_idx, 0));
+                }
+            }
+        }
+    }
+
+    cycle_count
+}
+
+struct GroupCollector<'a> {
+    module: &'a Module,
+    groups: HashMap<&'a str, usize>,
+    functions: HashMap<&'a str, usize>,
+    // groups and functions that were already visited, so that circular references don't cause an endless loop
+    visited_groups: HashSet<usize>,
+    visited_functions: HashSet<usize>,
+    members: HashSet<String>,
+}
+
+impl<'a> GroupCollector<'a> {
+    fn new(module: &'a Module) -> Self {
+        let groups = module
+            .group
+            .iter()
+            .enumerate()
+            .map(|(idx, group)| (group.name.as_str(), idx))
+            .collect();
+        let functions = module
+            .function
+            .iter()
+            .enumerate()
+            .map(|(idx, function)| (function.name.as_str(), idx))
+            .collect();
+        Self {
+            module,
+            groups,
+            functions,
+            visited_groups: HashSet::new(),
+            visited_functions: HashSet::new(),
+            members: HashSet::new(),
+        }
+    }
+
+    fn add_group(&mut self, name: &str) {
+        let Some(&idx) = self.groups.get(name) else {
+            return;
+        };
+        if !self.visited_groups.insert(idx) {
+            return;
+        }
+        let module = self.module;
+        let group = &module.group[idx];
+        if let Some(ref_characteristic) = &group.ref_characteristic {
+            self.members
+                .extend(ref_characteristic.identifier_list.iter().cloned());
+        }
+        if let Some(ref_measurement) = &group.ref_measurement {
+            self.members
+                .extend(ref_measurement.identifier_list.iter().cloned());
+        }
+        if let Some(function_list) = &group.function_list {
+            for function_name in &function_list.name_list {
+                self.add_function(function_name);
+            }
+        }
+        if let Some(sub_group) = &group.sub_group {
+            for sub_group_name in &sub_group.identifier_list {
+                self.add_group(sub_group_name);
+            }
+        }
+    }
+
+    fn add_function(&mut self, name: &str) {
+        let Some(&idx) = self.functions.get(name) else {
+            return;
+        };
+        if !self.visited_functions.insert(idx) {
+            return;
+        }
+        let module = self.module;
+        let function = &module.function[idx];
+        if let Some(def_characteristic) = &function.def_characteristic {
+            self.members
+                .extend(def_characteristic.identifier_list.iter().cloned());
+        }
+        if let Some(ref_characteristic) = &function.ref_characteristic {
+            self.members
+                .extend(ref_characteristic.identifier_list.iter().cloned());
+        }
+        if let Some(in_measurement) = &function.in_measurement {
+            self.members
+                .extend(in_measurement.identifier_list.iter().cloned());
+        }
+        if let Some(out_measurement) = &function.out_measurement {
+            self.members
+                .extend(out_measurement.identifier_list.iter().cloned());
+        }
+        if let Some(loc_measurement) = &function.loc_measurement {
+            self.members
+                .extend(loc_measurement.identifier_list.iter().cloned());
+        }
+        if let Some(sub_function) = &function.sub_function {
+            for sub_function_name in &sub_function.identifier_list {
+                self.add_function(sub_function_name);
+            }
+        }
+    }
+
+    // the axes of the selected CHARACTERISTICs belong to the selection
+    fn add_axis_pts_refs(&mut self) {
+        let axis_pts_names: Vec<String> = self
+            .module
+            .characteristic
+            .iter()
+            .filter(|characteristic| self.members.contains(&characteristic.name))
+            .flat_map(|characteristic| &characteristic.axis_descr)
+            .filter_map(|axis_descr| axis_descr.axis_pts_ref.as_ref())
+            .map(|axis_pts_ref| axis_pts_ref.axis_points.clone())
+            .collect();
+        self.members.extend(axis_pts_names);
+    }
+
+    // the MEASUREMENTs used as INPUT_QUANTITY or COMPARISON_QUANTITY by the selected objects are needed too
+    fn add_quantity_refs(&mut self) {
+        let axis_pts_quantities = self
+            .module
+            .axis_pts
+            .iter()
+            .filter(|axis_pts| self.members.contains(&axis_pts.name))
+            .map(|axis_pts| &axis_pts.input_quantity);
+        let characteristic_quantities = self
+            .module
+            .characteristic
+            .iter()
+            .filter(|characteristic| self.members.contains(&characteristic.name))
+            .flat_map(|characteristic| {
+                characteristic
+                    .axis_descr
+                    .iter()
+                    .map(|axis_descr| &axis_descr.input_quantity)
+                    .chain(
+                        characteristic
+                            .comparison_quantity
+                            .iter()
+                            .map(|comparison_quantity| &comparison_quantity.name),
+                    )
+            });
+        let quantity_names: Vec<String> = axis_pts_quantities
+            .chain(characteristic_quantities)
+            .filter(|name| *name != "NO_INPUT_QUANTITY")
+            .cloned()
+            .collect();
+        self.members.extend(quantity_names);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_circular_groups() {
+        let a2l_file = a2lfile::load(
+            "fixtures/a2l/circular_groups_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let mut log_msgs = Vec::new();
+        let count = check_circular_groups(&a2l_file, &mut log_msgs);
+        assert_eq!(count, 2);
+        assert!(log_msgs[0].contains("Group_A -> Group_B -> Group_C -> Group_A"));
+        assert!(log_msgs[1].contains("Self_Ref -> Self_Ref"));
+
+        // a valid hierarchy has no cycles
+        let a2l_file = a2lfile::load(
+            "fixtures/a2l/update_group_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let mut log_msgs = Vec::new();
+        assert_eq!(check_circular_groups(&a2l_file, &mut log_msgs), 0);
+        assert!(log_msgs.is_empty());
+    }
+
+    #[test]
+    fn test_collect_group_members() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Curve_A "" CURVE 0 RL 0 NO_COMPU_METHOD 0 100
+      /begin AXIS_DESCR COM_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 8 0 100
+        AXIS_PTS_REF Axis_A
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin GROUP Root ""
+      ROOT
+      /begin SUB_GROUP Child /end SUB_GROUP
+      /begin REF_MEASUREMENT Meas_Root /end REF_MEASUREMENT
+    /end GROUP
+    /begin GROUP Child ""
+      /begin SUB_GROUP Root /end SUB_GROUP
+      /begin REF_CHARACTERISTIC Curve_A /end REF_CHARACTERISTIC
+      /begin FUNCTION_LIST Func_A /end FUNCTION_LIST
+    /end GROUP
+    /begin GROUP Other ""
+      /begin REF_MEASUREMENT Meas_Other /end REF_MEASUREMENT
+    /end GROUP
+    /begin FUNCTION Func_A ""
+      /begin OUT_MEASUREMENT Meas_Func /end OUT_MEASUREMENT
+      /begin SUB_FUNCTION Func_B /end SUB_FUNCTION
+    /end FUNCTION
+    /begin FUNCTION Func_B ""
+      /begin DEF_CHARACTERISTIC Char_Func /end DEF_CHARACTERISTIC
+    /end FUNCTION
+  /end MODULE
+/end PROJECT"#;
+        let a2l_file = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+
+        // the circular reference between Root and Child is harmless
+        let members = collect_group_members(&a2l_file, &["Root"], &[]).unwrap();
+        let expected: HashSet<String> =
+            ["Meas_Root", "Curve_A", "Axis_A", "Meas_Func", "Char_Func"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+        assert_eq!(members, expected);
+
+        let members = collect_group_members(&a2l_file, &["Other"], &["Func_B"]).unwrap();
+        assert_eq!(members.len(), 2);
+        assert!(members.contains("Meas_Other"));
+        assert!(members.contains("Char_Func"));
+
+        let result = collect_group_members(&a2l_file, &["Missing"], &["Func_A"]);
+        assert_eq!(result.unwrap_err(), "there is no GROUP Missing");
+    }
+
+    #[test]
+    fn test_extract_groups() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin AXIS_PTS Axis_A "" 0 Speed RL_Axis 0 CM_Axis 4 0 100
+    /end AXIS_PTS
+    /begin CHARACTERISTIC Curve_A "" CURVE 0 RL_Curve 0 CM_Curve 0 100
+      /begin AXIS_DESCR COM_AXIS Speed CM_Axis 4 0 100
+        AXIS_PTS_REF Axis_A
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Value_B "" VALUE 0 RL_Value 0 CM_Other 0 100
+    /end CHARACTERISTIC
+    /begin MEASUREMENT Speed "" UWORD NO_COMPU_METHOD 0 0 0 100
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_A "" UBYTE NO_COMPU_METHOD 0 0 0 100
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_B "" UBYTE NO_COMPU_METHOD 0 0 0 100
+    /end MEASUREMENT
+    /begin INSTANCE Inst_A "" T_Struct 0
+    /end INSTANCE
+    /begin INSTANCE Inst_B "" T_Other 0
+    /end INSTANCE
+    /begin TYPEDEF_STRUCTURE T_Struct "" 4
+      /begin STRUCTURE_COMPONENT value T_Value 0
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+    /begin TYPEDEF_MEASUREMENT T_Value "" ULONG NO_COMPU_METHOD 0 0 0 100
+    /end TYPEDEF_MEASUREMENT
+    /begin TYPEDEF_MEASUREMENT T_Other "" ULONG NO_COMPU_METHOD 0 0 0 100
+    /end TYPEDEF_MEASUREMENT
+    /begin GROUP Group_A ""
+      /begin REF_CHARACTERISTIC Curve_A /end REF_CHARACTERISTIC
+      /begin REF_MEASUREMENT Meas_A Inst_A /end REF_MEASUREMENT
+      /begin SUB_GROUP Group_Sub /end SUB_GROUP
+    /end GROUP
+    /begin GROUP Group_Sub ""
+      /begin REF_MEASUREMENT Meas_A /end REF_MEASUREMENT
+    /end GROUP
+    /begin GROUP Group_B ""
+      /begin REF_CHARACTERISTIC Value_B /end REF_CHARACTERISTIC
+      /begin REF_MEASUREMENT Meas_B Inst_B /end REF_MEASUREMENT
+    /end GROUP
+    /begin COMPU_METHOD CM_Axis "" IDENTICAL "%.4" ""
+    /end COMPU_METHOD
+    /begin COMPU_METHOD CM_Curve "" IDENTICAL "%.4" ""
+    /end COMPU_METHOD
+    /begin COMPU_METHOD CM_Other "" IDENTICAL "%.4" ""
+    /end COMPU_METHOD
+    /begin RECORD_LAYOUT RL_Axis
+      AXIS_PTS_X 1 UWORD INDEX_INCR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT RL_Curve
+      FNC_VALUES 1 UWORD COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT RL_Value
+      FNC_VALUES 1 UBYTE COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+  /begin MODULE other ""
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+
+        let result = extract_groups(&mut a2l_file, &["Missing"], &mut Vec::new());
+        assert_eq!(result.unwrap_err(), "there is no GROUP Missing");
+        assert_eq!(a2l_file.project.module.len(), 2);
+
+        let mut log_msgs = Vec::new();
+        let removed_count = extract_groups(&mut a2l_file, &["Group_A"], &mut log_msgs).unwrap();
+        // Value_B, Meas_B, Inst_B, T_Other, Group_B, RL_Value and CM_Other
+        assert_eq!(removed_count, 7);
+        assert_eq!(a2l_file.project.module.len(), 1);
+        let module = &a2l_file.project.module[0];
+        let names = |items: Vec<&String>| -> Vec<String> { items.into_iter().cloned().collect() };
+        assert_eq!(
+            names(module.characteristic.iter().map(|c| &c.name).collect()),
+            ["Curve_A"]
+        );
+        assert_eq!(
+            names(module.axis_pts.iter().map(|a| &a.name).collect()),
+            ["Axis_A"]
+        );
+        // Speed is needed as the INPUT_QUANTITY of the axis
+        assert_eq!(
+            names(module.measurement.iter().map(|m| &m.name).collect()),
+            ["Speed", "Meas_A"]
+        );
+        assert_eq!(
+            names(module.instance.iter().map(|i| &i.name).collect()),
+            ["Inst_A"]
+        );
+        assert_eq!(
+            names(module.typedef_measurement.iter().map(|t| &t.name).collect()),
+            ["T_Value"]
+        );
+        assert_eq!(
+            names(module.group.iter().map(|g| &g.name).collect()),
+            ["Group_A", "Group_Sub"]
+        );
+        assert_eq!(
+            names(module.record_layout.iter().map(|r| &r.name).collect()),
+            ["RL_Axis", "RL_Curve"]
+        );
+        assert_eq!(
+            names(module.compu_method.iter().map(|c| &c.name).collect()),
+            ["CM_Axis", "CM_Curve"]
+        );
+        assert!(log_msgs.contains(&"Removed MODULE other".to_string()));
+    }
+}
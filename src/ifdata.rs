@@ -382,17 +382,17 @@ a2ml_specification! {
 
     taggedunion Daq_Event {
         "FIXED_EVENT_LIST" taggedstruct {
-            ("EVENT" uint)*;
+            ("EVENT" uint event_number)*;
         };
         "VARIABLE" taggedstruct {
             block "AVAILABLE_EVENT_LIST" taggedstruct {
-                ("EVENT" uint)*;
+                ("EVENT" uint event_number)*;
             };
             block "DEFAULT_EVENT_LIST" taggedstruct {
-                ("EVENT" uint)*;
+                ("EVENT" uint event_number)*;
             };
             block "CONSISTENCY_EVENT_LIST" taggedstruct {
-                ("EVENT" uint)*;
+                ("EVENT" uint event_number)*;
             };
         };
     };
@@ -0,0 +1,181 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+// Read an a2l file and resolve the paths of all /include commands in it.
+// The included files are searched relative to the a2l file, then in each of the include_dirs
+// and finally in the current working directory.
+// Each include path is replaced by the path of the file that was found, so that the returned
+// text can be loaded without knowledge of the include dirs. Includes inside of included files
+// are resolved by the loader relative to the included file.
+pub(crate) fn read_with_include_dirs(
+    filename: &OsStr,
+    include_dirs: &[PathBuf],
+) -> Result<String, String> {
+    let text = std::fs::read_to_string(filename)
+        .map_err(|err| format!("Failed to read {}: {err}", filename.to_string_lossy()))?;
+    let base_dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+    resolve_includes(&text, base_dir, include_dirs)
+}
+
+// Resolve the paths of all /include commands in the text; base_dir takes the place of the directory of the a2l file.
+pub(crate) fn resolve_includes(
+    text: &str,
+    base_dir: &Path,
+    include_dirs: &[PathBuf],
+) -> Result<String, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut copied_until = 0;
+
+    for (start, end) in find_include_paths(text) {
+        let incname = &text[start..end];
+        let resolved = resolve_include_path(incname, base_dir, include_dirs)?;
+        output.push_str(&text[copied_until..start]);
+        output.push_str(&resolved.to_string_lossy());
+        copied_until = end;
+    }
+    output.push_str(&text[copied_until..]);
+
+    Ok(output)
+}
+
+// find the positions of the file names of all /include commands
+// Comments and strings are skipped, so that an "/include" inside them is not used.
+fn find_include_paths(text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut paths = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        if bytes[pos..].starts_with(b"/*") {
+            pos = text[pos + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| pos + 2 + end + 2);
+        } else if bytes[pos..].starts_with(b"//") {
+            pos = text[pos..].find('\n').map_or(bytes.len(), |end| pos + end);
+        } else if bytes[pos] == b'"' {
+            pos = skip_string(bytes, pos);
+        } else if bytes[pos..].starts_with(b"/include")
+            && bytes.get(pos + 8).is_some_and(u8::is_ascii_whitespace)
+        {
+            pos += 8;
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            // the file name may be quoted or unquoted
+            if bytes.get(pos) == Some(&b'"') {
+                let end = skip_string(bytes, pos);
+                if end <= bytes.len() && bytes[end - 1] == b'"' && end - 1 > pos {
+                    paths.push((pos + 1, end - 1));
+                }
+                pos = end;
+            } else {
+                let start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                if pos > start {
+                    paths.push((start, pos));
+                }
+            }
+        } else {
+            pos += 1;
+        }
+    }
+
+    paths
+}
+
+// returns the position after the closing quote of the string that starts at pos
+// Quotes inside of strings are escaped either as \" or as ""
+fn skip_string(bytes: &[u8], pos: usize) -> usize {
+    let mut pos = pos + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'"' if bytes.get(pos + 1) == Some(&b'"') => pos += 2,
+            b'"' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn resolve_include_path(
+    incname: &str,
+    base_dir: &Path,
+    include_dirs: &[PathBuf],
+) -> Result<PathBuf, String> {
+    let incpath = Path::new(incname);
+    let candidates: Vec<PathBuf> = if incpath.is_absolute() {
+        vec![incpath.to_path_buf()]
+    } else {
+        std::iter::once(base_dir.join(incpath))
+            .chain(include_dirs.iter().map(|dir| dir.join(incpath)))
+            .chain(std::iter::once(incpath.to_path_buf()))
+            .collect()
+    };
+
+    if let Some(found) = candidates.iter().find(|candidate| candidate.is_file()) {
+        Ok(found.clone())
+    } else {
+        let attempted: Vec<String> = candidates
+            .iter()
+            .map(|candidate| format!("\"{}\"", candidate.to_string_lossy()))
+            .collect();
+        Err(format!(
+            "The included file \"{incname}\" could not be found. Attempted locations: {}",
+            attempted.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_include_paths() {
+        let text = r#"/* /include "comment.a2l" */
+/include "first.a2l"
+/begin MODULE m "text with /include \"string.a2l\" and ""quotes"""
+  // /include "line_comment.a2l"
+  /include second.a2l
+/end MODULE"#;
+        let paths: Vec<&str> = find_include_paths(text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect();
+        assert_eq!(paths, vec!["first.a2l", "second.a2l"]);
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let text = "/begin MODULE m \"\"\n  /include \"included.a2l\"\n/end MODULE";
+
+        // the file is found relative to the including file
+        let result = resolve_includes(text, Path::new("fixtures/a2l"), &[]).unwrap();
+        let expected_path = Path::new("fixtures/a2l").join("included.a2l");
+        assert!(result.contains(&format!("/include \"{}\"", expected_path.to_string_lossy())));
+
+        // the file is found in an include dir
+        let include_dirs = vec![PathBuf::from("fixtures/bin"), PathBuf::from("fixtures/a2l")];
+        let result = resolve_includes(text, Path::new("src"), &include_dirs).unwrap();
+        assert!(result.contains(&format!("/include \"{}\"", expected_path.to_string_lossy())));
+
+        // all attempted locations are listed if the file is not found
+        let error = resolve_includes(text, Path::new("src"), &include_dirs[..1]).unwrap_err();
+        assert!(error.contains(
+            &Path::new("src")
+                .join("included.a2l")
+                .to_string_lossy()
+                .to_string()
+        ));
+        assert!(error.contains(
+            &Path::new("fixtures/bin")
+                .join("included.a2l")
+                .to_string_lossy()
+                .to_string()
+        ));
+        assert!(error.contains("\"included.a2l\""));
+    }
+}
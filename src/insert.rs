@@ -1,12 +1,17 @@
 use a2lfile::{
-    A2lFile, A2lObject, AddrType, Characteristic, CharacteristicType, EcuAddress, FncValues, Group,
-    IndexMode, Instance, Measurement, Module, RecordLayout, RefCharacteristic, RefMeasurement,
-    Root, SymbolLink,
+    A2lFile, A2lObject, AddrType, ByteOrder, Characteristic, CharacteristicType, DataType,
+    EcuAddress, EcuAddressExtension, Encoding, FncValues, Group, IfData, IndexMode, Instance,
+    Measurement, Module, Number, ReadOnly, ReadWrite, RecordLayout, RefCharacteristic,
+    RefMeasurement, Root, SubGroup, SymbolLink, TypedefStructure, VirtualCharacteristic,
 };
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
-use crate::datatype::{get_a2l_datatype, get_type_limits};
-use crate::debuginfo::{DbgDataType, DebugData, TypeInfo};
+use crate::datatype::{
+    get_a2l_datatype, get_dbg_datatype, get_type_limits, get_wide_string_encoding,
+};
+use crate::debuginfo::{DbgDataType, DebugData, TypeInfo, TypeMap};
+use crate::ifdata;
 use crate::symbol::SymbolInfo;
 use crate::update::{
     self, enums, make_symbol_link_string, set_address_type, set_bitmask, set_matrix_dim,
@@ -28,6 +33,8 @@ struct InsertSupport<'a2l, 'dbg, 'param> {
     debug_data: &'dbg DebugData,
     compiled_meas_re: Vec<Regex>,
     compiled_char_re: Vec<Regex>,
+    excluded_meas_re: Vec<Regex>,
+    excluded_char_re: Vec<Regex>,
     measurement_ranges: &'param [(u64, u64)],
     characteristic_ranges: &'param [(u64, u64)],
     name_map: HashMap<String, ItemType>,
@@ -37,10 +44,40 @@ struct InsertSupport<'a2l, 'dbg, 'param> {
     meas_count: u32,
     chara_count: u32,
     instance_count: u32,
+    meas_excluded_count: u32,
+    chara_excluded_count: u32,
     version: A2lVersion,
     create_typedef: Vec<(&'dbg TypeInfo, usize)>,
+    error_count: u32,
+    name_affix: &'param NameAffix,
+}
+
+/// A prefix and a suffix that are added to the names of all inserted objects, e.g. to tell apart the
+/// objects of different cores in the same module. The SYMBOL_LINK always uses the unmodified symbol name.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameAffix {
+    pub(crate) prefix: String,
+    pub(crate) suffix: String,
+}
+
+impl NameAffix {
+    fn apply(&self, name: &str) -> String {
+        format!("{}{name}{}", self.prefix, self.suffix)
+    }
+}
+
+/// How MEASUREMENTs are inserted for a symbol whose type is a union
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnionMode {
+    /// insert one MEASUREMENT for the first member of the union
+    First,
+    /// insert one MEASUREMENT for the largest member of the union
+    Largest,
+    /// insert one MEASUREMENT for each member of the union; they all have the same address
+    All,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn insert_items(
     a2l_file: &mut A2lFile,
     debug_data: &DebugData,
@@ -49,35 +86,173 @@ pub(crate) fn insert_items(
     target_group: Option<&str>,
     log_msgs: &mut Vec<String>,
     enable_structures: bool,
+    max_errors: Option<u32>,
+    union_mode: Option<UnionMode>,
+    datatype_override: Option<DataType>,
+    flex_dim: Option<u64>,
+    name_affix: &NameAffix,
+    max_candidates: usize,
 ) {
     let version = A2lVersion::from(&*a2l_file);
     let module = &mut a2l_file.project.module[0];
     let (mut name_map, mut sym_map) = build_maps(module);
     let mut characteristic_list = vec![];
     let mut measurement_list = vec![];
+    let mut error_count = 0;
 
-    let mut insert_list: Vec<(&str, SymbolInfo, bool)> = Vec::new();
+    let mut insert_list: Vec<(String, SymbolInfo, bool)> = Vec::new();
 
-    for measure_sym in measurement_symbols {
-        match crate::symbol::find_symbol(measure_sym, debug_data) {
-            Ok(sym_info) => insert_list.push((measure_sym, sym_info, false)),
-            Err(errmsg) => log_msgs.push(format!(
-                "Insert skipped: Symbol {measure_sym} could not be added: {errmsg}"
-            )),
+    let symbols = measurement_symbols
+        .into_iter()
+        .map(|sym| (sym, false))
+        .chain(characteristic_symbols.into_iter().map(|sym| (sym, true)));
+    for (sym_name, is_calib) in symbols {
+        if is_error_limit_reached(error_count, max_errors) {
+            break;
         }
-    }
-    for characteristic_sym in characteristic_symbols {
-        match crate::symbol::find_symbol(characteristic_sym, debug_data) {
-            Ok(sym_info) => insert_list.push((characteristic_sym, sym_info, true)),
-            Err(errmsg) => log_msgs.push(format!(
-                "Insert skipped: Symbol {characteristic_sym} could not be added: {errmsg}"
-            )),
+        // array ranges like "table[0:3].value" select several symbols
+        match crate::symbol::find_symbol_range(sym_name, debug_data) {
+            Ok(sym_list) => {
+                for (sym_name, sym_info) in sym_list {
+                    if let Some(note) =
+                        crate::symbol::get_resolution_note(&sym_name, debug_data, max_candidates)
+                    {
+                        log_msgs.push(note);
+                    }
+                    // the members of a union are inserted instead of the union itself.
+                    // Without an explicit union mode, a union becomes an INSTANCE if structures are enabled.
+                    let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+                    let union_mode =
+                        union_mode.or((!enable_structures).then_some(UnionMode::First));
+                    match (&typeinfo.datatype, union_mode) {
+                        (DbgDataType::Union { members, .. }, Some(mode)) if !is_calib => {
+                            for member_name in select_union_members(members, mode, debug_data) {
+                                let member_sym_name = format!("{sym_name}.{member_name}");
+                                match mode {
+                                    UnionMode::First => log_msgs.push(format!(
+                                        "Symbol {sym_name} is a union, its first member {member_name} is used"
+                                    )),
+                                    UnionMode::Largest => log_msgs.push(format!(
+                                        "Symbol {sym_name} is a union, its largest member {member_name} is used"
+                                    )),
+                                    UnionMode::All => {}
+                                }
+                                match crate::symbol::find_symbol(&member_sym_name, debug_data) {
+                                    Ok(member_sym_info) => {
+                                        insert_list.push((member_sym_name, member_sym_info, false));
+                                    }
+                                    Err(errmsg) => {
+                                        log_msgs.push(format!(
+                                            "Insert skipped: Symbol {member_sym_name} could not be added: {errmsg}"
+                                        ));
+                                        error_count += 1;
+                                    }
+                                }
+                            }
+                        }
+                        _ => insert_list.push((sym_name, sym_info, is_calib)),
+                    }
+                }
+            }
+            Err(errmsg) => {
+                log_msgs.push(format!(
+                    "Insert skipped: Symbol {sym_name} could not be added: {errmsg}"
+                ));
+                error_count += 1;
+            }
         }
     }
 
+    // structs with a flexible array member get a modified type, which must outlive the TYPEDEF creation
+    let flex_types: Vec<Result<Option<TypeInfo>, String>> = insert_list
+        .iter()
+        .map(|(sym_name, sym_info, _)| {
+            resolve_flexible_array(
+                sym_name,
+                sym_info.typeinfo,
+                &debug_data.types,
+                flex_dim,
+                log_msgs,
+            )
+        })
+        .collect();
+
     let mut create_typedef = Vec::new();
-    for (sym_name, sym_info, is_calib) in insert_list {
+    for ((sym_name, sym_info, is_calib), flex_type) in insert_list.into_iter().zip(&flex_types) {
+        if is_error_limit_reached(error_count, max_errors) {
+            break;
+        }
+        let sym_info = match flex_type {
+            Ok(Some(typeinfo)) => SymbolInfo {
+                typeinfo,
+                ..sym_info
+            },
+            Ok(None) => sym_info,
+            Err(errmsg) => {
+                log_msgs.push(format!("Insert skipped: {errmsg}"));
+                error_count += 1;
+                continue;
+            }
+        };
+        if sym_info.is_from_mapfile() {
+            // the data type of a symbol from the map file is unknown, so it must be given explicitly
+            let Some(datatype) = datatype_override.as_ref().filter(|_| !is_calib) else {
+                let mapfile_name = debug_data
+                    .mapfile
+                    .as_ref()
+                    .map_or("", |mapfile| &mapfile.filename);
+                log_msgs.push(format!(
+                    "Insert skipped: Symbol {sym_name} was only found in the map file {mapfile_name} and its data type is unknown. It can be inserted as a MEASUREMENT if the data type is given with --datatype"
+                ));
+                error_count += 1;
+                continue;
+            };
+            let typeinfo = TypeInfo {
+                name: None,
+                unit_idx: usize::MAX,
+                datatype: get_dbg_datatype(datatype),
+                dbginfo_offset: 0,
+                big_endian: None,
+            };
+            if typeinfo.get_size() != sym_info.typeinfo.get_size() {
+                log_msgs.push(format!(
+                    "Warning: the size of symbol {sym_name} in the map file is {} bytes, but the data type {datatype} has a size of {} bytes",
+                    sym_info.typeinfo.get_size(),
+                    typeinfo.get_size()
+                ));
+            }
+            let sym_info = SymbolInfo {
+                typeinfo: &typeinfo,
+                ..sym_info
+            };
+            match insert_measurement_sym(
+                module, debug_data, &sym_info, &name_map, &sym_map, version, name_affix,
+            ) {
+                Ok(measure_name) => {
+                    log_msgs.push(format!("Inserted MEASUREMENT {measure_name}"));
+                    measurement_list.push(measure_name.clone());
+
+                    let it = ItemType::Measurement(module.measurement.len() - 1);
+                    name_map.insert(measure_name, it);
+                    sym_map.entry(sym_name.clone()).or_default().push(it);
+                }
+                Err(errmsg) => {
+                    log_msgs.push(format!("Insert skipped: {errmsg}"));
+                    error_count += 1;
+                }
+            }
+            continue;
+        }
+        if sym_info.is_type_inferred() {
+            log_msgs.push(format!(
+                "Note: Symbol {sym_name} is not described by the debug info. Its data type {} was inferred from the size of the ELF symbol",
+                sym_info.typeinfo
+            ));
+        }
+
+        // bitfields, e.g. the fields of SVD registers, are inserted with a BIT_MASK
         if is_simple_type(sym_info.typeinfo)
+            || matches!(sym_info.typeinfo.datatype, DbgDataType::Bitfield { .. })
             || sym_info
                 .typeinfo
                 .get_arraytype()
@@ -85,7 +260,8 @@ pub(crate) fn insert_items(
         {
             if is_calib {
                 match insert_characteristic_sym(
-                    module, debug_data, sym_name, &sym_info, &name_map, &sym_map, version,
+                    module, debug_data, &sym_name, &sym_info, &name_map, &sym_map, version,
+                    name_affix,
                 ) {
                     Ok(characteristic_name) => {
                         log_msgs.push(format!("Inserted CHARACTERISTIC {characteristic_name}"));
@@ -93,15 +269,16 @@ pub(crate) fn insert_items(
 
                         let it = ItemType::Characteristic(module.characteristic.len() - 1);
                         name_map.insert(characteristic_name, it);
-                        sym_map.entry(sym_name.to_string()).or_default().push(it);
+                        sym_map.entry(sym_name.clone()).or_default().push(it);
                     }
                     Err(errmsg) => {
                         log_msgs.push(format!("Insert skipped: {errmsg}"));
+                        error_count += 1;
                     }
                 }
             } else {
                 match insert_measurement_sym(
-                    module, debug_data, &sym_info, &name_map, &sym_map, version,
+                    module, debug_data, &sym_info, &name_map, &sym_map, version, name_affix,
                 ) {
                     Ok(measure_name) => {
                         log_msgs.push(format!("Inserted MEASUREMENT {measure_name}"));
@@ -109,10 +286,11 @@ pub(crate) fn insert_items(
 
                         let it = ItemType::Measurement(module.measurement.len() - 1);
                         name_map.insert(measure_name, it);
-                        sym_map.entry(sym_name.to_string()).or_default().push(it);
+                        sym_map.entry(sym_name.clone()).or_default().push(it);
                     }
                     Err(errmsg) => {
                         log_msgs.push(format!("Insert skipped: {errmsg}"));
+                        error_count += 1;
                     }
                 }
             }
@@ -120,7 +298,7 @@ pub(crate) fn insert_items(
             && !matches!(sym_info.typeinfo.datatype, DbgDataType::FuncPtr(_))
         {
             match insert_instance_sym(
-                module, debug_data, sym_name, &sym_info, &name_map, &sym_map, is_calib,
+                module, debug_data, &sym_name, &sym_info, &name_map, &sym_map, is_calib, name_affix,
             ) {
                 Ok((instance_name, typedef_typeinfo)) => {
                     if is_calib {
@@ -135,10 +313,11 @@ pub(crate) fn insert_items(
 
                     let it = ItemType::Instance(module.instance.len() - 1);
                     name_map.insert(instance_name, it);
-                    sym_map.entry(sym_name.to_string()).or_default().push(it);
+                    sym_map.entry(sym_name.clone()).or_default().push(it);
                 }
                 Err(errmsg) => {
                     log_msgs.push(format!("Insert skipped: {errmsg}"));
+                    error_count += 1;
                 }
             }
         } else {
@@ -146,8 +325,12 @@ pub(crate) fn insert_items(
                 "Insert skipped: Symbol {sym_name} exists but has the unsuitable data type {}",
                 sym_info.typeinfo
             ));
+            error_count += 1;
         }
     }
+    if is_error_limit_reached(error_count, max_errors) {
+        log_msgs.push(format!("Insert stopped after {error_count} errors"));
+    }
 
     update::typedef::create_new_typedefs(module, debug_data, log_msgs, &create_typedef);
 
@@ -156,6 +339,108 @@ pub(crate) fn insert_items(
     }
 }
 
+// select the members of a union for which MEASUREMENTs are inserted
+// If several members have the largest size, the first of them is used.
+fn select_union_members<'a>(
+    members: &'a IndexMap<String, (TypeInfo, u64)>,
+    mode: UnionMode,
+    debug_data: &DebugData,
+) -> Vec<&'a String> {
+    match mode {
+        UnionMode::First => members.keys().take(1).collect(),
+        UnionMode::All => members.keys().collect(),
+        UnionMode::Largest => {
+            let mut largest: Option<(&String, u64)> = None;
+            for (name, (typeinfo, _)) in members {
+                let size = typeinfo.get_reference(&debug_data.types).get_size();
+                if largest.is_none_or(|(_, largest_size)| size > largest_size) {
+                    largest = Some((name, size));
+                }
+            }
+            largest.map(|(name, _)| name).into_iter().collect()
+        }
+    }
+}
+
+// A struct may end with a flexible array member (`int data[]`), whose size is unknown in the debug info.
+// With a dimension override the flexible array is treated as an array with this number of elements,
+// otherwise the member is removed from the struct, since it would be inserted with a MATRIX_DIM of 0.
+// Returns the modified type, or None if the type does not contain a flexible array member.
+fn resolve_flexible_array(
+    sym_name: &str,
+    typeinfo: &TypeInfo,
+    types: &TypeMap,
+    flex_dim: Option<u64>,
+    log_msgs: &mut Vec<String>,
+) -> Result<Option<TypeInfo>, String> {
+    let typeinfo = typeinfo.get_reference(types);
+    if is_flexible_array(typeinfo) {
+        // the symbol is the flexible array member itself, e.g. "some_struct.data"
+        let Some(count) = flex_dim else {
+            return Err(format!(
+                "Symbol {sym_name} is a flexible array member of unknown size. Its number of elements can be given with --dim"
+            ));
+        };
+        return Ok(Some(resize_flexible_array(typeinfo, count)));
+    }
+
+    let DbgDataType::Struct { size, members } = &typeinfo.datatype else {
+        return Ok(None);
+    };
+    let Some((member_name, (member_type, member_offset))) = members.last() else {
+        return Ok(None);
+    };
+    let member_type = member_type.get_reference(types);
+    if !is_flexible_array(member_type) {
+        return Ok(None);
+    }
+
+    let mut new_members = members.clone();
+    let new_size = if let Some(count) = flex_dim {
+        let new_member_type = resize_flexible_array(member_type, count);
+        let new_size = (*size).max(member_offset + new_member_type.get_size());
+        log_msgs.push(format!(
+            "The flexible array member {member_name} of symbol {sym_name} is inserted with {count} elements"
+        ));
+        new_members.insert(member_name.clone(), (new_member_type, *member_offset));
+        new_size
+    } else {
+        log_msgs.push(format!(
+            "Warning: the flexible array member {member_name} of symbol {sym_name} has an unknown size and is skipped. Its number of elements can be given with --dim"
+        ));
+        new_members.pop();
+        *size
+    };
+
+    Ok(Some(TypeInfo {
+        name: typeinfo.name.clone(),
+        unit_idx: typeinfo.unit_idx,
+        datatype: DbgDataType::Struct {
+            size: new_size,
+            members: new_members,
+        },
+        dbginfo_offset: typeinfo.dbginfo_offset,
+        big_endian: typeinfo.big_endian,
+    }))
+}
+
+// a flexible array member has no upper bound, so the first dimension and the size are both zero
+fn is_flexible_array(typeinfo: &TypeInfo) -> bool {
+    matches!(&typeinfo.datatype, DbgDataType::Array { size: 0, dim, .. } if dim.first() == Some(&0))
+}
+
+fn resize_flexible_array(typeinfo: &TypeInfo, count: u64) -> TypeInfo {
+    let mut new_typeinfo = typeinfo.clone();
+    if let DbgDataType::Array {
+        size, dim, stride, ..
+    } = &mut new_typeinfo.datatype
+    {
+        dim[0] = count;
+        *size = dim.iter().fold(*stride, |acc, num| acc * num);
+    }
+    new_typeinfo
+}
+
 fn insert_measurement_sym(
     module: &mut Module,
     debug_data: &DebugData,
@@ -163,10 +448,12 @@ fn insert_measurement_sym(
     name_map: &HashMap<String, ItemType>,
     sym_map: &HashMap<String, Vec<ItemType>>,
     version: A2lVersion,
+    name_affix: &NameAffix,
 ) -> Result<String, String> {
     // Abort if a MEASUREMENT for this symbol already exists. Warn if any other reference to the symbol exists
     let symbol_link_text = make_symbol_link_string(sym_info, debug_data);
-    let item_name = make_unique_measurement_name(module, sym_map, &sym_info.name, name_map)?;
+    let item_name =
+        make_unique_measurement_name(module, sym_map, &sym_info.name, name_map, name_affix)?;
 
     let datatype = get_a2l_datatype(sym_info.typeinfo);
     let (lower_limit, upper_limit) = get_type_limits(sym_info.typeinfo, f64::MIN, f64::MAX);
@@ -192,10 +479,7 @@ fn insert_measurement_sym(
 
     // handle pointers - only allowed for version 1.7.0+ (the caller should take care of this precondition)
     update::set_address_type(&mut new_measurement.address_type, sym_info.typeinfo);
-    let typeinfo = sym_info
-        .typeinfo
-        .get_pointer(&debug_data.types)
-        .map_or(sym_info.typeinfo, |(_, t)| t);
+    let typeinfo = sym_info.typeinfo.get_pointer_target(&debug_data.types);
 
     // handle arrays and unwrap the typeinfo
     update::set_matrix_dim(
@@ -205,22 +489,52 @@ fn insert_measurement_sym(
     );
     let typeinfo = typeinfo.get_arraytype().unwrap_or(typeinfo);
 
-    if let DbgDataType::Enum { enumerators, .. } = &typeinfo.datatype {
+    // bitfields can have an enum type, e.g. the fields of registers from an SVD file
+    let value_typeinfo = match &typeinfo.datatype {
+        DbgDataType::Bitfield { basetype, .. } => basetype,
+        _ => typeinfo,
+    };
+    if let DbgDataType::Enum { enumerators, .. } = &value_typeinfo.datatype {
         // create a conversion table for enums
-        let enum_name = typeinfo
+        let enum_name = value_typeinfo
             .name
             .clone()
             .unwrap_or_else(|| format!("{}_compu_method", new_measurement.name));
         enums::cond_create_enum_conversion(module, &enum_name, enumerators);
         new_measurement.conversion = enum_name;
-    } else {
-        update::set_bitmask(&mut new_measurement.bit_mask, typeinfo);
     }
+    update::set_bitmask(&mut new_measurement.bit_mask, typeinfo);
+    set_variable_byte_order(
+        &mut new_measurement.byte_order,
+        module,
+        debug_data,
+        typeinfo,
+        version,
+    );
     module.measurement.push(new_measurement);
 
     Ok(item_name)
 }
 
+// add a BYTE_ORDER to a new item if the variable has a different byte order than the module
+pub(crate) fn set_variable_byte_order(
+    opt_byte_order: &mut Option<ByteOrder>,
+    module: &Module,
+    debug_data: &DebugData,
+    typeinfo: &TypeInfo,
+    version: A2lVersion,
+) {
+    let file_big_endian = debug_data.memory.big_endian;
+    update::set_byte_order(
+        opt_byte_order,
+        typeinfo,
+        update::module_big_endian(module, file_big_endian),
+        file_big_endian,
+        version,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
 fn insert_characteristic_sym(
     module: &mut Module,
     debug_data: &DebugData,
@@ -229,9 +543,11 @@ fn insert_characteristic_sym(
     name_map: &HashMap<String, ItemType>,
     sym_map: &HashMap<String, Vec<ItemType>>,
     version: A2lVersion,
+    name_affix: &NameAffix,
 ) -> Result<String, String> {
     let symbol_link_text = make_symbol_link_string(sym_info, debug_data);
-    let item_name = make_unique_characteristic_name(module, sym_map, characteristic_sym, name_map)?;
+    let item_name =
+        make_unique_characteristic_name(module, sym_map, characteristic_sym, name_map, name_affix)?;
 
     let mut matrix_dim = None;
     set_matrix_dim(
@@ -246,7 +562,7 @@ fn insert_characteristic_sym(
     };
 
     let datatype = get_a2l_datatype(typeinfo);
-    let recordlayout_name = format!("__{datatype}_Z");
+    let recordlayout_name = create_record_layout(module, datatype);
     let (lower_limit, upper_limit) = get_type_limits(typeinfo, f64::MIN, f64::MAX);
 
     let mut new_characteristic = Characteristic::new(
@@ -254,7 +570,7 @@ fn insert_characteristic_sym(
         format!("characteristic for {characteristic_sym}"),
         ctype,
         sym_info.address as u32,
-        recordlayout_name.clone(),
+        recordlayout_name,
         0f64,
         "NO_COMPU_METHOD".to_string(),
         lower_limit,
@@ -262,10 +578,35 @@ fn insert_characteristic_sym(
     );
     new_characteristic.matrix_dim = matrix_dim;
 
+    // arrays of wide characters are strings, even though the elements are 16 or 32 bit integers
+    if let Some(encoding) = get_wide_string_encoding(sym_info.typeinfo) {
+        let length = new_characteristic
+            .matrix_dim
+            .take()
+            .and_then(|matrix_dim| matrix_dim.dim_list.first().copied())
+            .unwrap_or(1);
+        new_characteristic.characteristic_type = CharacteristicType::Ascii;
+        new_characteristic.number = Some(Number::new(length));
+        if version >= A2lVersion::V1_7_0 {
+            new_characteristic.encoding = Some(Encoding::new(encoding));
+        }
+    }
+
     set_bitmask(&mut new_characteristic.bit_mask, typeinfo);
+    set_variable_byte_order(
+        &mut new_characteristic.byte_order,
+        module,
+        debug_data,
+        typeinfo,
+        version,
+    );
 
-    if let DbgDataType::Enum { enumerators, .. } = &typeinfo.datatype {
-        let enum_name = typeinfo
+    let value_typeinfo = match &typeinfo.datatype {
+        DbgDataType::Bitfield { basetype, .. } => basetype,
+        _ => typeinfo,
+    };
+    if let DbgDataType::Enum { enumerators, .. } = &value_typeinfo.datatype {
+        let enum_name = value_typeinfo
             .name
             .clone()
             .unwrap_or_else(|| format!("{item_name}_compu_method"));
@@ -284,27 +625,97 @@ fn insert_characteristic_sym(
     // insert the CHARACTERISTIC into the module's list
     module.characteristic.push(new_characteristic);
 
-    // create a RECORD_LAYOUT for the CHARACTERISTIC if it doesn't exist yet
-    // the used naming convention (__<type>_Z) matches default naming used by Vector tools
-    let mut recordlayout = RecordLayout::new(recordlayout_name.clone());
-    // set item 0 (name) to use an offset of 0 lines, i.e. no line break after /begin RECORD_LAYOUT
-    recordlayout.get_layout_mut().item_location.0 = 0;
-    recordlayout.fnc_values = Some(FncValues::new(
-        1,
-        datatype,
-        IndexMode::RowDir,
-        AddrType::Direct,
-    ));
+    Ok(item_name)
+}
+
+// Insert a CHARACTERISTIC for each of the given variables that were optimized to a constant value.
+// These variables have no storage, so the CHARACTERISTIC is a VIRTUAL_CHARACTERISTIC whose formula is the constant value.
+pub(crate) fn insert_const_characteristics(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    symbols: &[&str],
+    log_msgs: &mut Vec<String>,
+    name_affix: &NameAffix,
+) {
+    let module = &mut a2l_file.project.module[0];
+    let (name_map, sym_map) = build_maps(module);
+
+    for symbol in symbols {
+        let (Some(const_info), Some(value)) = (
+            debug_data.constants.get(*symbol),
+            debug_data.get_const_value_text(symbol),
+        ) else {
+            log_msgs.push(format!(
+                "Insert skipped: Symbol {symbol} is not a variable that was optimized to a constant"
+            ));
+            continue;
+        };
+        let Some(typeinfo) = debug_data
+            .types
+            .get(&const_info.typeref)
+            .map(|typeinfo| typeinfo.get_reference(&debug_data.types))
+            .filter(|typeinfo| is_simple_type(typeinfo))
+        else {
+            log_msgs.push(format!(
+                "Insert skipped: Symbol {symbol} was optimized to a constant value {value}, but its data type is not a simple number"
+            ));
+            continue;
+        };
+        let item_name = match make_unique_characteristic_name(
+            module, &sym_map, symbol, &name_map, name_affix,
+        ) {
+            Ok(item_name) => item_name,
+            Err(errmsg) => {
+                log_msgs.push(format!("Insert skipped: {errmsg}"));
+                continue;
+            }
+        };
+
+        let datatype = get_a2l_datatype(typeinfo);
+        let recordlayout_name = create_record_layout(module, datatype);
+        let (lower_limit, upper_limit) = get_type_limits(typeinfo, f64::MIN, f64::MAX);
+        let mut new_characteristic = Characteristic::new(
+            item_name.clone(),
+            format!("constant value of {symbol}"),
+            CharacteristicType::Value,
+            0,
+            recordlayout_name,
+            0f64,
+            "NO_COMPU_METHOD".to_string(),
+            lower_limit,
+            upper_limit,
+        );
+        new_characteristic.virtual_characteristic = Some(VirtualCharacteristic::new(value.clone()));
+        // no SYMBOL_LINK: virtual CHARACTERISTICs are not updated, and there is no symbol with storage to link to
+        module.characteristic.push(new_characteristic);
+        log_msgs.push(format!(
+            "Inserted virtual CHARACTERISTIC {item_name} for the constant value {value} of {symbol}"
+        ));
+    }
+}
+
+// create a RECORD_LAYOUT for a VALUE or VAL_BLK CHARACTERISTIC of the given datatype, if it doesn't exist yet
+// the used naming convention (__<type>_Z) matches default naming used by Vector tools
+pub(crate) fn create_record_layout(module: &mut Module, datatype: DataType) -> String {
+    let recordlayout_name = format!("__{datatype}_Z");
     // search through all existing record layouts and only add the new one if it doesn't exist yet
     if !module
         .record_layout
         .iter()
         .any(|rl| rl.name == recordlayout_name)
     {
+        let mut recordlayout = RecordLayout::new(recordlayout_name.clone());
+        // set item 0 (name) to use an offset of 0 lines, i.e. no line break after /begin RECORD_LAYOUT
+        recordlayout.get_layout_mut().item_location.0 = 0;
+        recordlayout.fnc_values = Some(FncValues::new(
+            1,
+            datatype,
+            IndexMode::RowDir,
+            AddrType::Direct,
+        ));
         module.record_layout.push(recordlayout);
     }
-
-    Ok(item_name)
+    recordlayout_name
 }
 
 fn make_unique_measurement_name(
@@ -312,10 +723,12 @@ fn make_unique_measurement_name(
     sym_map: &HashMap<String, Vec<ItemType>>,
     measure_sym: &str,
     name_map: &HashMap<String, ItemType>,
+    name_affix: &NameAffix,
 ) -> Result<String, String> {
-    // ideally the item name is the symbol name.
+    // ideally the item name is the symbol name, with the optional prefix and suffix.
     // if the symbol is a demangled c++ symbol, then it might contain a "::", e.g. namespace::variable
     let cleaned_sym = measure_sym.replace("::", "__");
+    let base_name = name_affix.apply(&cleaned_sym);
 
     // If an object of a different type already has this name, add the prefix "CHARACTERISTIC."
     let item_name = match sym_map.get(&cleaned_sym) {
@@ -331,14 +744,14 @@ fn make_unique_measurement_name(
                 ));
             } else {
                 // there is another object for this symbol
-                if name_map.get(&cleaned_sym).is_some() {
-                    format!("MEASUREMENT.{cleaned_sym}")
+                if name_map.get(&base_name).is_some() {
+                    format!("MEASUREMENT.{base_name}")
                 } else {
-                    cleaned_sym
+                    base_name
                 }
             }
         }
-        None => cleaned_sym,
+        None => base_name,
     };
     // fail if the name still isn't unique
     if name_map.get(&item_name).is_some() {
@@ -352,10 +765,12 @@ fn make_unique_characteristic_name(
     sym_map: &HashMap<String, Vec<ItemType>>,
     characteristic_sym: &str,
     name_map: &HashMap<String, ItemType>,
+    name_affix: &NameAffix,
 ) -> Result<String, String> {
-    // ideally the item name is the symbol name.
+    // ideally the item name is the symbol name, with the optional prefix and suffix.
     // if the symbol is a demangled c++ symbol, then it might contain a "::", e.g. namespace::variable
     let cleaned_sym = characteristic_sym.replace("::", "__");
+    let base_name = name_affix.apply(&cleaned_sym);
 
     // If an object of a different type already has this name, add the prefix "CHARACTERISTIC."
     let item_name = match sym_map.get(&cleaned_sym) {
@@ -371,14 +786,14 @@ fn make_unique_characteristic_name(
                 ));
             } else {
                 // there is another object for this symbol
-                if name_map.get(&cleaned_sym).is_some() {
-                    format!("CHARACTERISTIC.{cleaned_sym}")
+                if name_map.get(&base_name).is_some() {
+                    format!("CHARACTERISTIC.{base_name}")
                 } else {
-                    cleaned_sym
+                    base_name
                 }
             }
         }
-        None => cleaned_sym,
+        None => base_name,
     };
     // fail if the name still isn't unique
     if name_map.get(&item_name).is_some() {
@@ -392,10 +807,12 @@ fn make_unique_instance_name(
     sym_map: &HashMap<String, Vec<ItemType>>,
     instance_sym: &str,
     name_map: &HashMap<String, ItemType>,
+    name_affix: &NameAffix,
 ) -> Result<String, String> {
-    // ideally the item name is the symbol name.
+    // ideally the item name is the symbol name, with the optional prefix and suffix.
     // if the symbol is a demangled c++ symbol, then it might contain a "::", e.g. namespace::variable
     let cleaned_sym = instance_sym.replace("::", "__");
+    let base_name = name_affix.apply(&cleaned_sym);
 
     // If an object of a different type already has this name, add the prefix "INSTANCE."
     let item_name = match sym_map.get(&cleaned_sym) {
@@ -411,14 +828,14 @@ fn make_unique_instance_name(
                 ));
             } else {
                 // there is another object for this symbol
-                if name_map.get(&cleaned_sym).is_some() {
-                    format!("INSTANCE.{cleaned_sym}")
+                if name_map.get(&base_name).is_some() {
+                    format!("INSTANCE.{base_name}")
                 } else {
-                    cleaned_sym
+                    base_name
                 }
             }
         }
-        None => cleaned_sym,
+        None => base_name,
     };
     // fail if the name still isn't unique
     if name_map.get(&item_name).is_some() {
@@ -487,9 +904,14 @@ pub(crate) fn insert_many<'param>(
     characteristic_ranges: &'param [(u64, u64)],
     measurement_regexes: Vec<&str>,
     characteristic_regexes: Vec<&str>,
+    measurement_exclude: Vec<&str>,
+    characteristic_exclude: Vec<&str>,
     target_group: Option<&str>,
     log_msgs: &mut Vec<String>,
     enable_structures: bool,
+    max_errors: Option<u32>,
+    name_affix: &'param NameAffix,
+    include_untyped: bool,
 ) {
     let file_version = crate::A2lVersion::from(&*a2l_file);
     let use_new_arrays = file_version >= A2lVersion::V1_7_0;
@@ -498,8 +920,10 @@ pub(crate) fn insert_many<'param>(
     let mut isupp = InsertSupport {
         module,
         debug_data: debugdata,
-        compiled_meas_re: Vec::new(),
-        compiled_char_re: Vec::new(),
+        compiled_meas_re: compile_insert_regexes(measurement_regexes),
+        compiled_char_re: compile_insert_regexes(characteristic_regexes),
+        excluded_meas_re: compile_insert_regexes(measurement_exclude),
+        excluded_char_re: compile_insert_regexes(characteristic_exclude),
         measurement_ranges,
         characteristic_ranges,
         name_map,
@@ -509,41 +933,37 @@ pub(crate) fn insert_many<'param>(
         meas_count: 0u32,
         chara_count: 0u32,
         instance_count: 0u32,
+        meas_excluded_count: 0u32,
+        chara_excluded_count: 0u32,
         version: file_version,
         create_typedef: Vec::new(),
+        error_count: 0,
+        name_affix,
     };
-    // compile the regular expressions
-    for expr in measurement_regexes {
-        // extend the regex to match only the whole string, not just a substring
-        let extended_regex = if !expr.starts_with('^') && !expr.ends_with('$') {
-            format!("^{expr}$")
-        } else {
-            expr.to_string()
-        };
-        match Regex::new(&extended_regex) {
-            Ok(compiled_re) => isupp.compiled_meas_re.push(compiled_re),
-            Err(error) => println!("Invalid regex \"{expr}\": {error}"),
-        }
-    }
-    for expr in characteristic_regexes {
-        // extend the regex to match only the whole string, not just a substring
-        let extended_regex = if !expr.starts_with('^') && !expr.ends_with('$') {
-            format!("^{expr}$")
-        } else {
-            expr.to_string()
-        };
-        match Regex::new(&extended_regex) {
-            Ok(compiled_re) => isupp.compiled_char_re.push(compiled_re),
-            Err(error) => println!("Invalid regex \"{expr}\": {error}"),
-        }
-    }
-
     let mut debugdata_iter = debugdata.iter(use_new_arrays);
     let mut current_item = debugdata_iter.next();
     while let Some(sym_info) = current_item {
+        if is_error_limit_reached(isupp.error_count, max_errors) {
+            log_msgs.push(format!("Insert stopped after {} errors", isupp.error_count));
+            break;
+        }
         let mut skip_children = false;
         match &sym_info.typeinfo.datatype {
+            // symbols from the ELF symbol table are only inserted on request, since their data type is only a guess
+            _ if sym_info.is_type_inferred() => {
+                if include_untyped {
+                    check_and_insert_simple_type(&mut isupp, &sym_info, log_msgs);
+                }
+                skip_children = true;
+            }
             DbgDataType::TypeRef(_, _) | DbgDataType::FuncPtr(_) => {}
+            // there is no TYPEDEF_STRUCTURE for the unknown target of a void pointer
+            DbgDataType::Pointer(_, _)
+                if enable_structures && sym_info.typeinfo.is_opaque_pointer(&debugdata.types) =>
+            {
+                check_and_insert_simple_type(&mut isupp, &sym_info, log_msgs);
+                skip_children = true;
+            }
             DbgDataType::Other(_)
             | DbgDataType::Pointer(_, _)
             | DbgDataType::Struct { .. }
@@ -555,7 +975,10 @@ pub(crate) fn insert_many<'param>(
             }
             DbgDataType::Array { arraytype, .. } => {
                 if is_simple_type(arraytype) {
-                    if check_and_insert_simple_type(&mut isupp, &sym_info, log_msgs) {
+                    // the elements of an excluded array are excluded as well
+                    if check_and_insert_simple_type(&mut isupp, &sym_info, log_msgs)
+                        || isupp.is_excluded(&sym_info)
+                    {
                         skip_children = true;
                     }
                 } else if enable_structures
@@ -612,6 +1035,221 @@ pub(crate) fn insert_many<'param>(
     if isupp.chara_count > 0 {
         log_msgs.push(format!("Inserted {} CHARACTERISTICs", isupp.chara_count));
     }
+    if isupp.meas_excluded_count > 0 {
+        log_msgs.push(format!(
+            "Excluded {} symbols from insertion as MEASUREMENTs",
+            isupp.meas_excluded_count
+        ));
+    }
+    if isupp.chara_excluded_count > 0 {
+        log_msgs.push(format!(
+            "Excluded {} symbols from insertion as CHARACTERISTICs",
+            isupp.chara_excluded_count
+        ));
+    }
+}
+
+// compile the regular expressions for insert_many
+fn compile_insert_regexes(regexes: Vec<&str>) -> Vec<Regex> {
+    let mut compiled = Vec::new();
+    for expr in regexes {
+        // extend the regex to match only the whole string, not just a substring
+        let extended_regex = if !expr.starts_with('^') && !expr.ends_with('$') {
+            format!("^{expr}$")
+        } else {
+            expr.to_string()
+        };
+        match Regex::new(&extended_regex) {
+            Ok(compiled_re) => compiled.push(compiled_re),
+            Err(error) => log_println!("Invalid regex \"{expr}\": {error}"),
+        }
+    }
+    compiled
+}
+
+impl InsertSupport<'_, '_, '_> {
+    // check if the symbol matches one of the exclude regexes
+    fn is_excluded(&self, sym_info: &SymbolInfo) -> bool {
+        is_insert_excluded(&sym_info.name, &self.excluded_meas_re)
+            || is_insert_excluded(&sym_info.name, &self.excluded_char_re)
+    }
+
+    // check if a MEASUREMENT or INSTANCE should be inserted for the symbol; excluded symbols are counted
+    fn is_measurement_requested(&mut self, sym_info: &SymbolInfo) -> bool {
+        if !is_insert_requested(
+            sym_info.address,
+            &sym_info.name,
+            self.measurement_ranges,
+            &self.compiled_meas_re,
+        ) {
+            return false;
+        }
+        if is_insert_excluded(&sym_info.name, &self.excluded_meas_re) {
+            self.meas_excluded_count += 1;
+            return false;
+        }
+        true
+    }
+
+    // check if a CHARACTERISTIC or INSTANCE should be inserted for the symbol; excluded symbols are counted
+    fn is_characteristic_requested(&mut self, sym_info: &SymbolInfo) -> bool {
+        if !is_insert_requested(
+            sym_info.address,
+            &sym_info.name,
+            self.characteristic_ranges,
+            &self.compiled_char_re,
+        ) {
+            return false;
+        }
+        if is_insert_excluded(&sym_info.name, &self.excluded_char_re) {
+            self.chara_excluded_count += 1;
+            return false;
+        }
+        true
+    }
+}
+
+// attach a fixed XCP DAQ event to each of the given MEASUREMENTs
+pub(crate) fn set_default_daq_event(measurements: &mut [Measurement], event: u16) {
+    let event_ifdata = make_daq_event_ifdata(event);
+    for measurement in measurements {
+        // drop any existing XCP IF_DATA, since it would conflict with the new DAQ_EVENT
+        measurement.if_data.retain(|ifdata| {
+            ifdata::A2mlVector::load_from_ifdata(ifdata).is_none_or(|decoded| decoded.xcp.is_none())
+        });
+        measurement.if_data.push(event_ifdata.clone());
+    }
+}
+
+// mark each of the given MEASUREMENTs as writeable by adding READ_WRITE
+pub(crate) fn set_measurements_writeable(measurements: &mut [Measurement]) {
+    for measurement in measurements {
+        if measurement.read_write.is_none() {
+            measurement.read_write = Some(ReadWrite::new());
+        }
+    }
+}
+
+// mark each of the given CHARACTERISTICs as read-only by adding READ_ONLY
+pub(crate) fn set_characteristics_readonly(characteristics: &mut [Characteristic]) {
+    for characteristic in characteristics {
+        if characteristic.read_only.is_none() {
+            characteristic.read_only = Some(ReadOnly::new());
+        }
+    }
+}
+
+// set the ECU_ADDRESS_EXTENSION of each of the given MEASUREMENTs and CHARACTERISTICs
+// This is needed on targets with banked memory, where the address alone does not identify the variable.
+pub(crate) fn set_address_extension(
+    measurements: &mut [Measurement],
+    characteristics: &mut [Characteristic],
+    extension: i16,
+) {
+    for measurement in measurements {
+        measurement.ecu_address_extension = Some(EcuAddressExtension::new(extension));
+    }
+    for characteristic in characteristics {
+        characteristic.ecu_address_extension = Some(EcuAddressExtension::new(extension));
+    }
+}
+
+// set the limits of each of the given MEASUREMENTs and CHARACTERISTICs that has no conversion
+// Items with a conversion, e.g. for an enum, keep the limits that were derived from the conversion.
+// Returns the number of items whose limits were set.
+pub(crate) fn set_default_limits(
+    measurements: &mut [Measurement],
+    characteristics: &mut [Characteristic],
+    lower_limit: f64,
+    upper_limit: f64,
+) -> usize {
+    let mut count = 0;
+    for measurement in measurements
+        .iter_mut()
+        .filter(|measurement| measurement.conversion == "NO_COMPU_METHOD")
+    {
+        measurement.lower_limit = lower_limit;
+        measurement.upper_limit = upper_limit;
+        count += 1;
+    }
+    for characteristic in characteristics
+        .iter_mut()
+        .filter(|characteristic| characteristic.conversion == "NO_COMPU_METHOD")
+    {
+        characteristic.lower_limit = lower_limit;
+        characteristic.upper_limit = upper_limit;
+        count += 1;
+    }
+    count
+}
+
+// set the address of each newly inserted item for which an override was given
+// The items are identified by the symbol in their SYMBOL_LINK, or by their name if there is no SYMBOL_LINK
+pub(crate) fn set_address_overrides(
+    measurements: &mut [Measurement],
+    characteristics: &mut [Characteristic],
+    measurement_overrides: &HashMap<String, u64>,
+    characteristic_overrides: &HashMap<String, u64>,
+    log_msgs: &mut Vec<String>,
+) {
+    for (symbol, address) in measurement_overrides {
+        let measurement = measurements.iter_mut().find(|measurement| {
+            measurement
+                .symbol_link
+                .as_ref()
+                .map_or(&measurement.name, |sl| &sl.symbol_name)
+                == symbol
+        });
+        if let Some(measurement) = measurement {
+            if let Some(ecu_address) = &mut measurement.ecu_address {
+                ecu_address.address = *address as u32;
+            }
+            log_msgs.push(format!(
+                "Set the address of MEASUREMENT {} to 0x{address:X}",
+                measurement.name
+            ));
+        } else {
+            log_msgs.push(format!(
+                "Warning: could not override the address of {symbol}: no MEASUREMENT was inserted for it"
+            ));
+        }
+    }
+    for (symbol, address) in characteristic_overrides {
+        let characteristic = characteristics.iter_mut().find(|characteristic| {
+            characteristic
+                .symbol_link
+                .as_ref()
+                .map_or(&characteristic.name, |sl| &sl.symbol_name)
+                == symbol
+        });
+        if let Some(characteristic) = characteristic {
+            characteristic.address = *address as u32;
+            log_msgs.push(format!(
+                "Set the address of CHARACTERISTIC {} to 0x{address:X}",
+                characteristic.name
+            ));
+        } else {
+            log_msgs.push(format!(
+                "Warning: could not override the address of {symbol}: no CHARACTERISTIC was inserted for it"
+            ));
+        }
+    }
+}
+
+// build the block "IF_DATA XCP /begin DAQ_EVENT FIXED_EVENT_LIST EVENT <n> /end DAQ_EVENT"
+fn make_daq_event_ifdata(event: u16) -> IfData {
+    let mut fixed_event_list = ifdata::FixedEventList::new();
+    fixed_event_list.event.push(ifdata::Event2::new(event));
+    let mut daq_event = ifdata::DaqEvent::new();
+    daq_event.fixed_event_list = Some(fixed_event_list);
+    let mut xcp = ifdata::Xcp::new();
+    xcp.daq_event = Some(daq_event);
+    let mut decoded_ifdata = ifdata::A2mlVector::new();
+    decoded_ifdata.xcp = Some(xcp);
+
+    let mut event_ifdata = IfData::new();
+    decoded_ifdata.store_to_ifdata(&mut event_ifdata);
+    event_ifdata
 }
 
 fn is_simple_type(typeinfo: &TypeInfo) -> bool {
@@ -639,12 +1277,7 @@ fn check_and_insert_simple_type(
     let mut any_inserted = false;
 
     // insert if the address is inside a given range, or if a regex matches the symbol name
-    if is_insert_requested(
-        sym_info.address,
-        &sym_info.name,
-        isupp.measurement_ranges,
-        &isupp.compiled_meas_re,
-    ) {
+    if isupp.is_measurement_requested(sym_info) {
         match insert_measurement_sym(
             isupp.module,
             isupp.debug_data,
@@ -652,6 +1285,7 @@ fn check_and_insert_simple_type(
             &isupp.name_map,
             &isupp.sym_map,
             isupp.version,
+            isupp.name_affix,
         ) {
             Ok(measurement_name) => {
                 log_msgs.push(format!(
@@ -674,17 +1308,13 @@ fn check_and_insert_simple_type(
             }
             Err(errmsg) => {
                 log_msgs.push(format!("Skipped: {errmsg}"));
+                isupp.error_count += 1;
             }
         }
     }
 
     // insert if the address is inside a given range, or if a regex matches the symbol name
-    if is_insert_requested(
-        sym_info.address,
-        &sym_info.name,
-        isupp.characteristic_ranges,
-        &isupp.compiled_char_re,
-    ) {
+    if isupp.is_characteristic_requested(sym_info) {
         match insert_characteristic_sym(
             isupp.module,
             isupp.debug_data,
@@ -693,6 +1323,7 @@ fn check_and_insert_simple_type(
             &isupp.name_map,
             &isupp.sym_map,
             isupp.version,
+            isupp.name_affix,
         ) {
             Ok(characteristic_name) => {
                 log_msgs.push(format!(
@@ -715,6 +1346,7 @@ fn check_and_insert_simple_type(
             }
             Err(errmsg) => {
                 log_msgs.push(format!("Skipped: {errmsg}"));
+                isupp.error_count += 1;
             }
         }
     }
@@ -730,12 +1362,7 @@ fn check_and_insert_instance<'dbg>(
     let mut any_inserted = false;
 
     // insert if the address is inside a given range, or if a regex matches the symbol name
-    if is_insert_requested(
-        sym_info.address,
-        &sym_info.name,
-        isupp.measurement_ranges,
-        &isupp.compiled_meas_re,
-    ) {
+    if isupp.is_measurement_requested(sym_info) {
         match insert_instance_sym(
             isupp.module,
             isupp.debug_data,
@@ -744,6 +1371,7 @@ fn check_and_insert_instance<'dbg>(
             &isupp.name_map,
             &isupp.sym_map,
             false,
+            isupp.name_affix,
         ) {
             Ok((instance_name, typedef_typeinfo)) => {
                 log_msgs.push(format!(
@@ -769,17 +1397,13 @@ fn check_and_insert_instance<'dbg>(
             }
             Err(errmsg) => {
                 log_msgs.push(format!("Skipped: {errmsg}"));
+                isupp.error_count += 1;
             }
         }
     }
 
     // insert if the address is inside a given range, or if a regex matches the symbol name
-    if is_insert_requested(
-        sym_info.address,
-        &sym_info.name,
-        isupp.characteristic_ranges,
-        &isupp.compiled_char_re,
-    ) {
+    if isupp.is_characteristic_requested(sym_info) {
         match insert_instance_sym(
             isupp.module,
             isupp.debug_data,
@@ -788,6 +1412,7 @@ fn check_and_insert_instance<'dbg>(
             &isupp.name_map,
             &isupp.sym_map,
             true,
+            isupp.name_affix,
         ) {
             Ok((instance_name, typedef_typeinfo)) => {
                 log_msgs.push(format!(
@@ -813,6 +1438,7 @@ fn check_and_insert_instance<'dbg>(
             }
             Err(errmsg) => {
                 log_msgs.push(format!("Skipped: {errmsg}"));
+                isupp.error_count += 1;
             }
         }
     }
@@ -820,6 +1446,10 @@ fn check_and_insert_instance<'dbg>(
     any_inserted
 }
 
+fn is_error_limit_reached(error_count: u32, max_errors: Option<u32>) -> bool {
+    max_errors.is_some_and(|max_errors| error_count >= max_errors)
+}
+
 fn is_insert_requested(
     address: u64,
     symbol_name: &str,
@@ -836,6 +1466,12 @@ fn is_insert_requested(
         .any(|re| re.is_match(symbol_name))
 }
 
+// symbols that match any of the exclude regexes are never inserted, even if they are inside a range or match an include regex
+// The full name is checked, so for struct members the exclusion can match the member path, e.g. ".*\\.padding.*"
+fn is_insert_excluded(symbol_name: &str, exclude_regexes: &[Regex]) -> bool {
+    exclude_regexes.iter().any(|re| re.is_match(symbol_name))
+}
+
 fn create_or_update_group(
     module: &mut Module,
     group_name: &str,
@@ -882,27 +1518,321 @@ fn create_or_update_group(
     }
 }
 
-fn insert_instance_sym<'dbg>(
+/// Put the given MEASUREMENTs and CHARACTERISTICs into one GROUP per peripheral, if they were created from
+/// the registers of an SVD file. Items of other variables are not changed.
+/// Returns the number of items that were added to a peripheral group.
+pub(crate) fn create_peripheral_groups(
     module: &mut Module,
-    debug_data: &'dbg DebugData,
-    instance_sym: &str,
-    sym_info: &SymbolInfo<'dbg>,
-    name_map: &HashMap<String, ItemType>,
-    sym_map: &HashMap<String, Vec<ItemType>>,
-    is_calib: bool,
-) -> Result<(String, &'dbg TypeInfo), String> {
-    if !matches!(&sym_info.typeinfo.datatype, DbgDataType::FuncPtr(_)) {
-        // Abort if a INSTANCE for this symbol already exists. Warn if any other reference to the symbol exists
-        let item_name = make_unique_instance_name(module, sym_map, &sym_info.name, name_map)?;
+    first_new_measurement: usize,
+    first_new_characteristic: usize,
+    debug_data: &DebugData,
+) -> usize {
+    let get_peripheral = |name: &str, symbol_link: &Option<SymbolLink>| {
+        let symbol_name = symbol_link
+            .as_ref()
+            .map_or(name, |symbol_link| &symbol_link.symbol_name);
+        let base_name = symbol_name.split(['.', '[']).next().unwrap_or(symbol_name);
+        // variables of the debug info take precedence over peripherals with the same name
+        let svd = debug_data.svd.as_ref()?;
+        (svd.peripherals.variables.contains_key(base_name)
+            && !debug_data.variables.contains_key(base_name))
+        .then(|| base_name.to_string())
+    };
 
-        // use "magic" names to signal to the typedef creation code which kind of typedef should be created for this INSTANCE
-        let typdef_name = if is_calib {
-            update::typedef::FLAG_CREATE_CALIB.to_string()
-        } else {
-            update::typedef::FLAG_CREATE_MEAS.to_string()
-        };
+    let mut peripheral_items: IndexMap<String, (Vec<String>, Vec<String>)> = IndexMap::new();
+    for measurement in &module.measurement[first_new_measurement..] {
+        if let Some(peripheral) = get_peripheral(&measurement.name, &measurement.symbol_link) {
+            peripheral_items
+                .entry(peripheral)
+                .or_default()
+                .1
+                .push(measurement.name.clone());
+        }
+    }
+    for characteristic in &module.characteristic[first_new_characteristic..] {
+        if let Some(peripheral) = get_peripheral(&characteristic.name, &characteristic.symbol_link)
+        {
+            peripheral_items
+                .entry(peripheral)
+                .or_default()
+                .0
+                .push(characteristic.name.clone());
+        }
+    }
 
-        let mut new_instance_sym = Instance::new(
+    let mut count = 0;
+    for (peripheral, (characteristic_list, measurement_list)) in peripheral_items {
+        count += characteristic_list.len() + measurement_list.len();
+        create_or_update_group(module, &peripheral, characteristic_list, measurement_list);
+    }
+    count
+}
+
+// A GROUP that should exist for an INSTANCE or for one structure level inside of it
+struct InstanceGroup {
+    name: String,
+    characteristics: Vec<String>,
+    measurements: Vec<String>,
+    sub_groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TypedefKind {
+    Structure(usize),
+    Measurement,
+    Characteristic,
+}
+
+/// Create a GROUP for each INSTANCE of a TYPEDEF_STRUCTURE.
+/// The group references all leaf components of the instance (e.g. instance.component.subcomponent),
+/// and each nested structure level gets its own sub-group.
+/// Groups that already exist are extended instead of duplicated, so that running this repeatedly
+/// does not change the result.
+pub(crate) fn create_instance_groups(
+    module: &mut Module,
+    target_group: Option<&str>,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut instance_groups = Vec::new();
+    let mut top_level_groups = Vec::new();
+    {
+        let mut typedef_kinds = HashMap::new();
+        for (idx, td_struct) in module.typedef_structure.iter().enumerate() {
+            typedef_kinds.insert(td_struct.name.as_str(), TypedefKind::Structure(idx));
+        }
+        for td_meas in &module.typedef_measurement {
+            typedef_kinds.insert(td_meas.name.as_str(), TypedefKind::Measurement);
+        }
+        for td_char in &module.typedef_characteristic {
+            typedef_kinds.insert(td_char.name.as_str(), TypedefKind::Characteristic);
+        }
+        for td_axis in &module.typedef_axis {
+            typedef_kinds.insert(td_axis.name.as_str(), TypedefKind::Characteristic);
+        }
+        for td_blob in &module.typedef_blob {
+            typedef_kinds.insert(td_blob.name.as_str(), TypedefKind::Characteristic);
+        }
+
+        for instance in &module.instance {
+            // INSTANCEs of TYPEDEF_MEASUREMENT or TYPEDEF_CHARACTERISTIC have no components
+            if let Some(TypedefKind::Structure(idx)) = typedef_kinds.get(instance.type_ref.as_str())
+            {
+                let dims = instance
+                    .matrix_dim
+                    .as_ref()
+                    .map_or(&[] as &[u16], |md| md.dim_list.as_slice());
+                add_structure_groups(
+                    &module.typedef_structure,
+                    &typedef_kinds,
+                    instance.name.clone(),
+                    dims,
+                    *idx,
+                    &mut Vec::new(),
+                    &mut instance_groups,
+                );
+                top_level_groups.push(instance.name.clone());
+            }
+        }
+    }
+
+    let mut created_count = 0;
+    for instance_group in instance_groups {
+        let is_top_level = top_level_groups.contains(&instance_group.name);
+        let group = match module
+            .group
+            .iter()
+            .position(|grp| grp.name == instance_group.name)
+        {
+            Some(pos) => &mut module.group[pos],
+            None => {
+                let mut group = Group::new(instance_group.name.clone(), String::new());
+                if is_top_level && target_group.is_none() {
+                    group.root = Some(Root::new());
+                }
+                module.group.push(group);
+                created_count += 1;
+                let len = module.group.len();
+                &mut module.group[len - 1]
+            }
+        };
+
+        if !instance_group.characteristics.is_empty() {
+            let ref_characteristic = group
+                .ref_characteristic
+                .get_or_insert_with(RefCharacteristic::new);
+            extend_unique(
+                &mut ref_characteristic.identifier_list,
+                instance_group.characteristics,
+            );
+        }
+        if !instance_group.measurements.is_empty() {
+            let ref_measurement = group
+                .ref_measurement
+                .get_or_insert_with(RefMeasurement::new);
+            extend_unique(
+                &mut ref_measurement.identifier_list,
+                instance_group.measurements,
+            );
+        }
+        if !instance_group.sub_groups.is_empty() {
+            let sub_group = group.sub_group.get_or_insert_with(SubGroup::new);
+            extend_unique(&mut sub_group.identifier_list, instance_group.sub_groups);
+        }
+    }
+
+    // attach the groups of all instances to the target group
+    if let Some(group_name) = target_group {
+        if !top_level_groups.is_empty() {
+            let group = match module.group.iter().position(|grp| grp.name == group_name) {
+                Some(pos) => &mut module.group[pos],
+                None => {
+                    let mut group = Group::new(group_name.to_string(), String::new());
+                    group.root = Some(Root::new());
+                    module.group.push(group);
+                    let len = module.group.len();
+                    &mut module.group[len - 1]
+                }
+            };
+            let sub_group = group.sub_group.get_or_insert_with(SubGroup::new);
+            extend_unique(&mut sub_group.identifier_list, top_level_groups);
+        }
+    }
+
+    if created_count > 0 {
+        log_msgs.push(format!("Created {created_count} GROUPs for INSTANCEs"));
+    }
+    created_count
+}
+
+// create the groups for a (possibly multi-dimensional) array of structures
+// Each array element gets its own sub-group, e.g. instance.component[1]
+fn add_structure_groups(
+    structures: &[TypedefStructure],
+    typedef_kinds: &HashMap<&str, TypedefKind>,
+    path: String,
+    dims: &[u16],
+    struct_idx: usize,
+    parents: &mut Vec<usize>,
+    groups: &mut Vec<InstanceGroup>,
+) {
+    // older files pad MATRIX_DIM with trailing ones, e.g. MATRIX_DIM 5 1 1
+    let dim_count = dims
+        .iter()
+        .rposition(|dim| *dim > 1)
+        .map_or(0, |pos| pos + 1);
+    let dims = &dims[..dim_count];
+
+    if dims.is_empty() {
+        add_single_structure_group(structures, typedef_kinds, path, struct_idx, parents, groups);
+    } else {
+        let pos = groups.len();
+        let mut element_paths = vec![path.clone()];
+        for dim in dims {
+            element_paths = element_paths
+                .iter()
+                .flat_map(|elem_path| (0..*dim).map(move |idx| format!("{elem_path}[{idx}]")))
+                .collect();
+        }
+        for elem_path in &element_paths {
+            add_single_structure_group(
+                structures,
+                typedef_kinds,
+                elem_path.clone(),
+                struct_idx,
+                parents,
+                groups,
+            );
+        }
+        let group = InstanceGroup {
+            name: path,
+            characteristics: Vec::new(),
+            measurements: Vec::new(),
+            sub_groups: element_paths,
+        };
+        groups.insert(pos, group);
+    }
+}
+
+// create the group for a single structure, and recursively for all structures it contains
+fn add_single_structure_group(
+    structures: &[TypedefStructure],
+    typedef_kinds: &HashMap<&str, TypedefKind>,
+    path: String,
+    struct_idx: usize,
+    parents: &mut Vec<usize>,
+    groups: &mut Vec<InstanceGroup>,
+) {
+    let pos = groups.len();
+    let mut group = InstanceGroup {
+        name: path,
+        characteristics: Vec::new(),
+        measurements: Vec::new(),
+        sub_groups: Vec::new(),
+    };
+
+    parents.push(struct_idx);
+    for component in &structures[struct_idx].structure_component {
+        let component_path = format!("{}.{}", group.name, component.component_name);
+        match typedef_kinds.get(component.component_type.as_str()) {
+            // a structure can only contain itself through a pointer; don't follow it
+            Some(TypedefKind::Structure(idx)) if !parents.contains(idx) => {
+                let dims = component
+                    .matrix_dim
+                    .as_ref()
+                    .map_or(&[] as &[u16], |md| md.dim_list.as_slice());
+                add_structure_groups(
+                    structures,
+                    typedef_kinds,
+                    component_path.clone(),
+                    dims,
+                    *idx,
+                    parents,
+                    groups,
+                );
+                group.sub_groups.push(component_path);
+            }
+            Some(TypedefKind::Measurement) => group.measurements.push(component_path),
+            Some(TypedefKind::Characteristic) => group.characteristics.push(component_path),
+            Some(TypedefKind::Structure(_)) | None => {}
+        }
+    }
+    parents.pop();
+
+    groups.insert(pos, group);
+}
+
+fn extend_unique(list: &mut Vec<String>, new_items: Vec<String>) {
+    for item in new_items {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_instance_sym<'dbg>(
+    module: &mut Module,
+    debug_data: &'dbg DebugData,
+    instance_sym: &str,
+    sym_info: &SymbolInfo<'dbg>,
+    name_map: &HashMap<String, ItemType>,
+    sym_map: &HashMap<String, Vec<ItemType>>,
+    is_calib: bool,
+    name_affix: &NameAffix,
+) -> Result<(String, &'dbg TypeInfo), String> {
+    if !matches!(&sym_info.typeinfo.datatype, DbgDataType::FuncPtr(_)) {
+        // Abort if a INSTANCE for this symbol already exists. Warn if any other reference to the symbol exists
+        let item_name =
+            make_unique_instance_name(module, sym_map, &sym_info.name, name_map, name_affix)?;
+
+        // use "magic" names to signal to the typedef creation code which kind of typedef should be created for this INSTANCE
+        let typdef_name = if is_calib {
+            update::typedef::FLAG_CREATE_CALIB.to_string()
+        } else {
+            update::typedef::FLAG_CREATE_MEAS.to_string()
+        };
+
+        let mut new_instance_sym = Instance::new(
             item_name.clone(),
             format!("instance for symbol {}", sym_info.name),
             typdef_name,
@@ -998,6 +1928,12 @@ mod test {
             target_group,
             &mut log_msgs,
             false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         assert_eq!(a2l.project.module[0].measurement.len(), 2);
         assert_eq!(a2l.project.module[0].characteristic.len(), 2);
@@ -1017,6 +1953,12 @@ mod test {
             target_group,
             &mut log_msgs,
             false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         // verify that the new items were added with a prefix
         assert_eq!(a2l.project.module[0].measurement.len(), 4);
@@ -1044,6 +1986,12 @@ mod test {
             target_group,
             &mut log_msgs,
             false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         for msg in log_msgs {
             println!("{}", msg);
@@ -1053,6 +2001,186 @@ mod test {
         assert_eq!(a2l.project.module[0].characteristic.len(), 4);
     }
 
+    #[test]
+    fn test_insert_items_name_affix() {
+        let mut a2l = a2lfile::new();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+        let name_affix = NameAffix {
+            prefix: "TST_".to_string(),
+            suffix: "_x".to_string(),
+        };
+
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["Measurement_Value", "Curve_InternalAxis"],
+            vec!["Characteristic_Value"],
+            Some("TestGroup"),
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &name_affix,
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement[0].name, "TST_Measurement_Value_x");
+        assert_eq!(
+            module.measurement[0]
+                .symbol_link
+                .as_ref()
+                .unwrap()
+                .symbol_name,
+            "Measurement_Value"
+        );
+        assert_eq!(module.characteristic[0].name, "TST_Characteristic_Value_x");
+        // the INSTANCE gets the prefix and suffix, but its TYPEDEF is named after the type
+        assert_eq!(module.instance[0].name, "TST_Curve_InternalAxis_x");
+        assert_eq!(module.instance[0].type_ref, "UpdateTest_Curve_InternalAxis");
+        let group = &module.group[0];
+        let ref_measurement = group.ref_measurement.as_ref().unwrap();
+        assert!(ref_measurement
+            .identifier_list
+            .contains(&"TST_Measurement_Value_x".to_string()));
+
+        // the symbols were already inserted; the check for duplicates uses the final names
+        let mut log_msgs = Vec::new();
+        insert_many(
+            &mut a2l,
+            &debug_data,
+            &[],
+            &[],
+            vec!["Measurement_Value", "Measurement_Matrix"],
+            vec![],
+            vec![],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            &name_affix,
+            false,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        assert_eq!(module.measurement[1].name, "TST_Measurement_Matrix_x");
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("TST_Measurement_Value_x already references symbol")));
+    }
+
+    #[test]
+    fn test_insert_items_wide_string() {
+        let mut a2l = a2lfile::new();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/wide_string_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        let characteristic_symbols = vec!["Wide_String", "Utf16_String", "Word_Array"];
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec![],
+            characteristic_symbols,
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.characteristic.len(), 3);
+
+        // wchar_t is a 32-bit type on linux
+        let wide_string = &module.characteristic[0];
+        assert_eq!(wide_string.characteristic_type, CharacteristicType::Ascii);
+        assert_eq!(wide_string.number.as_ref().unwrap().number, 16);
+        assert!(wide_string.matrix_dim.is_none());
+        assert_eq!(
+            wide_string.encoding.as_ref().unwrap().encoding,
+            a2lfile::CharacterEncoding::Utf32
+        );
+
+        let utf16_string = &module.characteristic[1];
+        assert_eq!(utf16_string.characteristic_type, CharacteristicType::Ascii);
+        assert_eq!(utf16_string.number.as_ref().unwrap().number, 12);
+        assert_eq!(
+            utf16_string.encoding.as_ref().unwrap().encoding,
+            a2lfile::CharacterEncoding::Utf16
+        );
+
+        // an array of uint16_t is not a string
+        let word_array = &module.characteristic[2];
+        assert_eq!(word_array.characteristic_type, CharacteristicType::ValBlk);
+        assert!(word_array.encoding.is_none());
+    }
+
+    #[test]
+    fn test_insert_items_max_errors() {
+        let mut a2l = a2lfile::new();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        // the insert stops after two symbols could not be found
+        let measurement_symbols = vec!["Does_Not_Exist_1", "Does_Not_Exist_2", "Measurement_Value"];
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            measurement_symbols,
+            vec!["Characteristic_Value"],
+            None,
+            &mut log_msgs,
+            false,
+            Some(2),
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        assert!(a2l.project.module[0].measurement.is_empty());
+        assert!(a2l.project.module[0].characteristic.is_empty());
+        assert_eq!(log_msgs.last().unwrap(), "Insert stopped after 2 errors");
+
+        // without the limit, the valid symbols are inserted
+        let measurement_symbols = vec!["Does_Not_Exist_1", "Does_Not_Exist_2", "Measurement_Value"];
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            measurement_symbols,
+            vec!["Characteristic_Value"],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        assert_eq!(a2l.project.module[0].measurement.len(), 1);
+        assert_eq!(a2l.project.module[0].characteristic.len(), 1);
+    }
     #[test]
     fn test_insert_items_nonexistent() {
         let mut a2l = a2lfile::new();
@@ -1075,6 +2203,12 @@ mod test {
             target_group,
             &mut log_msgs,
             false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         // nothing was added
         assert_eq!(a2l.project.module[0].measurement.len(), 0);
@@ -1093,6 +2227,12 @@ mod test {
             target_group,
             &mut log_msgs,
             true,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         // nothing was added
         assert_eq!(a2l.project.module[0].measurement.len(), 0);
@@ -1129,6 +2269,12 @@ mod test {
             target_group,
             &mut log_msgs,
             true,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         // the basic types are inserted as MEASUREMENTs and CHARACTERISTICs as in the previous test
         assert_eq!(a2l.project.module[0].measurement.len(), 2);
@@ -1146,6 +2292,164 @@ mod test {
         assert_eq!(a2l.project.module[0].typedef_structure.len(), 2);
     }
 
+    #[test]
+    fn test_insert_items_union() {
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/union_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        // UnionMode::Largest: only the largest member of the union is inserted
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["union_value"],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            Some(UnionMode::Largest),
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 1);
+        assert_eq!(module.measurement[0].name, "union_value.as_u32");
+        assert_eq!(module.measurement[0].datatype, DataType::Ulong);
+
+        // UnionMode::All: each member is inserted, and all of them have the same address
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["union_value"],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            Some(UnionMode::All),
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 3);
+        assert!(module
+            .measurement
+            .iter()
+            .any(|m| m.name == "union_value.as_u8"));
+        assert!(module
+            .measurement
+            .iter()
+            .any(|m| m.name == "union_value.as_u16"));
+        let address = module.measurement[0].ecu_address.as_ref().unwrap().address;
+        assert!(module
+            .measurement
+            .iter()
+            .all(|m| m.ecu_address.as_ref().unwrap().address == address));
+    }
+
+    #[test]
+    fn test_insert_items_nested_union() {
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/union_test.elf"),
+            false,
+        )
+        .unwrap();
+        let base_address = debug_data.variables["tagged_array"][0].address as u32;
+
+        // members of unions inside of an array of structs can be named explicitly; they all start at the address of the union
+        // Without a union mode, the union itself is inserted as its first member
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec![
+                "tagged_array._1_.data.word",
+                "tagged_array._1_.data.parts.hi",
+                "tagged_array._2_.data",
+            ],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 3);
+        let get_address = |name: &str| {
+            module
+                .measurement
+                .iter()
+                .find(|m| m.name == name)
+                .unwrap()
+                .ecu_address
+                .as_ref()
+                .unwrap()
+                .address
+        };
+        // sizeof(TaggedValue) == 4, the union data has the offset 2
+        assert_eq!(get_address("tagged_array._1_.data.word"), base_address + 6);
+        assert_eq!(
+            get_address("tagged_array._1_.data.parts.hi"),
+            base_address + 7
+        );
+        assert_eq!(get_address("tagged_array._2_.data.word"), base_address + 10);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("its first member word is used")));
+
+        // with structures, all members of the union become STRUCTURE_COMPONENTs at offset 0
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["tagged_array"],
+            vec![],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.instance.len(), 1);
+        let union_typedef = module
+            .typedef_structure
+            .iter()
+            .find(|td| {
+                td.structure_component
+                    .iter()
+                    .any(|sc| sc.component_name == "word")
+            })
+            .unwrap();
+        assert_eq!(union_typedef.structure_component.len(), 3);
+        assert!(union_typedef
+            .structure_component
+            .iter()
+            .all(|sc| sc.address_offset == 0));
+    }
+
     #[test]
     fn test_insert_multiple_normal() {
         let mut a2l = a2lfile::new();
@@ -1169,9 +2473,14 @@ mod test {
             characteristic_ranges,
             measurement_regexes,
             characteristic_regexes,
+            vec![],
+            vec![],
             target_group,
             &mut log_msgs,
             false,
+            None,
+            &NameAffix::default(),
+            false,
         );
         // ^Measurement_.*$ expands to:
         //   Measurement_Matrix, Measurement_Value, Measurement_Bitfield.bits_1, Measurement_Bitfield.bits_2, Measurement_Bitfield.bits_3
@@ -1196,9 +2505,14 @@ mod test {
             characteristic_ranges,
             measurement_regexes,
             characteristic_regexes,
+            vec![],
+            vec![],
             target_group,
             &mut log_msgs,
             false,
+            None,
+            &NameAffix::default(),
+            false,
         );
         assert!(a2l.project.module[0].measurement.len() > 8);
         assert!(a2l.project.module[0].characteristic.len() > 6);
@@ -1212,6 +2526,123 @@ mod test {
             .any(|c| c.name == "CHARACTERISTIC.Measurement_Value"));
     }
 
+    #[test]
+    fn test_insert_multiple_exclude() {
+        let mut a2l = a2lfile::new();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        // the same regexes as in test_insert_multiple_normal, but some of the matches are excluded
+        let measurement_regexes = vec![r"^Measurement_.*$"];
+        let characteristic_regexes = vec![r"^Characteristic_.*$", r"^Map_.*$"];
+        let measurement_exclude = vec!["Measurement_Matrix", r".*\.bits_2"];
+        let characteristic_exclude = vec![r"Map_InternalAxis\..*"];
+        let mut log_msgs = Vec::new();
+        insert_many(
+            &mut a2l,
+            &debug_data,
+            &[],
+            &[],
+            measurement_regexes,
+            characteristic_regexes,
+            measurement_exclude,
+            characteristic_exclude,
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            &NameAffix::default(),
+            false,
+        );
+        let module = &a2l.project.module[0];
+        let measurement_names: Vec<&str> =
+            module.measurement.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(measurement_names.len(), 3);
+        assert!(measurement_names.contains(&"Measurement_Value"));
+        assert!(measurement_names.contains(&"Measurement_Bitfield.bits_1"));
+        assert!(!measurement_names.contains(&"Measurement_Bitfield.bits_2"));
+        // Map_InternalAxis.x, Map_InternalAxis.y and Map_InternalAxis.value are excluded
+        assert_eq!(module.characteristic.len(), 3);
+        assert!(!module
+            .characteristic
+            .iter()
+            .any(|c| c.name.starts_with("Map_InternalAxis")));
+        assert!(log_msgs.contains(&"Excluded 2 symbols from insertion as MEASUREMENTs".to_string()));
+        assert!(
+            log_msgs.contains(&"Excluded 3 symbols from insertion as CHARACTERISTICs".to_string())
+        );
+
+        // the exclusion also applies to address ranges
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_many(
+            &mut a2l,
+            &debug_data,
+            &[(0x1000, 0x10000)],
+            &[(0x1000, 0x10000)],
+            vec![],
+            vec![],
+            vec![".*"],
+            vec!["Characteristic_.*"],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            &NameAffix::default(),
+            false,
+        );
+        let module = &a2l.project.module[0];
+        assert!(module.measurement.is_empty());
+        assert!(!module.characteristic.is_empty());
+        assert!(!module
+            .characteristic
+            .iter()
+            .any(|c| c.name.starts_with("Characteristic_")));
+    }
+
+    #[test]
+    fn test_insert_anonymous_typedef_structures() {
+        let mut a2l = a2lfile::new();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/anonymous_typedef_test.elf"),
+            false,
+        )
+        .unwrap();
+        let mut log_msgs = Vec::new();
+        insert_many(
+            &mut a2l,
+            &debug_data,
+            &[],
+            &[],
+            vec![r"^Anon_.*_Var$"],
+            vec![],
+            vec![],
+            vec![],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            &NameAffix::default(),
+            false,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.instance.len(), 2);
+        // the TYPEDEF_STRUCTUREs are named after the C typedefs, since the structs themselves have no name
+        let typedef_names: Vec<&str> = module
+            .typedef_structure
+            .iter()
+            .map(|td| td.name.as_str())
+            .collect();
+        assert!(typedef_names.contains(&"Anon_Struct"));
+        assert!(typedef_names.contains(&"Anon_Outer"));
+        assert!(!typedef_names
+            .iter()
+            .any(|name| name.is_empty() || name.contains("_unnamed_")));
+    }
+
     #[test]
     fn test_insert_multiple_structures() {
         let mut a2l = a2lfile::new();
@@ -1235,9 +2666,14 @@ mod test {
             characteristic_ranges,
             measurement_regexes,
             characteristic_regexes,
+            vec![],
+            vec![],
             target_group,
             &mut log_msgs,
             true,
+            None,
+            &NameAffix::default(),
+            false,
         );
         // of the items matched by the measurement regex, only Measurement_Matrix, Measurement_Value are basic types
         assert_eq!(a2l.project.module[0].measurement.len(), 2);
@@ -1280,9 +2716,14 @@ mod test {
             characteristic_ranges,
             measurement_regexes,
             characteristic_regexes,
+            vec![],
+            vec![],
             target_group,
             &mut log_msgs,
             true,
+            None,
+            &NameAffix::default(),
+            false,
         );
         assert_eq!(a2l.project.module[0].instance.len(), 5);
         assert_eq!(
@@ -1313,8 +2754,322 @@ mod test {
             target_group,
             &mut log_msgs,
             false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
         );
         assert_eq!(a2l.project.module[0].measurement.len(), 0);
         assert_eq!(a2l.project.module[0].characteristic.len(), 0);
     }
+
+    #[test]
+    fn test_create_instance_groups() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin TYPEDEF_MEASUREMENT TM_Value "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end TYPEDEF_MEASUREMENT
+    /begin TYPEDEF_CHARACTERISTIC TC_Value "" VALUE RL_UBYTE 0 NO_COMPU_METHOD 0 255
+    /end TYPEDEF_CHARACTERISTIC
+    /begin TYPEDEF_STRUCTURE TS_Inner "" 2
+      /begin STRUCTURE_COMPONENT param TC_Value 0
+      /end STRUCTURE_COMPONENT
+      /begin STRUCTURE_COMPONENT signal TM_Value 1
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+    /begin TYPEDEF_STRUCTURE TS_Outer "" 6
+      /begin STRUCTURE_COMPONENT value TC_Value 0
+      /end STRUCTURE_COMPONENT
+      /begin STRUCTURE_COMPONENT inner TS_Inner 1
+      /end STRUCTURE_COMPONENT
+      /begin STRUCTURE_COMPONENT inner_array TS_Inner 3
+        MATRIX_DIM 2
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+    /begin INSTANCE Outer "" TS_Outer 0x1000
+    /end INSTANCE
+    /begin INSTANCE SimpleValue "" TC_Value 0x2000
+    /end INSTANCE
+  /end MODULE
+/end PROJECT
+"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let created = create_instance_groups(
+            &mut a2l_file.project.module[0],
+            Some("Instances"),
+            &mut log_msgs,
+        );
+        // groups: Outer, Outer.inner, Outer.inner_array, Outer.inner_array[0], Outer.inner_array[1]
+        assert_eq!(created, 5);
+        let module = &a2l_file.project.module[0];
+        // the target group is created with the ROOT attribute, and contains the group of the instance
+        assert_eq!(module.group.len(), 6);
+        let target = module.group.iter().find(|g| g.name == "Instances").unwrap();
+        assert!(target.root.is_some());
+        assert_eq!(
+            target.sub_group.as_ref().unwrap().identifier_list,
+            vec!["Outer".to_string()]
+        );
+
+        let outer = module.group.iter().find(|g| g.name == "Outer").unwrap();
+        assert!(outer.root.is_none());
+        assert_eq!(
+            outer.ref_characteristic.as_ref().unwrap().identifier_list,
+            vec!["Outer.value".to_string()]
+        );
+        assert!(outer.ref_measurement.is_none());
+        assert_eq!(
+            outer.sub_group.as_ref().unwrap().identifier_list,
+            vec!["Outer.inner".to_string(), "Outer.inner_array".to_string()]
+        );
+
+        let inner = module
+            .group
+            .iter()
+            .find(|g| g.name == "Outer.inner")
+            .unwrap();
+        assert_eq!(
+            inner.ref_characteristic.as_ref().unwrap().identifier_list,
+            vec!["Outer.inner.param".to_string()]
+        );
+        assert_eq!(
+            inner.ref_measurement.as_ref().unwrap().identifier_list,
+            vec!["Outer.inner.signal".to_string()]
+        );
+
+        let inner_array = module
+            .group
+            .iter()
+            .find(|g| g.name == "Outer.inner_array")
+            .unwrap();
+        assert_eq!(
+            inner_array.sub_group.as_ref().unwrap().identifier_list,
+            vec![
+                "Outer.inner_array[0]".to_string(),
+                "Outer.inner_array[1]".to_string()
+            ]
+        );
+        let array_elem = module
+            .group
+            .iter()
+            .find(|g| g.name == "Outer.inner_array[1]")
+            .unwrap();
+        assert_eq!(
+            array_elem.ref_measurement.as_ref().unwrap().identifier_list,
+            vec!["Outer.inner_array[1].signal".to_string()]
+        );
+
+        // running the group creation again does not change anything
+        let before = a2l_file.clone();
+        let created = create_instance_groups(
+            &mut a2l_file.project.module[0],
+            Some("Instances"),
+            &mut log_msgs,
+        );
+        assert_eq!(created, 0);
+        assert_eq!(before, a2l_file);
+    }
+
+    #[test]
+    fn test_insert_items_array_range() {
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/union_test.elf"),
+            false,
+        )
+        .unwrap();
+        let base_address = debug_data.variables["tagged_array"][0].address as u32;
+
+        // the range [0:2] selects the elements 0 and 1; sizeof(TaggedValue) == 4
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["tagged_array[0:2].kind"],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        assert_eq!(module.measurement[0].name, "tagged_array[0].kind");
+        assert_eq!(module.measurement[1].name, "tagged_array[1].kind");
+        assert_eq!(
+            module.measurement[0].ecu_address.as_ref().unwrap().address,
+            base_address
+        );
+        assert_eq!(
+            module.measurement[1].ecu_address.as_ref().unwrap().address,
+            base_address + 4
+        );
+
+        // the array only has 3 elements, so nothing is inserted for an out of bounds range
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["tagged_array[1:5].kind"],
+            vec![],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        assert!(a2l.project.module[0].measurement.is_empty());
+        assert_eq!(log_msgs.len(), 1);
+        assert!(log_msgs[0].contains("the array only has 3 elements"));
+    }
+
+    #[test]
+    fn test_insert_items_byte_order() {
+        // big_regs is declared with scalar_storage_order("big-endian") in a little-endian file
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/endian_test.elf"),
+            false,
+        )
+        .unwrap();
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["big_regs.status", "native_regs.status"],
+            vec!["big_regs.table"],
+            None,
+            &mut log_msgs,
+            false,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        assert_eq!(
+            module.measurement[0]
+                .byte_order
+                .as_ref()
+                .unwrap()
+                .byte_order,
+            a2lfile::ByteOrderEnum::BigEndian
+        );
+        assert!(module.measurement[1].byte_order.is_none());
+        assert_eq!(module.characteristic.len(), 1);
+        assert_eq!(
+            module.characteristic[0]
+                .byte_order
+                .as_ref()
+                .unwrap()
+                .byte_order,
+            a2lfile::ByteOrderEnum::BigEndian
+        );
+    }
+
+    #[test]
+    fn test_insert_items_flexible_array() {
+        // Flexible_Struct ends with the flexible array member "uint16_t data[]"
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/flexible_array_test.elf"),
+            false,
+        )
+        .unwrap();
+        let get_data_component = |a2l: &A2lFile| {
+            let td_struct = &a2l.project.module[0].typedef_structure[0];
+            let component = td_struct
+                .structure_component
+                .iter()
+                .find(|sc| sc.component_name == "data")
+                .cloned();
+            (
+                td_struct.structure_component.len(),
+                td_struct.total_size,
+                component,
+            )
+        };
+
+        // without a dimension the flexible array member is skipped
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["Flexible_Struct.data", "Flexible_Struct"],
+            vec![],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert!(module.measurement.is_empty());
+        assert!(log_msgs.iter().any(|msg| msg.starts_with(
+            "Insert skipped: Symbol Flexible_Struct.data is a flexible array member"
+        )));
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.starts_with("Warning: the flexible array member data")));
+        assert_eq!(module.instance.len(), 1);
+        let (component_count, total_size, data_component) = get_data_component(&a2l);
+        assert_eq!(component_count, 2);
+        assert_eq!(total_size, 8);
+        assert!(data_component.is_none());
+
+        // with a dimension the flexible array member is an array with this number of elements
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        insert_items(
+            &mut a2l,
+            &debug_data,
+            vec!["Flexible_Struct.data", "Flexible_Struct"],
+            vec![],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            Some(4),
+            &NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.measurement.len(), 1);
+        assert_eq!(module.measurement[0].datatype, DataType::Uword);
+        assert_eq!(
+            module.measurement[0].matrix_dim.as_ref().unwrap().dim_list,
+            vec![4]
+        );
+        assert_eq!(module.instance.len(), 1);
+        let (component_count, total_size, data_component) = get_data_component(&a2l);
+        assert_eq!(component_count, 3);
+        assert_eq!(total_size, 14);
+        let data_component = data_component.unwrap();
+        assert_eq!(data_component.address_offset, 6);
+        assert_eq!(data_component.matrix_dim.unwrap().dim_list, vec![4]);
+    }
 }
@@ -0,0 +1,201 @@
+use crate::datatype::get_dbg_datatype;
+use crate::debuginfo::TypeInfo;
+use crate::update::adjust_limits;
+use a2lfile::{A2lFile, A2lObject, CharacteristicType, CompuMethod, DataType};
+
+// Check that the limits of all MEASUREMENTs, CHARACTERISTICs and AXIS_PTS are inside the value range of their data type.
+// The data type of a CHARACTERISTIC or AXIS_PTS is taken from its RECORD_LAYOUT, so no debug info is needed.
+// The limits are physical values, so the value range of the data type is converted with the COMPU_METHOD.
+// If fix is set, limits that exceed the range are clamped to it.
+// Returns the number of objects whose limits exceed the value range.
+pub(crate) fn check_limits(a2l_file: &mut A2lFile, fix: bool, log_msgs: &mut Vec<String>) -> usize {
+    let mut problem_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let find_compu_method = |name: &str| module.compu_method.iter().find(|cm| cm.name == name);
+
+        for measurement in &mut module.measurement {
+            let line = measurement.get_line();
+            if check_object_limits(
+                "MEASUREMENT",
+                &measurement.name,
+                line,
+                &measurement.datatype,
+                find_compu_method(&measurement.conversion),
+                (&mut measurement.lower_limit, &mut measurement.upper_limit),
+                fix,
+                log_msgs,
+            ) {
+                problem_count += 1;
+            }
+        }
+
+        for characteristic in &mut module.characteristic {
+            // the limits of an ASCII string do not refer to its characters
+            if characteristic.characteristic_type == CharacteristicType::Ascii {
+                continue;
+            }
+            let Some(datatype) = module
+                .record_layout
+                .iter()
+                .find(|rl| rl.name == characteristic.deposit)
+                .and_then(|rl| rl.fnc_values.as_ref())
+                .map(|fnc_values| &fnc_values.datatype)
+            else {
+                continue;
+            };
+            let line = characteristic.get_line();
+            if check_object_limits(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                line,
+                datatype,
+                find_compu_method(&characteristic.conversion),
+                (
+                    &mut characteristic.lower_limit,
+                    &mut characteristic.upper_limit,
+                ),
+                fix,
+                log_msgs,
+            ) {
+                problem_count += 1;
+            }
+        }
+
+        for axis_pts in &mut module.axis_pts {
+            let Some(datatype) = module
+                .record_layout
+                .iter()
+                .find(|rl| rl.name == axis_pts.deposit_record)
+                .and_then(|rl| rl.axis_pts_x.as_ref())
+                .map(|axis_pts_x| &axis_pts_x.datatype)
+            else {
+                continue;
+            };
+            let line = axis_pts.get_line();
+            if check_object_limits(
+                "AXIS_PTS",
+                &axis_pts.name,
+                line,
+                datatype,
+                find_compu_method(&axis_pts.conversion),
+                (&mut axis_pts.lower_limit, &mut axis_pts.upper_limit),
+                fix,
+                log_msgs,
+            ) {
+                problem_count += 1;
+            }
+        }
+    }
+
+    problem_count
+}
+
+// compare the limits of one object with the value range of its data type
+// returns true if the limits exceed the range
+#[allow(clippy::too_many_arguments)]
+fn check_object_limits(
+    blocktype: &str,
+    name: &str,
+    line: u32,
+    datatype: &DataType,
+    compu_method: Option<&CompuMethod>,
+    (lower_limit, upper_limit): (&mut f64, &mut f64),
+    fix: bool,
+    log_msgs: &mut Vec<String>,
+) -> bool {
+    let typeinfo = TypeInfo {
+        name: None,
+        unit_idx: usize::MAX,
+        datatype: get_dbg_datatype(datatype),
+        dbginfo_offset: 0,
+        big_endian: None,
+    };
+    // adjust_limits shrinks the limits to the range of the data type
+    let (min, max) = adjust_limits(&typeinfo, *lower_limit, *upper_limit, compu_method, None);
+    if !is_below(*lower_limit, min) && !is_below(max, *upper_limit) {
+        return false;
+    }
+
+    if fix {
+        log_msgs.push(format!(
+            "{blocktype} {name} on line {line}: the limits [{lower_limit}, {upper_limit}] were changed to [{min}, {max}] to match the data type {datatype}"
+        ));
+        *lower_limit = min;
+        *upper_limit = max;
+    } else {
+        log_msgs.push(format!(
+            "{blocktype} {name} on line {line}: the limits [{lower_limit}, {upper_limit}] exceed the range [{min}, {max}] of the data type {datatype}"
+        ));
+    }
+    true
+}
+
+// true if value_a is smaller than value_b
+// Small differences are caused by rounding in the conversion of the limits and are ignored.
+fn is_below(value_a: f64, value_b: f64) -> bool {
+    value_b - value_a > 1e-9 * value_a.abs().max(value_b.abs()).max(1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin CHARACTERISTIC Byte_TooLarge "" VALUE 0x1000 Value_UBYTE 0 NO_COMPU_METHOD 0 1000
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Byte_Scaled "" VALUE 0x1001 Value_UBYTE 0 Factor10 0 2550
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Byte_Ok "" VALUE 0x1002 Value_UBYTE 0 NO_COMPU_METHOD 10 200
+    /end CHARACTERISTIC
+    /begin MEASUREMENT Sword_TooSmall "" SWORD NO_COMPU_METHOD 0 0 -40000 100
+    /end MEASUREMENT
+    /begin AXIS_PTS Axis_Ok "" 0x2000 Sword_TooSmall Axis_UBYTE 0 NO_COMPU_METHOD 4 0 255
+    /end AXIS_PTS
+    /begin COMPU_METHOD Factor10 "" LINEAR "%6.1" ""
+      COEFFS_LINEAR 10 0
+    /end COMPU_METHOD
+    /begin RECORD_LAYOUT Value_UBYTE
+      FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT Axis_UBYTE
+      AXIS_PTS_X 1 UBYTE INDEX_INCR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_check_limits() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+
+        // the UBYTE CHARACTERISTIC with upper limit 1000 and the SWORD MEASUREMENT with lower limit -40000 are reported
+        let mut log_msgs = Vec::new();
+        let count = check_limits(&mut a2l_file, false, &mut log_msgs);
+        assert_eq!(count, 2);
+        assert!(log_msgs[0].contains("MEASUREMENT Sword_TooSmall"));
+        assert!(log_msgs[1].contains("CHARACTERISTIC Byte_TooLarge"));
+        assert_eq!(
+            a2l_file.project.module[0].characteristic[0].upper_limit,
+            1000.0
+        );
+
+        // clamp the limits
+        let mut log_msgs = Vec::new();
+        let count = check_limits(&mut a2l_file, true, &mut log_msgs);
+        assert_eq!(count, 2);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.characteristic[0].lower_limit, 0.0);
+        assert_eq!(module.characteristic[0].upper_limit, 255.0);
+        assert_eq!(module.measurement[0].lower_limit, -32768.0);
+        assert_eq!(module.measurement[0].upper_limit, 100.0);
+        // the scaled limits of Byte_Scaled are valid
+        assert_eq!(module.characteristic[1].upper_limit, 2550.0);
+
+        // no problems remain
+        let mut log_msgs = Vec::new();
+        assert_eq!(check_limits(&mut a2l_file, false, &mut log_msgs), 0);
+    }
+}
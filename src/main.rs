@@ -1,22 +1,67 @@
 use clap::{builder::ValueParser, parser::ValuesRef, Arg, ArgGroup, ArgMatches, Command};
 
-use a2lfile::{A2lError, A2lFile, A2lObject};
+use a2lfile::{A2lError, A2lFile, A2lObject, DataType};
 use debuginfo::DebugData;
 use std::{
+    collections::{HashMap, HashSet},
     ffi::{OsStr, OsString},
     fmt::Display,
-    time::Instant,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
 use update::{UpdateMode, UpdateType};
 
+// the file name "-" stands for stdin when it is used as the input, and for stdout when it is used as the output
+const STDIO_FILENAME: &str = "-";
+
+// When the output is written to stdout, all messages go to stderr, so that the output stream only contains the a2l file.
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+// println! for all messages, which are redirected to stderr when the output is written to stdout.
+// The macro is defined before the modules, so that it can be used in all of them.
+macro_rules! log_println {
+    ($($arg:tt)*) => {
+        if crate::LOG_TO_STDERR.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+mod a2ml;
+mod bitfield_def;
+mod bitmask;
+mod cleanup;
+mod compu_tab;
+mod conversions;
+mod convert;
 mod datatype;
+mod datatype_map;
 mod debuginfo;
+mod embed_values;
+mod format_map;
+mod formats;
+mod groups;
 mod ifdata;
+mod include;
 mod insert;
+mod limits;
+mod merge;
 mod remove;
+mod rename;
+mod seed_addresses;
+mod shared_axes;
+mod signal_list;
+mod sort;
 mod symbol;
+mod typedefs;
 mod update;
 mod version;
+mod virtual_measurement;
+mod watch;
 mod xcp;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -32,13 +77,13 @@ pub enum A2lVersion {
 macro_rules! cond_print {
     ($verbose:ident, $now:ident, $formatexp:expr) => {
         if $verbose == 1 {
-            println!("{}", $formatexp);
+            log_println!("{}", $formatexp);
         } else if $verbose >= 2 {
             for line in $formatexp.split('\n') {
                 if line == "" {
-                    println!("");
+                    log_println!("");
                 } else {
-                    println!("[{:9.4}ms] {}", $now.elapsed().as_secs_f64() * 1000.0, line);
+                    log_println!("[{:9.4}ms] {}", $now.elapsed().as_secs_f64() * 1000.0, line);
                 }
             }
         }
@@ -48,13 +93,13 @@ macro_rules! cond_print {
 macro_rules! ext_println {
     ($verbose:ident, $now:ident, $formatexp:expr) => {
         if $verbose <= 1 {
-            println!("{}", $formatexp);
+            log_println!("{}", $formatexp);
         } else {
             for line in $formatexp.split('\n') {
                 if line == "" {
-                    println!("");
+                    log_println!("");
                 } else {
-                    println!("[{:9.4}ms] {}", $now.elapsed().as_secs_f64() * 1000.0, line);
+                    log_println!("[{:9.4}ms] {}", $now.elapsed().as_secs_f64() * 1000.0, line);
                 }
             }
         }
@@ -66,12 +111,78 @@ fn main() {
     match core(args) {
         Ok(()) => {}
         Err(err) => {
-            println!("{err}");
+            log_println!("{err}");
             std::process::exit(1);
         }
     }
 }
 
+fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
+    core_with_io(args, &mut std::io::stdin(), &mut std::io::stdout())
+}
+
+// The input file name "-" reads the a2l text from stdin, and the output file name "-" writes to stdout.
+// Tests can supply their own reader and writer instead.
+fn core_with_io(
+    args: impl Iterator<Item = OsString>,
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> Result<(), String> {
+    let arg_matches = parse_args(args);
+
+    let input_is_stdin = arg_matches
+        .get_one::<OsString>("INPUT")
+        .is_some_and(|input| input == STDIO_FILENAME);
+    let output_is_stdout = arg_matches
+        .get_one::<OsString>("OUTPUT")
+        .is_some_and(|output| output == STDIO_FILENAME);
+    LOG_TO_STDERR.store(output_is_stdout, Ordering::Relaxed);
+
+    let watch = *arg_matches
+        .get_one::<bool>("WATCH")
+        .expect("option watch must always exist");
+    if watch {
+        // the debug info file and the input a2l file are watched; the input is re-loaded for every run
+        if input_is_stdin || output_is_stdout {
+            return Err("Error: --watch can't be used with stdin or stdout".to_string());
+        }
+        let watched_files: Vec<&OsStr> = ["ELFFILE", "PDBFILE", "INPUT"]
+            .iter()
+            .filter_map(|id| arg_matches.get_one::<OsString>(id))
+            .map(|filename| &**filename)
+            .collect();
+        if watched_files.is_empty() {
+            return Err(
+                "Error: --watch requires an input a2l file or a debug info file that can be watched"
+                    .to_string(),
+            );
+        }
+        let input = arg_matches.get_one::<OsString>("INPUT");
+        let output = arg_matches.get_one::<OsString>("OUTPUT");
+        if let (Some(input), Some(output)) = (input, output) {
+            if watch::is_same_file(input, output) {
+                return Err(
+                    "Error: --watch can't be used if the output file is the same as the input file"
+                        .to_string(),
+                );
+            }
+        }
+        watch::watch_files(&watched_files, || run(&arg_matches, None, &mut *stdout))
+    } else {
+        // stdin can only be read once, so the text is read before the file is loaded
+        let input_text = if input_is_stdin {
+            let mut text = String::new();
+            stdin
+                .read_to_string(&mut text)
+                .map_err(|err| format!("Error: failed to read the input from stdin: {err}"))?;
+            Some(text)
+        } else {
+            None
+        };
+        run(&arg_matches, input_text.as_deref(), stdout)
+    }
+}
+
 // Implement all the operations supported by a2ltool
 // They will always be performed in this order:
 //  1) load input
@@ -84,38 +195,21 @@ fn main() {
 //  8) clean up ifdata
 //  9) sort the file
 // 10) output
-fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
-    let arg_matches = parse_args(args);
-
+fn run(
+    arg_matches: &ArgMatches,
+    input_text: Option<&str>,
+    stdout: &mut dyn Write,
+) -> Result<(), String> {
     let strict = *arg_matches
         .get_one::<bool>("STRICT")
         .expect("option strict must always exist");
-    let check = *arg_matches
-        .get_one::<bool>("CHECK")
-        .expect("option check must always exist");
     let debugprint = *arg_matches
         .get_one::<bool>("DEBUGPRINT")
         .expect("option debugprint must always exist");
-    let show_xcp = *arg_matches
-        .get_one::<bool>("SHOW_XCP")
-        .expect("option show-xcp must always exist");
     let enable_structures = *arg_matches
         .get_one::<bool>("ENABLE_STRUCTURES")
         .expect("option enable-structures must always exist");
-    let cleanup = *arg_matches
-        .get_one::<bool>("CLEANUP")
-        .expect("option cleanup must always exist");
-    let ifdata_cleanup = *arg_matches
-        .get_one::<bool>("IFDATA_CLEANUP")
-        .expect("option ifdata-cleanup must always exist");
-    let sort = *arg_matches
-        .get_one::<bool>("SORT")
-        .expect("option sort must always exist");
-    let merge_includes = *arg_matches
-        .get_one::<bool>("MERGEINCLUDES")
-        .expect("option merge-includes must always exist");
     let verbose = arg_matches.get_count("VERBOSE");
-    let opt_update_type = arg_matches.get_one::<UpdateType>("UPDATE_TYPE");
 
     if let Some(true) = arg_matches.get_one::<bool>("SAFE_UPDATE") {
         return Err("Error: The option --update-preserve is deprecated. Use --update-mode PRESERVE instead.".to_string());
@@ -129,16 +223,183 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
     );
 
     // load input
-    let (input_filename, mut a2l_file) = load_or_create_a2l(&arg_matches, strict, verbose, now)?;
+    let (input_filename, mut a2l_file) =
+        load_or_create_a2l(arg_matches, input_text, strict, verbose, now)?;
     if debugprint {
         // why not cond_print? in that case the output string must always be
         // formatted before cond_print can decide whether to print it. This can take longer than parsing the file.
-        println!("================\n{a2l_file:#?}\n================\n");
+        log_println!("================\n{a2l_file:#?}\n================\n");
+    }
+
+    // set the names of the PROJECT and the first MODULE
+    // There are no references to these names inside the file, so nothing else needs to be updated
+    if let Some(project_name) = arg_matches.get_one::<String>("PROJECT_NAME") {
+        a2l_file.project.name.clone_from(project_name);
+    }
+    if let Some(module_name) = arg_matches.get_one::<String>("MODULE_NAME") {
+        a2l_file.project.module[0].name.clone_from(module_name);
+    }
+
+    check_input(arg_matches, &mut a2l_file, input_filename, verbose, now)?;
+
+    // convert/downgrade the file to some version; this also sets the version of a file created with --create
+    if let Some(new_a2l_version) = arg_matches.get_one::<A2lVersion>("A2LVERSION") {
+        version::convert(&mut a2l_file, *new_a2l_version);
+    }
+
+    let current_version = A2lVersion::from(&a2l_file);
+    if enable_structures && current_version < A2lVersion::V1_7_1 {
+        return Err(format!("Error: The option --enable-structures requires a2l version 1.7.1, but the version of the file is {current_version}"));
+    }
+
+    // load debuginfo from an elf or pdb file
+    let opt_timeout = arg_matches.get_one::<u64>("TIMEOUT").copied();
+    let deadline = opt_timeout.map(|secs| now + Duration::from_secs(secs));
+    let mut debuginfo = load_debug_info(arg_matches, deadline, verbose, now)?;
+    // the data type mapping is applied to the debug info before anything is inserted or updated
+    let datatype_mapping = arg_matches
+        .get_one::<String>("DATATYPE_MAP")
+        .map(|text| datatype_map::parse_datatype_map(text))
+        .transpose()?;
+    let mut mapped_types = HashSet::new();
+    if let Some(debuginfo) = &mut debuginfo {
+        mapped_types = prepare_debug_info(
+            arg_matches,
+            &mut a2l_file,
+            debuginfo,
+            datatype_mapping.as_deref(),
+            verbose,
+            now,
+        )?;
+    }
+
+    merge_files(arg_matches, &mut a2l_file, verbose, now)?;
+    edit_items(arg_matches, &mut a2l_file, verbose, now)?;
+
+    if let Some(debugdata) = &debuginfo {
+        check_with_debug_info(arg_matches, &mut a2l_file, debugdata, verbose, now)?;
+        update_addresses(
+            arg_matches,
+            &mut a2l_file,
+            debugdata,
+            deadline,
+            verbose,
+            now,
+        )?;
+        verify_access_flags(arg_matches, &mut a2l_file, debugdata, verbose, now)?;
+
+        // remember where the newly inserted MEASUREMENTs and CHARACTERISTICs will start
+        let first_new_measurement = a2l_file.project.module[0].measurement.len();
+        let first_new_characteristic = a2l_file.project.module[0].characteristic.len();
+        insert_new_items(arg_matches, &mut a2l_file, debugdata, verbose, now)?;
+        configure_inserted_items(
+            arg_matches,
+            &mut a2l_file,
+            debugdata,
+            first_new_measurement,
+            first_new_characteristic,
+            verbose,
+            now,
+        )?;
+    }
+
+    create_derived_items(arg_matches, &mut a2l_file, debuginfo.as_ref(), verbose, now)?;
+
+    // objects that received a mapped data type need a manually maintained conversion
+    if let (Some(map), Some(debugdata)) = (&datatype_mapping, &debuginfo) {
+        let create_cm = *arg_matches
+            .get_one::<bool>("DATATYPE_MAP_CREATE_CM")
+            .expect("option datatype-map-create-cm must always exist");
+        let mut log_msgs: Vec<String> = Vec::new();
+        let count = datatype_map::check_mapped_objects(
+            &mut a2l_file,
+            debugdata,
+            map,
+            &mapped_types,
+            create_cm,
+            &mut log_msgs,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("{count} objects have a mapped data type")
+        );
+    }
+
+    fix_items(arg_matches, &mut a2l_file, debuginfo.as_ref(), verbose, now)?;
+    cleanup_items(arg_matches, &mut a2l_file, verbose, now)?;
+    write_output(arg_matches, &mut a2l_file, stdout, verbose, now)?;
+
+    cond_print!(verbose, now, "\nRun complete. Have a nice day!\n\n");
+
+    Ok(())
+}
+
+// report the contents of the input file and check its consistency, if requested
+fn check_input(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    input_filename: &OsStr,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let check = *arg_matches
+        .get_one::<bool>("CHECK")
+        .expect("option check must always exist");
+    let check_circular_groups = *arg_matches
+        .get_one::<bool>("CHECK_CIRCULAR_GROUPS")
+        .expect("option check-circular-groups must always exist");
+    let show_xcp = *arg_matches
+        .get_one::<bool>("SHOW_XCP")
+        .expect("option show-xcp must always exist");
+    let list_conversions = *arg_matches
+        .get_one::<bool>("LIST_CONVERSIONS")
+        .expect("option list-conversions must always exist");
+    let list_typedefs = *arg_matches
+        .get_one::<bool>("LIST_TYPEDEFS")
+        .expect("option list-typedefs must always exist");
+    let opt_update_type = arg_matches.get_one::<UpdateType>("UPDATE_TYPE");
+
+    // report the IF_DATA blocks that don't match the user-supplied A2ML specification
+    if let Some(a2ml_filename) = arg_matches.get_one::<OsString>("A2ML") {
+        let mut log_msgs = Vec::<String>::new();
+        let invalid_count = a2ml::check_ifdata(a2l_file, &mut log_msgs);
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {msg}"));
+        }
+        ext_println!(
+            verbose,
+            now,
+            format!(
+                "IF_DATA check against \"{}\" complete. {invalid_count} IF_DATA blocks could not be parsed.",
+                a2ml_filename.to_string_lossy()
+            )
+        );
     }
 
     // show XCP settings
     if show_xcp {
-        xcp::show_settings(&a2l_file, input_filename);
+        let show_xcp_format = arg_matches
+            .get_one::<xcp::ShowXcpFormat>("SHOW_XCP_FORMAT")
+            .copied()
+            .unwrap_or(xcp::ShowXcpFormat::Text);
+        xcp::show_settings(a2l_file, input_filename, show_xcp_format);
+    }
+
+    // show the TYPEDEF_STRUCTUREs and the INSTANCEs that use them
+    if list_typedefs {
+        typedefs::list_typedefs(a2l_file, input_filename);
+    }
+
+    // show the COMPU_METHODs and how often they are used
+    if list_conversions {
+        conversions::list_conversions(a2l_file, input_filename);
     }
 
     // additional consistency checks
@@ -153,6 +414,11 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
         );
         let mut log_msgs = Vec::<String>::new();
         a2l_file.check(&mut log_msgs);
+        formats::check_formats(a2l_file, false, &mut log_msgs);
+        // in a full update the BIT_MASKs are checked against the debug info instead
+        if opt_update_type != Some(&UpdateType::Full) {
+            bitmask::check_bitmasks(a2l_file, &mut log_msgs);
+        }
         if log_msgs.is_empty() {
             ext_println!(
                 verbose,
@@ -179,46 +445,239 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
         }
     }
 
-    // convert/downgrade the file to some version
-    if let Some(new_a2l_version) = arg_matches.get_one::<A2lVersion>("A2LVERSION") {
-        version::convert(&mut a2l_file, *new_a2l_version);
-    }
-
-    let current_version = A2lVersion::from(&a2l_file);
-    if enable_structures && current_version < A2lVersion::V1_7_1 {
-        return Err(format!("Error: The option --enable-structures requires input file version 1.7.1, but the current version is {current_version}"));
+    // find GROUPs that contain themselves through their SUB_GROUPs
+    if check_circular_groups {
+        let mut log_msgs = Vec::<String>::new();
+        let cycle_count = groups::check_circular_groups(a2l_file, &mut log_msgs);
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
+        }
+        ext_println!(
+            verbose,
+            now,
+            format!("Group check complete. {cycle_count} circular group references found.")
+        );
+        // in strict mode, exit with error if there are any problems
+        if strict && cycle_count > 0 {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
     }
+    Ok(())
+}
 
-    // load debuginfo from an elf or pdb file
+// load the debug info from an elf, pdb and/or svd file
+fn load_debug_info(
+    arg_matches: &ArgMatches,
+    deadline: Option<Instant>,
+    verbose: u8,
+    now: Instant,
+) -> Result<Option<DebugData>, String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
     let opt_elffile = arg_matches.get_one::<OsString>("ELFFILE");
     let opt_pdbfile = arg_matches.get_one::<OsString>("PDBFILE");
-    let debuginfo = if let Some(elffile) = opt_elffile {
-        Some(DebugData::load_dwarf(elffile, verbose > 0)?)
+
+    let mut debuginfo = if let Some(elffile) = opt_elffile {
+        // types that are named in --typedef might not be used by any variable, so they must be loaded explicitly
+        let named_types: HashSet<String> = ["TYPEDEF", "TYPEDEF_CALIB"]
+            .iter()
+            .filter_map(|id| arg_matches.get_many::<String>(id))
+            .flatten()
+            .cloned()
+            .collect();
+        let debugdata =
+            DebugData::load_dwarf_with_options(elffile, verbose > 0, deadline, &named_types)?;
+        // compile units with corrupt debug info are skipped, the remaining units can still be used
+        for msg in &debugdata.skipped_units {
+            ext_println!(
+                verbose,
+                now,
+                format!("Warning: {}: {msg}", elffile.to_string_lossy())
+            );
+        }
+        if strict && !debugdata.skipped_units.is_empty() {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
+        Some(debugdata)
     } else if let Some(pdbfile) = opt_pdbfile {
-        Some(DebugData::load_pdb(pdbfile, verbose > 0)?)
+        let debugdata = DebugData::load_pdb(pdbfile, verbose > 0)?;
+        // the pdb reader can't be interrupted, but there is no point in continuing after the deadline
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(format!(
+                "Error: {}: loading the debug info was aborted, because the time limit was reached",
+                pdbfile.to_string_lossy()
+            ));
+        }
+        Some(debugdata)
     } else {
         None
     };
-    // display statistics and debug data if requested
-    if let Some(debuginfo) = &debuginfo {
-        // either opt_elffile or opt_pdbfile must be present if debuginfo was loaded
-        let filename = opt_elffile.or(opt_pdbfile).unwrap();
+    // the registers of an SVD file can be used on their own, or in addition to the debug info
+    if let Some(svdfile) = arg_matches.get_one::<OsString>("SVDFILE") {
+        let debugdata = debuginfo.get_or_insert_with(DebugData::default);
+        let count = debugdata.load_svd(svdfile)?;
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Peripherals loaded from the SVD file \"{}\": {count} peripherals",
+                svdfile.to_string_lossy()
+            )
+        );
+    }
+    Ok(debuginfo)
+}
+
+// display the loaded debug info and apply the settings that modify it
+// The indices of the types that received a mapped data type are returned.
+fn prepare_debug_info(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debuginfo: &mut DebugData,
+    datatype_mapping: Option<&[(DataType, DataType)]>,
+    verbose: u8,
+    now: Instant,
+) -> Result<HashSet<usize>, String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let stamp_binary_id = *arg_matches
+        .get_one::<bool>("STAMP_BINARY_ID")
+        .expect("option stamp-binary-id must always exist");
+    let debugprint = *arg_matches
+        .get_one::<bool>("DEBUGPRINT")
+        .expect("option debugprint must always exist");
+    let opt_elffile = arg_matches.get_one::<OsString>("ELFFILE");
+    let opt_pdbfile = arg_matches.get_one::<OsString>("PDBFILE");
+
+    let mut mapped_types = HashSet::new();
+    if let Some(filename) = opt_elffile.or(opt_pdbfile) {
         cond_print!(
             verbose,
             now,
             format!(
-                "Variables and types loaded from \"{}\": {} variables available",
+                "Variables and types loaded from \"{}\": {} variables available, {} compile units skipped",
                 filename.to_string_lossy(),
-                debuginfo.variables.len()
+                debuginfo.variables.len(),
+                debuginfo.skipped_units.len()
+            )
+        );
+    }
+    if debugprint {
+        log_println!("================\n{debuginfo:#?}\n================\n");
+    }
+
+    // display how the type of a single symbol was resolved
+    if let Some(symbol_name) = arg_matches.get_one::<String>("DUMP_TYPE") {
+        let sym_info = symbol::find_symbol(symbol_name, debuginfo)
+            .map_err(|errmsg| format!("Error: {symbol_name}: {errmsg}"))?;
+        log_println!(
+            "================\n{} @ 0x{:X}:\n{:#?}\n================\n",
+            sym_info.name,
+            sym_info.address,
+            sym_info.typeinfo
+        );
+    }
+
+    // variables that are missing in the debug info can be taken from a linker map file
+    if let Some(mapfile) = arg_matches.get_one::<OsString>("MAPFILE") {
+        let count = debuginfo.load_mapfile(mapfile)?;
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Symbols loaded from the map file \"{}\": {count} data symbols",
+                mapfile.to_string_lossy()
+            )
+        );
+    }
+
+    // replace data types that downstream tools can't handle, e.g. FLOAT64_IEEE by A_UINT64
+    // get_a2l_datatype then returns the mapped data type for the insertion and the update
+    if let Some(map) = datatype_mapping {
+        mapped_types = datatype_map::map_debug_types(debuginfo, map);
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Replaced {} data types in the debug info",
+                mapped_types.len()
             )
         );
-        if debugprint {
-            println!("================\n{debuginfo:#?}\n================\n");
+    }
+
+    // compare the addresses in the debug info with the symbols of the binary file
+    if let Some(binfile) = arg_matches.get_one::<OsString>("VERIFY_ADDRESSES") {
+        let mut log_msgs = Vec::<String>::new();
+        debuginfo.verify_addresses(binfile, &mut log_msgs)?;
+        for msg in log_msgs {
+            ext_println!(verbose, now, msg);
         }
     }
 
+    // an a2l file for a different target or build is almost certainly a mistake, so this is always checked
+    // Only an SVD file has no binary that could be checked.
+    let warnings = if opt_elffile.or(opt_pdbfile).is_some() {
+        update::check_binary_compatibility(a2l_file, debuginfo)
+    } else {
+        Vec::new()
+    };
+    for msg in &warnings {
+        ext_println!(verbose, now, msg);
+    }
+    if strict && !warnings.is_empty() {
+        return Err("Exiting because strict mode is enabled.".to_string());
+    }
+
+    if stamp_binary_id {
+        update::stamp_binary_id(a2l_file, &debuginfo.binary);
+        cond_print!(
+            verbose,
+            now,
+            format!("Set the EPK to \"{}\"", debuginfo.binary)
+        );
+    }
+
+    if let Some(epk_symbol) = arg_matches.get_one::<String>("EPK_FROM_SYMBOL") {
+        let epk = update::set_epk_from_symbol(a2l_file, debuginfo, epk_symbol)?;
+        cond_print!(
+            verbose,
+            now,
+            format!("Set the EPK to \"{epk}\" from the symbol {epk_symbol}")
+        );
+    }
+    Ok(mapped_types)
+}
+
+// merge other a2l files at the module and project level, and merge the include files
+fn merge_files(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let merge_includes = *arg_matches
+        .get_one::<bool>("MERGEINCLUDES")
+        .expect("option merge-includes must always exist");
+
     // merge at the module level
     if let Some(merge_modules) = arg_matches.get_many::<OsString>("MERGEMODULE") {
+        // --merge-prefer resolves conflicts between items with the same name field by field
+        let merge_prefer = arg_matches
+            .get_one::<merge::MergePrefer>("MERGE_PREFER")
+            .copied();
+        // --merge-only and --merge-exclude restrict the merge to some block types
+        let merge_selection = if let Some(types) = arg_matches.get_one::<String>("MERGE_ONLY") {
+            Some(merge::parse_block_types(types)?)
+        } else if let Some(types) = arg_matches.get_one::<String>("MERGE_EXCLUDE") {
+            Some(merge::invert_block_types(&merge::parse_block_types(types)?))
+        } else {
+            None
+        };
         for merge_module_path in merge_modules {
             let mut load_log_msgs = Vec::<A2lError>::new();
             let load_result = a2lfile::load(
@@ -234,7 +693,27 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
                     cond_print!(verbose, now, msg.to_string());
                 }
                 // merge the module
-                a2l_file.merge_modules(&mut merge_a2l);
+                let conflicts = merge_prefer.map(|_| {
+                    merge::take_modules_conflicting_items(
+                        &a2l_file.project.module,
+                        &mut merge_a2l.project.module,
+                        merge_selection.as_ref(),
+                    )
+                });
+                if let Some(selected) = &merge_selection {
+                    merge_selected_modules(
+                        a2l_file,
+                        &mut merge_a2l.project.module,
+                        selected,
+                        verbose,
+                        now,
+                    );
+                } else {
+                    a2l_file.merge_modules(&mut merge_a2l);
+                }
+                if let (Some(prefer), Some(conflicts)) = (merge_prefer, conflicts) {
+                    merge_module_fields(a2l_file, conflicts, prefer, verbose, now);
+                }
                 cond_print!(
                     verbose,
                     now,
@@ -248,7 +727,27 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
                 Some(ifdata::A2MLVECTOR_TEXT.to_string()),
             ) {
                 // failed to load the file as a full A2L file, but loaded it as a module fragment
-                a2l_file.project.module[0].merge(&mut other_module);
+                let conflicts = merge_prefer.map(|_| {
+                    merge::take_modules_conflicting_items(
+                        &a2l_file.project.module,
+                        std::slice::from_mut(&mut other_module),
+                        merge_selection.as_ref(),
+                    )
+                });
+                if let Some(selected) = &merge_selection {
+                    merge_selected_modules(
+                        a2l_file,
+                        std::slice::from_mut(&mut other_module),
+                        selected,
+                        verbose,
+                        now,
+                    );
+                } else {
+                    a2l_file.project.module[0].merge(&mut other_module);
+                }
+                if let (Some(prefer), Some(conflicts)) = (merge_prefer, conflicts) {
+                    merge_module_fields(a2l_file, conflicts, prefer, verbose, now);
+                }
                 cond_print!(
                     verbose,
                     now,
@@ -292,7 +791,16 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
         a2l_file.merge_includes();
         cond_print!(verbose, now, "Include directives have been merged\n");
     }
+    Ok(())
+}
 
+// remove, extract and convert the items of the a2l file
+fn edit_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
     // remove items if --remove was given
     if arg_matches.contains_id("REMOVE_REGEX") {
         let regexes: Vec<&str> = match arg_matches.get_many::<String>("REMOVE_REGEX") {
@@ -301,1333 +809,6012 @@ fn core(args: impl Iterator<Item = OsString>) -> Result<(), String> {
         };
 
         let mut log_msgs: Vec<String> = Vec::new();
-        let removed_count = remove::remove_items(&mut a2l_file, &regexes, &mut log_msgs);
+        let removed_count = remove::remove_items(a2l_file, &regexes, &mut log_msgs);
         for msg in log_msgs {
             cond_print!(verbose, now, msg);
         }
         cond_print!(verbose, now, format!("Removed {} items", removed_count));
     }
 
-    if let Some(debugdata) = &debuginfo {
-        // update addresses
-        if let Some(update_type) = opt_update_type {
-            let update_mode = arg_matches
-                .get_one::<UpdateMode>("UPDATE_MODE")
-                .unwrap_or(&UpdateMode::Default);
-
-            let mut log_msgs = Vec::<String>::new();
-            let (summary, strict_error) = update::update_a2l(
-                &mut a2l_file,
-                debugdata,
-                &mut log_msgs,
-                *update_type,
-                *update_mode,
-                enable_structures,
-            );
-
-            let display_msg = if verbose > 0 || update_mode != &UpdateMode::Strict {
-                verbose
-            } else {
-                1
-            };
-            for msg in &log_msgs {
-                cond_print!(display_msg, now, msg);
-            }
+    // reduce the file to the given groups and their dependencies if --extract-group was given
+    if arg_matches.contains_id("EXTRACT_GROUP") {
+        let extract_groups: Vec<&str> = arg_matches
+            .get_many::<String>("EXTRACT_GROUP")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
 
-            cond_print!(verbose, now, "Address update done\nSummary:");
-            cond_print!(
-                verbose,
-                now,
-                format!(
-                    "   characteristic: {} updated, {} not found",
-                    summary.characteristic_updated, summary.characteristic_not_updated
-                )
-            );
-            cond_print!(
-                verbose,
-                now,
-                format!(
-                    "   measurement: {} updated, {} not found",
-                    summary.measurement_updated, summary.measurement_not_updated
-                )
-            );
-            cond_print!(
-                verbose,
-                now,
-                format!(
-                    "   axis_pts: {} updated, {} not found",
-                    summary.axis_pts_updated, summary.axis_pts_not_updated
-                )
-            );
-            cond_print!(
-                verbose,
-                now,
-                format!(
-                    "   blob: {} updated, {} not found",
-                    summary.blob_updated, summary.blob_not_updated
-                )
-            );
-            cond_print!(
-                verbose,
-                now,
-                format!(
-                    "   instance: {} updated, {} not found",
-                    summary.instance_updated, summary.instance_not_updated
-                )
-            );
+        let mut log_msgs: Vec<String> = Vec::new();
+        let removed_count = groups::extract_groups(a2l_file, &extract_groups, &mut log_msgs)
+            .map_err(|errmsg| format!("Error: {errmsg}"))?;
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Extracted the groups {}; removed {removed_count} objects",
+                extract_groups.join(", ")
+            )
+        );
+    }
 
-            // in strict mode, exit with error if there are any problems
-            if update_mode == &UpdateMode::Strict && strict_error {
-                return Err("Exiting because strict mode is enabled.".to_string());
-            }
+    // convert MEASUREMENTs to CHARACTERISTICs and vice versa
+    if arg_matches.contains_id("CONVERT_TO_CHARACTERISTIC")
+        || arg_matches.contains_id("CONVERT_TO_MEASUREMENT")
+    {
+        let to_characteristic: Vec<&str> =
+            match arg_matches.get_many::<String>("CONVERT_TO_CHARACTERISTIC") {
+                Some(values) => values.map(|x| &**x).collect(),
+                None => Vec::new(),
+            };
+        let to_measurement: Vec<&str> =
+            match arg_matches.get_many::<String>("CONVERT_TO_MEASUREMENT") {
+                Some(values) => values.map(|x| &**x).collect(),
+                None => Vec::new(),
+            };
+        let to_characteristic = convert::compile_regexes(&to_characteristic)?;
+        let to_measurement = convert::compile_regexes(&to_measurement)?;
+
+        let mut log_msgs: Vec<String> = Vec::new();
+        let mut error_msgs: Vec<String> = Vec::new();
+        let converted_count = convert::convert_items(
+            a2l_file,
+            &to_characteristic,
+            &to_measurement,
+            &mut log_msgs,
+            &mut error_msgs,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
         }
+        for msg in &error_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
+        }
+        cond_print!(verbose, now, format!("Converted {converted_count} items"));
+    }
 
-        // create new items
-        if arg_matches.contains_id("INSERT_CHARACTERISTIC")
-            || arg_matches.contains_id("INSERT_MEASUREMENT")
-        {
-            let target_group = arg_matches
-                .get_one::<String>("TARGET_GROUP")
-                .map(|group| &**group);
+    // assign placeholder addresses; an update from the debug info overrides them
+    if let Some(csv_file) = arg_matches.get_one::<OsString>("SEED_ADDRESSES") {
+        let addresses = seed_addresses::load_address_csv(csv_file)?;
+        let mut log_msgs: Vec<String> = Vec::new();
+        let seeded_count = seed_addresses::seed_addresses(a2l_file, &addresses, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Set the seed addresses of {seeded_count} objects")
+        );
+    }
+    Ok(())
+}
 
-            let measurement_symbols: Vec<&str> =
-                if let Some(values) = arg_matches.get_many::<String>("INSERT_MEASUREMENT") {
-                    values.into_iter().map(|x| &**x).collect()
-                } else {
-                    Vec::new()
-                };
-            let characteristic_symbols: Vec<&str> =
-                if let Some(values) = arg_matches.get_many::<String>("INSERT_CHARACTERISTIC") {
-                    values.into_iter().map(|x| &**x).collect()
-                } else {
-                    Vec::new()
-                };
+// checks and updates of the a2l file that need the debug info, but are independent of the address update
+fn check_with_debug_info(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debugdata: &DebugData,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let check_symbol_links = *arg_matches
+        .get_one::<bool>("CHECK_SYMBOL_LINKS")
+        .expect("option check-symbol-links must always exist");
+    let fix_symbol_links = *arg_matches
+        .get_one::<bool>("FIX_SYMBOL_LINKS")
+        .expect("option fix-symbol-links must always exist");
+    let check_axes = *arg_matches
+        .get_one::<bool>("CHECK_AXES")
+        .expect("option check-axes must always exist");
+    let update_system_constants = *arg_matches
+        .get_one::<bool>("UPDATE_SYSTEM_CONSTANTS")
+        .expect("option update-system-constants must always exist");
+    let update_memory_segments = *arg_matches
+        .get_one::<bool>("UPDATE_MEMORY_SEGMENTS")
+        .expect("option update-memory-segments must always exist");
+    let sync_byte_order = *arg_matches
+        .get_one::<bool>("SYNC_BYTE_ORDER")
+        .expect("option sync-byte-order must always exist");
 
-            let mut log_msgs: Vec<String> = Vec::new();
-            insert::insert_items(
-                &mut a2l_file,
-                debugdata,
-                measurement_symbols,
-                characteristic_symbols,
-                target_group,
-                &mut log_msgs,
-                enable_structures,
-            );
-            for msg in log_msgs {
-                cond_print!(verbose, now, msg);
-            }
+    // verify that the addresses of objects match their SYMBOL_LINKs
+    if check_symbol_links || fix_symbol_links {
+        let mut log_msgs = Vec::<String>::new();
+        let problem_count =
+            update::check_symbol_links(a2l_file, debugdata, fix_symbol_links, &mut log_msgs);
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
         }
+        ext_println!(
+            verbose,
+            now,
+            format!("Symbol link check complete. {problem_count} problems reported.")
+        );
 
-        if arg_matches.contains_id("INSERT_CHARACTERISTIC_RANGE")
-            || arg_matches.contains_id("INSERT_MEASUREMENT_RANGE")
-            || arg_matches.contains_id("INSERT_CHARACTERISTIC_REGEX")
-            || arg_matches.contains_id("INSERT_MEASUREMENT_REGEX")
-            || arg_matches.contains_id("INSERT_CHARACTERISTIC_SECTION")
-            || arg_matches.contains_id("INSERT_MEASUREMENT_SECTION")
-        {
-            cond_print!(
-                verbose,
-                now,
-                "Inserting new items from range, regex, or section"
-            );
-            let target_group = arg_matches
-                .get_one::<String>("TARGET_GROUP")
-                .map(|group| &**group);
-
-            let mut meas_ranges =
-                range_args_to_ranges(arg_matches.get_many::<u64>("INSERT_MEASUREMENT_RANGE"));
-            let mut char_ranges =
-                range_args_to_ranges(arg_matches.get_many::<u64>("INSERT_CHARACTERISTIC_RANGE"));
-
-            let mut meas_section_ranges = section_args_to_ranges(
-                arg_matches.get_many::<String>("INSERT_MEASUREMENT_SECTION"),
-                debugdata,
-                verbose,
-            );
-            let mut char_section_ranges = section_args_to_ranges(
-                arg_matches.get_many::<String>("INSERT_CHARACTERISTIC_SECTION"),
-                debugdata,
-                verbose,
-            );
-            meas_ranges.append(&mut meas_section_ranges);
-            char_ranges.append(&mut char_section_ranges);
+        // in strict mode, exit with error if there are any problems that were not fixed
+        if strict && problem_count > 0 && !fix_symbol_links {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
+    }
 
-            let meas_regexes: Vec<&str> =
-                match arg_matches.get_many::<String>("INSERT_MEASUREMENT_REGEX") {
-                    Some(values) => values.map(|x| &**x).collect(),
-                    None => Vec::new(),
-                };
-            let char_regexes: Vec<&str> =
-                match arg_matches.get_many::<String>("INSERT_CHARACTERISTIC_REGEX") {
-                    Some(values) => values.map(|x| &**x).collect(),
-                    None => Vec::new(),
-                };
+    // verify that the AXIS_PTS have as many axis points as the arrays they refer to
+    if check_axes {
+        let mut log_msgs = Vec::<String>::new();
+        let problem_count = update::check_axis_pts_dimensions(a2l_file, debugdata, &mut log_msgs);
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
+        }
+        ext_println!(
+            verbose,
+            now,
+            format!("Axis check complete. {problem_count} problems reported.")
+        );
 
-            let mut log_msgs: Vec<String> = Vec::new();
-            insert::insert_many(
-                &mut a2l_file,
-                debugdata,
-                &meas_ranges,
-                &char_ranges,
-                meas_regexes,
-                char_regexes,
-                target_group,
-                &mut log_msgs,
-                enable_structures,
-            );
-            for msg in log_msgs {
-                cond_print!(verbose, now, msg);
-            }
+        // in strict mode, exit with error if there are any problems
+        if strict && problem_count > 0 {
+            return Err("Exiting because strict mode is enabled.".to_string());
         }
     }
 
-    // clean up unreferenced items
-    if cleanup {
-        a2l_file.cleanup();
+    // set the values of SYSTEM_CONSTANTs from the elf file
+    if update_system_constants {
+        let dim_prefixes: Vec<&str> = arg_matches
+            .get_many::<String>("SYSTEM_CONSTANT_DIM_PREFIX")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let mut log_msgs = Vec::<String>::new();
+        let updated_count =
+            update::update_system_constants(a2l_file, debugdata, &dim_prefixes, &mut log_msgs);
+        for msg in &log_msgs {
+            cond_print!(verbose, now, msg);
+        }
         cond_print!(
             verbose,
             now,
-            "Cleanup of unused items and empty groups is complete"
+            format!("Updated {updated_count} SYSTEM_CONSTANTs")
         );
     }
 
-    // remove unknown IF_DATA
-    if ifdata_cleanup {
-        a2l_file.ifdata_cleanup();
-        cond_print!(verbose, now, "Unknown ifdata removal is done");
+    // set the default byte order of each module from the elf file
+    if sync_byte_order {
+        let mut log_msgs = Vec::<String>::new();
+        update::sync_byte_order(a2l_file, debugdata, &mut log_msgs);
+        for msg in &log_msgs {
+            cond_print!(verbose, now, msg);
+        }
     }
 
-    // sort all elements in the file
-    if sort {
-        a2l_file.sort();
-        cond_print!(verbose, now, "All objects have been sorted");
+    // set the addresses and sizes of the MEMORY_SEGMENTs from the sections of the elf file
+    if update_memory_segments {
+        let mut log_msgs = Vec::<String>::new();
+        let updated_count = update::update_memory_segments(a2l_file, debugdata, &mut log_msgs);
+        for msg in &log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Updated {updated_count} MEMORY_SEGMENTs")
+        );
     }
+    Ok(())
+}
 
-    // output
-    if arg_matches.contains_id("OUTPUT") {
-        a2l_file.sort_new_items();
-        if let Some(out_filename) = arg_matches.get_one::<OsString>("OUTPUT") {
-            let banner = &*format!("a2ltool {}", env!("CARGO_PKG_VERSION"));
-            a2l_file
-                .write(out_filename, Some(banner))
-                .map_err(|err| err.to_string())?;
-            cond_print!(
+// update the addresses and data types of all objects, if --update was given
+fn update_addresses(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debugdata: &DebugData,
+    deadline: Option<Instant>,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let fix_layouts = *arg_matches
+        .get_one::<bool>("FIX_LAYOUTS")
+        .expect("option fix-layouts must always exist");
+    let prefer_ifdata_symbol = *arg_matches
+        .get_one::<bool>("PREFER_IFDATA_SYMBOL")
+        .expect("option prefer-ifdata-symbol must always exist");
+    let enable_structures = *arg_matches
+        .get_one::<bool>("ENABLE_STRUCTURES")
+        .expect("option enable-structures must always exist");
+    let number_to_matrix_dim = *arg_matches
+        .get_one::<bool>("NUMBER_TO_MATRIX_DIM")
+        .expect("option number-to-matrix-dim must always exist");
+    let fix_bitmasks = *arg_matches
+        .get_one::<bool>("FIX_BITMASKS")
+        .expect("option fix-bitmasks must always exist");
+    let fix_integer_formats = *arg_matches
+        .get_one::<bool>("FIX_INTEGER_FORMATS")
+        .expect("option fix-integer-formats must always exist");
+    let opt_update_type = arg_matches.get_one::<UpdateType>("UPDATE_TYPE");
+    let opt_timeout = arg_matches.get_one::<u64>("TIMEOUT").copied();
+    let max_errors = arg_matches.get_one::<u32>("MAX_ERRORS").copied();
+    let max_candidates = arg_matches
+        .get_one::<usize>("MAX_CANDIDATES")
+        .copied()
+        .unwrap_or(symbol::MAX_LISTED_CANDIDATES);
+
+    // update addresses
+    if let Some(update_type) = opt_update_type {
+        let update_mode = arg_matches
+            .get_one::<UpdateMode>("UPDATE_MODE")
+            .unwrap_or(&UpdateMode::Default);
+        let preserved_report = arg_matches.get_one::<OsString>("PRESERVED_REPORT");
+        let report_moved = arg_matches.get_one::<u64>("REPORT_MOVED").copied();
+        let no_local_symbols = *arg_matches
+            .get_one::<bool>("NO_LOCAL_SYMBOLS")
+            .expect("option no-local-symbols must always exist");
+        if preserved_report.is_some() && update_mode != &UpdateMode::Preserve {
+            return Err(
+                "The option --preserved-report requires --update-mode PRESERVE".to_string(),
+            );
+        }
+
+        // the update can be restricted to the objects of some GROUPs and FUNCTIONs
+        let update_groups: Vec<&str> = arg_matches
+            .get_many::<String>("UPDATE_GROUP")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let update_functions: Vec<&str> = arg_matches
+            .get_many::<String>("UPDATE_FUNCTION")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let update_scope = if update_groups.is_empty() && update_functions.is_empty() {
+            None
+        } else {
+            let members =
+                groups::collect_group_members(a2l_file, &update_groups, &update_functions)
+                    .map_err(|errmsg| format!("Error: {errmsg}"))?;
+            Some(members)
+        };
+
+        let mut log_msgs = Vec::<String>::new();
+        let update_options = update::UpdateOptions {
+            limit_precision: arg_matches.get_one::<u8>("LIMIT_PRECISION").copied(),
+            max_errors,
+            fix_layouts,
+            prefer_ifdata_symbol,
+            number_to_matrix_dim,
+            fix_bitmasks,
+            update_scope,
+            deadline,
+            report_moved,
+            no_local_symbols,
+            fix_integer_formats,
+            max_candidates,
+            ..update::UpdateOptions::new(*update_type, *update_mode, enable_structures)
+        };
+        let (summary, strict_error) =
+            update::update_a2l(a2l_file, debugdata, &mut log_msgs, &update_options);
+
+        let display_msg = if verbose > 0 || update_mode != &UpdateMode::Strict {
+            verbose
+        } else {
+            1
+        };
+        for msg in &log_msgs {
+            cond_print!(display_msg, now, msg);
+        }
+        if let (Some(deadline), Some(timeout)) = (deadline, opt_timeout) {
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Error: the update was aborted, because the time limit of {timeout} seconds was reached"
+                ));
+            }
+        }
+
+        cond_print!(verbose, now, "Address update done\nSummary:");
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "   characteristic: {} updated, {} with changed data type, {} not found",
+                summary.characteristic_updated,
+                summary.characteristic_type_changed,
+                summary.characteristic_not_updated
+            )
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "   measurement: {} updated, {} with changed data type, {} not found",
+                summary.measurement_updated,
+                summary.measurement_type_changed,
+                summary.measurement_not_updated
+            )
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "   axis_pts: {} updated, {} with changed data type, {} not found",
+                summary.axis_pts_updated,
+                summary.axis_pts_type_changed,
+                summary.axis_pts_not_updated
+            )
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "   blob: {} updated, {} with changed data type, {} not found",
+                summary.blob_updated, summary.blob_type_changed, summary.blob_not_updated
+            )
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "   instance: {} updated, {} with changed data type, {} not found",
+                summary.instance_updated,
+                summary.instance_type_changed,
+                summary.instance_not_updated
+            )
+        );
+
+        // the moved objects are always listed, since the report was explicitly requested
+        if let Some(threshold) = report_moved {
+            for item in &summary.moved {
+                ext_println!(
+                    verbose,
+                    now,
+                    format!(
+                        "    {} {} on line {} moved from 0x{:X} to 0x{:X}",
+                        item.blocktype, item.name, item.line, item.old_address, item.new_address
+                    )
+                );
+            }
+            ext_println!(
                 verbose,
                 now,
-                format!("Output written to \"{}\"", out_filename.to_string_lossy())
+                format!(
+                    "{} objects moved by more than {threshold} bytes",
+                    summary.moved.len()
+                )
             );
         }
-    }
 
-    cond_print!(verbose, now, "\nRun complete. Have a nice day!\n\n");
+        // list the preserved objects in the report, and mark them with an ANNOTATION
+        if let Some(report_file) = preserved_report {
+            update::write_preserved_report(report_file, &summary.not_found)?;
+            let count = update::annotate_preserved_items(a2l_file, &summary.not_found);
+            cond_print!(
+                verbose,
+                now,
+                format!(
+                    "Wrote the list of {count} preserved objects to {}",
+                    report_file.to_string_lossy()
+                )
+            );
+        }
 
+        // in strict mode, exit with error if there are any problems
+        if update_mode == &UpdateMode::Strict && strict_error {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
+    }
     Ok(())
 }
 
-// load or create an a2l file, depending on the command line
-// return the file name (a dummy value if it is created) as well as the a2l data
-fn load_or_create_a2l(
+// verify that READ_ONLY / READ_WRITE match the sections in which the symbols are located
+fn verify_access_flags(
     arg_matches: &ArgMatches,
-    strict: bool,
+    a2l_file: &mut A2lFile,
+    debugdata: &DebugData,
     verbose: u8,
     now: Instant,
-) -> Result<(&std::ffi::OsStr, a2lfile::A2lFile), String> {
-    if let Some(input_filename) = arg_matches.get_one::<OsString>("INPUT") {
-        let mut log_msgs = Vec::<A2lError>::new();
-        let a2lresult = a2lfile::load(
-            input_filename,
-            Some(ifdata::A2MLVECTOR_TEXT.to_string()),
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let check_access_flags = *arg_matches
+        .get_one::<bool>("CHECK_ACCESS_FLAGS")
+        .expect("option check-access-flags must always exist");
+    let fix_access_flags = *arg_matches
+        .get_one::<bool>("FIX_ACCESS_FLAGS")
+        .expect("option fix-access-flags must always exist");
+
+    if check_access_flags || fix_access_flags {
+        let calibration_sections: Vec<&str> = arg_matches
+            .get_many::<String>("CALIBRATION_SECTION")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let mut log_msgs = Vec::<String>::new();
+        let problem_count = update::check_access_flags(
+            a2l_file,
+            debugdata,
+            &calibration_sections,
+            fix_access_flags,
             &mut log_msgs,
-            strict,
         );
-        let a2l_file = match a2lresult {
-            Ok(a2l_file) => {
-                for msg in log_msgs {
-                    cond_print!(verbose, now, msg.to_string());
-                }
-                a2l_file
-            }
-            Err(
-                ref error @ A2lError::ParserError {
-                    parser_error:
-                        a2lfile::ParserError::InvalidMultiplicityNotPresent { ref block, .. },
-                },
-            ) if block == "A2L_FILE" => {
-                // parse error in the outermost block "A2L_FILE" could indicate that this is an a2l fragment containing only the content of a MODULE
-                if let Ok(module) = a2lfile::load_fragment_file2(
-                    input_filename,
-                    Some(ifdata::A2MLVECTOR_TEXT.to_string()),
-                ) {
-                    // successfully loaded a module, now upgrade it to a full file
-                    let mut a2l_file = a2lfile::new();
-                    a2l_file.project.module[0] = module;
-                    a2l_file.project.module[0].get_layout_mut().start_offset = 1;
-                    a2l_file
-                } else {
-                    return Err(error.to_string());
-                }
-            }
-            Err(error) => {
-                return Err(error.to_string());
-            }
-        };
-
-        cond_print!(
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
+        }
+        ext_println!(
             verbose,
             now,
-            format!("Input \"{}\" loaded", input_filename.to_string_lossy())
+            format!("Access flag check complete. {problem_count} problems reported.")
         );
-        Ok((input_filename, a2l_file))
-    } else if arg_matches.contains_id("CREATE") {
-        // dummy file name
-        let input_filename = OsStr::new("<newly created>");
-        // a minimal a2l file needs only a PROJECT containing a MODULE
-        let mut project = a2lfile::Project::new(
-            "new_project".to_string(),
-            "description of project".to_string(),
+
+        // in strict mode, exit with error if there are any problems that were not fixed
+        if strict && problem_count > 0 && !fix_access_flags {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
+    }
+    Ok(())
+}
+
+// insert new items for the symbols, ranges, regexes, sections and types given on the command line
+fn insert_new_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debugdata: &DebugData,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let union_largest_member = *arg_matches
+        .get_one::<bool>("UNION_LARGEST_MEMBER")
+        .expect("option union-largest-member must always exist");
+    let enable_structures = *arg_matches
+        .get_one::<bool>("ENABLE_STRUCTURES")
+        .expect("option enable-structures must always exist");
+    let include_untyped = *arg_matches
+        .get_one::<bool>("INCLUDE_UNTYPED")
+        .expect("option include-untyped must always exist");
+    let allow_const_values = *arg_matches
+        .get_one::<bool>("ALLOW_CONST_VALUES")
+        .expect("option allow-const-values must always exist");
+    let max_errors = arg_matches.get_one::<u32>("MAX_ERRORS").copied();
+    let max_candidates = arg_matches
+        .get_one::<usize>("MAX_CANDIDATES")
+        .copied()
+        .unwrap_or(symbol::MAX_LISTED_CANDIDATES);
+
+    let name_affix = insert::NameAffix {
+        prefix: arg_matches
+            .get_one::<String>("NAME_PREFIX")
+            .cloned()
+            .unwrap_or_default(),
+        suffix: arg_matches
+            .get_one::<String>("NAME_SUFFIX")
+            .cloned()
+            .unwrap_or_default(),
+    };
+
+    // create new items
+    if arg_matches.contains_id("INSERT_CHARACTERISTIC")
+        || arg_matches.contains_id("INSERT_MEASUREMENT")
+    {
+        let target_group = arg_matches
+            .get_one::<String>("TARGET_GROUP")
+            .map(|group| &**group);
+
+        let measurement_symbols: Vec<&str> =
+            if let Some(values) = arg_matches.get_many::<String>("INSERT_MEASUREMENT") {
+                values.into_iter().map(|x| &**x).collect()
+            } else {
+                Vec::new()
+            };
+        let characteristic_symbols: Vec<&str> =
+            if let Some(values) = arg_matches.get_many::<String>("INSERT_CHARACTERISTIC") {
+                values.into_iter().map(|x| &**x).collect()
+            } else {
+                Vec::new()
+            };
+        // variables that were optimized to a constant value can only be inserted as virtual CHARACTERISTICs
+        let (const_symbols, characteristic_symbols): (Vec<&str>, Vec<&str>) =
+            characteristic_symbols
+                .into_iter()
+                .partition(|sym| allow_const_values && debugdata.constants.contains_key(*sym));
+
+        let union_mode = if union_largest_member {
+            Some(insert::UnionMode::Largest)
+        } else {
+            arg_matches
+                .get_one::<insert::UnionMode>("UNION_MODE")
+                .copied()
+        };
+
+        let mut log_msgs: Vec<String> = Vec::new();
+        insert::insert_items(
+            a2l_file,
+            debugdata,
+            measurement_symbols,
+            characteristic_symbols,
+            target_group,
+            &mut log_msgs,
+            enable_structures,
+            max_errors,
+            union_mode,
+            arg_matches.get_one::<DataType>("DATATYPE").cloned(),
+            arg_matches.get_one::<u64>("DIM").copied(),
+            &name_affix,
+            max_candidates,
+        );
+        insert::insert_const_characteristics(
+            a2l_file,
+            debugdata,
+            &const_symbols,
+            &mut log_msgs,
+            &name_affix,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+    }
+
+    if arg_matches.contains_id("INSERT_CHARACTERISTIC_RANGE")
+        || arg_matches.contains_id("INSERT_MEASUREMENT_RANGE")
+        || arg_matches.contains_id("INSERT_CHARACTERISTIC_REGEX")
+        || arg_matches.contains_id("INSERT_MEASUREMENT_REGEX")
+        || arg_matches.contains_id("INSERT_CHARACTERISTIC_SECTION")
+        || arg_matches.contains_id("INSERT_MEASUREMENT_SECTION")
+    {
+        cond_print!(
+            verbose,
+            now,
+            "Inserting new items from range, regex, or section"
+        );
+        let target_group = arg_matches
+            .get_one::<String>("TARGET_GROUP")
+            .map(|group| &**group);
+
+        let mut meas_ranges =
+            range_args_to_ranges(arg_matches.get_many::<u64>("INSERT_MEASUREMENT_RANGE"));
+        let mut char_ranges =
+            range_args_to_ranges(arg_matches.get_many::<u64>("INSERT_CHARACTERISTIC_RANGE"));
+
+        let mut meas_section_ranges = section_args_to_ranges(
+            arg_matches.get_many::<String>("INSERT_MEASUREMENT_SECTION"),
+            debugdata,
+            verbose,
+        );
+        let mut char_section_ranges = section_args_to_ranges(
+            arg_matches.get_many::<String>("INSERT_CHARACTERISTIC_SECTION"),
+            debugdata,
+            verbose,
+        );
+        meas_ranges.append(&mut meas_section_ranges);
+        char_ranges.append(&mut char_section_ranges);
+
+        let meas_regexes: Vec<&str> =
+            match arg_matches.get_many::<String>("INSERT_MEASUREMENT_REGEX") {
+                Some(values) => values.map(|x| &**x).collect(),
+                None => Vec::new(),
+            };
+        let char_regexes: Vec<&str> =
+            match arg_matches.get_many::<String>("INSERT_CHARACTERISTIC_REGEX") {
+                Some(values) => values.map(|x| &**x).collect(),
+                None => Vec::new(),
+            };
+        let meas_exclude: Vec<&str> = arg_matches
+            .get_many::<String>("MEASUREMENT_EXCLUDE")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let char_exclude: Vec<&str> = arg_matches
+            .get_many::<String>("CHARACTERISTIC_EXCLUDE")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+
+        let mut log_msgs: Vec<String> = Vec::new();
+        insert::insert_many(
+            a2l_file,
+            debugdata,
+            &meas_ranges,
+            &char_ranges,
+            meas_regexes,
+            char_regexes,
+            meas_exclude,
+            char_exclude,
+            target_group,
+            &mut log_msgs,
+            enable_structures,
+            max_errors,
+            &name_affix,
+            include_untyped,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+    }
+
+    // create TYPEDEFs for the named types, so that INSTANCEs of these types can be created later
+    let typedef_list: Vec<(&String, bool)> = arg_matches
+        .get_many::<String>("TYPEDEF")
+        .into_iter()
+        .flatten()
+        .map(|typename| (typename, false))
+        .chain(
+            arg_matches
+                .get_many::<String>("TYPEDEF_CALIB")
+                .into_iter()
+                .flatten()
+                .map(|typename| (typename, true)),
+        )
+        .collect();
+    if !typedef_list.is_empty() {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let mut type_list = Vec::new();
+        for (typename, is_calib) in typedef_list {
+            match update::typedef::find_named_type(debugdata, typename) {
+                Ok(typeinfo) => type_list.push((typeinfo, is_calib)),
+                Err(errmsg) if strict => return Err(errmsg),
+                Err(errmsg) => log_msgs.push(errmsg),
+            }
+        }
+        update::typedef::create_typedefs_for_types(
+            &mut a2l_file.project.module[0],
+            debugdata,
+            &mut log_msgs,
+            &type_list,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+    }
+
+    // create MEASUREMENTs for the named bit fields of integer variables
+    if let Some(def_file) = arg_matches.get_one::<OsString>("BITFIELD_DEF") {
+        let definitions = bitfield_def::load_definitions(def_file)?;
+        let mut log_msgs: Vec<String> = Vec::new();
+        let count = bitfield_def::create_bitfield_measurements(
+            a2l_file,
+            debugdata,
+            &definitions,
+            &mut log_msgs,
+        )?;
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Created or updated {count} bit field MEASUREMENTs")
+        );
+    }
+
+    // create CURVEs and MAPs whose axes are shared AXIS_PTS or TYPEDEF_AXIS components
+    if let Some(def_file) = arg_matches.get_one::<OsString>("SHARED_AXES") {
+        let definitions = shared_axes::load_definitions(def_file)?;
+        let mut log_msgs: Vec<String> = Vec::new();
+        let (map_count, axis_count) = shared_axes::create_shared_axis_maps(
+            a2l_file,
+            debugdata,
+            &definitions,
+            enable_structures,
+            &mut log_msgs,
+        )?;
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Created {map_count} CURVEs and MAPs and {axis_count} shared axes")
         );
-        project.module = vec![a2lfile::Module::new(
-            "new_module".to_string(),
-            String::new(),
-        )];
-        let mut a2l_file = a2lfile::A2lFile::new(project);
-        // only one line break for PROJECT (after ASAP2_VERSION) instead of the default 2
-        a2l_file.project.get_layout_mut().start_offset = 1;
-        // only one line break for MODULE [0] instead of the default 2
-        a2l_file.project.module[0].get_layout_mut().start_offset = 1;
-        // also set ASAP2_VERSION 1.71
-        a2l_file.asap2_version = Some(a2lfile::Asap2Version::new(1, 71));
-        Ok((input_filename, a2l_file))
-    } else {
-        // shouldn't be able to get here, the clap config requires either INPUT or CREATE
-        Err("impossible: no input filename and no --create".to_string())
     }
+    Ok(())
 }
 
-// set up the entire command line handling.
-// fortunately clap makes this painless
-fn parse_args(args: impl Iterator<Item = OsString>) -> ArgMatches {
-    let args = argfile::expand_args_from(args, argfile::parse_response, argfile::PREFIX)
-        .unwrap_or_else(|err| {
-            println!("invalid response file: {err}: {}", err.kind());
-            std::env::args_os().collect()
-        });
-    Command::new("a2ltool")
-    .version(env!("CARGO_PKG_VERSION"))
-    .about("Reads, writes and modifies A2L files")
-    .arg(Arg::new("INPUT")
-        .help("Input A2L file")
-        .index(1)
-        .value_parser(ValueParser::os_string())
-    )
-    .arg(Arg::new("CREATE")
-        .help("Create a new A2L file instead of loading an existing one")
-        .long("create")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("ELFFILE")
-        .help("Elf file containing symbols and address information in DWARF2+ format.\nAn exe file produced by MinGW with DWARF2 debug info can also be used.")
-        .short('e')
-        .long("elffile")
-        .number_of_values(1)
-        .value_name("ELFFILE")
-        .value_parser(ValueParser::os_string())
-        .alias("exefile")
-        .alias("elf")
-    )
-    .arg(Arg::new("PDBFILE")
-        .help("PDB file containig debugging information in Microsoft's Program Database format.")
-        .long("pdbfile")
-        .number_of_values(1)
-        .value_name("PDBFILE")
-        .value_parser(ValueParser::os_string())
-        .alias("pdb")
-    )
-    .arg(Arg::new("CHECK")
-        .help("Perform additional consistency checks")
-        .long("check")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("CLEANUP")
-        .help("Remove empty or unreferenced items")
-        .short('c')
-        .long("cleanup")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("MERGEMODULE")
-        .help("Merge another a2l file on the MODULE level.\nThe input file and the merge file must each contain exactly one MODULE.\nThe contents will be merged so that there is one merged MODULE in the output.")
-        .short('m')
-        .long("merge")
-        .number_of_values(1)
-        .value_name("A2LFILE")
-        .number_of_values(1)
-        .value_parser(ValueParser::os_string())
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("MERGEPROJECT")
-        .help("Merge another a2l file on the PROJECT level.\nIf the input file contains m MODULES and the merge file contains n MODULES, then there will be m + n MODULEs in the output.")
-        .short('p')
-        .long("merge-project")
-        .number_of_values(1)
-        .value_name("A2LFILE")
-        .number_of_values(1)
-        .value_parser(ValueParser::os_string())
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("MERGEINCLUDES")
-        .help("Merge the content of all included files. The output file will contain no /include commands.")
-        .short('i')
-        .long("merge-includes")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("UPDATE_TYPE")
-        .help("Update the A2L file based on the elf file. The update type can be one of:
-  FULL: Update the address and type info of all items. This is the default.
-  ADDRESSES: Update only the addresses.
-The arg --elffile must be present.")
-        .short('u')
-        .long("update")
-        .value_parser(UpdateTypeParser)
-        .num_args(0..=1)
-        .action(clap::ArgAction::Append)
-        .default_missing_value("FULL")
-        .requires("DEBUGINFO_ARGGROUP")
-    )
-    .arg(Arg::new("UPDATE_MODE")
-        .help("Update the A2L file based on the elf file. Action can be one of:
-  DEFAULT: Unknown objects are removed, invalid settings are updated.
-  STRICT: Unknown objects or invalid settings trigger an error.
-  PRESERVE: Unknown objects are preserved, with the address set to zero.
-The arg --update must be present.")
-        .long("update-mode")
-        .value_parser(UpdateModeParser)
-        .num_args(0..=1)
-        .action(clap::ArgAction::Append)
-        .default_missing_value("DEFAULT")
-        .requires("DEBUGINFO_ARGGROUP")
-        .requires("UPDATE_TYPE")
-    )
-    .arg(Arg::new("SAFE_UPDATE")
-        .long("update-preserve")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-        .hide(true)
-    )
-    .arg(Arg::new("ENABLE_STRUCTURES")
-        .help("Enable the the use of INSTANCE, TYPEDEF_STRUCTURE & co. for all operations. Requires a2l version 1.7.1")
-        .short('t')
-        .long("enable-structures")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-        .requires("DEBUGINFO_ARGGROUP")
-    )
-    .arg(Arg::new("A2LVERSION")
-        .help("Convert the input file to the given version (e.g. \"1.5.1\", \"1.6.0\", etc.). This is a lossy operation, which deletes incompatible information.")
-        .short('a')
-        .long("a2lversion")
-        .number_of_values(1)
-        .value_name("A2L_VERSION")
-        .value_parser(A2lVersionParser)
-    )
-    .arg(Arg::new("OUTPUT")
-        .help("Write to the given output file. If this flag is not present, no output will be written.")
-        .short('o')
-        .long("output")
-        .number_of_values(1)
-        .value_name("A2LFILE")
-        .value_parser(ValueParser::os_string())
-    )
-    .arg(Arg::new("STRICT")
-        .help("Parse all input in strict mode. An error wil be reported if the file has any inconsistency.")
-        .short('s')
-        .long("strict")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("VERBOSE")
-        .help("Display additional information")
-        .short('v')
-        .long("verbose")
-        .number_of_values(0)
-        .action(clap::ArgAction::Count)
-    )
-    .arg(Arg::new("DEBUGPRINT")
-        .help("Display internal data for debugging")
-        .long("debug-print")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("SORT")
-        .help("Sort all the elements in the file")
-        .long("sort")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("IFDATA_CLEANUP")
-        .help("Remove all IF_DATA blocks that cannot be parsed according to A2ML")
-        .long("ifdata-cleanup")
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("SHOW_XCP")
-        .help("Display the XCP settings in the a2l file, if they exist")
-        .long("show-xcp")
-        .number_of_values(0)
-        .action(clap::ArgAction::SetTrue)
-    )
-    .arg(Arg::new("INSERT_CHARACTERISTIC")
-        .help("Insert a CHARACTERISTIC based on a variable in the elf file. The variable name can be complex, e.g. var.element[0].subelement")
-        .short('C')
-        .long("characteristic")
-        .aliases(["insert-characteristic"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("VAR")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_CHARACTERISTIC_RANGE")
-        .help("Insert multiple CHARACTERISTICs. All variables whose address is inside the given range will be inserted as CHARACTERISTICs.\nThis is useful in order to add all variables from a tuning data section with fixed addresses.\nExample: --characteristic-range 0x1000 0x2000")
-        .long("characteristic-range")
-        .aliases(["insert-characteristic-range"])
-        .number_of_values(2)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("RANGE")
-        .value_parser(AddressValueParser)
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_CHARACTERISTIC_REGEX")
-        .help("Compare all symbol names in the elf file to the given regex. All matching ones will be inserted as CHARACTERISTICs")
-        .long("characteristic-regex")
-        .aliases(["insert-characteristic-regex"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("REGEX")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_CHARACTERISTIC_SECTION")
-        .help("Insert all variables from the given section as CHARACTERISTICs.")
-        .long("characteristic-section")
-        .aliases(["insert-characteristic-section"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("SECTION")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_MEASUREMENT")
-        .help("Insert a MEASUREMENT based on a variable in the elf file. The variable name can be complex, e.g. var.element[0].subelement")
-        .short('M')
-        .long("measurement")
-        .aliases(["insert-measurement"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("VAR")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_MEASUREMENT_RANGE")
-        .help("Insert multiple MEASUREMENTs. All variables whose address is inside the given range will be inserted as MEASUREMENTs.\nExample: --measurement-range 0x1000 0x2000")
-        .long("measurement-range")
-        .aliases(["insert-measurement-range"])
-        .number_of_values(2)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("RANGE")
-        .value_parser(AddressValueParser)
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_MEASUREMENT_REGEX")
-        .help("Compare all symbol names in the elf file to the given regex. All matching ones will be inserted as MEASUREMENTs")
-        .long("measurement-regex")
-        .aliases(["insert-measurement-regex"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("REGEX")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("INSERT_MEASUREMENT_SECTION")
-        .help("Insert all variables from the given section as MEASUREMENTs.")
-        .long("measurement-section")
-        .aliases(["insert-measurement-section"])
-        .number_of_values(1)
-        .requires("DEBUGINFO_ARGGROUP")
-        .value_name("SECTION")
-        .action(clap::ArgAction::Append)
-    )
-    .arg(Arg::new("TARGET_GROUP")
-        .help("When inserting items, put them into the group named in this option. The group will be created if it doe not exist.")
-        .long("target-group")
-        .number_of_values(1)
-        .requires("INSERT_ARGGROUP")
-        .value_name("GROUP")
-    )
-    .arg(Arg::new("REMOVE_REGEX")
-        .help("Remove any CHARACTERISTICs, MEASUREMENTs and INSTANCEs whose name matches the given regex.")
-        .short('R')
-        .long("remove")
-        .number_of_values(1)
-        .value_name("REGEX")
-        .action(clap::ArgAction::Append)
-    )
-    .group(
-        ArgGroup::new("DEBUGINFO_ARGGROUP")
-            .args(["ELFFILE", "PDBFILE"])
-            .multiple(false)
-    )
-    .group(
-        ArgGroup::new("INPUT_ARGGROUP")
-            .args(["INPUT", "CREATE"])
-            .multiple(false)
-            .required(true)
-    )
-    .group(
-        ArgGroup::new("UPDATE_ARGGROUP")
-            .args(["UPDATE_TYPE", "SAFE_UPDATE"])
-            .multiple(false)
-    )
-    .group(
-        ArgGroup::new("INSERT_ARGGROUP")
-            .args(["INSERT_CHARACTERISTIC", "INSERT_CHARACTERISTIC_RANGE", "INSERT_CHARACTERISTIC_REGEX",
-                "INSERT_MEASUREMENT", "INSERT_MEASUREMENT_RANGE", "INSERT_MEASUREMENT_REGEX",
-                "INSERT_MEASUREMENT_SECTION", "INSERT_MEASUREMENT_SECTION", ])
-            .multiple(true)
-    )
-    .next_line_help(false)
-    .after_help("Use @filename to read a response file, which may contain any of the command line arguments.")
-    .get_matches_from(args)
+// apply the settings for inserted items to the MEASUREMENTs and CHARACTERISTICs that were just inserted
+fn configure_inserted_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debugdata: &DebugData,
+    first_new_measurement: usize,
+    first_new_characteristic: usize,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let group_by_peripheral = *arg_matches
+        .get_one::<bool>("GROUP_BY_PERIPHERAL")
+        .expect("option group-by-peripheral must always exist");
+    let measurement_writeable = *arg_matches
+        .get_one::<bool>("MEASUREMENT_WRITEABLE")
+        .expect("option measurement-writeable must always exist");
+    let characteristic_readonly = *arg_matches
+        .get_one::<bool>("CHARACTERISTIC_READONLY")
+        .expect("option characteristic-readonly must always exist");
+
+    let (measurement_overrides, characteristic_overrides) = address_override_args(arg_matches)?;
+
+    // attach the default XCP event to all MEASUREMENTs that were inserted above
+    if let Some(event) = arg_matches.get_one::<u16>("DEFAULT_EVENT") {
+        let new_measurements = &mut a2l_file.project.module[0].measurement[first_new_measurement..];
+        insert::set_default_daq_event(new_measurements, *event);
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Attached XCP event {event} to {} inserted MEASUREMENTs",
+                new_measurements.len()
+            )
+        );
+    }
+
+    if measurement_writeable {
+        let new_measurements = &mut a2l_file.project.module[0].measurement[first_new_measurement..];
+        insert::set_measurements_writeable(new_measurements);
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Set READ_WRITE on {} inserted MEASUREMENTs",
+                new_measurements.len()
+            )
+        );
+    }
+
+    if characteristic_readonly {
+        let new_characteristics =
+            &mut a2l_file.project.module[0].characteristic[first_new_characteristic..];
+        insert::set_characteristics_readonly(new_characteristics);
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Set READ_ONLY on {} inserted CHARACTERISTICs",
+                new_characteristics.len()
+            )
+        );
+    }
+
+    if let Some(extension) = arg_matches.get_one::<i16>("ADDRESS_EXT") {
+        let module = &mut a2l_file.project.module[0];
+        let new_measurements = &mut module.measurement[first_new_measurement..];
+        let new_characteristics = &mut module.characteristic[first_new_characteristic..];
+        let count = new_measurements.len() + new_characteristics.len();
+        insert::set_address_extension(new_measurements, new_characteristics, *extension);
+        cond_print!(
+            verbose,
+            now,
+            format!("Set ECU_ADDRESS_EXTENSION {extension} on {count} inserted items")
+        );
+    }
+
+    // the limits derived from the data type are often much wider than the range of the actual values
+    if let Some(limits) = arg_matches.get_many::<f64>("DEFAULT_LIMITS") {
+        let limits: Vec<f64> = limits.copied().collect();
+        let (lower_limit, upper_limit) = (limits[0], limits[1]);
+        if lower_limit > upper_limit {
+            return Err(format!(
+                "Error: the lower limit {lower_limit} of --default-limits is greater than the upper limit {upper_limit}"
+            ));
+        }
+        let module = &mut a2l_file.project.module[0];
+        let count = insert::set_default_limits(
+            &mut module.measurement[first_new_measurement..],
+            &mut module.characteristic[first_new_characteristic..],
+            lower_limit,
+            upper_limit,
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!("Set the limits {lower_limit} to {upper_limit} on {count} inserted items")
+        );
+    }
+
+    // give the inserted items the addresses from --at-address, instead of the addresses from the elf file
+    if !measurement_overrides.is_empty() || !characteristic_overrides.is_empty() {
+        let module = &mut a2l_file.project.module[0];
+        let mut log_msgs: Vec<String> = Vec::new();
+        insert::set_address_overrides(
+            &mut module.measurement[first_new_measurement..],
+            &mut module.characteristic[first_new_characteristic..],
+            &measurement_overrides,
+            &characteristic_overrides,
+            &mut log_msgs,
+        );
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+    }
+
+    if group_by_peripheral {
+        let count = insert::create_peripheral_groups(
+            &mut a2l_file.project.module[0],
+            first_new_measurement,
+            first_new_characteristic,
+            debugdata,
+        );
+        cond_print!(
+            verbose,
+            now,
+            format!("Added {count} inserted items to the GROUPs of their peripherals")
+        );
+    }
+    Ok(())
+}
+
+// create items that are derived from other items or from the debug info
+fn create_derived_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debuginfo: Option<&DebugData>,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let embed_values = *arg_matches
+        .get_one::<bool>("EMBED_VALUES")
+        .expect("option embed-values must always exist");
+
+    // create virtual MEASUREMENTs; this happens after the insertion, so that inserted MEASUREMENTs can be used as inputs
+    if let Some(def_file) = arg_matches.get_one::<OsString>("VIRTUAL_MEASUREMENTS") {
+        let definitions = virtual_measurement::load_definitions(def_file)?;
+        let mut log_msgs: Vec<String> = Vec::new();
+        let count = virtual_measurement::create_virtual_measurements(
+            a2l_file,
+            &definitions,
+            &mut log_msgs,
+        )?;
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Created or updated {count} virtual MEASUREMENTs")
+        );
+    }
+
+    // store the current values of the CHARACTERISTICs, so that other tools can show them without access to the elf file
+    if embed_values {
+        if let Some(debugdata) = debuginfo {
+            let mut log_msgs: Vec<String> = Vec::new();
+            let count = embed_values::embed_values(a2l_file, &debugdata.memory, &mut log_msgs);
+            for msg in log_msgs {
+                cond_print!(verbose, now, msg);
+            }
+            cond_print!(
+                verbose,
+                now,
+                format!("Embedded the current values of {count} CHARACTERISTICs")
+            );
+        }
+    }
+    Ok(())
 }
 
-fn range_args_to_ranges(args: Option<ValuesRef<u64>>) -> Vec<(u64, u64)> {
-    if let Some(values) = args {
-        let rangevals: Vec<u64> = values.copied().collect();
-        let mut addr_ranges: Vec<(u64, u64)> = Vec::new();
-        for idx in (1..rangevals.len()).step_by(2) {
-            addr_ranges.push((rangevals[idx - 1], rangevals[idx]));
-        }
-        addr_ranges
-    } else {
-        Vec::new()
+// check and fix the limits, names, formats, groups and conversions of the items
+fn fix_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    debuginfo: Option<&DebugData>,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let strict = *arg_matches
+        .get_one::<bool>("STRICT")
+        .expect("option strict must always exist");
+    let check_limits = *arg_matches
+        .get_one::<bool>("CHECK_LIMITS")
+        .expect("option check-limits must always exist");
+    let fix_limits = *arg_matches
+        .get_one::<bool>("FIX_LIMITS")
+        .expect("option fix-limits must always exist");
+    let group_instances = *arg_matches
+        .get_one::<bool>("GROUP_INSTANCES")
+        .expect("option group-instances must always exist");
+    let normalize_compu_tab = *arg_matches
+        .get_one::<bool>("NORMALIZE_COMPU_TAB")
+        .expect("option normalize-compu-tab must always exist");
+    let enum_as_range = *arg_matches
+        .get_one::<bool>("ENUM_AS_RANGE")
+        .expect("option enum-as-range must always exist");
+    let fix_formats = *arg_matches
+        .get_one::<bool>("FIX_FORMATS")
+        .expect("option fix-formats must always exist");
+
+    // verify that the limits of all objects are inside the value range of their data types
+    if check_limits || fix_limits {
+        let mut log_msgs = Vec::<String>::new();
+        let problem_count = limits::check_limits(a2l_file, fix_limits, &mut log_msgs);
+        for msg in &log_msgs {
+            ext_println!(verbose, now, format!("    {}", msg));
+        }
+        ext_println!(
+            verbose,
+            now,
+            format!("Limit check complete. {problem_count} problems reported.")
+        );
+
+        // in strict mode, exit with error if there are any problems that were not fixed
+        if strict && problem_count > 0 && !fix_limits {
+            return Err("Exiting because strict mode is enabled.".to_string());
+        }
+    }
+
+    // rename items if --rename was given
+    if let Some(rename_exprs) = arg_matches.get_many::<String>("RENAME") {
+        let rules = rename_exprs
+            .map(|expr| rename::RenameRule::parse(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut log_msgs: Vec<String> = Vec::new();
+        let renamed_count = rename::rename_items(a2l_file, &rules, &mut log_msgs)?;
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(verbose, now, format!("Renamed {renamed_count} items"));
+    }
+
+    // rewrite FORMAT strings like "%8.3f" into the form %Length.Layout
+    if fix_formats {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let count = formats::check_formats(a2l_file, true, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Checked the FORMAT strings: {count} were invalid")
+        );
+    }
+
+    // set the FORMAT of objects whose names match the entries of the format map
+    if let Some(format_map_file) = arg_matches.get_one::<OsString>("FORMAT_MAP") {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let entries = format_map::load_format_map(format_map_file, &mut log_msgs)?;
+        let count = format_map::apply_format_map(a2l_file, &entries, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(verbose, now, format!("Set the FORMAT of {count} objects"));
+    }
+
+    // create a GROUP hierarchy for the components of each INSTANCE
+    if group_instances {
+        let target_group = arg_matches
+            .get_one::<String>("TARGET_GROUP")
+            .map(|group| &**group);
+        let mut log_msgs: Vec<String> = Vec::new();
+        let mut created_count = 0;
+        for module in &mut a2l_file.project.module {
+            created_count += insert::create_instance_groups(module, target_group, &mut log_msgs);
+        }
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Created {created_count} groups for the components of INSTANCEs")
+        );
+    }
+
+    // convert the COMPU_VTABs of enums to COMPU_VTAB_RANGEs
+    if enum_as_range {
+        if let Some(debugdata) = debuginfo {
+            let mut log_msgs: Vec<String> = Vec::new();
+            let count = compu_tab::enum_tabs_to_ranges(a2l_file, debugdata, &mut log_msgs);
+            for msg in log_msgs {
+                cond_print!(verbose, now, msg);
+            }
+            cond_print!(
+                verbose,
+                now,
+                format!("Converted {count} enum COMPU_VTABs to COMPU_VTAB_RANGEs")
+            );
+        }
+    }
+
+    // convert COMPU_VTAB_RANGEs that only contain single values to COMPU_VTABs
+    if normalize_compu_tab {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let count = compu_tab::normalize_compu_tabs(a2l_file, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Converted {count} COMPU_VTAB_RANGEs to COMPU_VTABs")
+        );
+    }
+    Ok(())
+}
+
+// remove unused items, empty groups and unknown IF_DATA
+fn cleanup_items(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let cleanup = *arg_matches
+        .get_one::<bool>("CLEANUP")
+        .expect("option cleanup must always exist");
+    let prune_groups = *arg_matches
+        .get_one::<bool>("PRUNE_GROUPS")
+        .expect("option prune-groups must always exist");
+    let cleanup_conversions = *arg_matches
+        .get_one::<bool>("CLEANUP_CONVERSIONS")
+        .expect("option cleanup-conversions must always exist");
+    let ifdata_cleanup = *arg_matches
+        .get_one::<bool>("IFDATA_CLEANUP")
+        .expect("option ifdata-cleanup must always exist");
+    let strip_address_ext = *arg_matches
+        .get_one::<bool>("STRIP_ADDRESS_EXT")
+        .expect("option strip-address-ext must always exist");
+
+    // clean up unreferenced items
+    if cleanup {
+        let cleanup_dry_run = *arg_matches
+            .get_one::<bool>("CLEANUP_DRY_RUN")
+            .expect("option cleanup-dry-run must always exist");
+        let keep_regexes: Vec<&str> = arg_matches
+            .get_many::<String>("CLEANUP_KEEP")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let keep_regexes = convert::compile_regexes(&keep_regexes)?;
+
+        let cleanup_items = cleanup::cleanup_with_report(a2l_file, &keep_regexes, cleanup_dry_run);
+        for item in &cleanup_items {
+            let msg = if item.kept {
+                format!("Kept {} {} (--cleanup-keep)", item.blocktype, item.name)
+            } else if cleanup_dry_run {
+                format!("Would remove {} {}", item.blocktype, item.name)
+            } else {
+                format!("Removed {} {}", item.blocktype, item.name)
+            };
+            // the purpose of a dry run is to show the list, so it is always displayed
+            if cleanup_dry_run {
+                ext_println!(verbose, now, msg);
+            } else {
+                cond_print!(verbose, now, msg);
+            }
+        }
+        let counts = cleanup::count_by_blocktype(&cleanup_items);
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        let count_text = counts
+            .iter()
+            .map(|(blocktype, count)| format!("{count} {blocktype}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if cleanup_dry_run {
+            ext_println!(
+                verbose,
+                now,
+                format!("Cleanup dry run: {total} items would be removed ({count_text})")
+            );
+        } else {
+            cond_print!(
+                verbose,
+                now,
+                format!("Cleanup of unused items and empty groups is complete: {total} items removed ({count_text})")
+            );
+        }
+    }
+
+    // remove unreferenced conversions only; this is redundant if a full cleanup was done
+    if cleanup_conversions && !cleanup {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let removed_count = remove::remove_unused_conversions(a2l_file, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Removed {removed_count} unreferenced conversion items")
+        );
+    }
+
+    // remove empty groups only; this is redundant if a full cleanup was done
+    if prune_groups && !cleanup {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let removed_count = remove::remove_empty_groups(a2l_file, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Removed {removed_count} empty groups")
+        );
+    }
+
+    // remove redundant ECU_ADDRESS_EXTENSION 0 blocks
+    if strip_address_ext {
+        let mut log_msgs: Vec<String> = Vec::new();
+        let removed_count = remove::remove_zero_address_extensions(a2l_file, &mut log_msgs);
+        for msg in log_msgs {
+            cond_print!(verbose, now, msg);
+        }
+        cond_print!(
+            verbose,
+            now,
+            format!("Removed {removed_count} ECU_ADDRESS_EXTENSION blocks with the extension 0")
+        );
+    }
+
+    // remove unknown IF_DATA
+    if ifdata_cleanup {
+        a2l_file.ifdata_cleanup();
+        cond_print!(verbose, now, "Unknown ifdata removal is done");
+    }
+    Ok(())
+}
+
+// write the signal list and the output file, sorting the items first if requested
+fn write_output(
+    arg_matches: &ArgMatches,
+    a2l_file: &mut A2lFile,
+    stdout: &mut dyn Write,
+    verbose: u8,
+    now: Instant,
+) -> Result<(), String> {
+    let sort = *arg_matches
+        .get_one::<bool>("SORT")
+        .expect("option sort must always exist");
+    let sort_stable = *arg_matches
+        .get_one::<bool>("SORT_STABLE")
+        .expect("option sort-stable must always exist");
+    let preserve_source_order = *arg_matches
+        .get_one::<bool>("PRESERVE_SOURCE_ORDER")
+        .expect("option preserve-source-order must always exist");
+    let watch = *arg_matches
+        .get_one::<bool>("WATCH")
+        .expect("option watch must always exist");
+
+    // write a list of the MEASUREMENTs, as a starting point for a measurement configuration
+    if let Some(signal_list_file) = arg_matches.get_one::<OsString>("EXPORT_SIGNAL_LIST") {
+        let format = arg_matches
+            .get_one::<signal_list::SignalListFormat>("SIGNAL_LIST_FORMAT")
+            .copied()
+            .unwrap_or(signal_list::SignalListFormat::Csv);
+        let export_groups: Vec<&str> = arg_matches
+            .get_many::<String>("EXPORT_GROUP")
+            .map(|values| values.map(|x| &**x).collect())
+            .unwrap_or_default();
+        let selection = if export_groups.is_empty() {
+            None
+        } else {
+            let members = groups::collect_group_members(a2l_file, &export_groups, &[])
+                .map_err(|errmsg| format!("Error: {errmsg}"))?;
+            Some(members)
+        };
+        let count = signal_list::export_signal_list(
+            a2l_file,
+            signal_list_file,
+            format,
+            selection.as_ref(),
+        )?;
+        cond_print!(
+            verbose,
+            now,
+            format!(
+                "Wrote {count} MEASUREMENTs to the signal list \"{}\"",
+                signal_list_file.to_string_lossy()
+            )
+        );
+    }
+
+    // sort all elements in the file
+    if sort {
+        a2l_file.sort();
+        cond_print!(verbose, now, "All objects have been sorted");
+    } else if sort_stable {
+        sort::sort_stable(a2l_file);
+        cond_print!(
+            verbose,
+            now,
+            "All objects have been sorted, keeping the order of items with equal names"
+        );
+    }
+
+    // output
+    if arg_matches.contains_id("OUTPUT") {
+        // new items are normally sorted, unless they should remain in the order of the debug info
+        // After --sort-stable all items count as new, and sorting them again could change the order of equal names.
+        if !preserve_source_order && !sort_stable {
+            a2l_file.sort_new_items();
+        }
+        if let Some(out_filename) = arg_matches.get_one::<OsString>("OUTPUT") {
+            let banner = build_banner(arg_matches.get_one::<String>("BANNER"))?;
+            if out_filename == STDIO_FILENAME {
+                write_to_stdout(a2l_file, &banner, stdout)?;
+            } else if watch {
+                // other programs may reload the output file whenever it changes
+                watch::write_atomically(a2l_file, out_filename, &banner)?;
+            } else {
+                a2l_file
+                    .write(out_filename, Some(&banner))
+                    .map_err(|err| err.to_string())?;
+            }
+            cond_print!(
+                verbose,
+                now,
+                format!("Output written to \"{}\"", out_filename.to_string_lossy())
+            );
+        }
+    }
+    Ok(())
+}
+
+// merge the fields of the conflicting items of a merge file after the merge, see --merge-prefer
+fn merge_module_fields(
+    a2l_file: &mut A2lFile,
+    conflicts: Vec<merge::ConflictingItems>,
+    prefer: merge::MergePrefer,
+    verbose: u8,
+    now: Instant,
+) {
+    let mut log_msgs = Vec::new();
+    let count = merge::merge_modules_conflicting_items(
+        &mut a2l_file.project.module,
+        conflicts,
+        prefer,
+        &mut log_msgs,
+    );
+    for msg in log_msgs {
+        cond_print!(verbose, now, msg);
+    }
+    cond_print!(
+        verbose,
+        now,
+        format!("Merged the fields of {count} conflicting items")
+    );
+}
+
+// merge the selected block types of the modules of a merge file, see --merge-only and --merge-exclude
+fn merge_selected_modules(
+    a2l_file: &mut A2lFile,
+    source_modules: &mut [a2lfile::Module],
+    selected: &HashSet<&'static str>,
+    verbose: u8,
+    now: Instant,
+) {
+    let mut log_msgs = Vec::new();
+    let count = merge::merge_modules_selected(
+        &mut a2l_file.project.module,
+        source_modules,
+        selected,
+        &mut log_msgs,
+    );
+    for msg in log_msgs {
+        cond_print!(verbose, now, msg);
+    }
+    cond_print!(verbose, now, format!("Merged {count} selected items"));
+}
+
+// write the a2l file to stdout, with the same banner comment that a2lfile::write puts at the start of a file
+fn write_to_stdout(a2l_file: &A2lFile, banner: &str, stdout: &mut dyn Write) -> Result<(), String> {
+    let file_text = a2l_file.write_to_string();
+    let mut outstr = format!("/* {banner} */");
+    if !file_text.starts_with('\n') {
+        outstr.push('\n');
+    }
+    outstr.push_str(&file_text);
+    stdout
+        .write_all(outstr.as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|err| format!("Error: failed to write the output to stdout: {err}"))
+}
+
+// load or create an a2l file, depending on the command line
+// return the file name (a dummy value if it is created) as well as the a2l data
+// input_text contains the text that was read from stdin, if the input file name is "-"
+fn load_or_create_a2l<'a>(
+    arg_matches: &'a ArgMatches,
+    input_text: Option<&str>,
+    strict: bool,
+    verbose: u8,
+    now: Instant,
+) -> Result<(&'a std::ffi::OsStr, a2lfile::A2lFile), String> {
+    if let Some(input_filename) = arg_matches.get_one::<OsString>("INPUT") {
+        // IF_DATA blocks are parsed using the built-in A2ML specification, unless the user supplied a different one
+        let a2ml_spec = match arg_matches.get_one::<OsString>("A2ML") {
+            Some(a2ml_filename) => a2ml::load_a2ml_file(a2ml_filename)?,
+            None => ifdata::A2MLVECTOR_TEXT.to_string(),
+        };
+        let mut log_msgs = Vec::<A2lError>::new();
+        let include_dirs: Option<Vec<PathBuf>> = arg_matches
+            .get_many::<OsString>("INCLUDE_DIR")
+            .map(|dirs| dirs.map(PathBuf::from).collect());
+        let a2lresult = if let Some(text) = input_text {
+            // includes in the text from stdin are relative to the current working directory
+            let text = match &include_dirs {
+                Some(include_dirs) => include::resolve_includes(text, Path::new(""), include_dirs)?,
+                None => text.to_string(),
+            };
+            a2lfile::load_from_string(&text, Some(a2ml_spec.clone()), &mut log_msgs, strict)
+        } else if let Some(include_dirs) = &include_dirs {
+            // the include paths are resolved before the file is loaded, since the loader only searches relative to the including file
+            let text = include::read_with_include_dirs(input_filename, include_dirs)?;
+            a2lfile::load_from_string(&text, Some(a2ml_spec.clone()), &mut log_msgs, strict)
+        } else {
+            a2lfile::load(
+                input_filename,
+                Some(a2ml_spec.clone()),
+                &mut log_msgs,
+                strict,
+            )
+        };
+        let mut a2l_file = match a2lresult {
+            Ok(a2l_file) => {
+                for msg in log_msgs {
+                    cond_print!(verbose, now, msg.to_string());
+                }
+                a2l_file
+            }
+            Err(
+                ref error @ A2lError::ParserError {
+                    parser_error:
+                        a2lfile::ParserError::InvalidMultiplicityNotPresent { ref block, .. },
+                },
+            ) if block == "A2L_FILE" => {
+                // parse error in the outermost block "A2L_FILE" could indicate that this is an a2l fragment containing only the content of a MODULE
+                let fragment = if let Some(text) = input_text {
+                    a2lfile::load_fragment2(text, Some(a2ml_spec.clone()))
+                } else {
+                    a2lfile::load_fragment_file2(input_filename, Some(a2ml_spec.clone()))
+                };
+                if let Ok(module) = fragment {
+                    // successfully loaded a module, now upgrade it to a full file
+                    let mut a2l_file = a2lfile::new();
+                    a2l_file.project.module[0] = module;
+                    a2l_file.project.module[0].get_layout_mut().start_offset = 1;
+                    a2l_file
+                } else {
+                    return Err(error.to_string());
+                }
+            }
+            Err(error) => {
+                return Err(error.to_string());
+            }
+        };
+
+        let input_filename = if input_text.is_some() {
+            OsStr::new("<stdin>")
+        } else {
+            input_filename.as_os_str()
+        };
+        cond_print!(
+            verbose,
+            now,
+            format!("Input \"{}\" loaded", input_filename.to_string_lossy())
+        );
+
+        // the A2ML block in the file may be older than the IF_DATA blocks, e.g. it might not define XCPplus
+        if !arg_matches.contains_id("A2ML") {
+            let revalidated = a2ml::revalidate_ifdata(&mut a2l_file);
+            if revalidated > 0 {
+                cond_print!(
+                    verbose,
+                    now,
+                    format!("{revalidated} IF_DATA blocks were parsed using the built-in A2ML specification")
+                );
+            }
+        }
+        Ok((input_filename, a2l_file))
+    } else if arg_matches.contains_id("CREATE") {
+        // dummy file name
+        let input_filename = OsStr::new("<newly created>");
+        // a minimal a2l file needs only a PROJECT containing a MODULE
+        let mut project = a2lfile::Project::new(
+            "new_project".to_string(),
+            "description of project".to_string(),
+        );
+        project.module = vec![a2lfile::Module::new(
+            "new_module".to_string(),
+            String::new(),
+        )];
+        let mut a2l_file = a2lfile::A2lFile::new(project);
+        // only one line break for PROJECT (after ASAP2_VERSION) instead of the default 2
+        a2l_file.project.get_layout_mut().start_offset = 1;
+        // only one line break for MODULE [0] instead of the default 2
+        a2l_file.project.module[0].get_layout_mut().start_offset = 1;
+        // also set ASAP2_VERSION 1.71; a version requested with --a2lversion is applied by the conversion in run()
+        a2l_file.asap2_version = Some(a2lfile::Asap2Version::new(1, 71));
+        Ok((input_filename, a2l_file))
+    } else {
+        // shouldn't be able to get here, the clap config requires either INPUT or CREATE
+        Err("impossible: no input filename and no --create".to_string())
+    }
+}
+
+// set up the entire command line handling.
+// fortunately clap makes this painless
+fn parse_args(args: impl Iterator<Item = OsString>) -> ArgMatches {
+    let args = argfile::expand_args_from(args, argfile::parse_response, argfile::PREFIX)
+        .unwrap_or_else(|err| {
+            log_println!("invalid response file: {err}: {}", err.kind());
+            std::env::args_os().collect()
+        });
+    Command::new("a2ltool")
+    .version(env!("CARGO_PKG_VERSION"))
+    .about("Reads, writes and modifies A2L files")
+    .arg(Arg::new("INPUT")
+        .help("Input A2L file. Use \"-\" to read the A2L text from stdin.")
+        .index(1)
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("CREATE")
+        .help("Create a new A2L file instead of loading an existing one")
+        .long("create")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("PROJECT_NAME")
+        .help("Set the name of the PROJECT, either in a newly created file or in the input file")
+        .long("project-name")
+        .alias("set-project-name")
+        .number_of_values(1)
+        .value_name("NAME")
+        .value_parser(parse_a2l_identifier)
+    )
+    .arg(Arg::new("MODULE_NAME")
+        .help("Set the name of the MODULE, either in a newly created file or in the input file.\nIf the file contains several MODULEs, then only the first one is renamed.")
+        .long("module-name")
+        .alias("set-module-name")
+        .number_of_values(1)
+        .value_name("NAME")
+        .value_parser(parse_a2l_identifier)
+    )
+    .arg(Arg::new("ELFFILE")
+        .help("Elf file containing symbols and address information in DWARF2+ format.\nAn exe file produced by MinGW with DWARF2 debug info can also be used.")
+        .short('e')
+        .long("elffile")
+        .number_of_values(1)
+        .value_name("ELFFILE")
+        .value_parser(ValueParser::os_string())
+        .alias("exefile")
+        .alias("elf")
+    )
+    .arg(Arg::new("PDBFILE")
+        .help("PDB file containig debugging information in Microsoft's Program Database format.")
+        .long("pdbfile")
+        .conflicts_with("ELFFILE")
+        .number_of_values(1)
+        .value_name("PDBFILE")
+        .value_parser(ValueParser::os_string())
+        .alias("pdb")
+    )
+    .arg(Arg::new("SVDFILE")
+        .help("CMSIS-SVD file describing the peripheral registers of a microcontroller.\nThe registers can be inserted like variables, e.g. --measurement UART0.STATUS.RXNE. The SVD file can be used on its own, or together with an elf or pdb file.")
+        .long("svdfile")
+        .number_of_values(1)
+        .value_name("SVDFILE")
+        .value_parser(ValueParser::os_string())
+        .alias("svd")
+    )
+    .arg(Arg::new("VERIFY_ADDRESSES")
+        .help("Compare the addresses of the variables in the debug info with the exports and the symbol table of the given executable file.\nMismatches and systematic address offsets are reported. This is mainly useful to check a PDB file against its PE file.")
+        .long("verify-addresses")
+        .number_of_values(1)
+        .value_name("EXEFILE")
+        .value_parser(ValueParser::os_string())
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("MAPFILE")
+        .help("Linker map file created by GNU ld or armlink.\nVariables that are missing in the debug info are taken from the map file. Only their address and size are known, so only the addresses of objects that refer to them can be updated.")
+        .long("mapfile")
+        .number_of_values(1)
+        .value_name("MAPFILE")
+        .value_parser(ValueParser::os_string())
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("TIMEOUT")
+        .help("Abort if loading the debug info and updating the addresses takes longer than the given number of seconds.\nThis guards against pathological debug info that would otherwise take a very long time to process.")
+        .long("timeout")
+        .number_of_values(1)
+        .value_name("SECONDS")
+        .value_parser(clap::value_parser!(u64))
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("CHECK")
+        .help("Perform additional consistency checks")
+        .long("check")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CHECK_CIRCULAR_GROUPS")
+        .help("Check if any GROUP contains itself through its SUB_GROUPs, directly or indirectly")
+        .long("check-circular-groups")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_FORMATS")
+        .help("Rewrite FORMAT strings that deviate from the form %Length.Layout, e.g. \"%8.3f\" or \"8.3\", into the correct form.\nFORMAT strings that can not be corrected are reported. --check also reports invalid FORMAT strings.")
+        .long("fix-formats")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CLEANUP")
+        .help("Remove empty or unreferenced items")
+        .short('c')
+        .long("cleanup")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CLEANUP_DRY_RUN")
+        .help("Display the items that would be removed by --cleanup, without removing them")
+        .long("cleanup-dry-run")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("CLEANUP")
+    )
+    .arg(Arg::new("CLEANUP_KEEP")
+        .help("Items whose name matches the regex are never removed by --cleanup, even if they are not referenced.\nThis protects e.g. conversions that are only used inside of IF_DATA blocks.")
+        .long("cleanup-keep")
+        .number_of_values(1)
+        .requires("CLEANUP")
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("CLEANUP_CONVERSIONS")
+        .help("Remove only unreferenced COMPU_METHODs, COMPU_TABs, COMPU_VTABs and COMPU_VTAB_RANGEs.\nAll other items are left unchanged.")
+        .long("cleanup-conversions")
+        .alias("strip-unused-conversions")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("NORMALIZE_COMPU_TAB")
+        .help("Convert each COMPU_VTAB_RANGE in which every range consists of a single value to an equivalent COMPU_VTAB.")
+        .long("normalize-compu-tab")
+        .alias("normalize-conversions")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("ENUM_AS_RANGE")
+        .help("Convert the COMPU_VTABs of enums to COMPU_VTAB_RANGEs, in which each enum value is a single-point range.\nThis applies to new and existing conversion tables that are named after an enum in the debug info.")
+        .long("enum-as-range")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("DEBUGINFO_ARGGROUP")
+        .conflicts_with("NORMALIZE_COMPU_TAB")
+    )
+    .arg(Arg::new("PRUNE_GROUPS")
+        .help("Remove only GROUPs that contain no objects and no sub-groups. Groups that become empty as a result are also removed.\nAll other items are left unchanged.")
+        .long("prune-groups")
+        .alias("remove-empty-groups")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("STRIP_ADDRESS_EXT")
+        .help("Remove ECU_ADDRESS_EXTENSION 0 from all objects. An extension of 0 is the default, so these blocks are redundant.\nNon-zero address extensions are not changed.")
+        .long("strip-address-ext")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("MERGEMODULE")
+        .help("Merge another a2l file on the MODULE level.\nThe input file and the merge file must each contain exactly one MODULE.\nThe contents will be merged so that there is one merged MODULE in the output.")
+        .short('m')
+        .long("merge")
+        .number_of_values(1)
+        .value_name("A2LFILE")
+        .number_of_values(1)
+        .value_parser(ValueParser::os_string())
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("MERGE_ONLY")
+        .help("Only merge the given block types from the files given with --merge, e.g. \"COMPU_METHOD,RECORD_LAYOUT\".\nExisting items are never replaced. References from the merged items to items that do not exist are reported.")
+        .long("merge-only")
+        .number_of_values(1)
+        .value_name("TYPES")
+        .requires("MERGEMODULE")
+        .conflicts_with("MERGE_EXCLUDE")
+    )
+    .arg(Arg::new("MERGE_EXCLUDE")
+        .help("Merge all block types except the given ones from the files given with --merge, e.g. \"MEASUREMENT,CHARACTERISTIC\".\nExisting items are never replaced. References from the merged items to items that do not exist are reported.")
+        .long("merge-exclude")
+        .number_of_values(1)
+        .value_name("TYPES")
+        .requires("MERGEMODULE")
+    )
+    .arg(Arg::new("MERGE_PREFER")
+        .help("Merge the fields of items that exist with the same name in the input file and in the files given with --merge.
+If a field has different values, SOURCE can be one of:
+  INPUT: the value of the input file is kept.
+  MERGE: the value of the merged file is used.
+Fields that are only present in one of the items are always used.
+Only MEASUREMENT, CHARACTERISTIC and AXIS_PTS are merged field by field; all other items are merged as usual.")
+        .long("merge-prefer")
+        .number_of_values(1)
+        .value_name("SOURCE")
+        .value_parser(MergePreferParser)
+        .requires("MERGEMODULE")
+    )
+    .arg(Arg::new("MERGEPROJECT")
+        .help("Merge another a2l file on the PROJECT level.\nIf the input file contains m MODULES and the merge file contains n MODULES, then there will be m + n MODULEs in the output.")
+        .short('p')
+        .long("merge-project")
+        .number_of_values(1)
+        .value_name("A2LFILE")
+        .number_of_values(1)
+        .value_parser(ValueParser::os_string())
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INCLUDE_DIR")
+        .help("Search for the files included by the input file in this directory, if they are not found relative to the input file.\nThis option can be given multiple times; the directories are searched in the given order, followed by the current directory.")
+        .long("include-dir")
+        .number_of_values(1)
+        .value_name("PATH")
+        .value_parser(ValueParser::os_string())
+        .requires("INPUT")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("MERGEINCLUDES")
+        .help("Merge the content of all included files. The output file will contain no /include commands.")
+        .short('i')
+        .long("merge-includes")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("UPDATE_TYPE")
+        .help("Update the A2L file based on the elf file. The update type can be one of:
+  FULL: Update the address and type info of all items. This is the default.
+  ADDRESSES: Update only the addresses.
+  TYPEDEFS: Update only the INSTANCEs and their TYPEDEF_STRUCTUREs. All other objects are left unchanged.
+The arg --elffile must be present.")
+        .short('u')
+        .long("update")
+        .value_parser(UpdateTypeParser)
+        .num_args(0..=1)
+        .action(clap::ArgAction::Append)
+        .default_missing_value("FULL")
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("UPDATE_MODE")
+        .help("Update the A2L file based on the elf file. Action can be one of:
+  DEFAULT: Unknown objects are removed, invalid settings are updated.
+  STRICT: Unknown objects or invalid settings trigger an error.
+  PRESERVE: Unknown objects are preserved, with the address set to zero.
+The arg --update must be present.")
+        .long("update-mode")
+        .value_parser(UpdateModeParser)
+        .num_args(0..=1)
+        .action(clap::ArgAction::Append)
+        .default_missing_value("DEFAULT")
+        .requires("DEBUGINFO_ARGGROUP")
+        .requires("UPDATE_TYPE")
+    )
+    .arg(Arg::new("LIMIT_PRECISION")
+        .help("Round the lower and upper limits calculated during the update to the given number of decimal places.\nLower limits are rounded down and upper limits are rounded up. Limits that are not changed by the update are not modified.")
+        .long("limit-precision")
+        .number_of_values(1)
+        .value_name("DECIMALS")
+        .value_parser(clap::value_parser!(u8).range(0..=15))
+        .requires("UPDATE_TYPE")
+    )
+    .arg(Arg::new("FIX_LAYOUTS")
+        .help("Replace RECORD_LAYOUTs that are referenced but do not exist with default RECORD_LAYOUTs during the update.\nThe default RECORD_LAYOUT is based on the data type of the symbol. This is only possible for CHARACTERISTICs without axes and for AXIS_PTS.")
+        .long("fix-layouts")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("UPDATE_GROUP")
+        .help("Only update the objects that are referenced by the given GROUP, including its sub-groups and the FUNCTIONs in its FUNCTION_LIST.\nThis option can be given multiple times, and it can be combined with --update-function. All other objects are left unchanged.")
+        .long("update-group")
+        .number_of_values(1)
+        .value_name("GROUP")
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("UPDATE_FUNCTION")
+        .help("Only update the objects that are referenced by the given FUNCTION, including its sub-functions.\nThis option can be given multiple times, and it can be combined with --update-group. All other objects are left unchanged.")
+        .long("update-function")
+        .number_of_values(1)
+        .value_name("FUNCTION")
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("NUMBER_TO_MATRIX_DIM")
+        .help("Replace the deprecated NUMBER of VAL_BLK CHARACTERISTICs by MATRIX_DIM during the update.\nThis only applies to files with version 1.6.1 or newer. Without this option, NUMBER is kept and updated to the new array length.")
+        .long("number-to-matrix-dim")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_BITMASKS")
+        .help("Remove the BIT_MASK of CHARACTERISTICs if it does not fit into the data type of the variable during a full update.\nWithout this option, such BIT_MASKs are only reported. The BIT_MASK of a bitfield is always updated from the debug info.")
+        .long("fix-bitmasks")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_INTEGER_FORMATS")
+        .help("During a full update, set the fractional digits of the FORMAT to 0 if the variable has an integer type and its value is not converted.\nA warning is shown for each changed FORMAT.")
+        .long("fix-integer-formats")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("PREFER_IFDATA_SYMBOL")
+        .help("Use the symbol from the CANAPE_EXT LINK_MAP in IF_DATA instead of the SYMBOL_LINK if both are present.\nA warning is shown if they refer to different symbols; the SYMBOL_LINK is then updated to match the LINK_MAP.")
+        .long("prefer-ifdata-symbol")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("PRESERVED_REPORT")
+        .help("Write a CSV report of all objects that were preserved by --update-mode PRESERVE because their symbols were not found.\nEach preserved object is also marked with an ANNOTATION labeled \"a2ltool-preserved\", which is removed once a later update finds the symbol again.")
+        .long("preserved-report")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+        .requires("UPDATE_MODE")
+    )
+    .arg(Arg::new("REPORT_MOVED")
+        .help("List all objects whose address was changed by the update by more than the given number of bytes.\nThis helps to catch accidental relocations, e.g. if a variable was placed in the wrong section.")
+        .long("report-moved")
+        .number_of_values(1)
+        .value_name("BYTES")
+        .value_parser(clap::value_parser!(u64))
+        .requires("UPDATE_TYPE")
+    )
+    .arg(Arg::new("NO_LOCAL_SYMBOLS")
+        .help("During the update, objects that were linked to a global variable are not matched to a static variable inside of a function.\nWithout this option such objects are updated with a warning, and their SYMBOL_LINK gets a {Function:...} discriminator.")
+        .long("no-local-symbols")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("UPDATE_TYPE")
+    )
+    .arg(Arg::new("MAX_ERRORS")
+        .help("Stop the update and the insertion of new items once the given number of errors has occurred.\nAll objects that have not been processed at that point are left unchanged.")
+        .long("max-errors")
+        .number_of_values(1)
+        .value_name("N")
+        .value_parser(clap::value_parser!(u32).range(1..))
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("MAX_CANDIDATES")
+        .help("The maximum number of candidates that are listed when a symbol name matches several variables. The default is 5.")
+        .long("max-candidates")
+        .number_of_values(1)
+        .value_name("N")
+        .value_parser(clap::value_parser!(usize))
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("SAFE_UPDATE")
+        .long("update-preserve")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .hide(true)
+    )
+    .arg(Arg::new("ENABLE_STRUCTURES")
+        .help("Enable the the use of INSTANCE, TYPEDEF_STRUCTURE & co. for all operations. Requires a2l version 1.7.1")
+        .short('t')
+        .long("enable-structures")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("TYPEDEF")
+        .help("Create a TYPEDEF_STRUCTURE with TYPEDEF_MEASUREMENTs for the named struct, union or typedef, even if no variable of this type exists.\nNo INSTANCE is created, so that this can be done later in a different a2l file. Requires --enable-structures")
+        .long("typedef")
+        .number_of_values(1)
+        .requires("ENABLE_STRUCTURES")
+        .value_name("TYPENAME")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("TYPEDEF_CALIB")
+        .help("Like --typedef, but the TYPEDEF_STRUCTURE contains TYPEDEF_CHARACTERISTICs, TYPEDEF_AXIS, etc. for calibration.")
+        .long("typedef-calib")
+        .number_of_values(1)
+        .requires("ENABLE_STRUCTURES")
+        .value_name("TYPENAME")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("A2LVERSION")
+        .help("Convert the input file to the given version (e.g. \"1.5.1\", \"1.6.0\", etc.). This is a lossy operation, which deletes incompatible information.\nWith --create, the new file is created with the given version.")
+        .short('a')
+        .long("a2lversion")
+        .number_of_values(1)
+        .value_name("A2L_VERSION")
+        .value_parser(A2lVersionParser)
+    )
+    .arg(Arg::new("OUTPUT")
+        .help("Write to the given output file. If this flag is not present, no output will be written.\nUse \"-\" to write to stdout; all messages are written to stderr instead.")
+        .short('o')
+        .long("output")
+        .number_of_values(1)
+        .value_name("A2LFILE")
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("BANNER")
+        .help("Append a custom text to the banner comment at the start of the output file, e.g. a build id or a date.\nA text containing line breaks is written as multiple comment lines.")
+        .long("banner")
+        .alias("append-banner-comment")
+        .number_of_values(1)
+        .value_name("TEXT")
+        .requires("OUTPUT")
+    )
+    .arg(Arg::new("WATCH")
+        .help("Keep running and repeat all operations whenever the elf/pdb file or the input a2l file is modified, until Ctrl-C is pressed.\nThe input file is re-loaded for every run, and the output file is replaced atomically.")
+        .long("watch")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("STRICT")
+        .help("Parse all input in strict mode. An error wil be reported if the file has any inconsistency.")
+        .short('s')
+        .long("strict")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("VERBOSE")
+        .help("Display additional information")
+        .short('v')
+        .long("verbose")
+        .number_of_values(0)
+        .action(clap::ArgAction::Count)
+    )
+    .arg(Arg::new("DEBUGPRINT")
+        .help("Display internal data for debugging")
+        .long("debug-print")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("DUMP_TYPE")
+        .help("Display the data type of the given symbol, as it was read from the debug info.\nThis shows the same internal data as --debug-print, but only for one symbol.")
+        .long("dump-type")
+        .number_of_values(1)
+        .value_name("SYMBOL")
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("SORT")
+        .help("Sort all the elements in the file")
+        .long("sort")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("SORT_STABLE")
+        .help("Sort all the elements in the file by name. Elements with equal names keep their original order,\nso sorting a file that is already sorted does not change it.")
+        .long("sort-stable")
+        .number_of_values(0)
+        .conflicts_with("SORT")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("PRESERVE_SOURCE_ORDER")
+        .help("Keep newly inserted items in the order in which the variables appear in the debug info,\ninstead of sorting them. This makes it easier to review the output against the source code.")
+        .long("preserve-source-order")
+        .number_of_values(0)
+        .conflicts_with_all(["SORT", "SORT_STABLE"])
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("A2ML")
+        .help("Parse the IF_DATA blocks of the input file using the A2ML specification in FILE instead of the built-in specification.\nAll IF_DATA blocks that cannot be parsed are reported; --ifdata-cleanup would remove them.")
+        .long("a2ml")
+        .alias("validate-a2ml")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+        .requires("INPUT")
+    )
+    .arg(Arg::new("IFDATA_CLEANUP")
+        .help("Remove all IF_DATA blocks that cannot be parsed according to A2ML")
+        .long("ifdata-cleanup")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CHECK_SYMBOL_LINKS")
+        .help("Check that the address of each object matches the address of the symbol in its SYMBOL_LINK.\nThe arg --elffile or --pdbfile must be present.")
+        .long("check-symbol-links")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_SYMBOL_LINKS")
+        .help("Check the SYMBOL_LINK of each object like --check-symbol-links, and change mismatched SYMBOL_LINKs to\nrefer to the symbol at the address of the object.\nThe arg --elffile or --pdbfile must be present.")
+        .long("fix-symbol-links")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("STAMP_BINARY_ID")
+        .help("Write the path, build-id and timestamp of the elf or pdb file into the EPK in MOD_PAR.\nLater runs warn if the a2l file is updated from a file with a different build-id.")
+        .long("stamp-binary-id")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("EPK_FROM_SYMBOL")
+        .help("Write the version string that is stored in the char array SYMBOL in the elf file into the EPK in MOD_PAR.\nADDR_EPK is set to the address of the symbol. The array must be initialized with printable text.")
+        .long("epk-from-symbol")
+        .number_of_values(1)
+        .value_name("SYMBOL")
+        .requires("DEBUGINFO_ARGGROUP")
+        .conflicts_with("STAMP_BINARY_ID")
+    )
+    .arg(Arg::new("CHECK_AXES")
+        .help("Check that the MAX_AXIS_POINTS of each AXIS_PTS matches the length of the array in the elf file.\nA full update corrects any mismatches.\nThe arg --elffile or --pdbfile must be present.")
+        .long("check-axes")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CHECK_ACCESS_FLAGS")
+        .help("Check that CHARACTERISTICs and AXIS_PTS whose symbols are located in read-only sections are READ_ONLY, and\nthat MEASUREMENTs in read-only sections are not READ_WRITE.\nThe arg --elffile or --pdbfile must be present.")
+        .long("check-access-flags")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_ACCESS_FLAGS")
+        .help("Check the access flags like --check-access-flags during the update, and correct the READ_ONLY flags of\nCHARACTERISTICs and AXIS_PTS. MEASUREMENTs are not changed.")
+        .long("fix-access-flags")
+        .number_of_values(0)
+        .requires("UPDATE_TYPE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CHECK_LIMITS")
+        .help("Check that the limits of all MEASUREMENTs, CHARACTERISTICs and AXIS_PTS are inside the value range of their data types.\nThe data types of CHARACTERISTICs and AXIS_PTS are taken from their RECORD_LAYOUTs, so no elf file is needed.")
+        .long("check-limits")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("FIX_LIMITS")
+        .help("Check the limits like --check-limits, and clamp limits that exceed the value range of the data type.")
+        .long("fix-limits")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CALIBRATION_SECTION")
+        .help("Name of a section which contains RAM-backed calibration memory. The wildcards '*' and '?' may be used.\nCHARACTERISTICs and AXIS_PTS in these sections must not be READ_ONLY. This option can be given multiple times.")
+        .long("calibration-section")
+        .number_of_values(1)
+        .value_name("SECTION")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("UPDATE_SYSTEM_CONSTANTS")
+        .help("Set the values of the SYSTEM_CONSTANTs in MOD_PAR from the elf file.\nA SYSTEM_CONSTANT with the same name as an integer variable receives the initial value of the variable.\nThe arg --elffile must be present.")
+        .long("update-system-constants")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("SYSTEM_CONSTANT_DIM_PREFIX")
+        .help("A SYSTEM_CONSTANT whose name starts with PREFIX receives the dimension of the array named by the rest of its name.\nExample: --system-constant-dim-prefix sizeof_ sets SYSTEM_CONSTANT \"sizeof_MapX\" to the dimension of the array MapX.")
+        .long("system-constant-dim-prefix")
+        .number_of_values(1)
+        .value_name("PREFIX")
+        .requires("UPDATE_SYSTEM_CONSTANTS")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("SYNC_BYTE_ORDER")
+        .help("Set the default BYTE_ORDER in MOD_COMMON to match the endianness of the elf file.\nMOD_COMMON is created if it does not exist. The BYTE_ORDER of individual objects is not changed.")
+        .long("sync-byte-order")
+        .number_of_values(0)
+        .requires("DEBUGINFO_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("UPDATE_MEMORY_SEGMENTS")
+        .help("Set the address and size of each MEMORY_SEGMENT in MOD_PAR from the section of the elf file with the same name.\nThe segment \"data\" also matches the section \".data\". Segments without a matching section are not changed.")
+        .long("update-memory-segments")
+        .number_of_values(0)
+        .requires("ELFFILE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("SHOW_XCP")
+        .help("Display the XCP settings in the a2l file, if they exist")
+        .long("show-xcp")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("SHOW_XCP_FORMAT")
+        .help("Select the output format of --show-xcp. The format can be one of:
+  text: Human readable output. This is the default.
+  json: Machine readable output in json format.")
+        .long("show-xcp-format")
+        .number_of_values(1)
+        .value_name("FORMAT")
+        .value_parser(ShowXcpFormatParser)
+        .requires("SHOW_XCP")
+    )
+    .arg(Arg::new("EXPORT_SIGNAL_LIST")
+        .help("Write a list of all MEASUREMENTs with their unit, data type, address, bit mask and XCP DAQ event to a file.\nThe list can be used as a starting point for a measurement configuration.")
+        .long("export-signal-list")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("SIGNAL_LIST_FORMAT")
+        .help("Select the format of the file written by --export-signal-list. The format can be one of:
+  csv: Comma separated values with a header line. This is the default.
+  json: A json object containing an array of signals.")
+        .long("signal-list-format")
+        .number_of_values(1)
+        .value_name("FORMAT")
+        .value_parser(SignalListFormatParser)
+        .requires("EXPORT_SIGNAL_LIST")
+    )
+    .arg(Arg::new("EXPORT_GROUP")
+        .help("Only export the MEASUREMENTs that are referenced by the given GROUP, including its sub-groups and the FUNCTIONs in its FUNCTION_LIST.\nThis option can be given multiple times.")
+        .long("export-group")
+        .number_of_values(1)
+        .value_name("GROUP")
+        .requires("EXPORT_SIGNAL_LIST")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("LIST_CONVERSIONS")
+        .help("Display each COMPU_METHOD with its type, its unit and the number of objects that use it, sorted by usage.\nThis helps to decide which conversions can be removed with --cleanup-conversions.")
+        .long("list-conversions")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("LIST_TYPEDEFS")
+        .help("Display each TYPEDEF_STRUCTURE with its components, and the INSTANCEs that use it")
+        .long("list-typedefs")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("INSERT_CHARACTERISTIC")
+        .help("Insert a CHARACTERISTIC based on a variable in the elf file. The variable name can be complex, e.g. var.element[0].subelement\nAn array range selects several elements, e.g. var.element[0:3].subelement inserts the elements 0, 1 and 2")
+        .short('C')
+        .long("characteristic")
+        .aliases(["insert-characteristic"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("VAR")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_CHARACTERISTIC_RANGE")
+        .help("Insert multiple CHARACTERISTICs. All variables whose address is inside the given range will be inserted as CHARACTERISTICs.\nThis is useful in order to add all variables from a tuning data section with fixed addresses.\nExample: --characteristic-range 0x1000 0x2000")
+        .long("characteristic-range")
+        .aliases(["insert-characteristic-range"])
+        .number_of_values(2)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("RANGE")
+        .value_parser(AddressValueParser)
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_CHARACTERISTIC_REGEX")
+        .help("Compare all symbol names in the elf file to the given regex. All matching ones will be inserted as CHARACTERISTICs")
+        .long("characteristic-regex")
+        .aliases(["insert-characteristic-regex"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_CHARACTERISTIC_SECTION")
+        .help("Insert all variables from the given section as CHARACTERISTICs.\nIf several sections have the same name, then variables from all of them are inserted.\nThe section name may contain the wildcards '*' and '?', e.g. \".data*\".")
+        .long("characteristic-section")
+        .aliases(["insert-characteristic-section"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("SECTION")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_MEASUREMENT")
+        .help("Insert a MEASUREMENT based on a variable in the elf file. The variable name can be complex, e.g. var.element[0].subelement\nAn array range selects several elements, e.g. var.element[0:3].subelement inserts the elements 0, 1 and 2")
+        .short('M')
+        .long("measurement")
+        .aliases(["insert-measurement"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("VAR")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_MEASUREMENT_RANGE")
+        .help("Insert multiple MEASUREMENTs. All variables whose address is inside the given range will be inserted as MEASUREMENTs.\nExample: --measurement-range 0x1000 0x2000")
+        .long("measurement-range")
+        .aliases(["insert-measurement-range"])
+        .number_of_values(2)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("RANGE")
+        .value_parser(AddressValueParser)
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_MEASUREMENT_REGEX")
+        .help("Compare all symbol names in the elf file to the given regex. All matching ones will be inserted as MEASUREMENTs")
+        .long("measurement-regex")
+        .aliases(["insert-measurement-regex"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("MEASUREMENT_EXCLUDE")
+        .help("Do not insert MEASUREMENTs for symbols whose name matches the given regex, even if they are selected by\n--measurement-range, --measurement-regex or --measurement-section.\nThe names of struct members are checked too, e.g. \".*\\.padding.*\"")
+        .long("measurement-exclude")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("CHARACTERISTIC_EXCLUDE")
+        .help("Do not insert CHARACTERISTICs for symbols whose name matches the given regex, even if they are selected by\n--characteristic-range, --characteristic-regex or --characteristic-section.\nThe names of struct members are checked too, e.g. \".*\\.padding.*\"")
+        .long("characteristic-exclude")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("INSERT_MEASUREMENT_SECTION")
+        .help("Insert all variables from the given section as MEASUREMENTs.\nIf several sections have the same name, then variables from all of them are inserted.\nThe section name may contain the wildcards '*' and '?', e.g. \".bss*\".")
+        .long("measurement-section")
+        .aliases(["insert-measurement-section"])
+        .number_of_values(1)
+        .requires("DEBUGINFO_ARGGROUP")
+        .value_name("SECTION")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("DATATYPE")
+        .help("The data type of MEASUREMENTs that are inserted with --measurement for variables that are only found in the map file.\nValid values: UBYTE, SBYTE, UWORD, SWORD, ULONG, SLONG, A_UINT64, A_INT64, FLOAT16_IEEE, FLOAT32_IEEE, FLOAT64_IEEE")
+        .long("datatype")
+        .number_of_values(1)
+        .requires("MAPFILE")
+        .value_name("TYPE")
+        .value_parser(DataTypeParser)
+    )
+    .arg(Arg::new("DIM")
+        .help("The number of elements of a flexible array member (e.g. \"int data[];\" at the end of a struct) in variables that are inserted with --measurement or --characteristic.\nWithout this option, flexible array members have an unknown size and are skipped.")
+        .long("dim")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("N")
+        .value_parser(clap::value_parser!(u64).range(1..))
+    )
+    .arg(Arg::new("DATATYPE_MAP")
+        .help("Replace data types of the debug info during the insertion and the update, e.g. \"FLOAT64_IEEE=A_UINT64,FLOAT32_IEEE=ULONG\".\nThe replacement must have the same size. Inserted and updated objects get the new data type, limits in its value range and RECORD_LAYOUTs that are named after it.\nThe conversion of the affected objects must be maintained manually.")
+        .long("datatype-map")
+        .requires("DEBUGINFO_ARGGROUP")
+        .alias("limit-to-datatype")
+        .number_of_values(1)
+        .value_name("MAPPING")
+    )
+    .arg(Arg::new("DATATYPE_MAP_CREATE_CM")
+        .help("Objects without a conversion whose data type is replaced by --datatype-map get a passthrough COMPU_METHOD of type IDENTICAL.")
+        .long("datatype-map-create-cm")
+        .number_of_values(0)
+        .requires("DATATYPE_MAP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("ALLOW_CONST_VALUES")
+        .help("Insert variables that were optimized to a constant value as virtual CHARACTERISTICs.\nThese variables have no storage, so by default they cannot be inserted.")
+        .long("allow-const-values")
+        .number_of_values(0)
+        .requires("INSERT_CHARACTERISTIC")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("UNION_MODE")
+        .help("Select how unions are inserted as MEASUREMENTs:\n  FIRST: insert only the first member of the union\n  LARGEST: insert only the largest member of the union\n  ALL: insert each member of the union as var.member; all of them share the same address\nIf no union mode is given, a union is inserted as an INSTANCE if --enable-structures is present, and as its first member otherwise.")
+        .long("union-mode")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("MODE")
+        .value_parser(UnionModeParser)
+    )
+    .arg(Arg::new("UNION_LARGEST_MEMBER")
+        .help("Insert the largest member of a union instead of the first one. This is the same as --union-mode LARGEST.")
+        .long("union-largest-member")
+        .number_of_values(0)
+        .requires("INSERT_ARGGROUP")
+        .conflicts_with("UNION_MODE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("NAME_PREFIX")
+        .help("Add a prefix to the names of all inserted MEASUREMENTs, CHARACTERISTICs and INSTANCEs.\nThe SYMBOL_LINK keeps the unmodified symbol name, and the names of new TYPEDEFs are not changed.")
+        .long("name-prefix")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("PREFIX")
+        .value_parser(parse_a2l_identifier)
+    )
+    .arg(Arg::new("NAME_SUFFIX")
+        .help("Add a suffix to the names of all inserted MEASUREMENTs, CHARACTERISTICs and INSTANCEs.\nThe SYMBOL_LINK keeps the unmodified symbol name, and the names of new TYPEDEFs are not changed.")
+        .long("name-suffix")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("SUFFIX")
+        .value_parser(parse_name_suffix)
+    )
+    .arg(Arg::new("TARGET_GROUP")
+        .help("When inserting items or creating groups for INSTANCEs, put them into the group named in this option. The group will be created if it doe not exist.")
+        .long("target-group")
+        .number_of_values(1)
+        .requires("TARGET_GROUP_ARGGROUP")
+        .value_name("GROUP")
+    )
+    .arg(Arg::new("GROUP_BY_PERIPHERAL")
+        .help("Put the items that are inserted for the registers of the SVD file into one GROUP per peripheral.")
+        .long("group-by-peripheral")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("SVDFILE")
+    )
+    .arg(Arg::new("GROUP_INSTANCES")
+        .help("Create a GROUP for each INSTANCE, which references all components of the instance.\nNested structures get their own sub-groups. If --target-group is given, the groups are attached to the target group.")
+        .long("group-instances")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("DEFAULT_EVENT")
+        .help("Attach the given XCP event to all MEASUREMENTs inserted in this run.\nThe event is stored in an IF_DATA XCP block as a FIXED_EVENT_LIST.")
+        .long("default-event")
+        .alias("measurement-default-event")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("EVENT")
+        .value_parser(clap::value_parser!(u16))
+    )
+    .arg(Arg::new("ADDRESS_EXT")
+        .help("Add an ECU_ADDRESS_EXTENSION with the given value to all MEASUREMENTs and CHARACTERISTICs inserted in this run.\nThis is needed on targets with banked memory or several address spaces.")
+        .long("address-ext")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("EXTENSION")
+        .value_parser(clap::value_parser!(i16))
+        .allow_negative_numbers(true)
+    )
+    .arg(Arg::new("DEFAULT_LIMITS")
+        .help("Use the given lower and upper limit for all MEASUREMENTs and CHARACTERISTICs without a conversion that are inserted in this run, instead of the limits of the data type.\nExample: --default-limits -100 100")
+        .long("default-limits")
+        .number_of_values(2)
+        .requires("INSERT_ARGGROUP")
+        .value_names(["LOWER", "UPPER"])
+        .value_parser(clap::value_parser!(f64))
+        .allow_negative_numbers(true)
+    )
+    .arg(Arg::new("EMBED_VALUES")
+        .help("Read the current values of all VALUE and VAL_BLK CHARACTERISTICs from the initialized data of the elf file,\nand store them in an ANNOTATION with the label \"a2ltool-value\". The values are raw values before conversion.")
+        .long("embed-values")
+        .number_of_values(0)
+        .requires("ELFFILE")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("INCLUDE_UNTYPED")
+        .help("Also insert variables that are not described by the debug info when inserting by regex or by address range.\nThe data type of these variables is inferred from the size of their ELF symbol.")
+        .long("include-untyped")
+        .number_of_values(0)
+        .action(clap::ArgAction::SetTrue)
+        .requires("INSERT_ARGGROUP")
+    )
+    .arg(Arg::new("MEASUREMENT_WRITEABLE")
+        .help("Add READ_WRITE to all MEASUREMENTs inserted in this run.\nBy default inserted MEASUREMENTs are read-only.")
+        .long("measurement-writeable")
+        .number_of_values(0)
+        .requires("INSERT_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("CHARACTERISTIC_READONLY")
+        .help("Add READ_ONLY to all CHARACTERISTICs inserted in this run.\nBy default inserted CHARACTERISTICs are writeable.")
+        .long("characteristic-readonly")
+        .number_of_values(0)
+        .requires("INSERT_ARGGROUP")
+        .action(clap::ArgAction::SetTrue)
+    )
+    .arg(Arg::new("AT_ADDRESS")
+        .help("Override the address of the item inserted by the immediately preceding --measurement or --characteristic.\nThe SYMBOL_LINK of the item still refers to the variable in the elf file.\nExample: --measurement var --at-address 0x1000")
+        .long("at-address")
+        .number_of_values(1)
+        .requires("INSERT_ARGGROUP")
+        .value_name("ADDR")
+        .value_parser(AddressValueParser)
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("REMOVE_REGEX")
+        .help("Remove any CHARACTERISTICs, MEASUREMENTs and INSTANCEs whose name matches the given regex.")
+        .short('R')
+        .long("remove")
+        .number_of_values(1)
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("EXTRACT_GROUP")
+        .help("Reduce the file to the given GROUP and everything it depends on: its sub-groups and FUNCTIONs, the referenced objects and their axes, input quantities, TYPEDEFs, RECORD_LAYOUTs and conversions.\nAll other objects are removed. This option can be given multiple times to extract several groups.")
+        .long("extract-group")
+        .number_of_values(1)
+        .value_name("GROUP")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("CONVERT_TO_CHARACTERISTIC")
+        .help("Convert any MEASUREMENTs whose name matches the given regex to CHARACTERISTICs of type VALUE or VAL_BLK.\nReferences in GROUPs and FUNCTIONs are moved to the corresponding CHARACTERISTIC lists.")
+        .long("convert-to-characteristic")
+        .number_of_values(1)
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("CONVERT_TO_MEASUREMENT")
+        .help("Convert any CHARACTERISTICs of type VALUE or VAL_BLK whose name matches the given regex to MEASUREMENTs.\nReferences in GROUPs and FUNCTIONs are moved to the corresponding MEASUREMENT lists.")
+        .long("convert-to-measurement")
+        .number_of_values(1)
+        .value_name("REGEX")
+        .action(clap::ArgAction::Append)
+    )
+    .arg(Arg::new("SEED_ADDRESSES")
+        .help("Set the addresses of objects from a CSV file before any update. Each line contains the name of an object\nand its address in hex, e.g. \"Characteristic_Value, 0x8001000\". An update from --elffile or --pdbfile overrides these addresses.")
+        .long("seed-addresses")
+        .number_of_values(1)
+        .value_name("CSV")
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("BITFIELD_DEF")
+        .help("Create a MEASUREMENT with a BIT_MASK for each named field of an integer variable, based on a definition file.\nEach line of the file contains: symbol; field name; bit or bit range (e.g. 0 or 4-7) [; value table (e.g. 0=Off, 1=On)]\nThe MEASUREMENTs are named <symbol>.<field name>. Existing MEASUREMENTs with these names are updated.")
+        .long("bitfield-def")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("SHARED_AXES")
+        .help("Create CURVEs and MAPs whose axes are shared AXIS_PTS, based on a definition file.\nEach line of the file contains: curve or map symbol; x axis symbol [; y axis symbol]\nOne AXIS_PTS is created for each axis symbol, and all CURVEs and MAPs using it reference it as a COM_AXIS.\nThe values of a MAP must be declared as value[y][x].\nWith --enable-structures, a CURVE or MAP whose axes are members of the same struct is created in a TYPEDEF_STRUCTURE, which contains each axis only once.")
+        .long("shared-axes")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+        .requires("DEBUGINFO_ARGGROUP")
+    )
+    .arg(Arg::new("VIRTUAL_MEASUREMENTS")
+        .help("Create virtual MEASUREMENTs with a COMPU_METHOD of type FORM from a definition file.\nEach line of the file contains: name; formula; input measurements (comma separated); unit; lower limit; upper limit [; inverse formula]\nThe inputs are referenced as X1, X2, ... in the formula. Existing virtual MEASUREMENTs are updated.")
+        .long("virtual-measurements")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("FORMAT_MAP")
+        .help("Set the FORMAT of MEASUREMENTs, CHARACTERISTICs and AXIS_PTS based on a map file.\nEach line of the file contains: name regex; format string (e.g. \"Speed_.*; %8.3\")\nThe format map is applied after any update or insertion. If several regexes match an object, the last one is used.")
+        .long("format-map")
+        .alias("format-string-override")
+        .number_of_values(1)
+        .value_name("FILE")
+        .value_parser(ValueParser::os_string())
+    )
+    .arg(Arg::new("RENAME")
+        .help("Rename CHARACTERISTICs, MEASUREMENTs, AXIS_PTS, BLOBs and INSTANCEs using a sed-like expression, e.g. \"s/^OLD_/NEW_/\".\nAll references to the renamed items are updated. SYMBOL_LINKs are not changed.")
+        .long("rename")
+        .number_of_values(1)
+        .value_name("EXPR")
+        .action(clap::ArgAction::Append)
+    )
+    .group(
+        ArgGroup::new("DEBUGINFO_ARGGROUP")
+            .args(["ELFFILE", "PDBFILE", "SVDFILE"])
+            .multiple(true)
+    )
+    .group(
+        ArgGroup::new("INPUT_ARGGROUP")
+            .args(["INPUT", "CREATE"])
+            .multiple(false)
+            .required(true)
+    )
+    .group(
+        ArgGroup::new("UPDATE_ARGGROUP")
+            .args(["UPDATE_TYPE", "SAFE_UPDATE"])
+            .multiple(false)
+    )
+    .group(
+        ArgGroup::new("INSERT_ARGGROUP")
+            .args(["INSERT_CHARACTERISTIC", "INSERT_CHARACTERISTIC_RANGE", "INSERT_CHARACTERISTIC_REGEX",
+                "INSERT_MEASUREMENT", "INSERT_MEASUREMENT_RANGE", "INSERT_MEASUREMENT_REGEX",
+                "INSERT_MEASUREMENT_SECTION", "INSERT_CHARACTERISTIC_SECTION", ])
+            .multiple(true)
+    )
+    .group(
+        ArgGroup::new("TARGET_GROUP_ARGGROUP")
+            .args(["INSERT_CHARACTERISTIC", "INSERT_CHARACTERISTIC_RANGE", "INSERT_CHARACTERISTIC_REGEX",
+                "INSERT_MEASUREMENT", "INSERT_MEASUREMENT_RANGE", "INSERT_MEASUREMENT_REGEX",
+                "INSERT_MEASUREMENT_SECTION", "INSERT_CHARACTERISTIC_SECTION", "GROUP_INSTANCES"])
+            .multiple(true)
+    )
+    .next_line_help(false)
+    .after_help("Use @filename to read a response file, which may contain any of the command line arguments.")
+    .get_matches_from(args)
+}
+
+// The banner is written as a comment at the start of the output file. A custom banner text is appended
+// after the version line; each line of a multiline text becomes a separate line of the comment.
+fn build_banner(custom_text: Option<&String>) -> Result<String, String> {
+    let mut banner = format!("a2ltool {}", env!("CARGO_PKG_VERSION"));
+    if let Some(text) = custom_text {
+        // "*/" would end the comment early, and the rest of the text would be parsed as a2l
+        if text.contains("*/") {
+            return Err(format!(
+                "Error: the banner text \"{text}\" may not contain \"*/\""
+            ));
+        }
+        for line in text.lines() {
+            banner.push_str("\n   ");
+            banner.push_str(line.trim_end());
+        }
+    }
+    Ok(banner)
+}
+
+// a suffix does not need to be a valid identifier by itself, since it is appended to one
+fn parse_name_suffix(value: &str) -> Result<String, String> {
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "\"{value}\" is not a valid suffix for an a2l identifier"
+        ))
+    }
+}
+
+// a2l identifiers must start with a letter or '_', and may only contain letters, digits and the characters "_.[]"
+fn parse_a2l_identifier(value: &str) -> Result<String, String> {
+    let mut chars = value.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let valid_rest = chars.all(|c| c.is_ascii_alphanumeric() || "_.[]".contains(c));
+    if valid_start && valid_rest {
+        Ok(value.to_string())
+    } else {
+        Err(format!("\"{value}\" is not a valid a2l identifier"))
+    }
+}
+
+fn range_args_to_ranges(args: Option<ValuesRef<u64>>) -> Vec<(u64, u64)> {
+    if let Some(values) = args {
+        let rangevals: Vec<u64> = values.copied().collect();
+        let mut addr_ranges: Vec<(u64, u64)> = Vec::new();
+        for idx in (1..rangevals.len()).step_by(2) {
+            addr_ranges.push((rangevals[idx - 1], rangevals[idx]));
+        }
+        addr_ranges
+    } else {
+        Vec::new()
+    }
+}
+
+// pair each --at-address with the --measurement or --characteristic that immediately precedes it
+// The result contains the address overrides for the MEASUREMENTs and for the CHARACTERISTICs, keyed by the symbol name
+#[allow(clippy::type_complexity)]
+fn address_override_args(
+    arg_matches: &ArgMatches,
+) -> Result<(HashMap<String, u64>, HashMap<String, u64>), String> {
+    let mut measurement_overrides = HashMap::new();
+    let mut characteristic_overrides = HashMap::new();
+    let (Some(addresses), Some(address_indices)) = (
+        arg_matches.get_many::<u64>("AT_ADDRESS"),
+        arg_matches.indices_of("AT_ADDRESS"),
+    ) else {
+        return Ok((measurement_overrides, characteristic_overrides));
+    };
+
+    // all inserts of single items, in the order of their position on the command line
+    let mut inserts: Vec<(usize, bool, &str)> = Vec::new();
+    for (arg_id, is_measurement) in [
+        ("INSERT_MEASUREMENT", true),
+        ("INSERT_CHARACTERISTIC", false),
+    ] {
+        if let (Some(values), Some(indices)) = (
+            arg_matches.get_many::<String>(arg_id),
+            arg_matches.indices_of(arg_id),
+        ) {
+            inserts.extend(
+                indices
+                    .zip(values)
+                    .map(|(index, value)| (index, is_measurement, &**value)),
+            );
+        }
+    }
+    inserts.sort_by_key(|(index, _, _)| *index);
+
+    let mut prev_address_index = 0;
+    for (address_index, address) in address_indices.zip(addresses) {
+        let preceding_insert = inserts
+            .iter()
+            .rev()
+            .find(|(index, _, _)| *index < address_index)
+            .filter(|(index, _, _)| *index > prev_address_index);
+        let Some((_, is_measurement, symbol)) = preceding_insert else {
+            return Err(format!(
+                "--at-address 0x{address:X} must immediately follow a --measurement or --characteristic"
+            ));
+        };
+        if *is_measurement {
+            measurement_overrides.insert(symbol.to_string(), *address);
+        } else {
+            characteristic_overrides.insert(symbol.to_string(), *address);
+        }
+        prev_address_index = address_index;
+    }
+
+    Ok((measurement_overrides, characteristic_overrides))
+}
+
+fn section_args_to_ranges(
+    args: Option<ValuesRef<String>>,
+    debug_data: &DebugData,
+    verbose: u8,
+) -> Vec<(u64, u64)> {
+    if let Some(values) = args {
+        let mut addr_ranges: Vec<(u64, u64)> = Vec::new();
+        for section in values {
+            // several sections can have the same name, and the name can contain wildcards
+            let section_ranges = debuginfo::find_section_ranges(&debug_data.sections, section);
+            if section_ranges.is_empty() && verbose > 0 {
+                log_println!("Cannot insert items from non-existent section {section}!");
+            }
+            for (name, start, end) in section_ranges {
+                if start == end {
+                    if verbose > 0 {
+                        log_println!("Section {name} at 0x{start:x} is empty and will be skipped");
+                    }
+                } else {
+                    if verbose > 0 {
+                        log_println!(
+                            "Inserting items from section {name}: 0x{start:x} - 0x{end:x}"
+                        );
+                    }
+                    addr_ranges.push((start, end));
+                }
+            }
+        }
+        addr_ranges
+    } else {
+        Vec::new()
+    }
+}
+
+#[derive(Clone)]
+struct AddressValueParser;
+
+impl clap::builder::TypedValueParser for AddressValueParser {
+    type Value = u64;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        if let Some(txt) = value.to_str() {
+            if let Some(hexval) = txt.strip_prefix("0x") {
+                if let Ok(value) = u64::from_str_radix(hexval, 16) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        let mut err = clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+        if let Some(arg) = arg {
+            err.insert(
+                clap::error::ContextKind::InvalidArg,
+                clap::error::ContextValue::String(arg.to_string()),
+            );
+        }
+        let strval = value.to_string_lossy();
+        err.insert(
+            clap::error::ContextKind::InvalidValue,
+            clap::error::ContextValue::String(String::from(strval)),
+        );
+        Err(err)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct A2lVersionParser;
+
+impl clap::builder::TypedValueParser for A2lVersionParser {
+    type Value = A2lVersion;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value_str = value.to_string_lossy();
+        match &*value_str {
+            "1.50" | "1.5.0" => Ok(A2lVersion::V1_5_0),
+            "1.51" | "1.5.1" => Ok(A2lVersion::V1_5_1),
+            "1.60" | "1.6.0" => Ok(A2lVersion::V1_6_0),
+            "1.61" | "1.6.1" => Ok(A2lVersion::V1_6_1),
+            "1.70" | "1.7.0" => Ok(A2lVersion::V1_7_0),
+            "1.71" | "1.7.1" => Ok(A2lVersion::V1_7_1),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+impl From<&A2lFile> for A2lVersion {
+    fn from(a2l_file: &A2lFile) -> Self {
+        if let Some(asap2_version) = &a2l_file.asap2_version {
+            match (asap2_version.version_no, asap2_version.upgrade_no) {
+                (1, 51) => A2lVersion::V1_5_1,
+                (1, 60) => A2lVersion::V1_6_0,
+                (1, 61) => A2lVersion::V1_6_1,
+                (1, 70) => A2lVersion::V1_7_0,
+                (1, 71) => A2lVersion::V1_7_1,
+                _ => A2lVersion::V1_5_0,
+            }
+        } else {
+            A2lVersion::V1_5_0
+        }
+    }
+}
+
+impl Display for A2lVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            A2lVersion::V1_5_0 => f.write_str("1.5.0"),
+            A2lVersion::V1_5_1 => f.write_str("1.5.1"),
+            A2lVersion::V1_6_0 => f.write_str("1.6.0"),
+            A2lVersion::V1_6_1 => f.write_str("1.6.1"),
+            A2lVersion::V1_7_0 => f.write_str("1.7.0"),
+            A2lVersion::V1_7_1 => f.write_str("1.7.1"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct UpdateModeParser;
+
+impl clap::builder::TypedValueParser for UpdateModeParser {
+    type Value = UpdateMode;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().as_ref() {
+            "DEFAULT" => Ok(UpdateMode::Default),
+            "STRICT" => Ok(UpdateMode::Strict),
+            "PRESERVE" => Ok(UpdateMode::Preserve),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct MergePreferParser;
+
+impl clap::builder::TypedValueParser for MergePreferParser {
+    type Value = merge::MergePrefer;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().as_ref() {
+            "INPUT" => Ok(merge::MergePrefer::Input),
+            "MERGE" => Ok(merge::MergePrefer::Merge),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct UpdateTypeParser;
+
+impl clap::builder::TypedValueParser for UpdateTypeParser {
+    type Value = UpdateType;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().as_ref() {
+            "FULL" => Ok(UpdateType::Full),
+            "ADDRESSES" => Ok(UpdateType::Addresses),
+            "TYPEDEFS" => Ok(UpdateType::Typedefs),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct UnionModeParser;
+
+impl clap::builder::TypedValueParser for UnionModeParser {
+    type Value = insert::UnionMode;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().as_ref() {
+            "FIRST" => Ok(insert::UnionMode::First),
+            "LARGEST" => Ok(insert::UnionMode::Largest),
+            "ALL" => Ok(insert::UnionMode::All),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DataTypeParser;
+
+impl clap::builder::TypedValueParser for DataTypeParser {
+    type Value = DataType;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match datatype::parse_a2l_datatype(value.to_string_lossy().as_ref()) {
+            Some(datatype) => Ok(datatype),
+            None => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ShowXcpFormatParser;
+
+impl clap::builder::TypedValueParser for ShowXcpFormatParser {
+    type Value = xcp::ShowXcpFormat;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().to_ascii_lowercase().as_ref() {
+            "text" => Ok(xcp::ShowXcpFormat::Text),
+            "json" => Ok(xcp::ShowXcpFormat::Json),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SignalListFormatParser;
+
+impl clap::builder::TypedValueParser for SignalListFormatParser {
+    type Value = signal_list::SignalListFormat;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value.to_string_lossy().to_ascii_lowercase().as_ref() {
+            "csv" => Ok(signal_list::SignalListFormat::Csv),
+            "json" => Ok(signal_list::SignalListFormat::Json),
+            _ => {
+                let mut err =
+                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
+                if let Some(arg) = arg {
+                    err.insert(
+                        clap::error::ContextKind::InvalidArg,
+                        clap::error::ContextValue::String(arg.to_string()),
+                    );
+                }
+                let strval = value.to_string_lossy();
+                err.insert(
+                    clap::error::ContextKind::InvalidValue,
+                    clap::error::ContextValue::String(String::from(strval)),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_option_create_output() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        let result = core(args.into_iter());
+        // Passing the option --create should neither panic nor return an error
+        // Passing the option --output should neither panic nor return an error
+        // After the run, the output file should exist
+        assert!(result.is_ok());
+        assert!(outfile.exists());
+        assert!(outfile.is_file());
+    }
+
+    #[test]
+    fn test_option_group_instances() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output1.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_typedef_test1.a2l"),
+            OsString::from("--group-instances"),
+            OsString::from("--target-group"),
+            OsString::from("Instances"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let target = module.group.iter().find(|g| g.name == "Instances").unwrap();
+        let target_subgroups = &target.sub_group.as_ref().unwrap().identifier_list;
+        assert!(target_subgroups.contains(&"reg".to_string()));
+        assert!(target_subgroups.contains(&"TEST_structarr".to_string()));
+
+        let reg_group = module.group.iter().find(|g| g.name == "reg").unwrap();
+        assert!(reg_group
+            .ref_measurement
+            .as_ref()
+            .unwrap()
+            .identifier_list
+            .contains(&"reg.Value".to_string()));
+        // arrays of structures get one sub-group per element
+        let arr_group = module
+            .group
+            .iter()
+            .find(|g| g.name == "TEST_structarr")
+            .unwrap();
+        assert_eq!(
+            arr_group.sub_group.as_ref().unwrap().identifier_list.len(),
+            10
+        );
+
+        // running the option again on the output does not create any additional groups or references
+        let outfile2 = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(outfile),
+            OsString::from("--group-instances"),
+            OsString::from("--target-group"),
+            OsString::from("Instances"),
+            OsString::from("--output"),
+            OsString::from(outfile2.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output2 = a2lfile::load(outfile2, None, &mut Vec::new(), false).unwrap();
+        let summarize = |a2l: &a2lfile::A2lFile| {
+            a2l.project.module[0]
+                .group
+                .iter()
+                .map(|g| {
+                    (
+                        g.name.clone(),
+                        g.ref_characteristic
+                            .as_ref()
+                            .map(|r| r.identifier_list.clone()),
+                        g.ref_measurement
+                            .as_ref()
+                            .map(|r| r.identifier_list.clone()),
+                        g.sub_group.as_ref().map(|r| r.identifier_list.clone()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summarize(&a2l_output), summarize(&a2l_output2));
+    }
+
+    #[test]
+    fn test_option_project_module_name() {
+        // the names of the PROJECT and MODULE can be set when a file is created
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output1.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--project-name"),
+            OsString::from("MyProject"),
+            OsString::from("--module-name"),
+            OsString::from("MyModule"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.name, "MyProject");
+        assert_eq!(a2l_output.project.module[0].name, "MyModule");
+
+        // the names can also be changed in an existing file
+        let outfile = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--project-name"),
+            OsString::from("OtherProject"),
+            OsString::from("--module-name"),
+            OsString::from("OtherModule"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.name, "OtherProject");
+        assert_eq!(a2l_output.project.module[0].name, "OtherModule");
+        // nothing else has changed
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            a2l_input.project.module[0].measurement.len(),
+            a2l_output.project.module[0].measurement.len()
+        );
+
+        assert!(parse_a2l_identifier("Valid_Name.x[0]").is_ok());
+        assert!(parse_a2l_identifier("0invalid").is_err());
+        assert!(parse_a2l_identifier("has space").is_err());
+        assert!(parse_a2l_identifier("").is_err());
+    }
+
+    #[test]
+    fn test_option_input() {
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+        ];
+        let result = core(args.into_iter());
+        // Passing the option --input should neither panic nor return an error
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_option_check() {
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/check_test.a2l"),
+            OsString::from("--check"),
+        ];
+        let result = core(args.into_iter());
+        // Passing the option --check should neither panic nor return an error
+        // check_test.a2l has problems, but without --strict they are only warnings
+        assert!(result.is_ok());
+
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/check_test.a2l"),
+            OsString::from("--check"),
+            OsString::from("--strict"),
+        ];
+        let result = core(args.into_iter());
+        // Passing the option --check should neither panic nor return an error
+        // check_test.a2l has problems, and with --strict they are errors
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_option_check_circular_groups() {
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/circular_groups_test.a2l"),
+            OsString::from("--check-circular-groups"),
+        ];
+        // without --strict the cycles are only reported
+        assert!(core(args.into_iter()).is_ok());
+
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/circular_groups_test.a2l"),
+            OsString::from("--check-circular-groups"),
+            OsString::from("--strict"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_elffile() {
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+        ];
+        // Passing the option --elffile should neither panic nor return an error
+        core(args.into_iter()).unwrap();
+    }
+
+    #[test]
+    fn test_option_cleanup() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/cleanup_test.a2l"),
+            OsString::from("--cleanup"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        // Passing the option --cleanup should neither panic nor return an error
+        // cleanup_test.a2l has unused items, but --cleanup should remove them
+        core(args.into_iter()).unwrap();
+
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/cleanup_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_ne!(a2l_input, a2l_output);
+        // all items in cleanup_test.a2l are used
+        assert!(a2l_output.project.module[0].record_layout.is_empty());
+        assert!(a2l_output.project.module[0].compu_method.is_empty());
+        assert!(a2l_output.project.module[0].group.is_empty());
+    }
+
+    #[test]
+    fn test_option_cleanup_dry_run() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/cleanup_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+
+        // a dry run lists the items, but the file is not changed
+        let outfile = tempdir.join("output1.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/cleanup_test.a2l"),
+            OsString::from("--cleanup"),
+            OsString::from("--cleanup-dry-run"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_input, a2l_output);
+
+        // items that match --cleanup-keep are not removed
+        let outfile = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/cleanup_test.a2l"),
+            OsString::from("--cleanup"),
+            OsString::from("--cleanup-keep"),
+            OsString::from(".*_Compu"),
+            OsString::from("-v"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(
+            a2l_output.project.module[0].compu_method.len(),
+            a2l_input.project.module[0].compu_method.len()
+        );
+        assert!(a2l_output.project.module[0].record_layout.is_empty());
+    }
+
+    #[test]
+    fn test_option_cleanup_conversions() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/cleanup_test.a2l"),
+            OsString::from("--cleanup-conversions"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/cleanup_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        // the COMPU_METHODs in cleanup_test.a2l are not used and should be removed
+        assert!(a2l_output.project.module[0].compu_method.is_empty());
+        // unlike --cleanup, the unused RECORD_LAYOUTs and empty GROUPs remain
+        assert_eq!(
+            a2l_input.project.module[0].record_layout.len(),
+            a2l_output.project.module[0].record_layout.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].group.len(),
+            a2l_output.project.module[0].group.len()
+        );
+    }
+
+    #[test]
+    fn test_option_normalize_compu_tab() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        let outfile = tempdir.join("output.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD cm "" TAB_VERB "%6.3" "" COMPU_TAB_REF degenerate
+    /end COMPU_METHOD
+    /begin COMPU_VTAB_RANGE degenerate "" 2
+      0 0 "off"
+      1 1 "on"
+    /end COMPU_VTAB_RANGE
+  /end MODULE
+/end PROJECT"#,
+        )
+        .unwrap();
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--normalize-compu-tab"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.compu_vtab_range.is_empty());
+        assert_eq!(module.compu_vtab.len(), 1);
+        assert_eq!(module.compu_vtab[0].name, "degenerate");
+        assert_eq!(module.compu_vtab[0].value_pairs[1].out_val, "on");
+    }
+
+    #[test]
+    fn test_option_enum_as_range() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Enum_Value"),
+            OsString::from("--enum-as-range"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.compu_vtab.is_empty());
+        assert_eq!(module.compu_vtab_range.len(), 1);
+        let compu_vtab_range = &module.compu_vtab_range[0];
+        assert_eq!(compu_vtab_range.name, "enumeration");
+        assert_eq!(compu_vtab_range.value_triples.len(), 6);
+        assert!(compu_vtab_range
+            .value_triples
+            .iter()
+            .all(|triple| triple.in_val_min == triple.in_val_max));
+    }
+
+    #[test]
+    fn test_option_svdfile() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--svdfile"),
+            OsString::from("fixtures/svd/svd_test.svd"),
+            OsString::from("--measurement"),
+            OsString::from("UART0.STATUS.RXNE"),
+            OsString::from("--measurement"),
+            OsString::from("UART0.CTRL.MODE"),
+            OsString::from("--measurement-regex"),
+            OsString::from("^UART1\\..*"),
+            OsString::from("--group-by-peripheral"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let rxne = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "UART0.STATUS.RXNE")
+            .unwrap();
+        assert_eq!(rxne.ecu_address.as_ref().unwrap().address, 0x4000_C004);
+        assert_eq!(rxne.bit_mask.as_ref().unwrap().mask, 0x20);
+        assert_eq!(rxne.datatype, DataType::Ulong);
+        let mode = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "UART0.CTRL.MODE")
+            .unwrap();
+        assert_eq!(mode.conversion, "UART0_CTRL_MODE");
+        assert_eq!(mode.bit_mask.as_ref().unwrap().mask, 0x30);
+        assert_eq!(module.compu_vtab.len(), 1);
+
+        // the regex inserts all fields and registers of UART1
+        let uart1_group = module.group.iter().find(|grp| grp.name == "UART1").unwrap();
+        let uart1_measurements = &uart1_group
+            .ref_measurement
+            .as_ref()
+            .unwrap()
+            .identifier_list;
+        assert_eq!(uart1_measurements.len(), 5);
+        assert!(uart1_measurements.contains(&"UART1.DATA".to_string()));
+        let uart0_group = module.group.iter().find(|grp| grp.name == "UART0").unwrap();
+        assert_eq!(
+            uart0_group
+                .ref_measurement
+                .as_ref()
+                .unwrap()
+                .identifier_list
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_option_fix_formats() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        let outfile = tempdir.join("output.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD cm "" IDENTICAL "%8.3f" ""
+    /end COMPU_METHOD
+    /begin MEASUREMENT Value "" UBYTE cm 0 0 0 255
+      FORMAT "6.2"
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT"#,
+        )
+        .unwrap();
+        // --check reports the invalid FORMAT strings, which is an error in strict mode
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile.clone()),
+            OsString::from("--check"),
+            OsString::from("--strict"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--fix-formats"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.compu_method[0].format, "%8.3");
+        assert_eq!(
+            module.measurement[0].format.as_ref().unwrap().format_string,
+            "%6.2"
+        );
+    }
+
+    #[test]
+    fn test_option_update_system_constants() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_PAR ""
+      SYSTEM_CONSTANT "Characteristic_Value" "0"
+      SYSTEM_CONSTANT "sizeof_Characteristic_ValBlk" "0"
+    /end MOD_PAR
+  /end MODULE
+/end PROJECT
+"#,
+        )
+        .unwrap();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update-system-constants"),
+            OsString::from("--system-constant-dim-prefix"),
+            OsString::from("sizeof_"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let mod_par = a2l_output.project.module[0].mod_par.as_ref().unwrap();
+        assert_eq!(mod_par.system_constant[0].value, "3");
+        assert_eq!(mod_par.system_constant[1].value, "5");
+    }
+
+    #[test]
+    fn test_option_epk_from_symbol() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/epk_test.elf"),
+            OsString::from("--epk-from-symbol"),
+            OsString::from("app_version"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let mod_par = a2l_output.project.module[0].mod_par.as_ref().unwrap();
+        assert_eq!(
+            mod_par.epk.as_ref().unwrap().identifier,
+            "ECU_APP V1.2.3 2024-05-01"
+        );
+        assert_eq!(mod_par.addr_epk.len(), 1);
+    }
+
+    #[test]
+    fn test_option_stamp_binary_id() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--stamp-binary-id"),
+            OsString::from("--strict"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let mod_par = a2l_output.project.module[0].mod_par.as_ref().unwrap();
+        let epk = &mod_par.epk.as_ref().unwrap().identifier;
+        assert!(epk.starts_with("fixtures/bin/update_test.elf"));
+        assert!(epk.contains("timestamp"));
+    }
+
+    #[test]
+    fn test_option_access_flags() {
+        // all symbols of update_test1.a2l are in writable sections, so there is nothing to report in strict mode
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--check-access-flags"),
+            OsString::from("--strict"),
+        ];
+        core(args.into_iter()).unwrap();
+
+        // the CHARACTERISTICs in the calibration section .data must not be READ_ONLY
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let mut a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        a2l_input.project.module[0].characteristic[0].read_only = Some(a2lfile::ReadOnly::new());
+        let infile = tempdir.join("input.a2l");
+        a2l_input.write(&infile, None).unwrap();
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--fix-access-flags"),
+            OsString::from("--calibration-section"),
+            OsString::from(".data"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        assert!(a2l_output.project.module[0].characteristic[0]
+            .read_only
+            .is_none());
+    }
+
+    #[test]
+    fn test_option_sync_byte_order() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/big_endian_test.elf"),
+            OsString::from("--sync-byte-order"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let mod_common = a2l_output.project.module[0].mod_common.as_ref().unwrap();
+        assert_eq!(
+            mod_common.byte_order.as_ref().unwrap().byte_order,
+            a2lfile::ByteOrderEnum::BigEndian
+        );
+    }
+
+    #[test]
+    fn test_option_update_memory_segments() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        let outfile = tempdir.join("output.a2l");
+        let input_text = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_PAR ""
+      /begin MEMORY_SEGMENT data "" DATA RAM INTERN 0x0 0x0 -1 -1 -1 -1 -1
+      /end MEMORY_SEGMENT
+    /end MOD_PAR
+  /end MODULE
+/end PROJECT
+"#;
+        std::fs::write(&infile, input_text).unwrap();
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update-memory-segments"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let mod_par = a2l_output.project.module[0].mod_par.as_ref().unwrap();
+        assert_ne!(mod_par.memory_segment[0].address, 0);
+        assert_ne!(mod_par.memory_segment[0].size, 0);
+    }
+
+    #[test]
+    fn test_option_dump_type() {
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--dump-type"),
+            OsString::from("Characteristic_ValBlk"),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--dump-type"),
+            OsString::from("Does_Not_Exist"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_update_group() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--update-group"),
+            OsString::from("Team_A"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_group_test.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let input_module = &a2l_input.project.module[0];
+        let module = &a2l_output.project.module[0];
+        let find_characteristic = |module: &a2lfile::Module, name: &str| {
+            module
+                .characteristic
+                .iter()
+                .find(|item| item.name == name)
+                .cloned()
+                .unwrap()
+        };
+        let find_measurement = |module: &a2lfile::Module, name: &str| {
+            module
+                .measurement
+                .iter()
+                .find(|item| item.name == name)
+                .cloned()
+                .unwrap()
+        };
+
+        // objects in Team_A and its sub-group are updated, including the axis of Curve_ExternalAxis.
+        // Characteristic_Value is also referenced by Team_B, but it is in scope because of Team_A_Curves
+        assert_ne!(find_characteristic(module, "Curve_ExternalAxis").address, 0);
+        assert_ne!(
+            find_characteristic(module, "Characteristic_Value").address,
+            0
+        );
+        assert_ne!(
+            module
+                .axis_pts
+                .iter()
+                .find(|item| item.name == "Axis_0")
+                .unwrap()
+                .address,
+            0
+        );
+        assert_ne!(
+            find_measurement(module, "Measurement_Value")
+                .ecu_address
+                .unwrap()
+                .address,
+            0
+        );
+
+        // objects that are only in Team_B or in no group at all are unchanged
+        assert_eq!(
+            find_characteristic(module, "Map_InternalAxis"),
+            find_characteristic(input_module, "Map_InternalAxis")
+        );
+        assert_eq!(
+            find_characteristic(module, "Curve_InternalAxis"),
+            find_characteristic(input_module, "Curve_InternalAxis")
+        );
+        assert_eq!(
+            find_measurement(module, "Measurement_Bitfield_bits1"),
+            find_measurement(input_module, "Measurement_Bitfield_bits1")
+        );
+
+        // unknown group names are an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--update-group"),
+            OsString::from("Team_C"),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.unwrap_err().contains("there is no GROUP Team_C"));
+    }
+
+    #[test]
+    fn test_option_max_errors() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test2.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("ADDRESSES"),
+            OsString::from("--max-errors"),
+            OsString::from("1"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        // the update stopped at the first error, so the MEASUREMENT whose symbol
+        // does not exist was not removed
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 7);
+    }
+
+    #[test]
+    fn test_option_max_candidates() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--max-candidates"),
+            OsString::from("2"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        assert!(outfile.exists());
+    }
+
+    #[test]
+    fn test_option_report_moved() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--report-moved"),
+            OsString::from("0"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        // listing the moved objects doesn't change the result of the update
+        core(args.into_iter()).unwrap();
+        assert!(outfile.exists());
+    }
+
+    #[test]
+    fn test_option_update() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+
+        // 1. full update
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("FULL"),
+            OsString::from("-v"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        // Passing the option --update should neither panic nor return an error
+        // update_test.elf has symbols that can be updated in the a2l file
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        // the output file should have updated addresses
+        let module = &a2l_output.project.module[0];
+        assert_ne!(module.characteristic[0].address, 0);
+        assert_ne!(
+            module.measurement[0].ecu_address.as_ref().unwrap().address,
+            0
+        );
+
+        // 2. address update only in strict mode on valid input
+        let outfile = tempdir.join("output2.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("ADDRESSES"),
+            OsString::from("--update-mode"),
+            OsString::from("STRICT"),
+            OsString::from("-v"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert!(a2l_output.project.module[0].characteristic[0].address != 0);
+
+        // 3. address update only in strict mode on invalid input
+        let outfile = tempdir.join("output3.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test2.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test_invalid.elf"),
+            OsString::from("--update"),
+            OsString::from("ADDRESSES"),
+            OsString::from("--update-mode"),
+            OsString::from("STRICT"),
+            OsString::from("-v"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_option_insert() {
+        // characteristics and measurements can be inserted in several different ways:
+        // - by name with --characteristic and --measurement
+        // - by address range with --characteristic-range and --measurement-range
+        // - by regex with --characteristic-regex and --measurement-regex
+        // - by section with --characteristic-section and --measurement-section
+        // The option --target-group can be used to put the inserted items into a group, and is tested here too
+
+        // 1. insert by name
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output1.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--target-group"),
+            OsString::from("TestGroup"),
+            OsString::from("-v"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
+        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
+        assert_eq!(a2l_output.project.module[0].group.len(), 1);
+        assert_eq!(a2l_output.project.module[0].group[0].name, "TestGroup");
+        // get the addresses of the inserted items for the second test
+        let measurement_addr = a2l_output.project.module[0].measurement[0]
+            .ecu_address
+            .as_ref()
+            .unwrap()
+            .address;
+        let characteristic_addr = a2l_output.project.module[0].characteristic[0].address;
+
+        // 2. insert by address range
+        let outfile = tempdir.join("output2.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic-range"),
+            OsString::from(format!("0x{:x}", characteristic_addr)),
+            OsString::from(format!("0x{:x}", characteristic_addr + 4)),
+            OsString::from("--measurement-range"),
+            OsString::from(format!("0x{:x}", measurement_addr)),
+            OsString::from(format!("0x{:x}", measurement_addr + 4)),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
+        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
+        assert_eq!(a2l_output.project.module[0].group.len(), 0); // no --target-group used this time
+
+        // 3. insert by regex
+        let outfile = tempdir.join("output3.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic-regex"),
+            OsString::from("C.*Value"),
+            OsString::from("--measurement-regex"),
+            OsString::from("M.*Valu."),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
+        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
+
+        // 4. insert by section
+        let outfile = tempdir.join("output4.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic-section"),
+            OsString::from(".data"),
+            OsString::from("--measurement-section"),
+            OsString::from(".bss"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.is_ok());
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert!(!a2l_output.project.module[0].measurement.is_empty());
+        assert!(!a2l_output.project.module[0].characteristic.is_empty());
+
+        // 5. insert by section, using a wildcard
+        let outfile = tempdir.join("output5.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement-section"),
+            OsString::from(".bs?*"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.is_ok());
+        let a2l_output5 = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(
+            a2l_output5.project.module[0].measurement.len(),
+            a2l_output.project.module[0].measurement.len()
+        );
+    }
+
+    #[test]
+    fn test_option_default_event() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--measurement-regex"),
+            OsString::from("Measurement_Matrix"),
+            OsString::from("--default-event"),
+            OsString::from("3"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(
+            outfile,
+            Some(ifdata::A2MLVECTOR_TEXT.to_string()),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        // every inserted MEASUREMENT references the event in its IF_DATA XCP
+        for measurement in &module.measurement {
+            assert_eq!(measurement.if_data.len(), 1);
+            let decoded = ifdata::A2mlVector::load_from_ifdata(&measurement.if_data[0]).unwrap();
+            assert!(decoded.xcp.is_some());
+        }
+        let output_text = std::fs::read_to_string(tempdir.join("output.a2l")).unwrap();
+        let event_regex = regex::Regex::new(r"FIXED_EVENT_LIST\s+EVENT\s+3\b").unwrap();
+        assert!(event_regex.is_match(&output_text));
+        // CHARACTERISTICs are not affected
+        assert!(module.characteristic[0].if_data.is_empty());
+    }
+
+    #[test]
+    fn test_option_address_ext() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--address-ext"),
+            OsString::from("2"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let output_text = std::fs::read_to_string(&outfile).unwrap();
+        assert_eq!(output_text.matches("ECU_ADDRESS_EXTENSION 2").count(), 2);
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let extension = module.measurement[0]
+            .ecu_address_extension
+            .as_ref()
+            .unwrap();
+        assert_eq!(extension.extension, 2);
+        let extension = module.characteristic[0]
+            .ecu_address_extension
+            .as_ref()
+            .unwrap();
+        assert_eq!(extension.extension, 2);
+    }
+
+    #[test]
+    fn test_option_default_limits() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Enum_Value"),
+            OsString::from("--default-limits"),
+            OsString::from("-10"),
+            OsString::from("250.5"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let measurement = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Measurement_Value")
+            .unwrap();
+        assert_eq!(measurement.lower_limit, -10.0);
+        assert_eq!(measurement.upper_limit, 250.5);
+        assert_eq!(module.characteristic[0].lower_limit, -10.0);
+        assert_eq!(module.characteristic[0].upper_limit, 250.5);
+        // items with a conversion keep their limits
+        let enum_measurement = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Enum_Value")
+            .unwrap();
+        assert_ne!(enum_measurement.upper_limit, 250.5);
+
+        // the lower limit must not be greater than the upper limit
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--default-limits"),
+            OsString::from("10"),
+            OsString::from("0"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_strip_address_ext() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--address-ext"),
+            OsString::from("0"),
+            OsString::from("--output"),
+            OsString::from(infile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let input_text = std::fs::read_to_string(&infile).unwrap();
+        assert_eq!(input_text.matches("ECU_ADDRESS_EXTENSION 0").count(), 2);
+
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--strip-address-ext"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let output_text = std::fs::read_to_string(&outfile).unwrap();
+        assert!(!output_text.contains("ECU_ADDRESS_EXTENSION"));
+    }
+
+    #[test]
+    fn test_option_include_untyped() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        // a variable without debug info can be inserted by name
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/untyped_symbol_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Asm_Long"),
+            OsString::from("--measurement-regex"),
+            OsString::from("Asm_.*"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        // the regex does not match any of the variables without debug info
+        assert_eq!(module.measurement.len(), 1);
+        assert_eq!(module.measurement[0].name, "Asm_Long");
+        assert_eq!(module.measurement[0].datatype, DataType::Ulong);
+        assert_eq!(
+            module.measurement[0].ecu_address.as_ref().unwrap().address,
+            0x4014
+        );
+
+        // with --include-untyped the regex inserts all of them
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/untyped_symbol_test.elf"),
+            OsString::from("--measurement-regex"),
+            OsString::from("Asm_.*"),
+            OsString::from("--include-untyped"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 5);
+        let buffer = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Asm_Buffer")
+            .unwrap();
+        assert_eq!(buffer.datatype, DataType::Ubyte);
+        assert_eq!(buffer.matrix_dim.as_ref().unwrap().dim_list, vec![24]);
+        // the local symbol Asm_Local is inserted as an array of bytes
+        let local = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Asm_Local")
+            .unwrap();
+        assert_eq!(local.ecu_address.as_ref().unwrap().address, 0x4030);
+        assert_eq!(local.matrix_dim.as_ref().unwrap().dim_list, vec![6]);
+    }
+
+    #[test]
+    fn test_option_watch() {
+        // watching is not possible if the output would overwrite the watched input file
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--watch"),
+            OsString::from("--output"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.unwrap_err().contains("same as the input file"));
+
+        // the file names are compared after resolving the paths
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--watch"),
+            OsString::from("--output"),
+            OsString::from("./fixtures/bin/../a2l/update_test1.a2l"),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.unwrap_err().contains("same as the input file"));
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--watch"),
+            OsString::from("--output"),
+            OsString::from("./fixtures/a2l/update_test1.a2l"),
+        ];
+        let result = core(args.into_iter());
+        assert!(result.unwrap_err().contains("same as the input file"));
+
+        // there must be at least one file to watch
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--watch"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_typedef() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/typedef_only_test.elf"),
+            OsString::from("--enable-structures"),
+            OsString::from("--typedef"),
+            OsString::from("Component_State"),
+            OsString::from("--typedef-calib"),
+            OsString::from("Component_Params"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.typedef_structure.len(), 2);
+        assert!(module.instance.is_empty());
+
+        // in strict mode a type that can't be found is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/typedef_only_test.elf"),
+            OsString::from("--enable-structures"),
+            OsString::from("--typedef"),
+            OsString::from("Missing_Type"),
+            OsString::from("--strict"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_embed_values() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/embed_values_test.elf"),
+            OsString::from("--characteristic-regex"),
+            OsString::from("Calib_.*"),
+            OsString::from("--embed-values"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let get_value = |name: &str| {
+            let characteristic = module
+                .characteristic
+                .iter()
+                .find(|characteristic| characteristic.name == name)
+                .unwrap();
+            characteristic
+                .annotation
+                .iter()
+                .find(|annotation| {
+                    annotation
+                        .annotation_label
+                        .as_ref()
+                        .is_some_and(|label| label.label == "a2ltool-value")
+                })
+                .and_then(|annotation| annotation.annotation_text.as_ref())
+                .map(|text| text.annotation_text_list.join(""))
+        };
+        assert_eq!(get_value("Calib_Scalar").as_deref(), Some("1234"));
+        assert_eq!(get_value("Calib_Negative").as_deref(), Some("-5"));
+        assert_eq!(get_value("Calib_Float").as_deref(), Some("2.5"));
+        assert_eq!(get_value("Calib_Array").as_deref(), Some("1 2 3 4"));
+        // large arrays and uninitialized variables have no value
+        assert_eq!(get_value("Calib_Large_Array"), None);
+        assert_eq!(get_value("Calib_Uninitialized"), None);
+    }
+
+    #[test]
+    fn test_typedef_bitfield_limits() {
+        // the TYPEDEF_MEASUREMENTs of bitfield members get the limits of the bitfield width and a BIT_MASK
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/bitfield_limits_test.elf"),
+            OsString::from("--enable-structures"),
+            OsString::from("--typedef"),
+            OsString::from("Bitfield_Struct"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.typedef_structure.len(), 1);
+        let td_struct = &module.typedef_structure[0];
+        let get_component_typedef = |component_name: &str| {
+            let component = td_struct
+                .structure_component
+                .iter()
+                .find(|sc| sc.component_name == component_name)
+                .unwrap();
+            module
+                .typedef_measurement
+                .iter()
+                .find(|td_meas| td_meas.name == component.component_type)
+                .unwrap()
+        };
+        for (component_name, lower_limit, upper_limit, mask) in [
+            ("unsigned_1", 0.0, 1.0, 0x1),
+            ("unsigned_3", 0.0, 7.0, 0xe),
+            ("signed_4", -8.0, 7.0, 0xf0),
+            ("signed_1", -1.0, 0.0, 0x100),
+            ("unsigned_12", 0.0, 4095.0, 0x1ffe00),
+            ("signed_12", -2048.0, 2047.0, 0xfff),
+        ] {
+            let td_meas = get_component_typedef(component_name);
+            assert_eq!(td_meas.lower_limit, lower_limit, "{component_name}");
+            assert_eq!(td_meas.upper_limit, upper_limit, "{component_name}");
+            assert_eq!(
+                td_meas.bit_mask.as_ref().unwrap().mask,
+                mask,
+                "{component_name}"
+            );
+        }
+        let td_meas = get_component_typedef("plain_value");
+        assert_eq!(td_meas.upper_limit, 255.0);
+        assert!(td_meas.bit_mask.is_none());
+    }
+
+    #[test]
+    fn test_insert_void_pointers() {
+        // void pointers have no TYPEDEF_STRUCTURE, so they are inserted as MEASUREMENTs of the pointer value
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/void_pointer_test.elf"),
+            OsString::from("--enable-structures"),
+            OsString::from("--measurement-regex"),
+            OsString::from(".*_Pointer"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 4);
+        assert!(module.instance.is_empty());
+        for measurement in &module.measurement {
+            assert_eq!(measurement.datatype, DataType::AUint64);
+            let address_type = measurement.address_type.as_ref().unwrap();
+            assert_eq!(address_type.address_type, a2lfile::AddrType::Plonglong);
+        }
+    }
+
+    #[test]
+    fn test_load_corrupt_units() {
+        // the variables of the valid compile unit can be inserted
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/corrupt_unit_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("app_version"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
+
+        // in strict mode the skipped compile units are an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/corrupt_unit_test.elf"),
+            OsString::from("--strict"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_access_for_inserts() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--measurement-writeable"),
+            OsString::from("--characteristic-readonly"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 1);
+        assert!(module.measurement[0].read_write.is_some());
+        assert_eq!(module.characteristic.len(), 1);
+        assert!(module.characteristic[0].read_only.is_some());
+
+        // without the options, MEASUREMENTs are read-only and CHARACTERISTICs are writeable
+        let outfile = tempdir.join("output_default.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.measurement[0].read_write.is_none());
+        assert!(module.characteristic[0].read_only.is_none());
+    }
+
+    #[test]
+    fn test_option_at_address() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--at-address"),
+            OsString::from("0x1234"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_Value"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let measurement = &module.measurement[0];
+        assert_eq!(measurement.ecu_address.as_ref().unwrap().address, 0x1234);
+        // the SYMBOL_LINK still refers to the variable in the elf file
+        let symbol_link = measurement.symbol_link.as_ref().unwrap();
+        assert_eq!(symbol_link.symbol_name, "Measurement_Value");
+        // the CHARACTERISTIC keeps the address from the elf file
+        assert_ne!(module.characteristic[0].address, 0x1234);
+        assert_ne!(module.characteristic[0].address, 0);
+
+        // --at-address without a preceding insert is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--at-address"),
+            OsString::from("0x1234"),
+            OsString::from("--at-address"),
+            OsString::from("0x5678"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_merge() {
+        // merging can be done on the MODULE level with --merge and on the PROJECT level with --merge-project
+
+        // 1. merge on the MODULE level
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--merge"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        // there should be only one MODULE in the output
+        assert_eq!(a2l_output.project.module.len(), 1);
+        // the input file was merged with an empty file, so the output should be the same as the input
+        assert_eq!(
+            a2l_output.project.module[0].measurement.len(),
+            a2l_input.project.module[0].measurement.len()
+        );
+        assert_eq!(
+            a2l_output.project.module[0].characteristic.len(),
+            a2l_input.project.module[0].characteristic.len()
+        );
+        assert_eq!(
+            a2l_output.project.module[0].group.len(),
+            a2l_input.project.module[0].group.len()
+        );
+        assert_eq!(
+            a2l_output.project.module[0].record_layout.len(),
+            a2l_input.project.module[0].record_layout.len()
+        );
+        assert_eq!(
+            a2l_output.project.module[0].compu_method.len(),
+            a2l_input.project.module[0].compu_method.len()
+        );
+
+        // 2. merge on the PROJECT level
+        let outfile = tempdir.join("output2.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--merge-project"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        // there should be two MODULEs in the output
+        assert_eq!(a2l_output.project.module.len(), 2);
+        // one of the two MODULEs in the output should be the same as the input file
+        let output_idx = a2l_output
+            .project
+            .module
+            .iter()
+            .position(|m| m.name == a2l_input.project.module[0].name)
+            .unwrap();
+        assert_eq!(
+            a2l_output.project.module[output_idx],
+            a2l_input.project.module[0]
+        );
+    }
+
+    #[test]
+    fn test_option_merge_prefer() {
+        // the input file and the merged file contain a MEASUREMENT and a COMPU_METHOD with the same name, but different content
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let input_file = tempdir.join("input.a2l");
+        let merge_file = tempdir.join("merge.a2l");
+        let make_text = |limits: &str, unit: &str| {
+            format!(
+                "ASAP2_VERSION 1 71\n/begin PROJECT p \"\"\n  /begin MODULE m \"\"\n    /begin COMPU_METHOD CM_Shared \"\" IDENTICAL \"%4.2\" \"{unit}\"\n    /end COMPU_METHOD\n    /begin MEASUREMENT Shared_Measurement \"\" UBYTE CM_Shared 0 0 {limits}\n    /end MEASUREMENT\n  /end MODULE\n/end PROJECT\n"
+            )
+        };
+        std::fs::write(&input_file, make_text("0 100", "unit")).unwrap();
+        std::fs::write(&merge_file, make_text("10 50", "other unit")).unwrap();
+
+        for (prefer, lower_limit, upper_limit, conversion, unit) in [
+            ("INPUT", 0.0, 100.0, "CM_Shared", "unit"),
+            ("MERGE", 10.0, 50.0, "CM_Shared.MERGE", "other unit"),
+        ] {
+            let outfile = tempdir.join(format!("output_{prefer}.a2l"));
+            let args = vec![
+                OsString::from("a2ltool"),
+                OsString::from(input_file.clone()),
+                OsString::from("--merge"),
+                OsString::from(merge_file.clone()),
+                OsString::from("--merge-prefer"),
+                OsString::from(prefer),
+                OsString::from("--output"),
+                OsString::from(outfile.clone()),
+            ];
+            core(args.into_iter()).unwrap();
+            let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+            let module = &a2l_output.project.module[0];
+            assert_eq!(module.measurement.len(), 1);
+            assert_eq!(module.measurement[0].lower_limit, lower_limit);
+            assert_eq!(module.measurement[0].upper_limit, upper_limit);
+            // the COMPU_METHOD has no field-level merge, so it is merged by Module::merge, which keeps both
+            assert_eq!(module.compu_method.len(), 2);
+            // the MEASUREMENT references the COMPU_METHOD of the preferred file, even though it was renamed
+            assert_eq!(module.measurement[0].conversion, conversion);
+            let compu_method = module
+                .compu_method
+                .iter()
+                .find(|item| item.name == conversion)
+                .unwrap();
+            assert_eq!(compu_method.unit, unit);
+        }
+    }
+
+    #[test]
+    fn test_option_merge_only() {
+        // only the COMPU_METHODs and RECORD_LAYOUTs are merged into a new file
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--merge"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--merge-only"),
+            OsString::from("COMPU_METHOD,RECORD_LAYOUT"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.measurement.is_empty());
+        assert!(module.characteristic.is_empty());
+        assert_eq!(
+            module.compu_method.len(),
+            a2l_input.project.module[0].compu_method.len()
+        );
+        assert_eq!(
+            module.record_layout.len(),
+            a2l_input.project.module[0].record_layout.len()
+        );
+
+        // --merge-exclude merges everything else
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--merge"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--merge-exclude"),
+            OsString::from("COMPU_METHOD,RECORD_LAYOUT"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.compu_method.is_empty());
+        assert!(module.record_layout.is_empty());
+        assert_eq!(
+            module.measurement.len(),
+            a2l_input.project.module[0].measurement.len()
+        );
+
+        // invalid block types are rejected
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--merge"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--merge-only"),
+            OsString::from("IF_DATA"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_verify_addresses() {
+        // the addresses in the debug info of update_test.elf match its symbol table
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--verify-addresses"),
+            OsString::from("fixtures/bin/update_test.elf"),
+        ];
+        core(args.into_iter()).unwrap();
+
+        // a missing file is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--verify-addresses"),
+            OsString::from("fixtures/bin/does_not_exist.exe"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_option_remove() {
+        // items can be removed by name with --remove
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        // get the names of the first characteristic and measurement, so they can be removed
+        let characteristic_name = a2l_input.project.module[0].characteristic[0].name.clone();
+        let measurement_name = a2l_input.project.module[0].measurement[0].name.clone();
+
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--remove"),
+            OsString::from(characteristic_name),
+            OsString::from("--remove"),
+            OsString::from(measurement_name),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        // the output should have one less characteristic and one less measurement than the input
+        assert_eq!(
+            a2l_input.project.module[0].characteristic.len(),
+            a2l_output.project.module[0].characteristic.len() + 1
+        );
+        assert_eq!(
+            a2l_input.project.module[0].measurement.len(),
+            a2l_output.project.module[0].measurement.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_option_extract_group() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--extract-group"),
+            OsString::from("Team_A"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let characteristic_names: Vec<&str> = module
+            .characteristic
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(
+            characteristic_names,
+            ["Curve_ExternalAxis", "Characteristic_Value"]
+        );
+        assert_eq!(module.measurement.len(), 1);
+        assert_eq!(module.measurement[0].name, "Measurement_Value");
+        // the axis of Curve_ExternalAxis is part of the extracted group
+        assert_eq!(module.axis_pts.len(), 1);
+        assert_eq!(module.axis_pts[0].name, "Axis_0");
+        assert!(module.blob.is_empty());
+        assert!(module.instance.is_empty());
+        assert_eq!(module.group.len(), 2);
+
+        // the group must exist
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--extract-group"),
+            OsString::from("Team_C"),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
-}
 
-fn section_args_to_ranges(
-    args: Option<ValuesRef<String>>,
-    debug_data: &DebugData,
-    verbose: u8,
-) -> Vec<(u64, u64)> {
-    if let Some(values) = args {
-        let mut addr_ranges: Vec<(u64, u64)> = Vec::new();
-        for section in values {
-            if let Some(range) = debug_data.sections.get(section).copied() {
-                addr_ranges.push(range);
-            } else if verbose > 0 {
-                println!("Cannot insert items from non-existent section {section}!");
-            }
-        }
-        addr_ranges
-    } else {
-        Vec::new()
+    #[test]
+    fn test_option_banner() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--banner"),
+            OsString::from("build 1234\npipeline nightly"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let text = std::fs::read_to_string(&outfile).unwrap();
+        assert!(text.contains(&format!("a2ltool {}", env!("CARGO_PKG_VERSION"))));
+        assert!(text.contains("\n   build 1234\n   pipeline nightly"));
+        // the output can still be loaded
+        a2lfile::load(outfile, None, &mut Vec::new(), true).unwrap();
+
+        // the banner text must not end the comment
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--banner"),
+            OsString::from("build */ 1234"),
+            OsString::from("--output"),
+            OsString::from(tempdir.join("output2.a2l")),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
-}
 
-#[derive(Clone)]
-struct AddressValueParser;
+    #[test]
+    fn test_option_export_signal_list() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let csv_file = tempdir.join("signals.csv");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--export-signal-list"),
+            OsString::from(csv_file.clone()),
+            OsString::from("--export-group"),
+            OsString::from("Team_A"),
+        ];
+        core(args.into_iter()).unwrap();
+        let text = std::fs::read_to_string(csv_file).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "name,unit,datatype,address,bitmask,event,event name,cycle"
+        );
+        assert!(lines[1].starts_with("Measurement_Value,"));
 
-impl clap::builder::TypedValueParser for AddressValueParser {
-    type Value = u64;
+        let json_file = tempdir.join("signals.json");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_group_test.a2l"),
+            OsString::from("--export-signal-list"),
+            OsString::from(json_file.clone()),
+            OsString::from("--signal-list-format"),
+            OsString::from("json"),
+        ];
+        core(args.into_iter()).unwrap();
+        let text = std::fs::read_to_string(json_file).unwrap();
+        assert!(text.contains("\"name\": \"Measurement_Value\""));
+    }
 
-    fn parse_ref(
-        &self,
-        cmd: &clap::Command,
-        arg: Option<&clap::Arg>,
-        value: &std::ffi::OsStr,
-    ) -> Result<Self::Value, clap::Error> {
-        if let Some(txt) = value.to_str() {
-            if let Some(hexval) = txt.strip_prefix("0x") {
-                if let Ok(value) = u64::from_str_radix(hexval, 16) {
-                    return Ok(value);
-                }
-            }
-        }
+    #[test]
+    fn test_option_mapfile() {
+        // lib_state is not present in the debug info, but it can be inserted from the map file if its data type is given
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/mapfile_test.elf"),
+            OsString::from("--mapfile"),
+            OsString::from("fixtures/bin/mapfile_test_armlink.map"),
+            OsString::from("--measurement"),
+            OsString::from("lib_state"),
+            OsString::from("--measurement"),
+            OsString::from("dbg_value"),
+            OsString::from("--datatype"),
+            OsString::from("UWORD"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 2);
+        let lib_state = module
+            .measurement
+            .iter()
+            .find(|m| m.name == "lib_state")
+            .unwrap();
+        assert_eq!(lib_state.datatype, DataType::Uword);
+        assert_eq!(lib_state.ecu_address.as_ref().unwrap().address, 0x2000_0018);
+        // dbg_value is found in the debug info, so the address from the map file is not used
+        let dbg_value = module
+            .measurement
+            .iter()
+            .find(|m| m.name == "dbg_value")
+            .unwrap();
+        assert_eq!(dbg_value.ecu_address.as_ref().unwrap().address, 0x4010);
 
-        let mut err = clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
-        if let Some(arg) = arg {
-            err.insert(
-                clap::error::ContextKind::InvalidArg,
-                clap::error::ContextValue::String(arg.to_string()),
-            );
-        }
-        let strval = value.to_string_lossy();
-        err.insert(
-            clap::error::ContextKind::InvalidValue,
-            clap::error::ContextValue::String(String::from(strval)),
-        );
-        Err(err)
+        // without --datatype, the map file symbol is not inserted
+        let outfile2 = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/mapfile_test.elf"),
+            OsString::from("--mapfile"),
+            OsString::from("fixtures/bin/mapfile_test_armlink.map"),
+            OsString::from("--measurement"),
+            OsString::from("lib_state"),
+            OsString::from("--output"),
+            OsString::from(outfile2.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile2, None, &mut Vec::new(), false).unwrap();
+        assert!(a2l_output.project.module[0].measurement.is_empty());
+
+        // map file symbols are not inserted by a regex, since their data type is unknown
+        let outfile3 = tempdir.join("output3.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/mapfile_test.elf"),
+            OsString::from("--mapfile"),
+            OsString::from("fixtures/bin/mapfile_test_armlink.map"),
+            OsString::from("--measurement-regex"),
+            OsString::from("lib_.*"),
+            OsString::from("--output"),
+            OsString::from(outfile3.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile3, None, &mut Vec::new(), false).unwrap();
+        assert!(!a2l_output.project.module[0]
+            .measurement
+            .iter()
+            .any(|m| m.name == "lib_state"));
     }
-}
 
-#[derive(Clone, Copy)]
-struct A2lVersionParser;
+    #[test]
+    fn test_option_seed_addresses() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let csvfile = tempdir.join("addresses.csv");
+        std::fs::write(
+            &csvfile,
+            "name,address\nCharacteristic_Value,0x8001000\nMeasurement_Value,0x8002000\n",
+        )
+        .unwrap();
 
-impl clap::builder::TypedValueParser for A2lVersionParser {
-    type Value = A2lVersion;
+        // without an elf file, the seeded addresses are written to the output
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--seed-addresses"),
+            OsString::from(csvfile.clone()),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let characteristic = module
+            .characteristic
+            .iter()
+            .find(|item| item.name == "Characteristic_Value")
+            .unwrap();
+        assert_eq!(characteristic.address, 0x8001000);
+        let measurement = module
+            .measurement
+            .iter()
+            .find(|item| item.name == "Measurement_Value")
+            .unwrap();
+        assert_eq!(measurement.ecu_address.as_ref().unwrap().address, 0x8002000);
 
-    fn parse_ref(
-        &self,
-        cmd: &clap::Command,
-        arg: Option<&clap::Arg>,
-        value: &std::ffi::OsStr,
-    ) -> Result<Self::Value, clap::Error> {
-        let value_str = value.to_string_lossy();
-        match &*value_str {
-            "1.50" | "1.5.0" => Ok(A2lVersion::V1_5_0),
-            "1.51" | "1.5.1" => Ok(A2lVersion::V1_5_1),
-            "1.60" | "1.6.0" => Ok(A2lVersion::V1_6_0),
-            "1.61" | "1.6.1" => Ok(A2lVersion::V1_6_1),
-            "1.70" | "1.7.0" => Ok(A2lVersion::V1_7_0),
-            "1.71" | "1.7.1" => Ok(A2lVersion::V1_7_1),
-            _ => {
-                let mut err =
-                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
-                if let Some(arg) = arg {
-                    err.insert(
-                        clap::error::ContextKind::InvalidArg,
-                        clap::error::ContextValue::String(arg.to_string()),
-                    );
-                }
-                let strval = value.to_string_lossy();
-                err.insert(
-                    clap::error::ContextKind::InvalidValue,
-                    clap::error::ContextValue::String(String::from(strval)),
-                );
-                Err(err)
-            }
-        }
+        // an update from the elf file replaces the seeded addresses
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--seed-addresses"),
+            OsString::from(csvfile),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let characteristic = a2l_output.project.module[0]
+            .characteristic
+            .iter()
+            .find(|item| item.name == "Characteristic_Value")
+            .unwrap();
+        assert_ne!(characteristic.address, 0x8001000);
     }
-}
 
-impl From<&A2lFile> for A2lVersion {
-    fn from(a2l_file: &A2lFile) -> Self {
-        if let Some(asap2_version) = &a2l_file.asap2_version {
-            match (asap2_version.version_no, asap2_version.upgrade_no) {
-                (1, 51) => A2lVersion::V1_5_1,
-                (1, 60) => A2lVersion::V1_6_0,
-                (1, 61) => A2lVersion::V1_6_1,
-                (1, 70) => A2lVersion::V1_7_0,
-                (1, 71) => A2lVersion::V1_7_1,
-                _ => A2lVersion::V1_5_0,
-            }
-        } else {
-            A2lVersion::V1_5_0
-        }
+    #[test]
+    fn test_option_virtual_measurements() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let deffile = tempdir.join("virtual.txt");
+        std::fs::write(
+            &deffile,
+            "# name; formula; inputs; unit; lower limit; upper limit\nPower; X1 * X2 / 9549; Measurement_Value, Measurement_Enum; kW; 0; 500\n",
+        )
+        .unwrap();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--virtual-measurements"),
+            OsString::from(deffile.clone()),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        let power = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Power")
+            .unwrap();
+        assert!(power.var_virtual.is_some());
+        assert!(module
+            .compu_method
+            .iter()
+            .any(|cm| cm.name == power.conversion && cm.formula.is_some()));
+
+        // an input measurement that does not exist is an error
+        std::fs::write(&deffile, "Power; X1; Does_Not_Exist; kW; 0; 500\n").unwrap();
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--virtual-measurements"),
+            OsString::from(deffile),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
-}
 
-impl Display for A2lVersion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            A2lVersion::V1_5_0 => f.write_str("1.5.0"),
-            A2lVersion::V1_5_1 => f.write_str("1.5.1"),
-            A2lVersion::V1_6_0 => f.write_str("1.6.0"),
-            A2lVersion::V1_6_1 => f.write_str("1.6.1"),
-            A2lVersion::V1_7_0 => f.write_str("1.7.0"),
-            A2lVersion::V1_7_1 => f.write_str("1.7.1"),
-        }
-    }
-}
+    #[test]
+    fn test_option_convert() {
+        // MEASUREMENTs can be converted to CHARACTERISTICs and back
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let measurement_name = a2l_input.project.module[0].measurement[0].name.clone();
 
-#[derive(Clone, Copy)]
-struct UpdateModeParser;
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--convert-to-characteristic"),
+            OsString::from(measurement_name.clone()),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(
+            module.measurement.len() + 1,
+            a2l_input.project.module[0].measurement.len()
+        );
+        assert!(module
+            .characteristic
+            .iter()
+            .any(|chr| chr.name == measurement_name));
 
-impl clap::builder::TypedValueParser for UpdateModeParser {
-    type Value = UpdateMode;
+        // convert it back
+        let outfile2 = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(outfile),
+            OsString::from("--convert-to-measurement"),
+            OsString::from(measurement_name.clone()),
+            OsString::from("--output"),
+            OsString::from(outfile2.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile2, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(
+            module.measurement.len(),
+            a2l_input.project.module[0].measurement.len()
+        );
+        assert!(module
+            .measurement
+            .iter()
+            .any(|meas| meas.name == measurement_name));
 
-    fn parse_ref(
-        &self,
-        cmd: &clap::Command,
-        arg: Option<&clap::Arg>,
-        value: &std::ffi::OsStr,
-    ) -> Result<Self::Value, clap::Error> {
-        match value.to_string_lossy().as_ref() {
-            "DEFAULT" => Ok(UpdateMode::Default),
-            "STRICT" => Ok(UpdateMode::Strict),
-            "PRESERVE" => Ok(UpdateMode::Preserve),
-            _ => {
-                let mut err =
-                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
-                if let Some(arg) = arg {
-                    err.insert(
-                        clap::error::ContextKind::InvalidArg,
-                        clap::error::ContextValue::String(arg.to_string()),
-                    );
-                }
-                let strval = value.to_string_lossy();
-                err.insert(
-                    clap::error::ContextKind::InvalidValue,
-                    clap::error::ContextValue::String(String::from(strval)),
-                );
-                Err(err)
-            }
-        }
+        // an invalid regex is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--convert-to-measurement"),
+            OsString::from("(abc"),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
-}
 
-#[derive(Clone, Copy)]
-struct UpdateTypeParser;
+    #[test]
+    fn test_option_rename() {
+        // items can be renamed with --rename, using sed-like expressions
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Measurement_Value"),
+            OsString::from("--target-group"),
+            OsString::from("TestGroup"),
+            OsString::from("--rename"),
+            OsString::from("s/^Measurement_/Meas_/"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement[0].name, "Meas_Value");
+        // the reference in the group is renamed too
+        assert_eq!(
+            module.group[0]
+                .ref_measurement
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec!["Meas_Value".to_string()]
+        );
 
-impl clap::builder::TypedValueParser for UpdateTypeParser {
-    type Value = UpdateType;
+        // an invalid rename expression is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--rename"),
+            OsString::from("not-an-expression"),
+        ];
+        assert!(core(args.into_iter()).is_err());
+    }
 
-    fn parse_ref(
-        &self,
-        cmd: &clap::Command,
-        arg: Option<&clap::Arg>,
-        value: &std::ffi::OsStr,
-    ) -> Result<Self::Value, clap::Error> {
-        match value.to_string_lossy().as_ref() {
-            "FULL" => Ok(UpdateType::Full),
-            "ADDRESSES" => Ok(UpdateType::Addresses),
-            _ => {
-                let mut err =
-                    clap::Error::new(clap::error::ErrorKind::ValueValidation).with_cmd(cmd);
-                if let Some(arg) = arg {
-                    err.insert(
-                        clap::error::ContextKind::InvalidArg,
-                        clap::error::ContextValue::String(arg.to_string()),
-                    );
-                }
-                let strval = value.to_string_lossy();
-                err.insert(
-                    clap::error::ContextKind::InvalidValue,
-                    clap::error::ContextValue::String(String::from(strval)),
-                );
-                Err(err)
-            }
-        }
+    #[test]
+    fn test_option_a2lversion() {
+        // the a2l version can be set with --a2lversion
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--a2lversion"),
+            OsString::from("1.6.0"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().version_no, 1);
+        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().upgrade_no, 60);
+
+        // structures can not be used in a newly created 1.6.0 file
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--a2lversion"),
+            OsString::from("1.6.0"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--enable-structures"),
+        ];
+        let error = core(args.into_iter()).unwrap_err();
+        assert!(error.contains("requires a2l version 1.7.1"));
+
+        // modify the a2l version of an existing file
+        let outfile2 = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--a2lversion"),
+            OsString::from("1.5.0"),
+            OsString::from("--output"),
+            OsString::from(outfile2.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile2, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().version_no, 1);
+        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().upgrade_no, 50);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    #[test]
+    fn test_option_merge_includes() {
+        // the content of all included files can be merged with --merge-includes
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/merge_inc_test.a2l"),
+            OsString::from("--merge-includes"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let output_text = std::fs::read_to_string(outfile).unwrap();
+        // the output file should not contain any /include commands
+        assert!(!output_text.contains("/include"));
+    }
+
+    #[test]
+    fn test_option_sort() {
+        // all items in the file can be sorted with --sort
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--sort"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+        let a2l_input = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+
+        // Though sorting does not change the meaning of the file, the order of the items in the output is different.
+        // That means the files are not directly equal.
+        assert_ne!(a2l_input, a2l_output);
+        // Comparing the number of items is a reasonable approximation to show that the content remains the same.
+        assert_eq!(
+            a2l_input.project.module[0].measurement.len(),
+            a2l_output.project.module[0].measurement.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].characteristic.len(),
+            a2l_output.project.module[0].characteristic.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].group.len(),
+            a2l_output.project.module[0].group.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].record_layout.len(),
+            a2l_output.project.module[0].record_layout.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].compu_method.len(),
+            a2l_output.project.module[0].compu_method.len()
+        );
+        assert_eq!(
+            a2l_input.project.module[0].instance.len(),
+            a2l_output.project.module[0].instance.len()
+        );
+    }
 
     #[test]
-    fn test_option_create_output() {
-        let tempdir = tempfile::tempdir().unwrap().into_path();
-        let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
+    fn test_stdin_stdout() {
+        // "-" reads the input from stdin and writes the output to stdout
+        let input_text = std::fs::read_to_string("fixtures/a2l/update_test1.a2l").unwrap();
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("--create"),
+            OsString::from("-"),
             OsString::from("--output"),
-            OsString::from(outfile.clone()),
+            OsString::from("-"),
         ];
-        let result = core(args.into_iter());
-        // Passing the option --create should neither panic nor return an error
-        // Passing the option --output should neither panic nor return an error
-        // After the run, the output file should exist
-        assert!(result.is_ok());
-        assert!(outfile.exists());
-        assert!(outfile.is_file());
-    }
+        let mut output = Vec::new();
+        core_with_io(args.into_iter(), &mut input_text.as_bytes(), &mut output).unwrap();
+        let output_text = String::from_utf8(output).unwrap();
+        // only the a2l file is written to stdout
+        assert!(output_text.starts_with("/* a2ltool"));
+        let a2l_input =
+            a2lfile::load_from_string(&input_text, None, &mut Vec::new(), false).unwrap();
+        let a2l_output =
+            a2lfile::load_from_string(&output_text, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(
+            a2l_input.project.module[0].measurement.len(),
+            a2l_output.project.module[0].measurement.len()
+        );
 
-    #[test]
-    fn test_option_input() {
+        // a fragment that only contains the content of a MODULE is also accepted from stdin
+        let fragment_text = r#"
+/begin MEASUREMENT Fragment_Value ""
+  UBYTE NO_COMPU_METHOD 0 0 0 255
+  ECU_ADDRESS 0x1000
+/end MEASUREMENT
+"#;
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("-"),
+            OsString::from("--output"),
+            OsString::from("-"),
         ];
-        let result = core(args.into_iter());
-        // Passing the option --input should neither panic nor return an error
-        assert!(result.is_ok());
+        let mut output = Vec::new();
+        core_with_io(args.into_iter(), &mut fragment_text.as_bytes(), &mut output).unwrap();
+        let output_text = String::from_utf8(output).unwrap();
+        let a2l_output =
+            a2lfile::load_from_string(&output_text, None, &mut Vec::new(), false).unwrap();
+        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
+        assert_eq!(
+            a2l_output.project.module[0].measurement[0].name,
+            "Fragment_Value"
+        );
     }
 
     #[test]
-    fn test_option_check() {
+    fn test_option_sort_stable() {
+        // sorting a file that was already sorted with --sort-stable does not change it
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile1 = tempdir.join("output1.a2l");
+        let outfile2 = tempdir.join("output2.a2l");
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/check_test.a2l"),
-            OsString::from("--check"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--sort-stable"),
+            OsString::from("--output"),
+            OsString::from(outfile1.clone()),
         ];
-        let result = core(args.into_iter());
-        // Passing the option --check should neither panic nor return an error
-        // check_test.a2l has problems, but without --strict they are only warnings
-        assert!(result.is_ok());
-
+        core(args.into_iter()).unwrap();
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/check_test.a2l"),
-            OsString::from("--check"),
-            OsString::from("--strict"),
+            OsString::from(outfile1.clone()),
+            OsString::from("--sort-stable"),
+            OsString::from("--output"),
+            OsString::from(outfile2.clone()),
         ];
-        let result = core(args.into_iter());
-        // Passing the option --check should neither panic nor return an error
-        // check_test.a2l has problems, and with --strict they are errors
-        assert!(result.is_err());
+        core(args.into_iter()).unwrap();
+        let text1 = std::fs::read_to_string(outfile1).unwrap();
+        let text2 = std::fs::read_to_string(outfile2).unwrap();
+        // the whitespace between the banner and ASAP2_VERSION is different when the input already has a banner
+        assert_eq!(
+            text1.split_once("ASAP2_VERSION").unwrap().1,
+            text2.split_once("ASAP2_VERSION").unwrap().1
+        );
+
+        let a2l_output = a2lfile::load_from_string(&text1, None, &mut Vec::new(), false).unwrap();
+        let names: Vec<&str> = a2l_output.project.module[0]
+            .measurement
+            .iter()
+            .map(|measurement| measurement.name.as_str())
+            .collect();
+        let mut sorted_names = names.clone();
+        sorted_names.sort();
+        assert_eq!(names, sorted_names);
     }
 
     #[test]
-    fn test_option_elffile() {
+    fn test_option_preserve_source_order() {
+        // with --preserve-source-order, new items are not sorted by name, but remain in the order of the debug info
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
             OsString::from("--elffile"),
             OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--measurement-regex"),
+            OsString::from("(Measurement_Value|Enum_Value|Characteristic_Value|Blob_2)"),
+            OsString::from("--preserve-source-order"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
         ];
-        // Passing the option --elffile should neither panic nor return an error
         core(args.into_iter()).unwrap();
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let output_names: Vec<&str> = a2l_output.project.module[0]
+            .measurement
+            .iter()
+            .map(|m| &*m.name)
+            .collect();
+
+        let debugdata =
+            DebugData::load_dwarf(OsStr::new("fixtures/bin/update_test.elf"), false).unwrap();
+        let source_names: Vec<&str> = debugdata
+            .variables
+            .keys()
+            .map(|name| &**name)
+            .filter(|name| output_names.contains(name))
+            .collect();
+        assert_eq!(output_names.len(), 4);
+        assert_eq!(output_names, source_names);
+        // in the source the variables are not in alphabetical order, so sorting would have changed the result
+        let mut sorted_names = source_names.clone();
+        sorted_names.sort_unstable();
+        assert_ne!(output_names, sorted_names);
     }
 
     #[test]
-    fn test_option_cleanup() {
-        let tempdir = tempfile::tempdir().unwrap().into_path();
-        let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
+    fn test_option_xcp() {
+        // the XCP settings in the file can be displayed with --show-xcp
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/cleanup_test.a2l"),
-            OsString::from("--cleanup"),
-            OsString::from("--output"),
-            OsString::from(outfile.clone()),
+            OsString::from("fixtures/a2l/xcp_test.a2l"),
+            OsString::from("--show-xcp"),
         ];
-        // Passing the option --cleanup should neither panic nor return an error
-        // cleanup_test.a2l has unused items, but --cleanup should remove them
+        // Passing the option --show-xcp should neither panic nor return an error
+        // The option only prints some information, so it is not possisble to check the output
         core(args.into_iter()).unwrap();
 
-        let a2l_input = a2lfile::load(
-            "fixtures/a2l/cleanup_test.a2l",
-            None,
-            &mut Vec::new(),
-            false,
-        )
-        .unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert_ne!(a2l_input, a2l_output);
-        // all items in cleanup_test.a2l are used
-        assert!(a2l_output.project.module[0].record_layout.is_empty());
-        assert!(a2l_output.project.module[0].compu_method.is_empty());
-        assert!(a2l_output.project.module[0].group.is_empty());
+        // the same information can be output as json
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/xcp_test.a2l"),
+            OsString::from("--show-xcp"),
+            OsString::from("--show-xcp-format"),
+            OsString::from("json"),
+        ];
+        core(args.into_iter()).unwrap();
     }
 
     #[test]
-    fn test_option_update() {
-        let tempdir = tempfile::tempdir().unwrap().into_path();
+    fn test_option_xcpplus() {
+        // the A2ML in xcpplus_test.a2l does not define XCPplus, so the built-in specification is used for the block
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/xcpplus_test.a2l"),
+            OsString::from("--show-xcp"),
+        ];
+        core(args.into_iter()).unwrap();
 
-        // 1. full update
+        // the XCPplus block is valid, and it is not deleted by --ifdata-cleanup
+        let tempdir = tempfile::tempdir().unwrap().into_path();
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/update_test1.a2l"),
-            OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--update"),
-            OsString::from("FULL"),
-            OsString::from("-v"),
+            OsString::from("fixtures/a2l/xcpplus_test.a2l"),
+            OsString::from("--ifdata-cleanup"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
-        // Passing the option --update should neither panic nor return an error
-        // update_test.elf has symbols that can be updated in the a2l file
         core(args.into_iter()).unwrap();
+        let output_text = std::fs::read_to_string(outfile).unwrap();
+        assert!(output_text.contains("/begin IF_DATA XCPplus"));
+        assert!(output_text.contains("/begin XCP_ON_UDP_IP"));
+    }
 
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        // the output file should have updated addresses
-        let module = &a2l_output.project.module[0];
-        assert_ne!(module.characteristic[0].address, 0);
-        assert_ne!(
-            module.measurement[0].ecu_address.as_ref().unwrap().address,
-            0
-        );
+    #[test]
+    fn test_option_list_typedefs() {
+        // the TYPEDEF_STRUCTUREs in the file can be displayed with --list-typedefs
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_typedef_test1.a2l"),
+            OsString::from("--list-typedefs"),
+        ];
+        // Passing the option --list-typedefs should neither panic nor return an error
+        core(args.into_iter()).unwrap();
+    }
 
-        // 2. address update only in strict mode on valid input
-        let outfile = tempdir.join("output2.a2l");
-        assert!(!outfile.exists());
+    #[test]
+    fn test_option_list_conversions() {
+        // the COMPU_METHODs in the file and their usage can be displayed with --list-conversions
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("fixtures/a2l/update_test1.a2l"),
-            OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--update"),
-            OsString::from("ADDRESSES"),
-            OsString::from("--update-mode"),
-            OsString::from("STRICT"),
-            OsString::from("-v"),
-            OsString::from("--output"),
-            OsString::from(outfile.clone()),
+            OsString::from("--list-conversions"),
         ];
+        // Passing the option --list-conversions should neither panic nor return an error
         core(args.into_iter()).unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert!(a2l_output.project.module[0].characteristic[0].address != 0);
+    }
 
-        // 3. address update only in strict mode on invalid input
-        let outfile = tempdir.join("output3.a2l");
-        assert!(!outfile.exists());
+    #[test]
+    fn test_option_include_dir() {
+        // the input file includes "included.a2l", which is not located next to it
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        let outfile = tempdir.join("output.a2l");
+        let input_text = std::fs::read_to_string("fixtures/a2l/merge_inc_test.a2l").unwrap();
+        std::fs::write(&infile, input_text).unwrap();
+
+        // with --include-dir the included file is found
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/update_test2.a2l"),
-            OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test_invalid.elf"),
-            OsString::from("--update"),
-            OsString::from("ADDRESSES"),
-            OsString::from("--update-mode"),
-            OsString::from("STRICT"),
-            OsString::from("-v"),
+            OsString::from(infile.clone()),
+            OsString::from("--include-dir"),
+            OsString::from("fixtures/a2l"),
+            OsString::from("--merge-includes"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
-        let result = core(args.into_iter());
-        assert!(result.is_err());
+        core(args.into_iter()).unwrap();
+        let output_text = std::fs::read_to_string(outfile).unwrap();
+        assert!(!output_text.contains("/include"));
+        assert!(output_text.contains("Measurement_Value"));
+
+        // the error message lists all locations that were searched
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile),
+            OsString::from("--include-dir"),
+            OsString::from("fixtures/bin"),
+        ];
+        let error = core(args.into_iter()).unwrap_err();
+        assert!(error.contains("Attempted locations"));
+        assert!(error.contains("fixtures/bin"));
     }
 
     #[test]
-    fn test_option_insert() {
-        // characteristics and measurements can be inserted in several different ways:
-        // - by name with --characteristic and --measurement
-        // - by address range with --characteristic-range and --measurement-range
-        // - by regex with --characteristic-regex and --measurement-regex
-        // - by section with --characteristic-section and --measurement-section
-        // The option --target-group can be used to put the inserted items into a group, and is tested here too
-
-        // 1. insert by name
+    fn test_option_prune_groups() {
+        // empty groups can be removed with --prune-groups
         let tempdir = tempfile::tempdir().unwrap().into_path();
-        let outfile = tempdir.join("output1.a2l");
-        assert!(!outfile.exists());
+        let outfile = tempdir.join("output.a2l");
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("--create"),
-            OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--characteristic"),
-            OsString::from("Characteristic_Value"),
-            OsString::from("--measurement"),
-            OsString::from("Measurement_Value"),
-            OsString::from("--target-group"),
-            OsString::from("TestGroup"),
-            OsString::from("-v"),
+            OsString::from("fixtures/a2l/cleanup_test.a2l"),
+            OsString::from("--prune-groups"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
         let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
-        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
-        assert_eq!(a2l_output.project.module[0].group.len(), 1);
-        assert_eq!(a2l_output.project.module[0].group[0].name, "TestGroup");
-        // get the addresses of the inserted items for the second test
-        let measurement_addr = a2l_output.project.module[0].measurement[0]
-            .ecu_address
-            .as_ref()
-            .unwrap()
-            .address;
-        let characteristic_addr = a2l_output.project.module[0].characteristic[0].address;
+        let module = &a2l_output.project.module[0];
+        assert!(module.group.iter().all(|grp| grp.sub_group.is_some()
+            || grp.ref_characteristic.is_some()
+            || grp.ref_measurement.is_some()
+            || grp.function_list.is_some()));
+    }
 
-        // 2. insert by address range
-        let outfile = tempdir.join("output2.a2l");
-        assert!(!outfile.exists());
+    #[test]
+    fn test_option_allow_const_values() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
             OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--characteristic-range"),
-            OsString::from(format!("0x{:x}", characteristic_addr)),
-            OsString::from(format!("0x{:x}", characteristic_addr + 4)),
-            OsString::from("--measurement-range"),
-            OsString::from(format!("0x{:x}", measurement_addr)),
-            OsString::from(format!("0x{:x}", measurement_addr + 4)),
+            OsString::from("fixtures/bin/const_value_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Default_Gain"),
+            OsString::from("--characteristic"),
+            OsString::from("Output_Value"),
+            OsString::from("--allow-const-values"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
-        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
-        assert_eq!(a2l_output.project.module[0].group.len(), 0); // no --target-group used this time
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.characteristic.len(), 2);
+        let gain = module
+            .characteristic
+            .iter()
+            .find(|item| item.name == "Default_Gain")
+            .unwrap();
+        assert_eq!(gain.address, 0);
+        assert_eq!(gain.virtual_characteristic.as_ref().unwrap().formula, "1.5");
 
-        // 3. insert by regex
-        let outfile = tempdir.join("output3.a2l");
-        assert!(!outfile.exists());
+        // without --allow-const-values, the constant is not inserted
+        let outfile = tempdir.join("output2.a2l");
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
             OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--characteristic-regex"),
-            OsString::from("C.*Value"),
-            OsString::from("--measurement-regex"),
-            OsString::from("M.*Valu."),
+            OsString::from("fixtures/bin/const_value_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Default_Gain"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert_eq!(a2l_output.project.module[0].measurement.len(), 1);
-        assert_eq!(a2l_output.project.module[0].characteristic.len(), 1);
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        assert!(a2l_output.project.module[0].characteristic.is_empty());
+    }
 
-        // 4. insert by section
-        let outfile = tempdir.join("output4.a2l");
-        assert!(!outfile.exists());
+    #[test]
+    fn test_option_timeout() {
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let outfile = tempdir.join("output.a2l");
+        // a timeout of zero seconds expires immediately, so loading the debug info is aborted
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
             OsString::from("--elffile"),
-            OsString::from("fixtures/bin/update_test.elf"),
-            OsString::from("--characteristic-section"),
-            OsString::from(".data"),
-            OsString::from("--measurement-section"),
-            OsString::from(".bss"),
+            OsString::from("fixtures/bin/debugdata_gcc.elf"),
+            OsString::from("--timeout"),
+            OsString::from("0"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         let result = core(args.into_iter());
-        assert!(result.is_ok());
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert!(!a2l_output.project.module[0].measurement.is_empty());
-        assert!(!a2l_output.project.module[0].characteristic.is_empty());
+        assert!(result.unwrap_err().contains("time limit"));
+        assert!(!outfile.exists());
+
+        // a generous timeout does not interfere
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/debugdata_gcc.elf"),
+            OsString::from("--timeout"),
+            OsString::from("600"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
     }
 
     #[test]
-    fn test_option_merge() {
-        // merging can be done on the MODULE level with --merge and on the PROJECT level with --merge-project
-
-        // 1. merge on the MODULE level
+    fn test_option_shared_axes() {
         let tempdir = tempfile::tempdir().unwrap().into_path();
+        let deffile = tempdir.join("shared_axes.txt");
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
+        std::fs::write(
+            &deffile,
+            "Ignition_Map; Speed_Axis; Load_Axis\nFuel_Map; Speed_Axis; Load_Axis\n",
+        )
+        .unwrap();
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
-            OsString::from("--merge"),
-            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/shared_axes_test.elf"),
+            OsString::from("--shared-axes"),
+            OsString::from(deffile),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_input = a2lfile::load(
-            "fixtures/a2l/update_test1.a2l",
-            None,
-            &mut Vec::new(),
-            false,
-        )
-        .unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        // there should be only one MODULE in the output
-        assert_eq!(a2l_output.project.module.len(), 1);
-        // the input file was merged with an empty file, so the output should be the same as the input
-        assert_eq!(
-            a2l_output.project.module[0].measurement.len(),
-            a2l_input.project.module[0].measurement.len()
-        );
-        assert_eq!(
-            a2l_output.project.module[0].characteristic.len(),
-            a2l_input.project.module[0].characteristic.len()
-        );
-        assert_eq!(
-            a2l_output.project.module[0].group.len(),
-            a2l_input.project.module[0].group.len()
-        );
-        assert_eq!(
-            a2l_output.project.module[0].record_layout.len(),
-            a2l_input.project.module[0].record_layout.len()
-        );
+        let a2l_output = a2lfile::load(&outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.axis_pts.len(), 2);
+        assert_eq!(module.characteristic.len(), 2);
         assert_eq!(
-            a2l_output.project.module[0].compu_method.len(),
-            a2l_input.project.module[0].compu_method.len()
+            module.characteristic[0].axis_descr[0].axis_pts_ref,
+            module.characteristic[1].axis_descr[0].axis_pts_ref
         );
+    }
 
-        // 2. merge on the PROJECT level
-        let outfile = tempdir.join("output2.a2l");
-        assert!(!outfile.exists());
+    #[test]
+    fn test_option_bitfield_def() {
+        // MEASUREMENTs for the bits of an integer variable can be created from a definition file
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let deffile = tempdir.join("bitfields.txt");
+        let outfile = tempdir.join("output.a2l");
+        std::fs::write(
+            &deffile,
+            "Measurement_Value; Ready; 0\nMeasurement_Value; Mode; 1-2; 0=Off, 1=On\n",
+        )
+        .unwrap();
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("--create"),
-            OsString::from("--merge-project"),
             OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--bitfield-def"),
+            OsString::from(deffile),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_input = a2lfile::load(
-            "fixtures/a2l/update_test1.a2l",
-            None,
-            &mut Vec::new(),
-            false,
-        )
-        .unwrap();
         let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        // there should be two MODULEs in the output
-        assert_eq!(a2l_output.project.module.len(), 2);
-        // one of the two MODULEs in the output should be the same as the input file
-        let output_idx = a2l_output
-            .project
-            .module
+        let module = &a2l_output.project.module[0];
+        let ready = module
+            .measurement
             .iter()
-            .position(|m| m.name == a2l_input.project.module[0].name)
+            .find(|meas| meas.name == "Measurement_Value.Ready")
             .unwrap();
-        assert_eq!(
-            a2l_output.project.module[output_idx],
-            a2l_input.project.module[0]
-        );
+        assert_eq!(ready.bit_mask.as_ref().unwrap().mask, 1);
     }
 
     #[test]
-    fn test_option_remove() {
-        // items can be removed by name with --remove
-        let a2l_input = a2lfile::load(
-            "fixtures/a2l/update_test1.a2l",
-            None,
-            &mut Vec::new(),
-            false,
-        )
-        .unwrap();
-        // get the names of the first characteristic and measurement, so they can be removed
-        let characteristic_name = a2l_input.project.module[0].characteristic[0].name.clone();
-        let measurement_name = a2l_input.project.module[0].measurement[0].name.clone();
-
+    fn test_option_datatype_map() {
+        // the float array Characteristic_ValBlk is inserted with the mapped data type ULONG
         let tempdir = tempfile::tempdir().unwrap().into_path();
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/update_test1.a2l"),
-            OsString::from("--remove"),
-            OsString::from(characteristic_name),
-            OsString::from("--remove"),
-            OsString::from(measurement_name),
+            OsString::from("--create"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--characteristic"),
+            OsString::from("Characteristic_ValBlk"),
+            OsString::from("--datatype-map"),
+            OsString::from("FLOAT32_IEEE=ULONG"),
+            OsString::from("--datatype-map-create-cm"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
+
         let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        // the output should have one less characteristic and one less measurement than the input
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.characteristic.len(), 1);
+        assert_eq!(module.characteristic[0].deposit, "__ULONG_Z");
+        assert_eq!(module.characteristic[0].lower_limit, 0.0);
+        assert_eq!(module.characteristic[0].upper_limit, f64::from(u32::MAX));
         assert_eq!(
-            a2l_input.project.module[0].characteristic.len(),
-            a2l_output.project.module[0].characteristic.len() + 1
+            module.record_layout[0]
+                .fnc_values
+                .as_ref()
+                .unwrap()
+                .datatype,
+            DataType::Ulong
         );
+        assert_eq!(module.compu_method.len(), 1);
         assert_eq!(
-            a2l_input.project.module[0].measurement.len(),
-            a2l_output.project.module[0].measurement.len() + 1
+            module.characteristic[0].conversion,
+            module.compu_method[0].name
         );
+
+        // the update renames the RECORD_LAYOUT of an existing CHARACTERISTIC after the mapped data type
+        let infile = tempdir.join("input.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Characteristic_ValBlk "" VAL_BLK 0x0 __FLOAT32_IEEE_Z 0 NO_COMPU_METHOD -1e30 1e30
+      MATRIX_DIM 5
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT __FLOAT32_IEEE_Z
+      FNC_VALUES 1 FLOAT32_IEEE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#,
+        )
+        .unwrap();
+        let outfile = tempdir.join("output2.a2l");
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile.clone()),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--datatype-map"),
+            OsString::from("FLOAT32_IEEE=ULONG"),
+            OsString::from("--output"),
+            OsString::from(outfile.clone()),
+        ];
+        core(args.into_iter()).unwrap();
+
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.characteristic[0].deposit, "__ULONG_Z");
+        assert_eq!(module.characteristic[0].lower_limit, 0.0);
+        assert_eq!(module.record_layout[0].name, "__ULONG_Z");
+        assert_eq!(module.characteristic[0].conversion, "NO_COMPU_METHOD");
+
+        // an invalid mapping is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from(infile.clone()),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--datatype-map"),
+            OsString::from("FLOAT64_IEEE=ULONG"),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
 
     #[test]
-    fn test_option_a2lversion() {
-        // the a2l version can be set with --a2lversion
+    fn test_option_dim() {
+        // the flexible array member of Flexible_Struct gets the number of elements given with --dim
         let tempdir = tempfile::tempdir().unwrap().into_path();
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("--create"),
-            OsString::from("--a2lversion"),
-            OsString::from("1.6.0"),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/flexible_array_test.elf"),
+            OsString::from("--measurement"),
+            OsString::from("Flexible_Struct.data"),
+            OsString::from("--dim"),
+            OsString::from("4"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
+
         let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
-        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().version_no, 1);
-        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().upgrade_no, 60);
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement.len(), 1);
+        assert_eq!(
+            module.measurement[0].matrix_dim.as_ref().unwrap().dim_list,
+            vec![4]
+        );
+    }
 
-        // modify the a2l version of an existing file
-        let outfile2 = tempdir.join("output2.a2l");
+    #[test]
+    fn test_option_format_map() {
+        // the FORMAT of all matching objects is set from the format map
+        let tempdir = tempfile::tempdir().unwrap().into_path();
+        let mapfile = tempdir.join("formats.txt");
+        let outfile = tempdir.join("output.a2l");
+        std::fs::write(&mapfile, "Measurement_Bitfield_.*; %.6\n").unwrap();
         let args = vec![
             OsString::from("a2ltool"),
             OsString::from("fixtures/a2l/update_test1.a2l"),
-            OsString::from("--a2lversion"),
-            OsString::from("1.5.0"),
+            OsString::from("--format-map"),
+            OsString::from(mapfile),
             OsString::from("--output"),
-            OsString::from(outfile2.clone()),
+            OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_output = a2lfile::load(outfile2, None, &mut Vec::new(), false).unwrap();
-        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().version_no, 1);
-        assert_eq!(a2l_output.asap2_version.as_ref().unwrap().upgrade_no, 50);
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        for measurement in &module.measurement {
+            let expected = measurement.name.starts_with("Measurement_Bitfield_");
+            let has_format = measurement
+                .format
+                .as_ref()
+                .is_some_and(|format| format.format_string == "%.6");
+            assert_eq!(has_format, expected);
+        }
+        assert!(module
+            .characteristic
+            .iter()
+            .all(|characteristic| characteristic.format.is_none()));
     }
 
     #[test]
-    fn test_option_merge_includes() {
-        // the content of all included files can be merged with --merge-includes
+    fn test_option_a2ml() {
         let tempdir = tempfile::tempdir().unwrap().into_path();
+        let a2ml_file = tempdir.join("custom.a2ml");
+        std::fs::write(
+            &a2ml_file,
+            r#"/begin A2ML
+  block "IF_DATA" taggedunion if_data {
+    "CUSTOM" struct {
+      uint;
+    };
+  };
+/end A2ML"#,
+        )
+        .unwrap();
+        let infile = tempdir.join("input.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Valid "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      /begin IF_DATA CUSTOM 1
+      /end IF_DATA
+    /end MEASUREMENT
+    /begin MEASUREMENT Invalid "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      /begin IF_DATA CANAPE_EXT 100
+      /end IF_DATA
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT"#,
+        )
+        .unwrap();
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/merge_inc_test.a2l"),
-            OsString::from("--merge-includes"),
+            OsString::from(infile),
+            OsString::from("--a2ml"),
+            OsString::from(a2ml_file),
+            OsString::from("--ifdata-cleanup"),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let output_text = std::fs::read_to_string(outfile).unwrap();
-        // the output file should not contain any /include commands
-        assert!(!output_text.contains("/include"));
+
+        // CANAPE_EXT is valid according to the built-in A2ML, but not according to the custom A2ML
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert_eq!(module.measurement[0].if_data.len(), 1);
+        assert!(module.measurement[1].if_data.is_empty());
+
+        // a missing A2ML file is an error
+        let args = vec![
+            OsString::from("a2ltool"),
+            OsString::from("fixtures/a2l/update_test1.a2l"),
+            OsString::from("--a2ml"),
+            OsString::from(tempdir.join("missing.a2ml")),
+        ];
+        assert!(core(args.into_iter()).is_err());
     }
 
     #[test]
-    fn test_option_sort() {
-        // all items in the file can be sorted with --sort
+    fn test_option_preserved_report() {
         let tempdir = tempfile::tempdir().unwrap().into_path();
+        let infile = tempdir.join("input.a2l");
+        std::fs::write(
+            &infile,
+            r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Measurement_Value "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      /begin ANNOTATION
+        ANNOTATION_LABEL "a2ltool-preserved"
+      /end ANNOTATION
+      ECU_ADDRESS 0
+    /end MEASUREMENT
+    /begin MEASUREMENT Missing_Value "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      ECU_ADDRESS 0x1234
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT
+"#,
+        )
+        .unwrap();
+        let reportfile = tempdir.join("report.csv");
         let outfile = tempdir.join("output.a2l");
-        assert!(!outfile.exists());
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/update_test1.a2l"),
-            OsString::from("--sort"),
+            OsString::from(infile.clone()),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--update-mode"),
+            OsString::from("PRESERVE"),
+            OsString::from("--preserved-report"),
+            OsString::from(reportfile.clone()),
             OsString::from("--output"),
             OsString::from(outfile.clone()),
         ];
         core(args.into_iter()).unwrap();
-        let a2l_input = a2lfile::load(
-            "fixtures/a2l/update_test1.a2l",
-            None,
-            &mut Vec::new(),
-            false,
-        )
-        .unwrap();
-        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
 
-        // Though sorting does not change the meaning of the file, the order of the items in the output is different.
-        // That means the files are not directly equal.
-        assert_ne!(a2l_input, a2l_output);
-        // Comparing the number of items is a reasonable approximation to show that the content remains the same.
-        assert_eq!(
-            a2l_input.project.module[0].measurement.len(),
-            a2l_output.project.module[0].measurement.len()
-        );
-        assert_eq!(
-            a2l_input.project.module[0].characteristic.len(),
-            a2l_output.project.module[0].characteristic.len()
-        );
-        assert_eq!(
-            a2l_input.project.module[0].group.len(),
-            a2l_output.project.module[0].group.len()
-        );
-        assert_eq!(
-            a2l_input.project.module[0].record_layout.len(),
-            a2l_output.project.module[0].record_layout.len()
-        );
-        assert_eq!(
-            a2l_input.project.module[0].compu_method.len(),
-            a2l_output.project.module[0].compu_method.len()
-        );
+        let report = std::fs::read_to_string(reportfile).unwrap();
+        assert!(report.contains("MEASUREMENT,Missing_Value,"));
+        assert!(!report.contains("Measurement_Value"));
+
+        // the found MEASUREMENT loses its old annotation, while the missing one is annotated
+        let a2l_output = a2lfile::load(outfile, None, &mut Vec::new(), false).unwrap();
+        let module = &a2l_output.project.module[0];
+        assert!(module.measurement[0].annotation.is_empty());
+        let annotation = &module.measurement[1].annotation[0];
         assert_eq!(
-            a2l_input.project.module[0].instance.len(),
-            a2l_output.project.module[0].instance.len()
+            annotation.annotation_label.as_ref().unwrap().label,
+            "a2ltool-preserved"
         );
-    }
 
-    #[test]
-    fn test_option_xcp() {
-        // the XCP settings in the file can be displayed with --show-xcp
+        // the report can only be created in PRESERVE mode
         let args = vec![
             OsString::from("a2ltool"),
-            OsString::from("fixtures/a2l/xcp_test.a2l"),
-            OsString::from("--show-xcp"),
+            OsString::from(infile),
+            OsString::from("--elffile"),
+            OsString::from("fixtures/bin/update_test.elf"),
+            OsString::from("--update"),
+            OsString::from("--update-mode"),
+            OsString::from("STRICT"),
+            OsString::from("--preserved-report"),
+            OsString::from(tempdir.join("report2.csv")),
         ];
-        // Passing the option --show-xcp should neither panic nor return an error
-        // The option only prints some information, so it is not possisble to check the output
-        core(args.into_iter()).unwrap();
+        assert!(core(args.into_iter()).is_err());
     }
 }
@@ -0,0 +1,948 @@
+use a2lfile::{AxisPts, Characteristic, Measurement, Module};
+use std::collections::{HashMap, HashSet};
+
+// which value is used if the same field of two items with the same name differs, see --merge-prefer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergePrefer {
+    // keep the value of the input file
+    Input,
+    // use the value of the merged file
+    Merge,
+}
+
+// the block types that can be selected for a selective merge
+const MERGE_BLOCK_TYPES: [&str; 20] = [
+    "AXIS_PTS",
+    "BLOB",
+    "CHARACTERISTIC",
+    "COMPU_METHOD",
+    "COMPU_TAB",
+    "COMPU_VTAB",
+    "COMPU_VTAB_RANGE",
+    "FRAME",
+    "FUNCTION",
+    "GROUP",
+    "INSTANCE",
+    "MEASUREMENT",
+    "RECORD_LAYOUT",
+    "TRANSFORMER",
+    "TYPEDEF_AXIS",
+    "TYPEDEF_BLOB",
+    "TYPEDEF_CHARACTERISTIC",
+    "TYPEDEF_MEASUREMENT",
+    "TYPEDEF_STRUCTURE",
+    "UNIT",
+];
+
+// move all items of the listed block types from the source module to the target module, if they are selected
+// Items whose name already exists in the target are not merged; a message is logged if the items differ.
+macro_rules! merge_blocks {
+    ($target:expr, $source:expr, $selected:expr, $imported:expr, $log_msgs:expr, $($field:ident: $blocktype:literal),+ $(,)?) => {
+        $(
+            if $selected.contains($blocktype) {
+                for item in std::mem::take(&mut $source.$field) {
+                    if let Some(existing) = $target.$field.iter().find(|ex| ex.name == item.name) {
+                        if *existing != item {
+                            $log_msgs.push(format!(
+                                "{} {} was not merged, because a different {} with the same name already exists",
+                                $blocktype, item.name, $blocktype
+                            ));
+                        }
+                    } else {
+                        $imported
+                            .entry($blocktype)
+                            .or_default()
+                            .insert(item.name.clone());
+                        $target.$field.push(item);
+                    }
+                }
+            }
+        )+
+    };
+}
+
+// take all items of the listed block types that exist with different content in both modules out of the source
+// module, so that the following merge leaves them alone
+macro_rules! take_conflicting_items {
+    ($target:expr, $source:expr, $is_selected:expr, $conflicts:expr, $($field:ident: $blocktype:literal),+ $(,)?) => {
+        $(
+            if $is_selected($blocktype) {
+                let (conflicting, remaining) = std::mem::take(&mut $source.$field)
+                    .into_iter()
+                    .partition(|item| {
+                        $target.$field
+                            .iter()
+                            .any(|existing| existing.name == item.name && *existing != *item)
+                    });
+                $conflicts.$field = conflicting;
+                $source.$field = remaining;
+            }
+        )+
+    };
+}
+
+// merge the fields of the conflicting items of the listed block types into the items of the target module
+macro_rules! merge_conflicting_fields {
+    ($target:expr, $conflicts:expr, $prefer:expr, $count:expr, $log_msgs:expr, $($field:ident: $blocktype:literal => $merge_fn:expr),+ $(,)?) => {
+        $(
+            for item in $conflicts.$field {
+                if let Some(existing) = $target.$field.iter_mut().find(|ex| ex.name == item.name) {
+                    let name = item.name.clone();
+                    $merge_fn(existing, item, $prefer);
+                    $count += 1;
+                    $log_msgs.push(format!(
+                        "{} {} exists in both files; the fields were merged, conflicting values were taken from the {} file",
+                        $blocktype, name, if $prefer == MergePrefer::Input { "input" } else { "merged" }
+                    ));
+                }
+            }
+        )+
+    };
+}
+
+// MEASUREMENTs, CHARACTERISTICs and AXIS_PTS that exist with different content in the input and in the merged file,
+// see --merge-prefer. They are set aside before the merge, and their fields are merged after it.
+#[derive(Debug, Default)]
+pub(crate) struct ConflictingItems {
+    target_idx: usize,
+    axis_pts: Vec<AxisPts>,
+    characteristic: Vec<Characteristic>,
+    measurement: Vec<Measurement>,
+    // the names of all items in both modules before the merge, for each block type that the items can reference
+    known_names: HashMap<&'static str, HashSet<String>>,
+}
+
+// parse a comma separated list of block types, e.g. "COMPU_METHOD,RECORD_LAYOUT"
+pub(crate) fn parse_block_types(text: &str) -> Result<HashSet<&'static str>, String> {
+    let mut block_types = HashSet::new();
+    for item in text.split(',') {
+        let item = item.trim().to_ascii_uppercase();
+        if item.is_empty() {
+            continue;
+        }
+        let Some(block_type) = MERGE_BLOCK_TYPES.iter().find(|bt| **bt == item) else {
+            return Err(format!(
+                "\"{item}\" is not a block type that can be merged. Valid types are: {}",
+                MERGE_BLOCK_TYPES.join(", ")
+            ));
+        };
+        block_types.insert(*block_type);
+    }
+    Ok(block_types)
+}
+
+// get the complement of a set of block types, for --merge-exclude
+pub(crate) fn invert_block_types(block_types: &HashSet<&'static str>) -> HashSet<&'static str> {
+    MERGE_BLOCK_TYPES
+        .iter()
+        .filter(|bt| !block_types.contains(*bt))
+        .copied()
+        .collect()
+}
+
+// Merge only the items of the selected block types from the source module into the target module.
+// Unlike Module::merge, items with conflicting names are never renamed; the existing item in the target is kept.
+// References from the imported items to items that don't exist in the target module are reported.
+// Returns the number of items that were imported.
+pub(crate) fn merge_selected(
+    target: &mut Module,
+    source: &mut Module,
+    selected: &HashSet<&'static str>,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut imported: HashMap<&'static str, HashSet<String>> = HashMap::new();
+
+    merge_blocks!(
+        target, source, selected, imported, log_msgs,
+        axis_pts: "AXIS_PTS",
+        blob: "BLOB",
+        characteristic: "CHARACTERISTIC",
+        compu_method: "COMPU_METHOD",
+        compu_tab: "COMPU_TAB",
+        compu_vtab: "COMPU_VTAB",
+        compu_vtab_range: "COMPU_VTAB_RANGE",
+        frame: "FRAME",
+        function: "FUNCTION",
+        group: "GROUP",
+        instance: "INSTANCE",
+        measurement: "MEASUREMENT",
+        record_layout: "RECORD_LAYOUT",
+        transformer: "TRANSFORMER",
+        typedef_axis: "TYPEDEF_AXIS",
+        typedef_blob: "TYPEDEF_BLOB",
+        typedef_characteristic: "TYPEDEF_CHARACTERISTIC",
+        typedef_measurement: "TYPEDEF_MEASUREMENT",
+        typedef_structure: "TYPEDEF_STRUCTURE",
+        unit: "UNIT",
+    );
+
+    check_references(target, &imported, log_msgs);
+
+    imported.values().map(HashSet::len).sum()
+}
+
+// Merge the selected block types of all source modules into the target modules.
+// Returns the number of items that were imported.
+pub(crate) fn merge_modules_selected(
+    target_modules: &mut [Module],
+    source_modules: &mut [Module],
+    selected: &HashSet<&'static str>,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut count = 0;
+    for source in source_modules {
+        let idx = find_target_module(target_modules, source);
+        count += merge_selected(&mut target_modules[idx], source, selected, log_msgs);
+    }
+    count
+}
+
+// Take the items that need a field-level merge out of the source module before the actual merge, see --merge-prefer.
+// MEASUREMENTs, CHARACTERISTICs and AXIS_PTS that exist with different content in both modules are removed from the
+// source module. All other items are left to the following merge.
+// If selected is given, only the selected block types are considered.
+pub(crate) fn take_conflicting_items(
+    target: &Module,
+    source: &mut Module,
+    selected: Option<&HashSet<&'static str>>,
+) -> ConflictingItems {
+    let is_selected = |blocktype: &str| match selected {
+        Some(selected) => selected.contains(blocktype),
+        None => true,
+    };
+    let mut conflicts = ConflictingItems::default();
+
+    take_conflicting_items!(
+        target, source, is_selected, conflicts,
+        axis_pts: "AXIS_PTS",
+        characteristic: "CHARACTERISTIC",
+        measurement: "MEASUREMENT",
+    );
+
+    // the items that are referenced by the conflicting items can be renamed by the merge
+    for module in [target, &*source] {
+        let known_names = &mut conflicts.known_names;
+        add_known_names(
+            known_names,
+            "AXIS_PTS",
+            module.axis_pts.iter().map(|item| &item.name),
+        );
+        add_known_names(
+            known_names,
+            "CHARACTERISTIC",
+            module.characteristic.iter().map(|item| &item.name),
+        );
+        add_known_names(
+            known_names,
+            "COMPU_METHOD",
+            module.compu_method.iter().map(|item| &item.name),
+        );
+        add_known_names(
+            known_names,
+            "MEASUREMENT",
+            module.measurement.iter().map(|item| &item.name),
+        );
+        add_known_names(
+            known_names,
+            "RECORD_LAYOUT",
+            module.record_layout.iter().map(|item| &item.name),
+        );
+    }
+
+    conflicts
+}
+
+// Merge the fields of the conflicting items into the items of the target module after the merge.
+// Module::merge renames incoming items whose name already exists with different content, and it updates the
+// references in the source module. The conflicting items were not part of the source module at that time,
+// so their references are updated here. Returns the number of merged items.
+pub(crate) fn merge_conflicting_items(
+    target: &mut Module,
+    mut conflicts: ConflictingItems,
+    prefer: MergePrefer,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut count = 0;
+
+    let known = |blocktype: &str| conflicts.known_names.get(blocktype);
+    let axis_pts_table = find_renamed_items(
+        target.axis_pts.iter().map(|item| &item.name),
+        known("AXIS_PTS"),
+    );
+    let characteristic_table = find_renamed_items(
+        target.characteristic.iter().map(|item| &item.name),
+        known("CHARACTERISTIC"),
+    );
+    let compu_method_table = find_renamed_items(
+        target.compu_method.iter().map(|item| &item.name),
+        known("COMPU_METHOD"),
+    );
+    let measurement_table = find_renamed_items(
+        target.measurement.iter().map(|item| &item.name),
+        known("MEASUREMENT"),
+    );
+    let record_layout_table = find_renamed_items(
+        target.record_layout.iter().map(|item| &item.name),
+        known("RECORD_LAYOUT"),
+    );
+
+    for item in &mut conflicts.axis_pts {
+        rename_reference(&mut item.conversion, &compu_method_table);
+        rename_reference(&mut item.deposit_record, &record_layout_table);
+        rename_reference(&mut item.input_quantity, &measurement_table);
+    }
+    for item in &mut conflicts.characteristic {
+        rename_reference(&mut item.conversion, &compu_method_table);
+        rename_reference(&mut item.deposit, &record_layout_table);
+        for axis_descr in &mut item.axis_descr {
+            rename_reference(&mut axis_descr.conversion, &compu_method_table);
+            rename_reference(&mut axis_descr.input_quantity, &measurement_table);
+            if let Some(axis_pts_ref) = &mut axis_descr.axis_pts_ref {
+                rename_reference(&mut axis_pts_ref.axis_points, &axis_pts_table);
+            }
+            if let Some(curve_axis_ref) = &mut axis_descr.curve_axis_ref {
+                rename_reference(&mut curve_axis_ref.curve_axis, &characteristic_table);
+            }
+        }
+    }
+    for item in &mut conflicts.measurement {
+        rename_reference(&mut item.conversion, &compu_method_table);
+    }
+
+    merge_conflicting_fields!(
+        target, conflicts, prefer, count, log_msgs,
+        axis_pts: "AXIS_PTS" => merge_axis_pts_fields,
+        characteristic: "CHARACTERISTIC" => merge_characteristic_fields,
+        measurement: "MEASUREMENT" => merge_measurement_fields,
+    );
+
+    count
+}
+
+// take the conflicting items out of all source modules, see take_conflicting_items
+pub(crate) fn take_modules_conflicting_items(
+    target_modules: &[Module],
+    source_modules: &mut [Module],
+    selected: Option<&HashSet<&'static str>>,
+) -> Vec<ConflictingItems> {
+    source_modules
+        .iter_mut()
+        .map(|source| {
+            let target_idx = find_target_module(target_modules, source);
+            ConflictingItems {
+                target_idx,
+                ..take_conflicting_items(&target_modules[target_idx], source, selected)
+            }
+        })
+        .collect()
+}
+
+// merge the fields of the conflicting items of all source modules, see merge_conflicting_items
+pub(crate) fn merge_modules_conflicting_items(
+    target_modules: &mut [Module],
+    conflicts: Vec<ConflictingItems>,
+    prefer: MergePrefer,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    conflicts
+        .into_iter()
+        .map(|conflicts| {
+            let idx = conflicts.target_idx;
+            merge_conflicting_items(&mut target_modules[idx], conflicts, prefer, log_msgs)
+        })
+        .sum()
+}
+
+// Module::merge gives an incoming item the new name "<name>.MERGE" or "<name>.MERGE<n>" if an item with the
+// same name but different content exists. The new name is the one that did not exist in either module before.
+fn find_renamed_items<'a>(
+    names: impl Iterator<Item = &'a String>,
+    known_names: Option<&HashSet<String>>,
+) -> HashMap<String, String> {
+    names
+        .filter(|name| !known_names.is_some_and(|known| known.contains(*name)))
+        .filter_map(|name| {
+            let (orig_name, suffix) = name.rsplit_once(".MERGE")?;
+            suffix
+                .chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| (orig_name.to_string(), name.clone()))
+        })
+        .collect()
+}
+
+fn add_known_names<'a>(
+    known_names: &mut HashMap<&'static str, HashSet<String>>,
+    blocktype: &'static str,
+    names: impl Iterator<Item = &'a String>,
+) {
+    known_names
+        .entry(blocktype)
+        .or_default()
+        .extend(names.cloned());
+}
+
+fn rename_reference(name: &mut String, rename_table: &HashMap<String, String>) {
+    if let Some(newname) = rename_table.get(name) {
+        newname.clone_into(name);
+    }
+}
+
+// Each source module is merged into the target module with the same name, or into the first target module
+// if no module has the same name
+fn find_target_module(target_modules: &[Module], source: &Module) -> usize {
+    target_modules
+        .iter()
+        .position(|module| module.name == source.name)
+        .unwrap_or(0)
+}
+
+// a mandatory field: if the values differ, the preferred one is used
+fn merge_value<T: PartialEq>(existing: &mut T, incoming: T, prefer: MergePrefer) {
+    if prefer == MergePrefer::Merge && *existing != incoming {
+        *existing = incoming;
+    }
+}
+
+// an optional field: a value that is only present in one of the items is always used,
+// and if both items have different values the preferred one is used
+fn merge_option<T: PartialEq>(existing: &mut Option<T>, incoming: Option<T>, prefer: MergePrefer) {
+    if existing.is_none() {
+        *existing = incoming;
+    } else if incoming.is_some() {
+        merge_value(existing, incoming, prefer);
+    }
+}
+
+fn merge_measurement_fields(existing: &mut Measurement, item: Measurement, prefer: MergePrefer) {
+    merge_value(&mut existing.long_identifier, item.long_identifier, prefer);
+    merge_value(&mut existing.datatype, item.datatype, prefer);
+    merge_value(&mut existing.conversion, item.conversion, prefer);
+    merge_value(&mut existing.resolution, item.resolution, prefer);
+    merge_value(&mut existing.accuracy, item.accuracy, prefer);
+    merge_value(&mut existing.lower_limit, item.lower_limit, prefer);
+    merge_value(&mut existing.upper_limit, item.upper_limit, prefer);
+    merge_option(&mut existing.address_type, item.address_type, prefer);
+    merge_option(&mut existing.array_size, item.array_size, prefer);
+    merge_option(&mut existing.bit_mask, item.bit_mask, prefer);
+    merge_option(&mut existing.byte_order, item.byte_order, prefer);
+    merge_option(&mut existing.discrete, item.discrete, prefer);
+    merge_option(&mut existing.ecu_address, item.ecu_address, prefer);
+    merge_option(
+        &mut existing.ecu_address_extension,
+        item.ecu_address_extension,
+        prefer,
+    );
+    merge_option(&mut existing.format, item.format, prefer);
+    merge_option(&mut existing.matrix_dim, item.matrix_dim, prefer);
+    merge_option(&mut existing.phys_unit, item.phys_unit, prefer);
+    merge_option(&mut existing.read_write, item.read_write, prefer);
+    merge_option(&mut existing.symbol_link, item.symbol_link, prefer);
+}
+
+fn merge_characteristic_fields(
+    existing: &mut Characteristic,
+    item: Characteristic,
+    prefer: MergePrefer,
+) {
+    merge_value(&mut existing.long_identifier, item.long_identifier, prefer);
+    merge_value(
+        &mut existing.characteristic_type,
+        item.characteristic_type,
+        prefer,
+    );
+    merge_value(&mut existing.address, item.address, prefer);
+    merge_value(&mut existing.deposit, item.deposit, prefer);
+    merge_value(&mut existing.max_diff, item.max_diff, prefer);
+    merge_value(&mut existing.conversion, item.conversion, prefer);
+    merge_value(&mut existing.lower_limit, item.lower_limit, prefer);
+    merge_value(&mut existing.upper_limit, item.upper_limit, prefer);
+    merge_value(&mut existing.axis_descr, item.axis_descr, prefer);
+    merge_option(&mut existing.bit_mask, item.bit_mask, prefer);
+    merge_option(&mut existing.byte_order, item.byte_order, prefer);
+    merge_option(&mut existing.discrete, item.discrete, prefer);
+    merge_option(
+        &mut existing.ecu_address_extension,
+        item.ecu_address_extension,
+        prefer,
+    );
+    merge_option(&mut existing.encoding, item.encoding, prefer);
+    merge_option(&mut existing.extended_limits, item.extended_limits, prefer);
+    merge_option(&mut existing.format, item.format, prefer);
+    merge_option(&mut existing.matrix_dim, item.matrix_dim, prefer);
+    merge_option(&mut existing.number, item.number, prefer);
+    merge_option(&mut existing.phys_unit, item.phys_unit, prefer);
+    merge_option(&mut existing.read_only, item.read_only, prefer);
+    merge_option(&mut existing.symbol_link, item.symbol_link, prefer);
+}
+
+fn merge_axis_pts_fields(existing: &mut AxisPts, item: AxisPts, prefer: MergePrefer) {
+    merge_value(&mut existing.long_identifier, item.long_identifier, prefer);
+    merge_value(&mut existing.address, item.address, prefer);
+    merge_value(&mut existing.input_quantity, item.input_quantity, prefer);
+    merge_value(&mut existing.deposit_record, item.deposit_record, prefer);
+    merge_value(&mut existing.max_diff, item.max_diff, prefer);
+    merge_value(&mut existing.conversion, item.conversion, prefer);
+    merge_value(&mut existing.max_axis_points, item.max_axis_points, prefer);
+    merge_value(&mut existing.lower_limit, item.lower_limit, prefer);
+    merge_value(&mut existing.upper_limit, item.upper_limit, prefer);
+    merge_option(&mut existing.byte_order, item.byte_order, prefer);
+    merge_option(
+        &mut existing.ecu_address_extension,
+        item.ecu_address_extension,
+        prefer,
+    );
+    merge_option(&mut existing.extended_limits, item.extended_limits, prefer);
+    merge_option(&mut existing.format, item.format, prefer);
+    merge_option(&mut existing.monotony, item.monotony, prefer);
+    merge_option(&mut existing.phys_unit, item.phys_unit, prefer);
+    merge_option(&mut existing.read_only, item.read_only, prefer);
+    merge_option(&mut existing.symbol_link, item.symbol_link, prefer);
+}
+
+// report all references from the imported items to items that do not exist in the module
+fn check_references(
+    module: &Module,
+    imported: &HashMap<&'static str, HashSet<String>>,
+    log_msgs: &mut Vec<String>,
+) {
+    let is_imported = |blocktype: &str, name: &str| {
+        imported
+            .get(blocktype)
+            .is_some_and(|names| names.contains(name))
+    };
+    let measurements: HashSet<&String> = module.measurement.iter().map(|item| &item.name).collect();
+    let axis_pts: HashSet<&String> = module.axis_pts.iter().map(|item| &item.name).collect();
+    let record_layouts: HashSet<&String> =
+        module.record_layout.iter().map(|item| &item.name).collect();
+    let compu_methods: HashSet<&String> =
+        module.compu_method.iter().map(|item| &item.name).collect();
+    let units: HashSet<&String> = module.unit.iter().map(|item| &item.name).collect();
+    let conversion_tables: HashSet<&String> = module
+        .compu_tab
+        .iter()
+        .map(|item| &item.name)
+        .chain(module.compu_vtab.iter().map(|item| &item.name))
+        .chain(module.compu_vtab_range.iter().map(|item| &item.name))
+        .collect();
+    let typedefs: HashSet<&String> = module
+        .typedef_axis
+        .iter()
+        .map(|item| &item.name)
+        .chain(module.typedef_blob.iter().map(|item| &item.name))
+        .chain(module.typedef_characteristic.iter().map(|item| &item.name))
+        .chain(module.typedef_measurement.iter().map(|item| &item.name))
+        .chain(module.typedef_structure.iter().map(|item| &item.name))
+        .collect();
+
+    let mut report = |blocktype: &str, name: &str, reftype: &str, refname: &str| {
+        log_msgs.push(format!(
+            "The merged {blocktype} {name} references the {reftype} {refname}, which does not exist"
+        ));
+    };
+    let conversion_missing = |conversion: &String| {
+        conversion != "NO_COMPU_METHOD" && !compu_methods.contains(conversion)
+    };
+    let input_missing =
+        |input: &String| input != "NO_INPUT_QUANTITY" && !measurements.contains(input);
+
+    for item in &module.characteristic {
+        if !is_imported("CHARACTERISTIC", &item.name) {
+            continue;
+        }
+        if !record_layouts.contains(&item.deposit) {
+            report("CHARACTERISTIC", &item.name, "RECORD_LAYOUT", &item.deposit);
+        }
+        if conversion_missing(&item.conversion) {
+            report(
+                "CHARACTERISTIC",
+                &item.name,
+                "COMPU_METHOD",
+                &item.conversion,
+            );
+        }
+        for axis_descr in &item.axis_descr {
+            if conversion_missing(&axis_descr.conversion) {
+                report(
+                    "CHARACTERISTIC",
+                    &item.name,
+                    "COMPU_METHOD",
+                    &axis_descr.conversion,
+                );
+            }
+            if input_missing(&axis_descr.input_quantity) {
+                report(
+                    "CHARACTERISTIC",
+                    &item.name,
+                    "MEASUREMENT",
+                    &axis_descr.input_quantity,
+                );
+            }
+            if let Some(axis_pts_ref) = &axis_descr.axis_pts_ref {
+                if !axis_pts.contains(&axis_pts_ref.axis_points) {
+                    report(
+                        "CHARACTERISTIC",
+                        &item.name,
+                        "AXIS_PTS",
+                        &axis_pts_ref.axis_points,
+                    );
+                }
+            }
+        }
+    }
+
+    for item in &module.axis_pts {
+        if !is_imported("AXIS_PTS", &item.name) {
+            continue;
+        }
+        if !record_layouts.contains(&item.deposit_record) {
+            report(
+                "AXIS_PTS",
+                &item.name,
+                "RECORD_LAYOUT",
+                &item.deposit_record,
+            );
+        }
+        if conversion_missing(&item.conversion) {
+            report("AXIS_PTS", &item.name, "COMPU_METHOD", &item.conversion);
+        }
+        if input_missing(&item.input_quantity) {
+            report("AXIS_PTS", &item.name, "MEASUREMENT", &item.input_quantity);
+        }
+    }
+
+    for item in &module.measurement {
+        if is_imported("MEASUREMENT", &item.name) && conversion_missing(&item.conversion) {
+            report("MEASUREMENT", &item.name, "COMPU_METHOD", &item.conversion);
+        }
+    }
+
+    for item in &module.compu_method {
+        if !is_imported("COMPU_METHOD", &item.name) {
+            continue;
+        }
+        if let Some(compu_tab_ref) = &item.compu_tab_ref {
+            if !conversion_tables.contains(&compu_tab_ref.conversion_table) {
+                report(
+                    "COMPU_METHOD",
+                    &item.name,
+                    "conversion table",
+                    &compu_tab_ref.conversion_table,
+                );
+            }
+        }
+        if let Some(status_string_ref) = &item.status_string_ref {
+            if !conversion_tables.contains(&status_string_ref.conversion_table) {
+                report(
+                    "COMPU_METHOD",
+                    &item.name,
+                    "conversion table",
+                    &status_string_ref.conversion_table,
+                );
+            }
+        }
+        if let Some(ref_unit) = &item.ref_unit {
+            if !units.contains(&ref_unit.unit) {
+                report("COMPU_METHOD", &item.name, "UNIT", &ref_unit.unit);
+            }
+        }
+    }
+
+    for item in &module.typedef_characteristic {
+        if !is_imported("TYPEDEF_CHARACTERISTIC", &item.name) {
+            continue;
+        }
+        if !record_layouts.contains(&item.record_layout) {
+            report(
+                "TYPEDEF_CHARACTERISTIC",
+                &item.name,
+                "RECORD_LAYOUT",
+                &item.record_layout,
+            );
+        }
+        if conversion_missing(&item.conversion) {
+            report(
+                "TYPEDEF_CHARACTERISTIC",
+                &item.name,
+                "COMPU_METHOD",
+                &item.conversion,
+            );
+        }
+    }
+
+    for item in &module.typedef_measurement {
+        if is_imported("TYPEDEF_MEASUREMENT", &item.name) && conversion_missing(&item.conversion) {
+            report(
+                "TYPEDEF_MEASUREMENT",
+                &item.name,
+                "COMPU_METHOD",
+                &item.conversion,
+            );
+        }
+    }
+
+    for item in &module.typedef_structure {
+        if !is_imported("TYPEDEF_STRUCTURE", &item.name) {
+            continue;
+        }
+        for component in &item.structure_component {
+            if !typedefs.contains(&component.component_type) {
+                report(
+                    "TYPEDEF_STRUCTURE",
+                    &item.name,
+                    "TYPEDEF",
+                    &component.component_type,
+                );
+            }
+        }
+    }
+
+    for item in &module.instance {
+        if is_imported("INSTANCE", &item.name) && !typedefs.contains(&item.type_ref) {
+            report("INSTANCE", &item.name, "TYPEDEF", &item.type_ref);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static TARGET_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD CM_Shared "" IDENTICAL "%4.2" "unit"
+    /end COMPU_METHOD
+    /begin MEASUREMENT Target_Measurement "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT
+"#;
+
+    static SOURCE_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Source_Characteristic "" VALUE 0x1000 RL_Source 0 CM_Source 0 100
+    /end CHARACTERISTIC
+    /begin COMPU_METHOD CM_Shared "" IDENTICAL "%4.2" "other unit"
+    /end COMPU_METHOD
+    /begin COMPU_METHOD CM_Source "" TAB_VERB "%4.2" ""
+      COMPU_TAB_REF VT_Source
+    /end COMPU_METHOD
+    /begin COMPU_VTAB VT_Source "" TAB_VERB 1
+      0 "zero"
+    /end COMPU_VTAB
+    /begin MEASUREMENT Source_Measurement "" UBYTE CM_Source 0 0 0 255
+    /end MEASUREMENT
+    /begin RECORD_LAYOUT RL_Source
+      FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT
+"#;
+
+    static PREFER_TARGET_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD CM_Shared "" IDENTICAL "%4.2" "unit"
+    /end COMPU_METHOD
+    /begin MEASUREMENT Shared_Measurement "input" UBYTE NO_COMPU_METHOD 0 0 0 100
+      FORMAT "%5.1"
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT
+"#;
+
+    static PREFER_SOURCE_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin COMPU_METHOD CM_Shared "" IDENTICAL "%4.2" "other unit"
+    /end COMPU_METHOD
+    /begin MEASUREMENT Shared_Measurement "merge" UBYTE NO_COMPU_METHOD 0 0 10 50
+      PHYS_UNIT "rpm"
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT
+"#;
+
+    static MULTI_MODULE_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE first ""
+  /end MODULE
+  /begin MODULE second ""
+  /end MODULE
+/end PROJECT
+"#;
+
+    static MULTI_MODULE_SOURCE_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE second ""
+    /begin UNIT U_Second "" "s" DERIVED
+    /end UNIT
+  /end MODULE
+  /begin MODULE other ""
+    /begin UNIT U_Other "" "o" DERIVED
+    /end UNIT
+  /end MODULE
+/end PROJECT
+"#;
+
+    fn load(text: &str) -> Module {
+        let mut a2l_file = a2lfile::load_from_string(text, None, &mut Vec::new(), false).unwrap();
+        a2l_file.project.module.remove(0)
+    }
+
+    #[test]
+    fn test_parse_block_types() {
+        let block_types = parse_block_types("compu_method, RECORD_LAYOUT,").unwrap();
+        assert_eq!(block_types.len(), 2);
+        assert!(block_types.contains("COMPU_METHOD"));
+        assert!(block_types.contains("RECORD_LAYOUT"));
+        assert!(parse_block_types("COMPU_METHOD,IF_DATA").is_err());
+
+        let inverted = invert_block_types(&block_types);
+        assert_eq!(inverted.len(), MERGE_BLOCK_TYPES.len() - 2);
+        assert!(!inverted.contains("COMPU_METHOD"));
+    }
+
+    #[test]
+    fn test_merge_selected() {
+        // only the conversions are merged: the COMPU_VTAB referenced by CM_Source is missing
+        let mut target = load(TARGET_TEXT);
+        let mut source = load(SOURCE_TEXT);
+        let mut log_msgs = Vec::new();
+        let selected = parse_block_types("COMPU_METHOD,RECORD_LAYOUT").unwrap();
+        let count = merge_selected(&mut target, &mut source, &selected, &mut log_msgs);
+        assert_eq!(count, 2);
+        assert!(target.measurement.len() == 1);
+        assert!(target.characteristic.is_empty());
+        assert_eq!(target.compu_method.len(), 2);
+        // the existing CM_Shared was kept
+        assert_eq!(target.compu_method[0].unit, "unit");
+        assert_eq!(log_msgs.len(), 2);
+        assert!(log_msgs[0].contains("COMPU_METHOD CM_Shared was not merged"));
+        assert!(log_msgs[1].contains("references the conversion table VT_Source"));
+
+        // everything except the RECORD_LAYOUTs and COMPU_METHODs is merged
+        let mut target = load(TARGET_TEXT);
+        let mut source = load(SOURCE_TEXT);
+        let mut log_msgs = Vec::new();
+        let selected = invert_block_types(&selected);
+        let count = merge_selected(&mut target, &mut source, &selected, &mut log_msgs);
+        assert_eq!(count, 3);
+        assert_eq!(target.measurement.len(), 2);
+        assert_eq!(target.characteristic.len(), 1);
+        assert_eq!(target.compu_vtab.len(), 1);
+        assert!(log_msgs.iter().any(|msg| msg
+            == "The merged CHARACTERISTIC Source_Characteristic references the RECORD_LAYOUT RL_Source, which does not exist"));
+        assert!(log_msgs.iter().any(|msg| msg
+            == "The merged CHARACTERISTIC Source_Characteristic references the COMPU_METHOD CM_Source, which does not exist"));
+        assert!(log_msgs.iter().any(|msg| msg
+            == "The merged MEASUREMENT Source_Measurement references the COMPU_METHOD CM_Source, which does not exist"));
+    }
+
+    #[test]
+    fn test_merge_fields() {
+        // the conflicting values of the input file are kept, but missing fields are added
+        let mut target = load(PREFER_TARGET_TEXT);
+        let mut source = load(PREFER_SOURCE_TEXT);
+        let mut log_msgs = Vec::new();
+        let conflicts = take_conflicting_items(&target, &mut source, None);
+        assert!(source.measurement.is_empty());
+        // items without a field-level merge are left for the merge
+        assert_eq!(source.compu_method.len(), 1);
+        target.merge(&mut source);
+        let count =
+            merge_conflicting_items(&mut target, conflicts, MergePrefer::Input, &mut log_msgs);
+        assert_eq!(count, 1);
+        assert_eq!(target.measurement.len(), 1);
+        let measurement = &target.measurement[0];
+        assert_eq!(measurement.long_identifier, "input");
+        assert_eq!(measurement.lower_limit, 0.0);
+        assert_eq!(measurement.upper_limit, 100.0);
+        assert_eq!(measurement.format.as_ref().unwrap().format_string, "%5.1");
+        assert_eq!(measurement.phys_unit.as_ref().unwrap().unit, "rpm");
+        assert_eq!(log_msgs.len(), 1);
+
+        // the conflicting values of the merged file are used
+        let mut target = load(PREFER_TARGET_TEXT);
+        let mut source = load(PREFER_SOURCE_TEXT);
+        let mut log_msgs = Vec::new();
+        let conflicts = take_conflicting_items(&target, &mut source, None);
+        target.merge(&mut source);
+        merge_conflicting_items(&mut target, conflicts, MergePrefer::Merge, &mut log_msgs);
+        let measurement = &target.measurement[0];
+        assert_eq!(measurement.long_identifier, "merge");
+        assert_eq!(measurement.lower_limit, 10.0);
+        assert_eq!(measurement.upper_limit, 50.0);
+        assert_eq!(measurement.format.as_ref().unwrap().format_string, "%5.1");
+        assert_eq!(measurement.phys_unit.as_ref().unwrap().unit, "rpm");
+
+        // block types that are not selected are not touched
+        let target = load(PREFER_TARGET_TEXT);
+        let mut source = load(PREFER_SOURCE_TEXT);
+        let selected = parse_block_types("COMPU_METHOD").unwrap();
+        let conflicts = take_conflicting_items(&target, &mut source, Some(&selected));
+        assert!(conflicts.measurement.is_empty());
+        assert_eq!(source.measurement.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_fields_renamed_reference() {
+        // the preferred MEASUREMENT references a COMPU_METHOD that exists with different content in both files
+        let target_text = PREFER_TARGET_TEXT.replace(
+            "Shared_Measurement \"input\" UBYTE NO_COMPU_METHOD",
+            "Shared_Measurement \"input\" UBYTE CM_Shared",
+        );
+        let source_text = PREFER_SOURCE_TEXT.replace(
+            "Shared_Measurement \"merge\" UBYTE NO_COMPU_METHOD",
+            "Shared_Measurement \"merge\" UBYTE CM_Shared",
+        );
+        let mut target = load(&target_text);
+        let mut source = load(&source_text);
+        let mut log_msgs = Vec::new();
+        let conflicts = take_conflicting_items(&target, &mut source, None);
+        target.merge(&mut source);
+        merge_conflicting_items(&mut target, conflicts, MergePrefer::Merge, &mut log_msgs);
+        // Module::merge renamed the incoming COMPU_METHOD, and the merged MEASUREMENT references it
+        let measurement = &target.measurement[0];
+        assert_eq!(measurement.conversion, "CM_Shared.MERGE");
+        let compu_method = target
+            .compu_method
+            .iter()
+            .find(|item| item.name == measurement.conversion)
+            .unwrap();
+        assert_eq!(compu_method.unit, "other unit");
+
+        // the input file is preferred, so the MEASUREMENT keeps the COMPU_METHOD of the input file
+        let mut target = load(&target_text);
+        let mut source = load(&source_text);
+        let conflicts = take_conflicting_items(&target, &mut source, None);
+        target.merge(&mut source);
+        merge_conflicting_items(&mut target, conflicts, MergePrefer::Input, &mut log_msgs);
+        assert_eq!(target.measurement[0].conversion, "CM_Shared");
+    }
+
+    #[test]
+    fn test_merge_modules_selected() {
+        let mut target =
+            a2lfile::load_from_string(MULTI_MODULE_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut source =
+            a2lfile::load_from_string(MULTI_MODULE_SOURCE_TEXT, None, &mut Vec::new(), false)
+                .unwrap();
+        let mut log_msgs = Vec::new();
+        let selected = parse_block_types("UNIT").unwrap();
+        let count = merge_modules_selected(
+            &mut target.project.module,
+            &mut source.project.module,
+            &selected,
+            &mut log_msgs,
+        );
+        assert_eq!(count, 2);
+        // the module "second" is merged by name, the module "other" has no match and goes into the first module
+        assert_eq!(target.project.module[0].unit.len(), 1);
+        assert_eq!(target.project.module[0].unit[0].name, "U_Other");
+        assert_eq!(target.project.module[1].unit.len(), 1);
+        assert_eq!(target.project.module[1].unit[0].name, "U_Second");
+    }
+}
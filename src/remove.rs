@@ -1,11 +1,12 @@
-use a2lfile::A2lFile;
+use a2lfile::{A2lFile, EcuAddressExtension};
+use std::collections::HashSet;
 
 pub(crate) fn remove_items(
     a2l_file: &mut A2lFile,
     regex_strings: &[&str],
     log_messages: &mut Vec<String>,
 ) -> usize {
-    let mut removed_items = std::collections::HashSet::<String>::new();
+    let mut removed_items = HashSet::<String>::new();
 
     let compiled_regexes = regex_strings
         .iter()
@@ -95,3 +96,383 @@ pub(crate) fn remove_items(
 
     removed_items.len()
 }
+
+// remove all COMPU_METHODs, COMPU_TABs, COMPU_VTABs and COMPU_VTAB_RANGEs that are not referenced
+// Unlike the full cleanup, this leaves all other objects (RECORD_LAYOUTs, GROUPs, etc.) untouched.
+pub(crate) fn remove_unused_conversions(
+    a2l_file: &mut A2lFile,
+    log_messages: &mut Vec<String>,
+) -> usize {
+    let mut removed_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        // collect the names of all COMPU_METHODs that are referenced by any object or AXIS_DESCR
+        let mut used_compu_methods = HashSet::<String>::new();
+        for measurement in &module.measurement {
+            used_compu_methods.insert(measurement.conversion.clone());
+        }
+        for characteristic in &module.characteristic {
+            used_compu_methods.insert(characteristic.conversion.clone());
+            for axis_descr in &characteristic.axis_descr {
+                used_compu_methods.insert(axis_descr.conversion.clone());
+            }
+        }
+        for axis_pts in &module.axis_pts {
+            used_compu_methods.insert(axis_pts.conversion.clone());
+        }
+        for typedef_measurement in &module.typedef_measurement {
+            used_compu_methods.insert(typedef_measurement.conversion.clone());
+        }
+        for typedef_characteristic in &module.typedef_characteristic {
+            used_compu_methods.insert(typedef_characteristic.conversion.clone());
+            for axis_descr in &typedef_characteristic.axis_descr {
+                used_compu_methods.insert(axis_descr.conversion.clone());
+            }
+        }
+        for typedef_axis in &module.typedef_axis {
+            used_compu_methods.insert(typedef_axis.conversion.clone());
+        }
+
+        module.compu_method.retain(|compu_method| {
+            let keep = used_compu_methods.contains(&compu_method.name);
+            if !keep {
+                removed_count += 1;
+                log_messages.push(format!("Removed COMPU_METHOD {}", compu_method.name));
+            }
+            keep
+        });
+
+        // conversion tables are only referenced by the remaining COMPU_METHODs
+        let mut used_compu_tabs = HashSet::<String>::new();
+        for compu_method in &module.compu_method {
+            if let Some(compu_tab_ref) = &compu_method.compu_tab_ref {
+                used_compu_tabs.insert(compu_tab_ref.conversion_table.clone());
+            }
+            if let Some(status_string_ref) = &compu_method.status_string_ref {
+                used_compu_tabs.insert(status_string_ref.conversion_table.clone());
+            }
+        }
+
+        module.compu_tab.retain(|compu_tab| {
+            let keep = used_compu_tabs.contains(&compu_tab.name);
+            if !keep {
+                removed_count += 1;
+                log_messages.push(format!("Removed COMPU_TAB {}", compu_tab.name));
+            }
+            keep
+        });
+        module.compu_vtab.retain(|compu_vtab| {
+            let keep = used_compu_tabs.contains(&compu_vtab.name);
+            if !keep {
+                removed_count += 1;
+                log_messages.push(format!("Removed COMPU_VTAB {}", compu_vtab.name));
+            }
+            keep
+        });
+        module.compu_vtab_range.retain(|compu_vtab_range| {
+            let keep = used_compu_tabs.contains(&compu_vtab_range.name);
+            if !keep {
+                removed_count += 1;
+                log_messages.push(format!(
+                    "Removed COMPU_VTAB_RANGE {}",
+                    compu_vtab_range.name
+                ));
+            }
+            keep
+        });
+    }
+
+    removed_count
+}
+
+// remove all GROUPs that contain no objects and no sub-groups
+// Removing a group can leave its parent group empty, so this is repeated until no further groups are removed.
+// All other objects are left untouched.
+pub(crate) fn remove_empty_groups(a2l_file: &mut A2lFile, log_messages: &mut Vec<String>) -> usize {
+    let mut removed_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        loop {
+            let mut removed_groups = HashSet::<String>::new();
+            module.group.retain(|group| {
+                let member_count = group
+                    .ref_characteristic
+                    .as_ref()
+                    .map_or(0, |refs| refs.identifier_list.len())
+                    + group
+                        .ref_measurement
+                        .as_ref()
+                        .map_or(0, |refs| refs.identifier_list.len())
+                    + group
+                        .function_list
+                        .as_ref()
+                        .map_or(0, |funcs| funcs.name_list.len())
+                    + group
+                        .sub_group
+                        .as_ref()
+                        .map_or(0, |sub_group| sub_group.identifier_list.len());
+                let is_empty = member_count == 0;
+                if is_empty {
+                    log_messages.push(format!("Removed empty GROUP {}", group.name));
+                    removed_groups.insert(group.name.clone());
+                }
+                !is_empty
+            });
+            if removed_groups.is_empty() {
+                break;
+            }
+            removed_count += removed_groups.len();
+
+            // remove the references to the removed groups
+            for group in &mut module.group {
+                if let Some(sub_group) = &mut group.sub_group {
+                    sub_group
+                        .identifier_list
+                        .retain(|ident| !removed_groups.contains(ident));
+                    if sub_group.identifier_list.is_empty() {
+                        group.sub_group = None;
+                    }
+                }
+            }
+            for user_rights in &mut module.user_rights {
+                for ref_group in &mut user_rights.ref_group {
+                    ref_group
+                        .identifier_list
+                        .retain(|ident| !removed_groups.contains(ident));
+                }
+            }
+        }
+    }
+
+    removed_count
+}
+
+// remove ECU_ADDRESS_EXTENSION 0 from all objects
+// An extension of 0 is the default, so the block is redundant, but some tools can't handle it.
+// Non-zero extensions are kept.
+pub(crate) fn remove_zero_address_extensions(
+    a2l_file: &mut A2lFile,
+    log_messages: &mut Vec<String>,
+) -> usize {
+    let mut removed_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let mut strip =
+            |blocktype: &str,
+             name: &str,
+             ecu_address_extension: &mut Option<EcuAddressExtension>| {
+                if ecu_address_extension
+                    .as_ref()
+                    .is_some_and(|ext| ext.extension == 0)
+                {
+                    *ecu_address_extension = None;
+                    removed_count += 1;
+                    log_messages.push(format!(
+                        "Removed ECU_ADDRESS_EXTENSION 0 from {blocktype} {name}"
+                    ));
+                }
+            };
+
+        for axis_pts in &mut module.axis_pts {
+            strip(
+                "AXIS_PTS",
+                &axis_pts.name,
+                &mut axis_pts.ecu_address_extension,
+            );
+        }
+        for blob in &mut module.blob {
+            strip("BLOB", &blob.name, &mut blob.ecu_address_extension);
+        }
+        for characteristic in &mut module.characteristic {
+            strip(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                &mut characteristic.ecu_address_extension,
+            );
+        }
+        for instance in &mut module.instance {
+            strip(
+                "INSTANCE",
+                &instance.name,
+                &mut instance.ecu_address_extension,
+            );
+        }
+        for measurement in &mut module.measurement {
+            strip(
+                "MEASUREMENT",
+                &measurement.name,
+                &mut measurement.ecu_address_extension,
+            );
+        }
+    }
+
+    removed_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin MEASUREMENT Meas "" UBYTE Meas_Compu 0 0 0 255
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Curve "" CURVE 0x1000 Curve_RecordLayout 0 NO_COMPU_METHOD 0 255
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY Axis_Compu 4 0 100
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin COMPU_METHOD Meas_Compu ""
+      TAB_VERB "%.0" ""
+      COMPU_TAB_REF Meas_Vtab
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Axis_Compu ""
+      TAB_INTP "%.0" ""
+      COMPU_TAB_REF Axis_Tab
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Orphan_Compu ""
+      TAB_VERB "%.0" ""
+      COMPU_TAB_REF Orphan_Vtab
+    /end COMPU_METHOD
+    /begin COMPU_VTAB Meas_Vtab "" TAB_VERB 2
+      0 "off"
+      1 "on"
+    /end COMPU_VTAB
+    /begin COMPU_VTAB Orphan_Vtab "" TAB_VERB 1
+      0 "zero"
+    /end COMPU_VTAB
+    /begin COMPU_VTAB_RANGE Orphan_VtabRange "" 1
+      0 10 "low"
+    /end COMPU_VTAB_RANGE
+    /begin COMPU_TAB Axis_Tab "" TAB_INTP 2
+      0 0
+      1 10
+    /end COMPU_TAB
+    /begin RECORD_LAYOUT Curve_RecordLayout
+      AXIS_PTS_X 1 UBYTE INDEX_INCR DIRECT
+      FNC_VALUES 2 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT Unused_RecordLayout
+      FNC_VALUES 1 UBYTE ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin GROUP Empty ""
+    /end GROUP
+    /begin GROUP Root "" ROOT
+      /begin SUB_GROUP Parent Used
+      /end SUB_GROUP
+    /end GROUP
+    /begin GROUP Parent ""
+      /begin SUB_GROUP Child
+      /end SUB_GROUP
+    /end GROUP
+    /begin GROUP Child ""
+      /begin SUB_GROUP Grandchild
+      /end SUB_GROUP
+    /end GROUP
+    /begin GROUP Grandchild ""
+    /end GROUP
+    /begin GROUP Used ""
+      /begin REF_MEASUREMENT Meas
+      /end REF_MEASUREMENT
+    /end GROUP
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_remove_unused_conversions() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let removed_count = remove_unused_conversions(&mut a2l_file, &mut log_msgs);
+        assert_eq!(removed_count, 3);
+        assert_eq!(log_msgs.len(), 3);
+
+        let module = &a2l_file.project.module[0];
+        // the orphan COMPU_METHOD and the tables that are only used by it are removed
+        assert_eq!(module.compu_method.len(), 2);
+        assert!(module
+            .compu_method
+            .iter()
+            .all(|cm| cm.name != "Orphan_Compu"));
+        assert_eq!(module.compu_vtab.len(), 1);
+        assert_eq!(module.compu_vtab[0].name, "Meas_Vtab");
+        assert!(module.compu_vtab_range.is_empty());
+        // the COMPU_TAB is referenced through the COMPU_METHOD of the AXIS_DESCR
+        assert_eq!(module.compu_tab.len(), 1);
+        // all other items are untouched, even if they are unused
+        assert_eq!(module.record_layout.len(), 2);
+        assert_eq!(module.group.len(), 6);
+    }
+
+    #[test]
+    fn test_remove_empty_groups() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let removed_count = remove_empty_groups(&mut a2l_file, &mut log_msgs);
+        // Grandchild is empty, which makes Child and then Parent empty
+        assert_eq!(removed_count, 4);
+        assert_eq!(log_msgs.len(), 4);
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.group.len(), 2);
+        let root = module.group.iter().find(|grp| grp.name == "Root").unwrap();
+        assert_eq!(
+            root.sub_group.as_ref().unwrap().identifier_list,
+            vec!["Used".to_string()]
+        );
+        assert!(module.group.iter().any(|grp| grp.name == "Used"));
+        // unreferenced conversions are not removed
+        assert_eq!(module.compu_method.len(), 3);
+    }
+
+    #[test]
+    fn test_remove_zero_address_extensions() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin MEASUREMENT Meas_Zero "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      ECU_ADDRESS_EXTENSION 0
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_Banked "" UBYTE NO_COMPU_METHOD 0 0 0 255
+      ECU_ADDRESS_EXTENSION 2
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_None "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Char_Zero "" VALUE 0x1000 RL 0 NO_COMPU_METHOD 0 255
+      ECU_ADDRESS_EXTENSION 0x0
+    /end CHARACTERISTIC
+    /begin AXIS_PTS Axis_Banked "" 0x2000 NO_INPUT_QUANTITY RL 0 NO_COMPU_METHOD 4 0 100
+      ECU_ADDRESS_EXTENSION 1
+    /end AXIS_PTS
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let removed_count = remove_zero_address_extensions(&mut a2l_file, &mut log_msgs);
+        assert_eq!(removed_count, 2);
+        assert_eq!(log_msgs.len(), 2);
+
+        let module = &a2l_file.project.module[0];
+        assert!(module.measurement[0].ecu_address_extension.is_none());
+        assert_eq!(
+            module.measurement[1]
+                .ecu_address_extension
+                .as_ref()
+                .unwrap()
+                .extension,
+            2
+        );
+        assert!(module.measurement[2].ecu_address_extension.is_none());
+        assert!(module.characteristic[0].ecu_address_extension.is_none());
+        assert_eq!(
+            module.axis_pts[0]
+                .ecu_address_extension
+                .as_ref()
+                .unwrap()
+                .extension,
+            1
+        );
+    }
+}
@@ -0,0 +1,669 @@
+use a2lfile::{A2lFile, Module};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+// A rename rule is given in sed syntax: s/pattern/replacement/flags
+// The only supported flag is "g", which replaces all matches instead of only the first one.
+pub(crate) struct RenameRule {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+}
+
+impl RenameRule {
+    pub(crate) fn parse(expr: &str) -> Result<Self, String> {
+        let mut chars = expr.chars();
+        let (Some('s'), Some(delimiter)) = (chars.next(), chars.next()) else {
+            return Err(format!(
+                "Invalid rename expression \"{expr}\": expected s/pattern/replacement/"
+            ));
+        };
+        if delimiter.is_alphanumeric() || delimiter == '\\' {
+            return Err(format!(
+                "Invalid rename expression \"{expr}\": '{delimiter}' cannot be used as a delimiter"
+            ));
+        }
+
+        // split the remaining text at each unescaped delimiter
+        let mut parts = vec![String::new()];
+        let mut escaped = false;
+        for c in chars {
+            if escaped {
+                if c != delimiter {
+                    parts.last_mut().unwrap().push('\\');
+                }
+                parts.last_mut().unwrap().push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter {
+                parts.push(String::new());
+            } else {
+                parts.last_mut().unwrap().push(c);
+            }
+        }
+        if escaped {
+            parts.last_mut().unwrap().push('\\');
+        }
+        if parts.len() != 3 {
+            return Err(format!(
+                "Invalid rename expression \"{expr}\": expected s/pattern/replacement/"
+            ));
+        }
+
+        let global = match &*parts[2] {
+            "" => false,
+            "g" => true,
+            flags => {
+                return Err(format!(
+                    "Invalid rename expression \"{expr}\": unsupported flags \"{flags}\""
+                ))
+            }
+        };
+        let regex = Regex::new(&parts[0])
+            .map_err(|err| format!("Invalid regex in rename expression \"{expr}\": {err}"))?;
+        let replacement = convert_replacement(&parts[1]);
+
+        Ok(Self {
+            regex,
+            replacement,
+            global,
+        })
+    }
+
+    fn apply(&self, name: &str) -> String {
+        if self.global {
+            self.regex.replace_all(name, &*self.replacement).to_string()
+        } else {
+            self.regex.replace(name, &*self.replacement).to_string()
+        }
+    }
+}
+
+// convert a sed replacement string to the syntax used by the regex crate:
+// "\1" becomes "${1}", "&" becomes "${0}", and a literal '$' must be written as "$$"
+fn convert_replacement(sed_replacement: &str) -> String {
+    let mut replacement = String::new();
+    let mut chars = sed_replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => replacement.push_str(&format!("${{{digit}}}")),
+                Some('$') => replacement.push_str("$$"),
+                Some(other) => replacement.push(other),
+                None => replacement.push('\\'),
+            },
+            '&' => replacement.push_str("${0}"),
+            '$' => replacement.push_str("$$"),
+            _ => replacement.push(c),
+        }
+    }
+    replacement
+}
+
+// rename CHARACTERISTICs, MEASUREMENTs, AXIS_PTS, BLOBs and INSTANCEs according to the rename rules
+// All references to the renamed objects are updated too.
+// If the renaming would cause any name collisions, then nothing is changed and an error is returned.
+pub(crate) fn rename_items(
+    a2l_file: &mut A2lFile,
+    rules: &[RenameRule],
+    log_messages: &mut Vec<String>,
+) -> Result<usize, String> {
+    // check all modules first, so that an error leaves the file completely unmodified
+    let mut rename_maps = Vec::with_capacity(a2l_file.project.module.len());
+    for module in &a2l_file.project.module {
+        rename_maps.push(build_rename_map(module, rules)?);
+    }
+
+    let mut renamed_count = 0;
+    for (module, rename_map) in a2l_file.project.module.iter_mut().zip(rename_maps) {
+        if rename_map.is_empty() {
+            continue;
+        }
+        let renamer = ReferenceRenamer::new(module, &rename_map);
+        rename_objects(module, &rename_map, log_messages);
+        rename_references(module, &renamer);
+        renamed_count += rename_map.len();
+    }
+
+    Ok(renamed_count)
+}
+
+// get the names of all objects that share the same namespace
+fn object_names(module: &Module) -> Vec<&String> {
+    module
+        .characteristic
+        .iter()
+        .map(|item| &item.name)
+        .chain(module.measurement.iter().map(|item| &item.name))
+        .chain(module.axis_pts.iter().map(|item| &item.name))
+        .chain(module.blob.iter().map(|item| &item.name))
+        .chain(module.instance.iter().map(|item| &item.name))
+        .collect()
+}
+
+// determine the new name of every object, and check that the new names are unique
+fn build_rename_map(
+    module: &Module,
+    rules: &[RenameRule],
+) -> Result<HashMap<String, String>, String> {
+    let mut rename_map = HashMap::new();
+    // new name -> list of the old names that map to it
+    let mut new_names = HashMap::<String, Vec<&str>>::new();
+    let mut errors = Vec::new();
+
+    for name in object_names(module) {
+        let new_name = rules
+            .iter()
+            .fold(name.clone(), |current, rule| rule.apply(&current));
+        if new_name.is_empty() {
+            errors.push(format!("{name} would be renamed to an empty name"));
+            continue;
+        }
+        if new_name != *name {
+            if !is_valid_identifier(&new_name) {
+                errors.push(format!(
+                    "{name} would be renamed to \"{new_name}\", which is not a valid a2l identifier"
+                ));
+                continue;
+            }
+            rename_map.insert(name.clone(), new_name.clone());
+        }
+        new_names.entry(new_name).or_default().push(name);
+    }
+
+    let mut collisions: Vec<_> = new_names
+        .iter()
+        .filter(|(_, old_names)| old_names.len() > 1)
+        .collect();
+    collisions.sort();
+    for (new_name, old_names) in collisions {
+        errors.push(format!(
+            "{} would all be named {new_name}",
+            old_names.join(", ")
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(rename_map)
+    } else {
+        Err(format!(
+            "Renaming failed in module {}:\n  {}",
+            module.name,
+            errors.join("\n  ")
+        ))
+    }
+}
+
+// a2l identifiers must start with a letter or '_', and may only contain letters, digits and the characters "_.[]"
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_.[]".contains(c))
+}
+
+fn rename_objects(
+    module: &mut Module,
+    rename_map: &HashMap<String, String>,
+    log_messages: &mut Vec<String>,
+) {
+    let names = module
+        .characteristic
+        .iter_mut()
+        .map(|item| &mut item.name)
+        .chain(module.measurement.iter_mut().map(|item| &mut item.name))
+        .chain(module.axis_pts.iter_mut().map(|item| &mut item.name))
+        .chain(module.blob.iter_mut().map(|item| &mut item.name))
+        .chain(module.instance.iter_mut().map(|item| &mut item.name));
+    for name in names {
+        if let Some(new_name) = rename_map.get(name.as_str()) {
+            log_messages.push(format!("Renamed {name} to {new_name}"));
+            *name = new_name.clone();
+        }
+    }
+}
+
+fn rename_references(module: &mut Module, renamer: &ReferenceRenamer) {
+    for group in &mut module.group {
+        if let Some(ref_characteristic) = &mut group.ref_characteristic {
+            renamer.rename_item_list(&mut ref_characteristic.identifier_list);
+        }
+        if let Some(ref_measurement) = &mut group.ref_measurement {
+            renamer.rename_item_list(&mut ref_measurement.identifier_list);
+        }
+    }
+
+    for function in &mut module.function {
+        if let Some(def_characteristic) = &mut function.def_characteristic {
+            renamer.rename_item_list(&mut def_characteristic.identifier_list);
+        }
+        if let Some(ref_characteristic) = &mut function.ref_characteristic {
+            renamer.rename_item_list(&mut ref_characteristic.identifier_list);
+        }
+        if let Some(in_measurement) = &mut function.in_measurement {
+            renamer.rename_item_list(&mut in_measurement.identifier_list);
+        }
+        if let Some(loc_measurement) = &mut function.loc_measurement {
+            renamer.rename_item_list(&mut loc_measurement.identifier_list);
+        }
+        if let Some(out_measurement) = &mut function.out_measurement {
+            renamer.rename_item_list(&mut out_measurement.identifier_list);
+        }
+    }
+
+    for characteristic in &mut module.characteristic {
+        for axis_descr in &mut characteristic.axis_descr {
+            renamer.rename_item(&mut axis_descr.input_quantity);
+            if let Some(axis_pts_ref) = &mut axis_descr.axis_pts_ref {
+                renamer.rename_item(&mut axis_pts_ref.axis_points);
+            }
+            if let Some(curve_axis_ref) = &mut axis_descr.curve_axis_ref {
+                renamer.rename_item(&mut curve_axis_ref.curve_axis);
+            }
+        }
+        if let Some(comparison_quantity) = &mut characteristic.comparison_quantity {
+            renamer.rename_item(&mut comparison_quantity.name);
+        }
+        if let Some(map_list) = &mut characteristic.map_list {
+            renamer.rename_item_list(&mut map_list.name_list);
+        }
+        if let Some(dependent_characteristic) = &mut characteristic.dependent_characteristic {
+            renamer.rename_item_list(&mut dependent_characteristic.characteristic_list);
+            dependent_characteristic.formula =
+                renamer.rename_in_formula(&dependent_characteristic.formula);
+        }
+        if let Some(virtual_characteristic) = &mut characteristic.virtual_characteristic {
+            renamer.rename_item_list(&mut virtual_characteristic.characteristic_list);
+            virtual_characteristic.formula =
+                renamer.rename_in_formula(&virtual_characteristic.formula);
+        }
+    }
+
+    for measurement in &mut module.measurement {
+        if let Some(virtual_meas) = &mut measurement.var_virtual {
+            renamer.rename_item_list(&mut virtual_meas.measuring_channel_list);
+        }
+    }
+
+    for axis_pts in &mut module.axis_pts {
+        renamer.rename_item(&mut axis_pts.input_quantity);
+    }
+
+    for typedef_characteristic in &mut module.typedef_characteristic {
+        for axis_descr in &mut typedef_characteristic.axis_descr {
+            renamer.rename_item(&mut axis_descr.input_quantity);
+            if let Some(axis_pts_ref) = &mut axis_descr.axis_pts_ref {
+                renamer.rename_item(&mut axis_pts_ref.axis_points);
+            }
+            if let Some(curve_axis_ref) = &mut axis_descr.curve_axis_ref {
+                renamer.rename_item(&mut curve_axis_ref.curve_axis);
+            }
+        }
+    }
+
+    for typedef_axis in &mut module.typedef_axis {
+        renamer.rename_item(&mut typedef_axis.input_quantity);
+    }
+
+    for frame in &mut module.frame {
+        if let Some(frame_measurement) = &mut frame.frame_measurement {
+            renamer.rename_item_list(&mut frame_measurement.identifier_list);
+        }
+    }
+
+    for transformer in &mut module.transformer {
+        if let Some(transformer_in_objects) = &mut transformer.transformer_in_objects {
+            renamer.rename_item_list(&mut transformer_in_objects.identifier_list);
+        }
+        if let Some(transformer_out_objects) = &mut transformer.transformer_out_objects {
+            renamer.rename_item_list(&mut transformer_out_objects.identifier_list);
+        }
+    }
+
+    if let Some(variant_coding) = &mut module.variant_coding {
+        for var_characteristic in &mut variant_coding.var_characteristic {
+            renamer.rename_item(&mut var_characteristic.name);
+        }
+        for var_criterion in &mut variant_coding.var_criterion {
+            if let Some(var_measurement) = &mut var_criterion.var_measurement {
+                renamer.rename_item(&mut var_measurement.name);
+            }
+            if let Some(var_selection_characteristic) =
+                &mut var_criterion.var_selection_characteristic
+            {
+                renamer.rename_item(&mut var_selection_characteristic.name);
+            }
+        }
+    }
+}
+
+// Updates the references to renamed objects.
+// References to the components of an INSTANCE start with the name of the instance, e.g. Inst.member
+// or Inst[2].member, so these are updated when the INSTANCE is renamed.
+struct ReferenceRenamer<'a> {
+    rename_map: &'a HashMap<String, String>,
+    instance_names: HashSet<&'a str>,
+    ident_regex: Regex,
+}
+
+impl<'a> ReferenceRenamer<'a> {
+    // must be called before the objects are renamed, because it uses the old names of the INSTANCEs
+    fn new(module: &Module, rename_map: &'a HashMap<String, String>) -> Self {
+        let instance_names = module
+            .instance
+            .iter()
+            .filter_map(|instance| rename_map.get_key_value(&instance.name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        Self {
+            rename_map,
+            instance_names,
+            // a2l identifiers may contain '.' and array indices
+            ident_regex: Regex::new(r"[A-Za-z_][A-Za-z0-9_.\[\]]*").unwrap(),
+        }
+    }
+
+    fn new_name(&self, name: &str) -> Option<String> {
+        if let Some(new_name) = self.rename_map.get(name) {
+            return Some(new_name.clone());
+        }
+        let (prefix, component) = name.split_at(name.find(['.', '['])?);
+        if self.instance_names.contains(prefix) {
+            Some(format!("{}{component}", self.rename_map[prefix]))
+        } else {
+            None
+        }
+    }
+
+    fn rename_item(&self, item: &mut String) {
+        if let Some(new_name) = self.new_name(item) {
+            *item = new_name;
+        }
+    }
+
+    fn rename_item_list(&self, item_list: &mut [String]) {
+        for item in item_list {
+            self.rename_item(item);
+        }
+    }
+
+    // Formulas usually refer to the characteristics in the characteristic list using the placeholders X1, X2, etc.
+    // Some tools put the names of the objects into the formula instead; these names need to be updated as well.
+    // The placeholders are never renamed, even if an object with the same name exists.
+    fn rename_in_formula(&self, formula: &str) -> String {
+        self.ident_regex
+            .replace_all(formula, |caps: &regex::Captures| {
+                let ident = &caps[0];
+                if is_formula_placeholder(ident) {
+                    return ident.to_string();
+                }
+                self.new_name(ident).unwrap_or_else(|| ident.to_string())
+            })
+            .to_string()
+    }
+}
+
+// X1, X2, ... stand for the entries of the characteristic list, and X for the input value of a formula
+fn is_formula_placeholder(ident: &str) -> bool {
+    ident
+        .strip_prefix('X')
+        .is_some_and(|index| index.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin MEASUREMENT ASW_Speed "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      SYMBOL_LINK "ASW_Speed" 0
+    /end MEASUREMENT
+    /begin MEASUREMENT Other "" UWORD NO_COMPU_METHOD 0 0 0 65535
+    /end MEASUREMENT
+    /begin AXIS_PTS ASW_Axis "" 0x1000 ASW_Speed Axis_RecordLayout 0 NO_COMPU_METHOD 4 0 100
+    /end AXIS_PTS
+    /begin CHARACTERISTIC ASW_Curve "" CURVE 0x2000 Curve_RecordLayout 0 NO_COMPU_METHOD 0 255
+      /begin AXIS_DESCR COM_AXIS ASW_Speed NO_COMPU_METHOD 4 0 100
+        AXIS_PTS_REF ASW_Axis
+      /end AXIS_DESCR
+      COMPARISON_QUANTITY ASW_Speed
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Dependent "" VALUE 0x3000 Value_RecordLayout 0 NO_COMPU_METHOD 0 255
+      /begin DEPENDENT_CHARACTERISTIC "X1 * 2 + ASW_Curve"
+        ASW_Curve
+      /end DEPENDENT_CHARACTERISTIC
+    /end CHARACTERISTIC
+    /begin GROUP Grp ""
+      /begin REF_MEASUREMENT ASW_Speed Other
+      /end REF_MEASUREMENT
+      /begin REF_CHARACTERISTIC ASW_Curve
+      /end REF_CHARACTERISTIC
+    /end GROUP
+    /begin FUNCTION Func ""
+      /begin IN_MEASUREMENT ASW_Speed
+      /end IN_MEASUREMENT
+      /begin DEF_CHARACTERISTIC ASW_Curve
+      /end DEF_CHARACTERISTIC
+    /end FUNCTION
+  /end MODULE
+/end PROJECT"#;
+
+    static INSTANCE_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin INSTANCE ASW_Inst "" Struct_Type 0x1000
+    /end INSTANCE
+    /begin INSTANCE ASW_Instance "" Struct_Type 0x2000
+    /end INSTANCE
+    /begin CHARACTERISTIC Dependent "" VALUE 0x3000 Value_RecordLayout 0 NO_COMPU_METHOD 0 255
+      /begin VIRTUAL_CHARACTERISTIC "ASW_Inst.gain * X1"
+        ASW_Inst.offset
+      /end VIRTUAL_CHARACTERISTIC
+    /end CHARACTERISTIC
+    /begin GROUP Grp ""
+      /begin REF_MEASUREMENT ASW_Inst ASW_Inst.value ASW_Inst[2].value ASW_Instance.value
+      /end REF_MEASUREMENT
+    /end GROUP
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_parse_rename_rule() {
+        let rule = RenameRule::parse("s/^ASW_//").unwrap();
+        assert_eq!(rule.apply("ASW_Speed"), "Speed");
+        assert_eq!(rule.apply("Speed_ASW_"), "Speed_ASW_");
+
+        let rule = RenameRule::parse(r"s#^(\w+)_(\w+)$#\2_\1#").unwrap();
+        assert_eq!(rule.apply("abc_def"), "def_abc");
+
+        let rule = RenameRule::parse("s/a/b/").unwrap();
+        assert_eq!(rule.apply("aaa"), "baa");
+        let rule = RenameRule::parse("s/a/b/g").unwrap();
+        assert_eq!(rule.apply("aaa"), "bbb");
+
+        // an escaped delimiter is part of the pattern
+        let rule = RenameRule::parse(r"s/x\/y/z/").unwrap();
+        assert_eq!(rule.apply("x/y"), "z");
+
+        assert!(RenameRule::parse("x/a/b/").is_err());
+        assert!(RenameRule::parse("s/a/b").is_err());
+        assert!(RenameRule::parse("s/a/b/q").is_err());
+        assert!(RenameRule::parse("s/(/b/").is_err());
+    }
+
+    #[test]
+    fn test_rename_items() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let rules = vec![RenameRule::parse("s/^ASW_//").unwrap()];
+        let mut log_msgs = Vec::new();
+        let renamed_count = rename_items(&mut a2l_file, &rules, &mut log_msgs).unwrap();
+        assert_eq!(renamed_count, 3);
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement[0].name, "Speed");
+        // the SYMBOL_LINK refers to the code and is not changed
+        assert_eq!(
+            module.measurement[0]
+                .symbol_link
+                .as_ref()
+                .unwrap()
+                .symbol_name,
+            "ASW_Speed"
+        );
+        assert_eq!(module.axis_pts[0].name, "Axis");
+        assert_eq!(module.axis_pts[0].input_quantity, "Speed");
+        let axis_descr = &module.characteristic[0].axis_descr[0];
+        assert_eq!(axis_descr.input_quantity, "Speed");
+        assert_eq!(
+            axis_descr.axis_pts_ref.as_ref().unwrap().axis_points,
+            "Axis"
+        );
+        assert_eq!(
+            module.characteristic[0]
+                .comparison_quantity
+                .as_ref()
+                .unwrap()
+                .name,
+            "Speed"
+        );
+        let dependent = module.characteristic[1]
+            .dependent_characteristic
+            .as_ref()
+            .unwrap();
+        assert_eq!(dependent.characteristic_list, vec!["Curve".to_string()]);
+        assert_eq!(dependent.formula, "X1 * 2 + Curve");
+        let group = &module.group[0];
+        assert_eq!(
+            group.ref_measurement.as_ref().unwrap().identifier_list,
+            vec!["Speed".to_string(), "Other".to_string()]
+        );
+        assert_eq!(
+            group.ref_characteristic.as_ref().unwrap().identifier_list,
+            vec!["Curve".to_string()]
+        );
+        let function = &module.function[0];
+        assert_eq!(
+            function.in_measurement.as_ref().unwrap().identifier_list,
+            vec!["Speed".to_string()]
+        );
+        assert_eq!(
+            function
+                .def_characteristic
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec!["Curve".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rename_collision() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let unmodified = a2l_file.clone();
+
+        // ASW_Speed -> Other collides with the existing MEASUREMENT Other
+        let rules = vec![RenameRule::parse("s/^ASW_Speed$/Other/").unwrap()];
+        let result = rename_items(&mut a2l_file, &rules, &mut Vec::new());
+        let err = result.unwrap_err();
+        assert!(err.contains("ASW_Speed"));
+        assert!(err.contains("Other"));
+        assert_eq!(a2l_file, unmodified);
+
+        // several objects are renamed to the same name
+        let rules = vec![RenameRule::parse("s/^.*$/Same/").unwrap()];
+        assert!(rename_items(&mut a2l_file, &rules, &mut Vec::new()).is_err());
+        assert_eq!(a2l_file, unmodified);
+    }
+
+    #[test]
+    fn test_rename_invalid_name() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let unmodified = a2l_file.clone();
+
+        // the new names must be valid a2l identifiers
+        for rule in ["s/ASW_/My /", "s/^ASW_/1/", "s/_/-/"] {
+            let rules = vec![RenameRule::parse(rule).unwrap()];
+            let err = rename_items(&mut a2l_file, &rules, &mut Vec::new()).unwrap_err();
+            assert!(err.contains("not a valid a2l identifier"));
+            assert_eq!(a2l_file, unmodified);
+        }
+
+        assert!(is_valid_identifier("Inst[2].value"));
+        assert!(is_valid_identifier("_x"));
+        assert!(!is_valid_identifier(""));
+        assert!(!is_valid_identifier("2x"));
+        assert!(!is_valid_identifier("a b"));
+    }
+
+    #[test]
+    fn test_rename_formula_placeholder() {
+        // an object may have the same name as a formula placeholder
+        let a2l_text = A2L_TEXT
+            .replace("MEASUREMENT Other", "MEASUREMENT X1")
+            .replace("ASW_Speed Other", "ASW_Speed X1");
+        let mut a2l_file =
+            a2lfile::load_from_string(&a2l_text, None, &mut Vec::new(), false).unwrap();
+        let rules = vec![RenameRule::parse("s/^X1$/Other/").unwrap()];
+        let renamed_count = rename_items(&mut a2l_file, &rules, &mut Vec::new()).unwrap();
+        assert_eq!(renamed_count, 1);
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement[1].name, "Other");
+        assert_eq!(
+            module.group[0]
+                .ref_measurement
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec!["ASW_Speed".to_string(), "Other".to_string()]
+        );
+        // the placeholder X1 in the formula is not renamed
+        let dependent = module.characteristic[1]
+            .dependent_characteristic
+            .as_ref()
+            .unwrap();
+        assert_eq!(dependent.formula, "X1 * 2 + ASW_Curve");
+    }
+
+    #[test]
+    fn test_rename_instance() {
+        let mut a2l_file =
+            a2lfile::load_from_string(INSTANCE_TEXT, None, &mut Vec::new(), false).unwrap();
+        let rules = vec![RenameRule::parse("s/^ASW_Inst$/Inst/").unwrap()];
+        let renamed_count = rename_items(&mut a2l_file, &rules, &mut Vec::new()).unwrap();
+        assert_eq!(renamed_count, 1);
+
+        // references to the components of the renamed INSTANCE are updated, but not those of ASW_Instance
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.instance[0].name, "Inst");
+        assert_eq!(
+            module.group[0]
+                .ref_measurement
+                .as_ref()
+                .unwrap()
+                .identifier_list,
+            vec![
+                "Inst".to_string(),
+                "Inst.value".to_string(),
+                "Inst[2].value".to_string(),
+                "ASW_Instance.value".to_string()
+            ]
+        );
+        let virtual_characteristic = module.characteristic[0]
+            .virtual_characteristic
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            virtual_characteristic.characteristic_list,
+            vec!["Inst.offset".to_string()]
+        );
+        assert_eq!(virtual_characteristic.formula, "Inst.gain * X1");
+    }
+}
@@ -0,0 +1,182 @@
+use a2lfile::{A2lFile, A2lObject, EcuAddress};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+
+// Load a list of object names and addresses from a CSV file.
+// Each line contains a name and a hex address, separated by a comma or a semicolon, e.g.
+//   Characteristic_Value, 0x8001000
+// Empty lines and lines starting with '#' are ignored. A header line is permitted as the first line.
+pub(crate) fn load_address_csv(filename: &OsStr) -> Result<HashMap<String, u32>, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the seed addresses from {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    parse_address_csv(&text).map_err(|err| format!("{}: {err}", filename.to_string_lossy()))
+}
+
+fn parse_address_csv(text: &str) -> Result<HashMap<String, u32>, String> {
+    let mut addresses = HashMap::new();
+    let mut first_line = true;
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_header = std::mem::replace(&mut first_line, false);
+        let Some((name, address_text)) = line.split_once([',', ';']) else {
+            return Err(format!(
+                "line {}: expected a name and an address separated by ','",
+                line_idx + 1
+            ));
+        };
+        let name = name.trim().trim_matches('"');
+        let address_text = address_text.trim().trim_matches('"');
+        let hex_digits = address_text
+            .strip_prefix("0x")
+            .or_else(|| address_text.strip_prefix("0X"))
+            .unwrap_or(address_text);
+        match u32::from_str_radix(hex_digits, 16) {
+            Ok(address) => {
+                if addresses.insert(name.to_string(), address).is_some() {
+                    return Err(format!(
+                        "line {}: the address of {name} is given more than once",
+                        line_idx + 1
+                    ));
+                }
+            }
+            // the first line may be a header, e.g. "name,address"
+            Err(_) if is_header => {}
+            Err(_) => {
+                return Err(format!(
+                    "line {}: \"{address_text}\" is not a valid hex address",
+                    line_idx + 1
+                ));
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+// Set the addresses of all objects whose names are listed in addresses.
+// CHARACTERISTICs, MEASUREMENTs, AXIS_PTS, BLOBs and INSTANCEs are supported; a MEASUREMENT
+// receives an ECU_ADDRESS if it does not have one yet.
+// Returns the number of objects whose address was set.
+pub(crate) fn seed_addresses(
+    a2l_file: &mut A2lFile,
+    addresses: &HashMap<String, u32>,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut used_names = Vec::new();
+
+    for module in &mut a2l_file.project.module {
+        for characteristic in &mut module.characteristic {
+            if let Some(address) = addresses.get(&characteristic.name) {
+                // display the address as hex
+                characteristic.get_layout_mut().item_location.3 .1 = true;
+                characteristic.address = *address;
+                used_names.push(characteristic.name.clone());
+            }
+        }
+        for measurement in &mut module.measurement {
+            if let Some(address) = addresses.get(&measurement.name) {
+                let ecu_address = measurement
+                    .ecu_address
+                    .get_or_insert_with(|| EcuAddress::new(0));
+                ecu_address.get_layout_mut().item_location.0 .1 = true;
+                ecu_address.address = *address;
+                used_names.push(measurement.name.clone());
+            }
+        }
+        for axis_pts in &mut module.axis_pts {
+            if let Some(address) = addresses.get(&axis_pts.name) {
+                axis_pts.get_layout_mut().item_location.2 .1 = true;
+                axis_pts.address = *address;
+                used_names.push(axis_pts.name.clone());
+            }
+        }
+        for blob in &mut module.blob {
+            if let Some(address) = addresses.get(&blob.name) {
+                blob.get_layout_mut().item_location.2 .1 = true;
+                blob.start_address = *address;
+                used_names.push(blob.name.clone());
+            }
+        }
+        for instance in &mut module.instance {
+            if let Some(address) = addresses.get(&instance.name) {
+                instance.get_layout_mut().item_location.3 .1 = true;
+                instance.start_address = *address;
+                used_names.push(instance.name.clone());
+            }
+        }
+    }
+
+    let mut unused_names: Vec<&String> = addresses
+        .keys()
+        .filter(|name| !used_names.contains(name))
+        .collect();
+    unused_names.sort();
+    for name in unused_names {
+        log_msgs.push(format!(
+            "The seed address of {name} was not used, because there is no object with this name"
+        ));
+    }
+
+    used_names.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_address_csv() {
+        let addresses =
+            parse_address_csv("name,address\n# comment\n\nAbc, 0x1000\nDef;2000\n\"Ghi\",\"0XFF\"")
+                .unwrap();
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses["Abc"], 0x1000);
+        assert_eq!(addresses["Def"], 0x2000);
+        assert_eq!(addresses["Ghi"], 0xFF);
+
+        assert!(parse_address_csv("Abc, 0x1000\nDef, xyz").is_err());
+        assert!(parse_address_csv("Abc, 0x1000\nDef").is_err());
+        assert!(parse_address_csv("Abc, 0x1000\nAbc, 0x2000").is_err());
+    }
+
+    #[test]
+    fn test_seed_addresses() {
+        let mut a2l_file = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let addresses = HashMap::from([
+            ("Characteristic_Value".to_string(), 0x1000),
+            ("Measurement_Value".to_string(), 0x2000),
+            ("Axis_0".to_string(), 0x3000),
+            ("Unknown".to_string(), 0x4000),
+        ]);
+        let mut log_msgs = Vec::new();
+        let count = seed_addresses(&mut a2l_file, &addresses, &mut log_msgs);
+        assert_eq!(count, 3);
+        assert_eq!(log_msgs.len(), 1);
+        let module = &a2l_file.project.module[0];
+        let characteristic = module
+            .characteristic
+            .iter()
+            .find(|item| item.name == "Characteristic_Value")
+            .unwrap();
+        assert_eq!(characteristic.address, 0x1000);
+        let measurement = module
+            .measurement
+            .iter()
+            .find(|item| item.name == "Measurement_Value")
+            .unwrap();
+        assert_eq!(measurement.ecu_address.as_ref().unwrap().address, 0x2000);
+        assert_eq!(module.axis_pts[0].address, 0x3000);
+    }
+}
@@ -0,0 +1,981 @@
+use a2lfile::{
+    A2lFile, A2lObject, AxisDescr, AxisDescrAttribute, AxisPts, AxisPtsRef, Characteristic,
+    CharacteristicType, Instance, Module, StructureComponent, SymbolLink, SymbolTypeLink,
+    TypedefAxis, TypedefCharacteristic, TypedefStructure,
+};
+use std::ffi::OsStr;
+
+use crate::datatype::{get_a2l_datatype, get_type_limits};
+use crate::debuginfo::{DbgDataType, DebugData, TypeInfo};
+use crate::insert::set_variable_byte_order;
+use crate::symbol::{find_symbol, SymbolInfo};
+use crate::update::{create_default_record_layout, make_symbol_link_string, RecordLayoutInfo};
+use crate::A2lVersion;
+
+// A CURVE or MAP whose axes are stored in separate arrays, as described by one line of the definition file
+#[derive(Debug, PartialEq)]
+pub(crate) struct SharedAxisDef {
+    pub(crate) map_symbol: String,
+    pub(crate) axis_symbols: Vec<String>,
+}
+
+// A symbol of the definition file, with the information that is needed to create the a2l object for it
+struct ArraySymbol<'dbg> {
+    name: String,
+    sym_info: SymbolInfo<'dbg>,
+    dim: Vec<u64>,
+    elemtype: &'dbg TypeInfo,
+}
+
+// A struct variable whose members are CURVEs, MAPs and their axes. It is created as an INSTANCE,
+// and each axis is a STRUCTURE_COMPONENT that is shared by all maps in the struct.
+struct StructMaps<'dbg> {
+    name: String,
+    sym_info: SymbolInfo<'dbg>,
+    typeinfo: &'dbg TypeInfo,
+    axes: Vec<ArraySymbol<'dbg>>,
+    maps: Vec<(ArraySymbol<'dbg>, Vec<usize>)>,
+}
+
+// Load the definitions of CURVEs and MAPs with shared axes from a file.
+//
+// Each non-empty line that does not start with '#' contains the array symbol of a CURVE or MAP,
+// followed by the array symbols of its axes:
+//   <curve symbol>; <x axis symbol>
+//   <map symbol>; <x axis symbol>; <y axis symbol>
+// The values of a MAP are stored row by row, i.e. a MAP in C is declared as value[y][x].
+pub(crate) fn load_definitions(filename: &OsStr) -> Result<Vec<SharedAxisDef>, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the shared axis definitions from {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    parse_definitions(&text).map_err(|err| format!("{}: {err}", filename.to_string_lossy()))
+}
+
+fn parse_definitions(text: &str) -> Result<Vec<SharedAxisDef>, String> {
+    let mut definitions: Vec<SharedAxisDef> = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        if fields.len() != 2 && fields.len() != 3 {
+            return Err(format!(
+                "line {line_num}: expected 2 or 3 fields separated by ';', found {}",
+                fields.len()
+            ));
+        }
+        if let Some(empty_idx) = fields.iter().position(|field| field.is_empty()) {
+            return Err(format!("line {line_num}: field {} is empty", empty_idx + 1));
+        }
+        let map_symbol = fields[0];
+        if definitions.iter().any(|def| def.map_symbol == map_symbol) {
+            return Err(format!(
+                "line {line_num}: the symbol {map_symbol} is defined twice"
+            ));
+        }
+        if fields[1..].contains(&map_symbol) {
+            return Err(format!(
+                "line {line_num}: the symbol {map_symbol} can not be its own axis"
+            ));
+        }
+        definitions.push(SharedAxisDef {
+            map_symbol: map_symbol.to_string(),
+            axis_symbols: fields[1..].iter().map(|axis| axis.to_string()).collect(),
+        });
+    }
+    Ok(definitions)
+}
+
+// Create one AXIS_PTS for each distinct axis symbol, and a CURVE or MAP for each definition.
+// The CURVEs and MAPs reference the AXIS_PTS through AXIS_DESCRs of type COM_AXIS, so that an
+// axis that is used by several maps only exists once in the a2l file. Their RECORD_LAYOUTs only
+// contain FNC_VALUES.
+// AXIS_PTS that already exist are reused. Returns the number of created CHARACTERISTICs and AXIS_PTS.
+//
+// If enable_structures is set, a curve or map whose axes are members of the same struct variable is
+// created as a TYPEDEF_CHARACTERISTIC instead. Its AXIS_DESCRs reference TYPEDEF_AXIS components
+// of the TYPEDEF_STRUCTURE, so that the axes are relative to the INSTANCE of the struct.
+pub(crate) fn create_shared_axis_maps(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    definitions: &[SharedAxisDef],
+    enable_structures: bool,
+    log_msgs: &mut Vec<String>,
+) -> Result<(usize, usize), String> {
+    let version = A2lVersion::from(&*a2l_file);
+    let module = &mut a2l_file.project.module[0];
+
+    // check all definitions before modifying anything
+    let mut errors = Vec::new();
+    let mut axes: Vec<ArraySymbol> = Vec::new();
+    let mut maps: Vec<(ArraySymbol, Vec<usize>)> = Vec::new();
+    let mut struct_maps: Vec<StructMaps> = Vec::new();
+    for def in definitions {
+        if let Some(parent) = get_struct_parent(def).filter(|_| enable_structures) {
+            if let Err(errmsg) = add_struct_map(&mut struct_maps, parent, def, debug_data) {
+                errors.push(errmsg);
+            }
+            continue;
+        }
+        let map = match get_array_symbol(&def.map_symbol, debug_data) {
+            Ok(map) => map,
+            Err(errmsg) => {
+                errors.push(errmsg);
+                continue;
+            }
+        };
+        if map.dim.len() != def.axis_symbols.len() {
+            errors.push(format!(
+                "{} has {} dimensions, but {} axes are given",
+                def.map_symbol,
+                map.dim.len(),
+                def.axis_symbols.len()
+            ));
+            continue;
+        }
+
+        let mut axis_indices = Vec::new();
+        for (axis_idx, axis_symbol) in def.axis_symbols.iter().enumerate() {
+            let idx = if let Some(idx) = axes.iter().position(|axis| axis.name == *axis_symbol) {
+                idx
+            } else {
+                match get_array_symbol(axis_symbol, debug_data) {
+                    Ok(axis) if axis.dim.len() == 1 => {
+                        axes.push(axis);
+                        axes.len() - 1
+                    }
+                    Ok(_) => {
+                        errors.push(format!(
+                            "the axis {axis_symbol} is not a one-dimensional array"
+                        ));
+                        continue;
+                    }
+                    Err(errmsg) => {
+                        errors.push(errmsg);
+                        continue;
+                    }
+                }
+            };
+            if let Err(errmsg) = check_axis_size(&map, axis_idx, &axes[idx]) {
+                errors.push(errmsg);
+                continue;
+            }
+            axis_indices.push(idx);
+        }
+        if axis_indices.len() == def.axis_symbols.len() {
+            maps.push((map, axis_indices));
+        }
+    }
+    for axis in &axes {
+        let item_name = axis.name.replace("::", "__");
+        if !module
+            .axis_pts
+            .iter()
+            .any(|axis_pts| axis_pts.name == item_name)
+        {
+            if let Err(errmsg) = check_unique_name(module, &item_name) {
+                errors.push(errmsg);
+            }
+        }
+    }
+    for (map, _) in &maps {
+        let item_name = map.name.replace("::", "__");
+        if let Err(errmsg) = check_unique_name(module, &item_name) {
+            errors.push(errmsg);
+        } else if axes.iter().any(|axis| axis.name == map.name) {
+            errors.push(format!(
+                "the symbol {} is used both as an axis and as a curve or map",
+                map.name
+            ));
+        }
+    }
+    for struct_map in &struct_maps {
+        if let Err(errmsg) = check_unique_name(module, &struct_map.name.replace("::", "__")) {
+            errors.push(errmsg);
+        }
+        for (map, _) in &struct_map.maps {
+            if struct_map.axes.iter().any(|axis| axis.name == map.name) {
+                errors.push(format!(
+                    "the symbol {} is used both as an axis and as a curve or map",
+                    map.name
+                ));
+            }
+        }
+        let typedef_name = make_struct_typedef_name(struct_map);
+        let member_names = struct_map
+            .axes
+            .iter()
+            .chain(struct_map.maps.iter().map(|(map, _)| map))
+            .map(|item| member_name(&item.name));
+        for name in std::iter::once(typedef_name.clone())
+            .chain(member_names.map(|member| format!("{typedef_name}_{member}")))
+        {
+            if let Err(errmsg) = check_unique_typedef_name(module, &name) {
+                errors.push(errmsg);
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "Could not create the maps with shared axes:\n  {}",
+            errors.join("\n  ")
+        ));
+    }
+
+    let mut recordlayout_info = RecordLayoutInfo::build(module);
+    let mut axis_count = 0;
+    let mut axis_names = Vec::new();
+    for axis in &axes {
+        let item_name = axis.name.replace("::", "__");
+        if module
+            .axis_pts
+            .iter()
+            .any(|axis_pts| axis_pts.name == item_name)
+        {
+            log_msgs.push(format!("Using the existing AXIS_PTS {item_name}"));
+        } else {
+            let axis_pts = create_axis_pts(
+                module,
+                &mut recordlayout_info,
+                debug_data,
+                axis,
+                &item_name,
+                version,
+            );
+            module.axis_pts.push(axis_pts);
+            log_msgs.push(format!("Created AXIS_PTS {item_name}"));
+            axis_count += 1;
+        }
+        axis_names.push(item_name);
+    }
+
+    let mut map_count = 0;
+    for (map, axis_indices) in &maps {
+        let item_name = map.name.replace("::", "__");
+        let axis_descr = axis_indices
+            .iter()
+            .map(|idx| {
+                let axis_name = &axis_names[*idx];
+                let axis_pts = module
+                    .axis_pts
+                    .iter()
+                    .find(|axis_pts| axis_pts.name == *axis_name)
+                    .expect("the AXIS_PTS was created above");
+                make_com_axis_descr(axis_pts)
+            })
+            .collect();
+        let characteristic = create_map_characteristic(
+            module,
+            &mut recordlayout_info,
+            debug_data,
+            map,
+            &item_name,
+            axis_descr,
+            version,
+        );
+        module.characteristic.push(characteristic);
+        log_msgs.push(format!(
+            "Created {} {item_name} with shared axes",
+            if axis_indices.len() == 1 {
+                "CURVE"
+            } else {
+                "MAP"
+            }
+        ));
+        map_count += 1;
+    }
+
+    for struct_map in &struct_maps {
+        let (struct_map_count, struct_axis_count) = create_struct_maps(
+            module,
+            &mut recordlayout_info,
+            debug_data,
+            struct_map,
+            version,
+            log_msgs,
+        );
+        map_count += struct_map_count;
+        axis_count += struct_axis_count;
+    }
+
+    Ok((map_count, axis_count))
+}
+
+// With --enable-structures, a map whose axes are members of the same struct as the map itself is
+// created inside a TYPEDEF_STRUCTURE. Returns the symbol name of the struct variable.
+fn get_struct_parent(def: &SharedAxisDef) -> Option<&str> {
+    let (parent, _) = def.map_symbol.rsplit_once('.')?;
+    def.axis_symbols
+        .iter()
+        .all(|axis| {
+            axis.rsplit_once('.')
+                .is_some_and(|(axis_parent, _)| axis_parent == parent)
+        })
+        .then_some(parent)
+}
+
+// the name of a struct member inside its struct, e.g. "speed_axis" for "Calibration.speed_axis"
+fn member_name(symbol: &str) -> &str {
+    symbol.rsplit_once('.').map_or(symbol, |(_, member)| member)
+}
+
+// check the map of one definition and its axes, and add them to the struct variable they belong to
+fn add_struct_map<'dbg>(
+    struct_maps: &mut Vec<StructMaps<'dbg>>,
+    parent: &str,
+    def: &SharedAxisDef,
+    debug_data: &'dbg DebugData,
+) -> Result<(), String> {
+    let struct_idx = if let Some(idx) = struct_maps.iter().position(|sm| sm.name == parent) {
+        idx
+    } else {
+        let sym_info = find_symbol(parent, debug_data)
+            .map_err(|errmsg| format!("the symbol {parent} could not be found: {errmsg}"))?;
+        let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+        if !matches!(typeinfo.datatype, DbgDataType::Struct { .. }) {
+            return Err(format!("the symbol {parent} is not a struct"));
+        }
+        struct_maps.push(StructMaps {
+            name: parent.to_string(),
+            sym_info,
+            typeinfo,
+            axes: Vec::new(),
+            maps: Vec::new(),
+        });
+        struct_maps.len() - 1
+    };
+    let struct_map = &mut struct_maps[struct_idx];
+
+    let map = get_array_symbol(&def.map_symbol, debug_data)?;
+    if map.dim.len() != def.axis_symbols.len() {
+        return Err(format!(
+            "{} has {} dimensions, but {} axes are given",
+            def.map_symbol,
+            map.dim.len(),
+            def.axis_symbols.len()
+        ));
+    }
+    let mut axis_indices = Vec::new();
+    for (axis_idx, axis_symbol) in def.axis_symbols.iter().enumerate() {
+        let idx = if let Some(idx) = struct_map
+            .axes
+            .iter()
+            .position(|axis| axis.name == *axis_symbol)
+        {
+            idx
+        } else {
+            let axis = get_array_symbol(axis_symbol, debug_data)?;
+            if axis.dim.len() != 1 {
+                return Err(format!(
+                    "the axis {axis_symbol} is not a one-dimensional array"
+                ));
+            }
+            struct_map.axes.push(axis);
+            struct_map.axes.len() - 1
+        };
+        check_axis_size(&map, axis_idx, &struct_map.axes[idx])?;
+        axis_indices.push(idx);
+    }
+    struct_map.maps.push((map, axis_indices));
+    Ok(())
+}
+
+// the x axis belongs to the last (fastest changing) dimension of the array
+// MAX_AXIS_POINTS is a UWORD, so larger axes can't be described in the a2l file
+fn check_axis_size(map: &ArraySymbol, axis_idx: usize, axis: &ArraySymbol) -> Result<(), String> {
+    let map_dim = map.dim[map.dim.len() - 1 - axis_idx];
+    if axis.dim[0] != map_dim {
+        Err(format!(
+            "the axis {} has {} points, but {} expects {map_dim}",
+            axis.name, axis.dim[0], map.name
+        ))
+    } else if axis.dim[0] > u64::from(u16::MAX) {
+        Err(format!(
+            "the axis {} has {} points, but an axis can have at most {} points",
+            axis.name,
+            axis.dim[0],
+            u16::MAX
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// find a symbol, and make sure that it is an array of a simple data type
+fn get_array_symbol<'dbg>(
+    symbol: &str,
+    debug_data: &'dbg DebugData,
+) -> Result<ArraySymbol<'dbg>, String> {
+    let sym_info = find_symbol(symbol, debug_data)
+        .map_err(|errmsg| format!("the symbol {symbol} could not be found: {errmsg}"))?;
+    let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+    let DbgDataType::Array { dim, arraytype, .. } = &typeinfo.datatype else {
+        return Err(format!("the symbol {symbol} is not an array"));
+    };
+    let elemtype = arraytype.get_reference(&debug_data.types);
+    if !matches!(
+        elemtype.datatype,
+        DbgDataType::Uint8
+            | DbgDataType::Uint16
+            | DbgDataType::Uint32
+            | DbgDataType::Uint64
+            | DbgDataType::Sint8
+            | DbgDataType::Sint16
+            | DbgDataType::Sint32
+            | DbgDataType::Sint64
+            | DbgDataType::Float
+            | DbgDataType::Double
+    ) {
+        return Err(format!(
+            "the elements of {symbol} are not numbers, so it can not be used as a curve, map or axis"
+        ));
+    }
+    Ok(ArraySymbol {
+        name: symbol.to_string(),
+        sym_info,
+        dim: dim.clone(),
+        elemtype,
+    })
+}
+
+// the names of AXIS_PTS, CHARACTERISTICs, MEASUREMENTs, INSTANCEs and BLOBs share a namespace
+fn check_unique_name(module: &Module, name: &str) -> Result<(), String> {
+    if module.axis_pts.iter().any(|item| item.name == name)
+        || module.characteristic.iter().any(|item| item.name == name)
+        || module.measurement.iter().any(|item| item.name == name)
+        || module.instance.iter().any(|item| item.name == name)
+        || module.blob.iter().any(|item| item.name == name)
+    {
+        Err(format!("an object named {name} already exists"))
+    } else {
+        Ok(())
+    }
+}
+
+// all TYPEDEF_* share a separate namespace
+fn check_unique_typedef_name(module: &Module, name: &str) -> Result<(), String> {
+    if module.typedef_axis.iter().any(|item| item.name == name)
+        || module.typedef_blob.iter().any(|item| item.name == name)
+        || module
+            .typedef_characteristic
+            .iter()
+            .any(|item| item.name == name)
+        || module
+            .typedef_measurement
+            .iter()
+            .any(|item| item.name == name)
+        || module
+            .typedef_structure
+            .iter()
+            .any(|item| item.name == name)
+    {
+        Err(format!("a TYPEDEF named {name} already exists"))
+    } else {
+        Ok(())
+    }
+}
+
+fn make_struct_typedef_name(struct_map: &StructMaps) -> String {
+    struct_map
+        .typeinfo
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{}_type", struct_map.name.replace("::", "__")))
+}
+
+fn create_axis_pts(
+    module: &mut Module,
+    recordlayout_info: &mut RecordLayoutInfo,
+    debug_data: &DebugData,
+    axis: &ArraySymbol,
+    item_name: &str,
+    version: A2lVersion,
+) -> AxisPts {
+    let datatype = get_a2l_datatype(axis.elemtype);
+    let deposit_record = create_default_record_layout(module, recordlayout_info, datatype, true);
+    let (lower_limit, upper_limit) = get_type_limits(axis.elemtype, f64::MIN, f64::MAX);
+    let mut axis_pts = AxisPts::new(
+        item_name.to_string(),
+        format!("axis points of {}", axis.name),
+        axis.sym_info.address as u32,
+        "NO_INPUT_QUANTITY".to_string(),
+        deposit_record,
+        0f64,
+        "NO_COMPU_METHOD".to_string(),
+        axis.dim[0] as u16,
+        lower_limit,
+        upper_limit,
+    );
+    // enable hex mode for the address (item 2 in the AXIS_PTS)
+    axis_pts.get_layout_mut().item_location.2 .1 = true;
+    set_variable_byte_order(
+        &mut axis_pts.byte_order,
+        module,
+        debug_data,
+        axis.elemtype,
+        version,
+    );
+    if version >= A2lVersion::V1_6_0 {
+        let symbol_link_text = make_symbol_link_string(&axis.sym_info, debug_data);
+        axis_pts.symbol_link = Some(SymbolLink::new(symbol_link_text, 0));
+    }
+    axis_pts
+}
+
+// an AXIS_DESCR of type COM_AXIS gets its properties from the referenced AXIS_PTS
+fn make_com_axis_descr(axis_pts: &AxisPts) -> AxisDescr {
+    let mut axis_descr = AxisDescr::new(
+        AxisDescrAttribute::ComAxis,
+        axis_pts.input_quantity.clone(),
+        axis_pts.conversion.clone(),
+        axis_pts.max_axis_points,
+        axis_pts.lower_limit,
+        axis_pts.upper_limit,
+    );
+    axis_descr.axis_pts_ref = Some(AxisPtsRef::new(axis_pts.name.clone()));
+    axis_descr
+}
+
+fn create_map_characteristic(
+    module: &mut Module,
+    recordlayout_info: &mut RecordLayoutInfo,
+    debug_data: &DebugData,
+    map: &ArraySymbol,
+    item_name: &str,
+    axis_descr: Vec<AxisDescr>,
+    version: A2lVersion,
+) -> Characteristic {
+    let (ctype, description) = if map.dim.len() == 1 {
+        (CharacteristicType::Curve, "curve")
+    } else {
+        (CharacteristicType::Map, "map")
+    };
+    let datatype = get_a2l_datatype(map.elemtype);
+    // the axis points are stored separately, so the RECORD_LAYOUT only contains FNC_VALUES
+    let deposit = create_default_record_layout(module, recordlayout_info, datatype, false);
+    let (lower_limit, upper_limit) = get_type_limits(map.elemtype, f64::MIN, f64::MAX);
+    let mut characteristic = Characteristic::new(
+        item_name.to_string(),
+        format!("{description} for {}", map.name),
+        ctype,
+        map.sym_info.address as u32,
+        deposit,
+        0f64,
+        "NO_COMPU_METHOD".to_string(),
+        lower_limit,
+        upper_limit,
+    );
+    characteristic.axis_descr = axis_descr;
+    // enable hex mode for the address (item 3 in the CHARACTERISTIC)
+    characteristic.get_layout_mut().item_location.3 .1 = true;
+    set_variable_byte_order(
+        &mut characteristic.byte_order,
+        module,
+        debug_data,
+        map.elemtype,
+        version,
+    );
+    if version >= A2lVersion::V1_6_0 {
+        let symbol_link_text = make_symbol_link_string(&map.sym_info, debug_data);
+        characteristic.symbol_link = Some(SymbolLink::new(symbol_link_text, 0));
+    }
+    characteristic
+}
+
+// Create the TYPEDEF_STRUCTURE for a struct variable with maps and shared axes, and an INSTANCE of it.
+// Each axis is a TYPEDEF_AXIS component, which exists only once in the struct even if several maps use it.
+// The maps are TYPEDEF_CHARACTERISTICs whose COM_AXIS AXIS_DESCRs reference the axis components by name,
+// i.e. the address of the axis is relative to the instance. Other members of the struct are not included.
+// Returns the number of created TYPEDEF_CHARACTERISTICs and TYPEDEF_AXIS.
+fn create_struct_maps(
+    module: &mut Module,
+    recordlayout_info: &mut RecordLayoutInfo,
+    debug_data: &DebugData,
+    struct_map: &StructMaps,
+    version: A2lVersion,
+    log_msgs: &mut Vec<String>,
+) -> (usize, usize) {
+    let typedef_name = make_struct_typedef_name(struct_map);
+    let mut td_struct = TypedefStructure::new(
+        typedef_name.clone(),
+        String::new(),
+        struct_map.typeinfo.get_size() as u32,
+    );
+    // display item .2 (size) in hex
+    td_struct.get_layout_mut().item_location.2 = (1, true);
+    // the SYMBOL_TYPE_LINK allows the typedef update to find the struct type again
+    if let Some(type_name) = &struct_map.typeinfo.name {
+        td_struct.symbol_type_link = Some(SymbolTypeLink::new(type_name.clone()));
+    }
+    let add_component =
+        |td_struct: &mut TypedefStructure, symbol: &ArraySymbol, component_type: String| {
+            let offset = symbol.sym_info.address - struct_map.sym_info.address;
+            let mut sc = StructureComponent::new(
+                member_name(&symbol.name).to_string(),
+                component_type,
+                offset as u32,
+            );
+            sc.symbol_type_link = Some(SymbolTypeLink::new(member_name(&symbol.name).to_string()));
+            let layout = sc.get_layout_mut();
+            layout.start_offset = 1; // only one newline before this block -- i.e. no empty lines
+            layout.item_location.2 = (1, false); // offset is placed on a new line, not displayd as hex
+            td_struct.structure_component.push(sc);
+        };
+
+    for axis in &struct_map.axes {
+        let name = format!("{typedef_name}_{}", member_name(&axis.name));
+        let datatype = get_a2l_datatype(axis.elemtype);
+        let record_layout = create_default_record_layout(module, recordlayout_info, datatype, true);
+        let (lower_limit, upper_limit) = get_type_limits(axis.elemtype, f64::MIN, f64::MAX);
+        let mut td_axis = TypedefAxis::new(
+            name.clone(),
+            format!("axis points of {}", axis.name),
+            "NO_INPUT_QUANTITY".to_string(),
+            record_layout,
+            0f64,
+            "NO_COMPU_METHOD".to_string(),
+            axis.dim[0] as u16,
+            lower_limit,
+            upper_limit,
+        );
+        set_variable_byte_order(
+            &mut td_axis.byte_order,
+            module,
+            debug_data,
+            axis.elemtype,
+            version,
+        );
+        module.typedef_axis.push(td_axis);
+        add_component(&mut td_struct, axis, name.clone());
+        log_msgs.push(format!("Created TYPEDEF_AXIS {name}"));
+    }
+
+    for (map, axis_indices) in &struct_map.maps {
+        let name = format!("{typedef_name}_{}", member_name(&map.name));
+        let ctype = if map.dim.len() == 1 {
+            CharacteristicType::Curve
+        } else {
+            CharacteristicType::Map
+        };
+        let datatype = get_a2l_datatype(map.elemtype);
+        let record_layout =
+            create_default_record_layout(module, recordlayout_info, datatype, false);
+        let (lower_limit, upper_limit) = get_type_limits(map.elemtype, f64::MIN, f64::MAX);
+        let mut td_char = TypedefCharacteristic::new(
+            name.clone(),
+            String::new(),
+            ctype,
+            record_layout,
+            0f64,
+            "NO_COMPU_METHOD".to_string(),
+            lower_limit,
+            upper_limit,
+        );
+        // the AXIS_PTS_REF names the axis component in the same structure
+        td_char.axis_descr = axis_indices
+            .iter()
+            .map(|idx| {
+                let axis = &struct_map.axes[*idx];
+                let (lower_limit, upper_limit) = get_type_limits(axis.elemtype, f64::MIN, f64::MAX);
+                let mut axis_descr = AxisDescr::new(
+                    AxisDescrAttribute::ComAxis,
+                    "NO_INPUT_QUANTITY".to_string(),
+                    "NO_COMPU_METHOD".to_string(),
+                    axis.dim[0] as u16,
+                    lower_limit,
+                    upper_limit,
+                );
+                axis_descr.axis_pts_ref =
+                    Some(AxisPtsRef::new(member_name(&axis.name).to_string()));
+                axis_descr
+            })
+            .collect();
+        set_variable_byte_order(
+            &mut td_char.byte_order,
+            module,
+            debug_data,
+            map.elemtype,
+            version,
+        );
+        module.typedef_characteristic.push(td_char);
+        add_component(&mut td_struct, map, name.clone());
+        log_msgs.push(format!(
+            "Created TYPEDEF_CHARACTERISTIC {name} with shared axes"
+        ));
+    }
+    module.typedef_structure.push(td_struct);
+    log_msgs.push(format!("Created TYPEDEF_STRUCTURE {typedef_name}"));
+
+    let instance_name = struct_map.name.replace("::", "__");
+    let mut instance = Instance::new(
+        instance_name.clone(),
+        format!("instance for symbol {}", struct_map.name),
+        typedef_name,
+        struct_map.sym_info.address as u32,
+    );
+    let symbol_link_text = make_symbol_link_string(&struct_map.sym_info, debug_data);
+    instance.symbol_link = Some(SymbolLink::new(symbol_link_text, 0));
+    // set the eddress of the new instance to be witten as hex
+    instance.get_layout_mut().item_location.3 = (0, true);
+    module.instance.push(instance);
+    log_msgs.push(format!("Created INSTANCE {instance_name}"));
+
+    (struct_map.maps.len(), struct_map.axes.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::update::typedef::get_typedef_axis_for_axis_descr;
+    use crate::update::{update_a2l, UpdateMode, UpdateOptions, UpdateType};
+    use std::ffi::OsString;
+
+    static DEFINITIONS: &str = r#"
+# map symbol; x axis symbol [; y axis symbol]
+Ignition_Map; Speed_Axis; Load_Axis
+Fuel_Map; Speed_Axis; Load_Axis
+Idle_Curve; Speed_Axis
+"#;
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(
+            definitions[0],
+            SharedAxisDef {
+                map_symbol: "Ignition_Map".to_string(),
+                axis_symbols: vec!["Speed_Axis".to_string(), "Load_Axis".to_string()],
+            }
+        );
+        assert_eq!(definitions[2].axis_symbols, vec!["Speed_Axis".to_string()]);
+
+        assert!(parse_definitions("Idle_Curve").is_err());
+        assert!(parse_definitions("Idle_Curve; ").is_err());
+        assert!(parse_definitions("Idle_Curve; Speed_Axis\nIdle_Curve; Load_Axis").is_err());
+        assert!(parse_definitions("Idle_Curve; Idle_Curve").is_err());
+    }
+
+    #[test]
+    fn test_create_shared_axis_maps() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/shared_axes_test.elf"), false)
+                .unwrap();
+        let mut a2l_file = a2lfile::new();
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+        let mut log_msgs = Vec::new();
+        let (map_count, axis_count) = create_shared_axis_maps(
+            &mut a2l_file,
+            &debug_data,
+            &definitions,
+            false,
+            &mut log_msgs,
+        )
+        .unwrap();
+        assert_eq!(map_count, 3);
+        assert_eq!(axis_count, 2);
+
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.axis_pts.len(), 2);
+        assert_eq!(module.axis_pts[0].name, "Speed_Axis");
+        assert_eq!(module.axis_pts[0].max_axis_points, 6);
+        assert_eq!(module.axis_pts[1].name, "Load_Axis");
+        assert_eq!(module.axis_pts[1].max_axis_points, 4);
+
+        // both maps reference the same AXIS_PTS through COM_AXIS
+        for characteristic in &module.characteristic[0..2] {
+            assert_eq!(characteristic.characteristic_type, CharacteristicType::Map);
+            assert_eq!(characteristic.axis_descr.len(), 2);
+            for (axis_descr, axis_name) in characteristic
+                .axis_descr
+                .iter()
+                .zip(["Speed_Axis", "Load_Axis"])
+            {
+                assert_eq!(axis_descr.attribute, AxisDescrAttribute::ComAxis);
+                assert_eq!(
+                    axis_descr.axis_pts_ref.as_ref().unwrap().axis_points,
+                    axis_name
+                );
+            }
+        }
+        let curve = &module.characteristic[2];
+        assert_eq!(curve.characteristic_type, CharacteristicType::Curve);
+        assert_eq!(curve.axis_descr[0].max_axis_points, 6);
+
+        // the RECORD_LAYOUTs of the maps only contain FNC_VALUES
+        let map_layout = module
+            .record_layout
+            .iter()
+            .find(|rl| rl.name == module.characteristic[0].deposit)
+            .unwrap();
+        assert!(map_layout.fnc_values.is_some());
+        assert!(map_layout.axis_pts_x.is_none());
+        assert!(map_layout.axis_pts_y.is_none());
+
+        // applying the definitions again reuses the AXIS_PTS, but the CHARACTERISTICs already exist
+        let result = create_shared_axis_maps(
+            &mut a2l_file,
+            &debug_data,
+            &definitions[..1],
+            false,
+            &mut log_msgs,
+        );
+        assert!(result.is_err());
+        assert_eq!(a2l_file.project.module[0].axis_pts.len(), 2);
+
+        // the size of the axis must match the size of the curve
+        let definitions = parse_definitions("Short_Curve; Speed_Axis").unwrap();
+        let error = create_shared_axis_maps(
+            &mut a2lfile::new(),
+            &debug_data,
+            &definitions,
+            false,
+            &mut log_msgs,
+        )
+        .unwrap_err();
+        assert!(error.contains("Short_Curve expects 5"));
+    }
+
+    #[test]
+    fn test_create_shared_axis_maps_in_struct() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/shared_axes_test.elf"), false)
+                .unwrap();
+        let mut a2l_file = a2lfile::new();
+        let definitions = parse_definitions(
+            "Engine_Calibration.ignition_map; Engine_Calibration.speed_axis; Engine_Calibration.load_axis\n\
+             Engine_Calibration.fuel_map; Engine_Calibration.speed_axis; Engine_Calibration.load_axis\n",
+        )
+        .unwrap();
+        let mut log_msgs = Vec::new();
+        let (map_count, axis_count) = create_shared_axis_maps(
+            &mut a2l_file,
+            &debug_data,
+            &definitions,
+            true,
+            &mut log_msgs,
+        )
+        .unwrap();
+        assert_eq!(map_count, 2);
+        assert_eq!(axis_count, 2);
+
+        let module = &a2l_file.project.module[0];
+        assert!(module.axis_pts.is_empty());
+        assert!(module.characteristic.is_empty());
+        assert_eq!(module.typedef_axis.len(), 2);
+        assert_eq!(module.typedef_characteristic.len(), 2);
+        assert_eq!(module.instance.len(), 1);
+        assert_eq!(module.instance[0].name, "Engine_Calibration");
+        assert_eq!(module.instance[0].type_ref, "engine_calibration");
+
+        // each axis is only present once in the structure
+        let td_struct = &module.typedef_structure[0];
+        assert_eq!(td_struct.name, "engine_calibration");
+        assert_eq!(td_struct.structure_component.len(), 4);
+        assert_eq!(
+            td_struct.structure_component[0].component_name,
+            "speed_axis"
+        );
+        assert_eq!(td_struct.structure_component[0].address_offset, 0);
+        assert_eq!(td_struct.structure_component[1].component_name, "load_axis");
+        assert_eq!(td_struct.structure_component[1].address_offset, 12);
+        assert_eq!(td_struct.structure_component[3].address_offset, 68);
+
+        // both maps resolve their axes to the same TYPEDEF_AXIS components of the structure
+        for td_char in &module.typedef_characteristic {
+            assert_eq!(td_char.characteristic_type, CharacteristicType::Map);
+            let td_axis =
+                get_typedef_axis_for_axis_descr(module, td_struct, &td_char.axis_descr[0]).unwrap();
+            assert_eq!(td_axis.name, "engine_calibration_speed_axis");
+            assert_eq!(td_axis.max_axis_points, 6);
+            let td_axis =
+                get_typedef_axis_for_axis_descr(module, td_struct, &td_char.axis_descr[1]).unwrap();
+            assert_eq!(td_axis.name, "engine_calibration_load_axis");
+            assert_eq!(td_char.axis_descr[1].max_axis_points, 4);
+        }
+
+        // without --enable-structures the axes are created as AXIS_PTS
+        let mut a2l_file = a2lfile::new();
+        create_shared_axis_maps(
+            &mut a2l_file,
+            &debug_data,
+            &definitions,
+            false,
+            &mut log_msgs,
+        )
+        .unwrap();
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.axis_pts.len(), 2);
+        assert!(module.typedef_structure.is_empty());
+    }
+
+    #[test]
+    fn test_update_shared_typedef_axis() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/shared_axes_test.elf"), false)
+                .unwrap();
+        let mut a2l_file = a2lfile::new();
+        let definitions = parse_definitions(
+            "Engine_Calibration.ignition_map; Engine_Calibration.speed_axis; Engine_Calibration.load_axis\n",
+        )
+        .unwrap();
+        create_shared_axis_maps(
+            &mut a2l_file,
+            &debug_data,
+            &definitions,
+            true,
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        // the load axis is no longer used as a shared axis, so the update must not change it
+        let module = &mut a2l_file.project.module[0];
+        module.typedef_characteristic[0].axis_descr[1].axis_pts_ref = None;
+        module.typedef_axis[0].max_axis_points = 3;
+        module.typedef_axis[1].max_axis_points = 9;
+
+        let options = UpdateOptions::new(UpdateType::Full, UpdateMode::Default, true);
+        let mut log_msgs = Vec::new();
+        update_a2l(&mut a2l_file, &debug_data, &mut log_msgs, &options);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.typedef_axis[0].name, "engine_calibration_speed_axis");
+        assert_eq!(module.typedef_axis[0].max_axis_points, 6);
+        assert_eq!(module.typedef_axis[1].name, "engine_calibration_load_axis");
+        assert_eq!(module.typedef_axis[1].max_axis_points, 9);
+        let axis_msgs: Vec<_> = log_msgs
+            .iter()
+            .filter(|msg| msg.contains("TYPEDEF_AXIS"))
+            .collect();
+        assert_eq!(axis_msgs.len(), 1);
+        assert!(axis_msgs[0].contains("MAX_AXIS_POINTS changed from 3 to 6"));
+
+        // nothing is reported if the TYPEDEF_AXIS is already up to date
+        let mut log_msgs = Vec::new();
+        update_a2l(&mut a2l_file, &debug_data, &mut log_msgs, &options);
+        assert!(!log_msgs.iter().any(|msg| msg.contains("TYPEDEF_AXIS")));
+    }
+
+    #[test]
+    fn test_check_axis_size() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/shared_axes_test.elf"), false)
+                .unwrap();
+        let map = get_array_symbol("Engine_Calibration.ignition_map", &debug_data).unwrap();
+        let mut axis = get_array_symbol("Engine_Calibration.speed_axis", &debug_data).unwrap();
+        assert!(check_axis_size(&map, 0, &axis).is_ok());
+        assert!(check_axis_size(&map, 1, &axis).is_err());
+
+        // MAX_AXIS_POINTS can't describe more than 65535 points
+        let mut map = map;
+        let last = map.dim.len() - 1;
+        map.dim[last] = 70000;
+        axis.dim[0] = 70000;
+        let errmsg = check_axis_size(&map, 0, &axis).unwrap_err();
+        assert!(errmsg.contains("at most 65535 points"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::ifdata::A2mlVector;
+use crate::xcp::{format_event_cycle, JsonValue};
+use a2lfile::{A2lFile, IfData, Measurement, Module};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SignalListFormat {
+    Csv,
+    Json,
+}
+
+// one row of the signal list
+#[derive(Debug, PartialEq)]
+struct SignalInfo {
+    name: String,
+    unit: String,
+    datatype: String,
+    address: Option<u32>,
+    bit_mask: Option<u64>,
+    event: Option<EventInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct EventInfo {
+    number: u16,
+    name: Option<String>,
+    cycle: Option<String>,
+}
+
+/// Write a list of all MEASUREMENTs to a file, as a starting point for a measurement configuration.
+/// If a selection is given, only the MEASUREMENTs whose names are in the selection are exported.
+/// Returns the number of exported MEASUREMENTs.
+pub(crate) fn export_signal_list(
+    a2l_file: &A2lFile,
+    filename: &OsStr,
+    format: SignalListFormat,
+    selection: Option<&HashSet<String>>,
+) -> Result<usize, String> {
+    let signals: Vec<SignalInfo> = a2l_file
+        .project
+        .module
+        .iter()
+        .flat_map(|module| collect_signals(module, selection))
+        .collect();
+    let text = match format {
+        SignalListFormat::Csv => format_csv(&signals),
+        SignalListFormat::Json => format_json(&signals),
+    };
+    std::fs::write(filename, text).map_err(|err| {
+        format!(
+            "Failed to write the signal list to {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    Ok(signals.len())
+}
+
+fn collect_signals(module: &Module, selection: Option<&HashSet<String>>) -> Vec<SignalInfo> {
+    let events = get_module_events(module);
+    module
+        .measurement
+        .iter()
+        .filter(|measurement| selection.is_none_or(|names| names.contains(&measurement.name)))
+        .map(|measurement| {
+            let event = get_measurement_event(&measurement.if_data).map(|number| {
+                events.get(&number).cloned().unwrap_or(EventInfo {
+                    number,
+                    name: None,
+                    cycle: None,
+                })
+            });
+            SignalInfo {
+                name: measurement.name.clone(),
+                unit: get_unit(module, measurement),
+                datatype: measurement.datatype.to_string(),
+                address: measurement
+                    .ecu_address
+                    .as_ref()
+                    .map(|ecu_address| ecu_address.address),
+                bit_mask: measurement
+                    .bit_mask
+                    .as_ref()
+                    .map(|bit_mask| u64::from(bit_mask.mask)),
+                event,
+            }
+        })
+        .collect()
+}
+
+// the PHYS_UNIT of the MEASUREMENT overrides the unit of its COMPU_METHOD
+fn get_unit(module: &Module, measurement: &Measurement) -> String {
+    if let Some(phys_unit) = &measurement.phys_unit {
+        return phys_unit.unit.clone();
+    }
+    module
+        .compu_method
+        .iter()
+        .find(|compu_method| compu_method.name == measurement.conversion)
+        .map(|compu_method| compu_method.unit.clone())
+        .unwrap_or_default()
+}
+
+// the DAQ events that are defined in the IF_DATA XCP / XCPplus of the module
+fn get_module_events(module: &Module) -> HashMap<u16, EventInfo> {
+    let mut events = HashMap::new();
+    for ifdata in &module.if_data {
+        let Some(decoded) = A2mlVector::load_from_ifdata(ifdata) else {
+            continue;
+        };
+        let daq_list = [
+            decoded.xcp.and_then(|xcp| xcp.daq),
+            decoded.xcpplus.and_then(|xcpplus| xcpplus.daq),
+        ];
+        for daq in daq_list.into_iter().flatten() {
+            for event in daq.event {
+                events.insert(
+                    event.event_channel_number,
+                    EventInfo {
+                        number: event.event_channel_number,
+                        name: Some(event.event_channel_name),
+                        cycle: format_event_cycle(event.time_cycle, event.time_unit),
+                    },
+                );
+            }
+        }
+    }
+    events
+}
+
+// get the DAQ event of a MEASUREMENT from its IF_DATA XCP.
+// With a FIXED_EVENT_LIST the first event is used, otherwise the first event of the DEFAULT_EVENT_LIST.
+fn get_measurement_event(ifdata_vec: &[IfData]) -> Option<u16> {
+    ifdata_vec.iter().find_map(|ifdata| {
+        let daq_event = A2mlVector::load_from_ifdata(ifdata)?.xcp?.daq_event?;
+        if let Some(fixed_event_list) = daq_event.fixed_event_list {
+            fixed_event_list
+                .event
+                .first()
+                .map(|event| event.event_number)
+        } else {
+            daq_event
+                .variable?
+                .default_event_list?
+                .event
+                .first()
+                .map(|event| event.event_number)
+        }
+    })
+}
+
+fn format_csv(signals: &[SignalInfo]) -> String {
+    let mut output = String::from("name,unit,datatype,address,bitmask,event,event name,cycle\n");
+    for signal in signals {
+        let event = signal.event.as_ref();
+        output.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv_field(&signal.name),
+            csv_field(&signal.unit),
+            signal.datatype,
+            signal
+                .address
+                .map(|address| format!("0x{address:X}"))
+                .unwrap_or_default(),
+            signal
+                .bit_mask
+                .map(|bit_mask| format!("0x{bit_mask:X}"))
+                .unwrap_or_default(),
+            event
+                .map(|event| event.number.to_string())
+                .unwrap_or_default(),
+            csv_field(event.and_then(|event| event.name.as_deref()).unwrap_or("")),
+            event.and_then(|event| event.cycle.as_deref()).unwrap_or(""),
+        ));
+    }
+    output
+}
+
+// quote a CSV field if it contains separators or quotes
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// values that are not available are omitted
+fn format_json(signals: &[SignalInfo]) -> String {
+    let values = signals
+        .iter()
+        .map(|signal| {
+            let mut items = vec![
+                ("name", JsonValue::String(signal.name.clone())),
+                ("unit", JsonValue::String(signal.unit.clone())),
+                ("datatype", JsonValue::String(signal.datatype.clone())),
+            ];
+            if let Some(address) = signal.address {
+                items.push(("address", JsonValue::Number(u64::from(address))));
+            }
+            if let Some(bit_mask) = signal.bit_mask {
+                items.push(("bitmask", JsonValue::Number(bit_mask)));
+            }
+            if let Some(event) = &signal.event {
+                let mut event_items = vec![("number", JsonValue::Number(u64::from(event.number)))];
+                if let Some(name) = &event.name {
+                    event_items.push(("name", JsonValue::String(name.clone())));
+                }
+                if let Some(cycle) = &event.cycle {
+                    event_items.push(("cycle", JsonValue::String(cycle.clone())));
+                }
+                items.push(("event", JsonValue::Object(event_items)));
+            }
+            JsonValue::Object(items)
+        })
+        .collect();
+    let mut output = String::new();
+    JsonValue::Object(vec![("signals", JsonValue::Array(values))]).write(&mut output, 0);
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin IF_DATA XCP
+      /begin DAQ DYNAMIC 0 2 0 OPTIMISATION_TYPE_DEFAULT ADDRESS_EXTENSION_FREE IDENTIFICATION_FIELD_TYPE_ABSOLUTE GRANULARITY_ODT_ENTRY_SIZE_DAQ_BYTE 8 NO_OVERLOAD_INDICATION
+        /begin EVENT "10ms" "10ms" 1 DAQ 1 10 6 0
+        /end EVENT
+        /begin EVENT "100ms" "100ms" 2 DAQ 1 100 6 0
+        /end EVENT
+      /end DAQ
+    /end IF_DATA
+    /begin MEASUREMENT Speed "" UWORD CM_Speed 0 0 0 300
+      ECU_ADDRESS 0x1000
+      /begin IF_DATA XCP
+        /begin DAQ_EVENT FIXED_EVENT_LIST EVENT 2 /end DAQ_EVENT
+      /end IF_DATA
+    /end MEASUREMENT
+    /begin MEASUREMENT Gear "" UBYTE NO_COMPU_METHOD 0 0 0 8
+      ECU_ADDRESS 0x1002
+      BIT_MASK 0x0F
+      PHYS_UNIT "-"
+    /end MEASUREMENT
+    /begin MEASUREMENT Other "" SLONG NO_COMPU_METHOD 0 0 0 8
+    /end MEASUREMENT
+    /begin COMPU_METHOD CM_Speed "" LINEAR "%6.2" "km/h"
+      COEFFS_LINEAR 1 0
+    /end COMPU_METHOD
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_collect_signals() {
+        let a2l_file = a2lfile::load_from_string(
+            A2L_TEXT,
+            Some(crate::ifdata::A2MLVECTOR_TEXT.to_string()),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let module = &a2l_file.project.module[0];
+        let selection: HashSet<String> = ["Speed".to_string(), "Gear".to_string()]
+            .into_iter()
+            .collect();
+        let signals = collect_signals(module, Some(&selection));
+        assert_eq!(
+            signals,
+            vec![
+                SignalInfo {
+                    name: "Speed".to_string(),
+                    unit: "km/h".to_string(),
+                    datatype: "UWORD".to_string(),
+                    address: Some(0x1000),
+                    bit_mask: None,
+                    event: Some(EventInfo {
+                        number: 2,
+                        name: Some("100ms".to_string()),
+                        cycle: Some("100ms".to_string()),
+                    }),
+                },
+                SignalInfo {
+                    name: "Gear".to_string(),
+                    unit: "-".to_string(),
+                    datatype: "UBYTE".to_string(),
+                    address: Some(0x1002),
+                    bit_mask: Some(0x0F),
+                    event: None,
+                },
+            ]
+        );
+        assert_eq!(collect_signals(module, None).len(), 3);
+
+        let csv = format_csv(&signals);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("name,unit,datatype,address,bitmask,event,event name,cycle")
+        );
+        assert_eq!(lines.next(), Some("Speed,km/h,UWORD,0x1000,,2,100ms,100ms"));
+        assert_eq!(lines.next(), Some("Gear,-,UBYTE,0x1002,0xF,,,"));
+
+        let json = format_json(&signals);
+        assert!(json.contains("\"name\": \"Speed\""));
+        assert!(json.contains("\"bitmask\": 15"));
+    }
+}
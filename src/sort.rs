@@ -0,0 +1,93 @@
+use a2lfile::{A2lFile, A2lObject};
+
+// Sort the items of every module by name. Unlike A2lFile::sort(), items with equal names keep
+// their original relative order, so sorting a file that is already sorted does not change it.
+pub(crate) fn sort_stable(a2l_file: &mut A2lFile) {
+    sort_by_name(&mut a2l_file.project.module, |module| &module.name);
+    for module in &mut a2l_file.project.module {
+        sort_by_name(&mut module.axis_pts, |item| &item.name);
+        sort_by_name(&mut module.blob, |item| &item.name);
+        sort_by_name(&mut module.characteristic, |item| &item.name);
+        sort_by_name(&mut module.compu_method, |item| &item.name);
+        sort_by_name(&mut module.compu_tab, |item| &item.name);
+        sort_by_name(&mut module.compu_vtab, |item| &item.name);
+        sort_by_name(&mut module.compu_vtab_range, |item| &item.name);
+        sort_by_name(&mut module.frame, |item| &item.name);
+        sort_by_name(&mut module.function, |item| &item.name);
+        sort_by_name(&mut module.group, |item| &item.name);
+        sort_by_name(&mut module.instance, |item| &item.name);
+        sort_by_name(&mut module.measurement, |item| &item.name);
+        sort_by_name(&mut module.record_layout, |item| &item.name);
+        sort_by_name(&mut module.transformer, |item| &item.name);
+        sort_by_name(&mut module.typedef_axis, |item| &item.name);
+        sort_by_name(&mut module.typedef_blob, |item| &item.name);
+        sort_by_name(&mut module.typedef_characteristic, |item| &item.name);
+        sort_by_name(&mut module.typedef_measurement, |item| &item.name);
+        sort_by_name(&mut module.typedef_structure, |item| &item.name);
+        sort_by_name(&mut module.unit, |item| &item.name);
+        sort_by_name(&mut module.user_rights, |item| &item.user_level_id);
+    }
+}
+
+fn sort_by_name<T, L>(items: &mut [T], get_name: impl Fn(&T) -> &String)
+where
+    T: A2lObject<L>,
+{
+    // slice::sort_by is a stable sort
+    items.sort_by(|a, b| get_name(a).cmp(get_name(b)));
+    // The writer places items that were loaded from a file at their original position, which is
+    // given by the uid and the line number. Once both are cleared, the items are written in the order of the list instead.
+    for item in items {
+        item.get_layout_mut().uid = 0;
+        item.get_layout_mut().line = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin GROUP b "second"
+    /end GROUP
+    /begin GROUP a "first"
+    /end GROUP
+    /begin GROUP c "third"
+    /end GROUP
+  /end MODULE
+/end PROJECT
+"#;
+
+    #[test]
+    fn test_sort_stable() {
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        // add a second group named "b", which must remain behind the original one
+        let mut duplicate = a2l_file.project.module[0].group[0].clone();
+        duplicate.long_identifier = "duplicate".to_string();
+        a2l_file.project.module[0].group.insert(0, duplicate);
+
+        sort_stable(&mut a2l_file);
+        let groups: Vec<(&str, &str)> = a2l_file.project.module[0]
+            .group
+            .iter()
+            .map(|group| (group.name.as_str(), group.long_identifier.as_str()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("a", "first"),
+                ("b", "duplicate"),
+                ("b", "second"),
+                ("c", "third")
+            ]
+        );
+
+        // sorting again does not change anything
+        let text = a2l_file.write_to_string();
+        sort_stable(&mut a2l_file);
+        assert_eq!(text, a2l_file.write_to_string());
+    }
+}
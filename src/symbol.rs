@@ -1,5 +1,5 @@
 use crate::debuginfo::iter::TypeInfoIter;
-use crate::debuginfo::{make_simple_unit_name, DebugData, TypeInfo};
+use crate::debuginfo::{make_simple_unit_name, DebugData, TypeInfo, VarSource};
 use crate::debuginfo::{DbgDataType, VarInfo};
 
 #[derive(Clone)]
@@ -8,11 +8,38 @@ pub(crate) struct SymbolInfo<'dbg> {
     pub(crate) address: u64,
     pub(crate) typeinfo: &'dbg TypeInfo,
     pub(crate) unit_idx: usize,
+    pub(crate) source: VarSource,
     pub(crate) function_name: &'dbg Option<String>,
     pub(crate) namespaces: &'dbg [String],
+    pub(crate) section: &'dbg Option<String>,
     pub(crate) is_unique: bool,
 }
 
+impl SymbolInfo<'_> {
+    // true if the symbol is only known from a linker map file, so its data type is unknown
+    pub(crate) fn is_from_mapfile(&self) -> bool {
+        self.source == VarSource::MapFile
+    }
+
+    // true if the symbol is only known from the ELF symbol table, and its data type was inferred from its size
+    pub(crate) fn is_type_inferred(&self) -> bool {
+        self.source == VarSource::SymbolTable
+    }
+
+    // true if the data type of the symbol is not known from the debug info
+    pub(crate) fn is_untyped(&self) -> bool {
+        self.is_from_mapfile() || self.is_type_inferred()
+    }
+
+    // true if the symbol is a static variable that is declared inside of a function
+    pub(crate) fn is_function_local(&self) -> bool {
+        self.function_name.is_some()
+    }
+}
+
+// the number of candidates that are listed when a symbol name is ambiguous
+pub(crate) const MAX_LISTED_CANDIDATES: usize = 5;
+
 struct AdditionalSpec {
     function_name: Option<String>,
     simple_unit_name: Option<String>,
@@ -56,11 +83,138 @@ pub(crate) fn find_symbol<'a>(
                 }
             }
 
-            Err(find_err)
+            // the variable might exist, but the compiler replaced it by a constant
+            if let Some(value) = debug_data.get_const_value_text(components[0]) {
+                return Err(format!(
+                    "symbol {} has no storage; it was optimized to a constant value {value}",
+                    components[0]
+                ));
+            }
+
+            // the symbols of a linker map file are the last resort, since their data type is unknown
+            let mapfile_symbol = find_mapfile_symbol(plain_symbol, &components, debug_data);
+
+            // the peripherals of an SVD file are only searched if the name is not a variable of the debug info
+            if let Some(svd) = &debug_data.svd {
+                if !debug_data.variables.contains_key(components[0])
+                    && !debug_data.demangled_names.contains_key(components[0])
+                {
+                    if svd.peripherals.variables.contains_key(components[0]) {
+                        return find_symbol(varname, &svd.peripherals);
+                    }
+                    if let Some(sym_info) = mapfile_symbol {
+                        return Ok(sym_info);
+                    }
+                    // tell the user where the symbol was searched, since a register name could be misspelled or be missing from the SVD file
+                    let sources = if debug_data.binary.path.is_empty() {
+                        format!("the SVD file {}", svd.filename)
+                    } else {
+                        format!("the debug info or in the SVD file {}", svd.filename)
+                    };
+                    return Err(format!(
+                        "Symbol \"{}\" does not exist in {sources}",
+                        components[0]
+                    ));
+                }
+            }
+
+            mapfile_symbol.ok_or(find_err)
         }
     }
 }
 
+// find a symbol in the map file; only plain names can be found, since the data type of the symbol is unknown
+fn find_mapfile_symbol<'a>(
+    name: &str,
+    components: &[&str],
+    debug_data: &'a DebugData,
+) -> Option<SymbolInfo<'a>> {
+    let mapfile = debug_data.mapfile.as_ref()?;
+    if components.len() != 1 || debug_data.variables.contains_key(name) {
+        return None;
+    }
+    let (address, typeinfo) = mapfile.get_symbol(name)?;
+    Some(SymbolInfo {
+        name: name.to_string(),
+        address,
+        typeinfo,
+        unit_idx: usize::MAX,
+        source: VarSource::MapFile,
+        function_name: &None,
+        namespaces: &[],
+        section: &None,
+        is_unique: true,
+    })
+}
+
+// find all symbols that are selected by a symbol name containing array ranges
+// e.g. "table[0:3].value" -> "table[0].value", "table[1].value", "table[2].value"
+// The end of a range is exclusive. A name without ranges results in exactly one symbol.
+// If any element of a range does not exist, e.g. because the range exceeds the array, then nothing is returned.
+pub(crate) fn find_symbol_range<'a>(
+    varname: &str,
+    debug_data: &'a DebugData,
+) -> Result<Vec<(String, SymbolInfo<'a>)>, String> {
+    let names = expand_array_ranges(varname)?;
+    if names.len() == 1 && names[0] == varname {
+        let sym_info = find_symbol(varname, debug_data)?;
+        return Ok(vec![(varname.to_string(), sym_info)]);
+    }
+
+    names
+        .into_iter()
+        .map(|name| match find_symbol(&name, debug_data) {
+            Ok(sym_info) => Ok((name, sym_info)),
+            Err(errmsg) => Err(format!(
+                "the array range in \"{varname}\" is not valid: {errmsg}"
+            )),
+        })
+        .collect()
+}
+
+// replace each array range "[start:end]" in a symbol name by the individual indices
+fn expand_array_ranges(varname: &str) -> Result<Vec<String>, String> {
+    let mut expanded = vec![String::new()];
+    let mut remainder = varname;
+
+    while let Some(open_pos) = remainder.find('[') {
+        let Some(close_pos) = remainder[open_pos..].find(']').map(|pos| open_pos + pos) else {
+            break;
+        };
+        let index_text = &remainder[open_pos + 1..close_pos];
+        if let Some((start_text, end_text)) = index_text.split_once(':') {
+            let prefix = &remainder[..open_pos];
+            let (Ok(start), Ok(end)) = (
+                start_text.trim().parse::<usize>(),
+                end_text.trim().parse::<usize>(),
+            ) else {
+                return Err(format!(
+                    "could not interpret \"[{index_text}]\" in \"{varname}\" as an array range"
+                ));
+            };
+            if start >= end {
+                return Err(format!(
+                    "the array range \"[{index_text}]\" in \"{varname}\" is empty"
+                ));
+            }
+            expanded = expanded
+                .iter()
+                .flat_map(|name| (start..end).map(move |idx| format!("{name}{prefix}[{idx}]")))
+                .collect();
+        } else {
+            for name in &mut expanded {
+                name.push_str(&remainder[..=close_pos]);
+            }
+        }
+        remainder = &remainder[close_pos + 1..];
+    }
+    for name in &mut expanded {
+        name.push_str(remainder);
+    }
+
+    Ok(expanded)
+}
+
 fn find_symbol_from_components<'a>(
     components: &[&str],
     additional_spec: &Option<AdditionalSpec>,
@@ -71,7 +225,9 @@ fn find_symbol_from_components<'a>(
         // somtimes there are several variables with the same name in different files or functions
         // select the best one of them based on the additional_data
         let varinfo = select_varinfo(varinfo_list, additional_spec, debug_data);
-        let is_unique = varinfo_list.len() == 1;
+        // a single global variable is preferred over function-local static variables, and needs no discriminator
+        let is_unique = debug_data.is_unique_variable(varinfo_list)
+            || get_single_global(varinfo_list).is_some_and(|global| std::ptr::eq(global, varinfo));
 
         // we also need the type in order to resolve struct members, etc.
         if let Some(vartype) = debug_data.types.get(&varinfo.typeref) {
@@ -82,36 +238,21 @@ fn find_symbol_from_components<'a>(
                     address: addr,
                     typeinfo,
                     unit_idx: varinfo.unit_idx,
+                    source: varinfo.source,
                     function_name: &varinfo.function,
                     namespaces: &varinfo.namespaces,
+                    section: &varinfo.section,
                     is_unique,
                 },
             )
         } else {
-            // this exists for completeness, but shouldn't happen with a correctly generated elffile
-            // if the variable is present in the elffile, then the type should also be present
-            if components.len() == 1 {
-                Ok(SymbolInfo {
-                    name: "".to_string(),
-                    address: varinfo.address,
-                    typeinfo: &TypeInfo {
-                        datatype: DbgDataType::Uint8,
-                        name: None,
-                        unit_idx: usize::MAX,
-                        dbginfo_offset: 0,
-                    },
-                    unit_idx: varinfo.unit_idx,
-                    namespaces: &varinfo.namespaces,
-                    function_name: &None,
-                    is_unique,
-                })
-            } else {
-                Err(format!(
-                    "Remaining portion \"{}\" of \"{}\" could not be matched",
-                    components[1..].join("."),
-                    components.join(".")
-                ))
-            }
+            // This shouldn't happen with a correctly generated elffile: if the variable is present, then the type
+            // should also be present.
+            // Guessing a type would silently change the datatype of the a2l object, so this is an error.
+            Err(format!(
+                "The data type of the variable \"{}\" was not loaded",
+                components[0]
+            ))
         }
     } else {
         Err(format!("Symbol \"{}\" does not exist", components[0]))
@@ -123,21 +264,112 @@ fn select_varinfo<'a>(
     additional_spec: &Option<AdditionalSpec>,
     debug_data: &DebugData,
 ) -> &'a VarInfo {
-    if let Some(additional_spec) = additional_spec {
-        let unit = &additional_spec.simple_unit_name;
-        let func = &additional_spec.function_name;
-        let ns = &additional_spec.namespaces;
-        for vi in varinfo_list {
-            if (unit.is_none() || *unit == make_simple_unit_name(debug_data, vi.unit_idx))
-                && (func.is_none() || *func == vi.function)
-                && *ns == vi.namespaces
-            {
-                return vi;
+    if let Some(vi) = match_additional_spec(varinfo_list, additional_spec, debug_data) {
+        return vi;
+    }
+    // spec was NOT matched. In this case we simply continue as if the spec didin't exist
+    // without a {Function:...} spec, a global variable is preferred over function-local static variables
+    varinfo_list
+        .iter()
+        .find(|vi| vi.function.is_none())
+        .unwrap_or(&varinfo_list[0])
+}
+
+fn match_additional_spec<'a>(
+    varinfo_list: &'a [VarInfo],
+    additional_spec: &Option<AdditionalSpec>,
+    debug_data: &DebugData,
+) -> Option<&'a VarInfo> {
+    let additional_spec = additional_spec.as_ref()?;
+    let unit = &additional_spec.simple_unit_name;
+    let func = &additional_spec.function_name;
+    let ns = &additional_spec.namespaces;
+    varinfo_list.iter().find(|vi| {
+        (unit.is_none() || *unit == make_simple_unit_name(debug_data, vi.unit_idx))
+            && (func.is_none() || *func == vi.function)
+            && *ns == vi.namespaces
+    })
+}
+
+// get the global variable if exactly one of the candidates is global, and all others are function-local
+fn get_single_global(varinfo_list: &[VarInfo]) -> Option<&VarInfo> {
+    let mut globals = varinfo_list.iter().filter(|vi| vi.function.is_none());
+    let global = globals.next()?;
+    globals.next().is_none().then_some(global)
+}
+
+// Explain how a symbol name that matches several variables was resolved.
+// Nothing is returned if the name refers to a single object, or if the discriminators in the name select one of the variables.
+// If the name is ambiguous, then all candidates are listed, but at most max_candidates of them.
+pub(crate) fn get_resolution_note(
+    varname: &str,
+    debug_data: &DebugData,
+    max_candidates: usize,
+) -> Option<String> {
+    let (plain_symbol, additional_spec) = get_additional_spec(varname);
+    let base_name = split_symbol_components(plain_symbol)[0];
+    let varinfo_list = debug_data.variables.get(base_name).or_else(|| {
+        let mangled = debug_data.demangled_names.get(base_name)?;
+        debug_data.variables.get(mangled)
+    })?;
+    if varinfo_list.len() < 2
+        || debug_data.is_unique_variable(varinfo_list)
+        || match_additional_spec(varinfo_list, &additional_spec, debug_data).is_some()
+    {
+        return None;
+    }
+
+    if let Some(global) = get_single_global(varinfo_list) {
+        Some(format!(
+            "Note: symbol {base_name} refers to the global variable at 0x{:X}; {} function-local static variables with the same name were ignored",
+            global.address,
+            varinfo_list.len() - 1
+        ))
+    } else {
+        let selected = select_varinfo(varinfo_list, &additional_spec, debug_data);
+        Some(format!(
+            "Warning: symbol {base_name} is ambiguous, the variable at 0x{:X} was used. Candidates:\n{}",
+            selected.address,
+            format_candidates(varinfo_list, debug_data, max_candidates)
+        ))
+    }
+}
+
+// list the candidates for an ambiguous symbol name, one per line
+fn format_candidates(
+    varinfo_list: &[VarInfo],
+    debug_data: &DebugData,
+    max_candidates: usize,
+) -> String {
+    let mut lines: Vec<String> = varinfo_list
+        .iter()
+        .take(max_candidates)
+        .map(|vi| {
+            let size = debug_data
+                .types
+                .get(&vi.typeref)
+                .map_or("unknown size".to_string(), |typeinfo| {
+                    format!("{} bytes", typeinfo.get_size())
+                });
+            let unit_name = debug_data
+                .unit_names
+                .get(vi.unit_idx)
+                .and_then(|name| name.as_deref())
+                .unwrap_or("<unknown>");
+            let mut line = format!("  0x{:X}, {size}, compile unit {unit_name}", vi.address);
+            if let Some(function) = &vi.function {
+                line.push_str(&format!(", function {function}"));
             }
-        }
-        // spec was NOT matched. In this case we simply continue as if the spec didin't exist
+            line
+        })
+        .collect();
+    if varinfo_list.len() > max_candidates {
+        lines.push(format!(
+            "  and {} more",
+            varinfo_list.len() - max_candidates
+        ));
     }
-    &varinfo_list[0]
+    lines.join("\n")
 }
 
 // split up a string of the form
@@ -345,8 +577,10 @@ pub(crate) fn find_symbol_by_offset<'a>(
                 address: item_offset + base_symbol.address,
                 typeinfo,
                 unit_idx: base_symbol.unit_idx,
+                source: base_symbol.source,
                 function_name: base_symbol.function_name,
                 namespaces: base_symbol.namespaces,
+                section: base_symbol.section,
                 is_unique: base_symbol.is_unique,
             });
         }
@@ -362,7 +596,6 @@ pub(crate) fn find_symbol_by_offset<'a>(
 mod test {
     use super::*;
     use indexmap::IndexMap;
-    use std::collections::HashMap;
 
     #[test]
     fn test_split_symbol_components() {
@@ -381,16 +614,36 @@ mod test {
         assert_eq!(result2[3], "_1_");
     }
 
+    #[test]
+    fn test_expand_array_ranges() {
+        let result = expand_array_ranges("table[0:3].value").unwrap();
+        assert_eq!(
+            result,
+            vec!["table[0].value", "table[1].value", "table[2].value"]
+        );
+
+        let result = expand_array_ranges("matrix[1:3][2].row[0:2]").unwrap();
+        assert_eq!(
+            result,
+            vec![
+                "matrix[1][2].row[0]",
+                "matrix[1][2].row[1]",
+                "matrix[2][2].row[0]",
+                "matrix[2][2].row[1]"
+            ]
+        );
+
+        // names without ranges are unchanged
+        let result = expand_array_ranges("var.element[5].subelement").unwrap();
+        assert_eq!(result, vec!["var.element[5].subelement"]);
+
+        assert!(expand_array_ranges("table[3:3]").is_err());
+        assert!(expand_array_ranges("table[a:3]").is_err());
+    }
+
     #[test]
     fn test_find_symbol_of_array() {
-        let mut dbgdata = DebugData {
-            types: HashMap::new(),
-            typenames: HashMap::new(),
-            variables: IndexMap::new(),
-            demangled_names: HashMap::new(),
-            unit_names: Vec::new(),
-            sections: HashMap::new(),
-        };
+        let mut dbgdata = DebugData::default();
         // global variable: uint32_t my_array[2]
         dbgdata.variables.insert(
             "my_array".to_string(),
@@ -398,8 +651,10 @@ mod test {
                 address: 0x1234,
                 typeref: 1,
                 unit_idx: 0,
+                source: VarSource::DebugInfo,
                 function: None,
                 namespaces: vec![],
+                section: None,
             }],
         );
         dbgdata.types.insert(
@@ -411,14 +666,17 @@ mod test {
                         name: None,
                         unit_idx: usize::MAX,
                         dbginfo_offset: 0,
+                        big_endian: None,
                     }),
                     dim: vec![2],
+                    lbound: vec![0],
                     size: 8, // total size of the array
                     stride: 4,
                 },
                 name: None,
                 unit_idx: usize::MAX,
                 dbginfo_offset: 0,
+                big_endian: None,
             },
         );
 
@@ -439,18 +697,27 @@ mod test {
         // going past the end of the array is also not permitted
         let result5 = find_symbol("my_array._2_", &dbgdata);
         assert!(result5.is_err());
+
+        // a variable whose type was not loaded is an error, instead of being treated as a UBYTE
+        dbgdata.variables.insert(
+            "no_type".to_string(),
+            vec![crate::debuginfo::VarInfo {
+                address: 0x1300,
+                typeref: 99,
+                unit_idx: 0,
+                source: VarSource::DebugInfo,
+                function: None,
+                namespaces: vec![],
+                section: None,
+            }],
+        );
+        let result6 = find_symbol("no_type", &dbgdata);
+        assert!(result6.is_err());
     }
 
     #[test]
     fn test_find_symbol_of_array_in_struct() {
-        let mut dbgdata = DebugData {
-            types: HashMap::new(),
-            typenames: HashMap::new(),
-            variables: IndexMap::new(),
-            demangled_names: HashMap::new(),
-            unit_names: Vec::new(),
-            sections: HashMap::new(),
-        };
+        let mut dbgdata = DebugData::default();
         // global variable defined in C like this:
         // struct {
         //        uint32_t array_item[2];
@@ -466,14 +733,17 @@ mod test {
                             name: None,
                             unit_idx: usize::MAX,
                             dbginfo_offset: 0,
+                            big_endian: None,
                         }),
                         dim: vec![2],
+                        lbound: vec![0],
                         size: 8,
                         stride: 4,
                     },
                     name: None,
                     unit_idx: usize::MAX,
                     dbginfo_offset: 0,
+                    big_endian: None,
                 },
                 0,
             ),
@@ -484,8 +754,10 @@ mod test {
                 address: 0x00ca_fe00,
                 typeref: 2,
                 unit_idx: 0,
+                source: VarSource::DebugInfo,
                 function: None,
                 namespaces: vec![],
+                section: None,
             }],
         );
         dbgdata.types.insert(
@@ -498,6 +770,7 @@ mod test {
                 unit_idx: 0,
                 name: None,
                 dbginfo_offset: 0,
+                big_endian: None,
             },
         );
 
@@ -515,14 +788,7 @@ mod test {
 
     #[test]
     fn test_select_varinfo() {
-        let mut debug_data = DebugData {
-            types: HashMap::new(),
-            typenames: HashMap::new(),
-            variables: IndexMap::new(),
-            demangled_names: HashMap::new(),
-            unit_names: Vec::new(),
-            sections: HashMap::new(),
-        };
+        let mut debug_data = DebugData::default();
         debug_data.types.insert(
             0,
             TypeInfo {
@@ -530,6 +796,7 @@ mod test {
                 name: None,
                 unit_idx: 0,
                 dbginfo_offset: 0,
+                big_endian: None,
             },
         );
         debug_data.variables.insert(
@@ -539,22 +806,28 @@ mod test {
                     address: 0,
                     typeref: 0,
                     unit_idx: 0,
+                    source: VarSource::DebugInfo,
                     function: Some("func_a".to_string()),
                     namespaces: vec![],
+                    section: None,
                 },
                 VarInfo {
                     address: 1000,
                     typeref: 0,
                     unit_idx: 1,
+                    source: VarSource::DebugInfo,
                     function: Some("func_b".to_string()),
                     namespaces: vec![],
+                    section: None,
                 },
                 VarInfo {
                     address: 2000,
                     typeref: 0,
                     unit_idx: 1,
+                    source: VarSource::DebugInfo,
                     function: Some("func_c".to_string()),
                     namespaces: vec![],
+                    section: None,
                 },
             ],
         );
@@ -578,6 +851,183 @@ mod test {
         assert_eq!(varinfo.address, 2000);
     }
 
+    #[test]
+    fn test_find_symbol_function_local() {
+        // in v2, Scope_Value is a static variable inside of the function get_scope_value
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &std::ffi::OsString::from("fixtures/bin/scope_change_test_v2.elf"),
+            false,
+        )
+        .unwrap();
+        let sym_info = find_symbol("Scope_Value", &debug_data).unwrap();
+        assert!(sym_info.is_function_local());
+        assert_eq!(sym_info.function_name.as_deref(), Some("get_scope_value"));
+        let sym_info = find_symbol("Global_Value", &debug_data).unwrap();
+        assert!(!sym_info.is_function_local());
+    }
+
+    #[test]
+    fn test_find_symbol_in_multiple_units() {
+        let mut debug_data = DebugData {
+            unit_names: vec![Some("file1.c".to_string()), Some("file2.c".to_string())],
+            ..Default::default()
+        };
+        for (typeref, unit_idx) in [(1, 0), (2, 1)] {
+            debug_data.types.insert(
+                typeref,
+                TypeInfo {
+                    datatype: DbgDataType::Uint32,
+                    name: None,
+                    unit_idx,
+                    dbginfo_offset: typeref,
+                    big_endian: None,
+                },
+            );
+        }
+        // the same inline variable is present in both compile units
+        let make_varinfo = |address, typeref, unit_idx| VarInfo {
+            address,
+            typeref,
+            unit_idx,
+            source: VarSource::DebugInfo,
+            function: None,
+            namespaces: vec![],
+            section: None,
+        };
+        debug_data.variables.insert(
+            "shared_var".to_string(),
+            vec![make_varinfo(0x100, 1, 0), make_varinfo(0x100, 2, 1)],
+        );
+        // two different static variables with the same name
+        debug_data.variables.insert(
+            "static_var".to_string(),
+            vec![make_varinfo(0x200, 1, 0), make_varinfo(0x300, 2, 1)],
+        );
+
+        let sym_info = find_symbol("shared_var", &debug_data).unwrap();
+        assert!(sym_info.is_unique);
+        // a discriminator referring to a compile unit that does not contain the variable is ignored
+        let sym_info = find_symbol(
+            "shared_var{CompileUnit:file3_c}{Namespace:Global}",
+            &debug_data,
+        )
+        .unwrap();
+        assert!(sym_info.is_unique);
+        assert_eq!(sym_info.address, 0x100);
+
+        let sym_info = find_symbol("static_var", &debug_data).unwrap();
+        assert!(!sym_info.is_unique);
+        let sym_info = find_symbol(
+            "static_var{CompileUnit:file2_c}{Namespace:Global}",
+            &debug_data,
+        )
+        .unwrap();
+        assert_eq!(sym_info.address, 0x300);
+    }
+
+    #[test]
+    fn test_get_resolution_note() {
+        let mut debug_data = DebugData {
+            unit_names: vec![
+                Some("file1.c".to_string()),
+                Some("file2.c".to_string()),
+                Some("file3.c".to_string()),
+            ],
+            ..Default::default()
+        };
+        for (typeref, datatype) in [(1, DbgDataType::Uint32), (2, DbgDataType::Float)] {
+            debug_data.types.insert(
+                typeref,
+                TypeInfo {
+                    datatype,
+                    name: None,
+                    unit_idx: 0,
+                    dbginfo_offset: typeref,
+                    big_endian: None,
+                },
+            );
+        }
+        let make_varinfo = |address, typeref, unit_idx, function: Option<&str>| VarInfo {
+            address,
+            typeref,
+            unit_idx,
+            source: VarSource::DebugInfo,
+            function: function.map(str::to_string),
+            namespaces: vec![],
+            section: None,
+        };
+        // same address and size, but different types
+        debug_data.variables.insert(
+            "same_var".to_string(),
+            vec![
+                make_varinfo(0x100, 1, 0, None),
+                make_varinfo(0x100, 2, 1, None),
+            ],
+        );
+        // one global and two function-local static variables
+        debug_data.variables.insert(
+            "global_var".to_string(),
+            vec![
+                make_varinfo(0x200, 1, 0, Some("func_a")),
+                make_varinfo(0x300, 1, 1, None),
+                make_varinfo(0x400, 1, 2, Some("func_b")),
+            ],
+        );
+        // three static variables in different files
+        debug_data.variables.insert(
+            "static_var".to_string(),
+            vec![
+                make_varinfo(0x500, 1, 0, None),
+                make_varinfo(0x600, 2, 1, None),
+                make_varinfo(0x700, 1, 2, Some("func_c")),
+            ],
+        );
+
+        let sym_info = find_symbol("same_var", &debug_data).unwrap();
+        assert!(sym_info.is_unique);
+        assert!(get_resolution_note("same_var", &debug_data, MAX_LISTED_CANDIDATES).is_none());
+
+        let sym_info = find_symbol("global_var", &debug_data).unwrap();
+        assert!(sym_info.is_unique);
+        assert_eq!(sym_info.address, 0x300);
+        let note = get_resolution_note("global_var", &debug_data, MAX_LISTED_CANDIDATES).unwrap();
+        assert!(note.contains("global variable at 0x300"));
+        assert!(note.contains("2 function-local static variables"));
+        // the function-local variable is selected explicitly
+        let sym_info = find_symbol(
+            "global_var{Function:func_b}{CompileUnit:file3_c}{Namespace:Global}",
+            &debug_data,
+        )
+        .unwrap();
+        assert!(!sym_info.is_unique);
+        assert_eq!(sym_info.address, 0x400);
+
+        let sym_info = find_symbol("static_var", &debug_data).unwrap();
+        assert!(!sym_info.is_unique);
+        let note = get_resolution_note("static_var", &debug_data, MAX_LISTED_CANDIDATES).unwrap();
+        let lines: Vec<&str> = note.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("ambiguous, the variable at 0x500 was used"));
+        assert_eq!(lines[1], "  0x500, 4 bytes, compile unit file1.c");
+        assert_eq!(lines[2], "  0x600, 4 bytes, compile unit file2.c");
+        assert_eq!(
+            lines[3],
+            "  0x700, 4 bytes, compile unit file3.c, function func_c"
+        );
+        // the list of candidates is limited
+        let note = get_resolution_note("static_var", &debug_data, 1).unwrap();
+        let lines: Vec<&str> = note.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "  and 2 more");
+        // no note if the discriminators select one of the variables
+        assert!(get_resolution_note(
+            "static_var{CompileUnit:file2_c}{Namespace:Global}",
+            &debug_data,
+            MAX_LISTED_CANDIDATES
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_get_additional_spec() {
         let (base, _add_spec) = get_additional_spec("varname");
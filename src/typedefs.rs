@@ -0,0 +1,160 @@
+use a2lfile::{A2lFile, MatrixDim, Module};
+use std::ffi::OsStr;
+use std::fmt::Write;
+
+// Display each TYPEDEF_STRUCTURE with its components, and the INSTANCEs and TYPEDEF_STRUCTUREs that use it
+pub(crate) fn list_typedefs(a2l_file: &A2lFile, filename: &OsStr) {
+    log_println!("TYPEDEF_STRUCTUREs in {}:", filename.to_string_lossy());
+    log_println!("{}", format_typedefs(a2l_file));
+}
+
+fn format_typedefs(a2l_file: &A2lFile) -> String {
+    let multi_module = a2l_file.project.module.len() > 1;
+    let mut out = String::new();
+
+    for module in &a2l_file.project.module {
+        if multi_module {
+            let _ = writeln!(out, "Module {}:", module.name);
+        }
+        if module.typedef_structure.is_empty() {
+            let _ = writeln!(out, "No TYPEDEF_STRUCTUREs found in module {}", module.name);
+            continue;
+        }
+
+        for td_struct in &module.typedef_structure {
+            let _ = writeln!(
+                out,
+                "TYPEDEF_STRUCTURE {} (size {})",
+                td_struct.name, td_struct.total_size
+            );
+            for component in &td_struct.structure_component {
+                let _ = writeln!(
+                    out,
+                    "    {}{}: {}, offset 0x{:X}",
+                    component.component_name,
+                    format_matrix_dim(&component.matrix_dim),
+                    describe_typedef(module, &component.component_type),
+                    component.address_offset
+                );
+            }
+
+            let mut users = Vec::new();
+            for instance in &module.instance {
+                if instance.type_ref == td_struct.name {
+                    users.push(format!(
+                        "INSTANCE {}{} at 0x{:X}",
+                        instance.name,
+                        format_matrix_dim(&instance.matrix_dim),
+                        instance.start_address
+                    ));
+                }
+            }
+            for parent in &module.typedef_structure {
+                for component in &parent.structure_component {
+                    if component.component_type == td_struct.name {
+                        users.push(format!(
+                            "TYPEDEF_STRUCTURE {}, component {}",
+                            parent.name, component.component_name
+                        ));
+                    }
+                }
+            }
+            if users.is_empty() {
+                let _ = writeln!(out, "  not used");
+            } else {
+                let _ = writeln!(out, "  used by:");
+                for user in users {
+                    let _ = writeln!(out, "    {user}");
+                }
+            }
+        }
+    }
+
+    out
+}
+
+// describe the typedef that is referenced by a STRUCTURE_COMPONENT
+fn describe_typedef(module: &Module, name: &str) -> String {
+    if let Some(td_meas) = module
+        .typedef_measurement
+        .iter()
+        .find(|item| item.name == name)
+    {
+        format!("TYPEDEF_MEASUREMENT {name} ({})", td_meas.datatype)
+    } else if let Some(td_char) = module
+        .typedef_characteristic
+        .iter()
+        .find(|item| item.name == name)
+    {
+        format!("TYPEDEF_CHARACTERISTIC {name} ({})", td_char.record_layout)
+    } else if let Some(td_axis) = module.typedef_axis.iter().find(|item| item.name == name) {
+        format!("TYPEDEF_AXIS {name} ({})", td_axis.record_layout)
+    } else if let Some(td_blob) = module.typedef_blob.iter().find(|item| item.name == name) {
+        format!("TYPEDEF_BLOB {name} ({} bytes)", td_blob.size)
+    } else if module
+        .typedef_structure
+        .iter()
+        .any(|item| item.name == name)
+    {
+        format!("TYPEDEF_STRUCTURE {name}")
+    } else {
+        format!("{name} (unknown typedef)")
+    }
+}
+
+fn format_matrix_dim(matrix_dim: &Option<MatrixDim>) -> String {
+    matrix_dim.as_ref().map_or(String::new(), |matrix_dim| {
+        matrix_dim
+            .dim_list
+            .iter()
+            .map(|dim| format!("[{dim}]"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT test ""
+  /begin MODULE mod ""
+    /begin INSTANCE Outer_Instance "" Outer 0x1000
+    /end INSTANCE
+    /begin TYPEDEF_MEASUREMENT Value_T "" UWORD NO_COMPU_METHOD 0 0 0 65535
+    /end TYPEDEF_MEASUREMENT
+    /begin TYPEDEF_STRUCTURE Inner "" 4
+      /begin STRUCTURE_COMPONENT value Value_T 0
+      /end STRUCTURE_COMPONENT
+      /begin STRUCTURE_COMPONENT extra Value_T 2
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+    /begin TYPEDEF_STRUCTURE Outer "" 12
+      /begin STRUCTURE_COMPONENT items Inner 0
+        MATRIX_DIM 3
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+    /begin TYPEDEF_STRUCTURE Unused "" 2
+      /begin STRUCTURE_COMPONENT value Value_T 0
+      /end STRUCTURE_COMPONENT
+    /end TYPEDEF_STRUCTURE
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_format_typedefs() {
+        let a2l_file = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let text = format_typedefs(&a2l_file);
+
+        assert!(text.contains("TYPEDEF_STRUCTURE Inner (size 4)"));
+        assert!(text.contains("    extra: TYPEDEF_MEASUREMENT Value_T (UWORD), offset 0x2"));
+        assert!(text.contains("    items[3]: TYPEDEF_STRUCTURE Inner, offset 0x0"));
+        assert!(text.contains("    TYPEDEF_STRUCTURE Outer, component items"));
+        assert!(text.contains("    INSTANCE Outer_Instance at 0x1000"));
+
+        // the output is grouped by typedef: Unused is the last one, and nobody refers to it
+        let unused_pos = text.find("TYPEDEF_STRUCTURE Unused").unwrap();
+        assert!(text[unused_pos..].contains("  not used"));
+        assert!(!text[..unused_pos].contains("not used"));
+    }
+}
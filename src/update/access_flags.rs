@@ -0,0 +1,249 @@
+use super::get_symbol_info;
+use crate::debuginfo::{find_section, wildcard_match, DebugData};
+use a2lfile::{A2lFile, A2lObject, IfData, ReadOnly, SymbolLink};
+
+// where the symbol of an object is located, as far as the READ_ONLY / READ_WRITE flags are concerned
+#[derive(Debug, PartialEq)]
+enum MemoryKind {
+    // the section is not writable, e.g. flash
+    ReadOnly,
+    // the section is listed as calibration memory, e.g. flash which is overlaid with RAM
+    Calibration,
+    // any other writable section
+    Writable,
+}
+
+/// Verify that the READ_ONLY flags of CHARACTERISTICs and AXIS_PTS and the READ_WRITE flags of MEASUREMENTs
+/// match the sections in which their symbols are located.
+/// - CHARACTERISTICs and AXIS_PTS in sections without write permission must be READ_ONLY
+/// - CHARACTERISTICs and AXIS_PTS in one of the `calibration_sections` must not be READ_ONLY
+/// - MEASUREMENTs in sections without write permission should not be READ_WRITE
+///
+/// If `fix` is set, the READ_ONLY flags of CHARACTERISTICs and AXIS_PTS are corrected.
+/// MEASUREMENTs are never changed; only a warning is generated for them.
+/// Returns the number of problems that were found.
+pub(crate) fn check_access_flags(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    calibration_sections: &[&str],
+    fix: bool,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut problem_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        for characteristic in &mut module.characteristic {
+            let Some((section, kind)) = get_memory_kind(
+                &characteristic.name,
+                &characteristic.symbol_link,
+                &characteristic.if_data,
+                debug_data,
+                calibration_sections,
+            ) else {
+                continue;
+            };
+            let line = characteristic.get_line();
+            if check_read_only(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                line,
+                &mut characteristic.read_only,
+                &section,
+                &kind,
+                fix,
+                log_msgs,
+            ) {
+                problem_count += 1;
+            }
+        }
+
+        for axis_pts in &mut module.axis_pts {
+            let Some((section, kind)) = get_memory_kind(
+                &axis_pts.name,
+                &axis_pts.symbol_link,
+                &axis_pts.if_data,
+                debug_data,
+                calibration_sections,
+            ) else {
+                continue;
+            };
+            let line = axis_pts.get_line();
+            if check_read_only(
+                "AXIS_PTS",
+                &axis_pts.name,
+                line,
+                &mut axis_pts.read_only,
+                &section,
+                &kind,
+                fix,
+                log_msgs,
+            ) {
+                problem_count += 1;
+            }
+        }
+
+        for measurement in &module.measurement {
+            if measurement.read_write.is_none() || measurement.var_virtual.is_some() {
+                continue;
+            }
+            let Some((section, kind)) = get_memory_kind(
+                &measurement.name,
+                &measurement.symbol_link,
+                &measurement.if_data,
+                debug_data,
+                calibration_sections,
+            ) else {
+                continue;
+            };
+            if kind == MemoryKind::ReadOnly {
+                log_msgs.push(format!(
+                    "Warning: MEASUREMENT {} on line {} is READ_WRITE, but its symbol is located in the read-only section {section}",
+                    measurement.name,
+                    measurement.get_line()
+                ));
+                problem_count += 1;
+            }
+        }
+    }
+
+    problem_count
+}
+
+// find the section of the symbol of an object, and determine what kind of memory it is
+// Objects whose symbol or section is unknown are not checked
+fn get_memory_kind(
+    name: &str,
+    symbol_link: &Option<SymbolLink>,
+    if_data: &[IfData],
+    debug_data: &DebugData,
+    calibration_sections: &[&str],
+) -> Option<(String, MemoryKind)> {
+    let sym_info = get_symbol_info(name, symbol_link, if_data, debug_data).ok()?;
+    let section = sym_info
+        .section
+        .clone()
+        .or_else(|| find_section(&debug_data.sections, sym_info.address))?;
+
+    let kind = if calibration_sections
+        .iter()
+        .any(|pattern| wildcard_match(pattern, &section))
+    {
+        MemoryKind::Calibration
+    } else if debug_data.readonly_sections.contains(&section) {
+        MemoryKind::ReadOnly
+    } else {
+        MemoryKind::Writable
+    };
+    Some((section, kind))
+}
+
+// check (and optionally fix) the READ_ONLY flag of a CHARACTERISTIC or AXIS_PTS
+// returns true if there was a problem
+#[allow(clippy::too_many_arguments)]
+fn check_read_only(
+    blocktype: &str,
+    name: &str,
+    line: u32,
+    read_only: &mut Option<ReadOnly>,
+    section: &str,
+    kind: &MemoryKind,
+    fix: bool,
+    log_msgs: &mut Vec<String>,
+) -> bool {
+    match kind {
+        MemoryKind::ReadOnly if read_only.is_none() => {
+            if fix {
+                log_msgs.push(format!(
+                    "{blocktype} {name} on line {line}: READ_ONLY was set, because the symbol is located in the read-only section {section}"
+                ));
+                *read_only = Some(ReadOnly::new());
+            } else {
+                log_msgs.push(format!(
+                    "{blocktype} {name} on line {line} is not READ_ONLY, but its symbol is located in the read-only section {section}"
+                ));
+            }
+            true
+        }
+        MemoryKind::Calibration if read_only.is_some() => {
+            if fix {
+                log_msgs.push(format!(
+                    "{blocktype} {name} on line {line}: READ_ONLY was removed, because the symbol is located in the calibration section {section}"
+                ));
+                *read_only = None;
+            } else {
+                log_msgs.push(format!(
+                    "{blocktype} {name} on line {line} is READ_ONLY, but its symbol is located in the calibration section {section}"
+                ));
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use a2lfile::ReadWrite;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_check_access_flags() {
+        let mut a2l_file = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        // .data and .bss are writable in the elf file
+        assert!(!debug_data.readonly_sections.contains(".data"));
+        assert!(!debug_data.readonly_sections.contains(".bss"));
+
+        // nothing is wrong with the unmodified file
+        let mut log_msgs = Vec::new();
+        let count = check_access_flags(&mut a2l_file, &debug_data, &[], false, &mut log_msgs);
+        assert_eq!(count, 0);
+
+        // pretend that the data sections are located in flash
+        debug_data.readonly_sections.insert(".data".to_string());
+        debug_data.readonly_sections.insert(".bss".to_string());
+        a2l_file.project.module[0].measurement[0].read_write = Some(ReadWrite::new());
+        let characteristic_count = a2l_file.project.module[0].characteristic.len();
+        let axis_pts_count = a2l_file.project.module[0].axis_pts.len();
+        let mut log_msgs = Vec::new();
+        let count = check_access_flags(&mut a2l_file, &debug_data, &[], false, &mut log_msgs);
+        assert_eq!(count, characteristic_count + axis_pts_count + 1);
+        assert!(a2l_file.project.module[0].characteristic[0]
+            .read_only
+            .is_none());
+
+        // fix the flags: all CHARACTERISTICs and AXIS_PTS become READ_ONLY, the MEASUREMENT is not changed
+        let mut log_msgs = Vec::new();
+        let count = check_access_flags(&mut a2l_file, &debug_data, &[], true, &mut log_msgs);
+        assert_eq!(count, characteristic_count + axis_pts_count + 1);
+        let module = &a2l_file.project.module[0];
+        assert!(module.characteristic.iter().all(|c| c.read_only.is_some()));
+        assert!(module.axis_pts.iter().all(|a| a.read_only.is_some()));
+        assert!(module.measurement[0].read_write.is_some());
+        let mut log_msgs = Vec::new();
+        let count = check_access_flags(&mut a2l_file, &debug_data, &[], false, &mut log_msgs);
+        assert_eq!(count, 1);
+
+        // the sections are RAM-backed calibration memory: READ_ONLY is removed again
+        let mut log_msgs = Vec::new();
+        let count = check_access_flags(
+            &mut a2l_file,
+            &debug_data,
+            &[".data", ".b?s"],
+            true,
+            &mut log_msgs,
+        );
+        assert_eq!(count, characteristic_count + axis_pts_count);
+        let module = &a2l_file.project.module[0];
+        assert!(module.characteristic.iter().all(|c| c.read_only.is_none()));
+        assert!(module.axis_pts.iter().all(|a| a.read_only.is_none()));
+    }
+}
@@ -3,7 +3,7 @@ use crate::debuginfo::DbgDataType;
 use crate::debuginfo::{DebugData, TypeInfo};
 use crate::symbol::SymbolInfo;
 use crate::A2lVersion;
-use a2lfile::{A2lObject, AxisPts, Module};
+use a2lfile::{A2lFile, A2lObject, AxisPts, Module};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::vec;
@@ -13,7 +13,8 @@ use crate::update::{
     enums::{cond_create_enum_conversion, update_enum_compu_methods},
     get_axis_pts_x_memberid, get_inner_type, get_symbol_info,
     ifdata_update::{update_ifdata_address, update_ifdata_type, zero_if_data},
-    make_symbol_link_string, set_symbol_link, update_record_layout, A2lUpdateInfo, A2lUpdater,
+    make_symbol_link_string, module_big_endian, remove_preserved_annotation, set_byte_order,
+    set_symbol_link, update_record_layout, A2lUpdateInfo, A2lUpdater, RecordLayoutInfo,
 };
 
 use super::UpdateResult;
@@ -29,6 +30,10 @@ pub(crate) fn update_all_module_axis_pts(
 
     std::mem::swap(&mut data.module.axis_pts, &mut axis_pts_list);
     for mut axis_pts in axis_pts_list {
+        if info.should_stop() || info.out_of_scope(&axis_pts.name) {
+            data.module.axis_pts.push(axis_pts);
+            continue;
+        }
         let update_result = update_module_axis_pts(&mut axis_pts, info, data, &mut enum_convlist);
         if matches!(update_result, UpdateResult::SymbolNotFound { .. }) {
             if info.preserve_unknown {
@@ -39,14 +44,17 @@ pub(crate) fn update_all_module_axis_pts(
                 removed_items.insert(axis_pts.name.clone());
             }
         } else {
+            remove_preserved_annotation(&mut axis_pts.annotation);
             data.module.axis_pts.push(axis_pts);
         }
+        info.count_result(&update_result);
         results.push(update_result);
     }
 
     // update COMPU_VTABs and COMPU_VTAB_RANGEs based on the data types used in MEASUREMENTs etc.
     update_enum_compu_methods(data.module, &enum_convlist);
     cleanup_removed_axis_pts(data.module, &removed_items);
+    info.removed_names.borrow_mut().extend(removed_items);
 
     results
 }
@@ -57,18 +65,28 @@ fn update_module_axis_pts<'dbg>(
     data: &mut A2lUpdater<'_>,
     enum_convlist: &mut HashMap<String, &'dbg TypeInfo>,
 ) -> UpdateResult {
-    match get_symbol_info(
-        &axis_pts.name,
-        &axis_pts.symbol_link,
-        &axis_pts.if_data,
-        info.debug_data,
-    ) {
+    match info.get_symbol_info(&axis_pts.name, &axis_pts.symbol_link, &axis_pts.if_data) {
         // match update_axis_pts_address(&mut axis_pts, info.debug_data, info.version) {
         Ok(sym_info) => {
+            let old_address = u64::from(axis_pts.address);
             update_axis_pts_address(axis_pts, info.debug_data, info.version, &sym_info);
+            info.check_moved(
+                "AXIS_PTS",
+                &axis_pts.name,
+                axis_pts.get_line(),
+                old_address,
+                sym_info.address,
+            );
             update_ifdata_address(&mut axis_pts.if_data, &sym_info.name, sym_info.address);
 
-            if info.full_update {
+            if sym_info.is_untyped() && (info.full_update || info.strict_update) {
+                // the data type of the symbol is not known from the debug info, so it can't be updated or verified
+                UpdateResult::AddressOnly {
+                    blocktype: "AXIS_PTS",
+                    name: axis_pts.name.clone(),
+                    line: axis_pts.get_line(),
+                }
+            } else if info.full_update {
                 // update the data type of the AXIS_PTS object
                 update_ifdata_type(&mut axis_pts.if_data, sym_info.typeinfo);
                 update_axis_pts_datatype(data, axis_pts, info, &sym_info, enum_convlist);
@@ -131,6 +149,16 @@ fn update_axis_pts_datatype<'dbg>(
                 // this is the only reasonable case for an AXIS_PTS object
                 // update max_axis_points to match the size of the array
                 if !dim.is_empty() {
+                    if axis_pts.max_axis_points != dim[0] as u16 {
+                        info.change_msgs.borrow_mut().push(format!(
+                            "AXIS_PTS {} on line {}: MAX_AXIS_POINTS changed from {} to {} to match the size of the array {}",
+                            axis_pts.name,
+                            axis_pts.get_line(),
+                            axis_pts.max_axis_points,
+                            dim[0],
+                            sym_info.name
+                        ));
+                    }
                     axis_pts.max_axis_points = dim[0] as u16;
                 }
                 update_axis_pts_conversion(data.module, axis_pts, arraytype, enum_convlist);
@@ -156,9 +184,27 @@ fn update_axis_pts_datatype<'dbg>(
             axis_pts.lower_limit,
             axis_pts.upper_limit,
             opt_compu_method,
+            info.limit_precision,
         );
         axis_pts.lower_limit = ll;
         axis_pts.upper_limit = ul;
+        info.fix_integer_format(
+            "AXIS_PTS",
+            &axis_pts.name,
+            axis_pts.get_line(),
+            &mut axis_pts.format,
+            inner_typeinfo.get_arraytype().unwrap_or(inner_typeinfo),
+            opt_compu_method,
+        );
+
+        let file_big_endian = info.debug_data.memory.big_endian;
+        set_byte_order(
+            &mut axis_pts.byte_order,
+            inner_typeinfo,
+            module_big_endian(data.module, file_big_endian),
+            file_big_endian,
+            info.version,
+        );
     }
 
     // update the data type in the referenced RECORD_LAYOUT
@@ -213,6 +259,7 @@ fn verify_axis_pts_datatype(
             axis_pts.lower_limit,
             axis_pts.upper_limit,
             opt_compu_method,
+            info.limit_precision,
         );
 
         let mut bad_datatype = false;
@@ -249,6 +296,52 @@ fn verify_axis_pts_datatype(
     }
 }
 
+/// Verify that the `MAX_AXIS_POINTS` of each AXIS_PTS matches the length of the array it refers to.
+/// AXIS_PTS whose symbol cannot be found, or whose data type is not an array, are not checked here;
+/// these problems are reported by the update.
+/// Returns the number of mismatches that were found.
+pub(crate) fn check_axis_pts_dimensions(
+    a2l_file: &A2lFile,
+    debug_data: &DebugData,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut problem_count = 0;
+
+    for module in &a2l_file.project.module {
+        let reclayout_info = RecordLayoutInfo::build(module);
+        for axis_pts in &module.axis_pts {
+            let Ok(sym_info) = get_symbol_info(
+                &axis_pts.name,
+                &axis_pts.symbol_link,
+                &axis_pts.if_data,
+                debug_data,
+            ) else {
+                continue;
+            };
+            let member_id =
+                get_axis_pts_x_memberid(module, &reclayout_info, &axis_pts.deposit_record);
+            if let Some(DbgDataType::Array { dim, .. }) =
+                get_inner_type(sym_info.typeinfo, member_id).map(|typeinfo| &typeinfo.datatype)
+            {
+                if let Some(array_len) = dim.first() {
+                    if u64::from(axis_pts.max_axis_points) != *array_len {
+                        log_msgs.push(format!(
+                            "AXIS_PTS {} on line {}: MAX_AXIS_POINTS is {}, but the array {} has {array_len} elements",
+                            axis_pts.name,
+                            axis_pts.get_line(),
+                            axis_pts.max_axis_points,
+                            sym_info.name
+                        ));
+                        problem_count += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    problem_count
+}
+
 // when update runs without preserve, AXIS_PTS be removed from the module
 // AXIS_PTS are only referenced through CHARACTERISTIC > AXIS_DESCR > AXIS_PTS_REF
 pub(crate) fn cleanup_removed_axis_pts(module: &mut Module, removed_items: &HashSet<String>) {
@@ -0,0 +1,245 @@
+use super::byte_order::module_big_endian;
+use crate::debuginfo::{BinaryInfo, DebugData};
+use crate::symbol::find_symbol;
+use a2lfile::{A2lFile, AddrEpk, Epk, ModPar, Module};
+
+/// Compare the a2l file with the binary file that the debug info was loaded from, in order to detect
+/// obvious mistakes, like using the elf file of a simulation build for the a2l file of the ECU.
+///  - the default BYTE_ORDER in MOD_COMMON must match the byte order of the binary file
+///  - the addresses of the variables in the binary file must fit into the 32-bit addresses of the a2l file
+///  - at least some of the existing addresses in the a2l file must lie inside the sections of the binary file
+///  - if the EPK was written by --stamp-binary-id, then its build-id must match the binary file
+///
+/// Returns a warning for each problem.
+pub(crate) fn check_binary_compatibility(
+    a2l_file: &A2lFile,
+    debug_data: &DebugData,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let binary = &debug_data.binary;
+    let file_big_endian = debug_data.memory.big_endian;
+    let file_byte_order = if file_big_endian {
+        "big endian"
+    } else {
+        "little endian"
+    };
+
+    for module in &a2l_file.project.module {
+        if module_big_endian(module, file_big_endian) != file_big_endian {
+            warnings.push(format!(
+                "Warning: the BYTE_ORDER of module {} does not match the byte order of {}, which is {file_byte_order}",
+                module.name, binary.path
+            ));
+        }
+
+        let addresses = get_object_addresses(module);
+        if !addresses.is_empty() && !debug_data.sections.is_empty() {
+            let in_section_count = addresses
+                .iter()
+                .filter(|address| {
+                    debug_data.sections.values().flatten().any(|(start, end)| {
+                        *start <= u64::from(**address) && u64::from(**address) < *end
+                    })
+                })
+                .count();
+            if in_section_count == 0 {
+                warnings.push(format!(
+                    "Warning: none of the {} addresses in module {} lie inside a section of {}. The a2l file might have been created for a different target.",
+                    addresses.len(),
+                    module.name,
+                    binary.path
+                ));
+            }
+        }
+
+        let stamped_build_id = module
+            .mod_par
+            .as_ref()
+            .and_then(|mod_par| mod_par.epk.as_ref())
+            .and_then(|epk| get_stamped_build_id(&epk.identifier));
+        if let (Some(stamped_build_id), Some(build_id)) = (stamped_build_id, &binary.build_id) {
+            if stamped_build_id != build_id {
+                warnings.push(format!(
+                    "Warning: module {} was last updated from the build-id {stamped_build_id}, but {} has the build-id {build_id}",
+                    module.name, binary.path
+                ));
+            }
+        }
+    }
+
+    if binary.is_64bit == Some(true) {
+        let high_address_count = debug_data
+            .variables
+            .values()
+            .flatten()
+            .filter(|varinfo| varinfo.address > u64::from(u32::MAX))
+            .count();
+        if high_address_count > 0 {
+            let architecture = binary.architecture.as_deref().unwrap_or("unknown");
+            warnings.push(format!(
+                "Warning: {} is a 64-bit file for the architecture {architecture}, and {high_address_count} variables are located at addresses which do not fit into the 32-bit addresses of an a2l file",
+                binary.path
+            ));
+        }
+    }
+
+    warnings
+}
+
+// the non-zero addresses of all MEASUREMENTs, CHARACTERISTICs and AXIS_PTS
+fn get_object_addresses(module: &Module) -> Vec<u32> {
+    let measurement_addresses = module
+        .measurement
+        .iter()
+        .filter_map(|measurement| measurement.ecu_address.as_ref())
+        .map(|ecu_address| ecu_address.address);
+    let characteristic_addresses = module
+        .characteristic
+        .iter()
+        .map(|characteristic| characteristic.address);
+    let axis_pts_addresses = module.axis_pts.iter().map(|axis_pts| axis_pts.address);
+    measurement_addresses
+        .chain(characteristic_addresses)
+        .chain(axis_pts_addresses)
+        .filter(|address| *address != 0)
+        .collect()
+}
+
+// the EPK written by stamp_binary_id has the form "<path> build-id <id> timestamp <time>"
+fn get_stamped_build_id(epk: &str) -> Option<&str> {
+    let mut words = epk.split_whitespace();
+    words.find(|word| *word == "build-id")?;
+    words.next()
+}
+
+/// Write the identity of the binary file into the EPK of each module, so that later runs can
+/// detect if the a2l file is updated from a different build.
+/// MOD_PAR is created if it does not exist.
+pub(crate) fn stamp_binary_id(a2l_file: &mut A2lFile, binary: &BinaryInfo) {
+    let stamp = binary.to_string();
+    for module in &mut a2l_file.project.module {
+        let mod_par = module
+            .mod_par
+            .get_or_insert_with(|| ModPar::new(String::new()));
+        mod_par.epk = Some(Epk::new(stamp.clone()));
+    }
+}
+
+/// Set the EPK in MOD_PAR to the version string that is stored in a char array in the binary file,
+/// and set ADDR_EPK to the address of the array, so that the version of the ECU can be compared with the a2l file.
+/// The content of the array must be initialized in the binary file, and it must be printable text.
+///
+/// Returns the new EPK.
+pub(crate) fn set_epk_from_symbol(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    symbol: &str,
+) -> Result<String, String> {
+    let sym_info = find_symbol(symbol, debug_data)
+        .map_err(|errmsg| format!("Error: the EPK symbol {symbol} could not be found: {errmsg}"))?;
+    let size = sym_info.typeinfo.get_size();
+    let bytes = debug_data
+        .memory
+        .read_c_string(sym_info.address, size)
+        .ok_or_else(|| {
+            format!(
+                "Error: the EPK symbol {symbol} at address 0x{:X} has no initialized content in the binary file",
+                sym_info.address
+            )
+        })?;
+    if bytes.is_empty() || !bytes.iter().all(|b| (b' '..=b'~').contains(b)) {
+        return Err(format!(
+            "Error: the content of the EPK symbol {symbol} is not a printable string"
+        ));
+    }
+    let epk = String::from_utf8_lossy(bytes).to_string();
+
+    for module in &mut a2l_file.project.module {
+        let mod_par = module
+            .mod_par
+            .get_or_insert_with(|| ModPar::new(String::new()));
+        mod_par.epk = Some(Epk::new(epk.clone()));
+        mod_par.addr_epk = vec![AddrEpk::new(sym_info.address as u32)];
+    }
+
+    Ok(epk)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_check_binary_compatibility() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_COMMON ""
+      BYTE_ORDER MSB_FIRST
+    /end MOD_COMMON
+    /begin CHARACTERISTIC Far_Away "" VALUE 0x7FFF0000 RL 0 NO_COMPU_METHOD 0 255
+    /end CHARACTERISTIC
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        assert_eq!(debug_data.binary.is_64bit, Some(false));
+        assert_eq!(debug_data.binary.architecture.as_deref(), Some("Arm"));
+
+        let warnings = check_binary_compatibility(&a2l_file, &debug_data);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("does not match the byte order"));
+        assert!(warnings[0].contains("little endian"));
+        assert!(warnings[1].contains("none of the 1 addresses in module m"));
+
+        // the stamp is only compared if both the EPK and the binary file have a build-id
+        debug_data.binary.build_id = Some("0123abcd".to_string());
+        stamp_binary_id(&mut a2l_file, &debug_data.binary);
+        let epk = &a2l_file.project.module[0].mod_par.as_ref().unwrap().epk;
+        assert!(epk
+            .as_ref()
+            .unwrap()
+            .identifier
+            .contains("build-id 0123abcd"));
+        assert_eq!(check_binary_compatibility(&a2l_file, &debug_data).len(), 2);
+
+        debug_data.binary.build_id = Some("4567ef".to_string());
+        let warnings = check_binary_compatibility(&a2l_file, &debug_data);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[2].contains("last updated from the build-id 0123abcd"));
+    }
+
+    #[test]
+    fn test_set_epk_from_symbol() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/epk_test.elf"), false).unwrap();
+        let mut a2l_file = a2lfile::new();
+        assert!(a2l_file.project.module[0].mod_par.is_none());
+
+        let epk = set_epk_from_symbol(&mut a2l_file, &debug_data, "app_version").unwrap();
+        assert_eq!(epk, "ECU_APP V1.2.3 2024-05-01");
+        let mod_par = a2l_file.project.module[0].mod_par.as_ref().unwrap();
+        assert_eq!(mod_par.epk.as_ref().unwrap().identifier, epk);
+        let address = debug_data.variables["app_version"][0].address;
+        assert_eq!(mod_par.addr_epk.len(), 1);
+        assert_eq!(u64::from(mod_par.addr_epk[0].address), address);
+
+        // an existing EPK is replaced
+        a2l_file.project.module[0].mod_par.as_mut().unwrap().epk =
+            Some(Epk::new("old version".to_string()));
+        set_epk_from_symbol(&mut a2l_file, &debug_data, "app_version").unwrap();
+        let mod_par = a2l_file.project.module[0].mod_par.as_ref().unwrap();
+        assert_eq!(mod_par.epk.as_ref().unwrap().identifier, epk);
+        assert_eq!(mod_par.addr_epk.len(), 1);
+
+        // the symbol must exist, be initialized and contain text
+        let error = set_epk_from_symbol(&mut a2l_file, &debug_data, "calibration_id").unwrap_err();
+        assert!(error.contains("not a printable string"));
+        let error = set_epk_from_symbol(&mut a2l_file, &debug_data, "runtime_version").unwrap_err();
+        assert!(error.contains("no initialized content"));
+        assert!(set_epk_from_symbol(&mut a2l_file, &debug_data, "missing_version").is_err());
+    }
+}
@@ -5,8 +5,8 @@ use std::collections::HashSet;
 
 use super::ifdata_update::{update_ifdata_address, update_ifdata_type, zero_if_data};
 use super::{
-    cleanup_item_list, get_symbol_info, make_symbol_link_string, set_symbol_link, A2lUpdateInfo,
-    A2lUpdater, UpdateResult,
+    cleanup_item_list, make_symbol_link_string, remove_preserved_annotation, set_symbol_link,
+    A2lUpdateInfo, A2lUpdater, UpdateResult,
 };
 
 // update all BLOB objects in a module
@@ -20,6 +20,10 @@ pub(crate) fn update_all_module_blobs(
 
     std::mem::swap(&mut data.module.blob, &mut blob_list);
     for mut blob in blob_list {
+        if info.should_stop() || info.out_of_scope(&blob.name) {
+            data.module.blob.push(blob);
+            continue;
+        }
         let update_result = update_module_blob(&mut blob, info);
         if matches!(update_result, UpdateResult::SymbolNotFound { .. }) {
             if info.preserve_unknown {
@@ -30,30 +34,43 @@ pub(crate) fn update_all_module_blobs(
                 removed_items.insert(blob.name.clone());
             }
         } else {
+            remove_preserved_annotation(&mut blob.annotation);
             data.module.blob.push(blob);
         }
+        info.count_result(&update_result);
         results.push(update_result);
     }
     cleanup_removed_blobs(data.module, &removed_items);
+    info.removed_names.borrow_mut().extend(removed_items);
 
     results
 }
 
 // update a single BLOB object
 fn update_module_blob(blob: &mut Blob, info: &A2lUpdateInfo<'_>) -> UpdateResult {
-    match get_symbol_info(
-        &blob.name,
-        &blob.symbol_link,
-        &blob.if_data,
-        info.debug_data,
-    ) {
+    match info.get_symbol_info(&blob.name, &blob.symbol_link, &blob.if_data) {
         // match update_blob_address(&mut blob, debug_data) {
         Ok(sym_info) => {
+            let old_address = u64::from(blob.start_address);
             update_blob_address(blob, info.debug_data, &sym_info);
+            info.check_moved(
+                "BLOB",
+                &blob.name,
+                blob.get_line(),
+                old_address,
+                sym_info.address,
+            );
 
             update_ifdata_address(&mut blob.if_data, &sym_info.name, sym_info.address);
 
-            if info.full_update {
+            if sym_info.is_untyped() && (info.full_update || info.strict_update) {
+                // the data type of the symbol is not known from the debug info, so it can't be updated or verified
+                UpdateResult::AddressOnly {
+                    blocktype: "BLOB",
+                    name: blob.name.clone(),
+                    line: blob.get_line(),
+                }
+            } else if info.full_update {
                 // update the data type of the BLOB object
                 update_ifdata_type(&mut blob.if_data, sym_info.typeinfo);
 
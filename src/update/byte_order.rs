@@ -0,0 +1,233 @@
+use crate::debuginfo::{DebugData, TypeInfo};
+use crate::A2lVersion;
+use a2lfile::{A2lFile, ByteOrder, ByteOrderEnum, ModCommon, Module};
+
+/// Set the default BYTE_ORDER in MOD_COMMON of each module to the byte order of the debug data.
+/// MOD_COMMON is created if it does not exist. The BYTE_ORDER of individual objects is not changed.
+/// Returns the number of modules whose BYTE_ORDER was changed.
+pub(crate) fn sync_byte_order(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let big_endian = debug_data.memory.big_endian;
+    let new_byte_order = if big_endian {
+        ByteOrderEnum::BigEndian
+    } else {
+        ByteOrderEnum::LittleEndian
+    };
+    let mut changed_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let mod_common = module
+            .mod_common
+            .get_or_insert_with(|| ModCommon::new(String::new()));
+        let current = mod_common
+            .byte_order
+            .as_ref()
+            .map(|bo| is_big_endian(bo.byte_order));
+        if current != Some(big_endian) {
+            log_msgs.push(format!(
+                "Module {}: the default BYTE_ORDER in MOD_COMMON was set to {new_byte_order}",
+                module.name
+            ));
+            mod_common.byte_order = Some(ByteOrder::new(new_byte_order));
+            changed_count += 1;
+        }
+    }
+
+    changed_count
+}
+
+// get the default byte order of a module from MOD_COMMON.
+// If MOD_COMMON does not specify a BYTE_ORDER, the byte order of the debug data is used
+pub(crate) fn module_big_endian(module: &Module, file_big_endian: bool) -> bool {
+    module
+        .mod_common
+        .as_ref()
+        .and_then(|mod_common| mod_common.byte_order.as_ref())
+        .map_or(file_big_endian, |bo| is_big_endian(bo.byte_order))
+}
+
+// MEASUREMENT, CHARACTERISTIC, AXIS_PTS and the TYPEDEF_* objects may contain a BYTE_ORDER
+// which overrides the default from MOD_COMMON. This is needed if the variable has an explicit
+// byte order in the debug info (DW_AT_endianity), which differs from the default.
+// A BYTE_ORDER that no longer matches the variable is corrected or removed.
+pub(crate) fn set_byte_order(
+    opt_byte_order: &mut Option<ByteOrder>,
+    typeinfo: &TypeInfo,
+    module_big_endian: bool,
+    file_big_endian: bool,
+    version: A2lVersion,
+) {
+    let typeinfo = typeinfo.get_arraytype().unwrap_or(typeinfo);
+    let current = opt_byte_order
+        .as_ref()
+        .map(|bo| is_big_endian(bo.byte_order));
+    match (typeinfo.big_endian, current) {
+        (Some(big_endian), Some(current)) if big_endian != current => {
+            *opt_byte_order = Some(ByteOrder::new(byte_order_enum(big_endian, version)));
+        }
+        (Some(big_endian), None) if big_endian != module_big_endian => {
+            *opt_byte_order = Some(ByteOrder::new(byte_order_enum(big_endian, version)));
+        }
+        (None, Some(current)) if current != file_big_endian => {
+            // the variable uses the byte order of the file, and the BYTE_ORDER is stale
+            if file_big_endian == module_big_endian {
+                *opt_byte_order = None;
+            } else {
+                *opt_byte_order = Some(ByteOrder::new(byte_order_enum(file_big_endian, version)));
+            }
+        }
+        _ => {}
+    }
+}
+
+// the deprecated MSB_FIRST and MSB_LAST have the same meaning as BIG_ENDIAN and LITTLE_ENDIAN
+fn is_big_endian(byte_order: ByteOrderEnum) -> bool {
+    matches!(
+        byte_order,
+        ByteOrderEnum::BigEndian | ByteOrderEnum::MsbFirst
+    )
+}
+
+// BIG_ENDIAN and LITTLE_ENDIAN are only available in a2l version 1.7.0 and later
+fn byte_order_enum(big_endian: bool, version: A2lVersion) -> ByteOrderEnum {
+    match (big_endian, version >= A2lVersion::V1_7_0) {
+        (true, true) => ByteOrderEnum::BigEndian,
+        (false, true) => ByteOrderEnum::LittleEndian,
+        (true, false) => ByteOrderEnum::MsbFirst,
+        (false, false) => ByteOrderEnum::MsbLast,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_sync_byte_order() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_COMMON ""
+      BYTE_ORDER MSB_LAST
+    /end MOD_COMMON
+  /end MODULE
+/end PROJECT
+"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+
+        // MSB_LAST is little endian, which already matches update_test.elf
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let mut log_msgs = Vec::new();
+        assert_eq!(
+            sync_byte_order(&mut a2l_file, &debug_data, &mut log_msgs),
+            0
+        );
+        assert!(log_msgs.is_empty());
+
+        // big_endian_test.elf was built for big-endian PowerPC
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/big_endian_test.elf"), false)
+                .unwrap();
+        assert!(debug_data.memory.big_endian);
+        assert!(debug_data.variables.contains_key("BigEndianValue"));
+        let mut log_msgs = Vec::new();
+        assert_eq!(
+            sync_byte_order(&mut a2l_file, &debug_data, &mut log_msgs),
+            1
+        );
+        let mod_common = a2l_file.project.module[0].mod_common.as_ref().unwrap();
+        assert_eq!(
+            mod_common.byte_order.as_ref().unwrap().byte_order,
+            ByteOrderEnum::BigEndian
+        );
+
+        // MOD_COMMON is created if it is missing
+        let mut a2l_file = a2lfile::new();
+        assert!(a2l_file.project.module[0].mod_common.is_none());
+        sync_byte_order(&mut a2l_file, &debug_data, &mut log_msgs);
+        let mod_common = a2l_file.project.module[0].mod_common.as_ref().unwrap();
+        assert_eq!(
+            mod_common.byte_order.as_ref().unwrap().byte_order,
+            ByteOrderEnum::BigEndian
+        );
+    }
+
+    #[test]
+    fn test_update_byte_order() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT big_status "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      ECU_ADDRESS 0
+      SYMBOL_LINK "big_regs.status" 0
+    /end MEASUREMENT
+    /begin MEASUREMENT native_status "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      BYTE_ORDER MSB_FIRST
+      ECU_ADDRESS 0
+      SYMBOL_LINK "native_regs.status" 0
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT
+"#;
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/endian_test.elf"), false).unwrap();
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        crate::update::update_a2l(
+            &mut a2l_file,
+            &debug_data,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Full,
+                crate::update::UpdateMode::Default,
+                false,
+            ),
+        );
+
+        // big_status gets a BYTE_ORDER, while the stale BYTE_ORDER of native_status is removed
+        let module = &a2l_file.project.module[0];
+        assert_eq!(
+            module.measurement[0]
+                .byte_order
+                .as_ref()
+                .unwrap()
+                .byte_order,
+            ByteOrderEnum::BigEndian
+        );
+        assert!(module.measurement[1].byte_order.is_none());
+
+        // if the module default is big endian, then the little endian variable needs a BYTE_ORDER instead
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut mod_common = ModCommon::new(String::new());
+        mod_common.byte_order = Some(ByteOrder::new(ByteOrderEnum::BigEndian));
+        a2l_file.project.module[0].mod_common = Some(mod_common);
+        crate::update::update_a2l(
+            &mut a2l_file,
+            &debug_data,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Full,
+                crate::update::UpdateMode::Default,
+                false,
+            ),
+        );
+        let module = &a2l_file.project.module[0];
+        assert!(module.measurement[0].byte_order.is_none());
+        assert_eq!(
+            module.measurement[1]
+                .byte_order
+                .as_ref()
+                .unwrap()
+                .byte_order,
+            ByteOrderEnum::LittleEndian
+        );
+    }
+}
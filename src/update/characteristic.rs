@@ -1,19 +1,24 @@
-use crate::datatype::get_a2l_datatype;
+use crate::bitmask::bitmask_fits;
+use crate::datatype::{get_a2l_datatype, get_wide_string_encoding};
 use crate::debuginfo::DbgDataType;
 use crate::debuginfo::{DebugData, TypeInfo};
 use crate::symbol::SymbolInfo;
 use crate::A2lVersion;
-use a2lfile::{A2lObject, AxisDescr, Characteristic, CharacteristicType, Module, RecordLayout};
+use a2lfile::{
+    A2lObject, AxisDescr, Characteristic, CharacteristicType, Encoding, Module, Number,
+    RecordLayout,
+};
 use std::collections::HashMap;
 use std::collections::HashSet;
 
 use crate::update::{
-    adjust_limits, cleanup_item_list,
+    adjust_limits, check_record_layout_members, cleanup_item_list,
     enums::{cond_create_enum_conversion, update_enum_compu_methods},
-    get_fnc_values_memberid, get_inner_type, get_symbol_info,
+    get_fnc_values_memberid, get_inner_type,
     ifdata_update::{update_ifdata_address, update_ifdata_type, zero_if_data},
-    make_symbol_link_string, set_bitmask, set_matrix_dim, set_symbol_link, update_record_layout,
-    A2lUpdateInfo, A2lUpdater, UpdateResult,
+    make_symbol_link_string, module_big_endian, remove_preserved_annotation, set_bitmask,
+    set_byte_order, set_matrix_dim, set_symbol_link, update_record_layout, A2lUpdateInfo,
+    A2lUpdater, UpdateResult,
 };
 
 // update all CHARACTERISTICs in the module
@@ -38,6 +43,10 @@ pub(crate) fn update_all_module_characteristics(
 
     std::mem::swap(&mut data.module.characteristic, &mut characteristic_list);
     for mut characteristic in characteristic_list {
+        if info.should_stop() || info.out_of_scope(&characteristic.name) {
+            data.module.characteristic.push(characteristic);
+            continue;
+        }
         let update_result = update_module_characteristic(
             &mut characteristic,
             info,
@@ -54,14 +63,17 @@ pub(crate) fn update_all_module_characteristics(
                 removed_items.insert(characteristic.name.clone());
             }
         } else {
+            remove_preserved_annotation(&mut characteristic.annotation);
             data.module.characteristic.push(characteristic);
         }
+        info.count_result(&update_result);
         results.push(update_result);
     }
 
     // update COMPU_VTABs and COMPU_VTAB_RANGEs based on the data types used in CHARACTERISTICs
     update_enum_compu_methods(data.module, &enum_convlist);
     cleanup_removed_characteristics(data.module, &removed_items);
+    info.removed_names.borrow_mut().extend(removed_items);
 
     results
 }
@@ -76,19 +88,26 @@ fn update_module_characteristic<'dbg>(
 ) -> UpdateResult {
     if characteristic.virtual_characteristic.is_none() {
         // only update the address if the CHARACTERISTIC is not a VIRTUAL_CHARACTERISTIC
-        match get_symbol_info(
+        match info.get_symbol_info(
             &characteristic.name,
             &characteristic.symbol_link,
             &characteristic.if_data,
-            info.debug_data,
         ) {
             Ok(sym_info) => {
+                let old_address = u64::from(characteristic.address);
                 update_characteristic_address(
                     characteristic,
                     info.debug_data,
                     info.version,
                     &sym_info,
                 );
+                info.check_moved(
+                    "CHARACTERISTIC",
+                    &characteristic.name,
+                    characteristic.get_line(),
+                    old_address,
+                    sym_info.address,
+                );
 
                 update_ifdata_address(
                     &mut characteristic.if_data,
@@ -96,19 +115,25 @@ fn update_module_characteristic<'dbg>(
                     sym_info.address,
                 );
 
-                if info.full_update {
+                if sym_info.is_untyped() && (info.full_update || info.strict_update) {
+                    // the data type of the symbol is not known from the debug info, so it can't be updated or verified
+                    UpdateResult::AddressOnly {
+                        blocktype: "CHARACTERISTIC",
+                        name: characteristic.name.clone(),
+                        line: characteristic.get_line(),
+                    }
+                } else if info.full_update {
                     // update the data type of the CHARACTERISTIC object
                     update_ifdata_type(&mut characteristic.if_data, sym_info.typeinfo);
 
                     // update as much as possible of the information inside the CHARACTERISTIC
                     update_characteristic_datatype(
                         data,
+                        info,
                         characteristic,
                         sym_info.typeinfo,
                         enum_convlist,
                         axis_pts_dim,
-                        info.version >= A2lVersion::V1_7_0,
-                        &info.compu_method_index,
                     );
                     UpdateResult::Updated
                 } else if info.strict_update {
@@ -162,13 +187,13 @@ fn update_characteristic_address<'dbg>(
 // update as much as possible of the information inside the CHARACTERISTIC
 fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
     data: &mut A2lUpdater,
+    info: &A2lUpdateInfo<'_>,
     characteristic: &mut Characteristic,
     typeinfo: &'typeinfo TypeInfo,
     enum_convlist: &'enumlist mut HashMap<String, &'typeinfo TypeInfo>,
     axis_pts_dim: &HashMap<String, u16>,
-    use_new_matrix_dim: bool,
-    compu_method_index: &HashMap<String, usize>,
 ) {
+    let use_new_matrix_dim = info.version >= A2lVersion::V1_7_0;
     let member_id =
         get_fnc_values_memberid(data.module, &data.reclayout_info, &characteristic.deposit);
     if let Some(inner_typeinfo) = get_inner_type(typeinfo, member_id) {
@@ -184,7 +209,8 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
             enum_convlist.insert(characteristic.conversion.clone(), inner_typeinfo);
         }
 
-        let opt_compu_method = compu_method_index
+        let opt_compu_method = info
+            .compu_method_index
             .get(&characteristic.conversion)
             .and_then(|idx| data.module.compu_method.get(*idx));
         let (ll, ul) = adjust_limits(
@@ -192,11 +218,29 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
             characteristic.lower_limit,
             characteristic.upper_limit,
             opt_compu_method,
+            info.limit_precision,
         );
         characteristic.lower_limit = ll;
         characteristic.upper_limit = ul;
+        info.fix_integer_format(
+            "CHARACTERISTIC",
+            &characteristic.name,
+            characteristic.get_line(),
+            &mut characteristic.format,
+            inner_typeinfo.get_arraytype().unwrap_or(inner_typeinfo),
+            opt_compu_method,
+        );
 
         set_bitmask(&mut characteristic.bit_mask, inner_typeinfo);
+        check_characteristic_bitmask(info, characteristic, inner_typeinfo);
+        let file_big_endian = info.debug_data.memory.big_endian;
+        set_byte_order(
+            &mut characteristic.byte_order,
+            inner_typeinfo,
+            module_big_endian(data.module, file_big_endian),
+            file_big_endian,
+            info.version,
+        );
     }
 
     // Patch up incomplete characteristics: Curve, Map, Cuboid, Cube4 and Cube5 all require AXIS_DESCR to function correctly
@@ -212,10 +256,16 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
         characteristic.characteristic_type = CharacteristicType::Value;
     }
 
+    // if the CHARACTERISTIC represents a string, then NUMBER should contain the length of the string buffer
+    if characteristic.characteristic_type == CharacteristicType::Ascii {
+        update_ascii_number(characteristic, typeinfo, info);
+    }
+
     // if the characteristic does not have any axes, update MATRIX_DIM and switch between types VALUE and VAL_BLK as needed
     if characteristic.characteristic_type == CharacteristicType::Value
         || characteristic.characteristic_type == CharacteristicType::ValBlk
     {
+        let has_number = characteristic.number.is_some();
         set_matrix_dim(&mut characteristic.matrix_dim, typeinfo, use_new_matrix_dim);
         // arrays of values should have the type ValBlk, while single values should NOT have the type ValBlk
         if characteristic.characteristic_type == CharacteristicType::Value
@@ -229,8 +279,12 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
             // change ValBlk -> Value
             characteristic.characteristic_type = CharacteristicType::Value;
         }
-        characteristic.number = None;
-    } else {
+        if characteristic.characteristic_type == CharacteristicType::ValBlk && has_number {
+            update_valblk_number(characteristic, typeinfo, info);
+        } else {
+            characteristic.number = None;
+        }
+    } else if characteristic.characteristic_type != CharacteristicType::Ascii {
         characteristic.matrix_dim = None;
     }
 
@@ -240,6 +294,18 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
         None
     };
 
+    // internal axes are stored in the same struct as the values; the members must be in the order given by the RECORD_LAYOUT
+    if let Some(rl) = record_layout {
+        for problem in check_record_layout_members(rl, typeinfo) {
+            info.change_msgs.borrow_mut().push(format!(
+                "Warning: the RECORD_LAYOUT {} of CHARACTERISTIC {} on line {} does not match the data type of the symbol: {problem}",
+                rl.name,
+                characteristic.name,
+                characteristic.get_line()
+            ));
+        }
+    }
+
     update_characteristic_axis(
         &mut characteristic.axis_descr,
         record_layout,
@@ -254,6 +320,115 @@ fn update_characteristic_datatype<'enumlist, 'typeinfo: 'enumlist>(
     );
 }
 
+// set_bitmask derives the BIT_MASK of a bitfield from the debug info, but a BIT_MASK of any other
+// variable is kept, because it might be an explicit bitfield with a hardcoded shift and mask in the code.
+// Such a BIT_MASK must still fit into the data type of the variable.
+fn check_characteristic_bitmask(
+    info: &A2lUpdateInfo<'_>,
+    characteristic: &mut Characteristic,
+    typeinfo: &TypeInfo,
+) {
+    let Some(bit_mask) = &characteristic.bit_mask else {
+        return;
+    };
+    let datatype = get_a2l_datatype(typeinfo);
+    if bitmask_fits(bit_mask.mask, &datatype) {
+        return;
+    }
+    let msg = format!(
+        "CHARACTERISTIC {} on line {}: the BIT_MASK 0x{:X} does not fit into the data type {datatype} of the variable",
+        characteristic.name,
+        characteristic.get_line(),
+        bit_mask.mask
+    );
+    if info.fix_bitmasks {
+        info.change_msgs
+            .borrow_mut()
+            .push(format!("{msg}; the BIT_MASK was removed"));
+        characteristic.bit_mask = None;
+    } else {
+        info.change_msgs
+            .borrow_mut()
+            .push(format!("Warning: {msg}"));
+    }
+}
+
+// update NUMBER of an ASCII CHARACTERISTIC to match the length of its string buffer
+fn update_ascii_number(
+    characteristic: &mut Characteristic,
+    typeinfo: &TypeInfo,
+    info: &A2lUpdateInfo<'_>,
+) {
+    // a string is an array of characters. We only require the array, because a
+    // character type can be different things in different situations or languages: e.g. char / wchar_t
+    if let DbgDataType::Array { dim, .. } = &typeinfo.datatype {
+        if dim.len() == 1 {
+            let new_number = u16::try_from(dim[0]).unwrap_or(u16::MAX);
+            let old_number = characteristic.number.as_ref().map(|number| number.number);
+            if old_number != Some(new_number) {
+                characteristic.number = Some(Number::new(new_number));
+                info.change_msgs.borrow_mut().push(format!(
+                    "Info: NUMBER of CHARACTERISTIC {} on line {} was changed from {} to {new_number}",
+                    characteristic.name,
+                    characteristic.get_line(),
+                    old_number.map_or("<none>".to_string(), |num| num.to_string())
+                ));
+            }
+            if characteristic.matrix_dim.take().is_some() {
+                info.change_msgs.borrow_mut().push(format!(
+                    "Info: MATRIX_DIM was removed from the ASCII CHARACTERISTIC {} on line {}",
+                    characteristic.name,
+                    characteristic.get_line()
+                ));
+            }
+            // strings of wide characters need an ENCODING; for byte strings any existing ENCODING is kept
+            if let Some(encoding) = get_wide_string_encoding(typeinfo) {
+                if info.version >= A2lVersion::V1_7_0 {
+                    characteristic.encoding = Some(Encoding::new(encoding));
+                }
+            }
+        }
+        // don't know what to do with multi-dimensional arrays, so just leave those untouched
+    } else {
+        // clearly this is not a string - change the type to value instead
+        characteristic.characteristic_type = CharacteristicType::Value;
+    }
+}
+
+// A VAL_BLK can describe its size with NUMBER instead of MATRIX_DIM.
+// NUMBER is deprecated since version 1.6.1, but it is only replaced by MATRIX_DIM if this was requested.
+// NUMBER can't describe multi-dimensional arrays, so it is always replaced in this case.
+fn update_valblk_number(
+    characteristic: &mut Characteristic,
+    typeinfo: &TypeInfo,
+    info: &A2lUpdateInfo<'_>,
+) {
+    let convert = info.number_to_matrix_dim && info.version >= A2lVersion::V1_6_1;
+    let name = characteristic.name.clone();
+    let line = characteristic.get_line();
+    match &typeinfo.datatype {
+        DbgDataType::Array { dim, .. } if dim.len() == 1 && !convert => {
+            let new_number = u16::try_from(dim[0]).unwrap_or(u16::MAX);
+            characteristic.matrix_dim = None;
+            if let Some(number) = &mut characteristic.number {
+                if number.number != new_number {
+                    info.change_msgs.borrow_mut().push(format!(
+                        "Info: NUMBER of CHARACTERISTIC {name} on line {line} was changed from {} to {new_number}",
+                        number.number
+                    ));
+                    number.number = new_number;
+                }
+            }
+        }
+        _ => {
+            characteristic.number = None;
+            info.change_msgs.borrow_mut().push(format!(
+                "Info: NUMBER of CHARACTERISTIC {name} on line {line} was replaced by MATRIX_DIM"
+            ));
+        }
+    }
+}
+
 // update all the AXIS_DESCRs inside a CHARACTERISTIC (or TYPEDEF_CHARACTERISTIC)
 // for the list of AXIS_DESCR the ordering matters: the first AXIS_DESCR describes the x axis, the second describes the y axis, etc.
 pub(crate) fn update_characteristic_axis(
@@ -331,6 +506,7 @@ fn verify_characteristic_datatype<'dbg>(
             characteristic.lower_limit,
             characteristic.upper_limit,
             opt_compu_method,
+            info.limit_precision,
         );
         if ll != characteristic.lower_limit || ul != characteristic.upper_limit {
             bad_characteristic = true;
@@ -352,12 +528,19 @@ fn verify_characteristic_datatype<'dbg>(
                 }
             }
             CharacteristicType::ValBlk => {
-                // the matrix dim of a ValBlk must exist and remain unchanged
-                set_matrix_dim(&mut dummy_matrix_dim, inner_typeinfo, use_new_matrix_dim);
-                if characteristic.matrix_dim.is_none()
-                    || dummy_matrix_dim != characteristic.matrix_dim
-                {
-                    bad_characteristic = true;
+                if let Some(number) = &characteristic.number {
+                    // a ValBlk that uses NUMBER must still have the same length
+                    if !number_matches_array(number, typeinfo) {
+                        bad_characteristic = true;
+                    }
+                } else {
+                    // the matrix dim of a ValBlk must exist and remain unchanged
+                    set_matrix_dim(&mut dummy_matrix_dim, inner_typeinfo, use_new_matrix_dim);
+                    if characteristic.matrix_dim.is_none()
+                        || dummy_matrix_dim != characteristic.matrix_dim
+                    {
+                        bad_characteristic = true;
+                    }
                 }
             }
             CharacteristicType::Map
@@ -371,7 +554,12 @@ fn verify_characteristic_datatype<'dbg>(
                 }
             }
             CharacteristicType::Ascii => {
-                // no extra checks for ASCII
+                // the length of the string buffer must not have changed
+                if let Some(number) = &characteristic.number {
+                    if !number_matches_array(number, typeinfo) {
+                        bad_characteristic = true;
+                    }
+                }
             }
         }
 
@@ -408,6 +596,11 @@ fn verify_characteristic_datatype<'dbg>(
     }
 }
 
+// check if NUMBER matches the length of a one-dimensional array
+fn number_matches_array(number: &Number, typeinfo: &TypeInfo) -> bool {
+    matches!(&typeinfo.datatype, DbgDataType::Array { dim, .. } if dim.len() == 1 && dim[0] == u64::from(number.number))
+}
+
 // when update runs without preserve, CHARACTERISTICs could be removed from the module
 // these items should also be removed from the identifier lists in GROUPs and FUNCTIONs
 pub(crate) fn cleanup_removed_characteristics(
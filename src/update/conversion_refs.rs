@@ -0,0 +1,167 @@
+use a2lfile::{A2lObject, ConversionType, Module};
+use std::collections::HashSet;
+
+// Check that all conversions referenced by MEASUREMENTs, CHARACTERISTICs, AXIS_PTS, their AXIS_DESCRs
+// and the TYPEDEFs exist, and that the tables referenced by the table-based COMPU_METHODs exist too.
+// NO_COMPU_METHOD is the predefined identity conversion, so it never needs to exist in the file.
+// Returns a warning for each broken reference.
+pub(crate) fn check_conversion_refs(module: &Module) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let compu_methods: HashSet<&str> = module
+        .compu_method
+        .iter()
+        .map(|cm| cm.name.as_str())
+        .collect();
+
+    let mut check_ref = |blocktype: &str, name: &str, line: u32, conversion: &str| {
+        if conversion != "NO_COMPU_METHOD" && !compu_methods.contains(conversion) {
+            warnings.push(format!(
+                "Warning: {blocktype} {name} on line {line} references the COMPU_METHOD {conversion}, which does not exist"
+            ));
+        }
+    };
+
+    for measurement in &module.measurement {
+        check_ref(
+            "MEASUREMENT",
+            &measurement.name,
+            measurement.get_line(),
+            &measurement.conversion,
+        );
+    }
+    for characteristic in &module.characteristic {
+        check_ref(
+            "CHARACTERISTIC",
+            &characteristic.name,
+            characteristic.get_line(),
+            &characteristic.conversion,
+        );
+        for axis_descr in &characteristic.axis_descr {
+            check_ref(
+                "AXIS_DESCR of CHARACTERISTIC",
+                &characteristic.name,
+                axis_descr.get_line(),
+                &axis_descr.conversion,
+            );
+        }
+    }
+    for axis_pts in &module.axis_pts {
+        check_ref(
+            "AXIS_PTS",
+            &axis_pts.name,
+            axis_pts.get_line(),
+            &axis_pts.conversion,
+        );
+    }
+    for td_meas in &module.typedef_measurement {
+        check_ref(
+            "TYPEDEF_MEASUREMENT",
+            &td_meas.name,
+            td_meas.get_line(),
+            &td_meas.conversion,
+        );
+    }
+    for td_char in &module.typedef_characteristic {
+        check_ref(
+            "TYPEDEF_CHARACTERISTIC",
+            &td_char.name,
+            td_char.get_line(),
+            &td_char.conversion,
+        );
+        for axis_descr in &td_char.axis_descr {
+            check_ref(
+                "AXIS_DESCR of TYPEDEF_CHARACTERISTIC",
+                &td_char.name,
+                axis_descr.get_line(),
+                &axis_descr.conversion,
+            );
+        }
+    }
+    for td_axis in &module.typedef_axis {
+        check_ref(
+            "TYPEDEF_AXIS",
+            &td_axis.name,
+            td_axis.get_line(),
+            &td_axis.conversion,
+        );
+    }
+
+    // table-based conversions need a COMPU_TAB, COMPU_VTAB or COMPU_VTAB_RANGE
+    let conversion_tables: HashSet<&str> = module
+        .compu_tab
+        .iter()
+        .map(|tab| tab.name.as_str())
+        .chain(module.compu_vtab.iter().map(|vtab| vtab.name.as_str()))
+        .chain(
+            module
+                .compu_vtab_range
+                .iter()
+                .map(|vtab_range| vtab_range.name.as_str()),
+        )
+        .collect();
+    for compu_method in &module.compu_method {
+        if !matches!(
+            compu_method.conversion_type,
+            ConversionType::TabVerb | ConversionType::TabIntp | ConversionType::TabNointp
+        ) {
+            continue;
+        }
+        if let Some(compu_tab_ref) = &compu_method.compu_tab_ref {
+            if !conversion_tables.contains(compu_tab_ref.conversion_table.as_str()) {
+                warnings.push(format!(
+                    "Warning: COMPU_METHOD {} on line {} references the conversion table {}, which does not exist",
+                    compu_method.name,
+                    compu_method.get_line(),
+                    compu_tab_ref.conversion_table
+                ));
+            }
+        } else {
+            warnings.push(format!(
+                "Warning: COMPU_METHOD {} on line {} is of type {} but has no COMPU_TAB_REF",
+                compu_method.name,
+                compu_method.get_line(),
+                compu_method.conversion_type
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_conversion_refs() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Meas_Ok "" UBYTE Enum_Ok 0 0 0 255
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_Identity "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end MEASUREMENT
+    /begin MEASUREMENT Meas_Missing "" UBYTE Missing_Method 0 0 0 255
+    /end MEASUREMENT
+    /begin COMPU_METHOD Enum_Ok "" TAB_VERB "%.4" ""
+      COMPU_TAB_REF Enum_Ok
+    /end COMPU_METHOD
+    /begin COMPU_VTAB Enum_Ok "" TAB_VERB 1
+      0 "Off"
+    /end COMPU_VTAB
+    /begin COMPU_METHOD Enum_Missing_Table "" TAB_VERB "%.4" ""
+      COMPU_TAB_REF Missing_Table
+    /end COMPU_METHOD
+    /begin COMPU_METHOD Identical "" IDENTICAL "%.4" ""
+    /end COMPU_METHOD
+  /end MODULE
+/end PROJECT"#;
+        let a2l_file = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let warnings = check_conversion_refs(&a2l_file.project.module[0]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("MEASUREMENT Meas_Missing on line 8"));
+        assert!(warnings[0].contains("Missing_Method"));
+        assert!(warnings[1].contains("COMPU_METHOD Enum_Missing_Table"));
+        assert!(warnings[1].contains("Missing_Table"));
+    }
+}
@@ -233,6 +233,7 @@ mod test {
         unit_idx: 0,
         datatype: DbgDataType::Uint32,
         dbginfo_offset: 0,
+        big_endian: None,
     };
 
     fn test_setup(input: &str) -> a2lfile::A2lFile {
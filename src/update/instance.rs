@@ -7,10 +7,11 @@ use std::collections::HashSet;
 
 use crate::update::{
     cleanup_removed_axis_pts, cleanup_removed_blobs, cleanup_removed_characteristics,
-    cleanup_removed_measurements, get_symbol_info,
+    cleanup_removed_measurements,
     ifdata_update::{update_ifdata_address, update_ifdata_type, zero_if_data},
-    make_symbol_link_string, set_address_type, set_matrix_dim, set_symbol_link, A2lUpdateInfo,
-    A2lUpdater, TypedefNames, TypedefReferrer, TypedefsRefInfo, UpdateResult,
+    make_symbol_link_string, remove_preserved_annotation, set_address_type, set_matrix_dim,
+    set_symbol_link, A2lUpdateInfo, A2lUpdater, TypedefNames, TypedefReferrer, TypedefsRefInfo,
+    UpdateResult,
 };
 
 // update all INSTANCE objects in a module
@@ -26,6 +27,14 @@ pub(crate) fn update_all_module_instances<'dbg>(
     let mut instance_list = Vec::new();
     std::mem::swap(&mut data.module.instance, &mut instance_list);
     for mut instance in instance_list {
+        if info.should_stop() || info.out_of_scope(&instance.name) {
+            // keep the remaining INSTANCEs and the INSTANCEs outside of the update scope unchanged, together with their TYPEDEFs
+            let entry = typedef_types.entry(instance.type_ref.clone());
+            let typedef_map_value = (None, TypedefReferrer::Instance(data.module.instance.len()));
+            entry.or_default().push(typedef_map_value);
+            data.module.instance.push(instance);
+            continue;
+        }
         let (update_result, opt_typeinfo) = update_module_instance(&mut instance, info, nameset);
 
         // prepare the typedef map entry for the instance
@@ -46,13 +55,16 @@ pub(crate) fn update_all_module_instances<'dbg>(
                 removed_items.insert(instance.name.clone());
             }
         } else {
+            remove_preserved_annotation(&mut instance.annotation);
             data.module.instance.push(instance);
             // store the typeinfo and the index of the INSTANCE object to enable updating the TYPEDEF_* object later
             entry.or_default().push(typedef_map_value);
         }
+        info.count_result(&update_result);
         results.push(update_result);
     }
     cleanup_removed_instances(data.module, &removed_items);
+    info.removed_names.borrow_mut().extend(removed_items);
 
     (results, typedef_types)
 }
@@ -63,15 +75,18 @@ fn update_module_instance<'dbg>(
     info: &A2lUpdateInfo<'dbg>,
     nameset: &TypedefNames,
 ) -> (UpdateResult, Option<&'dbg TypeInfo>) {
-    match get_symbol_info(
-        &instance.name,
-        &instance.symbol_link,
-        &instance.if_data,
-        info.debug_data,
-    ) {
+    match info.get_symbol_info(&instance.name, &instance.symbol_link, &instance.if_data) {
         // match update_instance_address(&mut instance, info.debug_data) {
         Ok(sym_info) => {
+            let old_address = u64::from(instance.start_address);
             update_instance_address(instance, info.debug_data, &sym_info);
+            info.check_moved(
+                "INSTANCE",
+                &instance.name,
+                instance.get_line(),
+                old_address,
+                sym_info.address,
+            );
             update_ifdata_address(&mut instance.if_data, &sym_info.name, sym_info.address);
 
             let type_ref_valid = nameset.contains(&instance.type_ref);
@@ -85,7 +100,15 @@ fn update_module_instance<'dbg>(
 
             let basetype = basetype.get_arraytype().unwrap_or(basetype);
 
-            if info.full_update {
+            if sym_info.is_untyped() && (info.full_update || info.strict_update) {
+                // the symbol is only known from the map file, so there is no type for a TYPEDEF_STRUCTURE
+                let result = UpdateResult::AddressOnly {
+                    blocktype: "INSTANCE",
+                    name: instance.name.clone(),
+                    line: instance.get_line(),
+                };
+                (result, None)
+            } else if info.full_update {
                 if type_ref_valid {
                     update_instance_datatype(info, instance, sym_info.typeinfo);
                 }
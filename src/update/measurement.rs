@@ -9,9 +9,10 @@ use std::collections::HashSet;
 use crate::update::{
     adjust_limits, cleanup_item_list,
     enums::{cond_create_enum_conversion, update_enum_compu_methods},
-    get_a2l_datatype, get_symbol_info,
+    get_a2l_datatype,
     ifdata_update::{update_ifdata_address, update_ifdata_type, zero_if_data},
-    set_bitmask, set_matrix_dim, set_measurement_ecu_address, set_symbol_link, A2lUpdater,
+    module_big_endian, remove_preserved_annotation, set_bitmask, set_byte_order, set_matrix_dim,
+    set_measurement_ecu_address, set_symbol_link, A2lUpdater,
 };
 
 use super::{make_symbol_link_string, set_address_type, A2lUpdateInfo, UpdateResult};
@@ -27,6 +28,10 @@ pub(crate) fn update_all_module_measurements(
 
     std::mem::swap(&mut data.module.measurement, &mut measurement_list);
     for mut measurement in measurement_list {
+        if info.should_stop() || info.out_of_scope(&measurement.name) {
+            data.module.measurement.push(measurement);
+            continue;
+        }
         let update_result =
             update_module_measurement(&mut measurement, info, data, &mut enum_convlist);
         if matches!(update_result, UpdateResult::SymbolNotFound { .. }) {
@@ -38,14 +43,17 @@ pub(crate) fn update_all_module_measurements(
                 removed_items.insert(measurement.name.clone());
             }
         } else {
+            remove_preserved_annotation(&mut measurement.annotation);
             data.module.measurement.push(measurement);
         }
+        info.count_result(&update_result);
         results.push(update_result);
     }
 
     // update COMPU_VTABs and COMPU_VTAB_RANGEs based on the data types used in MEASUREMENTs
     update_enum_compu_methods(data.module, &enum_convlist);
     cleanup_removed_measurements(data.module, &removed_items);
+    info.removed_names.borrow_mut().extend(removed_items);
 
     results
 }
@@ -58,19 +66,36 @@ fn update_module_measurement<'dbg>(
 ) -> UpdateResult {
     if measurement.var_virtual.is_none() {
         // only MEASUREMENTS that are not VIRTUAL can be updated
-        match get_symbol_info(
+        match info.get_symbol_info(
             &measurement.name,
             &measurement.symbol_link,
             &measurement.if_data,
-            info.debug_data,
         ) {
             // match update_measurement_address(&mut measurement, info.debug_data, info.version) {
             Ok(sym_info) => {
+                let old_address = measurement
+                    .ecu_address
+                    .as_ref()
+                    .map_or(0, |ecu_address| u64::from(ecu_address.address));
                 update_measurement_address(measurement, info.debug_data, info.version, &sym_info);
+                info.check_moved(
+                    "MEASUREMENT",
+                    &measurement.name,
+                    measurement.get_line(),
+                    old_address,
+                    sym_info.address,
+                );
 
                 update_ifdata_address(&mut measurement.if_data, &sym_info.name, sym_info.address);
 
-                if info.full_update {
+                if sym_info.is_untyped() && (info.full_update || info.strict_update) {
+                    // the data type of the symbol is not known from the debug info, so it can't be updated or verified
+                    UpdateResult::AddressOnly {
+                        blocktype: "MEASUREMENT",
+                        name: measurement.name.clone(),
+                        line: measurement.get_line(),
+                    }
+                } else if info.full_update {
                     // update the data type of the MEASUREMENT object
                     update_ifdata_type(&mut measurement.if_data, sym_info.typeinfo);
 
@@ -133,9 +158,7 @@ fn update_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
 ) {
     // handle pointers - only allowed for version 1.7.0+ (the caller should take care of this precondition)
     set_address_type(&mut measurement.address_type, typeinfo);
-    let typeinfo = typeinfo
-        .get_pointer(&info.debug_data.types)
-        .map_or(typeinfo, |(_, t)| t);
+    let typeinfo = typeinfo.get_pointer_target(&info.debug_data.types);
 
     // handle arrays and unwrap the typeinfo
     let use_new_matrix_dim = info.version >= A2lVersion::V1_7_0;
@@ -163,12 +186,29 @@ fn update_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
         measurement.lower_limit,
         measurement.upper_limit,
         opt_compu_method,
+        info.limit_precision,
     );
     measurement.lower_limit = ll;
     measurement.upper_limit = ul;
+    info.fix_integer_format(
+        "MEASUREMENT",
+        &measurement.name,
+        measurement.get_line(),
+        &mut measurement.format,
+        typeinfo,
+        opt_compu_method,
+    );
 
     measurement.datatype = get_a2l_datatype(typeinfo);
     set_bitmask(&mut measurement.bit_mask, typeinfo);
+    let file_big_endian = info.debug_data.memory.big_endian;
+    set_byte_order(
+        &mut measurement.byte_order,
+        typeinfo,
+        module_big_endian(module, file_big_endian),
+        file_big_endian,
+        info.version,
+    );
 }
 
 fn verify_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
@@ -180,9 +220,7 @@ fn verify_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
     // handle pointers - only allowed for version 1.7.0+ (the caller should take care of this precondition)
     let mut dummy_address_type = measurement.address_type.clone();
     set_address_type(&mut dummy_address_type, typeinfo);
-    let typeinfo = typeinfo
-        .get_pointer(&info.debug_data.types)
-        .map_or(typeinfo, |(_, t)| t);
+    let typeinfo = typeinfo.get_pointer_target(&info.debug_data.types);
 
     // handle arrays and unwrap the typeinfo
     let use_new_matrix_dim = info.version >= A2lVersion::V1_7_0;
@@ -207,6 +245,7 @@ fn verify_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
         measurement.lower_limit,
         measurement.upper_limit,
         opt_compu_method,
+        info.limit_precision,
     );
 
     let computed_datatype = get_a2l_datatype(typeinfo);
@@ -234,6 +273,8 @@ fn verify_measurement_datatype<'enumlist, 'typeinfo: 'enumlist>(
 
 // when update runs without preserve some MEASUREMENTs could be removed
 // these items should also be removed from the identifier lists in GROUPs, FUNCTIONs, etc
+// References via INPUT_QUANTITY and COMPARISON_QUANTITY are handled by cleanup_removed_quantity_refs
+// once all objects have been updated.
 pub(crate) fn cleanup_removed_measurements(module: &mut Module, removed_items: &HashSet<String>) {
     if removed_items.is_empty() {
         return;
@@ -268,38 +309,4 @@ pub(crate) fn cleanup_removed_measurements(module: &mut Module, removed_items: &
             }
         }
     }
-
-    for characteristic in &mut module.characteristic {
-        for axis_descr in &mut characteristic.axis_descr {
-            if removed_items.get(&axis_descr.input_quantity).is_some() {
-                axis_descr.input_quantity = "NO_INPUT_QUANTITY".to_string();
-            }
-        }
-
-        if let Some(comparison_quantity) = &characteristic.comparison_quantity {
-            if removed_items.get(&comparison_quantity.name).is_some() {
-                characteristic.comparison_quantity = None;
-            }
-        }
-    }
-
-    for typedef_characteristic in &mut module.typedef_characteristic {
-        for axis_descr in &mut typedef_characteristic.axis_descr {
-            if removed_items.get(&axis_descr.input_quantity).is_some() {
-                axis_descr.input_quantity = "NO_INPUT_QUANTITY".to_string();
-            }
-        }
-    }
-
-    for axis_pts in &mut module.axis_pts {
-        if removed_items.get(&axis_pts.input_quantity).is_some() {
-            axis_pts.input_quantity = "NO_INPUT_QUANTITY".to_string();
-        }
-    }
-
-    for typedef_axis in &mut module.typedef_axis {
-        if removed_items.get(&typedef_axis.input_quantity).is_some() {
-            typedef_axis.input_quantity = "NO_INPUT_QUANTITY".to_string();
-        }
-    }
 }
@@ -0,0 +1,109 @@
+use crate::debuginfo::DebugData;
+use a2lfile::{A2lFile, A2lObject};
+
+/// Set the address and size of the MEMORY_SEGMENTs in MOD_PAR from the sections of the elf file.
+///
+/// A MEMORY_SEGMENT matches a section if it has the same name, or if the section name is the
+/// segment name with a leading '.', e.g. the segment "data" matches the section ".data". The leading
+/// '.' is needed because the name of a MEMORY_SEGMENT must be a valid identifier.
+/// Segments without a matching section are left untouched.
+/// Returns the number of MEMORY_SEGMENTs that were updated.
+pub(crate) fn update_memory_segments(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut updated_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let Some(mod_par) = &mut module.mod_par else {
+            continue;
+        };
+        for memory_segment in &mut mod_par.memory_segment {
+            let line = memory_segment.get_layout().line;
+            let dotted_name = format!(".{}", memory_segment.name);
+            let section_ranges = debug_data
+                .sections
+                .get(&memory_segment.name)
+                .or_else(|| debug_data.sections.get(&dotted_name));
+            match section_ranges.map(Vec::as_slice) {
+                Some([(start, end)]) => {
+                    let address = *start as u32;
+                    let size = (*end - *start) as u32;
+                    if address != memory_segment.address || size != memory_segment.size {
+                        log_msgs.push(format!(
+                            "MEMORY_SEGMENT {} on line {line}: address 0x{:X} and size 0x{:X} changed to address 0x{address:X} and size 0x{size:X}",
+                            memory_segment.name, memory_segment.address, memory_segment.size
+                        ));
+                        memory_segment.address = address;
+                        memory_segment.size = size;
+                    }
+                    updated_count += 1;
+                }
+                Some(_) => {
+                    log_msgs.push(format!(
+                        "Warning: MEMORY_SEGMENT {} on line {line} was not changed: there are several sections with this name",
+                        memory_segment.name
+                    ));
+                }
+                None => {
+                    log_msgs.push(format!(
+                        "Warning: MEMORY_SEGMENT {} on line {line} was not changed: there is no section with this name",
+                        memory_segment.name
+                    ));
+                }
+            }
+        }
+    }
+
+    updated_count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_update_memory_segments() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_PAR ""
+      /begin MEMORY_SEGMENT data "" DATA RAM INTERN 0x0 0x0 -1 -1 -1 -1 -1
+      /end MEMORY_SEGMENT
+      /begin MEMORY_SEGMENT Calibration "" DATA FLASH INTERN 0x8000 0x1000 -1 -1 -1 -1 -1
+      /end MEMORY_SEGMENT
+    /end MOD_PAR
+  /end MODULE
+/end PROJECT
+"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let (start, end) = debug_data.sections[".data"][0];
+
+        let mut log_msgs = Vec::new();
+        let count = update_memory_segments(&mut a2l_file, &debug_data, &mut log_msgs);
+        assert_eq!(count, 1);
+        let memory_segments = &a2l_file.project.module[0]
+            .mod_par
+            .as_ref()
+            .unwrap()
+            .memory_segment;
+        assert_eq!(u64::from(memory_segments[0].address), start);
+        assert_eq!(u64::from(memory_segments[0].size), end - start);
+        // there is no section named Calibration
+        assert_eq!(memory_segments[1].address, 0x8000);
+        assert_eq!(memory_segments[1].size, 0x1000);
+        assert_eq!(log_msgs.len(), 2);
+        assert!(log_msgs[1].starts_with("Warning: MEMORY_SEGMENT Calibration"));
+
+        // a second update finds nothing to change
+        let mut log_msgs = Vec::new();
+        let count = update_memory_segments(&mut a2l_file, &debug_data, &mut log_msgs);
+        assert_eq!(count, 1);
+        assert_eq!(log_msgs.len(), 1);
+    }
+}
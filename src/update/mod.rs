@@ -1,37 +1,64 @@
 use crate::debuginfo::{make_simple_unit_name, DebugData, TypeInfo};
 use crate::{ifdata, A2lVersion};
 use a2lfile::{
-    A2lFile, A2lObject, AddrType, AddressType, BitMask, CompuMethod, EcuAddress, IfData, MatrixDim,
-    Module, SymbolLink,
+    A2lFile, A2lObject, AddrType, AddressType, BitMask, CompuMethod, ConversionType, EcuAddress,
+    Format, IfData, MatrixDim, Module, SymbolLink,
 };
 use instance::update_all_module_instances;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::ops::AddAssign;
+use std::time::Instant;
 
+mod access_flags;
 mod axis_pts;
+mod binary_check;
 mod blob;
+mod byte_order;
 mod characteristic;
+mod conversion_refs;
 pub mod enums;
 mod ifdata_update;
 mod instance;
 mod measurement;
+mod memory_segments;
+mod preserved;
+mod quantity_refs;
 mod record_layout;
+mod symbol_links;
+mod system_constants;
 pub(crate) mod typedef;
 
 use crate::datatype::{get_a2l_datatype, get_type_limits};
 use crate::debuginfo::DbgDataType;
-use crate::symbol::{find_symbol, find_symbol_by_offset, SymbolInfo};
+use crate::formats::FormatSpec;
+use crate::symbol::{
+    find_symbol, find_symbol_by_offset, get_resolution_note, SymbolInfo, MAX_LISTED_CANDIDATES,
+};
+pub(crate) use access_flags::check_access_flags;
+pub(crate) use axis_pts::check_axis_pts_dimensions;
 use axis_pts::*;
+pub(crate) use binary_check::{check_binary_compatibility, set_epk_from_symbol, stamp_binary_id};
 use blob::{cleanup_removed_blobs, update_all_module_blobs};
+pub(crate) use byte_order::{module_big_endian, set_byte_order, sync_byte_order};
 use characteristic::*;
+use conversion_refs::check_conversion_refs;
 use measurement::*;
+pub(crate) use memory_segments::update_memory_segments;
+use preserved::remove_preserved_annotation;
+pub(crate) use preserved::{annotate_preserved_items, write_preserved_report};
+use quantity_refs::cleanup_removed_quantity_refs;
 use record_layout::*;
-use typedef::update_module_typedefs;
+pub(crate) use record_layout::{create_default_record_layout, RecordLayoutInfo};
+pub(crate) use symbol_links::check_symbol_links;
+pub(crate) use system_constants::update_system_constants;
+use typedef::{update_module_typedefs, update_typedef_structure_offsets};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum UpdateType {
     Full,
     Addresses,
+    Typedefs,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,14 +72,42 @@ pub(crate) enum UpdateMode {
 pub(crate) struct UpdateSumary {
     pub(crate) measurement_updated: u32,
     pub(crate) measurement_not_updated: u32,
+    pub(crate) measurement_type_changed: u32,
     pub(crate) characteristic_updated: u32,
     pub(crate) characteristic_not_updated: u32,
+    pub(crate) characteristic_type_changed: u32,
     pub(crate) axis_pts_updated: u32,
     pub(crate) axis_pts_not_updated: u32,
+    pub(crate) axis_pts_type_changed: u32,
     pub(crate) blob_updated: u32,
     pub(crate) blob_not_updated: u32,
+    pub(crate) blob_type_changed: u32,
     pub(crate) instance_updated: u32,
     pub(crate) instance_not_updated: u32,
+    pub(crate) instance_type_changed: u32,
+    // all objects whose symbols were not found
+    pub(crate) not_found: Vec<NotFoundItem>,
+    // objects whose address changed by more than the threshold of --report-moved
+    pub(crate) moved: Vec<MovedItem>,
+}
+
+// an object whose symbol could not be found during the update
+#[derive(Debug, Clone)]
+pub(crate) struct NotFoundItem {
+    pub(crate) blocktype: &'static str,
+    pub(crate) name: String,
+    pub(crate) line: u32,
+    pub(crate) errors: Vec<String>,
+}
+
+// an object whose symbol was found at a different address
+#[derive(Debug, Clone)]
+pub(crate) struct MovedItem {
+    pub(crate) blocktype: &'static str,
+    pub(crate) name: String,
+    pub(crate) line: u32,
+    pub(crate) old_address: u64,
+    pub(crate) new_address: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +138,21 @@ enum UpdateResult {
         name: String,
         line: u32,
     },
+    // the symbol was found in the map file or in the ELF symbol table: the address was updated, but the data type is unknown
+    AddressOnly {
+        blocktype: &'static str,
+        name: String,
+        line: u32,
+    },
+}
+
+impl UpdateResult {
+    fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            UpdateResult::Updated | UpdateResult::AddressOnly { .. }
+        )
+    }
 }
 
 // the data used by the a2l update has been split into two parts.
@@ -96,6 +166,38 @@ pub(crate) struct A2lUpdateInfo<'dbg> {
     pub(crate) version: A2lVersion,
     pub(crate) enable_structures: bool,
     pub(crate) compu_method_index: HashMap<String, usize>,
+    pub(crate) limit_precision: Option<u8>,
+    pub(crate) max_errors: Option<u32>,
+    // counts the errors of all objects, so that the update can stop once max_errors is reached
+    pub(crate) error_count: Cell<u32>,
+    pub(crate) fix_layouts: bool,
+    // only INSTANCEs and their TYPEDEFs are updated; all other objects are left unchanged
+    pub(crate) typedefs_only: bool,
+    // the symbol in the CANAPE_EXT LINK_MAP takes precedence over the SYMBOL_LINK
+    pub(crate) prefer_ifdata_symbol: bool,
+    // warnings about objects whose SYMBOL_LINK and LINK_MAP refer to different symbols
+    pub(crate) symbol_warnings: RefCell<Vec<String>>,
+    // the update is stopped once this point in time is reached
+    pub(crate) deadline: Option<Instant>,
+    // the deprecated NUMBER of VAL_BLK CHARACTERISTICs is replaced by MATRIX_DIM
+    pub(crate) number_to_matrix_dim: bool,
+    // BIT_MASKs that do not fit into the data type of the variable are removed instead of only being reported
+    pub(crate) fix_bitmasks: bool,
+    // messages about changes and problems that are not reported as part of the UpdateResult of an object
+    pub(crate) change_msgs: RefCell<Vec<String>>,
+    // if present, only the objects with these names are updated
+    pub(crate) update_scope: Option<HashSet<String>>,
+    // the names of all objects that were removed by the update, so that references to them can be cleaned up
+    pub(crate) removed_names: RefCell<HashSet<String>>,
+    // objects whose address changes by more than this number of bytes are reported as moved
+    pub(crate) report_moved: Option<u64>,
+    pub(crate) moved_items: RefCell<Vec<MovedItem>>,
+    // objects that were linked to a global variable are not updated if only a function-local static variable is found
+    pub(crate) no_local_symbols: bool,
+    // the fractional digits of the FORMAT are removed if the variable has an integer type
+    pub(crate) fix_integer_formats: bool,
+    // the number of candidates that are listed when a symbol name is ambiguous
+    pub(crate) max_candidates: usize,
 }
 
 // This struct contains the data that is modified / updated during the a2l update process.
@@ -107,32 +209,116 @@ pub(crate) struct A2lUpdater<'a2l> {
 
 type TypedefsRefInfo<'a> = HashMap<String, Vec<(Option<&'a TypeInfo>, TypedefReferrer)>>;
 
+// options that control the update of the a2l file.
+// The three basic settings are required by new(), all other options can be set as needed.
+#[derive(Debug, Clone)]
+pub(crate) struct UpdateOptions {
+    pub(crate) update_type: UpdateType,
+    pub(crate) update_mode: UpdateMode,
+    pub(crate) enable_structures: bool,
+    // the number of fractional digits of the limits is limited to this value
+    pub(crate) limit_precision: Option<u8>,
+    // the update stops once this many objects could not be updated; all remaining objects are kept unchanged
+    pub(crate) max_errors: Option<u32>,
+    // missing RECORD_LAYOUTs are replaced by default RECORD_LAYOUTs
+    pub(crate) fix_layouts: bool,
+    // the CANAPE_EXT LINK_MAP takes precedence over the SYMBOL_LINK
+    pub(crate) prefer_ifdata_symbol: bool,
+    // VAL_BLKs in files with version 1.6.1+ use MATRIX_DIM instead of NUMBER
+    pub(crate) number_to_matrix_dim: bool,
+    // BIT_MASKs that do not fit into the data type of a non-bitfield variable are removed
+    pub(crate) fix_bitmasks: bool,
+    // if present, only the objects named in it are updated; all other objects are kept unchanged
+    pub(crate) update_scope: Option<HashSet<String>>,
+    // the update stops once this point in time is reached; all remaining objects are kept unchanged
+    pub(crate) deadline: Option<Instant>,
+    // all objects whose address changed by more than this many bytes are listed in the summary
+    pub(crate) report_moved: Option<u64>,
+    // objects that were linked to a global variable are not matched to a function-local static variable
+    pub(crate) no_local_symbols: bool,
+    // the FORMAT of objects with an integer type and no conversion gets 0 fractional digits
+    pub(crate) fix_integer_formats: bool,
+    // if a symbol name is ambiguous, at most this many of the matching variables are listed in the warning
+    pub(crate) max_candidates: usize,
+}
+
+impl UpdateOptions {
+    pub(crate) fn new(
+        update_type: UpdateType,
+        update_mode: UpdateMode,
+        enable_structures: bool,
+    ) -> Self {
+        Self {
+            update_type,
+            update_mode,
+            enable_structures,
+            limit_precision: None,
+            max_errors: None,
+            fix_layouts: false,
+            prefer_ifdata_symbol: false,
+            number_to_matrix_dim: false,
+            fix_bitmasks: false,
+            update_scope: None,
+            deadline: None,
+            report_moved: None,
+            no_local_symbols: false,
+            fix_integer_formats: false,
+            max_candidates: MAX_LISTED_CANDIDATES,
+        }
+    }
+}
+
 // perform an address update.
 // This update can be destructive (any object that cannot be updated will be discarded)
 // or non-destructive (addresses of invalid objects will be set to zero).
+// The details of the update are controlled by the UpdateOptions.
 pub(crate) fn update_a2l(
     a2l_file: &mut A2lFile,
     debug_data: &DebugData,
     log_msgs: &mut Vec<String>,
-    update_type: UpdateType,
-    update_mode: UpdateMode,
-    enable_structures: bool,
+    options: &UpdateOptions,
 ) -> (UpdateSumary, bool) {
     let version = A2lVersion::from(&*a2l_file);
     let mut summary = UpdateSumary::new();
     let mut strict_error = false;
+    let mut error_count = 0;
     for module in &mut a2l_file.project.module {
-        let (mut data, update_info) = init_update(
+        let (mut data, mut update_info) = init_update(
             debug_data,
             module,
             version,
-            update_type,
-            update_mode,
-            enable_structures,
+            options.update_type,
+            options.update_mode,
+            options.enable_structures,
         );
+        update_info.limit_precision = options.limit_precision;
+        update_info.max_errors = options.max_errors;
+        update_info.fix_layouts = options.fix_layouts;
+        update_info.prefer_ifdata_symbol = options.prefer_ifdata_symbol;
+        update_info.deadline = options.deadline;
+        update_info.number_to_matrix_dim = options.number_to_matrix_dim;
+        update_info.fix_bitmasks = options.fix_bitmasks;
+        update_info.update_scope = options.update_scope.clone();
+        update_info.report_moved = options.report_moved;
+        update_info.no_local_symbols = options.no_local_symbols;
+        update_info.fix_integer_formats = options.fix_integer_formats;
+        update_info.max_candidates = options.max_candidates;
+        update_info.error_count.set(error_count);
         let (module_summary, module_strict_error) = run_update(&mut data, &update_info, log_msgs);
         summary += module_summary;
         strict_error |= module_strict_error;
+        error_count = update_info.error_count.get();
+    }
+    if let Some(max_errors) = options.max_errors {
+        if error_count >= max_errors {
+            log_msgs.push(format!("Update stopped after {max_errors} errors"));
+        }
+    }
+    if options
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        log_msgs.push("Update stopped because the time limit was reached".to_string());
     }
     (summary, strict_error)
 }
@@ -147,7 +333,10 @@ pub fn init_update<'a2l, 'dbg>(
 ) -> (A2lUpdater<'a2l>, A2lUpdateInfo<'dbg>) {
     let preserve_unknown = update_mode == UpdateMode::Preserve;
     let strict_update = update_mode == UpdateMode::Strict;
-    let full_update = update_type == UpdateType::Full;
+    // updating only the typedefs requires a full update of the INSTANCEs and TYPEDEFs
+    let typedefs_only = update_type == UpdateType::Typedefs;
+    let full_update = update_type == UpdateType::Full || typedefs_only;
+    let enable_structures = enable_structures || typedefs_only;
     let reclayout_info = RecordLayoutInfo::build(module);
 
     let compu_method_index = module
@@ -169,10 +358,176 @@ pub fn init_update<'a2l, 'dbg>(
             version,
             enable_structures,
             compu_method_index,
+            limit_precision: None,
+            max_errors: None,
+            error_count: Cell::new(0),
+            fix_layouts: false,
+            typedefs_only,
+            prefer_ifdata_symbol: false,
+            symbol_warnings: RefCell::new(Vec::new()),
+            deadline: None,
+            number_to_matrix_dim: false,
+            fix_bitmasks: false,
+            change_msgs: RefCell::new(Vec::new()),
+            update_scope: None,
+            removed_names: RefCell::new(HashSet::new()),
+            report_moved: None,
+            moved_items: RefCell::new(Vec::new()),
+            no_local_symbols: false,
+            fix_integer_formats: false,
+            max_candidates: MAX_LISTED_CANDIDATES,
         },
     )
 }
 
+impl<'dbg> A2lUpdateInfo<'dbg> {
+    // count the result of updating one object. Every result other than Updated is an error.
+    fn count_result(&self, result: &UpdateResult) {
+        if result.is_error() {
+            self.error_count.set(self.error_count.get() + 1);
+        }
+    }
+
+    // find the symbol of an object, and record a warning if its SYMBOL_LINK and LINK_MAP disagree
+    // or if an object that was linked to a global variable is now linked to a function-local static variable
+    fn get_symbol_info(
+        &self,
+        name: &str,
+        opt_symbol_link: &Option<SymbolLink>,
+        ifdata_vec: &[IfData],
+    ) -> Result<SymbolInfo<'dbg>, Vec<String>> {
+        let (mut sym_info, warning) = get_symbol_info_with_precedence(
+            name,
+            opt_symbol_link,
+            ifdata_vec,
+            self.debug_data,
+            self.prefer_ifdata_symbol,
+        )?;
+        if let Some(warning) = warning {
+            self.symbol_warnings.borrow_mut().push(warning);
+        }
+
+        // a symbol link without a {Function:...} discriminator refers to a global variable
+        let old_link = opt_symbol_link
+            .as_ref()
+            .map_or(name, |symbol_link| symbol_link.symbol_name.as_str());
+        if let Some(note) = get_resolution_note(old_link, self.debug_data, self.max_candidates) {
+            self.symbol_warnings
+                .borrow_mut()
+                .push(format!("{name}: {note}"));
+        }
+        if sym_info.is_function_local() && !old_link.contains("{Function:") {
+            let function_name = sym_info.function_name.as_deref().unwrap_or_default();
+            if self.no_local_symbols {
+                return Err(vec![format!(
+                    "Symbol {} is only found as a static variable in the function {function_name}, which is not allowed by --no-local-symbols",
+                    sym_info.name
+                )]);
+            }
+            self.symbol_warnings.borrow_mut().push(format!(
+                "Warning: {name} was linked to a global variable, but {} is now a static variable in the function {function_name}",
+                sym_info.name
+            ));
+            // the new SYMBOL_LINK gets the discriminators of the local variable, e.g. {Function:...}
+            sym_info.is_unique = false;
+        }
+        Ok(sym_info)
+    }
+
+    // true once the configured maximum number of errors has been reached
+    // All objects that have not been processed yet are then left unchanged.
+    fn error_limit_reached(&self) -> bool {
+        self.max_errors
+            .is_some_and(|max_errors| self.error_count.get() >= max_errors)
+    }
+
+    // true if the object is excluded from the update, because it is not part of the update_scope
+    fn out_of_scope(&self, name: &str) -> bool {
+        self.update_scope
+            .as_ref()
+            .is_some_and(|scope| !scope.contains(name))
+    }
+
+    // remember the object if its symbol was found at an address that differs from the old address by more than
+    // the report_moved threshold. Objects without an old address (i.e. address 0) have not moved.
+    fn check_moved(
+        &self,
+        blocktype: &'static str,
+        name: &str,
+        line: u32,
+        old_address: u64,
+        new_address: u64,
+    ) {
+        if let Some(threshold) = self.report_moved {
+            if old_address != 0 && old_address.abs_diff(new_address) > threshold {
+                self.moved_items.borrow_mut().push(MovedItem {
+                    blocktype,
+                    name: name.to_string(),
+                    line,
+                    old_address,
+                    new_address,
+                });
+            }
+        }
+    }
+
+    // set the fractional digits of the FORMAT to 0 if the variable has an integer type, and fix_integer_formats is set.
+    // Only values that are displayed without conversion are affected, since a COMPU_METHOD can produce fractional values.
+    fn fix_integer_format(
+        &self,
+        blocktype: &str,
+        name: &str,
+        line: u32,
+        opt_format: &mut Option<Format>,
+        typeinfo: &TypeInfo,
+        opt_compu_method: Option<&CompuMethod>,
+    ) {
+        if !self.fix_integer_formats
+            || opt_compu_method.is_some_and(|compu_method| {
+                compu_method.conversion_type != ConversionType::Identical
+            })
+        {
+            return;
+        }
+        let is_integer = matches!(
+            typeinfo.datatype,
+            DbgDataType::Uint8
+                | DbgDataType::Uint16
+                | DbgDataType::Uint32
+                | DbgDataType::Uint64
+                | DbgDataType::Sint8
+                | DbgDataType::Sint16
+                | DbgDataType::Sint32
+                | DbgDataType::Sint64
+                | DbgDataType::Bitfield { .. }
+        );
+        let Some(format) = opt_format else {
+            return;
+        };
+        if let Some(spec) = FormatSpec::parse(&format.format_string) {
+            if is_integer && spec.layout.is_some_and(|layout| layout > 0) {
+                let new_spec = FormatSpec {
+                    layout: Some(0),
+                    ..spec
+                };
+                self.change_msgs.borrow_mut().push(format!(
+                    "Warning: {blocktype} {name} on line {line} has an integer data type, its FORMAT \"{}\" was changed to \"{new_spec}\"",
+                    format.format_string
+                ));
+                format.format_string = new_spec.to_string();
+            }
+        }
+    }
+
+    // true if the update should not process any more objects, because the error limit or the time limit was reached
+    fn should_stop(&self) -> bool {
+        self.error_limit_reached()
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
 fn run_update(
     data: &mut A2lUpdater,
     info: &A2lUpdateInfo,
@@ -181,44 +536,71 @@ fn run_update(
     let mut summary = UpdateSumary::new();
     let mut strict_error = false;
 
-    // update all AXIS_PTS
-    let result = update_all_module_axis_pts(data, info);
-    strict_error |= result.iter().any(|r| r != &UpdateResult::Updated);
-    let (updated, not_updated) = log_update_results(log_msgs, &result);
-    summary.axis_pts_updated += updated;
-    summary.axis_pts_not_updated += not_updated;
-
-    // update all MEASUREMENTs
-    let results = update_all_module_measurements(data, info);
-    strict_error |= results.iter().any(|r| r != &UpdateResult::Updated);
-    let (updated, not_updated) = log_update_results(log_msgs, &results);
-    summary.measurement_updated += updated;
-    summary.measurement_not_updated += not_updated;
-
-    // update all CHARACTERISTICs
-    let results = update_all_module_characteristics(data, info);
-    strict_error |= results.iter().any(|r| r != &UpdateResult::Updated);
-    let (updated, not_updated) = log_update_results(log_msgs, &results);
-    summary.characteristic_updated += updated;
-    summary.characteristic_not_updated += not_updated;
-
-    // update all BLOBs
-    let results = update_all_module_blobs(data, info);
-    strict_error |= results.iter().any(|r| r != &UpdateResult::Updated);
-    let (updated, not_updated) = log_update_results(log_msgs, &results);
-    summary.blob_updated += updated;
-    summary.blob_not_updated += not_updated;
+    if !info.typedefs_only {
+        // verify that all referenced RECORD_LAYOUTs exist, and create default RECORD_LAYOUTs if requested
+        check_record_layouts(data, info, log_msgs);
+
+        // update all AXIS_PTS
+        let result = update_all_module_axis_pts(data, info);
+        strict_error |= result.iter().any(UpdateResult::is_error);
+        let (updated, type_changed, not_updated) = log_update_results(log_msgs, &result);
+        summary.axis_pts_updated += updated;
+        summary.axis_pts_type_changed += type_changed;
+        summary.axis_pts_not_updated += not_updated;
+        collect_not_found(&mut summary.not_found, &result);
+
+        // update all MEASUREMENTs
+        let results = update_all_module_measurements(data, info);
+        strict_error |= results.iter().any(UpdateResult::is_error);
+        let (updated, type_changed, not_updated) = log_update_results(log_msgs, &results);
+        summary.measurement_updated += updated;
+        summary.measurement_type_changed += type_changed;
+        summary.measurement_not_updated += not_updated;
+        collect_not_found(&mut summary.not_found, &results);
+
+        // update all CHARACTERISTICs
+        let results = update_all_module_characteristics(data, info);
+        strict_error |= results.iter().any(UpdateResult::is_error);
+        let (updated, type_changed, not_updated) = log_update_results(log_msgs, &results);
+        summary.characteristic_updated += updated;
+        summary.characteristic_type_changed += type_changed;
+        summary.characteristic_not_updated += not_updated;
+        collect_not_found(&mut summary.not_found, &results);
+
+        // update all BLOBs
+        let results = update_all_module_blobs(data, info);
+        strict_error |= results.iter().any(UpdateResult::is_error);
+        let (updated, type_changed, not_updated) = log_update_results(log_msgs, &results);
+        summary.blob_updated += updated;
+        summary.blob_type_changed += type_changed;
+        summary.blob_not_updated += not_updated;
+        collect_not_found(&mut summary.not_found, &results);
+    }
 
     let typedef_names = TypedefNames::new(data.module);
 
     // update all INSTANCEs
     let (update_result, typedef_ref_info) = update_all_module_instances(data, info, &typedef_names);
-    strict_error |= results.iter().any(|r| r != &UpdateResult::Updated);
-    let (updated, not_updated) = log_update_results(log_msgs, &update_result);
+    strict_error |= update_result.iter().any(UpdateResult::is_error);
+    let (updated, type_changed, not_updated) = log_update_results(log_msgs, &update_result);
     summary.instance_updated += updated;
+    summary.instance_type_changed += type_changed;
     summary.instance_not_updated += not_updated;
+    collect_not_found(&mut summary.not_found, &update_result);
+
+    log_msgs.extend(info.symbol_warnings.take());
+    log_msgs.extend(info.change_msgs.take());
+    summary.moved.extend(info.moved_items.take());
+
+    // AXIS_PTS, AXIS_DESCRs and CHARACTERISTICs may reference MEASUREMENTs that were removed
+    strict_error |= cleanup_removed_quantity_refs(
+        data.module,
+        &info.removed_names.borrow(),
+        info.strict_update,
+        log_msgs,
+    );
 
-    if info.full_update && info.enable_structures {
+    if info.full_update && info.enable_structures && !info.should_stop() {
         update_module_typedefs(
             info,
             data.module,
@@ -227,30 +609,52 @@ fn run_update(
             typedef_names,
             &mut data.reclayout_info,
         );
+    } else if !info.full_update && !info.should_stop() {
+        // an address-only update doesn't rebuild the TYPEDEFs, but the offsets of moved struct members must be updated
+        update_typedef_structure_offsets(data.module, info.debug_data, &typedef_ref_info, log_msgs);
     }
 
+    // the update may have created or renamed conversions; warn about any broken references
+    log_msgs.extend(check_conversion_refs(data.module));
+
     (summary, strict_error)
 }
 
 // try to get the symbol name used in the elf file, and find its address and type
-fn get_symbol_info<'a>(
+pub(crate) fn get_symbol_info<'a>(
     name: &str,
     opt_symbol_link: &Option<SymbolLink>,
     ifdata_vec: &[IfData],
     debug_data: &'a DebugData,
 ) -> Result<SymbolInfo<'a>, Vec<String>> {
+    get_symbol_info_with_precedence(name, opt_symbol_link, ifdata_vec, debug_data, false)
+        .map(|(sym_info, _)| sym_info)
+}
+
+// find the symbol of an object using its SYMBOL_LINK, the CANAPE_EXT LINK_MAP in its IF_DATA or its name.
+// By default the SYMBOL_LINK takes precedence over the LINK_MAP; prefer_ifdata reverses this.
+// If both resolve to different symbols or addresses, a warning naming both candidates is returned.
+// The caller rewrites the losing source, since the SYMBOL_LINK and LINK_MAP are both set from the result.
+fn get_symbol_info_with_precedence<'a>(
+    name: &str,
+    opt_symbol_link: &Option<SymbolLink>,
+    ifdata_vec: &[IfData],
+    debug_data: &'a DebugData,
+    prefer_ifdata: bool,
+) -> Result<(SymbolInfo<'a>, Option<String>), Vec<String>> {
     let mut symbol_link_errmsg = None;
     let mut ifdata_errmsg = None;
     let mut object_name_errmsg = None;
-    // preferred: get symbol information from a SYMBOL_LINK attribute
+    // get symbol information from a SYMBOL_LINK attribute
+    let mut symbol_link_info = None;
     if let Some(symbol_link) = opt_symbol_link {
         match find_symbol(&symbol_link.symbol_name, debug_data) {
             Ok(sym_info) => {
                 if symbol_link.offset == 0 {
-                    return Ok(sym_info);
+                    symbol_link_info = Some(sym_info);
                 } else {
                     match find_symbol_by_offset(&sym_info, symbol_link.offset, debug_data) {
-                        Ok(sym_info) => return Ok(sym_info),
+                        Ok(sym_info) => symbol_link_info = Some(sym_info),
                         Err(errmsg) => return Err(vec![errmsg]),
                     }
                 }
@@ -259,20 +663,50 @@ fn get_symbol_info<'a>(
         };
     }
 
-    // second option: get symbol information from a CANAPE_EXT block inside of IF_DATA.
+    // get symbol information from a CANAPE_EXT block inside of IF_DATA.
     // The content of IF_DATA can be different for each tool vendor, but the blocks used
     // by the Vector tools are understood by some other software.
+    let mut ifdata_info = None;
     if let Some(ifdata_symbol_name) = get_symbol_name_from_ifdata(ifdata_vec) {
         match find_symbol(&ifdata_symbol_name, debug_data) {
-            Ok(sym_info) => return Ok(sym_info),
+            Ok(sym_info) => ifdata_info = Some(sym_info),
             Err(errmsg) => ifdata_errmsg = Some(errmsg),
         };
     }
 
+    match (symbol_link_info, ifdata_info) {
+        (Some(symbol_link_info), Some(ifdata_info)) => {
+            let disagree = symbol_link_info.name != ifdata_info.name
+                || symbol_link_info.address != ifdata_info.address;
+            let used_source = if prefer_ifdata {
+                "LINK_MAP"
+            } else {
+                "SYMBOL_LINK"
+            };
+            let warning = disagree.then(|| {
+                format!(
+                    "Warning: the SYMBOL_LINK of {name} refers to {} at 0x{:X}, but the CANAPE_EXT LINK_MAP refers to {} at 0x{:X}. The symbol from the {used_source} is used.",
+                    symbol_link_info.name,
+                    symbol_link_info.address,
+                    ifdata_info.name,
+                    ifdata_info.address
+                )
+            });
+            let used = if prefer_ifdata {
+                ifdata_info
+            } else {
+                symbol_link_info
+            };
+            return Ok((used, warning));
+        }
+        (Some(sym_info), None) | (None, Some(sym_info)) => return Ok((sym_info, None)),
+        (None, None) => {}
+    }
+
     // If there is no SYMBOL_LINK and no (usable) IF_DATA, then maybe the object name is also the symbol name
     if opt_symbol_link.is_none() {
         match find_symbol(name, debug_data) {
-            Ok(sym_info) => return Ok(sym_info),
+            Ok(sym_info) => return Ok((sym_info, None)),
             Err(errmsg) => object_name_errmsg = Some(errmsg),
         };
     }
@@ -304,8 +738,11 @@ fn log_update_errors(errorlog: &mut Vec<String>, errmsgs: Vec<String>, blockname
     }
 }
 
-fn log_update_results(errorlog: &mut Vec<String>, results: &[UpdateResult]) -> (u32, u32) {
+// log the errors and warnings of an update, and count the results
+// returns the number of updated objects, the number of objects whose data type has changed, and the number of objects that were not found
+fn log_update_results(errorlog: &mut Vec<String>, results: &[UpdateResult]) -> (u32, u32, u32) {
     let mut updated = 0;
+    let mut type_changed = 0;
     let mut not_updated = 0;
     for result in results {
         match result {
@@ -332,12 +769,42 @@ fn log_update_results(errorlog: &mut Vec<String>, results: &[UpdateResult]) -> (
                 errorlog.push(format!(
                     "Error updating {blocktype} {name} on line {line}: data type has changed",
                 ));
+                type_changed += 1;
+            }
+            UpdateResult::AddressOnly {
+                blocktype,
+                name,
+                line,
+            } => {
+                errorlog.push(format!(
+                    "Warning: only the address of {blocktype} {name} on line {line} was updated, because its symbol is not described by the debug info and its data type is unknown",
+                ));
                 updated += 1;
             }
         }
     }
 
-    (updated, not_updated)
+    (updated, type_changed, not_updated)
+}
+
+// collect the objects whose symbols were not found, e.g. to write a report about preserved objects
+fn collect_not_found(not_found: &mut Vec<NotFoundItem>, results: &[UpdateResult]) {
+    for result in results {
+        if let UpdateResult::SymbolNotFound {
+            blocktype,
+            name,
+            line,
+            errors,
+        } = result
+        {
+            not_found.push(NotFoundItem {
+                blocktype,
+                name: name.clone(),
+                line: *line,
+                errors: errors.clone(),
+            });
+        }
+    }
 }
 
 pub(crate) fn make_symbol_link_string(sym_info: &SymbolInfo, debug_data: &DebugData) -> String {
@@ -489,11 +956,13 @@ fn get_symbol_name_from_ifdata(ifdata_vec: &[IfData]) -> Option<String> {
 // generate adjusted min and max limits based on the datatype.
 // since the updater code has no knowledge how the data is handled in the application it
 // is only possible to shrink existing limits, but not expand them
-fn adjust_limits(
+// If limit_precision is set, the calculated limits are rounded to that many decimal places.
+pub(crate) fn adjust_limits(
     typeinfo: &TypeInfo,
     old_lower_limit: f64,
     old_upper_limit: f64,
     opt_compu_method: Option<&CompuMethod>,
+    limit_precision: Option<u8>,
 ) -> (f64, f64) {
     let (mut new_lower_limit, mut new_upper_limit) =
         get_type_limits(typeinfo, old_lower_limit, old_upper_limit);
@@ -553,6 +1022,9 @@ fn adjust_limits(
         }
     }
 
+    // the physical value range of the datatype, which the rounded limits may not exceed
+    let type_range = (new_lower_limit, new_upper_limit);
+
     // if non-zero limits exist, then the limits can only shrink, but not grow
     // if the limits are both zero, then the maximum range allowed by the datatype is used
     if old_lower_limit != 0f64 || old_upper_limit != 0f64 {
@@ -577,7 +1049,78 @@ fn adjust_limits(
         new_upper_limit = f64::MAX;
     }
 
-    (new_lower_limit, new_upper_limit)
+    if let Some(decimals) = limit_precision {
+        round_limits(
+            (new_lower_limit, new_upper_limit),
+            (old_lower_limit, old_upper_limit),
+            type_range,
+            decimals,
+        )
+    } else {
+        (new_lower_limit, new_upper_limit)
+    }
+}
+
+// round calculated limits to the given number of decimal places, e.g. 35.50000000000001 -> 35.5
+// Lower limits are rounded down and upper limits are rounded up, so that no valid values are excluded.
+// A limit that would be pushed outside the value range of the datatype by this is rounded towards
+// the inside of the range instead.
+// Limits that are identical to the old limits were not changed by the update and are not modified.
+fn round_limits(
+    (lower_limit, upper_limit): (f64, f64),
+    (old_lower_limit, old_upper_limit): (f64, f64),
+    (range_lower, range_upper): (f64, f64),
+    decimals: u8,
+) -> (f64, f64) {
+    let mut new_lower_limit = lower_limit;
+    if lower_limit != old_lower_limit {
+        new_lower_limit = round_limit(lower_limit, decimals, false);
+        if new_lower_limit < range_lower {
+            new_lower_limit = round_limit(lower_limit, decimals, true);
+        }
+        if new_lower_limit < range_lower {
+            new_lower_limit = lower_limit;
+        }
+    }
+
+    let mut new_upper_limit = upper_limit;
+    if upper_limit != old_upper_limit {
+        new_upper_limit = round_limit(upper_limit, decimals, true);
+        if new_upper_limit > range_upper {
+            new_upper_limit = round_limit(upper_limit, decimals, false);
+        }
+        if new_upper_limit > range_upper {
+            new_upper_limit = upper_limit;
+        }
+    }
+
+    // rounding towards the inside of a very small range could invert the limits
+    if new_lower_limit > new_upper_limit {
+        (lower_limit, upper_limit)
+    } else {
+        (new_lower_limit, new_upper_limit)
+    }
+}
+
+// round a single value to the given number of decimal places.
+// Differences that are only caused by floating point imprecision are always rounded away,
+// otherwise the value is rounded in the requested direction.
+fn round_limit(value: f64, decimals: u8, round_up: bool) -> f64 {
+    let scale = 10f64.powi(i32::from(decimals));
+    let scaled = value * scale;
+    // beyond 2^52 an f64 cannot represent any fractional digits, so there is nothing to round
+    if !scaled.is_finite() || scaled.abs() >= 4_503_599_627_370_496.0 {
+        return value;
+    }
+
+    let nearest = scaled.round();
+    if (scaled - nearest).abs() <= scaled.abs().max(1.0) * 64.0 * f64::EPSILON {
+        nearest / scale
+    } else if round_up {
+        scaled.ceil() / scale
+    } else {
+        scaled.floor() / scale
+    }
 }
 
 // remove the identifiers in removed_items from the item_list
@@ -596,15 +1139,22 @@ impl UpdateSumary {
     fn new() -> Self {
         Self {
             axis_pts_not_updated: 0,
+            axis_pts_type_changed: 0,
             axis_pts_updated: 0,
             blob_not_updated: 0,
+            blob_type_changed: 0,
             blob_updated: 0,
             characteristic_not_updated: 0,
+            characteristic_type_changed: 0,
             characteristic_updated: 0,
             measurement_not_updated: 0,
+            measurement_type_changed: 0,
             measurement_updated: 0,
             instance_not_updated: 0,
+            instance_type_changed: 0,
             instance_updated: 0,
+            not_found: Vec::new(),
+            moved: Vec::new(),
         }
     }
 }
@@ -652,15 +1202,22 @@ impl TypedefNames {
 impl AddAssign for UpdateSumary {
     fn add_assign(&mut self, other: Self) {
         self.axis_pts_not_updated += other.axis_pts_not_updated;
+        self.axis_pts_type_changed += other.axis_pts_type_changed;
         self.axis_pts_updated += other.axis_pts_updated;
         self.blob_not_updated += other.blob_not_updated;
+        self.blob_type_changed += other.blob_type_changed;
         self.blob_updated += other.blob_updated;
         self.characteristic_not_updated += other.characteristic_not_updated;
+        self.characteristic_type_changed += other.characteristic_type_changed;
         self.characteristic_updated += other.characteristic_updated;
         self.measurement_not_updated += other.measurement_not_updated;
+        self.measurement_type_changed += other.measurement_type_changed;
         self.measurement_updated += other.measurement_updated;
         self.instance_not_updated += other.instance_not_updated;
+        self.instance_type_changed += other.instance_type_changed;
         self.instance_updated += other.instance_updated;
+        self.not_found.extend(other.not_found);
+        self.moved.extend(other.moved);
     }
 }
 
@@ -671,7 +1228,7 @@ mod test {
         debuginfo::{DbgDataType, TypeInfo},
         A2lVersion,
     };
-    use a2lfile::{Coeffs, CoeffsLinear, CompuMethod, ConversionType};
+    use a2lfile::{CharacteristicType, Coeffs, CoeffsLinear, CompuMethod, ConversionType};
     use std::ffi::OsString;
 
     #[test]
@@ -681,6 +1238,7 @@ mod test {
             unit_idx: 0,
             datatype: DbgDataType::Uint8,
             dbginfo_offset: 0,
+            big_endian: None,
         };
         let mut compu_method = CompuMethod::new(
             "name".to_string(),
@@ -691,7 +1249,7 @@ mod test {
         );
         compu_method.coeffs_linear = Some(CoeffsLinear::new(0.1, 10.0));
 
-        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 100.0, Some(&compu_method));
+        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 100.0, Some(&compu_method), None);
         assert_eq!(lower, 10.0);
         assert_eq!(upper, 35.5);
 
@@ -701,6 +1259,7 @@ mod test {
             unit_idx: 0,
             datatype: DbgDataType::Uint8,
             dbginfo_offset: 0,
+            big_endian: None,
         };
         let mut compu_method = CompuMethod::new(
             "name".to_string(),
@@ -711,7 +1270,7 @@ mod test {
         );
         compu_method.coeffs = Some(Coeffs::new(0., 0.025, 0., 0., 0., 1.0));
 
-        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 0.0, Some(&compu_method));
+        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 0.0, Some(&compu_method), None);
         assert_eq!(lower, 0.0);
         assert_eq!(upper, 10200.0);
 
@@ -723,6 +1282,7 @@ mod test {
             unit_idx: 0,
             datatype: DbgDataType::Double,
             dbginfo_offset: 0,
+            big_endian: None,
         };
         let mut compu_method = CompuMethod::new(
             "name".to_string(),
@@ -733,11 +1293,113 @@ mod test {
         );
         compu_method.coeffs = Some(Coeffs::new(0., 4.0, 0., 0., 0., 2.0));
 
-        let (lower, upper) = adjust_limits(&typeinfo, f64::MIN, f64::MAX, Some(&compu_method));
+        let (lower, upper) =
+            adjust_limits(&typeinfo, f64::MIN, f64::MAX, Some(&compu_method), None);
         assert_ne!(lower, f64::MIN);
         assert_ne!(upper, f64::MAX);
     }
 
+    #[test]
+    fn test_adjust_limits_precision() {
+        let typeinfo = TypeInfo {
+            name: None,
+            unit_idx: 0,
+            datatype: DbgDataType::Uint8,
+            dbginfo_offset: 0,
+            big_endian: None,
+        };
+        let mut compu_method = CompuMethod::new(
+            "name".to_string(),
+            "".to_string(),
+            ConversionType::Linear,
+            "".to_string(),
+            "".to_string(),
+        );
+        compu_method.coeffs_linear = Some(CoeffsLinear::new(0.01, 0.0));
+
+        // without rounding, the limit is affected by floating point imprecision: 255 * 0.01 = 2.5500000000000003
+        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 0.0, Some(&compu_method), None);
+        assert_eq!(lower, 0.0);
+        assert_ne!(upper, 2.55);
+        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 0.0, Some(&compu_method), Some(2));
+        assert_eq!(lower, 0.0);
+        assert_eq!(upper, 2.55);
+
+        // rounding up would exceed the range of the datatype, so the upper limit is rounded down instead
+        let (lower, upper) = adjust_limits(&typeinfo, 0.0, 0.0, Some(&compu_method), Some(1));
+        assert_eq!(lower, 0.0);
+        assert_eq!(upper, 2.5);
+
+        // limits that are not changed by the update are not rounded
+        let (lower, upper) =
+            adjust_limits(&typeinfo, 1.23456, 2.34567, Some(&compu_method), Some(2));
+        assert_eq!(lower, 1.23456);
+        assert_eq!(upper, 2.34567);
+    }
+
+    #[test]
+    fn test_round_limits() {
+        let full_range = (f64::MIN, f64::MAX);
+
+        // values that only differ from the rounded value by floating point noise are rounded to the nearest value
+        let (lower, upper) = round_limits(
+            (0.30000000000000004, 2.9999999999999996),
+            (0.0, 0.0),
+            full_range,
+            3,
+        );
+        assert_eq!(lower, 0.3);
+        assert_eq!(upper, 3.0);
+
+        // lower limits are rounded down and upper limits are rounded up
+        let (lower, upper) = round_limits((1.234, 5.678), (0.0, 0.0), full_range, 2);
+        assert_eq!(lower, 1.23);
+        assert_eq!(upper, 5.68);
+        let (lower, upper) = round_limits((-1.234, -0.001), (0.0, 0.0), full_range, 2);
+        assert_eq!(lower, -1.24);
+        assert_eq!(upper, 0.0);
+
+        // values near integer boundaries
+        let (lower, upper) = round_limits((-0.5, 0.5), (0.0, 0.0), full_range, 0);
+        assert_eq!(lower, -1.0);
+        assert_eq!(upper, 1.0);
+        let (lower, upper) = round_limits(
+            (0.9999999999999999, 254.99999999999997),
+            (0.0, 0.0),
+            full_range,
+            0,
+        );
+        assert_eq!(lower, 1.0);
+        assert_eq!(upper, 255.0);
+        let (lower, upper) = round_limits((-0.0001, 254.0001), (0.0, 0.0), full_range, 0);
+        assert_eq!(lower, -1.0);
+        assert_eq!(upper, 255.0);
+
+        // rounding may not leave the value range of the datatype
+        let (lower, upper) = round_limits((-12.85, 12.85), (0.0, 0.0), (-12.85, 12.85), 1);
+        assert_eq!(lower, -12.8);
+        assert_eq!(upper, 12.8);
+
+        // rounding towards the inside of a tiny range would invert the limits
+        let (lower, upper) = round_limits((0.0011, 0.0012), (0.0, 0.0), (0.0011, 0.0012), 2);
+        assert_eq!(lower, 0.0011);
+        assert_eq!(upper, 0.0012);
+
+        // huge values have no fractional digits and are left unchanged
+        let (lower, upper) = round_limits((f64::MIN, f64::MAX), (0.0, 0.0), full_range, 5);
+        assert_eq!(lower, f64::MIN);
+        assert_eq!(upper, f64::MAX);
+        let (lower, upper) =
+            round_limits((-1.5e17, 3.4028234663852886e38), (0.0, 0.0), full_range, 2);
+        assert_eq!(lower, -1.5e17);
+        assert_eq!(upper, 3.4028234663852886e38);
+
+        // limits which are unchanged compared to the old limits are not modified
+        let (lower, upper) = round_limits((1.234, 5.678), (1.234, 0.0), full_range, 2);
+        assert_eq!(lower, 1.234);
+        assert_eq!(upper, 5.68);
+    }
+
     fn test_setup(a2l_name: &str) -> (crate::debuginfo::DebugData, a2lfile::A2lFile) {
         let mut log_msgs = Vec::new();
         let a2l = a2lfile::load(
@@ -774,7 +1436,7 @@ mod test {
         let result = update_all_module_axis_pts(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 3);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 3);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -794,7 +1456,7 @@ mod test {
         let result = update_all_module_axis_pts(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 3);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 3);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -841,7 +1503,7 @@ mod test {
         let result = update_all_module_blobs(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 2);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 2);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -861,7 +1523,7 @@ mod test {
         let result = update_all_module_blobs(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 2);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 2);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -907,7 +1569,7 @@ mod test {
         let result = update_all_module_characteristics(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 6);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 6);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -927,7 +1589,7 @@ mod test {
         let result = update_all_module_characteristics(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 6);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 6);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -978,7 +1640,7 @@ mod test {
         let (result, _) = update_all_module_instances(&mut data, &info, &typedef_names);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 1);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 1);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -999,7 +1661,7 @@ mod test {
         let (result, _) = update_all_module_instances(&mut data, &info, &typedef_names);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 1);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 1);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -1046,7 +1708,7 @@ mod test {
         let result = update_all_module_measurements(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 6);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 6);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -1066,7 +1728,7 @@ mod test {
         let result = update_all_module_measurements(&mut data, &info);
         assert!(result.iter().all(|r| r == &UpdateResult::Updated));
         assert_eq!(result.len(), 6);
-        let (updated, not_updated) = log_update_results(&mut log_msgs, &result);
+        let (updated, _, not_updated) = log_update_results(&mut log_msgs, &result);
         assert_eq!(updated, 6);
         assert_eq!(not_updated, 0);
         assert!(log_msgs.is_empty());
@@ -1097,6 +1759,516 @@ mod test {
         assert!(matches!(result[6], UpdateResult::SymbolNotFound { .. }));
     }
 
+    #[test]
+    fn test_update_max_errors() {
+        let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test2.a2l");
+
+        // the update of the MEASUREMENTs stops after the first two errors
+        let version = A2lVersion::from(&a2l);
+        let (mut data, mut info) = init_update(
+            &debug_data,
+            &mut a2l.project.module[0],
+            version,
+            UpdateType::Addresses,
+            UpdateMode::Strict,
+            true,
+        );
+        info.max_errors = Some(2);
+        let result = update_all_module_measurements(&mut data, &info);
+        assert_eq!(result.len(), 2);
+        assert!(info.error_limit_reached());
+        // the MEASUREMENTs that were not processed are retained
+        assert_eq!(data.module.measurement.len(), 7);
+
+        // the error limit applies to all object types together
+        let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test2.a2l");
+        let mut log_msgs = Vec::new();
+        let (summary, strict_error) = update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions {
+                max_errors: Some(3),
+                ..UpdateOptions::new(UpdateType::Addresses, UpdateMode::Strict, false)
+            },
+        );
+        assert!(strict_error);
+        // objects whose data type has changed are errors, too
+        let errors = summary.axis_pts_not_updated
+            + summary.axis_pts_type_changed
+            + summary.measurement_not_updated
+            + summary.measurement_type_changed
+            + summary.characteristic_not_updated
+            + summary.characteristic_type_changed
+            + summary.blob_not_updated
+            + summary.blob_type_changed
+            + summary.instance_not_updated
+            + summary.instance_type_changed;
+        assert_eq!(errors, 3);
+        assert_eq!(log_msgs.last().unwrap(), "Update stopped after 3 errors");
+    }
+
+    #[test]
+    fn test_update_missing_record_layout() {
+        let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test1.a2l");
+        let module = &mut a2l.project.module[0];
+        let value_idx = module
+            .characteristic
+            .iter()
+            .position(|c| c.name == "Characteristic_Value")
+            .unwrap();
+        module.characteristic[value_idx].deposit = "Missing_RecordLayout".to_string();
+        let axis_idx = module
+            .axis_pts
+            .iter()
+            .position(|a| a.name == "Axis_0")
+            .unwrap();
+        module.axis_pts[axis_idx].deposit_record = "Missing_AxisLayout".to_string();
+        let mut a2l_fixed = a2l.clone();
+
+        // without fix_layouts, a warning is generated for each dangling reference
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false),
+        );
+        assert!(log_msgs.iter().any(|msg| msg.contains(
+            "CHARACTERISTIC Characteristic_Value on line 87 references the RECORD_LAYOUT Missing_RecordLayout, which does not exist"
+        )));
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("AXIS_PTS Axis_0") && msg.contains("Missing_AxisLayout")));
+        assert_eq!(
+            a2l.project.module[0].characteristic[value_idx].deposit,
+            "Missing_RecordLayout"
+        );
+
+        // with fix_layouts, default RECORD_LAYOUTs are created
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l_fixed,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions {
+                fix_layouts: true,
+                ..UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false)
+            },
+        );
+        let module = &a2l_fixed.project.module[0];
+        let deposit = &module.characteristic[value_idx].deposit;
+        assert_ne!(deposit, "Missing_RecordLayout");
+        let reclayout = module
+            .record_layout
+            .iter()
+            .find(|rl| &rl.name == deposit)
+            .unwrap();
+        // uint32_t Characteristic_Value
+        assert_eq!(
+            reclayout.fnc_values.as_ref().unwrap().datatype,
+            a2lfile::DataType::Ulong
+        );
+        let deposit_record = &module.axis_pts[axis_idx].deposit_record;
+        assert_ne!(deposit_record, "Missing_AxisLayout");
+        assert!(module
+            .record_layout
+            .iter()
+            .any(|rl| &rl.name == deposit_record && rl.axis_pts_x.is_some()));
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg
+                .contains("the missing RECORD_LAYOUT Missing_RecordLayout was replaced by")));
+    }
+
+    #[test]
+    fn test_update_mapfile_symbol() {
+        let mut debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/mapfile_test.elf"), false).unwrap();
+        debug_data
+            .load_mapfile(&OsString::from("fixtures/bin/mapfile_test_armlink.map"))
+            .unwrap();
+        let mut a2l = a2lfile::new();
+        // lib_state has no debug info and is not in the symbol table; its data type in the a2l file can't be verified
+        let mut measurement = a2lfile::Measurement::new(
+            "lib_state".to_string(),
+            String::new(),
+            a2lfile::DataType::Slong,
+            "NO_COMPU_METHOD".to_string(),
+            0,
+            0.0,
+            -100.0,
+            100.0,
+        );
+        measurement.ecu_address = Some(a2lfile::EcuAddress::new(0));
+        a2l.project.module[0].measurement.push(measurement);
+
+        let mut log_msgs = Vec::new();
+        let (summary, strict_error) = update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Strict, false),
+        );
+        // only the address is updated, and a warning is generated instead of an error
+        assert!(!strict_error);
+        assert_eq!(summary.measurement_updated, 1);
+        let measurement = &a2l.project.module[0].measurement[0];
+        assert_eq!(
+            measurement.ecu_address.as_ref().unwrap().address,
+            0x2000_0018
+        );
+        assert_eq!(measurement.datatype, a2lfile::DataType::Slong);
+        assert_eq!(measurement.lower_limit, -100.0);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("only the address of MEASUREMENT lib_state")));
+    }
+
+    #[test]
+    fn test_update_untyped_symbol() {
+        let debug_data = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/untyped_symbol_test.elf"),
+            false,
+        )
+        .unwrap();
+        let mut a2l = a2lfile::new();
+        // Asm_Word is defined in assembly code, so its data type is only inferred from the symbol size
+        let mut measurement = a2lfile::Measurement::new(
+            "Asm_Word".to_string(),
+            String::new(),
+            a2lfile::DataType::Sword,
+            "NO_COMPU_METHOD".to_string(),
+            0,
+            0.0,
+            -100.0,
+            100.0,
+        );
+        measurement.ecu_address = Some(a2lfile::EcuAddress::new(0));
+        a2l.project.module[0].measurement.push(measurement);
+
+        let mut log_msgs = Vec::new();
+        let (summary, strict_error) = update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Strict, false),
+        );
+        // the inferred data type does not replace the data type in the a2l file
+        assert!(!strict_error);
+        assert_eq!(summary.measurement_updated, 1);
+        let measurement = &a2l.project.module[0].measurement[0];
+        assert_eq!(measurement.ecu_address.as_ref().unwrap().address, 0x4012);
+        assert_eq!(measurement.datatype, a2lfile::DataType::Sword);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("only the address of MEASUREMENT Asm_Word")));
+    }
+
+    #[test]
+    fn test_update_fix_integer_formats() {
+        let (debug_data, _) = test_setup("fixtures/a2l/update_test1.a2l");
+        for fix_integer_formats in [false, true] {
+            let mut a2l = a2lfile::new();
+            let module = &mut a2l.project.module[0];
+            // Measurement_Value is a uint16_t, but its FORMAT shows fractional digits
+            let mut measurement = a2lfile::Measurement::new(
+                "Measurement_Value".to_string(),
+                String::new(),
+                a2lfile::DataType::Float32Ieee,
+                "NO_COMPU_METHOD".to_string(),
+                0,
+                0.0,
+                0.0,
+                100.0,
+            );
+            measurement.format = Some(Format::new("%8.3".to_string()));
+            module.measurement.push(measurement);
+
+            let mut log_msgs = Vec::new();
+            update_a2l(
+                &mut a2l,
+                &debug_data,
+                &mut log_msgs,
+                &UpdateOptions {
+                    fix_integer_formats,
+                    ..UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false)
+                },
+            );
+            let measurement = &a2l.project.module[0].measurement[0];
+            assert_eq!(measurement.datatype, a2lfile::DataType::Uword);
+            let format_string = &measurement.format.as_ref().unwrap().format_string;
+            if fix_integer_formats {
+                assert_eq!(format_string, "%8.0");
+                assert!(log_msgs
+                    .iter()
+                    .any(|msg| msg.contains("MEASUREMENT Measurement_Value")
+                        && msg.contains("was changed to \"%8.0\"")));
+            } else {
+                assert_eq!(format_string, "%8.3");
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_report_moved() {
+        let (debug_data, _) = test_setup("fixtures/a2l/update_test1.a2l");
+        let mut a2l = a2lfile::new();
+        let module = &mut a2l.project.module[0];
+        // Measurement_Value used to be located in .data, but it was moved to .bss at 0x926C
+        let mut measurement = a2lfile::Measurement::new(
+            "Measurement_Value".to_string(),
+            String::new(),
+            a2lfile::DataType::Ulong,
+            "NO_COMPU_METHOD".to_string(),
+            0,
+            0.0,
+            0.0,
+            100.0,
+        );
+        measurement.ecu_address = Some(a2lfile::EcuAddress::new(0x9000));
+        module.measurement.push(measurement);
+        // Characteristic_Value only moved by a few bytes, and Enum_Value had no address before
+        for (name, address) in [("Characteristic_Value", 0x90C8), ("Enum_Value", 0)] {
+            module.characteristic.push(a2lfile::Characteristic::new(
+                name.to_string(),
+                String::new(),
+                CharacteristicType::Value,
+                address,
+                "RecordLayout".to_string(),
+                0.0,
+                "NO_COMPU_METHOD".to_string(),
+                0.0,
+                100.0,
+            ));
+        }
+
+        let mut log_msgs = Vec::new();
+        let (summary, _) = update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions {
+                report_moved: Some(0x100),
+                ..UpdateOptions::new(UpdateType::Addresses, UpdateMode::Default, false)
+            },
+        );
+        // the addresses of all objects are updated, but only the large change is reported
+        let module = &a2l.project.module[0];
+        assert_eq!(
+            module.measurement[0].ecu_address.as_ref().unwrap().address,
+            0x926C
+        );
+        assert_eq!(module.characteristic[0].address, 0x90D0);
+        assert_eq!(module.characteristic[1].address, 0x90D4);
+        assert_eq!(summary.moved.len(), 1);
+        assert_eq!(summary.moved[0].blocktype, "MEASUREMENT");
+        assert_eq!(summary.moved[0].name, "Measurement_Value");
+        assert_eq!(summary.moved[0].old_address, 0x9000);
+        assert_eq!(summary.moved[0].new_address, 0x926C);
+    }
+
+    #[test]
+    fn test_update_function_local_symbol() {
+        // Scope_Value is a global variable in v1, and a static variable in the function get_scope_value in v2
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Global_Value "" ULONG NO_COMPU_METHOD 0 0 0 4294967295
+      ECU_ADDRESS 0
+      SYMBOL_LINK "Global_Value" 0
+    /end MEASUREMENT
+    /begin MEASUREMENT Scope_Value "" ULONG NO_COMPU_METHOD 0 0 0 4294967295
+      ECU_ADDRESS 0
+      SYMBOL_LINK "Scope_Value" 0
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT"#;
+        let run_test = |elf_name: &str, no_local_symbols: bool| {
+            let debug_data =
+                crate::debuginfo::DebugData::load_dwarf(&OsString::from(elf_name), false).unwrap();
+            let mut a2l =
+                a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+            let mut log_msgs = Vec::new();
+            let (summary, _) = update_a2l(
+                &mut a2l,
+                &debug_data,
+                &mut log_msgs,
+                &UpdateOptions {
+                    no_local_symbols,
+                    ..UpdateOptions::new(UpdateType::Addresses, UpdateMode::Default, false)
+                },
+            );
+            (a2l, summary, log_msgs)
+        };
+        let scope_warning = "Scope_Value is now a static variable in the function get_scope_value";
+
+        // in v1 both symbols are global variables
+        let (a2l, summary, log_msgs) = run_test("fixtures/bin/scope_change_test_v1.elf", true);
+        assert_eq!(summary.measurement_updated, 2);
+        assert!(!log_msgs.iter().any(|msg| msg.contains(scope_warning)));
+        let measurement = &a2l.project.module[0].measurement[1];
+        assert_eq!(
+            measurement.symbol_link.as_ref().unwrap().symbol_name,
+            "Scope_Value"
+        );
+
+        // by default, the local variable in v2 is used with a warning, and the SYMBOL_LINK gets a discriminator
+        let (a2l, summary, log_msgs) = run_test("fixtures/bin/scope_change_test_v2.elf", false);
+        assert_eq!(summary.measurement_updated, 2);
+        assert!(log_msgs.iter().any(|msg| msg.contains(scope_warning)));
+        let measurement = &a2l.project.module[0].measurement[1];
+        assert!(measurement
+            .symbol_link
+            .as_ref()
+            .unwrap()
+            .symbol_name
+            .starts_with("Scope_Value{Function:get_scope_value}"));
+        assert_ne!(measurement.ecu_address.as_ref().unwrap().address, 0);
+
+        // with no_local_symbols, the local variable is not used and Scope_Value is not found
+        let (_, summary, _) = run_test("fixtures/bin/scope_change_test_v2.elf", true);
+        assert_eq!(summary.measurement_updated, 1);
+        assert_eq!(summary.not_found.len(), 1);
+        assert_eq!(summary.not_found[0].name, "Scope_Value");
+    }
+
+    #[test]
+    fn test_update_a2l_type_changed() {
+        let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test2.a2l");
+
+        // objects whose data type has changed are counted separately, not as updated
+        let mut log_msgs = Vec::new();
+        let (summary, strict_error) = update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Addresses, UpdateMode::Strict, false),
+        );
+        assert!(strict_error);
+        assert_eq!(summary.axis_pts_updated, 1);
+        assert_eq!(summary.axis_pts_type_changed, 2);
+        assert_eq!(summary.axis_pts_not_updated, 1);
+        assert_eq!(summary.instance_updated, 2);
+        assert_eq!(summary.instance_type_changed, 0);
+        assert_eq!(summary.instance_not_updated, 1);
+
+        // a changed data type is an error in strict mode, even if the symbol was found
+        let result = vec![UpdateResult::InvalidDataType {
+            blocktype: "MEASUREMENT",
+            name: "Measurement".to_string(),
+            line: 1,
+        }];
+        assert!(result.iter().any(UpdateResult::is_error));
+        let mut log_msgs = Vec::new();
+        assert_eq!(log_update_results(&mut log_msgs, &result), (0, 1, 0));
+        assert_eq!(log_msgs.len(), 1);
+    }
+
+    #[test]
+    fn test_update_removed_comparison_quantity() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Missing_Signal "" ULONG NO_COMPU_METHOD 0 0 0 100
+    /end MEASUREMENT
+    /begin CHARACTERISTIC Characteristic_Value "" VALUE 0 RL_U32 0 NO_COMPU_METHOD 0 100
+      COMPARISON_QUANTITY Missing_Signal
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT RL_U32
+      FNC_VALUES 1 ULONG COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        for (update_mode, expect_removed) in [
+            (UpdateMode::Default, true),
+            (UpdateMode::Strict, true),
+            (UpdateMode::Preserve, false),
+        ] {
+            let mut a2l =
+                a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+            let mut log_msgs = Vec::new();
+            let (_, strict_error) = update_a2l(
+                &mut a2l,
+                &debug_data,
+                &mut log_msgs,
+                &UpdateOptions::new(UpdateType::Addresses, update_mode, false),
+            );
+            let module = &a2l.project.module[0];
+            assert_eq!(module.measurement.is_empty(), expect_removed);
+            let comparison_quantity = &module.characteristic[0].comparison_quantity;
+            match update_mode {
+                UpdateMode::Default => {
+                    assert!(comparison_quantity.is_none());
+                    assert!(log_msgs.iter().any(|msg| msg.starts_with(
+                        "Warning: CHARACTERISTIC Characteristic_Value on line 6 references the COMPARISON_QUANTITY Missing_Signal"
+                    )));
+                }
+                UpdateMode::Strict => {
+                    assert!(strict_error);
+                    assert!(comparison_quantity.is_some());
+                    assert!(log_msgs
+                        .iter()
+                        .any(|msg| msg.starts_with("Error: CHARACTERISTIC")));
+                }
+                UpdateMode::Preserve => {
+                    // the MEASUREMENT still exists, with address 0
+                    assert!(comparison_quantity.is_some());
+                    assert!(!log_msgs
+                        .iter()
+                        .any(|msg| msg.contains("COMPARISON_QUANTITY")));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_invalid_bitmask() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Measurement_Value "" VALUE 0 RL_U16 0 NO_COMPU_METHOD 0 100
+      BIT_MASK 0xF0000
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT RL_U16
+      FNC_VALUES 1 UWORD COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+
+        // Measurement_Value is a uint16_t, so the BIT_MASK is reported, but kept by default
+        for (fix_bitmasks, expect_removed) in [(false, false), (true, true)] {
+            let mut a2l =
+                a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+            let mut log_msgs = Vec::new();
+            update_a2l(
+                &mut a2l,
+                &debug_data,
+                &mut log_msgs,
+                &UpdateOptions {
+                    fix_bitmasks,
+                    ..UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false)
+                },
+            );
+            let characteristic = &a2l.project.module[0].characteristic[0];
+            assert_eq!(characteristic.bit_mask.is_none(), expect_removed);
+            assert!(log_msgs.iter().any(|msg| msg.contains(
+                "CHARACTERISTIC Measurement_Value on line 4: the BIT_MASK 0xF0000 does not fit"
+            )));
+        }
+    }
+
     #[test]
     fn test_update_a2l_ok() {
         let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test1.a2l");
@@ -1107,9 +2279,7 @@ mod test {
             &mut a2l,
             &debug_data,
             &mut log_msgs,
-            UpdateType::Addresses,
-            UpdateMode::Strict,
-            false,
+            &UpdateOptions::new(UpdateType::Addresses, UpdateMode::Strict, false),
         );
         assert!(!strict_error);
         assert_eq!(summary.axis_pts_not_updated, 0);
@@ -1130,9 +2300,7 @@ mod test {
             &mut a2l,
             &debug_data,
             &mut log_msgs,
-            UpdateType::Full,
-            UpdateMode::Default,
-            false,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false),
         );
         assert_eq!(summary.axis_pts_not_updated, 0);
         assert_eq!(summary.axis_pts_updated, 3);
@@ -1198,4 +2366,283 @@ mod test {
         let sym_info_result = get_symbol_info("", &Some(symbol_link_elem), &[], &debug_data);
         assert!(sym_info_result.is_err());
     }
+
+    #[test]
+    fn test_symbol_link_and_ifdata_precedence() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Meas "" UWORD NO_COMPU_METHOD 0 0 0 65535
+      /begin IF_DATA CANAPE_EXT 100
+        LINK_MAP "Measurement_Value" 0x0 0x0 0 0x0 0 0x0 0x0
+      /end IF_DATA
+      SYMBOL_LINK "Characteristic_Value" 0
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT"#;
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/update_test.elf"),
+            false,
+        )
+        .unwrap();
+        let a2l = a2lfile::load_from_string(
+            A2L_TEXT,
+            Some(ifdata::A2MLVECTOR_TEXT.to_string()),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let ifdata_vec = &a2l.project.module[0].measurement[0].if_data;
+        let link_meas = Some(SymbolLink::new("Measurement_Value".to_string(), 0));
+        let link_char = Some(SymbolLink::new("Characteristic_Value".to_string(), 0));
+        let link_missing = Some(SymbolLink::new("Does_Not_Exist".to_string(), 0));
+
+        // both sources agree
+        let (sym_info, warning) =
+            get_symbol_info_with_precedence("x", &link_meas, ifdata_vec, &debug_data, false)
+                .unwrap();
+        assert_eq!(sym_info.name, "Measurement_Value");
+        assert!(warning.is_none());
+
+        // the sources disagree: the SYMBOL_LINK wins by default, the LINK_MAP wins with prefer_ifdata
+        let (sym_info, warning) =
+            get_symbol_info_with_precedence("x", &link_char, ifdata_vec, &debug_data, false)
+                .unwrap();
+        assert_eq!(sym_info.name, "Characteristic_Value");
+        let warning = warning.unwrap();
+        assert!(warning.contains("Characteristic_Value") && warning.contains("Measurement_Value"));
+        assert!(warning.contains("from the SYMBOL_LINK is used"));
+        let (sym_info, warning) =
+            get_symbol_info_with_precedence("x", &link_char, ifdata_vec, &debug_data, true)
+                .unwrap();
+        assert_eq!(sym_info.name, "Measurement_Value");
+        assert!(warning.unwrap().contains("from the LINK_MAP is used"));
+
+        // only one of the sources can be resolved
+        let (sym_info, warning) =
+            get_symbol_info_with_precedence("x", &link_missing, ifdata_vec, &debug_data, false)
+                .unwrap();
+        assert_eq!(sym_info.name, "Measurement_Value");
+        assert!(warning.is_none());
+        let (sym_info, warning) =
+            get_symbol_info_with_precedence("x", &link_char, &[], &debug_data, true).unwrap();
+        assert_eq!(sym_info.name, "Characteristic_Value");
+        assert!(warning.is_none());
+
+        // a full update with prefer_ifdata_symbol rewrites the SYMBOL_LINK to match the LINK_MAP
+        let mut a2l = a2l;
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions {
+                prefer_ifdata_symbol: true,
+                ..UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false)
+            },
+        );
+        let measurement = &a2l.project.module[0].measurement[0];
+        assert_eq!(
+            measurement.symbol_link.as_ref().unwrap().symbol_name,
+            "Measurement_Value"
+        );
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.starts_with("Warning: the SYMBOL_LINK of Meas")));
+    }
+
+    #[test]
+    fn test_update_characteristic_number() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Vin_String "" ASCII 0 __SBYTE_Z 0 NO_COMPU_METHOD -128 127
+      NUMBER 17
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Version_String "" ASCII 0 __SBYTE_Z 0 NO_COMPU_METHOD -128 127
+      MATRIX_DIM 16
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Calibration_Block "" VAL_BLK 0 __UBYTE_Z 0 NO_COMPU_METHOD 0 255
+      NUMBER 8
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT __SBYTE_Z
+      FNC_VALUES 1 SBYTE COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT __UBYTE_Z
+      FNC_VALUES 1 UBYTE COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+        let debug_data_v1 = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/string_length_test_v1.elf"),
+            false,
+        )
+        .unwrap();
+        let debug_data_v2 = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/string_length_test_v2.elf"),
+            false,
+        )
+        .unwrap();
+
+        // the file matches v1, so a strict update accepts the NUMBERs
+        let mut a2l = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        let (summary, _) = update_a2l(
+            &mut a2l,
+            &debug_data_v1,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Strict, false),
+        );
+        assert_eq!(summary.characteristic_updated, 3);
+
+        // the string buffers are larger in v2: NUMBER is updated, and MATRIX_DIM is removed from the ASCII object
+        let mut a2l = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data_v2,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false),
+        );
+        let module = &a2l.project.module[0];
+        let vin = &module.characteristic[0];
+        assert_eq!(vin.number.as_ref().unwrap().number, 18);
+        let version = &module.characteristic[1];
+        assert_eq!(version.number.as_ref().unwrap().number, 32);
+        assert!(version.matrix_dim.is_none());
+        let block = &module.characteristic[2];
+        assert_eq!(block.characteristic_type, CharacteristicType::ValBlk);
+        assert_eq!(block.number.as_ref().unwrap().number, 12);
+        assert!(block.matrix_dim.is_none());
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("NUMBER of CHARACTERISTIC Vin_String")
+                && msg.contains("from 17 to 18")));
+        assert!(log_msgs.iter().any(|msg| msg
+            .contains("MATRIX_DIM was removed from the ASCII CHARACTERISTIC Version_String")));
+
+        // with number_to_matrix_dim, the VAL_BLK uses MATRIX_DIM instead
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data_v2,
+            &mut log_msgs,
+            &UpdateOptions {
+                number_to_matrix_dim: true,
+                ..UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false)
+            },
+        );
+        let module = &a2l.project.module[0];
+        let block = &module.characteristic[2];
+        assert!(block.number.is_none());
+        assert_eq!(block.matrix_dim.as_ref().unwrap().dim_list, vec![12]);
+        // NUMBER remains in use for the ASCII objects
+        assert_eq!(module.characteristic[0].number.as_ref().unwrap().number, 18);
+    }
+
+    #[test]
+    fn test_update_embedded_axis() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin CHARACTERISTIC Embedded_Curve "" CURVE 0 RL_Curve 0 NO_COMPU_METHOD -32768 32767
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 5 0 65535
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Embedded_Map "" MAP 0 RL_Map 0 NO_COMPU_METHOD -32768 32767
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 5 0 65535
+      /end AXIS_DESCR
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 5 0 65535
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin CHARACTERISTIC Misordered_Curve "" CURVE 0 RL_Curve 0 NO_COMPU_METHOD -32768 32767
+      /begin AXIS_DESCR STD_AXIS NO_INPUT_QUANTITY NO_COMPU_METHOD 5 0 65535
+      /end AXIS_DESCR
+    /end CHARACTERISTIC
+    /begin RECORD_LAYOUT RL_Curve
+      NO_AXIS_PTS_X 1 UBYTE
+      AXIS_PTS_X 2 UWORD INDEX_INCR DIRECT
+      FNC_VALUES 3 SWORD COLUMN_DIR DIRECT
+    /end RECORD_LAYOUT
+    /begin RECORD_LAYOUT RL_Map
+      NO_AXIS_PTS_X 1 UBYTE
+      NO_AXIS_PTS_Y 2 UBYTE
+      AXIS_PTS_X 3 UWORD INDEX_INCR DIRECT
+      AXIS_PTS_Y 4 UWORD INDEX_INCR DIRECT
+      FNC_VALUES 5 SWORD ROW_DIR DIRECT
+    /end RECORD_LAYOUT
+  /end MODULE
+/end PROJECT"#;
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/embedded_axis_test.elf"),
+            false,
+        )
+        .unwrap();
+        let mut a2l = a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Default, false),
+        );
+
+        // the number of axis points is taken from the array dimensions of the axis members
+        let module = &a2l.project.module[0];
+        assert_eq!(module.characteristic[0].axis_descr[0].max_axis_points, 8);
+        assert_eq!(module.characteristic[1].axis_descr[0].max_axis_points, 6);
+        assert_eq!(module.characteristic[1].axis_descr[1].max_axis_points, 4);
+
+        // the members of Misordered_Curve don't match the positions in RL_Curve
+        let warnings: Vec<&String> = log_msgs
+            .iter()
+            .filter(|msg| msg.starts_with("Warning: the RECORD_LAYOUT"))
+            .collect();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("Misordered_Curve"));
+        assert!(warnings[0].contains("AXIS_PTS_X at position 2 refers to the member no_axis_pts_x"));
+        assert!(warnings[1].contains("NO_AXIS_PTS_X at position 1 refers to the member axis_pts_x"));
+    }
+
+    #[test]
+    fn test_check_axis_pts_dimensions() {
+        let (debug_data, mut a2l) = test_setup("fixtures/a2l/update_test1.a2l");
+        let mut log_msgs = Vec::new();
+        assert_eq!(
+            check_axis_pts_dimensions(&a2l, &debug_data, &mut log_msgs),
+            0
+        );
+
+        // the array Axis_0 has 5 elements; simulate an a2l file that was created when it only had 4
+        let axis_idx = a2l.project.module[0]
+            .axis_pts
+            .iter()
+            .position(|axis_pts| axis_pts.name == "Axis_0")
+            .unwrap();
+        a2l.project.module[0].axis_pts[axis_idx].max_axis_points = 4;
+        let mut log_msgs = Vec::new();
+        assert_eq!(
+            check_axis_pts_dimensions(&a2l, &debug_data, &mut log_msgs),
+            1
+        );
+        assert!(log_msgs[0].contains("MAX_AXIS_POINTS is 4, but the array Axis_0 has 5 elements"));
+
+        // a full update fixes the mismatch and reports the change
+        let mut log_msgs = Vec::new();
+        update_a2l(
+            &mut a2l,
+            &debug_data,
+            &mut log_msgs,
+            &UpdateOptions::new(UpdateType::Full, UpdateMode::Default, true),
+        );
+        assert_eq!(a2l.project.module[0].axis_pts[axis_idx].max_axis_points, 5);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("MAX_AXIS_POINTS changed from 4 to 5")));
+        let mut log_msgs = Vec::new();
+        assert_eq!(
+            check_axis_pts_dimensions(&a2l, &debug_data, &mut log_msgs),
+            0
+        );
+    }
 }
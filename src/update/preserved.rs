@@ -0,0 +1,207 @@
+use super::NotFoundItem;
+use a2lfile::{A2lFile, Annotation, AnnotationLabel, AnnotationOrigin, AnnotationText};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// objects that were preserved during an update are marked with an ANNOTATION using this label
+const PRESERVED_LABEL: &str = "a2ltool-preserved";
+
+// remove the a2ltool-preserved ANNOTATION, once the symbol of the object has been found again
+pub(crate) fn remove_preserved_annotation(annotations: &mut Vec<Annotation>) {
+    annotations.retain(|annotation| !is_preserved_annotation(annotation));
+}
+
+fn is_preserved_annotation(annotation: &Annotation) -> bool {
+    annotation
+        .annotation_label
+        .as_ref()
+        .is_some_and(|label| label.label == PRESERVED_LABEL)
+}
+
+// add an a2ltool-preserved ANNOTATION to each object whose symbol was not found during the update
+// An existing a2ltool-preserved ANNOTATION is replaced, so that it always contains the latest date and reason.
+// Returns the number of annotated objects.
+pub(crate) fn annotate_preserved_items(
+    a2l_file: &mut A2lFile,
+    not_found: &[NotFoundItem],
+) -> usize {
+    let date = current_date();
+    let mut not_found_map: HashMap<&str, HashMap<&str, &NotFoundItem>> = HashMap::new();
+    for item in not_found {
+        not_found_map
+            .entry(item.blocktype)
+            .or_default()
+            .insert(&item.name, item);
+    }
+    let mut count = 0;
+
+    let mut annotate = |blocktype: &str, name: &str, annotations: &mut Vec<Annotation>| {
+        if let Some(item) = not_found_map
+            .get(blocktype)
+            .and_then(|items| items.get(name))
+        {
+            remove_preserved_annotation(annotations);
+            annotations.push(make_preserved_annotation(item, &date));
+            count += 1;
+        }
+    };
+
+    for module in &mut a2l_file.project.module {
+        for axis_pts in &mut module.axis_pts {
+            annotate("AXIS_PTS", &axis_pts.name, &mut axis_pts.annotation);
+        }
+        for blob in &mut module.blob {
+            annotate("BLOB", &blob.name, &mut blob.annotation);
+        }
+        for characteristic in &mut module.characteristic {
+            annotate(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                &mut characteristic.annotation,
+            );
+        }
+        for instance in &mut module.instance {
+            annotate("INSTANCE", &instance.name, &mut instance.annotation);
+        }
+        for measurement in &mut module.measurement {
+            annotate(
+                "MEASUREMENT",
+                &measurement.name,
+                &mut measurement.annotation,
+            );
+        }
+    }
+
+    count
+}
+
+fn make_preserved_annotation(item: &NotFoundItem, date: &str) -> Annotation {
+    let mut annotation = Annotation::new();
+    annotation.annotation_label = Some(AnnotationLabel::new(PRESERVED_LABEL.to_string()));
+    annotation.annotation_origin = Some(AnnotationOrigin::new("a2ltool".to_string()));
+    let mut annotation_text = AnnotationText::new();
+    annotation_text
+        .annotation_text_list
+        .push(format!("preserved on {date}: the symbol was not found"));
+    for err in &item.errors {
+        annotation_text.annotation_text_list.push(err.clone());
+    }
+    annotation.annotation_text = Some(annotation_text);
+    annotation
+}
+
+// write the list of objects that could not be matched to a symbol as CSV
+pub(crate) fn write_preserved_report(
+    filename: &OsStr,
+    not_found: &[NotFoundItem],
+) -> Result<(), String> {
+    std::fs::write(filename, format_preserved_report(not_found)).map_err(|err| {
+        format!(
+            "Failed to write the report of preserved objects to {}: {err}",
+            filename.to_string_lossy()
+        )
+    })
+}
+
+fn format_preserved_report(not_found: &[NotFoundItem]) -> String {
+    let mut report = String::from("block type,name,line,errors\n");
+    for item in not_found {
+        report.push_str(&format!(
+            "{},{},{},{}\n",
+            item.blocktype,
+            csv_field(&item.name),
+            item.line,
+            csv_field(&item.errors.join("; "))
+        ));
+    }
+    report
+}
+
+// quote a CSV field if it contains separators or quotes
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// get the current date in the format YYYY-MM-DD (UTC)
+fn current_date() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// convert a number of days since 1970-01-01 to a date in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19723), (2024, 1, 1));
+    }
+
+    #[test]
+    fn test_preserved_annotation() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MEASUREMENT Missing "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end MEASUREMENT
+    /begin MEASUREMENT Found "" UBYTE NO_COMPU_METHOD 0 0 0 255
+    /end MEASUREMENT
+  /end MODULE
+/end PROJECT"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let not_found = vec![NotFoundItem {
+            blocktype: "MEASUREMENT",
+            name: "Missing".to_string(),
+            line: 4,
+            errors: vec!["symbol \"Missing\" not found".to_string()],
+        }];
+
+        // annotating twice does not duplicate the annotation
+        assert_eq!(annotate_preserved_items(&mut a2l_file, &not_found), 1);
+        assert_eq!(annotate_preserved_items(&mut a2l_file, &not_found), 1);
+        let module = &mut a2l_file.project.module[0];
+        assert_eq!(module.measurement[0].annotation.len(), 1);
+        assert!(module.measurement[1].annotation.is_empty());
+        let text = &module.measurement[0].annotation[0]
+            .annotation_text
+            .as_ref()
+            .unwrap()
+            .annotation_text_list;
+        assert!(text[0].starts_with("preserved on "));
+        assert_eq!(text[1], "symbol \"Missing\" not found");
+
+        remove_preserved_annotation(&mut module.measurement[0].annotation);
+        assert!(module.measurement[0].annotation.is_empty());
+
+        let report = format_preserved_report(&not_found);
+        assert_eq!(
+            report,
+            "block type,name,line,errors\nMEASUREMENT,Missing,4,\"symbol \"\"Missing\"\" not found\"\n"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use a2lfile::{A2lObject, Module};
+use std::collections::HashSet;
+
+// Objects can reference MEASUREMENTs as INPUT_QUANTITY (AXIS_PTS, AXIS_DESCR, TYPEDEF_AXIS) or as
+// COMPARISON_QUANTITY (CHARACTERISTIC). If the referenced MEASUREMENT was removed by the update, then
+// the reference is dangling, which causes errors in tools like CANape.
+// In a strict update each dangling reference is an error and the reference is left unchanged.
+// Otherwise INPUT_QUANTITY references are replaced by NO_INPUT_QUANTITY and COMPARISON_QUANTITY
+// references are deleted, with a warning for each.
+// Returns true if any errors were found in a strict update.
+pub(crate) fn cleanup_removed_quantity_refs(
+    module: &mut Module,
+    removed_names: &HashSet<String>,
+    strict_update: bool,
+    log_msgs: &mut Vec<String>,
+) -> bool {
+    if removed_names.is_empty() {
+        return false;
+    }
+    let mut strict_error = false;
+
+    // returns true if the reference should be removed
+    let mut check_ref = |blocktype: &str, name: &str, line: u32, kind: &str, quantity: &str| {
+        if !removed_names.contains(quantity) {
+            return false;
+        }
+        if strict_update {
+            log_msgs.push(format!(
+                "Error: {blocktype} {name} on line {line} references the {kind} {quantity}, which was removed"
+            ));
+            strict_error = true;
+            false
+        } else {
+            let replacement = if kind == "INPUT_QUANTITY" {
+                "it is replaced by NO_INPUT_QUANTITY"
+            } else {
+                "the reference is removed"
+            };
+            log_msgs.push(format!(
+                "Warning: {blocktype} {name} on line {line} references the {kind} {quantity}, which was removed; {replacement}"
+            ));
+            true
+        }
+    };
+
+    for axis_pts in &mut module.axis_pts {
+        let line = axis_pts.get_line();
+        if check_ref(
+            "AXIS_PTS",
+            &axis_pts.name,
+            line,
+            "INPUT_QUANTITY",
+            &axis_pts.input_quantity,
+        ) {
+            axis_pts.input_quantity = "NO_INPUT_QUANTITY".to_string();
+        }
+    }
+
+    for characteristic in &mut module.characteristic {
+        for axis_descr in &mut characteristic.axis_descr {
+            let line = axis_descr.get_line();
+            if check_ref(
+                "AXIS_DESCR of CHARACTERISTIC",
+                &characteristic.name,
+                line,
+                "INPUT_QUANTITY",
+                &axis_descr.input_quantity,
+            ) {
+                axis_descr.input_quantity = "NO_INPUT_QUANTITY".to_string();
+            }
+        }
+        if let Some(comparison_quantity) = &characteristic.comparison_quantity {
+            if check_ref(
+                "CHARACTERISTIC",
+                &characteristic.name,
+                characteristic.get_line(),
+                "COMPARISON_QUANTITY",
+                &comparison_quantity.name,
+            ) {
+                characteristic.comparison_quantity = None;
+            }
+        }
+    }
+
+    for typedef_characteristic in &mut module.typedef_characteristic {
+        for axis_descr in &mut typedef_characteristic.axis_descr {
+            let line = axis_descr.get_line();
+            if check_ref(
+                "AXIS_DESCR of TYPEDEF_CHARACTERISTIC",
+                &typedef_characteristic.name,
+                line,
+                "INPUT_QUANTITY",
+                &axis_descr.input_quantity,
+            ) {
+                axis_descr.input_quantity = "NO_INPUT_QUANTITY".to_string();
+            }
+        }
+    }
+
+    for typedef_axis in &mut module.typedef_axis {
+        let line = typedef_axis.get_line();
+        if check_ref(
+            "TYPEDEF_AXIS",
+            &typedef_axis.name,
+            line,
+            "INPUT_QUANTITY",
+            &typedef_axis.input_quantity,
+        ) {
+            typedef_axis.input_quantity = "NO_INPUT_QUANTITY".to_string();
+        }
+    }
+
+    strict_error
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin AXIS_PTS Axis_A "" 0 Speed NO_RECORD_LAYOUT 0 NO_COMPU_METHOD 4 0 100
+    /end AXIS_PTS
+    /begin AXIS_PTS Axis_B "" 0 Torque NO_RECORD_LAYOUT 0 NO_COMPU_METHOD 4 0 100
+    /end AXIS_PTS
+    /begin CHARACTERISTIC Curve_A "" CURVE 0 RL 0 NO_COMPU_METHOD 0 100
+      /begin AXIS_DESCR STD_AXIS Speed NO_COMPU_METHOD 4 0 100
+      /end AXIS_DESCR
+      COMPARISON_QUANTITY Speed
+    /end CHARACTERISTIC
+    /begin TYPEDEF_AXIS T_Axis "" Speed NO_RECORD_LAYOUT 0 NO_COMPU_METHOD 4 0 100
+    /end TYPEDEF_AXIS
+  /end MODULE
+/end PROJECT"#;
+
+    #[test]
+    fn test_cleanup_removed_quantity_refs() {
+        let removed_names: HashSet<String> = ["Speed".to_string()].into_iter().collect();
+
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let module = &mut a2l_file.project.module[0];
+        let mut log_msgs = Vec::new();
+        let strict_error =
+            cleanup_removed_quantity_refs(module, &removed_names, false, &mut log_msgs);
+        assert!(!strict_error);
+        assert_eq!(log_msgs.len(), 4);
+        assert!(log_msgs[0].starts_with("Warning: AXIS_PTS Axis_A on line 4"));
+        assert_eq!(module.axis_pts[0].input_quantity, "NO_INPUT_QUANTITY");
+        // Torque was not removed
+        assert_eq!(module.axis_pts[1].input_quantity, "Torque");
+        let characteristic = &module.characteristic[0];
+        assert_eq!(
+            characteristic.axis_descr[0].input_quantity,
+            "NO_INPUT_QUANTITY"
+        );
+        assert!(characteristic.comparison_quantity.is_none());
+        assert_eq!(module.typedef_axis[0].input_quantity, "NO_INPUT_QUANTITY");
+
+        // in a strict update the references are errors, and they are not modified
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let module = &mut a2l_file.project.module[0];
+        let mut log_msgs = Vec::new();
+        let strict_error =
+            cleanup_removed_quantity_refs(module, &removed_names, true, &mut log_msgs);
+        assert!(strict_error);
+        assert_eq!(log_msgs.len(), 4);
+        assert!(log_msgs.iter().all(|msg| msg.starts_with("Error: ")));
+        assert_eq!(module.axis_pts[0].input_quantity, "Speed");
+        assert!(module.characteristic[0].comparison_quantity.is_some());
+    }
+}
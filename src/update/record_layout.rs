@@ -1,6 +1,9 @@
 use crate::debuginfo::{DbgDataType, TypeInfo};
-use crate::update::get_a2l_datatype;
-use a2lfile::{Module, RecordLayout};
+use crate::update::{get_a2l_datatype, get_symbol_info, A2lUpdateInfo, A2lUpdater};
+use a2lfile::{
+    A2lObject, AddrType, AxisPtsDim, CharacteristicType, DataType, FncValues, IndexMode,
+    IndexOrder, Module, RecordLayout,
+};
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -63,6 +66,121 @@ pub(crate) fn get_inner_type(typeinfo: &TypeInfo, memberid: u16) -> Option<&Type
     }
 }
 
+// Check that the members of a struct match the positions of the internal axes in a RECORD_LAYOUT.
+// Each position refers to one member of the struct, in the order of the members. The AXIS_PTS_*
+// must refer to arrays, and the NO_AXIS_PTS_* must refer to single values.
+// Returns a description of each mismatch.
+pub(crate) fn check_record_layout_members(
+    record_layout: &RecordLayout,
+    typeinfo: &TypeInfo,
+) -> Vec<String> {
+    let DbgDataType::Struct { members, .. } = &typeinfo.datatype else {
+        return Vec::new();
+    };
+    let axis_pts_items = [
+        (
+            "AXIS_PTS_X",
+            record_layout.axis_pts_x.as_ref().map(|item| item.position),
+        ),
+        (
+            "AXIS_PTS_Y",
+            record_layout.axis_pts_y.as_ref().map(|item| item.position),
+        ),
+        (
+            "AXIS_PTS_Z",
+            record_layout.axis_pts_z.as_ref().map(|item| item.position),
+        ),
+        (
+            "AXIS_PTS_4",
+            record_layout.axis_pts_4.as_ref().map(|item| item.position),
+        ),
+        (
+            "AXIS_PTS_5",
+            record_layout.axis_pts_5.as_ref().map(|item| item.position),
+        ),
+    ];
+    let no_axis_pts_items = [
+        (
+            "NO_AXIS_PTS_X",
+            record_layout
+                .no_axis_pts_x
+                .as_ref()
+                .map(|item| item.position),
+        ),
+        (
+            "NO_AXIS_PTS_Y",
+            record_layout
+                .no_axis_pts_y
+                .as_ref()
+                .map(|item| item.position),
+        ),
+        (
+            "NO_AXIS_PTS_Z",
+            record_layout
+                .no_axis_pts_z
+                .as_ref()
+                .map(|item| item.position),
+        ),
+        (
+            "NO_AXIS_PTS_4",
+            record_layout
+                .no_axis_pts_4
+                .as_ref()
+                .map(|item| item.position),
+        ),
+        (
+            "NO_AXIS_PTS_5",
+            record_layout
+                .no_axis_pts_5
+                .as_ref()
+                .map(|item| item.position),
+        ),
+    ];
+    let member_name = |position: u16| {
+        members
+            .get_index(usize::from(position.max(1)) - 1)
+            .map_or("", |(name, _)| name.as_str())
+    };
+
+    let mut problems = Vec::new();
+    for (itemname, position) in axis_pts_items
+        .iter()
+        .filter_map(|(itemname, position)| position.map(|pos| (itemname, pos)))
+    {
+        match get_inner_type(typeinfo, position).map(|itemtype| &itemtype.datatype) {
+            Some(DbgDataType::Array { .. }) => {}
+            Some(_) => problems.push(format!(
+                "{itemname} at position {position} refers to the member {}, which is not an array",
+                member_name(position)
+            )),
+            None => problems.push(format!(
+                "{itemname} at position {position} does not refer to a member, because there are only {} members",
+                members.len()
+            )),
+        }
+    }
+    for (itemname, position) in no_axis_pts_items
+        .iter()
+        .filter_map(|(itemname, position)| position.map(|pos| (itemname, pos)))
+    {
+        match get_inner_type(typeinfo, position).map(|itemtype| &itemtype.datatype) {
+            Some(DbgDataType::Array { .. } | DbgDataType::Struct { .. }) => {
+                problems.push(format!(
+                    "{itemname} at position {position} refers to the member {}, which is not a single value",
+                    member_name(position)
+                ));
+            }
+            Some(_) => {}
+            None => problems.push(format!(
+                "{itemname} at position {position} does not refer to a member, because there are only {} members",
+                members.len()
+            )),
+        }
+    }
+
+    problems
+}
+
 pub(crate) fn update_record_layout(
     module: &mut Module,
     recordlayout_info: &mut RecordLayoutInfo,
@@ -233,6 +351,165 @@ pub(crate) fn update_record_layout(
     }
 }
 
+// Verify that the RECORD_LAYOUT referenced by each CHARACTERISTIC and AXIS_PTS exists.
+// If fix_layouts is set, each missing RECORD_LAYOUT is replaced by a default RECORD_LAYOUT for the data type
+// of the symbol. This is not possible for CHARACTERISTICs with axes, since their layout can't be guessed.
+pub(crate) fn check_record_layouts(
+    data: &mut A2lUpdater,
+    info: &A2lUpdateInfo,
+    log_msgs: &mut Vec<String>,
+) {
+    for idx in 0..data.module.characteristic.len() {
+        let characteristic = &data.module.characteristic[idx];
+        if data
+            .reclayout_info
+            .idxmap
+            .contains_key(&characteristic.deposit)
+        {
+            continue;
+        }
+        let name = characteristic.name.clone();
+        let line = characteristic.get_line();
+        let deposit = characteristic.deposit.clone();
+        if !info.fix_layouts {
+            log_msgs.push(format!(
+                "Warning: CHARACTERISTIC {name} on line {line} references the RECORD_LAYOUT {deposit}, which does not exist"
+            ));
+            continue;
+        }
+        if !matches!(
+            characteristic.characteristic_type,
+            CharacteristicType::Value | CharacteristicType::ValBlk | CharacteristicType::Ascii
+        ) {
+            log_msgs.push(format!(
+                "Warning: the missing RECORD_LAYOUT {deposit} of CHARACTERISTIC {name} on line {line} cannot be created for type {}",
+                characteristic.characteristic_type
+            ));
+            continue;
+        }
+        match get_symbol_info(
+            &characteristic.name,
+            &characteristic.symbol_link,
+            &characteristic.if_data,
+            info.debug_data,
+        ) {
+            Ok(sym_info) => {
+                let datatype = get_a2l_datatype(sym_info.typeinfo);
+                let new_deposit = create_default_record_layout(
+                    data.module,
+                    &mut data.reclayout_info,
+                    datatype,
+                    false,
+                );
+                log_msgs.push(format!(
+                    "CHARACTERISTIC {name} on line {line}: the missing RECORD_LAYOUT {deposit} was replaced by {new_deposit}"
+                ));
+                data.module.characteristic[idx].deposit = new_deposit;
+            }
+            Err(_) => {
+                log_msgs.push(format!(
+                    "Warning: the missing RECORD_LAYOUT {deposit} of CHARACTERISTIC {name} on line {line} cannot be created, because the symbol was not found"
+                ));
+            }
+        }
+    }
+
+    for idx in 0..data.module.axis_pts.len() {
+        let axis_pts = &data.module.axis_pts[idx];
+        if data
+            .reclayout_info
+            .idxmap
+            .contains_key(&axis_pts.deposit_record)
+        {
+            continue;
+        }
+        let name = axis_pts.name.clone();
+        let line = axis_pts.get_line();
+        let deposit = axis_pts.deposit_record.clone();
+        if !info.fix_layouts {
+            log_msgs.push(format!(
+                "Warning: AXIS_PTS {name} on line {line} references the RECORD_LAYOUT {deposit}, which does not exist"
+            ));
+            continue;
+        }
+        match get_symbol_info(
+            &axis_pts.name,
+            &axis_pts.symbol_link,
+            &axis_pts.if_data,
+            info.debug_data,
+        ) {
+            Ok(sym_info) => {
+                let datatype = get_a2l_datatype(sym_info.typeinfo);
+                let new_deposit = create_default_record_layout(
+                    data.module,
+                    &mut data.reclayout_info,
+                    datatype,
+                    true,
+                );
+                log_msgs.push(format!(
+                    "AXIS_PTS {name} on line {line}: the missing RECORD_LAYOUT {deposit} was replaced by {new_deposit}"
+                ));
+                data.module.axis_pts[idx].deposit_record = new_deposit;
+            }
+            Err(_) => {
+                log_msgs.push(format!(
+                    "Warning: the missing RECORD_LAYOUT {deposit} of AXIS_PTS {name} on line {line} cannot be created, because the symbol was not found"
+                ));
+            }
+        }
+    }
+}
+
+// create a RECORD_LAYOUT which only contains FNC_VALUES (for a CHARACTERISTIC) or AXIS_PTS_X (for an AXIS_PTS)
+// of the given data type. An existing RECORD_LAYOUT with the same content is used instead, if there is one.
+pub(crate) fn create_default_record_layout(
+    module: &mut Module,
+    recordlayout_info: &mut RecordLayoutInfo,
+    datatype: DataType,
+    for_axis_pts: bool,
+) -> String {
+    let mut new_reclayout = if for_axis_pts {
+        let mut reclayout = RecordLayout::new(format!("__{datatype}_AXIS_PTS_X"));
+        reclayout.axis_pts_x = Some(AxisPtsDim::new(
+            1,
+            datatype,
+            IndexOrder::IndexIncr,
+            AddrType::Direct,
+        ));
+        reclayout
+    } else {
+        // the naming convention (__<type>_Z) matches the RECORD_LAYOUTs created by --insert
+        let mut reclayout = RecordLayout::new(format!("__{datatype}_Z"));
+        reclayout.fnc_values = Some(FncValues::new(
+            1,
+            datatype,
+            IndexMode::RowDir,
+            AddrType::Direct,
+        ));
+        reclayout
+    };
+    // no line break after /begin RECORD_LAYOUT
+    new_reclayout.get_layout_mut().item_location.0 = 0;
+
+    let idx = if let Some(existing_idx) = module
+        .record_layout
+        .iter()
+        .position(|item| compare_rl_content(&new_reclayout, item))
+    {
+        existing_idx
+    } else {
+        new_reclayout.name = make_unique_reclayout_name(new_reclayout.name, recordlayout_info);
+        recordlayout_info
+            .idxmap
+            .insert(new_reclayout.name.clone(), module.record_layout.len());
+        recordlayout_info.refcount.push(0);
+        module.record_layout.push(new_reclayout);
+        module.record_layout.len() - 1
+    };
+    recordlayout_info.refcount[idx] += 1;
+    module.record_layout[idx].name.clone()
+}
+
 fn make_unique_reclayout_name(
     initial_name: String,
     recordlayout_info: &RecordLayoutInfo,
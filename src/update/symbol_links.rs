@@ -0,0 +1,275 @@
+use super::{get_symbol_info, make_symbol_link_string, set_symbol_link};
+use crate::debuginfo::{DbgDataType, DebugData};
+use crate::symbol::SymbolInfo;
+use crate::A2lVersion;
+use a2lfile::{A2lFile, A2lObject, SymbolLink};
+use std::collections::{HashMap, HashSet};
+
+// an object which has both an address and a SYMBOL_LINK
+struct LinkedItem<'a2l> {
+    blocktype: &'static str,
+    name: &'a2l str,
+    line: u32,
+    address: u32,
+    symbol_link: &'a2l mut Option<SymbolLink>,
+}
+
+/// Verify that the address of each object matches the address of the symbol named in its `SYMBOL_LINK`.
+/// If `fix` is set, the `SYMBOL_LINK` of each mismatched object is changed to name the symbol that is
+/// located at the address of the object.
+/// Returns the number of problems that were found.
+pub(crate) fn check_symbol_links(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    fix: bool,
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let use_new_arrays = A2lVersion::from(&*a2l_file) >= A2lVersion::V1_7_0;
+    let mut problem_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let mut items = Vec::new();
+        for measurement in &mut module.measurement {
+            if measurement.symbol_link.is_some() {
+                if let Some(ecu_address) = &measurement.ecu_address {
+                    items.push(LinkedItem {
+                        blocktype: "MEASUREMENT",
+                        name: &measurement.name,
+                        line: measurement.get_layout().line,
+                        address: ecu_address.address,
+                        symbol_link: &mut measurement.symbol_link,
+                    });
+                }
+            }
+        }
+        for characteristic in &mut module.characteristic {
+            if characteristic.symbol_link.is_some() {
+                items.push(LinkedItem {
+                    blocktype: "CHARACTERISTIC",
+                    name: &characteristic.name,
+                    line: characteristic.get_layout().line,
+                    address: characteristic.address,
+                    symbol_link: &mut characteristic.symbol_link,
+                });
+            }
+        }
+        for axis_pts in &mut module.axis_pts {
+            if axis_pts.symbol_link.is_some() {
+                items.push(LinkedItem {
+                    blocktype: "AXIS_PTS",
+                    name: &axis_pts.name,
+                    line: axis_pts.get_layout().line,
+                    address: axis_pts.address,
+                    symbol_link: &mut axis_pts.symbol_link,
+                });
+            }
+        }
+        for blob in &mut module.blob {
+            if blob.symbol_link.is_some() {
+                items.push(LinkedItem {
+                    blocktype: "BLOB",
+                    name: &blob.name,
+                    line: blob.get_layout().line,
+                    address: blob.start_address,
+                    symbol_link: &mut blob.symbol_link,
+                });
+            }
+        }
+        for instance in &mut module.instance {
+            if instance.symbol_link.is_some() {
+                items.push(LinkedItem {
+                    blocktype: "INSTANCE",
+                    name: &instance.name,
+                    line: instance.get_layout().line,
+                    address: instance.start_address,
+                    symbol_link: &mut instance.symbol_link,
+                });
+            }
+        }
+
+        let mut mismatched = Vec::new();
+        for (idx, item) in items.iter().enumerate() {
+            // the IF_DATA is deliberately ignored here: only the SYMBOL_LINK should be checked
+            match get_symbol_info(item.name, &*item.symbol_link, &[], debug_data) {
+                Ok(sym_info) => {
+                    if sym_info.address != u64::from(item.address) {
+                        log_msgs.push(format!(
+                            "{} {} on line {}: the address 0x{:X} does not match the address 0x{:X} of the SYMBOL_LINK {}",
+                            item.blocktype,
+                            item.name,
+                            item.line,
+                            item.address,
+                            sym_info.address,
+                            sym_info.name
+                        ));
+                        mismatched.push(idx);
+                    }
+                }
+                Err(errmsgs) => {
+                    for errmsg in errmsgs {
+                        log_msgs.push(format!(
+                            "{} {} on line {}: the SYMBOL_LINK could not be resolved: {errmsg}",
+                            item.blocktype, item.name, item.line
+                        ));
+                    }
+                    problem_count += 1;
+                }
+            }
+        }
+        problem_count += mismatched.len();
+
+        if fix && !mismatched.is_empty() {
+            // find all symbols located at the addresses of the mismatched items
+            let wanted_addresses: HashSet<u64> = mismatched
+                .iter()
+                .map(|idx| u64::from(items[*idx].address))
+                .collect();
+            let mut candidates: HashMap<u64, Vec<SymbolInfo>> = HashMap::new();
+            for sym_info in debug_data.iter(use_new_arrays) {
+                if wanted_addresses.contains(&sym_info.address) {
+                    candidates
+                        .entry(sym_info.address)
+                        .or_default()
+                        .push(sym_info);
+                }
+            }
+
+            for idx in mismatched {
+                let item = &mut items[idx];
+                let opt_sym_info = candidates
+                    .get(&u64::from(item.address))
+                    .and_then(|symbols| select_symbol(symbols, item.blocktype == "INSTANCE"));
+                if let Some(sym_info) = opt_sym_info {
+                    let symbol_name = make_symbol_link_string(sym_info, debug_data);
+                    log_msgs.push(format!(
+                        "{} {} on line {}: the SYMBOL_LINK was changed to {symbol_name}",
+                        item.blocktype, item.name, item.line
+                    ));
+                    set_symbol_link(item.symbol_link, symbol_name);
+                    if let Some(symbol_link) = item.symbol_link {
+                        symbol_link.offset = 0;
+                    }
+                } else {
+                    log_msgs.push(format!(
+                        "{} {} on line {}: there is no symbol at address 0x{:X}, the SYMBOL_LINK was not changed",
+                        item.blocktype, item.name, item.line, item.address
+                    ));
+                }
+            }
+        }
+    }
+
+    problem_count
+}
+
+// Several symbols can be located at the same address, e.g. a struct variable and its first member.
+// An INSTANCE should refer to the outermost symbol, while all other objects should refer to
+// the outermost symbol which is not a struct, class or union.
+fn select_symbol<'a, 'dbg>(
+    symbols: &'a [SymbolInfo<'dbg>],
+    is_instance: bool,
+) -> Option<&'a SymbolInfo<'dbg>> {
+    if is_instance {
+        symbols.first()
+    } else {
+        symbols.iter().find(|sym_info| {
+            !matches!(
+                sym_info.typeinfo.datatype,
+                DbgDataType::Struct { .. } | DbgDataType::Class { .. } | DbgDataType::Union { .. }
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use a2lfile::{EcuAddress, Measurement};
+    use std::ffi::OsString;
+
+    fn make_measurement(name: &str, address: u64, symbol_name: &str) -> Measurement {
+        let mut measurement = Measurement::new(
+            name.to_string(),
+            String::new(),
+            a2lfile::DataType::Ulong,
+            "NO_COMPU_METHOD".to_string(),
+            0,
+            0.0,
+            0.0,
+            100.0,
+        );
+        measurement.ecu_address = Some(EcuAddress::new(address as u32));
+        measurement.symbol_link = Some(SymbolLink::new(symbol_name.to_string(), 0));
+        measurement
+    }
+
+    #[test]
+    fn test_check_symbol_links() {
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+        let meas_address = debug_data.variables["Measurement_Value"][0].address;
+        let char_address = debug_data.variables["Characteristic_Value"][0].address;
+
+        let mut a2l_file = a2lfile::new();
+        let module = &mut a2l_file.project.module[0];
+        // consistent: the address matches the SYMBOL_LINK
+        module.measurement.push(make_measurement(
+            "Consistent",
+            meas_address,
+            "Measurement_Value",
+        ));
+        // inconsistent: the address belongs to Characteristic_Value
+        module.measurement.push(make_measurement(
+            "Inconsistent",
+            char_address,
+            "Measurement_Value",
+        ));
+
+        // check only: the problem is reported, but nothing is changed
+        let mut log_msgs = Vec::new();
+        let problems = check_symbol_links(&mut a2l_file, &debug_data, false, &mut log_msgs);
+        assert_eq!(problems, 1);
+        assert_eq!(log_msgs.len(), 1);
+        assert!(log_msgs[0].contains("Inconsistent"));
+        let module = &a2l_file.project.module[0];
+        assert_eq!(
+            module.measurement[1]
+                .symbol_link
+                .as_ref()
+                .unwrap()
+                .symbol_name,
+            "Measurement_Value"
+        );
+
+        // fix: the SYMBOL_LINK is changed to the symbol at the ECU_ADDRESS
+        let mut log_msgs = Vec::new();
+        let problems = check_symbol_links(&mut a2l_file, &debug_data, true, &mut log_msgs);
+        assert_eq!(problems, 1);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(
+            module.measurement[0]
+                .symbol_link
+                .as_ref()
+                .unwrap()
+                .symbol_name,
+            "Measurement_Value"
+        );
+        assert_eq!(
+            module.measurement[1]
+                .symbol_link
+                .as_ref()
+                .unwrap()
+                .symbol_name,
+            "Characteristic_Value"
+        );
+        assert_eq!(
+            module.measurement[1].ecu_address.as_ref().unwrap().address,
+            char_address as u32
+        );
+
+        // after the fix there are no more problems
+        let mut log_msgs = Vec::new();
+        let problems = check_symbol_links(&mut a2l_file, &debug_data, false, &mut log_msgs);
+        assert_eq!(problems, 0);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::debuginfo::{DbgDataType, DebugData};
+use crate::symbol::find_symbol;
+use a2lfile::{A2lFile, A2lObject};
+
+/// Set the values of the SYSTEM_CONSTANTs in MOD_PAR from the symbols in the debug data.
+///
+/// A SYSTEM_CONSTANT whose name starts with one of the `dim_prefixes` receives the dimension of the
+/// array named by the remainder of the name, e.g. "sizeof_MapX" -> dimension of "MapX".
+/// Otherwise, a SYSTEM_CONSTANT with the same name as an integer variable receives the initial value
+/// of the variable from the data sections of the elf file.
+/// Constants without a matching symbol are left untouched.
+/// Returns the number of SYSTEM_CONSTANTs that were updated.
+pub(crate) fn update_system_constants(
+    a2l_file: &mut A2lFile,
+    debug_data: &DebugData,
+    dim_prefixes: &[&str],
+    log_msgs: &mut Vec<String>,
+) -> usize {
+    let mut updated_count = 0;
+
+    for module in &mut a2l_file.project.module {
+        let Some(mod_par) = &mut module.mod_par else {
+            continue;
+        };
+        for system_constant in &mut mod_par.system_constant {
+            let line = system_constant.get_layout().line;
+            match get_system_constant_value(&system_constant.name, debug_data, dim_prefixes) {
+                Ok(value) => {
+                    if value != system_constant.value {
+                        log_msgs.push(format!(
+                            "SYSTEM_CONSTANT {} on line {line}: value changed from \"{}\" to \"{value}\"",
+                            system_constant.name, system_constant.value
+                        ));
+                        system_constant.value = value;
+                    }
+                    updated_count += 1;
+                }
+                Err(errmsg) => {
+                    log_msgs.push(format!(
+                        "SYSTEM_CONSTANT {} on line {line} was not changed: {errmsg}",
+                        system_constant.name
+                    ));
+                }
+            }
+        }
+    }
+
+    updated_count
+}
+
+fn get_system_constant_value(
+    name: &str,
+    debug_data: &DebugData,
+    dim_prefixes: &[&str],
+) -> Result<String, String> {
+    // "<prefix><array name>": use the dimension of the array
+    for prefix in dim_prefixes {
+        if let Some(array_name) = name.strip_prefix(prefix) {
+            if let Ok(sym_info) = find_symbol(array_name, debug_data) {
+                let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+                return match &typeinfo.datatype {
+                    DbgDataType::Array { dim, .. } if !dim.is_empty() => Ok(dim[0].to_string()),
+                    _ => Err(format!("symbol {array_name} is not an array")),
+                };
+            }
+        }
+    }
+
+    // the name of the SYSTEM_CONSTANT is the name of an integer variable: use its initial value
+    let sym_info = find_symbol(name, debug_data)?;
+    let typeinfo = sym_info.typeinfo.get_reference(&debug_data.types);
+    let signed = match &typeinfo.datatype {
+        DbgDataType::Uint8 | DbgDataType::Uint16 | DbgDataType::Uint32 | DbgDataType::Uint64 => {
+            false
+        }
+        DbgDataType::Sint8 | DbgDataType::Sint16 | DbgDataType::Sint32 | DbgDataType::Sint64 => {
+            true
+        }
+        DbgDataType::Enum { signed, .. } => *signed,
+        _ => {
+            return Err(format!(
+                "symbol {name} has the unsuitable data type {typeinfo}"
+            ))
+        }
+    };
+    debug_data
+        .memory
+        .read_integer(sym_info.address, typeinfo.get_size(), signed)
+        .map(|value| value.to_string())
+        .ok_or_else(|| {
+            format!(
+                "the value of symbol {name} at address 0x{:X} is not initialized in the elf file",
+                sym_info.address
+            )
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_update_system_constants() {
+        static A2L_TEXT: &str = r#"ASAP2_VERSION 1 71
+/begin PROJECT p ""
+  /begin MODULE m ""
+    /begin MOD_PAR ""
+      SYSTEM_CONSTANT "Characteristic_Value" "0"
+      SYSTEM_CONSTANT "dim_Characteristic_ValBlk" "0"
+      SYSTEM_CONSTANT "Measurement_Value" "7"
+      SYSTEM_CONSTANT "Unknown_Constant" "42"
+    /end MOD_PAR
+  /end MODULE
+/end PROJECT
+"#;
+        let mut a2l_file =
+            a2lfile::load_from_string(A2L_TEXT, None, &mut Vec::new(), false).unwrap();
+        let debug_data =
+            DebugData::load_dwarf(&OsString::from("fixtures/bin/update_test.elf"), false).unwrap();
+
+        let mut log_msgs = Vec::new();
+        let count = update_system_constants(&mut a2l_file, &debug_data, &["dim_"], &mut log_msgs);
+        assert_eq!(count, 2);
+        let system_constants = &a2l_file.project.module[0]
+            .mod_par
+            .as_ref()
+            .unwrap()
+            .system_constant;
+        // uint32_t Characteristic_Value = 3;
+        assert_eq!(system_constants[0].value, "3");
+        // float Characteristic_ValBlk[5]
+        assert_eq!(system_constants[1].value, "5");
+        // Measurement_Value is not initialized, and Unknown_Constant does not exist
+        assert_eq!(system_constants[2].value, "7");
+        assert_eq!(system_constants[3].value, "42");
+        assert_eq!(log_msgs.len(), 4);
+
+        // a second update finds nothing to change
+        let mut log_msgs = Vec::new();
+        let count = update_system_constants(&mut a2l_file, &debug_data, &["dim_"], &mut log_msgs);
+        assert_eq!(count, 2);
+        assert_eq!(log_msgs.len(), 2);
+    }
+}
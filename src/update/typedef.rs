@@ -1,14 +1,16 @@
-use crate::debuginfo::{make_simple_unit_name, DbgDataType, DebugData, TypeInfo};
+use crate::debuginfo::{make_simple_unit_name, DbgDataType, DebugData, TypeInfo, TypeMap};
 use crate::update::enums::{cond_create_enum_conversion, update_enum_compu_methods};
 use crate::update::{
-    adjust_limits, get_a2l_datatype, get_fnc_values_memberid, get_inner_type, set_address_type,
-    set_bitmask, set_matrix_dim, update_characteristic_axis, update_record_layout, A2lUpdateInfo,
-    RecordLayoutInfo, TypedefNames, TypedefReferrer, TypedefsRefInfo,
+    adjust_limits, get_a2l_datatype, get_fnc_values_memberid, get_inner_type, module_big_endian,
+    set_address_type, set_bitmask, set_byte_order, set_matrix_dim, update_characteristic_axis,
+    update_record_layout, A2lUpdateInfo, RecordLayoutInfo, TypedefNames, TypedefReferrer,
+    TypedefsRefInfo,
 };
+use crate::A2lVersion;
 use a2lfile::{
-    A2lObject, AddrType, CharacteristicType, FncValues, IndexMode, Module, Number, RecordLayout,
-    StructureComponent, SymbolTypeLink, TypedefBlob, TypedefCharacteristic, TypedefMeasurement,
-    TypedefStructure,
+    A2lObject, AddrType, AxisDescr, ByteOrder, CharacteristicType, FncValues, IndexMode, Module,
+    Number, RecordLayout, StructureComponent, SymbolTypeLink, TypedefAxis, TypedefBlob,
+    TypedefCharacteristic, TypedefMeasurement, TypedefStructure,
 };
 use fxhash::FxBuildHasher;
 use indexmap::{IndexMap, IndexSet};
@@ -46,6 +48,8 @@ struct TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
     log_msgs: &'log mut Vec<String>,
     /// name to index mapping for CompuMethods
     compu_method_index: &'cm HashMap<String, usize>,
+    /// number of decimal places for calculated limits, if they should be rounded
+    limit_precision: Option<u8>,
 
     // --- computed data ---
     /// all TYPEDEF_STRUCTURES, extracted from the module during the update for access by name
@@ -85,11 +89,93 @@ pub(crate) fn update_module_typedefs(
         recordlayout_info,
         typedef_ref_info,
         &info.compu_method_index,
+        info.limit_precision,
     );
 
     updater.process_typedefs(info.preserve_unknown, false);
 }
 
+/// Refresh the `ADDRESS_OFFSET` of the STRUCTURE_COMPONENTs of the TYPEDEF_STRUCTUREs used by INSTANCEs,
+/// without rebuilding the TYPEDEFs. This lets an address-only update follow members that moved within a struct.
+/// Each component is matched to a member of the struct by its `SYMBOL_TYPE_LINK`, or by its name.
+/// Nested TYPEDEF_STRUCTUREs are refreshed using the type of the member that contains them.
+/// Components that can't be matched keep their offset.
+pub(crate) fn update_typedef_structure_offsets(
+    module: &mut Module,
+    debug_data: &DebugData,
+    typedef_ref_info: &TypedefsRefInfo,
+    log_msgs: &mut Vec<String>,
+) {
+    let td_struct_index: HashMap<String, usize> = module
+        .typedef_structure
+        .iter()
+        .enumerate()
+        .map(|(idx, td_struct)| (td_struct.name.clone(), idx))
+        .collect();
+    let mut pending: Vec<(String, &TypeInfo)> = typedef_ref_info
+        .iter()
+        .filter_map(|(typedef_name, referrers)| {
+            referrers
+                .iter()
+                .find_map(|(typeinfo, referrer)| match referrer {
+                    TypedefReferrer::Instance(_) => *typeinfo,
+                    TypedefReferrer::StructureComponent(..) => None,
+                })
+                .map(|typeinfo| (typedef_name.clone(), typeinfo))
+        })
+        .collect();
+    // a TYPEDEF_STRUCTURE could be used with several different types, but only the first one is applied
+    let mut visited = HashSet::new();
+
+    while let Some((typedef_name, typeinfo)) = pending.pop() {
+        let Some(&idx) = td_struct_index.get(&typedef_name) else {
+            continue;
+        };
+        if !visited.insert(typedef_name) {
+            continue;
+        }
+        let typeinfo = typeinfo.get_reference(&debug_data.types);
+        let members = match &typeinfo.datatype {
+            DbgDataType::Struct { members, .. }
+            | DbgDataType::Class { members, .. }
+            | DbgDataType::Union { members, .. } => members,
+            _ => continue,
+        };
+
+        let td_struct = &mut module.typedef_structure[idx];
+        for sc in &mut td_struct.structure_component {
+            let member_name = sc
+                .symbol_type_link
+                .as_ref()
+                .map_or(&sc.component_name, |stl| &stl.symbol_type);
+            if let Some((member_typeinfo, member_offset)) = members.get(member_name) {
+                let new_offset = *member_offset as u32;
+                if sc.address_offset != new_offset {
+                    log_msgs.push(format!(
+                        "STRUCTURE_COMPONENT \"{}\" of TYPEDEF_STRUCTURE \"{}\": ADDRESS_OFFSET changed from {} to {new_offset}",
+                        sc.component_name, td_struct.name, sc.address_offset
+                    ));
+                    sc.address_offset = new_offset;
+                }
+                // the component might be a nested struct, whose components need to be refreshed too
+                let member_typeinfo = member_typeinfo.get_reference(&debug_data.types);
+                let member_typeinfo = member_typeinfo
+                    .get_pointer(&debug_data.types)
+                    .map_or(member_typeinfo, |(_, t)| t);
+                let member_typeinfo = member_typeinfo.get_arraytype().unwrap_or(member_typeinfo);
+                pending.push((sc.component_type.clone(), member_typeinfo));
+            } else {
+                log_msgs.push(format!(
+                    "Warning: STRUCTURE_COMPONENT \"{}\" of TYPEDEF_STRUCTURE \"{}\" on line {} does not match any member of the struct; its ADDRESS_OFFSET was not changed",
+                    sc.component_name,
+                    td_struct.name,
+                    sc.get_line()
+                ));
+            }
+        }
+    }
+}
+
 pub(crate) fn create_new_typedefs<'a>(
     module: &mut Module,
     debug_data: &'a DebugData,
@@ -117,13 +203,93 @@ pub(crate) fn create_new_typedefs<'a>(
         &mut recordlayout_info,
         typedef_ref_info,
         &dummy_cm_index,
+        None,
     );
 
     updater.process_typedefs(true, true);
 }
 
+/// Create TYPEDEFs for explicitly requested types, so that INSTANCEs of these types can be created later.
+/// No INSTANCE is created or modified. Each entry of `type_list` states if the TYPEDEF should be
+/// a calibration type (`true`) or a measurement type (`false`).
+pub(crate) fn create_typedefs_for_types<'a>(
+    module: &mut Module,
+    debug_data: &'a DebugData,
+    log_msgs: &mut Vec<String>,
+    type_list: &[(&'a TypeInfo, bool)],
+) {
+    let typedef_names = TypedefNames::new(module);
+    let mut recordlayout_info = RecordLayoutInfo::build(module);
+    let dummy_cm_index = HashMap::new();
+    let updater = TypedefUpdater::new(
+        module,
+        debug_data,
+        log_msgs,
+        typedef_names,
+        &mut recordlayout_info,
+        HashMap::new(),
+        &dummy_cm_index,
+        None,
+    );
+
+    updater.process_named_types(type_list);
+}
+
+/// Find a type by its name, which can be the name of a struct, union or class, or the name of a typedef.
+/// The same type is usually present in several compile units; this is only a problem if the definitions differ.
+pub(crate) fn find_named_type<'a>(
+    debug_data: &'a DebugData,
+    typename: &str,
+) -> Result<&'a TypeInfo, String> {
+    let Some(type_offsets) = debug_data.typenames().get(typename) else {
+        return Err(format!(
+            "Error: the type {typename} was not found in the debug info"
+        ));
+    };
+    let mut distinct_types = Vec::<&TypeInfo>::new();
+    for typeinfo in type_offsets
+        .iter()
+        .filter_map(|offset| debug_data.types.get(offset))
+    {
+        // a typedef is replaced by the type it refers to
+        let typeinfo = typeinfo.get_reference(&debug_data.types);
+        if !distinct_types
+            .iter()
+            .any(|other| typeinfo.compare(other, &debug_data.types))
+        {
+            distinct_types.push(typeinfo);
+        }
+    }
+
+    match distinct_types.len() {
+        0 => Err(format!(
+            "Error: the type {typename} was not found in the debug info"
+        )),
+        1 => Ok(distinct_types[0]),
+        _ => {
+            let locations: Vec<String> = distinct_types
+                .iter()
+                .map(|typeinfo| {
+                    debug_data
+                        .unit_names
+                        .get(typeinfo.unit_idx)
+                        .and_then(Option::as_deref)
+                        .unwrap_or("<unknown compile unit>")
+                        .to_string()
+                })
+                .collect();
+            Err(format!(
+                "Error: the type name {typename} is ambiguous. There are {} different definitions in the compile units {}",
+                distinct_types.len(),
+                locations.join(", ")
+            ))
+        }
+    }
+}
+
 impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
     /// create a new `TypedefUpdater`
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         module: &'a2l mut Module,
         debug_data: &'dbg DebugData,
@@ -132,6 +298,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
         recordlayout_info: &'rl mut RecordLayoutInfo,
         typedef_ref_info: TypedefsRefInfo<'dbg>,
         compu_method_index: &'cm HashMap<String, usize>,
+        limit_precision: Option<u8>,
     ) -> Self {
         let axis_pts_dim: HashMap<String, u16> = module
             .axis_pts
@@ -148,6 +315,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
             debug_data,
             log_msgs,
             compu_method_index,
+            limit_precision,
             typedef_names,
             recordlayout_info,
             typedef_ref_info,
@@ -171,6 +339,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
             self.update_all_typedef_characteristic();
             self.update_all_typedef_measurement();
             self.update_all_typedef_structure();
+            self.update_instance_relative_axes();
 
             if !preserve_unknown {
                 self.cleanup_unused_typedefs();
@@ -188,6 +357,37 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
         }
     }
 
+    /// create TYPEDEFs for the given types, without any reference from an INSTANCE
+    /// Existing TYPEDEFs are reused if they match the type.
+    fn process_named_types(mut self, type_list: &[(&'dbg TypeInfo, bool)]) {
+        self.typedef_names.structure = HashSet::new();
+
+        self.calc_structure_category();
+        self.build_structure_hash();
+        self.process_structure_components(true);
+
+        let mut enum_convlist = HashMap::<String, &TypeInfo>::new();
+        for (typeinfo, is_calib) in type_list {
+            if self
+                .create_typedef(typeinfo, *is_calib, &mut enum_convlist)
+                .is_none()
+            {
+                self.log_msgs.push(format!(
+                    "Warning: no TYPEDEF can be created for the type {typeinfo}"
+                ));
+            }
+        }
+        update_enum_compu_methods(self.module, &enum_convlist);
+
+        // store the TPEDEF_STRUCTUREs in the module again
+        for (_, td_struct) in self.typedef_structs {
+            self.module.typedef_structure.push(td_struct);
+        }
+        for (_, td_struct) in self.preserved_structs {
+            self.module.typedef_structure.push(td_struct);
+        }
+    }
+
     /// separate the `TYPEDEF_STRUCTUREs` into two groups - one references only
     /// `TYPEDEF_MEASUREMENTS`, the other only references `TYPEDEF_AXIS/BLOB/CHARACTERISTIC`
     fn calc_structure_category(&mut self) {
@@ -437,6 +637,12 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
                         {
                             self.store_structure_component(idx, sc, component_typeinfo);
                         }
+                    } else {
+                        // the member was removed from the struct, or renamed
+                        self.log_msgs.push(format!(
+                            "removing STRUCTURE_COMPONENT \"{}\" from TYPEDEF_STRUCTURE \"{}\": there is no matching member in the debug info",
+                            sc.component_name, self.typedef_structs[idx].name
+                        ));
                     }
                 }
             } else if let Some(component_typeinfo) = typeinfo.get_arraytype().or_else(|| {
@@ -513,8 +719,8 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
             }
         } else if self.typedef_names.contains(component_type) {
             // ok - refers to TYPEDEF_AXIS or TYPEDEF_BLOB
-            // no additional restrictions here, we don't create or update
-            // TYPEDEF_AXIS, and TYPEDEF_BLOB can legitimately be anything
+            // no additional restrictions here, we don't create TYPEDEF_AXIS
+            // and TYPEDEF_BLOB can legitimately be anything
             return true;
         }
         false
@@ -879,7 +1085,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
         if let Some(typeoffsets) = typeinfo
             .name
             .as_deref()
-            .and_then(|tname| self.debug_data.typenames.get(tname))
+            .and_then(|tname| self.debug_data.typenames().get(tname))
         {
             let typename = typeinfo.name.as_deref().unwrap();
             // If the type has a typename, then debug_data.typenames will have a HashMap of all typeinfos for this typename
@@ -969,9 +1175,125 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
         }
     }
 
-    /// update `TYPEDEF_AXIS`?
+    /// update the `TYPEDEF_AXIS` that are used as shared axes
+    ///
+    /// Only a `TYPEDEF_AXIS` that is referenced by the `AXIS_PTS_REF` of a `TYPEDEF_CHARACTERISTIC` in the
+    /// same structure is updated; this is the layout that is created by --shared-axes.
+    /// All other `TYPEDEF_AXIS` are left unchanged. Only the number of axis points is updated, based on
+    /// the size of the array.
     fn update_all_typedef_axis(&mut self) {
-        // It's not clear that these can or should be updated.
+        let axis_sizes = self.shared_typedef_axis_sizes();
+        for td_axis in &mut self.module.typedef_axis {
+            let Some(&size) = axis_sizes.get(&td_axis.name) else {
+                continue;
+            };
+            match u16::try_from(size) {
+                Ok(max_axis_points) if max_axis_points != td_axis.max_axis_points => {
+                    self.log_msgs.push(format!(
+                        "updating TYPEDEF_AXIS \"{}\": MAX_AXIS_POINTS changed from {} to {max_axis_points}",
+                        td_axis.name, td_axis.max_axis_points
+                    ));
+                    td_axis.max_axis_points = max_axis_points;
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    self.log_msgs.push(format!(
+                        "Error: TYPEDEF_AXIS \"{}\" on line {} was not updated: the array has {size} elements, but an axis can have at most {} points",
+                        td_axis.name,
+                        td_axis.get_line(),
+                        u16::MAX
+                    ));
+                }
+            }
+        }
+    }
+
+    /// get the array sizes of all `TYPEDEF_AXIS` that are used as instance-relative axes in a `TYPEDEF_STRUCTURE`
+    ///
+    /// The typeinfo of a `STRUCTURE_COMPONENT` is the element type of the array, so the size is taken
+    /// from the member of the struct.
+    fn shared_typedef_axis_sizes(&self) -> HashMap<String, u64> {
+        let mut axis_sizes = HashMap::new();
+        for td_struct in self.typedef_structs.values() {
+            let Some((typeinfo, _)) = self.typedef_map.get(&td_struct.name) else {
+                continue;
+            };
+            let typeinfo = typeinfo
+                .get_pointer(&self.debug_data.types)
+                .map_or(*typeinfo, |(_, t)| t);
+            let Some(members) = typeinfo.get_members() else {
+                continue;
+            };
+            let axis_descrs = td_struct
+                .structure_component
+                .iter()
+                .filter_map(|sc| {
+                    self.module
+                        .typedef_characteristic
+                        .iter()
+                        .find(|td_char| td_char.name == sc.component_type)
+                })
+                .flat_map(|td_char| &td_char.axis_descr);
+            for axis_descr in axis_descrs {
+                let Some(td_axis) =
+                    get_typedef_axis_for_axis_descr(self.module, td_struct, axis_descr)
+                else {
+                    continue;
+                };
+                // get_typedef_axis_for_axis_descr found the TYPEDEF_AXIS through this component
+                let Some(axis_sc) = td_struct.structure_component.iter().find(|sc| {
+                    axis_descr
+                        .axis_pts_ref
+                        .as_ref()
+                        .is_some_and(|axis_pts_ref| sc.component_name == axis_pts_ref.axis_points)
+                }) else {
+                    continue;
+                };
+                let member_name = axis_sc
+                    .symbol_type_link
+                    .as_ref()
+                    .map_or(&axis_sc.component_name, |stl| &stl.symbol_type);
+                if let Some((member_typeinfo, _)) = members.get(member_name) {
+                    let member_typeinfo = member_typeinfo.get_reference(&self.debug_data.types);
+                    if let DbgDataType::Array { dim, .. } = &member_typeinfo.datatype {
+                        axis_sizes.insert(td_axis.name.clone(), dim[0]);
+                    }
+                }
+            }
+        }
+        axis_sizes
+    }
+
+    /// update the `AXIS_DESCRs` of `TYPEDEF_CHARACTERISTICs` that use a `TYPEDEF_AXIS` in the same structure
+    ///
+    /// The `AXIS_PTS_REF` of such an axis names a `STRUCTURE_COMPONENT` instead of an `AXIS_PTS`, so
+    /// `update_characteristic_axis` can't find it.
+    fn update_instance_relative_axes(&mut self) {
+        let mut updates = Vec::new();
+        for td_struct in self.typedef_structs.values() {
+            for sc in &td_struct.structure_component {
+                let Some((char_idx, td_char)) = self
+                    .module
+                    .typedef_characteristic
+                    .iter()
+                    .enumerate()
+                    .find(|(_, td_char)| td_char.name == sc.component_type)
+                else {
+                    continue;
+                };
+                for (axis_idx, axis_descr) in td_char.axis_descr.iter().enumerate() {
+                    if let Some(td_axis) =
+                        get_typedef_axis_for_axis_descr(self.module, td_struct, axis_descr)
+                    {
+                        updates.push((char_idx, axis_idx, td_axis.max_axis_points));
+                    }
+                }
+            }
+        }
+        for (char_idx, axis_idx, max_axis_points) in updates {
+            self.module.typedef_characteristic[char_idx].axis_descr[axis_idx].max_axis_points =
+                max_axis_points;
+        }
     }
 
     /// update all `TYPEDEF_BLOBs`
@@ -1057,6 +1379,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
                 enum_convlist.insert(td_char.conversion.clone(), inner_typeinfo);
             }
             set_bitmask(&mut td_char.bit_mask, inner_typeinfo);
+            self.set_typedef_byte_order(&mut td_char.byte_order, inner_typeinfo);
 
             let opt_compu_method = self
                 .compu_method_index
@@ -1067,6 +1390,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
                 td_char.lower_limit,
                 td_char.upper_limit,
                 opt_compu_method,
+                self.limit_precision,
             );
             td_char.lower_limit = ll;
             td_char.upper_limit = ul;
@@ -1158,6 +1482,19 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
         update_enum_compu_methods(self.module, &enum_convlist);
     }
 
+    /// set or remove the `BYTE_ORDER` of a `TYPEDEF_*`, if the type has an explicit byte order
+    /// TYPEDEF_* items only exist in a2l version 1.7.0 and later
+    fn set_typedef_byte_order(&self, opt_byte_order: &mut Option<ByteOrder>, typeinfo: &TypeInfo) {
+        let file_big_endian = self.debug_data.memory.big_endian;
+        set_byte_order(
+            opt_byte_order,
+            typeinfo,
+            module_big_endian(self.module, file_big_endian),
+            file_big_endian,
+            A2lVersion::V1_7_0,
+        );
+    }
+
     /// update one `TYPEDEF_MEASUREMENT`
     fn update_typedef_measurement(
         &mut self,
@@ -1167,6 +1504,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
     ) {
         td_meas.datatype = get_a2l_datatype(meas_type);
         set_bitmask(&mut td_meas.bit_mask, meas_type);
+        self.set_typedef_byte_order(&mut td_meas.byte_order, meas_type);
         if let DbgDataType::Enum { enumerators, .. } = &meas_type.datatype {
             if td_meas.conversion == "NO_COMPU_METHOD" {
                 td_meas.conversion = meas_type
@@ -1187,6 +1525,7 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
             td_meas.lower_limit,
             td_meas.upper_limit,
             opt_compu_method,
+            self.limit_precision,
         );
         td_meas.lower_limit = ll;
         td_meas.upper_limit = ul;
@@ -1398,6 +1737,33 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
                 }
             }
         }
+
+        // report the changes, if an existing TYPEDEF_STRUCTURE was updated to match a modified struct
+        if !structure_components.is_empty() {
+            for sc in &td_struct.structure_component {
+                if !structure_components
+                    .iter()
+                    .any(|old_sc| old_sc.component_name == sc.component_name)
+                {
+                    self.log_msgs.push(format!(
+                        "adding STRUCTURE_COMPONENT \"{}\" to TYPEDEF_STRUCTURE \"{}\"",
+                        sc.component_name, td_struct.name
+                    ));
+                }
+            }
+            for old_sc in &structure_components {
+                if !td_struct
+                    .structure_component
+                    .iter()
+                    .any(|sc| sc.component_name == old_sc.component_name)
+                {
+                    self.log_msgs.push(format!(
+                        "removing STRUCTURE_COMPONENT \"{}\" from TYPEDEF_STRUCTURE \"{}\"",
+                        old_sc.component_name, td_struct.name
+                    ));
+                }
+            }
+        }
     }
 
     /// update the `SYMBOL_TYPE_LINK` of a `TYPEDEF_STRUCTURE`
@@ -1507,6 +1873,26 @@ impl<'dbg, 'a2l, 'rl, 'log, 'cm> TypedefUpdater<'dbg, 'a2l, 'rl, 'log, 'cm> {
     }
 }
 
+/// find the `TYPEDEF_AXIS` of an instance-relative axis
+///
+/// Inside a `TYPEDEF_STRUCTURE`, the `AXIS_PTS_REF` of a `TYPEDEF_CHARACTERISTIC` may reference another
+/// `STRUCTURE_COMPONENT` of the same structure, whose type is a `TYPEDEF_AXIS`.
+pub(crate) fn get_typedef_axis_for_axis_descr<'a>(
+    module: &'a Module,
+    td_struct: &TypedefStructure,
+    axis_descr: &AxisDescr,
+) -> Option<&'a TypedefAxis> {
+    let axis_pts_ref = axis_descr.axis_pts_ref.as_ref()?;
+    let component = td_struct
+        .structure_component
+        .iter()
+        .find(|sc| sc.component_name == axis_pts_ref.axis_points)?;
+    module
+        .typedef_axis
+        .iter()
+        .find(|td_axis| td_axis.name == component.component_type)
+}
+
 /// take the type name from a `SYMBOL_TYPE_LINK` and try to find a matching type in the `debug_data`
 fn get_typeinfo_from_symbol_link<'dbg>(
     debug_data: &'dbg DebugData,
@@ -1530,7 +1916,7 @@ fn get_typeinfo_from_symbol_link<'dbg>(
         }
     }
 
-    let typeinfo_list = debug_data.typenames.get(symbol_type)?;
+    let typeinfo_list = debug_data.typenames().get(symbol_type)?;
     match typeinfo_list.len() {
         0 => None,
         1 => debug_data.types.get(&typeinfo_list[0]),
@@ -1580,7 +1966,7 @@ fn get_structure_component_typeinfo<'dbg>(
 }
 
 /// is the given typeinfo suitable to use for a `TYPEDEF_STRUCTURE`?
-fn is_structure_typeinfo(typeinfo: &TypeInfo, types: &HashMap<usize, TypeInfo>) -> bool {
+fn is_structure_typeinfo(typeinfo: &TypeInfo, types: &TypeMap) -> bool {
     let typeinfo = typeinfo.get_pointer(types).map_or(typeinfo, |(_, t)| t);
     match &typeinfo.datatype {
         DbgDataType::Pointer(_, offset) => {
@@ -1622,7 +2008,7 @@ fn is_calibration_typeinfo(typeinfo: &TypeInfo) -> bool {
 }
 
 /// is the given typeinfo suitable to use for a `TYPEDEF_MEASUREMENT`?
-fn is_measurement_typeinfo(typeinfo: &TypeInfo, types: &HashMap<usize, TypeInfo>) -> bool {
+fn is_measurement_typeinfo(typeinfo: &TypeInfo, types: &TypeMap) -> bool {
     let typeinfo = typeinfo.get_pointer(types).map_or(typeinfo, |(_, t)| t);
     let typeinfo = typeinfo.get_arraytype().unwrap_or(typeinfo);
     match &typeinfo.datatype {
@@ -1649,7 +2035,7 @@ fn is_measurement_typeinfo(typeinfo: &TypeInfo, types: &HashMap<usize, TypeInfo>
 /// extension to distingush between them?
 /// A qualifier {`CompileUnit`:...} is not needed if all of the types are actually identical.
 fn is_type_discriminant_needed(debug_data: &DebugData, name: &String) -> bool {
-    let type_offsets = debug_data.typenames.get(name).unwrap();
+    let type_offsets = debug_data.typenames().get(name).unwrap();
     if type_offsets.len() < 2 {
         return false;
     }
@@ -1827,7 +2213,9 @@ fn fully_unwrap_typeinfo<'dbg>(
 
 #[cfg(test)]
 mod test {
-    use super::{update_module_typedefs, TypedefUpdater};
+    use super::{
+        create_typedefs_for_types, find_named_type, update_module_typedefs, TypedefUpdater,
+    };
     use crate::{
         debuginfo::{DebugData, TypeInfo},
         update::{get_symbol_info, A2lUpdateInfo, RecordLayoutInfo, TypedefNames, TypedefReferrer},
@@ -1868,6 +2256,7 @@ mod test {
             &mut reclayout,
             HashMap::new(),
             &dummy_cm_index,
+            None,
         );
 
         tdu.typedef_names.structure = HashSet::new();
@@ -1906,6 +2295,7 @@ mod test {
             &mut reclayout,
             HashMap::new(),
             &dummy_cm_index,
+            None,
         );
 
         tdu.typedef_names.structure = HashSet::new();
@@ -1946,6 +2336,7 @@ mod test {
             &mut reclayout,
             HashMap::new(),
             &dummy_cm_index,
+            None,
         );
 
         tdu.typedef_names.structure = HashSet::new();
@@ -1992,7 +2383,7 @@ mod test {
 
         let structb_typeinfo = debug_data
             .types
-            .get(&debug_data.typenames.get("StructB").unwrap()[0])
+            .get(&debug_data.typenames().get("StructB").unwrap()[0])
             .unwrap();
 
         // the typedef_ref_info for INSTANCEs is normally collected by the INSTANCE update function
@@ -2012,6 +2403,7 @@ mod test {
             &mut recordlayout_info,
             typedef_ref_info,
             &dummy_cm_index,
+            None,
         );
 
         tdu.typedef_names.structure = HashSet::new();
@@ -2045,6 +2437,7 @@ mod test {
             &mut recordlayout_info,
             HashMap::new(),
             &dummy_cm_index,
+            None,
         );
         let mut enum_convlist = HashMap::<String, &TypeInfo>::new();
 
@@ -2058,7 +2451,7 @@ mod test {
         // get the typeinfo for StructA
         let typeinfo = debug_data
             .types
-            .get(&debug_data.typenames.get("StructA").unwrap()[0])
+            .get(&debug_data.typenames().get("StructA").unwrap()[0])
             .unwrap();
         // create the TYPEDEF_STRUCTURE for StructA - calibration
         let name = tdu
@@ -2070,7 +2463,7 @@ mod test {
         // get the typeinfo for StructB
         let typeinfo = debug_data
             .types
-            .get(&debug_data.typenames.get("StructB").unwrap()[0])
+            .get(&debug_data.typenames().get("StructB").unwrap()[0])
             .unwrap();
         // create the TYPEDEF_STRUCTURE for StructB - calibration
         let name = tdu
@@ -2120,6 +2513,7 @@ mod test {
             &mut recordlayout_info,
             HashMap::new(),
             &dummy_cm_index,
+            None,
         );
         let mut enum_convlist = HashMap::<String, &TypeInfo>::new();
 
@@ -2144,6 +2538,67 @@ mod test {
         assert_eq!(tdu.module.typedef_blob.len(), 1);
     }
 
+    #[test]
+    fn test_create_typedefs_for_types() {
+        let elf_name = OsString::from("fixtures/bin/typedef_only_test.elf");
+        // types that are not used by any variable are only loaded on request
+        let debug_data = crate::debuginfo::DebugData::load_dwarf(&elf_name, false).unwrap();
+        assert!(!debug_data.typenames().contains_key("Component_Params"));
+        let named_types: HashSet<String> = [
+            "Component_Params",
+            "Component_State",
+            "Ambiguous_Type",
+            "Missing_Type",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        let debug_data = crate::debuginfo::DebugData::load_dwarf_with_options(
+            &elf_name,
+            false,
+            None,
+            &named_types,
+        )
+        .unwrap();
+
+        let params_type = find_named_type(&debug_data, "Component_Params").unwrap();
+        let state_type = find_named_type(&debug_data, "Component_State").unwrap();
+        let errmsg = find_named_type(&debug_data, "Missing_Type").unwrap_err();
+        assert!(errmsg.contains("not found"));
+        // the two different definitions of Ambiguous_Type are reported with their compile units
+        let errmsg = find_named_type(&debug_data, "Ambiguous_Type").unwrap_err();
+        assert!(errmsg.contains("ambiguous"));
+        assert!(errmsg.contains("typedef_only_test.c"));
+        assert!(errmsg.contains("typedef_only_test_b.c"));
+
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        create_typedefs_for_types(
+            &mut a2l.project.module[0],
+            &debug_data,
+            &mut log_msgs,
+            &[(params_type, true), (state_type, false)],
+        );
+        let module = &a2l.project.module[0];
+        assert!(module.instance.is_empty());
+        assert_eq!(module.typedef_structure.len(), 2);
+        let td_params = module
+            .typedef_structure
+            .iter()
+            .find(|td_struct| td_struct.name == "Component_Params")
+            .unwrap();
+        assert_eq!(td_params.structure_component.len(), 3);
+        let td_state = module
+            .typedef_structure
+            .iter()
+            .find(|td_struct| td_struct.name == "Component_State")
+            .unwrap();
+        assert_eq!(td_state.structure_component.len(), 2);
+        // the members of Component_Params are calibration objects, those of Component_State are measurements
+        assert!(!module.typedef_characteristic.is_empty());
+        assert!(!module.typedef_measurement.is_empty());
+    }
+
     #[test]
     fn test_update() {
         let (mut a2l, debug_data, names, mut reclayout) = test_setup(
@@ -2178,6 +2633,24 @@ mod test {
             version,
             enable_structures: true,
             compu_method_index: HashMap::new(),
+            limit_precision: None,
+            max_errors: None,
+            error_count: std::cell::Cell::new(0),
+            fix_layouts: false,
+            typedefs_only: false,
+            prefer_ifdata_symbol: false,
+            symbol_warnings: std::cell::RefCell::new(Vec::new()),
+            deadline: None,
+            number_to_matrix_dim: false,
+            fix_bitmasks: false,
+            change_msgs: std::cell::RefCell::new(Vec::new()),
+            update_scope: None,
+            removed_names: std::cell::RefCell::new(HashSet::new()),
+            report_moved: None,
+            moved_items: std::cell::RefCell::new(Vec::new()),
+            no_local_symbols: false,
+            fix_integer_formats: false,
+            max_candidates: crate::symbol::MAX_LISTED_CANDIDATES,
         };
         update_module_typedefs(
             &info,
@@ -2203,4 +2676,244 @@ mod test {
 
         assert_eq!(a2l, reference_a2l);
     }
+
+    #[test]
+    fn test_update_changed_struct() {
+        let dbg_v1 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v1.elf"),
+            false,
+        )
+        .unwrap();
+        let dbg_v2 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v2.elf"),
+            false,
+        )
+        .unwrap();
+
+        // create an INSTANCE and its TYPEDEF_STRUCTURE based on the first version of the struct
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        crate::insert::insert_items(
+            &mut a2l,
+            &dbg_v1,
+            vec![],
+            vec!["changing_struct"],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &crate::insert::NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let get_components = |a2l: &A2lFile| {
+            let module = &a2l.project.module[0];
+            let td_struct = module
+                .typedef_structure
+                .iter()
+                .find(|td| td.name == module.instance[0].type_ref)
+                .unwrap();
+            let components: Vec<(String, u32)> = td_struct
+                .structure_component
+                .iter()
+                .map(|sc| (sc.component_name.clone(), sc.address_offset))
+                .collect();
+            (components, td_struct.total_size)
+        };
+        let (components, total_size) = get_components(&a2l);
+        assert_eq!(
+            components,
+            vec![
+                ("first".to_string(), 0),
+                ("removed".to_string(), 4),
+                ("inner".to_string(), 6),
+                ("last".to_string(), 12)
+            ]
+        );
+        assert_eq!(total_size, 16);
+
+        // in the second version, the member "removed" is gone and the member "added" is new
+        let mut log_msgs = Vec::new();
+        crate::update::update_a2l(
+            &mut a2l,
+            &dbg_v2,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Full,
+                crate::update::UpdateMode::Default,
+                true,
+            ),
+        );
+        let (components, total_size) = get_components(&a2l);
+        assert_eq!(
+            components,
+            vec![
+                ("first".to_string(), 0),
+                ("inner".to_string(), 4),
+                ("added".to_string(), 8),
+                ("last".to_string(), 16)
+            ]
+        );
+        assert_eq!(total_size, 24);
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.starts_with("removing STRUCTURE_COMPONENT \"removed\"")));
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.starts_with("adding STRUCTURE_COMPONENT \"added\"")));
+
+        // updating back to the first version restores the original layout
+        let mut log_msgs = Vec::new();
+        crate::update::update_a2l(
+            &mut a2l,
+            &dbg_v1,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Full,
+                crate::update::UpdateMode::Default,
+                true,
+            ),
+        );
+        let (components, total_size) = get_components(&a2l);
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[1], ("removed".to_string(), 4));
+        assert_eq!(total_size, 16);
+    }
+
+    #[test]
+    fn test_update_offsets_address_only() {
+        let dbg_v1 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v1.elf"),
+            false,
+        )
+        .unwrap();
+        let dbg_v2 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v2.elf"),
+            false,
+        )
+        .unwrap();
+
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        crate::insert::insert_items(
+            &mut a2l,
+            &dbg_v1,
+            vec![],
+            vec!["changing_struct"],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &crate::insert::NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let typedef_count = a2l.project.module[0].typedef_structure.len();
+
+        // an address-only update moves the members, but does not add or remove STRUCTURE_COMPONENTs
+        let mut log_msgs = Vec::new();
+        crate::update::update_a2l(
+            &mut a2l,
+            &dbg_v2,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Addresses,
+                crate::update::UpdateMode::Default,
+                true,
+            ),
+        );
+        let module = &a2l.project.module[0];
+        assert_eq!(module.typedef_structure.len(), typedef_count);
+        let td_struct = module
+            .typedef_structure
+            .iter()
+            .find(|td| td.name == module.instance[0].type_ref)
+            .unwrap();
+        let components: Vec<(&str, u32)> = td_struct
+            .structure_component
+            .iter()
+            .map(|sc| (sc.component_name.as_str(), sc.address_offset))
+            .collect();
+        // "removed" no longer exists, so it keeps its offset
+        assert_eq!(
+            components,
+            vec![("first", 0), ("removed", 4), ("inner", 4), ("last", 16)]
+        );
+        assert!(log_msgs.iter().any(|msg| msg
+            .starts_with("Warning: STRUCTURE_COMPONENT \"removed\"")
+            && msg.contains("does not match any member")));
+        assert!(log_msgs
+            .iter()
+            .any(|msg| msg.contains("\"last\"") && msg.contains("changed from 12 to 16")));
+    }
+
+    #[test]
+    fn test_update_typedefs_only() {
+        let dbg_v1 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v1.elf"),
+            false,
+        )
+        .unwrap();
+        let dbg_v2 = DebugData::load_dwarf(
+            &OsString::from("fixtures/bin/struct_change_test_v2.elf"),
+            false,
+        )
+        .unwrap();
+
+        // create an INSTANCE for the struct and a MEASUREMENT for one of its members using the first version
+        let mut a2l = a2lfile::new();
+        let mut log_msgs = Vec::new();
+        crate::insert::insert_items(
+            &mut a2l,
+            &dbg_v1,
+            vec!["changing_struct.last"],
+            vec!["changing_struct"],
+            None,
+            &mut log_msgs,
+            true,
+            None,
+            None,
+            None,
+            None,
+            &crate::insert::NameAffix::default(),
+            crate::symbol::MAX_LISTED_CANDIDATES,
+        );
+        let measurement_before = a2l.project.module[0].measurement[0].clone();
+        assert_eq!(a2l.project.module[0].instance.len(), 1);
+
+        // update only the typedefs with the second version
+        let mut log_msgs = Vec::new();
+        let (summary, _) = crate::update::update_a2l(
+            &mut a2l,
+            &dbg_v2,
+            &mut log_msgs,
+            &crate::update::UpdateOptions::new(
+                crate::update::UpdateType::Typedefs,
+                crate::update::UpdateMode::Default,
+                false,
+            ),
+        );
+        assert_eq!(summary.measurement_updated, 0);
+        assert_eq!(summary.instance_updated, 1);
+
+        // the TYPEDEF_STRUCTURE was updated, even though enable_structures was not set
+        let module = &a2l.project.module[0];
+        let td_struct = module
+            .typedef_structure
+            .iter()
+            .find(|td| td.name == module.instance[0].type_ref)
+            .unwrap();
+        assert_eq!(td_struct.total_size, 24);
+        assert!(td_struct
+            .structure_component
+            .iter()
+            .any(|sc| sc.component_name == "added"));
+
+        // the MEASUREMENT still has the address of the first version
+        assert_eq!(module.measurement[0], measurement_before);
+    }
 }
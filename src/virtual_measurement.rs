@@ -0,0 +1,329 @@
+use a2lfile::{
+    A2lFile, A2lObject, CompuMethod, ConversionType, DataType, Formula, FormulaInv, Measurement,
+    Module, Virtual,
+};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+
+// A virtual MEASUREMENT, as described by one line of the definition file
+#[derive(Debug, PartialEq)]
+pub(crate) struct VirtualMeasurementDef {
+    pub(crate) name: String,
+    pub(crate) formula: String,
+    pub(crate) formula_inv: Option<String>,
+    pub(crate) inputs: Vec<String>,
+    pub(crate) unit: String,
+    pub(crate) lower_limit: f64,
+    pub(crate) upper_limit: f64,
+}
+
+// Load the definitions of virtual MEASUREMENTs from a file.
+//
+// Each non-empty line that does not start with '#' defines one virtual MEASUREMENT:
+//   <name>; <formula>; <input measurements>; <unit>; <lower limit>; <upper limit> [; <inverse formula>]
+// The input measurements are separated by commas. In the formula, they are referenced as X1, X2, ...
+// in the order in which they are listed, e.g.
+//   Power; X1 * X2 / 9549; EngineTorque, EngineSpeed; kW; 0; 500
+pub(crate) fn load_definitions(filename: &OsStr) -> Result<Vec<VirtualMeasurementDef>, String> {
+    let text = std::fs::read_to_string(filename).map_err(|err| {
+        format!(
+            "Failed to read the virtual measurement definitions from {}: {err}",
+            filename.to_string_lossy()
+        )
+    })?;
+    parse_definitions(&text).map_err(|err| format!("{}: {err}", filename.to_string_lossy()))
+}
+
+fn parse_definitions(text: &str) -> Result<Vec<VirtualMeasurementDef>, String> {
+    let mut definitions: Vec<VirtualMeasurementDef> = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        if fields.len() != 6 && fields.len() != 7 {
+            return Err(format!(
+                "line {line_num}: expected 6 or 7 fields separated by ';', found {}",
+                fields.len()
+            ));
+        }
+
+        let name = fields[0];
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.[]".contains(c))
+        {
+            return Err(format!("line {line_num}: \"{name}\" is not a valid name"));
+        }
+        if definitions.iter().any(|def| def.name == name) {
+            return Err(format!(
+                "line {line_num}: the virtual measurement {name} is defined twice"
+            ));
+        }
+        if fields[1].is_empty() {
+            return Err(format!("line {line_num}: the formula of {name} is empty"));
+        }
+        let inputs: Vec<String> = fields[2]
+            .split(',')
+            .map(str::trim)
+            .filter(|input| !input.is_empty())
+            .map(String::from)
+            .collect();
+        if inputs.is_empty() {
+            return Err(format!(
+                "line {line_num}: the virtual measurement {name} has no input measurements"
+            ));
+        }
+        let lower_limit = parse_limit(fields[4], line_num)?;
+        let upper_limit = parse_limit(fields[5], line_num)?;
+        if lower_limit > upper_limit {
+            return Err(format!(
+                "line {line_num}: the lower limit {lower_limit} of {name} is greater than the upper limit {upper_limit}"
+            ));
+        }
+        let formula_inv = fields
+            .get(6)
+            .filter(|formula_inv| !formula_inv.is_empty())
+            .map(|formula_inv| formula_inv.to_string());
+
+        definitions.push(VirtualMeasurementDef {
+            name: name.to_string(),
+            formula: fields[1].to_string(),
+            formula_inv,
+            inputs,
+            unit: fields[3].to_string(),
+            lower_limit,
+            upper_limit,
+        });
+    }
+
+    Ok(definitions)
+}
+
+fn parse_limit(text: &str, line_num: usize) -> Result<f64, String> {
+    text.parse::<f64>()
+        .map_err(|_| format!("line {line_num}: \"{text}\" is not a valid limit"))
+}
+
+// Create a virtual MEASUREMENT and its COMPU_METHOD of type FORM for each definition.
+// Virtual MEASUREMENTs that already exist are updated instead.
+// All input measurements must exist in the module, or be defined as virtual MEASUREMENTs themselves.
+// Returns the number of virtual MEASUREMENTs that were created or updated.
+pub(crate) fn create_virtual_measurements(
+    a2l_file: &mut A2lFile,
+    definitions: &[VirtualMeasurementDef],
+    log_msgs: &mut Vec<String>,
+) -> Result<usize, String> {
+    let module = &mut a2l_file.project.module[0];
+
+    // check all definitions before modifying anything
+    let known_names: HashSet<&str> = module
+        .measurement
+        .iter()
+        .map(|meas| meas.name.as_str())
+        .chain(definitions.iter().map(|def| def.name.as_str()))
+        .collect();
+    let mut errors = Vec::new();
+    for def in definitions {
+        for input in &def.inputs {
+            if *input == def.name {
+                errors.push(format!(
+                    "the virtual measurement {} cannot use itself as an input",
+                    def.name
+                ));
+            } else if !known_names.contains(input.as_str()) {
+                errors.push(format!(
+                    "the input measurement {input} of the virtual measurement {} does not exist",
+                    def.name
+                ));
+            }
+        }
+        if let Some(existing) = module.measurement.iter().find(|meas| meas.name == def.name) {
+            if existing.var_virtual.is_none() {
+                errors.push(format!(
+                    "MEASUREMENT {} on line {} already exists and is not virtual",
+                    def.name,
+                    existing.get_line()
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "Could not create the virtual measurements:\n  {}",
+            errors.join("\n  ")
+        ));
+    }
+
+    for def in definitions {
+        let compu_method_name = create_formula_compu_method(module, def);
+        let mut virtual_list = Virtual::new();
+        virtual_list.measuring_channel_list = def.inputs.clone();
+
+        if let Some(measurement) = module
+            .measurement
+            .iter_mut()
+            .find(|meas| meas.name == def.name)
+        {
+            measurement.conversion = compu_method_name;
+            measurement.lower_limit = def.lower_limit;
+            measurement.upper_limit = def.upper_limit;
+            measurement.var_virtual = Some(virtual_list);
+            log_msgs.push(format!(
+                "Updated virtual MEASUREMENT {} on line {}",
+                def.name,
+                measurement.get_line()
+            ));
+        } else {
+            let mut measurement = Measurement::new(
+                def.name.clone(),
+                format!("virtual measurement {}", def.name),
+                DataType::Float32Ieee,
+                compu_method_name,
+                0,
+                0f64,
+                def.lower_limit,
+                def.upper_limit,
+            );
+            measurement.var_virtual = Some(virtual_list);
+            module.measurement.push(measurement);
+            log_msgs.push(format!("Created virtual MEASUREMENT {}", def.name));
+        }
+    }
+
+    Ok(definitions.len())
+}
+
+// create or update the COMPU_METHOD of type FORM which calculates the value of a virtual MEASUREMENT
+fn create_formula_compu_method(module: &mut Module, def: &VirtualMeasurementDef) -> String {
+    let compu_method_name = format!("{}_Formula", def.name);
+    let mut formula = Formula::new(def.formula.clone());
+    formula.formula_inv = def.formula_inv.clone().map(FormulaInv::new);
+
+    if let Some(compu_method) = module
+        .compu_method
+        .iter_mut()
+        .find(|cm| cm.name == compu_method_name)
+    {
+        compu_method.conversion_type = ConversionType::Form;
+        compu_method.unit.clone_from(&def.unit);
+        compu_method.formula = Some(formula);
+    } else {
+        let mut compu_method = CompuMethod::new(
+            compu_method_name.clone(),
+            format!("Formula for the virtual measurement {}", def.name),
+            ConversionType::Form,
+            "%.4".to_string(),
+            def.unit.clone(),
+        );
+        compu_method.formula = Some(formula);
+        module.compu_method.push(compu_method);
+    }
+
+    compu_method_name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static DEFINITIONS: &str = r#"
+# name; formula; inputs; unit; lower limit; upper limit; inverse formula
+Power; X1 * X2 / 9549; Measurement_Value, Measurement_Enum; kW; 0; 500
+DoublePower; X1 * 2; Power; kW; 0; 1000; X1 / 2
+"#;
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions[0].name, "Power");
+        assert_eq!(definitions[0].formula, "X1 * X2 / 9549");
+        assert_eq!(
+            definitions[0].inputs,
+            vec!["Measurement_Value", "Measurement_Enum"]
+        );
+        assert_eq!(definitions[0].unit, "kW");
+        assert_eq!(definitions[0].upper_limit, 500.0);
+        assert!(definitions[0].formula_inv.is_none());
+        assert_eq!(definitions[1].formula_inv.as_deref(), Some("X1 / 2"));
+
+        assert!(parse_definitions("Power; X1; Input; kW; 0").is_err());
+        assert!(parse_definitions("Power; X1; Input; kW; 0; abc").is_err());
+        assert!(parse_definitions("Power; X1; ; kW; 0; 1").is_err());
+        assert!(parse_definitions("Power; X1; A; kW; 10; 1").is_err());
+        assert!(parse_definitions("P; X1; A; kW; 0; 1\nP; X1; A; kW; 0; 1").is_err());
+    }
+
+    #[test]
+    fn test_create_virtual_measurements() {
+        let mut a2l_file = a2lfile::load(
+            "fixtures/a2l/update_test1.a2l",
+            None,
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+        let measurement_count = a2l_file.project.module[0].measurement.len();
+        let definitions = parse_definitions(DEFINITIONS).unwrap();
+
+        let mut log_msgs = Vec::new();
+        let count =
+            create_virtual_measurements(&mut a2l_file, &definitions, &mut log_msgs).unwrap();
+        assert_eq!(count, 2);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement.len(), measurement_count + 2);
+        let power = module
+            .measurement
+            .iter()
+            .find(|meas| meas.name == "Power")
+            .unwrap();
+        assert!(power.ecu_address.is_none());
+        assert_eq!(
+            power.var_virtual.as_ref().unwrap().measuring_channel_list,
+            vec!["Measurement_Value", "Measurement_Enum"]
+        );
+        let compu_method = module
+            .compu_method
+            .iter()
+            .find(|cm| cm.name == power.conversion)
+            .unwrap();
+        assert_eq!(compu_method.conversion_type, ConversionType::Form);
+        assert_eq!(compu_method.unit, "kW");
+        assert_eq!(compu_method.formula.as_ref().unwrap().fx, "X1 * X2 / 9549");
+
+        // running the same definitions again updates the existing virtual measurements
+        let mut definitions = definitions;
+        definitions[0].formula = "X1 * X2 / 9550".to_string();
+        let compu_method_count = module.compu_method.len();
+        let count =
+            create_virtual_measurements(&mut a2l_file, &definitions, &mut log_msgs).unwrap();
+        assert_eq!(count, 2);
+        let module = &a2l_file.project.module[0];
+        assert_eq!(module.measurement.len(), measurement_count + 2);
+        assert_eq!(module.compu_method.len(), compu_method_count);
+        let compu_method = module
+            .compu_method
+            .iter()
+            .find(|cm| cm.name == "Power_Formula")
+            .unwrap();
+        assert_eq!(compu_method.formula.as_ref().unwrap().fx, "X1 * X2 / 9550");
+
+        // inputs must exist, and existing non-virtual MEASUREMENTs can't be replaced
+        let definitions = parse_definitions(
+            "Bad; X1; Does_Not_Exist; -; 0; 1\nMeasurement_Value; X1; Power; -; 0; 1",
+        )
+        .unwrap();
+        let result = create_virtual_measurements(&mut a2l_file, &definitions, &mut log_msgs);
+        let errmsg = result.unwrap_err();
+        assert!(errmsg.contains("Does_Not_Exist"));
+        assert!(errmsg.contains("is not virtual"));
+        assert_eq!(
+            a2l_file.project.module[0].measurement.len(),
+            measurement_count + 2
+        );
+    }
+}
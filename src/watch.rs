@@ -0,0 +1,233 @@
+use a2lfile::A2lFile;
+use notify::{Event, RecursiveMode, Watcher};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+// Changes that follow each other within this time are combined, so that the operations are not
+// run while the linker is still writing the elf file.
+const DEBOUNCE_TIME: Duration = Duration::from_millis(500);
+
+pub(crate) enum WatchEvent {
+    // one of the watched files was created or modified
+    Changed(PathBuf),
+    // Ctrl-C was pressed
+    Stop,
+}
+
+// Run the operations once, and then again every time one of the watched files is changed.
+// Watching only stops when Ctrl-C is pressed; errors during a run are reported and don't end the watch.
+pub(crate) fn watch_files<F>(filenames: &[&OsStr], run: F) -> Result<(), String>
+where
+    F: FnMut() -> Result<(), String>,
+{
+    let files = filenames
+        .iter()
+        .map(|filename| get_absolute_path(filename))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (sender, receiver) = mpsc::channel();
+
+    let stop_sender = sender.clone();
+    ctrlc::set_handler(move || {
+        let _ = stop_sender.send(WatchEvent::Stop);
+    })
+    .map_err(|err| format!("Error: failed to set up the Ctrl-C handler: {err}"))?;
+
+    // The directories are watched instead of the files, because the linker might delete the
+    // elf file and then create a new one. A watch on the file itself would end at that point.
+    let watched_files = files.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result {
+            if event.kind.is_create() || event.kind.is_modify() {
+                for path in event.paths {
+                    if watched_files.contains(&path) {
+                        let _ = sender.send(WatchEvent::Changed(path));
+                    }
+                }
+            }
+        }
+    })
+    .map_err(|err| format!("Error: failed to watch for file changes: {err}"))?;
+    let mut directories: Vec<&Path> = files.iter().filter_map(|file| file.parent()).collect();
+    directories.sort();
+    directories.dedup();
+    for dir in directories {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|err| format!("Error: failed to watch \"{}\": {err}", dir.display()))?;
+    }
+
+    log_println!("Watching for changes, press Ctrl-C to stop");
+    let run_count = watch_loop(&receiver, &files, run);
+    log_println!("Watch stopped after {run_count} runs");
+
+    Ok(())
+}
+
+// Perform the initial run, and then one run after each (debounced) change.
+// Returns the number of runs.
+fn watch_loop<F>(receiver: &Receiver<WatchEvent>, files: &[PathBuf], mut run: F) -> u32
+where
+    F: FnMut() -> Result<(), String>,
+{
+    let mut run_count = 1;
+    timed_run(run_count, "initial run", &mut run);
+
+    // the loop ends when Ctrl-C was pressed, or when the watcher was shut down
+    while let Ok(WatchEvent::Changed(mut changed_file)) = receiver.recv() {
+        // wait until there have been no more changes for some time
+        loop {
+            match receiver.recv_timeout(DEBOUNCE_TIME) {
+                Ok(WatchEvent::Changed(path)) => changed_file = path,
+                Ok(WatchEvent::Stop) | Err(RecvTimeoutError::Disconnected) => return run_count,
+                Err(RecvTimeoutError::Timeout) => {
+                    // a file which was deleted and is being re-created can't be used yet
+                    if files.iter().all(|file| file.exists()) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        run_count += 1;
+        let reason = changed_file.file_name().map_or_else(
+            || changed_file.to_string_lossy().to_string(),
+            |name| format!("{} changed", name.to_string_lossy()),
+        );
+        timed_run(run_count, &reason, &mut run);
+    }
+
+    run_count
+}
+
+// run the operations and print a one-line summary
+fn timed_run<F>(run_count: u32, reason: &str, run: &mut F)
+where
+    F: FnMut() -> Result<(), String>,
+{
+    let start = Instant::now();
+    match run() {
+        Ok(()) => log_println!(
+            "[{run_count}] {reason}: run complete in {:.2}s",
+            start.elapsed().as_secs_f64()
+        ),
+        Err(errmsg) => log_println!(
+            "[{run_count}] {reason}: run failed after {:.2}s: {errmsg}",
+            start.elapsed().as_secs_f64()
+        ),
+    }
+}
+
+// The paths in the file change events are based on the watched directories, so the file names
+// must be built the same way in order to be comparable.
+fn get_absolute_path(filename: &OsStr) -> Result<PathBuf, String> {
+    let path = Path::new(filename);
+    let Some(file_name) = path.file_name() else {
+        return Err(format!(
+            "Error: \"{}\" is not a file name",
+            filename.to_string_lossy()
+        ));
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let dir = dir
+        .canonicalize()
+        .map_err(|err| format!("Error: could not access \"{}\": {err}", dir.display()))?;
+    Ok(dir.join(file_name))
+}
+
+// true if both file names refer to the same file, e.g. "dir/file.a2l" and "./dir/../dir/file.a2l"
+// The second file might not exist yet, so only its directory can be canonicalized in that case.
+pub(crate) fn is_same_file(filename1: &OsStr, filename2: &OsStr) -> bool {
+    let resolve = |filename: &OsStr| {
+        Path::new(filename)
+            .canonicalize()
+            .ok()
+            .or_else(|| get_absolute_path(filename).ok())
+    };
+    match (resolve(filename1), resolve(filename2)) {
+        (Some(path1), Some(path2)) => path1 == path2,
+        _ => filename1 == filename2,
+    }
+}
+
+// Write the output to a temporary file first, and then rename it to the output file name.
+// This makes sure that other programs never read a half-written a2l file.
+pub(crate) fn write_atomically(
+    a2l_file: &A2lFile,
+    filename: &OsStr,
+    banner: &str,
+) -> Result<(), String> {
+    let mut tmp_filename = filename.to_os_string();
+    tmp_filename.push(".tmp");
+    a2l_file
+        .write(&tmp_filename, Some(banner))
+        .map_err(|err| err.to_string())?;
+    std::fs::rename(&tmp_filename, filename).map_err(|err| {
+        let _ = std::fs::remove_file(&tmp_filename);
+        format!(
+            "Error: could not replace \"{}\": {err}",
+            filename.to_string_lossy()
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_watch_loop() {
+        let files = vec![get_absolute_path(OsStr::new("fixtures/bin/update_test.elf")).unwrap()];
+        let (sender, receiver) = mpsc::channel();
+        let changed_file = files[0].clone();
+        let sender_thread = std::thread::spawn(move || {
+            // several changes in quick succession only cause a single run
+            for _ in 0..3 {
+                sender
+                    .send(WatchEvent::Changed(changed_file.clone()))
+                    .unwrap();
+            }
+            std::thread::sleep(DEBOUNCE_TIME * 3);
+            sender.send(WatchEvent::Stop).unwrap();
+        });
+
+        let mut call_count = 0;
+        let run_count = watch_loop(&receiver, &files, || {
+            call_count += 1;
+            // errors are reported, but they don't stop the watch
+            if call_count == 1 {
+                Err("Error: failed".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        sender_thread.join().unwrap();
+        assert_eq!(run_count, 2);
+        assert_eq!(call_count, 2);
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let outfile = tempdir.path().join("output.a2l");
+        let a2l_file = a2lfile::new();
+        write_atomically(&a2l_file, outfile.as_os_str(), "banner").unwrap();
+        assert!(outfile.exists());
+        // the temporary file is gone after the rename
+        assert!(!tempdir.path().join("output.a2l.tmp").exists());
+        let mut log_msgs = Vec::new();
+        assert!(a2lfile::load(&outfile, None, &mut log_msgs, false).is_ok());
+
+        assert_eq!(
+            get_absolute_path(OsStr::new("fixtures/bin/update_test.elf")).unwrap(),
+            Path::new("fixtures/bin")
+                .canonicalize()
+                .unwrap()
+                .join("update_test.elf")
+        );
+    }
+}
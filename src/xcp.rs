@@ -1,27 +1,41 @@
 use std::ffi::OsStr;
+use std::fmt::Write;
 
 use crate::ifdata::{
-    A2mlVector, Address2, CAN_Parameters, Channel, Cmd, CycleRepetition, Daq2, EvServ,
+    A2mlVector, Address2, CAN_Parameters, Channel, Cmd, CycleRepetition, Daq, Daq2, EvServ,
     FLX_Parameters, FlxSlotId, HostName, InitialCmdBuffer, InitialResErrBuffer, Ipv6, LpduId,
-    MaxFlxLenBuf, Offset, PoolBuffer, ResErr, Stim2, TCP_IP_Parameters, UDP_IP_Parameters, XCPplus,
-    Xcp, XcpPacket,
+    MaxFlxLenBuf, Offset, Pag, Pgm, PoolBuffer, ResErr, Segment, Stim2, TCP_IP_Parameters,
+    UDP_IP_Parameters, XCPplus, Xcp, XcpPacket,
 };
-use a2lfile::{A2lFile, A2lObject};
+use a2lfile::{A2lFile, A2lObject, Module};
 
-pub(crate) fn show_settings(a2l_file: &A2lFile, filename: &OsStr) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShowXcpFormat {
+    Text,
+    Json,
+}
+
+pub(crate) fn show_settings(a2l_file: &A2lFile, filename: &OsStr, format: ShowXcpFormat) {
+    match format {
+        ShowXcpFormat::Text => show_settings_text(a2l_file, filename),
+        ShowXcpFormat::Json => show_settings_json(a2l_file, filename),
+    }
+}
+
+fn show_settings_text(a2l_file: &A2lFile, filename: &OsStr) {
     let multi_module = a2l_file.project.module.len() > 1;
 
-    println!("XCP settings in {}:", filename.to_string_lossy());
+    log_println!("XCP settings in {}:", filename.to_string_lossy());
 
     for module in &a2l_file.project.module {
         if multi_module {
-            println!("XCP settings for module {}", module.name);
+            log_println!("XCP settings for module {}", module.name);
         }
 
         let mut found = false;
         for ifdata in &module.if_data {
             if !ifdata.ifdata_valid {
-                println!(
+                log_println!(
                     "Warning: the IF_DATA block on line {} is not valid",
                     ifdata.get_layout().line
                 );
@@ -38,11 +52,384 @@ pub(crate) fn show_settings(a2l_file: &A2lFile, filename: &OsStr) {
             }
         }
 
-        if !found {
-            println!("No XCP settings found in module {}", module.name);
+        if found {
+            print_memory_segments(module);
+        } else {
+            log_println!("No XCP settings found in module {}", module.name);
+        }
+    }
+    log_println!();
+}
+
+fn show_settings_json(a2l_file: &A2lFile, filename: &OsStr) {
+    let mut modules = Vec::new();
+    for module in &a2l_file.project.module {
+        let mut xcp_settings = Vec::new();
+        for ifdata in &module.if_data {
+            if !ifdata.ifdata_valid {
+                // warnings go to stderr, so that stdout only contains valid json
+                eprintln!(
+                    "Warning: the IF_DATA block on line {} is not valid",
+                    ifdata.get_layout().line
+                );
+            }
+            if let Some(decoded_ifdata) = A2mlVector::load_from_ifdata(ifdata) {
+                if let Some(xcp) = &decoded_ifdata.xcp {
+                    xcp_settings.push(xcp_to_json(xcp));
+                }
+                if let Some(xcpplus) = &decoded_ifdata.xcpplus {
+                    xcp_settings.push(xcpplus_to_json(xcpplus));
+                }
+            }
+        }
+
+        let memory_segments = get_xcp_memory_segments(module)
+            .into_iter()
+            .map(|(name, address, size, segment)| {
+                JsonValue::Object(vec![
+                    ("name", JsonValue::String(name.to_string())),
+                    ("address", JsonValue::Number(u64::from(address))),
+                    ("size", JsonValue::Number(u64::from(size))),
+                    (
+                        "segment_number",
+                        JsonValue::Number(u64::from(segment.segment_number)),
+                    ),
+                    ("num_pages", JsonValue::Number(u64::from(segment.num_pages))),
+                    (
+                        "address_extension",
+                        JsonValue::Number(u64::from(segment.address_extension)),
+                    ),
+                ])
+            })
+            .collect();
+
+        modules.push(JsonValue::Object(vec![
+            ("name", JsonValue::String(module.name.clone())),
+            ("xcp", JsonValue::Array(xcp_settings)),
+            ("memory_segments", JsonValue::Array(memory_segments)),
+        ]));
+    }
+
+    let output = JsonValue::Object(vec![
+        (
+            "file",
+            JsonValue::String(filename.to_string_lossy().to_string()),
+        ),
+        ("modules", JsonValue::Array(modules)),
+    ]);
+    let mut outstr = String::new();
+    output.write(&mut outstr, 0);
+    log_println!("{outstr}");
+}
+
+fn xcp_to_json(xcp: &Xcp) -> JsonValue {
+    let mut transport_layers = Vec::new();
+    if let Some(xcp_on_can) = &xcp.xcp_on_can {
+        transport_layers.push(can_to_json(&xcp_on_can.can_parameters));
+    }
+    if let Some(xcp_on_flx) = &xcp.xcp_on_flx {
+        transport_layers.push(flx_to_json(&xcp_on_flx.flx_parameters));
+    }
+    if let Some(xcp_on_tcp_ip) = &xcp.xcp_on_tcp_ip {
+        let params = &xcp_on_tcp_ip.tcp_ip_parameters;
+        transport_layers.push(ip_to_json(
+            "TCP_IP",
+            &params.host_name,
+            &params.address,
+            &params.ipv6,
+            params.port,
+        ));
+    }
+    if let Some(xcp_on_udp_ip) = &xcp.xcp_on_udp_ip {
+        let params = &xcp_on_udp_ip.udp_ip_parameters;
+        transport_layers.push(ip_to_json(
+            "UDP_IP",
+            &params.host_name,
+            &params.address,
+            &params.ipv6,
+            params.port,
+        ));
+    }
+
+    let mut items = vec![
+        ("type", JsonValue::String("XCP".to_string())),
+        ("transport_layers", JsonValue::Array(transport_layers)),
+    ];
+    common_parameters_to_json(&mut items, &xcp.daq, &xcp.pag, &xcp.pgm);
+    JsonValue::Object(items)
+}
+
+fn xcpplus_to_json(xcpplus: &XCPplus) -> JsonValue {
+    let mut transport_layers = Vec::new();
+    for xcp_on_can in &xcpplus.xcp_on_can {
+        transport_layers.push(can_to_json(&xcp_on_can.can_parameters));
+    }
+    for xcp_on_flx in &xcpplus.xcp_on_flx {
+        transport_layers.push(flx_to_json(&xcp_on_flx.flx_parameters));
+    }
+    for xcp_on_tcp_ip in &xcpplus.xcp_on_tcp_ip {
+        let params = &xcp_on_tcp_ip.tcp_ip_parameters;
+        transport_layers.push(ip_to_json(
+            "TCP_IP",
+            &params.host_name,
+            &params.address,
+            &params.ipv6,
+            params.port,
+        ));
+    }
+    for xcp_on_udp_ip in &xcpplus.xcp_on_udp_ip {
+        let params = &xcp_on_udp_ip.udp_ip_parameters;
+        transport_layers.push(ip_to_json(
+            "UDP_IP",
+            &params.host_name,
+            &params.address,
+            &params.ipv6,
+            params.port,
+        ));
+    }
+
+    let mut items = vec![
+        ("type", JsonValue::String("XCPplus".to_string())),
+        (
+            "version",
+            JsonValue::Number(u64::from(xcpplus.xcpplus_version)),
+        ),
+        ("transport_layers", JsonValue::Array(transport_layers)),
+    ];
+    common_parameters_to_json(&mut items, &xcpplus.daq, &xcpplus.pag, &xcpplus.pgm);
+    JsonValue::Object(items)
+}
+
+fn can_to_json(can_parameters: &CAN_Parameters) -> JsonValue {
+    let mut items = vec![("type", JsonValue::String("CAN".to_string()))];
+    if let Some(can_id_master) = &can_parameters.can_id_master {
+        items.push((
+            "can_id_master",
+            JsonValue::Number(u64::from(can_id_master.value & 0x1fff_ffff)),
+        ));
+    }
+    if let Some(can_id_slave) = &can_parameters.can_id_slave {
+        items.push((
+            "can_id_slave",
+            JsonValue::Number(u64::from(can_id_slave.value & 0x1fff_ffff)),
+        ));
+    }
+    if let Some(baudrate) = &can_parameters.baudrate {
+        items.push(("baudrate", JsonValue::Number(u64::from(baudrate.value))));
+    }
+    if let Some(can_fd) = &can_parameters.can_fd {
+        let mut fd_items = Vec::new();
+        if let Some(baudrate) = &can_fd.can_fd_data_transfer_baudrate {
+            fd_items.push((
+                "data_baudrate",
+                JsonValue::Number(u64::from(baudrate.value)),
+            ));
+        }
+        if let Some(max_dlc) = &can_fd.max_dlc {
+            fd_items.push(("max_dlc", JsonValue::Number(u64::from(max_dlc.value))));
+        }
+        items.push(("can_fd", JsonValue::Object(fd_items)));
+    }
+    JsonValue::Object(items)
+}
+
+fn flx_to_json(flx_parameters: &FLX_Parameters) -> JsonValue {
+    let mut items = vec![("type", JsonValue::String("FLX".to_string()))];
+    if !flx_parameters.fibex_file.is_empty() {
+        items.push((
+            "fibex_file",
+            JsonValue::String(flx_parameters.fibex_file.clone()),
+        ));
+    }
+    JsonValue::Object(items)
+}
+
+fn ip_to_json(
+    transport_type: &str,
+    host_name: &Option<HostName>,
+    address: &Option<Address2>,
+    ipv6: &Option<Ipv6>,
+    port: u16,
+) -> JsonValue {
+    let mut items = vec![("type", JsonValue::String(transport_type.to_string()))];
+    if let Some(HostName { hostname, .. }) = host_name {
+        items.push(("hostname", JsonValue::String(hostname.clone())));
+    }
+    if let Some(Address2 { address_v4, .. }) = address {
+        items.push(("address", JsonValue::String(address_v4.clone())));
+    }
+    if let Some(Ipv6 { address_v6, .. }) = ipv6 {
+        items.push(("address", JsonValue::String(address_v6.clone())));
+    }
+    items.push(("port", JsonValue::Number(u64::from(port))));
+    JsonValue::Object(items)
+}
+
+fn common_parameters_to_json(
+    items: &mut Vec<(&'static str, JsonValue)>,
+    daq: &Option<Daq>,
+    pag: &Option<Pag>,
+    pgm: &Option<Pgm>,
+) {
+    if let Some(daq) = daq {
+        let events = daq
+            .event
+            .iter()
+            .map(|event| {
+                let mut event_items = vec![
+                    ("name", JsonValue::String(event.event_channel_name.clone())),
+                    (
+                        "short_name",
+                        JsonValue::String(event.event_channel_short_name.clone()),
+                    ),
+                    (
+                        "number",
+                        JsonValue::Number(u64::from(event.event_channel_number)),
+                    ),
+                    (
+                        "max_daq_list",
+                        JsonValue::Number(u64::from(event.max_daq_list)),
+                    ),
+                    ("time_cycle", JsonValue::Number(u64::from(event.time_cycle))),
+                    ("time_unit", JsonValue::Number(u64::from(event.time_unit))),
+                    ("priority", JsonValue::Number(u64::from(event.priority))),
+                ];
+                if let Some(cycle) = format_event_cycle(event.time_cycle, event.time_unit) {
+                    event_items.push(("cycle", JsonValue::String(cycle)));
+                }
+                JsonValue::Object(event_items)
+            })
+            .collect();
+        items.push((
+            "daq",
+            JsonValue::Object(vec![
+                ("max_daq", JsonValue::Number(u64::from(daq.max_daq))),
+                (
+                    "max_event_channel",
+                    JsonValue::Number(u64::from(daq.max_event_channel)),
+                ),
+                ("min_daq", JsonValue::Number(u64::from(daq.min_daq))),
+                (
+                    "predefined_daq_lists",
+                    JsonValue::Number(daq.daq_list.len() as u64),
+                ),
+                ("events", JsonValue::Array(events)),
+            ]),
+        ));
+    }
+
+    if let Some(pag) = pag {
+        items.push((
+            "pag",
+            JsonValue::Object(vec![(
+                "max_segments",
+                JsonValue::Number(u64::from(pag.max_segments)),
+            )]),
+        ));
+    }
+
+    if let Some(pgm) = pgm {
+        let sectors = pgm
+            .sector
+            .iter()
+            .map(|sector| {
+                JsonValue::Object(vec![
+                    ("name", JsonValue::String(sector.sector_name.clone())),
+                    ("number", JsonValue::Number(u64::from(sector.sector_number))),
+                    ("address", JsonValue::Number(u64::from(sector.address))),
+                    ("length", JsonValue::Number(u64::from(sector.length))),
+                ])
+            })
+            .collect();
+        items.push((
+            "pgm",
+            JsonValue::Object(vec![
+                ("max_sectors", JsonValue::Number(u64::from(pgm.max_sectors))),
+                ("max_cto_pgm", JsonValue::Number(u64::from(pgm.max_cto_pgm))),
+                ("sectors", JsonValue::Array(sectors)),
+            ]),
+        ));
+    }
+}
+
+// Minimal json representation for the output of --show-xcp-format json and --export-signal-list
+// Optional values that are not present in the file are simply omitted from objects.
+pub(crate) enum JsonValue {
+    Number(u64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(&'static str, JsonValue)>),
+}
+
+impl JsonValue {
+    pub(crate) fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            JsonValue::Number(num) => {
+                let _ = write!(out, "{num}");
+            }
+            JsonValue::String(text) => write_json_string(out, text),
+            JsonValue::Array(values) => {
+                if values.is_empty() {
+                    out.push_str("[]");
+                } else {
+                    out.push_str("[\n");
+                    for (idx, value) in values.iter().enumerate() {
+                        push_indent(out, indent + 1);
+                        value.write(out, indent + 1);
+                        if idx + 1 < values.len() {
+                            out.push(',');
+                        }
+                        out.push('\n');
+                    }
+                    push_indent(out, indent);
+                    out.push(']');
+                }
+            }
+            JsonValue::Object(items) => {
+                if items.is_empty() {
+                    out.push_str("{}");
+                } else {
+                    out.push_str("{\n");
+                    for (idx, (key, value)) in items.iter().enumerate() {
+                        push_indent(out, indent + 1);
+                        write_json_string(out, key);
+                        out.push_str(": ");
+                        value.write(out, indent + 1);
+                        if idx + 1 < items.len() {
+                            out.push(',');
+                        }
+                        out.push('\n');
+                    }
+                    push_indent(out, indent);
+                    out.push('}');
+                }
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
         }
     }
-    println!();
+    out.push('"');
 }
 
 fn print_xcp(xcp: &Xcp) {
@@ -61,7 +448,10 @@ fn print_xcp(xcp: &Xcp) {
     if let Some(xcp_on_udp_ip) = &xcp.xcp_on_udp_ip {
         print_xcp_on_udp_ip(&xcp_on_udp_ip.udp_ip_parameters);
     }
+
+    print_common_parameters(&xcp.daq, &xcp.pag, &xcp.pgm);
 }
+
 fn print_xcpplus(xcpplus: &XCPplus) {
     for xcp_on_can in &xcpplus.xcp_on_can {
         print_xcp_on_can(&xcp_on_can.can_parameters);
@@ -78,40 +468,150 @@ fn print_xcpplus(xcpplus: &XCPplus) {
     for xcp_on_udp_ip in &xcpplus.xcp_on_udp_ip {
         print_xcp_on_udp_ip(&xcp_on_udp_ip.udp_ip_parameters);
     }
+
+    print_common_parameters(&xcpplus.daq, &xcpplus.pag, &xcpplus.pgm);
+}
+
+// print the DAQ, PAG and PGM resource info from the default parameters of the XCP IF_DATA
+fn print_common_parameters(daq: &Option<Daq>, pag: &Option<Pag>, pgm: &Option<Pgm>) {
+    if let Some(daq) = daq {
+        log_println!("  DAQ:");
+        log_println!("    max DAQ lists: {}", daq.max_daq);
+        log_println!("    max event channels: {}", daq.max_event_channel);
+        log_println!("    min DAQ: {}", daq.min_daq);
+        if !daq.daq_list.is_empty() {
+            log_println!("    predefined DAQ lists: {}", daq.daq_list.len());
+        }
+        if !daq.event.is_empty() {
+            log_println!("    events:");
+            for event in &daq.event {
+                let mut line = format!(
+                    "      {}: {}",
+                    event.event_channel_number, event.event_channel_name
+                );
+                if let Some(cycle) = format_event_cycle(event.time_cycle, event.time_unit) {
+                    let _ = write!(line, ", cycle: {cycle}");
+                }
+                log_println!("{line}, priority: {}", event.priority);
+            }
+        }
+    }
+
+    if let Some(pag) = pag {
+        log_println!("  PAG:");
+        log_println!("    max segments: {}", pag.max_segments);
+    }
+
+    if let Some(pgm) = pgm {
+        log_println!("  PGM:");
+        log_println!("    max sectors: {}", pgm.max_sectors);
+        log_println!("    max CTO PGM: {}", pgm.max_cto_pgm);
+        for sector in &pgm.sector {
+            log_println!(
+                "    sector {} ({}): address 0x{:X}, length 0x{:X}",
+                sector.sector_number,
+                sector.sector_name,
+                sector.address,
+                sector.length
+            );
+        }
+    }
+}
+
+// print the MEMORY_SEGMENTs of the module that carry XCP SEGMENT info
+fn print_memory_segments(module: &Module) {
+    let segments = get_xcp_memory_segments(module);
+    if !segments.is_empty() {
+        log_println!("  Memory segments:");
+        for (name, address, size, segment) in segments {
+            log_println!(
+                "    {name}: address 0x{address:X}, size 0x{size:X}, segment number {}, pages: {}",
+                segment.segment_number,
+                segment.num_pages
+            );
+        }
+    }
+}
+
+fn get_xcp_memory_segments(module: &Module) -> Vec<(&str, u32, u32, Segment)> {
+    let mut segments = Vec::new();
+    if let Some(mod_par) = &module.mod_par {
+        for memory_segment in &mod_par.memory_segment {
+            for ifdata in &memory_segment.if_data {
+                if let Some(Some(segment)) = A2mlVector::load_from_ifdata(ifdata)
+                    .and_then(|decoded| decoded.xcp)
+                    .map(|xcp| xcp.segment)
+                {
+                    segments.push((
+                        memory_segment.name.as_str(),
+                        memory_segment.address,
+                        memory_segment.size,
+                        segment,
+                    ));
+                }
+            }
+        }
+    }
+    segments
+}
+
+// The cycle time of an event is given as TIME_CYCLE * TIME_UNIT.
+// A TIME_CYCLE of zero means that the event is not cyclic.
+pub(crate) fn format_event_cycle(time_cycle: u8, time_unit: u8) -> Option<String> {
+    if time_cycle == 0 {
+        return None;
+    }
+    let (factor, unit) = match time_unit {
+        0 => (1, "ns"),
+        1 => (10, "ns"),
+        2 => (100, "ns"),
+        3 => (1, "us"),
+        4 => (10, "us"),
+        5 => (100, "us"),
+        6 => (1, "ms"),
+        7 => (10, "ms"),
+        8 => (100, "ms"),
+        9 => (1, "s"),
+        10 => (1, "ps"),
+        11 => (10, "ps"),
+        12 => (100, "ps"),
+        _ => return Some(format!("{time_cycle} (unknown time unit {time_unit})")),
+    };
+    Some(format!("{}{unit}", u32::from(time_cycle) * factor))
 }
 
 fn print_xcp_on_can(can_parameters: &CAN_Parameters) {
-    println!("  XCP on CAN:");
+    log_println!("  XCP on CAN:");
     if let Some(can_id_master) = &can_parameters.can_id_master {
-        println!(
+        log_println!(
             "    CAN id master: 0x{:X}",
             (can_id_master.value & 0x1fff_ffff)
         );
     }
     if let Some(can_id_slave) = &can_parameters.can_id_slave {
-        println!(
+        log_println!(
             "    CAN id slave: 0x{:X}",
             (can_id_slave.value & 0x1fff_ffff)
         );
     }
     if let Some(baudrate) = &can_parameters.baudrate {
-        println!("    CAN baudrate: {} kbps", baudrate.value / 1000);
+        log_println!("    CAN baudrate: {} kbps", baudrate.value / 1000);
     }
     if let Some(can_fd) = &can_parameters.can_fd {
-        println!("    CAN-FD enabled:");
+        log_println!("    CAN-FD enabled:");
         if let Some(baudrate) = &can_fd.can_fd_data_transfer_baudrate {
-            println!("      CAN-FD data baudrate: {} kbps", baudrate.value / 1000);
+            log_println!("      CAN-FD data baudrate: {} kbps", baudrate.value / 1000);
         }
         if let Some(max_dlc) = &can_fd.max_dlc {
-            println!("      CAN-FD max DLC: {}", max_dlc.value);
+            log_println!("      CAN-FD max DLC: {}", max_dlc.value);
         }
     }
 }
 
 fn print_xcp_on_flx(flx_parameters: &FLX_Parameters) {
-    println!("  XCP on Flexray");
+    log_println!("  XCP on Flexray");
     if !flx_parameters.fibex_file.is_empty() {
-        println!("    fibex file: {}", flx_parameters.fibex_file);
+        log_println!("    fibex file: {}", flx_parameters.fibex_file);
     }
 
     if let Some(buffer) = &flx_parameters.initial_cmd_buffer {
@@ -122,7 +622,7 @@ fn print_xcp_on_flx(flx_parameters: &FLX_Parameters) {
             xcp_packet,
             ..
         } = buffer;
-        println!("    Initial cmd buffer:");
+        log_println!("    Initial cmd buffer:");
         print_xcp_on_flx_buffer(*flx_buf, max_flx_len_buf, lpdu_id, xcp_packet);
     }
 
@@ -134,7 +634,7 @@ fn print_xcp_on_flx(flx_parameters: &FLX_Parameters) {
             xcp_packet,
             ..
         } = buffer;
-        println!("    Initial res / err buffer:");
+        log_println!("    Initial res / err buffer:");
         print_xcp_on_flx_buffer(*flx_buf, max_flx_len_buf, lpdu_id, xcp_packet);
     }
 
@@ -146,7 +646,7 @@ fn print_xcp_on_flx(flx_parameters: &FLX_Parameters) {
             xcp_packet,
             ..
         } = buffer;
-        println!("    pool buffer:");
+        log_println!("    pool buffer:");
         print_xcp_on_flx_buffer(*flx_buf, max_flx_len_buf, lpdu_id, xcp_packet);
     }
 }
@@ -157,17 +657,17 @@ fn print_xcp_on_flx_buffer(
     lpdu_id: &Option<LpduId>,
     xcp_packet: &Option<XcpPacket>,
 ) {
-    println!("      buffer id: {flx_buf_id}");
+    log_println!("      buffer id: {flx_buf_id}");
 
     if let Some(MaxFlxLenBuf {
         fixed, variable, ..
     }) = &max_flx_len_buf
     {
         if let Some(fixed) = fixed {
-            println!("      buffer length: {} bytes (fixed)", fixed.length);
+            log_println!("      buffer length: {} bytes (fixed)", fixed.length);
         }
         if let Some(variable) = variable {
-            println!("      buffer length: {} bytes (variable)", variable.length);
+            log_println!("      buffer length: {} bytes (variable)", variable.length);
         }
     }
     if let Some(LpduId {
@@ -178,23 +678,23 @@ fn print_xcp_on_flx_buffer(
         ..
     }) = lpdu_id
     {
-        print!("      ");
+        let mut line = String::from("      ");
         if let Some(FlxSlotId {
             fixed, variable, ..
         }) = flx_slot_id
         {
             if let Some(fixed) = fixed {
-                print!("slot id: {}", fixed.slot_id);
+                let _ = write!(line, "slot id: {}", fixed.slot_id);
             }
             if let Some(variable) = variable {
                 if let Some(initial) = &variable.initial_value {
-                    print!("slot id variable, initial value: {}", initial.slot_id);
+                    let _ = write!(line, "slot id variable, initial value: {}", initial.slot_id);
                 } else {
-                    print!("slot id variable");
+                    line.push_str("slot id variable");
                 }
             }
         } else {
-            print!("slot id: undefined");
+            line.push_str("slot id: undefined");
         }
 
         if let Some(CycleRepetition {
@@ -202,13 +702,13 @@ fn print_xcp_on_flx_buffer(
         }) = cycle_repetition
         {
             if let Some(fixed) = fixed {
-                print!(", cycle: {}", fixed.cycle);
+                let _ = write!(line, ", cycle: {}", fixed.cycle);
             }
             if let Some(variable) = variable {
                 if let Some(initial) = &variable.initial_value {
-                    print!(", cycle variable, initial value: {}", initial.cycle);
+                    let _ = write!(line, ", cycle variable, initial value: {}", initial.cycle);
                 } else {
-                    print!(", cycle variable");
+                    line.push_str(", cycle variable");
                 }
             }
         }
@@ -218,13 +718,13 @@ fn print_xcp_on_flx_buffer(
         }) = offset
         {
             if let Some(fixed) = fixed {
-                print!(", offset: {}", fixed.offset);
+                let _ = write!(line, ", offset: {}", fixed.offset);
             }
             if let Some(variable) = variable {
                 if let Some(initial) = &variable.initial_value {
-                    print!(", offset variable, initial value: {}", initial.offset);
+                    let _ = write!(line, ", offset variable, initial value: {}", initial.offset);
                 } else {
-                    print!(", offset variable");
+                    line.push_str(", offset variable");
                 }
             }
         }
@@ -234,17 +734,21 @@ fn print_xcp_on_flx_buffer(
         }) = channel
         {
             if let Some(fixed) = fixed {
-                print!(", channel: {:?}", fixed.channel);
+                let _ = write!(line, ", channel: {:?}", fixed.channel);
             }
             if let Some(variable) = variable {
                 if let Some(initial) = &variable.initial_value {
-                    print!(", channel variable, initial value: {:?}", initial.channel);
+                    let _ = write!(
+                        line,
+                        ", channel variable, initial value: {:?}",
+                        initial.channel
+                    );
                 } else {
-                    print!(", channel variable");
+                    line.push_str(", channel variable");
                 }
             }
         }
-        println!();
+        log_println!("{line}");
     }
 
     if let Some(XcpPacket {
@@ -256,47 +760,47 @@ fn print_xcp_on_flx_buffer(
         ..
     }) = xcp_packet
     {
-        println!("      packet types: ");
+        log_println!("      packet types: ");
         if let Some(Cmd {
             packet_assignment_type,
             ..
         }) = cmd
         {
-            println!("        Cmd: {packet_assignment_type:?}");
+            log_println!("        Cmd: {packet_assignment_type:?}");
         }
         if let Some(ResErr {
             packet_assignment_type,
             ..
         }) = res_err
         {
-            println!("        Res / Err: {packet_assignment_type:?}");
+            log_println!("        Res / Err: {packet_assignment_type:?}");
         }
         if let Some(EvServ {
             packet_assignment_type,
             ..
         }) = ev_serv
         {
-            println!("        EvServ: {packet_assignment_type:?}");
+            log_println!("        EvServ: {packet_assignment_type:?}");
         }
         if let Some(Daq2 {
             packet_assignment_type,
             ..
         }) = daq
         {
-            println!("        Daq: {packet_assignment_type:?}");
+            log_println!("        Daq: {packet_assignment_type:?}");
         }
         if let Some(Stim2 {
             packet_assignment_type,
             ..
         }) = stim
         {
-            println!("        Stim: {packet_assignment_type:?}");
+            log_println!("        Stim: {packet_assignment_type:?}");
         }
     }
 }
 
 fn print_xcp_on_tcp_ip(tcp_ip_parameters: &TCP_IP_Parameters) {
-    println!("  XCP on TCP/IP");
+    log_println!("  XCP on TCP/IP");
     print_xcp_on_ip_common(
         &tcp_ip_parameters.host_name,
         &tcp_ip_parameters.address,
@@ -306,7 +810,7 @@ fn print_xcp_on_tcp_ip(tcp_ip_parameters: &TCP_IP_Parameters) {
 }
 
 fn print_xcp_on_udp_ip(udp_ip_parameters: &UDP_IP_Parameters) {
-    println!("  XCP on UDP/IP");
+    log_println!("  XCP on UDP/IP");
     print_xcp_on_ip_common(
         &udp_ip_parameters.host_name,
         &udp_ip_parameters.address,
@@ -322,13 +826,45 @@ fn print_xcp_on_ip_common(
     port: u16,
 ) {
     if let Some(HostName { hostname, .. }) = host_name {
-        println!("    hostname: {hostname}");
+        log_println!("    hostname: {hostname}");
     }
     if let Some(Address2 { address_v4, .. }) = address {
-        println!("    address: {address_v4}");
+        log_println!("    address: {address_v4}");
     }
     if let Some(Ipv6 { address_v6, .. }) = ipv6 {
-        println!("    address: {address_v6}");
+        log_println!("    address: {address_v6}");
+    }
+    log_println!("    port: {port}");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_event_cycle() {
+        assert_eq!(format_event_cycle(0, 6), None);
+        assert_eq!(format_event_cycle(10, 6), Some("10ms".to_string()));
+        assert_eq!(format_event_cycle(5, 8), Some("500ms".to_string()));
+        assert_eq!(format_event_cycle(1, 9), Some("1s".to_string()));
+        assert!(format_event_cycle(1, 42).is_some());
+    }
+
+    #[test]
+    fn test_json_output() {
+        let value = JsonValue::Object(vec![
+            ("name", JsonValue::String("a \"quoted\"\\ name".to_string())),
+            ("empty", JsonValue::Array(vec![])),
+            (
+                "list",
+                JsonValue::Array(vec![JsonValue::Number(1), JsonValue::Number(2)]),
+            ),
+        ]);
+        let mut out = String::new();
+        value.write(&mut out, 0);
+        assert_eq!(
+            out,
+            "{\n  \"name\": \"a \\\"quoted\\\"\\\\ name\",\n  \"empty\": [],\n  \"list\": [\n    1,\n    2\n  ]\n}"
+        );
     }
-    println!("    port: {port}");
 }